@@ -9,6 +9,7 @@
 #include <linux/fcntl.h>
 #include <linux/futex.h>
 #include <linux/in.h>
+#include <linux/ipc.h>
 #include <linux/limits.h>
 #include <linux/mman.h>
 #include <linux/netlink.h>
@@ -17,11 +18,13 @@
 #include <linux/rseq.h>
 #include <linux/rtnetlink.h>
 #include <linux/sched.h>
+#include <linux/shm.h>
 #include <linux/signal.h>
 #include <linux/sockios.h>
 #include <linux/stat.h>
 #include <linux/time.h>
 #include <linux/time_types.h>
+#include <linux/timex.h>
 #include <linux/unistd.h>
 #include <linux/utsname.h>
 #include <linux/wait.h>
@@ -121,6 +121,10 @@ bindgen_flags+=("--allowlist-type=nlmsghdr")
 # select.h
 bindgen_flags+=("--allowlist-type=stat")
 
+# ipc.h and shm.h
+bindgen_flags+=("--allowlist-type=ipc64_perm")
+bindgen_flags+=("--allowlist-type=shmid64_ds")
+
 # non-exposed socket types
 bindgen_flags+=("--allowlist-type=sock_shutdown_cmd")
 
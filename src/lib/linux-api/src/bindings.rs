@@ -521,6 +521,30 @@ pub const LINUX_INADDR_ALLHOSTS_GROUP: u32 = 3758096385;
 pub const LINUX_INADDR_ALLRTRS_GROUP: u32 = 3758096386;
 pub const LINUX_INADDR_ALLSNOOPERS_GROUP: u32 = 3758096490;
 pub const LINUX_INADDR_MAX_LOCAL_GROUP: u32 = 3758096639;
+pub const LINUX_IPC_CREAT: u32 = 512;
+pub const LINUX_IPC_EXCL: u32 = 1024;
+pub const LINUX_IPC_NOWAIT: u32 = 2048;
+pub const LINUX_IPC_DIPC: u32 = 4096;
+pub const LINUX_IPC_OWN: u32 = 8192;
+pub const LINUX_IPC_RMID: u32 = 0;
+pub const LINUX_IPC_SET: u32 = 1;
+pub const LINUX_IPC_STAT: u32 = 2;
+pub const LINUX_IPC_INFO: u32 = 3;
+pub const LINUX_IPC_OLD: u32 = 0;
+pub const LINUX_IPC_64: u32 = 256;
+pub const LINUX_SEMOP: u32 = 1;
+pub const LINUX_SEMGET: u32 = 2;
+pub const LINUX_SEMCTL: u32 = 3;
+pub const LINUX_SEMTIMEDOP: u32 = 4;
+pub const LINUX_MSGSND: u32 = 11;
+pub const LINUX_MSGRCV: u32 = 12;
+pub const LINUX_MSGGET: u32 = 13;
+pub const LINUX_MSGCTL: u32 = 14;
+pub const LINUX_SHMAT: u32 = 21;
+pub const LINUX_SHMDT: u32 = 22;
+pub const LINUX_SHMGET: u32 = 23;
+pub const LINUX_SHMCTL: u32 = 24;
+pub const LINUX_DIPC: u32 = 25;
 pub const LINUX___LITTLE_ENDIAN: u32 = 1234;
 pub const LINUX_NR_OPEN: u32 = 1024;
 pub const LINUX_NGROUPS_MAX: u32 = 65536;
@@ -1101,6 +1125,36 @@ pub const LINUX_SCHED_FLAG_UTIL_CLAMP_MAX: u32 = 64;
 pub const LINUX_SCHED_FLAG_KEEP_ALL: u32 = 24;
 pub const LINUX_SCHED_FLAG_UTIL_CLAMP: u32 = 96;
 pub const LINUX_SCHED_FLAG_ALL: u32 = 127;
+pub const LINUX_SHMMIN: u32 = 1;
+pub const LINUX_SHMMNI: u32 = 4096;
+pub const LINUX_SHMSEG: u32 = 4096;
+pub const LINUX_SHM_R: u32 = 256;
+pub const LINUX_SHM_W: u32 = 128;
+pub const LINUX_SHM_HUGETLB: u32 = 2048;
+pub const LINUX_SHM_NORESERVE: u32 = 4096;
+pub const LINUX_SHM_HUGE_SHIFT: u32 = 26;
+pub const LINUX_SHM_HUGE_MASK: u32 = 63;
+pub const LINUX_SHM_HUGE_64KB: u32 = 1073741824;
+pub const LINUX_SHM_HUGE_512KB: u32 = 1275068416;
+pub const LINUX_SHM_HUGE_1MB: u32 = 1342177280;
+pub const LINUX_SHM_HUGE_2MB: u32 = 1409286144;
+pub const LINUX_SHM_HUGE_8MB: u32 = 1543503872;
+pub const LINUX_SHM_HUGE_16MB: u32 = 1610612736;
+pub const LINUX_SHM_HUGE_32MB: u32 = 1677721600;
+pub const LINUX_SHM_HUGE_256MB: u32 = 1879048192;
+pub const LINUX_SHM_HUGE_512MB: u32 = 1946157056;
+pub const LINUX_SHM_HUGE_1GB: u32 = 2013265920;
+pub const LINUX_SHM_HUGE_2GB: u32 = 2080374784;
+pub const LINUX_SHM_HUGE_16GB: u32 = 2281701376;
+pub const LINUX_SHM_RDONLY: u32 = 4096;
+pub const LINUX_SHM_RND: u32 = 8192;
+pub const LINUX_SHM_REMAP: u32 = 16384;
+pub const LINUX_SHM_EXEC: u32 = 32768;
+pub const LINUX_SHM_LOCK: u32 = 11;
+pub const LINUX_SHM_UNLOCK: u32 = 12;
+pub const LINUX_SHM_STAT: u32 = 13;
+pub const LINUX_SHM_INFO: u32 = 14;
+pub const LINUX_SHM_STAT_ANY: u32 = 15;
 pub const LINUX_NSIG: u32 = 32;
 pub const LINUX_SIGHUP: u32 = 1;
 pub const LINUX_SIGINT: u32 = 2;
@@ -1400,6 +1454,53 @@ pub const LINUX_MAX_CLOCKS: u32 = 16;
 pub const LINUX_CLOCKS_MASK: u32 = 1;
 pub const LINUX_CLOCKS_MONO: u32 = 1;
 pub const LINUX_TIMER_ABSTIME: u32 = 1;
+pub const LINUX_NTP_API: u32 = 4;
+pub const LINUX_ADJ_OFFSET: u32 = 1;
+pub const LINUX_ADJ_FREQUENCY: u32 = 2;
+pub const LINUX_ADJ_MAXERROR: u32 = 4;
+pub const LINUX_ADJ_ESTERROR: u32 = 8;
+pub const LINUX_ADJ_STATUS: u32 = 16;
+pub const LINUX_ADJ_TIMECONST: u32 = 32;
+pub const LINUX_ADJ_TAI: u32 = 128;
+pub const LINUX_ADJ_SETOFFSET: u32 = 256;
+pub const LINUX_ADJ_MICRO: u32 = 4096;
+pub const LINUX_ADJ_NANO: u32 = 8192;
+pub const LINUX_ADJ_TICK: u32 = 16384;
+pub const LINUX_ADJ_OFFSET_SINGLESHOT: u32 = 32769;
+pub const LINUX_ADJ_OFFSET_SS_READ: u32 = 40961;
+pub const LINUX_MOD_OFFSET: u32 = 1;
+pub const LINUX_MOD_FREQUENCY: u32 = 2;
+pub const LINUX_MOD_MAXERROR: u32 = 4;
+pub const LINUX_MOD_ESTERROR: u32 = 8;
+pub const LINUX_MOD_STATUS: u32 = 16;
+pub const LINUX_MOD_TIMECONST: u32 = 32;
+pub const LINUX_MOD_TAI: u32 = 128;
+pub const LINUX_MOD_MICRO: u32 = 4096;
+pub const LINUX_MOD_NANO: u32 = 8192;
+pub const LINUX_STA_PLL: u32 = 1;
+pub const LINUX_STA_PPSFREQ: u32 = 2;
+pub const LINUX_STA_PPSTIME: u32 = 4;
+pub const LINUX_STA_FLL: u32 = 8;
+pub const LINUX_STA_INS: u32 = 16;
+pub const LINUX_STA_DEL: u32 = 32;
+pub const LINUX_STA_UNSYNC: u32 = 64;
+pub const LINUX_STA_FREQHOLD: u32 = 128;
+pub const LINUX_STA_PPSSIGNAL: u32 = 256;
+pub const LINUX_STA_PPSJITTER: u32 = 512;
+pub const LINUX_STA_PPSWANDER: u32 = 1024;
+pub const LINUX_STA_PPSERROR: u32 = 2048;
+pub const LINUX_STA_CLOCKERR: u32 = 4096;
+pub const LINUX_STA_NANO: u32 = 8192;
+pub const LINUX_STA_MODE: u32 = 16384;
+pub const LINUX_STA_CLK: u32 = 32768;
+pub const LINUX_STA_RONLY: u32 = 65280;
+pub const LINUX_TIME_OK: u32 = 0;
+pub const LINUX_TIME_INS: u32 = 1;
+pub const LINUX_TIME_DEL: u32 = 2;
+pub const LINUX_TIME_OOP: u32 = 3;
+pub const LINUX_TIME_WAIT: u32 = 4;
+pub const LINUX_TIME_ERROR: u32 = 5;
+pub const LINUX_TIME_BAD: u32 = 5;
 pub const LINUX___X32_SYSCALL_BIT: u32 = 1073741824;
 pub const LINUX___NR_read: u32 = 0;
 pub const LINUX___NR_write: u32 = 1;
@@ -2468,6 +2569,146 @@ fn bindgen_test_layout_sockaddr_in() {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct linux_ipc64_perm {
+    pub key: linux___kernel_key_t,
+    pub uid: linux___kernel_uid32_t,
+    pub gid: linux___kernel_gid32_t,
+    pub cuid: linux___kernel_uid32_t,
+    pub cgid: linux___kernel_gid32_t,
+    pub mode: linux___kernel_mode_t,
+    pub l__pad1: [::core::ffi::c_uchar; 0usize],
+    pub seq: ::core::ffi::c_ushort,
+    pub l__pad2: ::core::ffi::c_ushort,
+    pub l__unused1: linux___kernel_ulong_t,
+    pub l__unused2: linux___kernel_ulong_t,
+}
+#[test]
+fn bindgen_test_layout_ipc64_perm() {
+    const UNINIT: ::core::mem::MaybeUninit<linux_ipc64_perm> = ::core::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::core::mem::size_of::<linux_ipc64_perm>(),
+        48usize,
+        concat!("Size of: ", stringify!(linux_ipc64_perm))
+    );
+    assert_eq!(
+        ::core::mem::align_of::<linux_ipc64_perm>(),
+        8usize,
+        concat!("Alignment of ", stringify!(linux_ipc64_perm))
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).key) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(key)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).uid) as usize - ptr as usize },
+        4usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(uid)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).gid) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(gid)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).cuid) as usize - ptr as usize },
+        12usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(cuid)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).cgid) as usize - ptr as usize },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(cgid)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).mode) as usize - ptr as usize },
+        20usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(mode)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).l__pad1) as usize - ptr as usize },
+        24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(l__pad1)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).seq) as usize - ptr as usize },
+        24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(seq)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).l__pad2) as usize - ptr as usize },
+        26usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(l__pad2)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).l__unused1) as usize - ptr as usize },
+        32usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(l__unused1)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).l__unused2) as usize - ptr as usize },
+        40usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_ipc64_perm),
+            "::",
+            stringify!(l__unused2)
+        )
+    );
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct linux_nlmsghdr {
     pub nlmsg_len: linux___u32,
     pub nlmsg_type: linux___u16,
@@ -3961,6 +4202,135 @@ fn bindgen_test_layout_clone_args() {
         )
     );
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct linux_shmid64_ds {
+    pub shm_perm: linux_ipc64_perm,
+    pub shm_segsz: linux___kernel_size_t,
+    pub shm_atime: ::core::ffi::c_long,
+    pub shm_dtime: ::core::ffi::c_long,
+    pub shm_ctime: ::core::ffi::c_long,
+    pub shm_cpid: linux___kernel_pid_t,
+    pub shm_lpid: linux___kernel_pid_t,
+    pub shm_nattch: ::core::ffi::c_ulong,
+    pub l__unused4: ::core::ffi::c_ulong,
+    pub l__unused5: ::core::ffi::c_ulong,
+}
+#[test]
+fn bindgen_test_layout_shmid64_ds() {
+    const UNINIT: ::core::mem::MaybeUninit<linux_shmid64_ds> = ::core::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::core::mem::size_of::<linux_shmid64_ds>(),
+        112usize,
+        concat!("Size of: ", stringify!(linux_shmid64_ds))
+    );
+    assert_eq!(
+        ::core::mem::align_of::<linux_shmid64_ds>(),
+        8usize,
+        concat!("Alignment of ", stringify!(linux_shmid64_ds))
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).shm_perm) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(shm_perm)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).shm_segsz) as usize - ptr as usize },
+        48usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(shm_segsz)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).shm_atime) as usize - ptr as usize },
+        56usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(shm_atime)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).shm_dtime) as usize - ptr as usize },
+        64usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(shm_dtime)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).shm_ctime) as usize - ptr as usize },
+        72usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(shm_ctime)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).shm_cpid) as usize - ptr as usize },
+        80usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(shm_cpid)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).shm_lpid) as usize - ptr as usize },
+        84usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(shm_lpid)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).shm_nattch) as usize - ptr as usize },
+        88usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(shm_nattch)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).l__unused4) as usize - ptr as usize },
+        96usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(l__unused4)
+        )
+    );
+    assert_eq!(
+        unsafe { ::core::ptr::addr_of!((*ptr).l__unused5) as usize - ptr as usize },
+        104usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(linux_shmid64_ds),
+            "::",
+            stringify!(l__unused5)
+        )
+    );
+}
 pub type linux_sigset_t = ::core::ffi::c_ulong;
 pub type linux___signalfn_t =
     ::core::option::Option<unsafe extern "C" fn(arg1: ::core::ffi::c_int)>;
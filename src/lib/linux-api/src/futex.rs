@@ -13,6 +13,10 @@ pub type robust_list_head = linux_robust_list_head;
 unsafe impl shadow_pod::Pod for robust_list_head {}
 
 pub const FUTEX_CMD_MASK: i32 = bindings::LINUX_FUTEX_CMD_MASK;
+pub const FUTEX_BITSET_MATCH_ANY: u32 = bindings::LINUX_FUTEX_BITSET_MATCH_ANY;
+pub const FUTEX_WAITERS: u32 = bindings::LINUX_FUTEX_WAITERS;
+pub const FUTEX_OWNER_DIED: u32 = bindings::LINUX_FUTEX_OWNER_DIED;
+pub const FUTEX_TID_MASK: u32 = bindings::LINUX_FUTEX_TID_MASK;
 
 bitflags::bitflags! {
     /// Flags that can be used in the `op` argument for the [`futex`] syscall.
@@ -156,6 +156,10 @@ pub enum IoctlRequest {
     SIOCGHWTSTAMP = bindings::LINUX_SIOCGHWTSTAMP,
     SIOCDEVPRIVATE = bindings::LINUX_SIOCDEVPRIVATE,
     SIOCPROTOPRIVATE = bindings::LINUX_SIOCPROTOPRIVATE,
+    // The block device requests in linux/fs.h are defined using the `_IO` and `_IOR` macros, which
+    // bindgen can't evaluate, so we use their values directly.
+    BLKSSZGET = 0x1268,
+    BLKGETSIZE64 = 0x8008_1272,
 }
 
 impl IoctlRequest {
@@ -71,6 +71,7 @@ pub mod resource;
 pub mod rseq;
 pub mod rtnetlink;
 pub mod sched;
+pub mod shm;
 pub mod signal;
 pub mod socket;
 pub mod stat;
@@ -0,0 +1,71 @@
+//! SysV shared memory, as used by `shmget(2)`, `shmat(2)`, `shmdt(2)`, and `shmctl(2)`.
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::bindings;
+use crate::const_conversions;
+
+/// The key that always creates a new segment.
+// bindgen skips this since it's defined with a cast: `((__kernel_key_t) 0)`
+pub const IPC_PRIVATE: bindings::linux___kernel_key_t = 0;
+
+/// Set in the `cmd` argument of `shmctl(2)` by libcs to request the "new" 64-bit structures. On
+/// x86-64 these are the only structures, so the bit can be ignored.
+pub const IPC_64: i32 = const_conversions::i32_from_u32(bindings::LINUX_IPC_64);
+
+bitflags::bitflags! {
+    /// Flags passed to `shmget(2)`. The lower 9 bits are the permissions of a new segment.
+    #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+    pub struct ShmgetFlags: i32 {
+        const IPC_CREAT = const_conversions::i32_from_u32(bindings::LINUX_IPC_CREAT);
+        const IPC_EXCL = const_conversions::i32_from_u32(bindings::LINUX_IPC_EXCL);
+        const SHM_HUGETLB = const_conversions::i32_from_u32(bindings::LINUX_SHM_HUGETLB);
+        const SHM_NORESERVE = const_conversions::i32_from_u32(bindings::LINUX_SHM_NORESERVE);
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags passed to `shmat(2)`.
+    #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+    pub struct ShmatFlags: i32 {
+        const SHM_RDONLY = const_conversions::i32_from_u32(bindings::LINUX_SHM_RDONLY);
+        const SHM_RND = const_conversions::i32_from_u32(bindings::LINUX_SHM_RND);
+        const SHM_REMAP = const_conversions::i32_from_u32(bindings::LINUX_SHM_REMAP);
+        const SHM_EXEC = const_conversions::i32_from_u32(bindings::LINUX_SHM_EXEC);
+    }
+}
+
+/// Commands passed to `shmctl(2)`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[repr(i32)]
+#[allow(non_camel_case_types)]
+pub enum ShmctlCmd {
+    IPC_RMID = const_conversions::i32_from_u32(bindings::LINUX_IPC_RMID),
+    IPC_SET = const_conversions::i32_from_u32(bindings::LINUX_IPC_SET),
+    IPC_STAT = const_conversions::i32_from_u32(bindings::LINUX_IPC_STAT),
+    IPC_INFO = const_conversions::i32_from_u32(bindings::LINUX_IPC_INFO),
+    SHM_LOCK = const_conversions::i32_from_u32(bindings::LINUX_SHM_LOCK),
+    SHM_UNLOCK = const_conversions::i32_from_u32(bindings::LINUX_SHM_UNLOCK),
+    SHM_STAT = const_conversions::i32_from_u32(bindings::LINUX_SHM_STAT),
+    SHM_INFO = const_conversions::i32_from_u32(bindings::LINUX_SHM_INFO),
+    SHM_STAT_ANY = const_conversions::i32_from_u32(bindings::LINUX_SHM_STAT_ANY),
+}
+
+/// Set in [`ipc64_perm::mode`] once a segment has been removed with `IPC_RMID`.
+// Not exposed in the uapi headers; copied from kernel source linux/shm.h.
+pub const SHM_DEST: bindings::linux___kernel_mode_t = 0o1000;
+
+/// Segment addresses are aligned to this boundary when `SHM_RND` is used (the page size on
+/// x86-64).
+// Not exposed in the uapi headers; copied from kernel source asm/shmparam.h.
+pub const SHMLBA: usize = 4096;
+
+pub use bindings::linux_ipc64_perm;
+#[allow(non_camel_case_types)]
+pub type ipc64_perm = linux_ipc64_perm;
+unsafe impl shadow_pod::Pod for ipc64_perm {}
+
+pub use bindings::linux_shmid64_ds;
+#[allow(non_camel_case_types)]
+pub type shmid64_ds = linux_shmid64_ds;
+unsafe impl shadow_pod::Pod for shmid64_ds {}
@@ -5,7 +5,7 @@ use num_enum::{IntoPrimitive, TryFromPrimitive};
 use shadow_pod::Pod;
 use vasi::VirtualAddressSpaceIndependent;
 
-use crate::bindings::{self, linux_sigval};
+use crate::bindings;
 use crate::const_conversions;
 use crate::const_conversions::i32_from_u32_allowing_wraparound;
 use crate::errno::Errno;
@@ -574,7 +574,12 @@ impl siginfo_t {
         }
     }
 
-    pub fn new_for_timer(signal: Signal, timer_id: i32, overrun: i32) -> Self {
+    pub fn new_for_timer(
+        signal: Signal,
+        timer_id: i32,
+        overrun: i32,
+        sigval: linux_sigval,
+    ) -> Self {
         // sigaction(2):
         // > Signals sent by POSIX.1b timers (since Linux 2.6) fill in si_overrun and
         // > si_timerid.  The si_timerid field is  an  internal ID  used by the kernel
@@ -591,7 +596,7 @@ impl siginfo_t {
                     l_timer: SigInfoDetailsTimer {
                         l_tid: timer_id,
                         l_overrun: overrun,
-                        l_sigval: core::mem::zeroed(),
+                        l_sigval: sigval,
                         l_sys_private: 0,
                     },
                 },
@@ -1320,6 +1325,21 @@ pub unsafe fn rt_sigaction(
     }
 }
 
+pub use bindings::linux_sigval;
+#[allow(non_camel_case_types)]
+pub type sigval = linux_sigval;
+
+/// Notification methods in a `struct sigevent`, as used by e.g. `timer_create(2)`.
+#[allow(non_camel_case_types)]
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+pub enum SigEvNotify {
+    SIGEV_SIGNAL = const_conversions::i32_from_u32(bindings::LINUX_SIGEV_SIGNAL),
+    SIGEV_NONE = const_conversions::i32_from_u32(bindings::LINUX_SIGEV_NONE),
+    SIGEV_THREAD = const_conversions::i32_from_u32(bindings::LINUX_SIGEV_THREAD),
+    SIGEV_THREAD_ID = const_conversions::i32_from_u32(bindings::LINUX_SIGEV_THREAD_ID),
+}
+
 /// For use with [`rt_sigprocmask`].
 #[allow(non_camel_case_types)]
 #[repr(i32)]
@@ -45,6 +45,14 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// Valid flags passed to `timer_settime(2)`.
+    #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+    pub struct TimerSettimeFlags: i32 {
+        const TIMER_ABSTIME = const_conversions::i32_from_u32(bindings::LINUX_TIMER_ABSTIME);
+    }
+}
+
 /// Interval timers
 #[derive(Debug, Copy, Clone, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
 // getitimer takes `int`:
@@ -79,6 +87,95 @@ pub use bindings::linux___kernel_old_timeval;
 pub type kernel_old_timeval = linux___kernel_old_timeval;
 unsafe impl shadow_pod::Pod for kernel_old_timeval {}
 
+bitflags::bitflags! {
+    /// Mode bits of [`timex::modes`], as passed to `adjtimex(2)` and `clock_adjtime(2)`.
+    #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+    pub struct AdjtimexModes: u32 {
+        const ADJ_OFFSET = bindings::LINUX_ADJ_OFFSET;
+        const ADJ_FREQUENCY = bindings::LINUX_ADJ_FREQUENCY;
+        const ADJ_MAXERROR = bindings::LINUX_ADJ_MAXERROR;
+        const ADJ_ESTERROR = bindings::LINUX_ADJ_ESTERROR;
+        const ADJ_STATUS = bindings::LINUX_ADJ_STATUS;
+        const ADJ_TIMECONST = bindings::LINUX_ADJ_TIMECONST;
+        const ADJ_TAI = bindings::LINUX_ADJ_TAI;
+        const ADJ_SETOFFSET = bindings::LINUX_ADJ_SETOFFSET;
+        const ADJ_MICRO = bindings::LINUX_ADJ_MICRO;
+        const ADJ_NANO = bindings::LINUX_ADJ_NANO;
+        const ADJ_TICK = bindings::LINUX_ADJ_TICK;
+        const ADJ_OFFSET_SINGLESHOT = bindings::LINUX_ADJ_OFFSET_SINGLESHOT;
+        const ADJ_OFFSET_SS_READ = bindings::LINUX_ADJ_OFFSET_SS_READ;
+    }
+}
+
+/// Clock states returned by `adjtimex(2)` and `clock_adjtime(2)`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[repr(i32)]
+#[allow(non_camel_case_types)]
+pub enum ClockState {
+    TIME_OK = const_conversions::i32_from_u32(bindings::LINUX_TIME_OK),
+    TIME_INS = const_conversions::i32_from_u32(bindings::LINUX_TIME_INS),
+    TIME_DEL = const_conversions::i32_from_u32(bindings::LINUX_TIME_DEL),
+    TIME_OOP = const_conversions::i32_from_u32(bindings::LINUX_TIME_OOP),
+    TIME_WAIT = const_conversions::i32_from_u32(bindings::LINUX_TIME_WAIT),
+    TIME_ERROR = const_conversions::i32_from_u32(bindings::LINUX_TIME_ERROR),
+}
+
+// Manually translated from linux/timex.h.
+// bindgen generates an opaque bitfield unit for the anonymous `int :32` padding at the end.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct linux_timex {
+    /// mode selector
+    pub modes: core::ffi::c_uint,
+    /// time offset (usec)
+    pub offset: bindings::linux___kernel_long_t,
+    /// frequency offset (scaled ppm)
+    pub freq: bindings::linux___kernel_long_t,
+    /// maximum error (usec)
+    pub maxerror: bindings::linux___kernel_long_t,
+    /// estimated error (usec)
+    pub esterror: bindings::linux___kernel_long_t,
+    /// clock command/status
+    pub status: core::ffi::c_int,
+    /// pll time constant
+    pub constant: bindings::linux___kernel_long_t,
+    /// clock precision (usec) (read only)
+    pub precision: bindings::linux___kernel_long_t,
+    /// clock frequency tolerance (ppm) (read only)
+    pub tolerance: bindings::linux___kernel_long_t,
+    /// (read only, except for ADJ_SETOFFSET)
+    pub time: linux_timeval,
+    /// (modified) usecs between clock ticks
+    pub tick: bindings::linux___kernel_long_t,
+    /// pps frequency (scaled ppm) (ro)
+    pub ppsfreq: bindings::linux___kernel_long_t,
+    /// pps jitter (us) (ro)
+    pub jitter: bindings::linux___kernel_long_t,
+    /// interval duration (s) (shift) (ro)
+    pub shift: core::ffi::c_int,
+    /// pps stability (scaled ppm) (ro)
+    pub stabil: bindings::linux___kernel_long_t,
+    /// jitter limit exceeded (ro)
+    pub jitcnt: bindings::linux___kernel_long_t,
+    /// calibration intervals (ro)
+    pub calcnt: bindings::linux___kernel_long_t,
+    /// calibration errors (ro)
+    pub errcnt: bindings::linux___kernel_long_t,
+    /// stability limit exceeded (ro)
+    pub stbcnt: bindings::linux___kernel_long_t,
+    /// TAI offset (ro)
+    pub tai: core::ffi::c_int,
+    // Manually translated from the eleven `int :32;` fields.
+    pub __reserved: [core::ffi::c_int; 11],
+}
+
+#[allow(non_camel_case_types)]
+pub type timex = linux_timex;
+unsafe impl shadow_pod::Pod for timex {}
+
+static_assertions::assert_eq_size!(timex, [u8; 208]);
+
 pub fn clock_gettime_raw(clockid: linux___kernel_clockid_t) -> Result<timespec, Errno> {
     let mut t = shadow_pod::zeroed();
     unsafe { syscall!(linux_syscall::SYS_clock_gettime, clockid, &mut t) }
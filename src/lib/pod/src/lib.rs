@@ -281,6 +281,8 @@ unsafe impl Pod for libc::signalfd_siginfo {}
 unsafe impl Pod for libc::sigset_t {}
 unsafe impl Pod for libc::sigval {}
 unsafe impl Pod for libc::sock_extended_err {}
+unsafe impl Pod for libc::sock_filter {}
+unsafe impl Pod for libc::sock_fprog {}
 unsafe impl Pod for libc::sockaddr {}
 unsafe impl Pod for libc::sockaddr_alg {}
 unsafe impl Pod for libc::sockaddr_can {}
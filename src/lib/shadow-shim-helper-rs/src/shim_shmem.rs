@@ -305,6 +305,8 @@ impl ThreadShmem {
                         ss_flags: libc::SS_DISABLE,
                         ss_size: 0,
                     }),
+                    user_cpu_time: SimulationTime::ZERO,
+                    system_cpu_time: SimulationTime::ZERO,
                 },
             ),
         }
@@ -342,6 +344,12 @@ pub struct ThreadShmemProtected {
 
     // Configured alternate signal stack for this thread.
     sigaltstack: StackWrapper,
+
+    // Simulated CPU time used by this thread in user mode and in the kernel. This is the latency
+    // modeled for the thread's syscalls, so is only non-zero if `model_unblocked_syscall_latency`
+    // is enabled.
+    pub user_cpu_time: SimulationTime,
+    pub system_cpu_time: SimulationTime,
 }
 
 impl ThreadShmemProtected {
@@ -700,6 +708,30 @@ pub mod export {
         lock.unapplied_cpu_latency = SimulationTime::ZERO;
     }
 
+    /// Add to the simulated CPU time used by the thread, in user mode if `user` is true and in
+    /// the kernel otherwise.
+    ///
+    /// # Safety
+    ///
+    /// Pointer args must be safely dereferenceable.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn shimshmem_incrementThreadCpuTime(
+        lock: *const ShimShmemHostLock,
+        thread: *const ShimShmemThread,
+        dt: CSimulationTime,
+        user: bool,
+    ) {
+        let thread_mem = unsafe { thread.as_ref().unwrap() };
+        let lock = unsafe { lock.as_ref().unwrap() };
+        let mut protected = thread_mem.protected.borrow_mut(&lock.root);
+        let dt = SimulationTime::from_c_simtime(dt).unwrap();
+        if user {
+            protected.user_cpu_time += dt;
+        } else {
+            protected.system_cpu_time += dt;
+        }
+    }
+
     /// Get whether to model latency of unblocked syscalls.
     ///
     /// # Safety
@@ -110,6 +110,21 @@ static void _shim_parent_init_seccomp() {
     shim_seccomp_init();
 }
 
+// The kernel fills the 16 bytes at `AT_RANDOM` with (non-deterministic) random bytes. Replace them
+// with bytes from shadow's deterministic random source. The loader has already used the original
+// bytes for the stack protector, but anything reading them later will get the deterministic bytes.
+static void _shim_parent_init_auxv_random() {
+    void* at_random = (void*)getauxval(AT_RANDOM);
+    if (at_random == NULL) {
+        return;
+    }
+
+    long rv = shim_emulated_syscall(NULL, SYS_getrandom, at_random, 16, 0);
+    if (rv != 16) {
+        panic("Couldn't overwrite AT_RANDOM bytes: %ld", rv);
+    }
+}
+
 static void _shim_parent_init_rdtsc_emu() {
     shim_rdtsc_init();
 }
@@ -128,6 +143,7 @@ void _shim_parent_init_preload() {
     _shim_init_signal_stack();
     _shim_init_death_signal();
     _shim_parent_init_memory_manager();
+    _shim_parent_init_auxv_random();
     _shim_parent_init_rdtsc_emu();
     _shim_parent_init_seccomp();
     _shim_parent_close_stdin();
@@ -38,20 +38,27 @@ uint64_t shim_sys_get_simtime_nanos() {
            SIMTIME_ONE_NANOSECOND;
 }
 
-static CSimulationTime _shim_sys_latency_for_syscall(long n) {
+// Whether the syscall would typically be a VDSO call outside of Shadow, which
+// runs in user mode.
+static bool _shim_sys_is_vdso_syscall(long n) {
     switch (n) {
         case SYS_clock_gettime:
         case SYS_time:
         case SYS_gettimeofday:
         case SYS_getcpu:
-            // This would typically be a VDSO call outside of Shadow.
-            //
-            // It might not be, if the caller directly used a `syscall`
-            // instruction or function call, but this is unusual, and charging
-            // too-little latency here shouldn't hurt much, given that its main
-            // purpose is currently to escape busy loops rather than to fully
-            // model CPU time.
-            return shimshmem_unblockedVdsoLatency(shim_hostSharedMem());
+            return true;
+    }
+    return false;
+}
+
+static CSimulationTime _shim_sys_latency_for_syscall(long n) {
+    if (_shim_sys_is_vdso_syscall(n)) {
+        // It might not be a VDSO call, if the caller directly used a `syscall`
+        // instruction or function call, but this is unusual, and charging
+        // too-little latency here shouldn't hurt much, given that its main
+        // purpose is currently to escape busy loops rather than to fully
+        // model CPU time.
+        return shimshmem_unblockedVdsoLatency(shim_hostSharedMem());
     }
     // This would typically *not* be a VDSO call outside of Shadow, even if
     // Shadow does implement it in the shim.
@@ -68,6 +75,19 @@ bool shim_sys_handle_syscall_locally(long syscall_num, long* rv, va_list args) {
         case SYS_clock_gettime: {
             syscallName = "clock_gettime";
 
+            // Shadow tracks the CPU time of the process's threads and any slewing of the host's
+            // clock, so let it handle the CPU-time and raw clocks. Peek at the clock id with a
+            // copy so that the arguments are left unconsumed.
+            va_list args_copy;
+            va_copy(args_copy, args);
+            clockid_t peek_clk_id = va_arg(args_copy, clockid_t);
+            va_end(args_copy);
+            if (peek_clk_id == LINUX_CLOCK_PROCESS_CPUTIME_ID ||
+                peek_clk_id == LINUX_CLOCK_THREAD_CPUTIME_ID ||
+                peek_clk_id == LINUX_CLOCK_MONOTONIC_RAW) {
+                return false;
+            }
+
             CEmulatedTime emulated_time = _shim_sys_get_time();
 
             trace("servicing syscall %ld:clock_gettime from the shim", syscall_num);
@@ -183,8 +203,11 @@ bool shim_sys_handle_syscall_locally(long syscall_num, long* rv, va_list args) {
 
     if (shimshmem_getModelUnblockedSyscallLatency(shim_hostSharedMem())) {
         ShimShmemHostLock* host_lock = shimshmemhost_lock(shim_hostSharedMem());
-        shimshmem_incrementUnappliedCpuLatency(
-            host_lock, _shim_sys_latency_for_syscall(syscall_num));
+        CSimulationTime latency = _shim_sys_latency_for_syscall(syscall_num);
+        shimshmem_incrementUnappliedCpuLatency(host_lock, latency);
+        // the latency is also the CPU time that the thread used for the syscall
+        shimshmem_incrementThreadCpuTime(host_lock, shim_threadSharedMem(), latency,
+                                         _shim_sys_is_vdso_syscall(syscall_num));
         CSimulationTime unappliedCpuLatency = shimshmem_getUnappliedCpuLatency(host_lock);
         // TODO: Once ptrace mode is deprecated, we can hold this lock longer to
         // avoid having to reacquire it below. We currently can't hold the lock
@@ -331,6 +331,24 @@ impl RecvQueue {
 
         Ok(bytes_copied)
     }
+
+    /// Like [`read`](Self::read), but doesn't remove the data from the queue.
+    pub fn peek(&self, mut writer: impl Write, len: usize) -> Result<usize, std::io::Error> {
+        let mut bytes_copied = 0;
+
+        for data in &self.segments {
+            if bytes_copied >= len {
+                break;
+            }
+
+            let data = &data[..std::cmp::min(data.len(), len - bytes_copied)];
+            writer.write_all(data)?;
+
+            bytes_copied += data.len();
+        }
+
+        Ok(bytes_copied)
+    }
 }
 
 #[derive(Debug)]
@@ -106,13 +106,17 @@ impl<I: Instant> Connection<I> {
         Ok(len)
     }
 
-    pub fn recv(&mut self, writer: impl Write, len: usize) -> Result<usize, RecvError> {
+    pub fn recv(&mut self, writer: impl Write, len: usize, peek: bool) -> Result<usize, RecvError> {
         let recv = self.recv.as_mut().unwrap();
 
         if recv.buffer.is_empty() {
             return Err(RecvError::Empty);
         }
 
+        if peek {
+            return recv.buffer.peek(writer, len).map_err(RecvError::Io);
+        }
+
         recv.buffer.read(writer, len).map_err(RecvError::Io)
     }
 
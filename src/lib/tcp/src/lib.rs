@@ -207,7 +207,14 @@ where
         (self.into(), Err(SendError::InvalidState))
     }
 
-    fn recv(self, _writer: impl Write, _len: usize) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
+    /// Read up to `len` bytes from the receive buffer. If `peek` is true, the bytes are copied but
+    /// not removed from the receive buffer.
+    fn recv(
+        self,
+        _writer: impl Write,
+        _len: usize,
+        _peek: bool,
+    ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
         (self.into(), Err(RecvError::InvalidState))
     }
 
@@ -301,8 +308,8 @@ impl<X: Dependencies> TcpState<X> {
     }
 
     #[inline]
-    pub fn recv(&mut self, writer: impl Write, len: usize) -> Result<usize, RecvError> {
-        self.with_state(|state| state.recv(writer, len))
+    pub fn recv(&mut self, writer: impl Write, len: usize, peek: bool) -> Result<usize, RecvError> {
+        self.with_state(|state| state.recv(writer, len, peek))
     }
 
     #[inline]
@@ -316,7 +316,12 @@ impl<X: Dependencies> TcpStateTrait<X> for InitState<X> {
         (self.into(), Err(SendError::NotConnected))
     }
 
-    fn recv(self, _writer: impl Write, _len: usize) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
+    fn recv(
+        self,
+        _writer: impl Write,
+        _len: usize,
+        _peek: bool,
+    ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
         (self.into(), Err(RecvError::NotConnected))
     }
 
@@ -537,10 +542,9 @@ impl<X: Dependencies> TcpStateTrait<X> for ListenState<X> {
     }
 
     fn shutdown(self, _how: Shutdown) -> (TcpStateEnum<X>, Result<(), ShutdownError>) {
-        // TODO: Linux will reset back to the initial state (allowing future connect(), listen(),
-        // etc for the same socket) for SHUT_RD or SHUT_RDWR. But this should probably be handled in
-        // a higher layer (for example having the socket replace this tcp state with a new tcp state
-        // object).
+        // Linux stops listening for SHUT_RD or SHUT_RDWR and does nothing for SHUT_WR. This is
+        // handled in a higher layer (the socket closes this tcp state). TODO: Linux will also reset
+        // back to the initial state, allowing future connect(), listen(), etc for the same socket.
 
         (self.into(), Err(ShutdownError::NotConnected))
     }
@@ -598,7 +602,12 @@ impl<X: Dependencies> TcpStateTrait<X> for ListenState<X> {
         (self.into(), Err(SendError::NotConnected))
     }
 
-    fn recv(self, _writer: impl Write, _len: usize) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
+    fn recv(
+        self,
+        _writer: impl Write,
+        _len: usize,
+        _peek: bool,
+    ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
         (self.into(), Err(RecvError::NotConnected))
     }
 
@@ -815,7 +824,12 @@ impl<X: Dependencies> TcpStateTrait<X> for SynSentState<X> {
         (self.into(), Err(SendError::NotConnected))
     }
 
-    fn recv(self, _writer: impl Write, _len: usize) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
+    fn recv(
+        self,
+        _writer: impl Write,
+        _len: usize,
+        _peek: bool,
+    ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
         (self.into(), Err(RecvError::NotConnected))
     }
 
@@ -974,7 +988,12 @@ impl<X: Dependencies> TcpStateTrait<X> for SynReceivedState<X> {
         (self.into(), Err(SendError::NotConnected))
     }
 
-    fn recv(self, _writer: impl Write, _len: usize) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
+    fn recv(
+        self,
+        _writer: impl Write,
+        _len: usize,
+        _peek: bool,
+    ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
         (self.into(), Err(RecvError::NotConnected))
     }
 
@@ -1114,8 +1133,9 @@ impl<X: Dependencies> TcpStateTrait<X> for EstablishedState<X> {
         mut self,
         writer: impl Write,
         len: usize,
+        peek: bool,
     ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
-        let rv = self.connection.recv(writer, len);
+        let rv = self.connection.recv(writer, len, peek);
         (self.into(), rv)
     }
 
@@ -1245,8 +1265,9 @@ impl<X: Dependencies> TcpStateTrait<X> for FinWaitOneState<X> {
         mut self,
         writer: impl Write,
         len: usize,
+        peek: bool,
     ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
-        let rv = self.connection.recv(writer, len);
+        let rv = self.connection.recv(writer, len, peek);
         (self.into(), rv)
     }
 
@@ -1388,8 +1409,9 @@ impl<X: Dependencies> TcpStateTrait<X> for FinWaitTwoState<X> {
         mut self,
         writer: impl Write,
         len: usize,
+        peek: bool,
     ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
-        let rv = self.connection.recv(writer, len);
+        let rv = self.connection.recv(writer, len, peek);
         (self.into(), rv)
     }
 
@@ -1519,8 +1541,9 @@ impl<X: Dependencies> TcpStateTrait<X> for ClosingState<X> {
         mut self,
         writer: impl Write,
         len: usize,
+        peek: bool,
     ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
-        let rv = self.connection.recv(writer, len);
+        let rv = self.connection.recv(writer, len, peek);
 
         // the peer won't send any more data (it sent a FIN), so if there's no more data in the
         // buffer, inform the socket
@@ -1676,8 +1699,9 @@ impl<X: Dependencies> TcpStateTrait<X> for TimeWaitState<X> {
         mut self,
         writer: impl Write,
         len: usize,
+        peek: bool,
     ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
-        let rv = self.connection.recv(writer, len);
+        let rv = self.connection.recv(writer, len, peek);
 
         // the peer won't send any more data (it sent a FIN), so if there's no more data in the
         // buffer, inform the socket
@@ -1827,8 +1851,9 @@ impl<X: Dependencies> TcpStateTrait<X> for CloseWaitState<X> {
         mut self,
         writer: impl Write,
         len: usize,
+        peek: bool,
     ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
-        let rv = self.connection.recv(writer, len);
+        let rv = self.connection.recv(writer, len, peek);
 
         // the peer won't send any more data (it sent a FIN), so if there's no more data in the
         // buffer, inform the socket
@@ -1961,8 +1986,9 @@ impl<X: Dependencies> TcpStateTrait<X> for LastAckState<X> {
         mut self,
         writer: impl Write,
         len: usize,
+        peek: bool,
     ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
-        let rv = self.connection.recv(writer, len);
+        let rv = self.connection.recv(writer, len, peek);
 
         // the peer won't send any more data (it sent a FIN), so if there's no more data in the
         // buffer, inform the socket
@@ -2111,7 +2137,12 @@ impl<X: Dependencies> TcpStateTrait<X> for RstState<X> {
         }
     }
 
-    fn recv(self, _writer: impl Write, _len: usize) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
+    fn recv(
+        self,
+        _writer: impl Write,
+        _len: usize,
+        _peek: bool,
+    ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
         if self.was_connected {
             (self.into(), Err(RecvError::StreamClosed))
         } else {
@@ -2247,6 +2278,7 @@ impl<X: Dependencies> TcpStateTrait<X> for ClosedState<X> {
         mut self,
         writer: impl Write,
         len: usize,
+        peek: bool,
     ) -> (TcpStateEnum<X>, Result<usize, RecvError>) {
         if !self.was_connected {
             return (self.into(), Err(RecvError::NotConnected));
@@ -2256,7 +2288,12 @@ impl<X: Dependencies> TcpStateTrait<X> for ClosedState<X> {
             return (self.into(), Err(RecvError::StreamClosed));
         }
 
-        let rv = self.recv_buffer.read(writer, len).map_err(RecvError::Io);
+        let rv = if peek {
+            self.recv_buffer.peek(writer, len)
+        } else {
+            self.recv_buffer.read(writer, len)
+        };
+        let rv = rv.map_err(RecvError::Io);
 
         (self.into(), rv)
     }
@@ -664,10 +664,11 @@ impl TcpSocket {
         socket: &Rc<RefCell<Self>>,
         buffer: impl Write,
         len: usize,
+        peek: bool,
     ) -> Result<usize, Errno> {
         let socket_ref = &mut *socket.borrow_mut();
 
-        let rv = socket_ref.with_tcp_state(|state| state.recv(buffer, len));
+        let rv = socket_ref.with_tcp_state(|state| state.recv(buffer, len, peek));
 
         match rv {
             Ok(n) => Ok(n),
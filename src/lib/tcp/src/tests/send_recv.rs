@@ -5,7 +5,7 @@ use std::rc::Rc;
 
 use bytes::Bytes;
 
-use crate::tests::{establish_helper, Host, Scheduler, TcpSocket, TestEnvState};
+use crate::tests::{establish_helper, Errno, Host, Scheduler, TcpSocket, TestEnvState};
 use crate::{Ipv4Header, Payload, Shutdown, TcpFlags, TcpHeader, TcpState};
 
 #[test]
@@ -53,7 +53,7 @@ fn test_send_recv() {
 
     // recv on the socket
     let mut recv_buf = vec![0; 5];
-    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 5).unwrap();
+    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 5, false).unwrap();
     assert_eq!(recv_buf, b"world");
 }
 
@@ -239,10 +239,74 @@ fn test_coalesce_recv() {
 
     // recv on the socket
     let mut recv_buf = vec![0; 10];
-    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 10).unwrap();
+    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 10, false).unwrap();
     assert_eq!(recv_buf, b"helloworld");
 }
 
+#[test]
+fn test_peek() {
+    let scheduler = Scheduler::new();
+    let mut host = Host::new();
+
+    // get an established tcp socket
+    let tcp = establish_helper(&scheduler, &mut host);
+
+    // nothing to peek yet
+    let mut recv_buf = vec![0; 10];
+    assert_eq!(
+        TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 10, true),
+        Err(Errno::EWOULDBLOCK)
+    );
+
+    // send two packets to the socket
+    for (seq, message) in [(1, b"hello"), (6, b"world")] {
+        let header = TcpHeader {
+            ip: Ipv4Header {
+                src: "5.6.7.8".parse().unwrap(),
+                dst: host.ip_addr,
+            },
+            flags: TcpFlags::empty(),
+            src_port: 20,
+            dst_port: 10,
+            seq,
+            ack: 6,
+            window_size: 10000,
+            selective_acks: None,
+            window_scale: None,
+            timestamp: None,
+            timestamp_echo: None,
+        };
+        let pushed_len = tcp
+            .borrow_mut()
+            .push_in_packet(&header, Bytes::from(&message[..]).into());
+        assert_eq!(pushed_len, message.len());
+    }
+
+    // peek across both packets, and peeking again returns the same bytes
+    for _ in 0..2 {
+        let mut recv_buf = vec![0; 7];
+        TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 7, true).unwrap();
+        assert_eq!(recv_buf, b"hellowo");
+    }
+
+    // a normal recv returns the peeked bytes
+    let mut recv_buf = vec![0; 3];
+    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 3, false).unwrap();
+    assert_eq!(recv_buf, b"hel");
+
+    // peeking starts after the received bytes
+    let mut recv_buf = vec![0; 10];
+    assert_eq!(TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 10, true), Ok(7));
+    assert_eq!(&recv_buf[..7], b"loworld");
+
+    let mut recv_buf = vec![0; 10];
+    assert_eq!(
+        TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 10, false),
+        Ok(7)
+    );
+    assert_eq!(&recv_buf[..7], b"loworld");
+}
+
 #[test]
 fn test_close_with_non_empty_recv_buffer() {
     let scheduler = Scheduler::new();
@@ -342,7 +406,7 @@ fn test_recv_after_shutdown_both() {
 
     // should still be able to recv old data on the socket
     let mut recv_buf = vec![0; 2];
-    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 2).unwrap();
+    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 2, false).unwrap();
     assert_eq!(recv_buf, b"he");
 
     // send a FIN packet to the socket
@@ -368,7 +432,7 @@ fn test_recv_after_shutdown_both() {
 
     // should still be able to recv old data on the socket
     let mut recv_buf = vec![0; 2];
-    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 2).unwrap();
+    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 2, false).unwrap();
     assert_eq!(recv_buf, b"ll");
 
     // check that our FIN was acknowledged
@@ -383,7 +447,7 @@ fn test_recv_after_shutdown_both() {
 
     // should still be able to recv old data on the socket
     let mut recv_buf = vec![0; 2];
-    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 2).unwrap();
+    TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 2, false).unwrap();
     assert_eq!(recv_buf, b"o\0");
 }
 
@@ -442,7 +506,7 @@ fn test_incoming_payload_after_close() {
     // (typically on linux we'd receive an ECONNRESET for the first read, but we don't use the error
     // state in our test socket wrapper)
     let mut recv_buf = vec![0; 5];
-    assert_eq!(TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 5), Ok(0));
+    assert_eq!(TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 5, false), Ok(0));
 }
 
 #[test]
@@ -500,5 +564,5 @@ fn test_incoming_payload_after_shutdown_read() {
     // (typically on linux we'd receive an ECONNRESET for the first read, but we don't use the error
     // state in our test socket wrapper)
     let mut recv_buf = vec![0; 5];
-    assert_eq!(TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 5), Ok(0));
+    assert_eq!(TcpSocket::recvmsg(&tcp, &mut recv_buf[..], 5, false), Ok(0));
 }
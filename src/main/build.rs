@@ -147,6 +147,8 @@ fn run_bindgen(build_common: &ShadowBuildCommon) {
         .header("host/descriptor/epoll.h")
         .header("host/descriptor/regular_file.h")
         .header("host/descriptor/tcp_cong.h")
+        .header("host/descriptor/tcp_cong_bbr.h")
+        .header("host/descriptor/tcp_cong_cubic.h")
         .header("host/descriptor/tcp_cong_reno.h")
         .header("host/futex.h")
         .header("host/status_listener.h")
@@ -238,14 +240,20 @@ fn run_bindgen(build_common: &ShadowBuildCommon) {
         .allowlist_type("LogInfoFlags")
         .allowlist_type("SimulationTime")
         .allowlist_type("ProtocolTCPFlags")
+        .allowlist_type("ProtocolECN")
         .allowlist_type("PacketDeliveryStatusFlags")
+        .allowlist_type("ShadowFeatureStatus")
         .allowlist_type("ShadowSyscallNum")
+        .allowlist_type("ShadowTcpCcPhase")
+        .allowlist_type("ShadowTcpCcState")
         .allowlist_var("AFFINITY_UNINIT")
         .allowlist_var("CONFIG_HEADER_SIZE_TCP")
         .allowlist_var("CONFIG_PIPE_BUFFER_SIZE")
         .allowlist_var("CONFIG_MTU")
         .allowlist_var("SYSCALL_IO_BUFSIZE")
         .allowlist_var("SHADOW_SOMAXCONN")
+        .allowlist_var("TCP_CONG_BBR_NAME")
+        .allowlist_var("TCP_CONG_CUBIC_NAME")
         .allowlist_var("TCP_CONG_RENO_NAME")
         .allowlist_var("SHADOW_FLAG_MASK")
         .allowlist_var("GLIB_MAJOR_VERSION")
@@ -356,6 +364,8 @@ fn build_shadow_c(build_common: &ShadowBuildCommon) {
         "host/descriptor/socket.c",
         "host/descriptor/tcp.c",
         "host/descriptor/tcp_cong.c",
+        "host/descriptor/tcp_cong_bbr.c",
+        "host/descriptor/tcp_cong_cubic.c",
         "host/descriptor/tcp_cong_reno.c",
         "host/process.c",
         "host/futex.c",
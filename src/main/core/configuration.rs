@@ -74,6 +74,11 @@ pub struct CliOptions {
     #[clap(long)]
     pub show_config: bool,
 
+    /// Write the syscall counts of each process to a JSON file at the end of the simulation. A
+    /// relative path is relative to the data directory
+    #[clap(long, value_name = "path")]
+    pub dump_syscall_counts: Option<std::path::PathBuf>,
+
     #[clap(flatten)]
     pub general: GeneralOptions,
 
@@ -174,6 +179,18 @@ impl ConfigOptions {
         SimulationTime::from_nanos(nanos)
     }
 
+    pub fn cpu_migration_cost(&self) -> SimulationTime {
+        let nanos = self.experimental.cpu_migration_cost.unwrap();
+        let nanos = nanos.convert(units::TimePrefix::Nano).unwrap().value();
+        SimulationTime::from_nanos(nanos)
+    }
+
+    pub fn entropy_pool_init_time(&self) -> SimulationTime {
+        let nanos = self.experimental.entropy_pool_init_time.unwrap();
+        let nanos = nanos.convert(units::TimePrefix::Nano).unwrap().value();
+        SimulationTime::from_nanos(nanos)
+    }
+
     pub fn strace_logging_mode(&self) -> Option<FmtOptions> {
         match self.experimental.strace_logging_mode.as_ref().unwrap() {
             StraceLoggingMode::Standard => Some(FmtOptions::Standard),
@@ -226,6 +243,19 @@ pub struct GeneralOptions {
     #[serde(default = "default_some_info")]
     pub log_level: Option<LogLevel>,
 
+    /// Format of log messages written on stdout: 'text' for human-readable lines, or 'json' for
+    /// one JSON object per line
+    #[clap(long, value_name = "format")]
+    #[clap(help = GENERAL_HELP.get("log_format").unwrap().as_str())]
+    #[serde(default = "default_some_log_format_text")]
+    pub log_format: Option<LogFormat>,
+
+    /// Also write each host's log messages to a 'shadow.log' file in the host's data directory
+    #[clap(long, value_name = "bool")]
+    #[clap(help = GENERAL_HELP.get("log_per_host").unwrap().as_str())]
+    #[serde(default = "default_some_false")]
+    pub log_per_host: Option<bool>,
+
     /// Interval at which to print heartbeat messages
     #[clap(long, value_name = "seconds")]
     #[clap(help = GENERAL_HELP.get("heartbeat_interval").unwrap().as_str())]
@@ -464,6 +494,20 @@ pub struct ExperimentalOptions {
     #[clap(help = EXP_HELP.get("unblocked_vdso_latency").unwrap().as_str())]
     pub unblocked_vdso_latency: Option<units::Time<units::TimePrefix>>,
 
+    /// Simulated CPU time used by a thread when the `shadow_pin_cpu` syscall moves it to a
+    /// different simulated CPU.
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "seconds")]
+    #[clap(help = EXP_HELP.get("cpu_migration_cost").unwrap().as_str())]
+    pub cpu_migration_cost: Option<units::Time<units::TimePrefix>>,
+
+    /// Simulated time after the start of the simulation before a host's entropy pool is
+    /// initialized. Until then, `getrandom` calls with `GRND_NONBLOCK` fail with `EAGAIN`.
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "seconds")]
+    #[clap(help = EXP_HELP.get("entropy_pool_init_time").unwrap().as_str())]
+    pub entropy_pool_init_time: Option<units::Time<units::TimePrefix>>,
+
     /// The host scheduler implementation, which decides how to assign hosts to threads and threads
     /// to CPU cores
     #[clap(hide_short_help = true)]
@@ -482,6 +526,97 @@ pub struct ExperimentalOptions {
     #[clap(long, value_name = "bool")]
     #[clap(help = EXP_HELP.get("use_new_tcp").unwrap().as_str())]
     pub use_new_tcp: Option<bool>,
+
+    /// Fail connecting or sending to an address that doesn't belong to any simulated host with
+    /// ENETUNREACH. If false, connecting fails with ECONNREFUSED and sent datagrams are dropped.
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bool")]
+    #[clap(help = EXP_HELP.get("use_strict_unreachable").unwrap().as_str())]
+    pub use_strict_unreachable: Option<bool>,
+
+    /// The inclusive range of ports that are assigned to sockets that bind to port 0 or are bound
+    /// implicitly (for example when connecting)
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "start-end")]
+    #[clap(help = EXP_HELP.get("ephemeral_port_range").unwrap().as_str())]
+    pub ephemeral_port_range: Option<PortRange>,
+
+    /// Negotiate explicit congestion notification (ECN) on TCP connections
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bool")]
+    #[clap(help = EXP_HELP.get("use_ecn").unwrap().as_str())]
+    pub use_ecn: Option<bool>,
+
+    /// Mark ECN-capable packets with "congestion experienced" when a host's inbound router queue
+    /// holds more than this many bytes
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bytes")]
+    #[clap(help = EXP_HELP.get("ecn_mark_threshold").unwrap().as_str())]
+    pub ecn_mark_threshold: Option<NullableOption<units::Bytes<units::SiPrefixUpper>>>,
+
+    /// The active queue management (AQM) algorithm used by each host's inbound router queue
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "name")]
+    #[clap(help = EXP_HELP.get("router_aqm").unwrap().as_str())]
+    pub router_aqm: Option<RouterAqm>,
+
+    /// The maximum number of bytes that each host's inbound router queue can hold, after which
+    /// arriving packets are dropped
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bytes")]
+    #[clap(help = EXP_HELP.get("router_queue_size").unwrap().as_str())]
+    pub router_queue_size: Option<NullableOption<units::Bytes<units::SiPrefixUpper>>>,
+
+    /// The target queueing delay of the CoDel router queue
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "seconds")]
+    #[clap(help = EXP_HELP.get("router_codel_target").unwrap().as_str())]
+    pub router_codel_target: Option<units::Time<units::TimePrefix>>,
+
+    /// The interval over which the CoDel router queue measures the queueing delay
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "seconds")]
+    #[clap(help = EXP_HELP.get("router_codel_interval").unwrap().as_str())]
+    pub router_codel_interval: Option<units::Time<units::TimePrefix>>,
+
+    /// The average size of the RED router queue at which it starts dropping packets
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bytes")]
+    #[clap(help = EXP_HELP.get("router_red_min_threshold").unwrap().as_str())]
+    pub router_red_min_threshold: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The average size of the RED router queue at which it drops every packet
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bytes")]
+    #[clap(help = EXP_HELP.get("router_red_max_threshold").unwrap().as_str())]
+    pub router_red_max_threshold: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The probability that the RED router queue drops a packet when its average size reaches the
+    /// maximum threshold
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "probability")]
+    #[clap(help = EXP_HELP.get("router_red_max_probability").unwrap().as_str())]
+    pub router_red_max_probability: Option<f64>,
+
+    /// Accept and ignore setsockopt() calls for socket options that shadow doesn't support, rather
+    /// than returning ENOPROTOOPT
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bool")]
+    #[clap(help = EXP_HELP.get("ignore_unsupported_sockopts").unwrap().as_str())]
+    pub ignore_unsupported_sockopts: Option<bool>,
+
+    /// The congestion window (in segments) of new TCP connections
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "segments")]
+    #[clap(help = EXP_HELP.get("tcp_initial_cwnd").unwrap().as_str())]
+    pub tcp_initial_cwnd: Option<u32>,
+
+    /// Place memory mappings that don't request an address at pseudo-random addresses derived
+    /// from the seed, rather than at the kernel's non-randomized addresses
+    #[clap(hide_short_help = true)]
+    #[clap(long, value_name = "bool")]
+    #[clap(help = EXP_HELP.get("use_deterministic_aslr").unwrap().as_str())]
+    pub use_deterministic_aslr: Option<bool>,
 }
 
 impl ExperimentalOptions {
@@ -509,6 +644,8 @@ impl Default for ExperimentalOptions {
             // Actual latencies vary from ~40 to ~400 CPU cycles. https://stackoverflow.com/a/13096917
             // Default to the lower end to minimize effect in simualations without busy loops.
             unblocked_vdso_latency: Some(units::Time::new(10, units::TimePrefix::Nano)),
+            cpu_migration_cost: Some(units::Time::new(0, units::TimePrefix::Nano)),
+            entropy_pool_init_time: Some(units::Time::new(0, units::TimePrefix::Nano)),
             use_memory_manager: Some(false),
             use_cpu_pinning: Some(true),
             use_worker_spinning: Some(true),
@@ -532,6 +669,24 @@ impl Default for ExperimentalOptions {
             scheduler: Some(Scheduler::ThreadPerCore),
             report_errors_to_stderr: Some(true),
             use_new_tcp: Some(false),
+            use_strict_unreachable: Some(true),
+            // the default of linux's "net.ipv4.ip_local_port_range"
+            ephemeral_port_range: Some(PortRange {
+                start: 32768,
+                end: 60999,
+            }),
+            use_ecn: Some(false),
+            ecn_mark_threshold: Some(NullableOption::Null),
+            router_aqm: Some(RouterAqm::Codel),
+            router_queue_size: Some(NullableOption::Null),
+            router_codel_target: Some(units::Time::new(10, units::TimePrefix::Milli)),
+            router_codel_interval: Some(units::Time::new(100, units::TimePrefix::Milli)),
+            router_red_min_threshold: Some(units::Bytes::new(30, units::SiPrefixUpper::Kilo)),
+            router_red_max_threshold: Some(units::Bytes::new(90, units::SiPrefixUpper::Kilo)),
+            router_red_max_probability: Some(0.1),
+            ignore_unsupported_sockopts: Some(false),
+            tcp_initial_cwnd: Some(2),
+            use_deterministic_aslr: Some(false),
         }
     }
 }
@@ -563,6 +718,11 @@ pub struct HostDefaultOptions {
     #[clap(long, value_name = "bytes")]
     #[clap(help = HOST_HELP.get("pcap_capture_size").unwrap().as_str())]
     pub pcap_capture_size: Option<units::Bytes<units::SiPrefixUpper>>,
+
+    /// The congestion control algorithm used by new TCP sockets
+    #[clap(long, value_name = "algorithm")]
+    #[clap(help = HOST_HELP.get("tcp_congestion_control").unwrap().as_str())]
+    pub tcp_congestion_control: Option<TcpCongestionControl>,
 }
 
 impl HostDefaultOptions {
@@ -574,6 +734,7 @@ impl HostDefaultOptions {
             // capture all the data available from the packet". The maximum length of an IP packet
             // (including the header) is 65535 bytes.
             pcap_capture_size: Some(units::Bytes::new(65535, units::SiPrefixUpper::Base)),
+            tcp_congestion_control: Some(TcpCongestionControl::Reno),
         }
     }
 
@@ -596,6 +757,7 @@ impl Default for HostDefaultOptions {
             log_level: None,
             pcap_enabled: None,
             pcap_capture_size: None,
+            tcp_congestion_control: None,
         }
     }
 }
@@ -689,6 +851,26 @@ pub struct HostOptions {
     #[serde(default)]
     pub bandwidth_up: Option<units::BitsPerSec<units::SiPrefixUpper>>,
 
+    /// Files or directories to make available read-only to the host's processes, keyed by the
+    /// absolute path that the processes will access them at
+    #[serde(default)]
+    pub read_only_mounts: BTreeMap<std::path::PathBuf, std::path::PathBuf>,
+
+    /// Size-limited in-memory filesystems to make available to the host's processes, keyed by the
+    /// absolute path that the processes will access them at
+    #[serde(default)]
+    pub tmpfs_mounts: BTreeMap<std::path::PathBuf, units::Bytes<units::SiPrefixUpper>>,
+
+    /// Fixed-size block devices to make available to the host's processes, keyed by the absolute
+    /// path that the processes will access them at
+    #[serde(default)]
+    pub block_devices: BTreeMap<std::path::PathBuf, BlockDeviceOptions>,
+
+    /// Files to create before the host's processes start, keyed by the path that the processes
+    /// will access them at
+    #[serde(default)]
+    pub preloaded_files: BTreeMap<std::path::PathBuf, PreloadedFileSource>,
+
     #[serde(default)]
     pub host_options: HostDefaultOptions,
 }
@@ -735,6 +917,21 @@ impl From<LogLevel> for log::Level {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq, Serialize, JsonSchema)]
 pub struct HostName(String);
 
@@ -970,6 +1167,103 @@ impl FromStr for QDiscMode {
     }
 }
 
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RouterAqm {
+    None,
+    Codel,
+    Red,
+}
+
+impl FromStr for RouterAqm {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum TcpCongestionControl {
+    Reno,
+    Cubic,
+    Bbr,
+}
+
+impl FromStr for TcpCongestionControl {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+    }
+}
+
+/// An inclusive range of ports, written as "start-end".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl FromStr for PortRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((start, end)) = s.split_once('-') else {
+            return Err(format!("Port range '{s}' is not of the form 'start-end'"));
+        };
+
+        let parse_port = |x: &str| {
+            x.trim()
+                .parse::<u16>()
+                .map_err(|e| format!("Invalid port '{x}' in port range '{s}': {e}"))
+        };
+
+        let range = Self {
+            start: parse_port(start)?,
+            end: parse_port(end)?,
+        };
+
+        if range.start == 0 || range.start > range.end {
+            return Err(format!("Invalid port range '{s}'"));
+        }
+
+        Ok(range)
+    }
+}
+
+impl TryFrom<String> for PortRange {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<PortRange> for String {
+    fn from(range: PortRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl std::fmt::Display for PortRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl JsonSchema for PortRange {
+    fn schema_name() -> String {
+        String::from("PortRange")
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum Compression {
@@ -992,6 +1286,25 @@ pub enum GraphSource {
     Inline(String),
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BlockDeviceOptions {
+    /// The size of the device
+    pub size: units::Bytes<units::SiPrefixUpper>,
+    /// The device's logical sector size in bytes
+    #[serde(default = "default_sector_size")]
+    pub sector_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreloadedFileSource {
+    /// The path to a file whose contents will be copied
+    File(String),
+    /// The file's contents
+    Inline(String),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 // we use "kebab-case" for other shadow options, but are leaving this as "snake_case" for backwards
 // compatibility
@@ -1315,6 +1628,11 @@ fn default_sigterm() -> Signal {
     Signal(nix::sys::signal::Signal::SIGTERM)
 }
 
+/// Helper function for serde default block device sector sizes.
+fn default_sector_size() -> u32 {
+    512
+}
+
 /// Helper function for serde default `Some(0)` values.
 fn default_some_time_0() -> Option<units::Time<units::TimePrefix>> {
     Some(units::Time::new(0, units::TimePrefix::Sec))
@@ -1346,6 +1664,11 @@ fn default_some_nullable_time_1() -> Option<NullableOption<units::Time<units::Ti
     Some(NullableOption::Value(time))
 }
 
+/// Helper function for serde default `Some(LogFormat::Text)` values.
+fn default_some_log_format_text() -> Option<LogFormat> {
+    Some(LogFormat::Text)
+}
+
 /// Helper function for serde default `Some(LogLevel::Info)` values.
 fn default_some_info() -> Option<LogLevel> {
     Some(LogLevel::Info)
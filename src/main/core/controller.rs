@@ -1,4 +1,5 @@
 use std::io::IsTerminal;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -12,6 +13,7 @@ use shadow_shim_helper_rs::util::time::TimeParts;
 use crate::core::configuration::ConfigOptions;
 use crate::core::manager::{Manager, ManagerConfig};
 use crate::core::sim_config::SimConfig;
+use crate::core::sim_stats;
 use crate::core::worker;
 use crate::utility::status_bar::{self, StatusBar, StatusPrinter};
 
@@ -22,10 +24,17 @@ pub struct Controller<'a> {
 
     // the simulator should attempt to end immediately after this time
     end_time: EmulatedTime,
+
+    // where to write the syscall counts of each process, if requested
+    dump_syscall_counts: Option<PathBuf>,
 }
 
 impl<'a> Controller<'a> {
-    pub fn new(sim_config: SimConfig, config: &'a ConfigOptions) -> Self {
+    pub fn new(
+        sim_config: SimConfig,
+        config: &'a ConfigOptions,
+        dump_syscall_counts: Option<PathBuf>,
+    ) -> Self {
         let end_time: Duration = config.general.stop_time.unwrap().into();
         let end_time: SimulationTime = end_time.try_into().unwrap();
         let end_time = EmulatedTime::SIMULATION_START + end_time;
@@ -34,6 +43,7 @@ impl<'a> Controller<'a> {
             config,
             sim_config: Some(sim_config),
             end_time,
+            dump_syscall_counts,
         }
     }
 
@@ -66,6 +76,18 @@ impl<'a> Controller<'a> {
         let num_plugin_errors = manager.run(status_logger.as_ref().map(|x| x.status()))?;
         log::info!("Finished simulation");
 
+        if let Some(path) = &self.dump_syscall_counts {
+            // a relative path is relative to the data directory
+            let data_path = std::env::current_dir()
+                .context("Failed to get the current working directory")?
+                .join(self.config.general.data_directory.as_ref().unwrap());
+            let path = data_path.join(path);
+
+            worker::with_global_sim_stats(|stats| {
+                sim_stats::write_process_syscall_counts_to_file(&path, stats)
+            })?;
+        }
+
         if num_plugin_errors > 0 {
             return Err(anyhow::anyhow!(
                 "{num_plugin_errors} managed processes in unexpected final state"
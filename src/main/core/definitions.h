@@ -128,6 +128,11 @@ typedef struct Host Host;
  */
 #define CONFIG_TCP_MAX_SEGMENT_SIZE (CONFIG_MTU - CONFIG_HEADER_SIZE_TCPIP)
 
+/**
+ * Segment size assumed before a connection's segment size is known (TCP_MSS_DEFAULT in linux)
+ */
+#define CONFIG_TCP_DEFAULT_SEGMENT_SIZE 536
+
 /**
  * Maximum size of a datagram we are allowed to send out over the network
  */
@@ -1,16 +1,21 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
-use std::sync::{Mutex, RwLock};
+use std::sync::{Mutex, RwLock, TryLockError};
 use std::time::Duration;
 
 use crossbeam::queue::ArrayQueue;
 use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
 use logger as c_log;
 use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
 use shadow_shim_helper_rs::util::time::TimeParts;
 
+use crate::core::configuration::LogFormat;
 use crate::core::worker::Worker;
 use crate::host::host::HostInfo;
 
@@ -32,9 +37,11 @@ static SHADOW_LOGGER: Lazy<ShadowLogger> = Lazy::new(ShadowLogger::new);
 /// Initialize the Shadow logger.
 pub fn init(
     max_log_level: LevelFilter,
+    log_format: LogFormat,
     report_errors_to_stderr: bool,
 ) -> Result<(), SetLoggerError> {
     SHADOW_LOGGER.set_max_level(max_log_level);
+    SHADOW_LOGGER.set_log_format(log_format);
     SHADOW_LOGGER.set_report_errors_to_stderr(report_errors_to_stderr);
 
     log::set_logger(&*SHADOW_LOGGER)?;
@@ -100,8 +107,15 @@ pub struct ShadowLogger {
     // The maximum log level, unless overridden by a host-specific log level.
     max_log_level: OnceCell<LevelFilter>,
 
+    // The format of the records written to stdout.
+    log_format: OnceCell<LogFormat>,
+
     // Whether to report errors to stderr in addition to logging to stdout.
     report_errors_to_stderr: OnceCell<bool>,
+
+    // Per-host log files that records are also written to, keyed by the path in the record's
+    // `HostInfo`. Each file is opened when its host's first record is flushed.
+    host_logs: Mutex<HashMap<PathBuf, BufWriter<std::fs::File>>>,
 }
 
 thread_local!(static SENDER: RefCell<Option<Sender<LoggerCommand>>> = const{ RefCell::new(None)});
@@ -151,7 +165,9 @@ impl ShadowLogger {
             command_receiver: Mutex::new(receiver),
             buffering_enabled: RwLock::new(false),
             max_log_level: OnceCell::new(),
+            log_format: OnceCell::new(),
             report_errors_to_stderr: OnceCell::new(),
+            host_logs: Mutex::new(HashMap::new()),
         }
     }
 
@@ -177,8 +193,6 @@ impl ShadowLogger {
     // self.records. If `done_sender` is provided, it's notified after the flush
     // has completed.
     fn flush_records(&self, done_sender: Option<Sender<()>>) -> std::io::Result<()> {
-        use std::io::Write;
-
         // Only flush records that are already in the queue, not ones that
         // arrive while we're flushing. Otherwise callers who perform a
         // synchronous flush (whether this flush operation or another one that
@@ -190,6 +204,17 @@ impl ShadowLogger {
         let stdout_locked = stdout_unlocked.lock();
         let mut stdout = std::io::BufWriter::new(stdout_locked);
 
+        let log_format = *self.log_format.get().unwrap();
+
+        // Normally only the logger thread flushes, but a panicking thread (including the logger
+        // thread itself) may also flush while the host logs are locked. Rather than risk a
+        // deadlock, records are then only written to stdout.
+        let mut host_logs = match self.host_logs.try_lock() {
+            Ok(x) => Some(x),
+            Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        };
+
         while toflush > 0 {
             let record = match self.records.pop() {
                 Some(r) => r,
@@ -202,7 +227,20 @@ impl ShadowLogger {
             };
             toflush -= 1;
 
-            write!(stdout, "{record}")?;
+            record.write_to(&mut stdout, log_format)?;
+
+            // Records logged outside of a host are only written to stdout.
+            let path = record.host_info.as_ref().and_then(|x| x.log_path.as_ref());
+            if let (Some(host_logs), Some(path)) = (host_logs.as_mut(), path) {
+                if !host_logs.contains_key(path) {
+                    let file = std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)?;
+                    host_logs.insert(path.clone(), BufWriter::new(file));
+                }
+                record.write_to(host_logs.get_mut(path).unwrap(), log_format)?;
+            }
 
             if record.level <= Level::Error && *self.report_errors_to_stderr.get().unwrap() {
                 // *also* summarize on stderr.
@@ -229,6 +267,12 @@ impl ShadowLogger {
         stdout.flush()?;
         drop(stdout);
 
+        if let Some(mut host_logs) = host_logs {
+            for host_log in host_logs.values_mut() {
+                host_log.flush()?;
+            }
+        }
+
         if let Some(done_sender) = done_sender {
             // We can't log from this thread without risking deadlock, so in the
             // unlikely case that the calling thread has gone away, just print
@@ -266,6 +310,14 @@ impl ShadowLogger {
         self.max_log_level.set(level).unwrap()
     }
 
+    /// Set the format of the records written to stdout.
+    ///
+    /// Is only intended to be called from `init()`. Will panic if called more
+    /// than once.
+    fn set_log_format(&self, log_format: LogFormat) {
+        self.log_format.set(log_format).unwrap()
+    }
+
     /// Set whether to report errors to stderr in addition to logging on stdout.
     ///
     /// Is only intended to be called from `init()`. Will panic if called more
@@ -337,6 +389,7 @@ impl Log for ShadowLogger {
 
         let mut shadowrecord = ShadowLogRecord {
             level: record.level(),
+            target: record.target().to_string(),
             file: record.file_static(),
             module_path: record.module_path_static(),
             line: record.line(),
@@ -353,6 +406,8 @@ impl Log for ShadowLogger {
                 .try_with(|id| *id)
                 .unwrap_or_else(|_| nix::unistd::gettid()),
             host_info,
+            sim_process_id: Worker::active_process_id().map(Into::into),
+            sim_thread_id: Worker::active_thread_id().map(Into::into),
         };
 
         loop {
@@ -387,6 +442,7 @@ impl Log for ShadowLogger {
 
 struct ShadowLogRecord {
     level: Level,
+    target: String,
     file: Option<&'static str>,
     module_path: Option<&'static str>,
     line: Option<u32>,
@@ -397,6 +453,67 @@ struct ShadowLogRecord {
     thread_name: String,
     thread_id: nix::unistd::Pid,
     host_info: Option<Arc<HostInfo>>,
+    sim_process_id: Option<libc::pid_t>,
+    sim_thread_id: Option<libc::pid_t>,
+}
+
+impl ShadowLogRecord {
+    /// Write the record as a line in the given format.
+    fn write_to(&self, writer: &mut impl Write, log_format: LogFormat) -> std::io::Result<()> {
+        match log_format {
+            LogFormat::Text => write!(writer, "{self}"),
+            LogFormat::Json => {
+                serde_json::to_writer(&mut *writer, &self.to_json())?;
+                writeln!(writer)
+            }
+        }
+    }
+
+    /// The record in the form that's written when using the JSON log format.
+    fn to_json(&self) -> JsonLogRecord {
+        JsonLogRecord {
+            sim_time_ns: self.emu_time.map(|x| {
+                let sim_time = x.duration_since(&EmulatedTime::SIMULATION_START);
+                u64::try_from(sim_time.as_nanos()).unwrap()
+            }),
+            real_time_us: u64::try_from(self.wall_time.as_micros()).unwrap(),
+            level: self.level.as_str(),
+            host: self.host_info.as_ref().map(|x| x.name.as_str()),
+            host_ip: self.host_info.as_ref().map(|x| x.default_ip),
+            process: self.sim_process_id,
+            thread: self.sim_thread_id,
+            worker_thread_id: self.thread_id.as_raw(),
+            worker_thread_name: &self.thread_name,
+            target: &self.target,
+            file: self.file,
+            line: self.line,
+            message: &self.message,
+        }
+    }
+}
+
+/// A log record written as a single line of JSON. The field names are part of Shadow's output
+/// format, so shouldn't be changed.
+#[derive(Serialize)]
+struct JsonLogRecord<'a> {
+    /// Simulated time since the start of the simulation, in nanoseconds.
+    sim_time_ns: Option<u64>,
+    /// Real time since Shadow started, in microseconds.
+    real_time_us: u64,
+    level: &'static str,
+    host: Option<&'a str>,
+    host_ip: Option<std::net::Ipv4Addr>,
+    /// The simulated process ID.
+    process: Option<libc::pid_t>,
+    /// The simulated thread ID.
+    thread: Option<libc::pid_t>,
+    /// The native ID of the Shadow thread that logged the record.
+    worker_thread_id: libc::pid_t,
+    worker_thread_name: &'a str,
+    target: &'a str,
+    file: Option<&'static str>,
+    line: Option<u32>,
+    message: &'a str,
 }
 
 impl std::fmt::Display for ShadowLogRecord {
@@ -321,6 +321,7 @@ impl<'a> Manager<'a> {
                     .collect(),
                 bootstrap_end_time,
                 sim_end_time: self.end_time,
+                use_strict_unreachable: self.config.experimental.use_strict_unreachable.unwrap(),
             });
 
         // scope used so that the scheduler is dropped before we log the global counters below
@@ -591,7 +592,9 @@ impl<'a> Manager<'a> {
                     .log_level
                     .map(|x| x.to_c_loglevel())
                     .unwrap_or(c::_LogLevel_LOGLEVEL_UNSET),
+                log_per_host: self.config.general.log_per_host.unwrap(),
                 pcap_config: host_info.pcap_config,
+                tcp_congestion_control: host_info.tcp_congestion_control,
                 qdisc: host_info.qdisc,
                 init_sock_recv_buf_size: host_info.recv_buf_size,
                 autotune_recv_buf: host_info.autotune_recv_buf,
@@ -602,14 +605,32 @@ impl<'a> Manager<'a> {
                 max_unapplied_cpu_latency: self.config.max_unapplied_cpu_latency(),
                 unblocked_syscall_latency: self.config.unblocked_syscall_latency(),
                 unblocked_vdso_latency: self.config.unblocked_vdso_latency(),
+                cpu_migration_cost: self.config.cpu_migration_cost(),
+                entropy_pool_init_time: self.config.entropy_pool_init_time(),
                 strace_logging_options: self.config.strace_logging_mode(),
                 shim_log_level: host_info
                     .log_level
                     .unwrap_or_else(|| self.config.general.log_level.unwrap())
                     .to_c_loglevel(),
                 use_new_tcp: self.config.experimental.use_new_tcp.unwrap(),
+                ignore_unsupported_sockopts: self
+                    .config
+                    .experimental
+                    .ignore_unsupported_sockopts
+                    .unwrap(),
+                ephemeral_port_range: self.config.experimental.ephemeral_port_range.unwrap(),
+                use_ecn: host_info.use_ecn,
+                ecn_mark_threshold: host_info.ecn_mark_threshold,
+                router_aqm: host_info.router_aqm,
+                router_queue_size: host_info.router_queue_size,
+                tcp_initial_cwnd: host_info.tcp_initial_cwnd,
                 use_mem_mapper: self.config.experimental.use_memory_manager.unwrap(),
                 use_syscall_counters: self.config.experimental.use_syscall_counters.unwrap(),
+                use_deterministic_aslr: self.config.experimental.use_deterministic_aslr.unwrap(),
+                read_only_mounts: host_info.read_only_mounts.clone(),
+                tmpfs_mounts: host_info.tmpfs_mounts.clone(),
+                block_devices: host_info.block_devices.clone(),
+                preloaded_files: host_info.preloaded_files.clone(),
             };
 
             Box::new(unsafe {
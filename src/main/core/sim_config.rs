@@ -7,7 +7,7 @@ use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::{OsStr, OsString};
 use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Component, PathBuf};
 use std::sync::RwLock;
 use std::time::Duration;
 
@@ -18,10 +18,12 @@ use rand_xoshiro::Xoshiro256PlusPlus;
 use shadow_shim_helper_rs::simulation_time::SimulationTime;
 
 use crate::core::configuration::{
-    parse_string_as_args, ConfigOptions, EnvName, Flatten, HostOptions, LogInfoFlag, LogLevel,
-    ProcessArgs, ProcessFinalState, ProcessOptions, QDiscMode,
+    parse_string_as_args, BlockDeviceOptions, ConfigOptions, EnvName, Flatten, HostOptions,
+    LogInfoFlag, LogLevel, PreloadedFileSource, ProcessArgs, ProcessFinalState, ProcessOptions,
+    QDiscMode, RouterAqm, TcpCongestionControl,
 };
 use crate::network::graph::{load_network_graph, IpAssignment, NetworkGraph, RoutingInfo};
+use crate::network::router::AqmConfig;
 use crate::utility::units::{self, Unit};
 use crate::utility::{tilde_expansion, verify_plugin_path};
 
@@ -175,9 +177,14 @@ pub struct HostInfo {
     pub cpu_precision: Option<SimulationTime>,
     pub bandwidth_down_bits: Option<u64>,
     pub bandwidth_up_bits: Option<u64>,
+    pub read_only_mounts: BTreeMap<PathBuf, PathBuf>,
+    pub tmpfs_mounts: BTreeMap<PathBuf, u64>,
+    pub block_devices: BTreeMap<PathBuf, BlockDeviceConfig>,
+    pub preloaded_files: BTreeMap<PathBuf, Vec<u8>>,
     pub ip_addr: Option<std::net::IpAddr>,
     pub log_level: Option<LogLevel>,
     pub pcap_config: Option<PcapConfig>,
+    pub tcp_congestion_control: TcpCongestionControl,
     pub heartbeat_log_level: Option<LogLevel>,
     pub heartbeat_log_info: HashSet<LogInfoFlag>,
     pub heartbeat_interval: Option<SimulationTime>,
@@ -186,6 +193,11 @@ pub struct HostInfo {
     pub autotune_send_buf: bool,
     pub autotune_recv_buf: bool,
     pub qdisc: QDiscMode,
+    pub use_ecn: bool,
+    pub ecn_mark_threshold: Option<u64>,
+    pub router_aqm: AqmConfig,
+    pub router_queue_size: Option<u64>,
+    pub tcp_initial_cwnd: u32,
 }
 
 #[derive(Clone)]
@@ -210,6 +222,12 @@ pub struct PcapConfig {
     pub capture_size: u64,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct BlockDeviceConfig {
+    pub size: u64,
+    pub sector_size: u32,
+}
+
 /// For a host entry in the configuration options, build `HostInfo` object.
 fn build_host(
     config: &ConfigOptions,
@@ -238,6 +256,9 @@ fn build_host(
         })
         .collect::<anyhow::Result<_>>()?;
 
+    let tmpfs_mounts = build_tmpfs_mounts(host)?;
+    let preloaded_files = build_preloaded_files(host, &tmpfs_mounts)?;
+
     Ok(HostInfo {
         name: hostname,
         processes,
@@ -253,8 +274,12 @@ fn build_host(
             .bandwidth_down
             .map(|x| x.convert(units::SiPrefixUpper::Base).unwrap().value()),
         bandwidth_up_bits: host
-            .bandwidth_down
+            .bandwidth_up
             .map(|x| x.convert(units::SiPrefixUpper::Base).unwrap().value()),
+        read_only_mounts: build_read_only_mounts(host)?,
+        tmpfs_mounts,
+        block_devices: build_block_devices(host)?,
+        preloaded_files,
 
         ip_addr: host.ip_addr.map(|x| x.into()),
         log_level: host.host_options.log_level.flatten(),
@@ -271,6 +296,7 @@ fn build_host(
                     .unwrap()
                     .value(),
             }),
+        tcp_congestion_control: host.host_options.tcp_congestion_control.unwrap(),
 
         // some options come from the config options and not the host options
         heartbeat_log_level: config.experimental.host_heartbeat_log_level,
@@ -301,9 +327,289 @@ fn build_host(
         autotune_send_buf: config.experimental.socket_send_autotune.unwrap(),
         autotune_recv_buf: config.experimental.socket_recv_autotune.unwrap(),
         qdisc: config.experimental.interface_qdisc.unwrap(),
+        use_ecn: config.experimental.use_ecn.unwrap(),
+        ecn_mark_threshold: config
+            .experimental
+            .ecn_mark_threshold
+            .flatten()
+            .map(|x| x.convert(units::SiPrefixUpper::Base).unwrap().value()),
+        router_aqm: build_router_aqm(config)?,
+        router_queue_size: build_router_queue_size(config)?,
+        tcp_initial_cwnd: build_tcp_initial_cwnd(config)?,
     })
 }
 
+/// Resolve the real paths of a host's read-only mounts.
+fn build_read_only_mounts(host: &HostOptions) -> anyhow::Result<BTreeMap<PathBuf, PathBuf>> {
+    host.read_only_mounts
+        .iter()
+        .map(|(target, source)| {
+            // a mount at "/" would hide every other file
+            if !target.is_absolute() || target.parent().is_none() {
+                return Err(anyhow::anyhow!(
+                    "The read-only mount path '{}' must be an absolute path other than '/'",
+                    target.display(),
+                ));
+            }
+
+            let expanded = tilde_expansion(source.to_str().unwrap());
+            let source = std::fs::canonicalize(&expanded).with_context(|| {
+                format!(
+                    "Failed to resolve the source '{}' of read-only mount '{}'",
+                    expanded.display(),
+                    target.display(),
+                )
+            })?;
+
+            Ok((target.clone(), source))
+        })
+        .collect()
+}
+
+/// Check a host's tmpfs mounts and resolve their sizes in bytes.
+fn build_tmpfs_mounts(host: &HostOptions) -> anyhow::Result<BTreeMap<PathBuf, u64>> {
+    host.tmpfs_mounts
+        .iter()
+        .map(|(target, size)| {
+            // a mount at "/" would hide every other file
+            if !target.is_absolute() || target.parent().is_none() {
+                return Err(anyhow::anyhow!(
+                    "The tmpfs mount path '{}' must be an absolute path other than '/'",
+                    target.display(),
+                ));
+            }
+
+            // files are looked up in a single mount, so mounts can't be nested
+            let nested = host
+                .read_only_mounts
+                .keys()
+                .chain(host.tmpfs_mounts.keys())
+                .filter(|x| *x != target)
+                .find(|x| x.starts_with(target) || target.starts_with(x));
+            if let Some(other) = nested {
+                return Err(anyhow::anyhow!(
+                    "The tmpfs mount '{}' can't be nested with the mount '{}'",
+                    target.display(),
+                    other.display(),
+                ));
+            }
+
+            let size = size.convert(units::SiPrefixUpper::Base).unwrap().value();
+            if size == 0 {
+                return Err(anyhow::anyhow!(
+                    "The size of tmpfs mount '{}' must be non-zero",
+                    target.display(),
+                ));
+            }
+
+            Ok((target.clone(), size))
+        })
+        .collect()
+}
+
+/// Check a host's block devices and resolve their sizes in bytes.
+fn build_block_devices(host: &HostOptions) -> anyhow::Result<BTreeMap<PathBuf, BlockDeviceConfig>> {
+    host.block_devices
+        .iter()
+        .map(|(target, BlockDeviceOptions { size, sector_size })| {
+            if !target.is_absolute() || target.parent().is_none() {
+                return Err(anyhow::anyhow!(
+                    "The block device path '{}' must be an absolute path other than '/'",
+                    target.display(),
+                ));
+            }
+
+            // paths are looked up in a single mount or device
+            let nested = host
+                .read_only_mounts
+                .keys()
+                .chain(host.tmpfs_mounts.keys())
+                .chain(host.block_devices.keys())
+                .filter(|x| *x != target)
+                .find(|x| x.starts_with(target) || target.starts_with(x));
+            if let Some(other) = nested {
+                return Err(anyhow::anyhow!(
+                    "The block device '{}' can't be nested with the mount or device '{}'",
+                    target.display(),
+                    other.display(),
+                ));
+            }
+
+            if ![512, 1024, 2048, 4096].contains(sector_size) {
+                return Err(anyhow::anyhow!(
+                    "The sector size of block device '{}' must be 512, 1024, 2048, or 4096 bytes",
+                    target.display(),
+                ));
+            }
+
+            let size = size.convert(units::SiPrefixUpper::Base).unwrap().value();
+            if size == 0 || size % u64::from(*sector_size) != 0 {
+                return Err(anyhow::anyhow!(
+                    "The size of block device '{}' must be a non-zero multiple of its sector size",
+                    target.display(),
+                ));
+            }
+
+            Ok((
+                target.clone(),
+                BlockDeviceConfig {
+                    size,
+                    sector_size: *sector_size,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Check the paths and sizes of a host's preloaded files and read their contents.
+fn build_preloaded_files(
+    host: &HostOptions,
+    tmpfs_mounts: &BTreeMap<PathBuf, u64>,
+) -> anyhow::Result<BTreeMap<PathBuf, Vec<u8>>> {
+    let files: BTreeMap<PathBuf, Vec<u8>> = host
+        .preloaded_files
+        .iter()
+        .map(|(target, source)| {
+            // the files are created in the host's data directory (the processes' working
+            // directory) or in a tmpfs mount, and a ".." component could leave them
+            let in_tmpfs_mount = tmpfs_mounts
+                .keys()
+                .any(|x| target.starts_with(x) && target != x);
+            if target.file_name().is_none()
+                || target.components().any(|x| x == Component::ParentDir)
+                || (target.is_absolute() && !in_tmpfs_mount)
+            {
+                return Err(anyhow::anyhow!(
+                    "The preloaded file path '{}' must be a relative path or a path within one of \
+                     the host's tmpfs mounts, and must not contain '..'",
+                    target.display(),
+                ));
+            }
+
+            let contents = match source {
+                PreloadedFileSource::File(path) => {
+                    let expanded = tilde_expansion(path);
+                    std::fs::read(&expanded).with_context(|| {
+                        format!(
+                            "Failed to read the source '{}' of preloaded file '{}'",
+                            expanded.display(),
+                            target.display(),
+                        )
+                    })?
+                }
+                PreloadedFileSource::Inline(x) => x.as_bytes().to_vec(),
+            };
+
+            Ok((target.clone(), contents))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    // the preloaded files count towards the size of the tmpfs mount they're in
+    for (mount, size) in tmpfs_mounts {
+        let used: u64 = files
+            .iter()
+            .filter(|(target, _)| target.starts_with(mount))
+            .map(|(_, contents)| u64::try_from(contents.len()).unwrap())
+            .sum();
+        if used > *size {
+            return Err(anyhow::anyhow!(
+                "The preloaded files in tmpfs mount '{}' use {used} bytes, which exceeds the \
+                 mount's size of {size} bytes",
+                mount.display(),
+            ));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Build the configuration of the hosts' inbound router queues.
+fn build_router_aqm(config: &ConfigOptions) -> anyhow::Result<AqmConfig> {
+    let exp = &config.experimental;
+
+    match exp.router_aqm.unwrap() {
+        RouterAqm::None => Ok(AqmConfig::None),
+        RouterAqm::Codel => {
+            let target =
+                SimulationTime::try_from(Duration::from(exp.router_codel_target.unwrap())).unwrap();
+            let interval =
+                SimulationTime::try_from(Duration::from(exp.router_codel_interval.unwrap()))
+                    .unwrap();
+
+            if target.is_zero() || interval.is_zero() {
+                return Err(anyhow::anyhow!(
+                    "The CoDel target '{}' and interval '{}' must be non-zero",
+                    exp.router_codel_target.unwrap(),
+                    exp.router_codel_interval.unwrap(),
+                ));
+            }
+
+            Ok(AqmConfig::CoDel { target, interval })
+        }
+        RouterAqm::Red => {
+            let bytes = |x: units::Bytes<units::SiPrefixUpper>| {
+                usize::try_from(x.convert(units::SiPrefixUpper::Base).unwrap().value()).unwrap()
+            };
+            let min_threshold = bytes(exp.router_red_min_threshold.unwrap());
+            let max_threshold = bytes(exp.router_red_max_threshold.unwrap());
+            let max_probability = exp.router_red_max_probability.unwrap();
+
+            if min_threshold >= max_threshold {
+                return Err(anyhow::anyhow!(
+                    "The RED minimum threshold '{}' must be smaller than the maximum threshold '{}'",
+                    exp.router_red_min_threshold.unwrap(),
+                    exp.router_red_max_threshold.unwrap(),
+                ));
+            }
+
+            if !(max_probability > 0.0 && max_probability <= 1.0) {
+                return Err(anyhow::anyhow!(
+                    "The RED maximum probability '{max_probability}' must be within (0, 1]",
+                ));
+            }
+
+            Ok(AqmConfig::Red {
+                min_threshold,
+                max_threshold,
+                max_probability,
+            })
+        }
+    }
+}
+
+/// Get the capacity in bytes of the hosts' inbound router queues, if any.
+fn build_router_queue_size(config: &ConfigOptions) -> anyhow::Result<Option<u64>> {
+    let Some(size) = config.experimental.router_queue_size.flatten() else {
+        return Ok(None);
+    };
+
+    let bytes = size.convert(units::SiPrefixUpper::Base).unwrap().value();
+
+    // a smaller queue could never hold a full-sized packet
+    let mtu = u64::from(crate::cshadow::CONFIG_MTU);
+    if bytes < mtu {
+        return Err(anyhow::anyhow!(
+            "The router queue size '{size}' must be at least the MTU ({mtu} bytes)",
+        ));
+    }
+
+    Ok(Some(bytes))
+}
+
+/// Get the congestion window in segments of new TCP connections.
+fn build_tcp_initial_cwnd(config: &ConfigOptions) -> anyhow::Result<u32> {
+    let cwnd = config.experimental.tcp_initial_cwnd.unwrap();
+
+    // a connection with an empty window could never send any data
+    if cwnd == 0 {
+        return Err(anyhow::anyhow!(
+            "The initial TCP congestion window must be at least 1 segment"
+        ));
+    }
+
+    Ok(cwnd)
+}
+
 /// For a process entry in the configuration options, build a `ProcessInfo` object.
 fn build_process(proc: &ProcessOptions, config: &ConfigOptions) -> anyhow::Result<ProcessInfo> {
     let start_time = Duration::from(proc.start_time).try_into().unwrap();
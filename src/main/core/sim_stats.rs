@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::sync::Mutex;
 
 use anyhow::Context;
@@ -6,12 +7,31 @@ use serde::Serialize;
 
 use crate::utility::counter::Counter;
 
+/// Syscall counts for each process, keyed by host name and then by process name.
+pub type ProcessSyscallCounts = BTreeMap<String, BTreeMap<String, Counter>>;
+
+/// Add the syscall counts of a single process to `counts`.
+pub fn add_process_syscall_counts(
+    counts: &mut ProcessSyscallCounts,
+    host_name: &str,
+    process_name: &str,
+    syscall_counts: &Counter,
+) {
+    counts
+        .entry(host_name.to_string())
+        .or_default()
+        .entry(process_name.to_string())
+        .or_default()
+        .add_counter(syscall_counts);
+}
+
 /// Simulation statistics to be accessed by a single thread.
 #[derive(Debug)]
 pub struct LocalSimStats {
     pub alloc_counts: RefCell<Counter>,
     pub dealloc_counts: RefCell<Counter>,
     pub syscall_counts: RefCell<Counter>,
+    pub process_syscall_counts: RefCell<ProcessSyscallCounts>,
 }
 
 impl LocalSimStats {
@@ -20,6 +40,7 @@ impl LocalSimStats {
             alloc_counts: RefCell::new(Counter::new()),
             dealloc_counts: RefCell::new(Counter::new()),
             syscall_counts: RefCell::new(Counter::new()),
+            process_syscall_counts: RefCell::new(ProcessSyscallCounts::new()),
         }
     }
 }
@@ -36,6 +57,7 @@ pub struct SharedSimStats {
     pub alloc_counts: Mutex<Counter>,
     pub dealloc_counts: Mutex<Counter>,
     pub syscall_counts: Mutex<Counter>,
+    pub process_syscall_counts: Mutex<ProcessSyscallCounts>,
 }
 
 impl SharedSimStats {
@@ -44,6 +66,7 @@ impl SharedSimStats {
             alloc_counts: Mutex::new(Counter::new()),
             dealloc_counts: Mutex::new(Counter::new()),
             syscall_counts: Mutex::new(Counter::new()),
+            process_syscall_counts: Mutex::new(ProcessSyscallCounts::new()),
         }
     }
 
@@ -64,6 +87,20 @@ impl SharedSimStats {
         *local_alloc_counts = Counter::new();
         *local_dealloc_counts = Counter::new();
         *local_syscall_counts = Counter::new();
+
+        let mut shared_process_syscall_counts = self.process_syscall_counts.lock().unwrap();
+        let local_process_syscall_counts =
+            std::mem::take(&mut *local.process_syscall_counts.borrow_mut());
+        for (host_name, processes) in local_process_syscall_counts {
+            for (process_name, syscall_counts) in processes {
+                add_process_syscall_counts(
+                    &mut shared_process_syscall_counts,
+                    &host_name,
+                    &process_name,
+                    &syscall_counts,
+                );
+            }
+        }
     }
 }
 
@@ -125,3 +162,24 @@ pub fn write_stats_to_file(
 
     Ok(())
 }
+
+/// Writes the syscall counts of each process as a JSON object that maps host names to process
+/// names to syscall names to counts. May reset fields of `stats`.
+pub fn write_process_syscall_counts_to_file(
+    filename: &std::path::Path,
+    stats: &SharedSimStats,
+) -> anyhow::Result<()> {
+    let counts = std::mem::take(&mut *stats.process_syscall_counts.lock().unwrap());
+
+    let file = std::fs::File::create(filename)
+        .with_context(|| format!("Failed to create file '{}'", filename.display()))?;
+
+    serde_json::to_writer_pretty(file, &counts).with_context(|| {
+        format!(
+            "Failed to write syscall counts json to file '{}'",
+            filename.display()
+        )
+    })?;
+
+    Ok(())
+}
@@ -18,7 +18,7 @@ use super::work::event_queue::EventQueue;
 use crate::core::controller::ShadowStatusBarState;
 use crate::core::runahead::Runahead;
 use crate::core::sim_config::Bandwidth;
-use crate::core::sim_stats::{LocalSimStats, SharedSimStats};
+use crate::core::sim_stats::{self, LocalSimStats, SharedSimStats};
 use crate::core::work::event::Event;
 use crate::cshadow;
 use crate::host::host::Host;
@@ -32,6 +32,9 @@ use crate::utility::status_bar;
 
 static USE_OBJECT_COUNTERS: AtomicBool = AtomicBool::new(false);
 
+// Whether syscall counts should also be collected for each process.
+static USE_PROCESS_SYSCALL_COUNTERS: AtomicBool = AtomicBool::new(false);
+
 // global counters to be used when there is no worker active
 static SIM_STATS: Lazy<SharedSimStats> = Lazy::new(SharedSimStats::new);
 
@@ -69,6 +72,11 @@ pub struct Worker {
     active_process: RefCell<Option<RootedRc<RootedRefCell<Process>>>>,
     active_thread: RefCell<Option<RootedRc<RootedRefCell<Thread>>>>,
 
+    // The IDs of the active Process and Thread. These are cached so that they can be looked up
+    // without borrowing the Process or Thread, which may already be mutably borrowed.
+    active_process_id: Cell<Option<ProcessId>>,
+    active_thread_id: Cell<Option<ThreadId>>,
+
     clock: RefCell<Clock>,
 
     // This value is not the minimum latency of the simulation, but just a saved copy of this
@@ -91,6 +99,8 @@ impl Worker {
                 active_host: RefCell::new(None),
                 active_process: RefCell::new(None),
                 active_thread: RefCell::new(None),
+                active_process_id: Cell::new(None),
+                active_thread_id: Cell::new(None),
                 clock: RefCell::new(Clock {
                     now: None,
                     barrier: None,
@@ -195,7 +205,10 @@ impl Worker {
     /// Set the currently-active Process.
     pub fn set_active_process(process: &RootedRc<RootedRefCell<Process>>) {
         Worker::with(|w| {
-            let process = process.clone(w.active_host.borrow().as_ref().unwrap().root());
+            let host = w.active_host.borrow();
+            let root = host.as_ref().unwrap().root();
+            w.active_process_id.set(Some(process.borrow(root).id()));
+            let process = process.clone(root);
             let old = w.active_process.borrow_mut().replace(process);
             debug_assert!(old.is_none());
         })
@@ -206,6 +219,7 @@ impl Worker {
     pub fn clear_active_process() {
         Worker::with(|w| {
             let old = w.active_process.borrow_mut().take().unwrap();
+            w.active_process_id.set(None);
             let host = w.active_host.borrow();
             let host = host.as_ref().unwrap();
             old.explicit_drop_recursive(host.root(), host);
@@ -216,7 +230,10 @@ impl Worker {
     /// Set the currently-active Thread.
     pub fn set_active_thread(thread: &RootedRc<RootedRefCell<Thread>>) {
         Worker::with(|w| {
-            let thread = thread.clone(w.active_host.borrow().as_ref().unwrap().root());
+            let host = w.active_host.borrow();
+            let root = host.as_ref().unwrap().root();
+            w.active_thread_id.set(Some(thread.borrow(root).id()));
+            let thread = thread.clone(root);
             let old = w.active_thread.borrow_mut().replace(thread);
             debug_assert!(old.is_none());
         })
@@ -229,6 +246,7 @@ impl Worker {
             let host = w.active_host.borrow();
             let host = host.as_ref().unwrap();
             let old = w.active_thread.borrow_mut().take().unwrap();
+            w.active_thread_id.set(None);
             old.explicit_drop_recursive(host.root(), host);
         })
         .unwrap()
@@ -248,12 +266,16 @@ impl Worker {
         Worker::with_active_process(|p| p.native_pid())
     }
 
+    /// ID of the current Process, if any. Unlike [`Worker::with_active_process`], this doesn't
+    /// borrow the Process.
     pub fn active_process_id() -> Option<ProcessId> {
-        Worker::with_active_process(|p| p.id())
+        Worker::with(|w| w.active_process_id.get()).flatten()
     }
 
+    /// ID of the current Thread, if any. Unlike [`Worker::with_active_thread`], this doesn't borrow
+    /// the Thread.
     pub fn active_thread_id() -> Option<ThreadId> {
-        Worker::with_active_thread(|thread| thread.id())
+        Worker::with(|w| w.active_thread_id.get()).flatten()
     }
 
     pub fn active_thread_native_tid() -> Option<Pid> {
@@ -486,6 +508,38 @@ impl Worker {
         });
     }
 
+    /// Add the syscall counts of a thread to the counts of its process. Does nothing unless
+    /// [`enable_process_syscall_counters`] was called.
+    pub fn add_process_syscall_counts(
+        host_name: &str,
+        process_name: &str,
+        syscall_counts: &Counter,
+    ) {
+        if !USE_PROCESS_SYSCALL_COUNTERS.load(std::sync::atomic::Ordering::Relaxed) {
+            return;
+        }
+
+        Worker::with(|w| {
+            sim_stats::add_process_syscall_counts(
+                &mut w.sim_stats.process_syscall_counts.borrow_mut(),
+                host_name,
+                process_name,
+                syscall_counts,
+            );
+        })
+        .unwrap_or_else(|| {
+            // no live worker; fall back to the shared counter
+            sim_stats::add_process_syscall_counts(
+                &mut SIM_STATS.process_syscall_counts.lock().unwrap(),
+                host_name,
+                process_name,
+                syscall_counts,
+            );
+
+            debug_panic!("Trying to add syscall counts when there is no worker");
+        });
+    }
+
     pub fn add_to_global_sim_stats() {
         Worker::with(|w| SIM_STATS.add_from_local_stats(&w.sim_stats)).unwrap()
     }
@@ -494,6 +548,12 @@ impl Worker {
         Worker::with(|w| w.shared.is_routable(src, dst)).unwrap()
     }
 
+    /// Should connecting or sending to an address that doesn't belong to any simulated host fail
+    /// with ENETUNREACH?
+    pub fn use_strict_unreachable() -> bool {
+        Worker::with(|w| w.shared.use_strict_unreachable).unwrap()
+    }
+
     pub fn increment_plugin_error_count() {
         Worker::with(|w| w.shared.increment_plugin_error_count()).unwrap()
     }
@@ -536,6 +596,8 @@ pub struct WorkerShared {
     pub event_queues: HashMap<HostId, Arc<Mutex<EventQueue>>>,
     pub bootstrap_end_time: EmulatedTime,
     pub sim_end_time: EmulatedTime,
+    /// Connecting or sending to an address outside of the simulation fails with ENETUNREACH.
+    pub use_strict_unreachable: bool,
 }
 
 impl WorkerShared {
@@ -665,6 +727,10 @@ pub fn enable_object_counters() {
     USE_OBJECT_COUNTERS.store(true, std::sync::atomic::Ordering::Relaxed);
 }
 
+pub fn enable_process_syscall_counters() {
+    USE_PROCESS_SYSCALL_COUNTERS.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
 pub fn with_global_sim_stats<T>(f: impl FnOnce(&SharedSimStats) -> T) -> T {
     f(&SIM_STATS)
 }
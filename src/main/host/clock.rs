@@ -0,0 +1,109 @@
+//! The host's clock frequency adjustment, as set by `adjtimex(2)` and `clock_adjtime(2)`.
+
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
+
+/// The kernel's `MAXFREQ_SCALED` limit of 500 ppm, in the scaled ppm units of `timex::freq`.
+const MAX_FREQ: i64 = 500 << 16;
+
+/// One second in the scaled ppm units of `timex::freq`, which have a 16-bit fractional part.
+const FREQ_SCALE: i128 = 1_000_000 << 16;
+
+/// The frequency adjustment of the host's clock.
+///
+/// Shadow's simulated time is the host's disciplined clock, which `CLOCK_MONOTONIC` and
+/// `CLOCK_REALTIME` report. When the clock is slewed, we model the host's oscillator as running at
+/// a correspondingly different rate, so that `CLOCK_MONOTONIC_RAW` (which measures the oscillator
+/// directly) diverges from the other clocks like it does on Linux.
+#[derive(Debug)]
+pub struct ClockAdjustment {
+    /// The frequency offset, in ppm with a 16-bit fractional part.
+    freq: i64,
+    /// The simulated time at which the frequency offset was last changed.
+    base_time: EmulatedTime,
+    /// The raw clock's time at `base_time`.
+    base_raw_time: EmulatedTime,
+}
+
+impl ClockAdjustment {
+    pub fn new() -> Self {
+        Self {
+            freq: 0,
+            base_time: EmulatedTime::UNIX_EPOCH,
+            base_raw_time: EmulatedTime::UNIX_EPOCH,
+        }
+    }
+
+    /// The frequency offset, in ppm with a 16-bit fractional part.
+    pub fn freq(&self) -> i64 {
+        self.freq
+    }
+
+    /// Change the frequency offset at the simulated time `now`. Like Linux, the offset is clamped
+    /// to +/- 500 ppm.
+    pub fn set_freq(&mut self, now: EmulatedTime, freq: i64) {
+        self.base_raw_time = self.raw_time(now);
+        self.base_time = now;
+        self.freq = freq.clamp(-MAX_FREQ, MAX_FREQ);
+    }
+
+    /// The time of the host's unadjusted clock at the simulated time `now`, which must not be
+    /// earlier than the last frequency change.
+    pub fn raw_time(&self, now: EmulatedTime) -> EmulatedTime {
+        let elapsed = i128::try_from(now.duration_since(&self.base_time).as_nanos()).unwrap();
+
+        // The adjusted clock runs `1 + freq` times as fast as the oscillator.
+        let raw_elapsed = elapsed * FREQ_SCALE / (FREQ_SCALE + i128::from(self.freq));
+        let raw_elapsed = SimulationTime::from_nanos(raw_elapsed.try_into().unwrap());
+
+        self.base_raw_time + raw_elapsed
+    }
+}
+
+impl Default for ClockAdjustment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unadjusted() {
+        let clock = ClockAdjustment::new();
+        let now = EmulatedTime::SIMULATION_START + SimulationTime::from_secs(10);
+        assert_eq!(clock.raw_time(now), now);
+    }
+
+    #[test]
+    fn slewed() {
+        let mut clock = ClockAdjustment::new();
+        let start = EmulatedTime::SIMULATION_START;
+
+        // 100 ppm faster than the oscillator
+        clock.set_freq(start, 100 << 16);
+        assert_eq!(clock.raw_time(start), start);
+
+        let now = start + SimulationTime::from_nanos(1_000_100_000);
+        assert_eq!(clock.raw_time(now), start + SimulationTime::from_secs(1));
+
+        // the raw time continues from where it was when the frequency changes
+        clock.set_freq(now, 0);
+        assert_eq!(clock.raw_time(now), start + SimulationTime::from_secs(1));
+        assert_eq!(
+            clock.raw_time(now + SimulationTime::from_secs(1)),
+            start + SimulationTime::from_secs(2)
+        );
+    }
+
+    #[test]
+    fn clamped() {
+        let mut clock = ClockAdjustment::new();
+        clock.set_freq(EmulatedTime::SIMULATION_START, 1000 << 16);
+        assert_eq!(clock.freq(), 500 << 16);
+        clock.set_freq(EmulatedTime::SIMULATION_START, -1000 << 16);
+        assert_eq!(clock.freq(), -500 << 16);
+    }
+}
@@ -189,6 +189,15 @@ static gchar* _legacyfile_statusToString(FileState ds) {
     if (ds & FileState_SOCKET_ALLOWING_CONNECT) {
         g_string_append_printf(string, "SOCKET_ALLOWING_CONNECT|");
     }
+    if (ds & FileState_PRIORITY) {
+        g_string_append_printf(string, "PRIORITY|");
+    }
+    if (ds & FileState_SOCKET_OUTPUT_ACKED) {
+        g_string_append_printf(string, "SOCKET_OUTPUT_ACKED|");
+    }
+    if (ds & FileState_RDHUP) {
+        g_string_append_printf(string, "RDHUP|");
+    }
     if (string->len == 0) {
         g_string_append_printf(string, "NONE|");
     }
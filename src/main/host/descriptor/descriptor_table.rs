@@ -1,5 +1,6 @@
 use std::collections::{BTreeSet, HashMap};
 
+use linux_api::fcntl::DescriptorFlags;
 use log::*;
 use shadow_shim_helper_rs::explicit_drop::ExplicitDrop;
 use shadow_shim_helper_rs::syscall_types::SyscallReg;
@@ -220,13 +221,37 @@ impl DescriptorTable {
         descriptors.into_iter()
     }
 
+    /// Remove and return all descriptors that have [`DescriptorFlags::FD_CLOEXEC`] set, as is done
+    /// when a process calls `execve`. The descriptors are returned in increasing fd order so that
+    /// they're closed in a deterministic order.
+    pub fn remove_cloexec(&mut self) -> impl Iterator<Item = (DescriptorHandle, Descriptor)> {
+        let mut fds: Vec<_> = self.iter_cloexec().map(|(fd, _)| *fd).collect();
+        fds.sort_unstable();
+
+        let mut descriptors = Vec::with_capacity(fds.len());
+        for fd in fds {
+            descriptors.push((fd, self.deregister_descriptor(fd).unwrap()));
+        }
+
+        descriptors.into_iter()
+    }
+
+    /// Iterate over all descriptors in the table. The iteration order is unspecified.
     pub fn iter(&self) -> impl Iterator<Item = (&DescriptorHandle, &Descriptor)> {
         self.descriptors.iter()
     }
 
+    /// Iterate over all descriptors in the table. The iteration order is unspecified.
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&DescriptorHandle, &mut Descriptor)> {
         self.descriptors.iter_mut()
     }
+
+    /// Iterate over all descriptors that have [`DescriptorFlags::FD_CLOEXEC`] set. The iteration
+    /// order is unspecified.
+    pub fn iter_cloexec(&self) -> impl Iterator<Item = (&DescriptorHandle, &Descriptor)> {
+        self.iter()
+            .filter(|(_, desc)| desc.flags().contains(DescriptorFlags::FD_CLOEXEC))
+    }
 }
 
 impl Default for DescriptorTable {
@@ -1,8 +1,18 @@
+use std::sync::Arc;
+
 use linux_api::epoll::EpollEvents;
 
+use super::exclusive::ExclusiveWakeup;
 use crate::host::descriptor::listener::StateListenHandle;
 use crate::host::descriptor::{FileSignals, FileState};
 
+/// The flags that change how events are reported, rather than selecting events. Like linux, this
+/// is all that remains of a one-shot entry's interest once it reports an event.
+const NON_EVENT_FLAGS: EpollEvents = EpollEvents::EPOLLONESHOT
+    .union(EpollEvents::EPOLLET)
+    .union(EpollEvents::EPOLLWAKEUP)
+    .union(EpollEvents::EPOLLEXCLUSIVE);
+
 /// Used to track the status of a file we are monitoring for events. Any complicated logic for
 /// deciding when a file has events that epoll should report should be specified in this object's
 /// implementation.
@@ -20,6 +30,15 @@ pub(super) struct Entry {
     /// The file state changes we have already reported since the state last changed. When a state
     /// changes, that event becomes uncollected until `collect_ready_events` is called.
     collected: FileState,
+    /// The wakeup state shared with other exclusive entries for the same file, if this entry was
+    /// added with `EPOLLEXCLUSIVE`.
+    exclusive: Option<Arc<ExclusiveWakeup>>,
+    /// Whether an exclusive entry was woken by (or added during) the current readiness of its
+    /// file. An exclusive entry only reports events while woken.
+    woken: bool,
+    /// The states that an exclusive entry claimed in its `exclusive` wakeup state, which are
+    /// released when the entry is removed.
+    claimed: FileState,
     /// TODO remove when legacy tcp is removed.
     is_legacy: bool,
 }
@@ -33,10 +52,26 @@ impl Entry {
             listener_handle: None,
             state,
             collected: FileState::empty(),
+            exclusive: None,
+            woken: false,
+            claimed: FileState::empty(),
             is_legacy: false,
         }
     }
 
+    /// Only report events when this entry wins the `exclusive` wakeup for its file. Like linux, an
+    /// entry whose file is already ready when it's added is woken regardless of other entries.
+    pub fn set_exclusive(&mut self, exclusive: Arc<ExclusiveWakeup>) {
+        self.woken = self
+            .state
+            .intersects(Self::state_from_events(self.interest));
+        self.exclusive = Some(exclusive);
+    }
+
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive.is_some()
+    }
+
     // TODO remove when legacy tcp is removed.
     pub fn set_legacy(&mut self) {
         self.is_legacy = true;
@@ -75,10 +110,40 @@ impl Entry {
         self.state = new_state;
         self.collected.remove(changed);
 
+        if let Some(exclusive) = &self.exclusive {
+            let interest = Self::state_from_events(self.interest);
+            let mut turned_on = changed.intersection(new_state).intersection(interest);
+            let turned_off = changed.difference(new_state).intersection(interest);
+
+            // Like linux, more data arriving at a readable file is a new wakeup, which can wake an
+            // entry if the one it last woke was removed.
+            if signals.contains(FileSignals::READ_BUFFER_GREW) {
+                turned_on.insert(
+                    new_state
+                        .intersection(interest)
+                        .intersection(FileState::READABLE),
+                );
+            }
+
+            exclusive.release(turned_off);
+            self.claimed.remove(turned_off);
+
+            if !new_state.intersects(interest) {
+                self.woken = false;
+            } else if !turned_on.is_empty() {
+                let claimed = exclusive.claim(turned_on);
+                self.claimed.insert(claimed);
+                self.woken |= !claimed.is_empty();
+            }
+        }
+
         // If the file is written again, let the epoll waiter collect the events again.
         if signals.contains(FileSignals::READ_BUFFER_GREW) {
-            // We only subscribe to `READ_BUFFER_GREW` signals for edge-triggered entries.
-            debug_assert!(self.interest.intersects(EpollEvents::EPOLLET));
+            // We only subscribe to `READ_BUFFER_GREW` signals for edge-triggered and exclusive
+            // entries.
+            debug_assert!(
+                self.interest.intersects(EpollEvents::EPOLLET) || self.exclusive.is_some()
+            );
 
             // Ignore the `READ_BUFFER_GREW` if the file isn't READABLE.
             if new_state.contains(FileState::READABLE) {
@@ -104,7 +169,7 @@ impl Entry {
     pub fn get_listener_signals(&self) -> FileSignals {
         let mut signals = FileSignals::empty();
 
-        if self.interest.intersects(EpollEvents::EPOLLET) {
+        if self.interest.intersects(EpollEvents::EPOLLET) || self.exclusive.is_some() {
             signals.insert(FileSignals::READ_BUFFER_GREW);
         }
 
@@ -116,6 +181,11 @@ impl Entry {
     }
 
     pub fn has_ready_events(&self) -> bool {
+        // An exclusive entry that wasn't woken isn't ready, even if its file is.
+        if self.exclusive.is_some() && !self.woken {
+            return false;
+        }
+
         // TODO remove this if block when legacy tcp is removed.
         if self.is_legacy {
             if self.state.contains(FileState::CLOSED) {
@@ -139,8 +209,10 @@ impl Entry {
 
         self.collected.insert(Self::state_from_events(events));
 
+        // A one-shot entry is disabled after reporting any event, including events that weren't
+        // ready, until it's rearmed with `modify()`. File state changes don't rearm it.
         if self.interest.contains(EpollEvents::EPOLLONESHOT) {
-            self.interest.remove(events)
+            self.interest = self.interest.intersection(NON_EVENT_FLAGS);
         }
 
         log::trace!(
@@ -176,6 +248,12 @@ impl Entry {
         if state.intersects(FileState::WRITABLE) {
             events.insert(EpollEvents::EPOLLOUT);
         }
+        if state.intersects(FileState::PRIORITY) {
+            events.insert(EpollEvents::EPOLLPRI);
+        }
+        if state.intersects(FileState::RDHUP) {
+            events.insert(EpollEvents::EPOLLRDHUP);
+        }
 
         events
     }
@@ -189,11 +267,26 @@ impl Entry {
         if events.intersects(EpollEvents::EPOLLOUT) {
             state.insert(FileState::WRITABLE)
         }
+        if events.intersects(EpollEvents::EPOLLPRI) {
+            state.insert(FileState::PRIORITY)
+        }
+        if events.intersects(EpollEvents::EPOLLRDHUP) {
+            state.insert(FileState::RDHUP)
+        }
 
         state
     }
 }
 
+impl Drop for Entry {
+    fn drop(&mut self) {
+        // Let the states that woke this entry wake another exclusive entry for the same file.
+        if let Some(exclusive) = &self.exclusive {
+            exclusive.release(self.claimed);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -551,4 +644,164 @@ mod tests {
         assert!(!entry.has_ready_events());
         assert_eq!(entry.collect_ready_events(), None);
     }
+
+    #[test]
+    fn one_shot_other_events() {
+        let inout_os = EpollEvents::EPOLLIN | EpollEvents::EPOLLOUT | EpollEvents::EPOLLONESHOT;
+        let mut entry = Entry::new(inout_os, DATA, FileState::empty());
+
+        entry.notify(
+            FileState::READABLE,
+            FileState::READABLE,
+            FileSignals::empty(),
+        );
+        assert_eq!(
+            entry.collect_ready_events(),
+            Some((EpollEvents::EPOLLIN, DATA))
+        );
+
+        // The entry is disabled, so the other event isn't reported either.
+        entry.notify(
+            FileState::READABLE | FileState::WRITABLE,
+            FileState::WRITABLE,
+            FileSignals::empty(),
+        );
+        assert!(!entry.has_ready_events());
+        assert_eq!(entry.collect_ready_events(), None);
+
+        entry.modify(inout_os, DATA, FileState::READABLE | FileState::WRITABLE);
+        assert_eq!(
+            entry.collect_ready_events(),
+            Some((EpollEvents::EPOLLIN | EpollEvents::EPOLLOUT, DATA))
+        );
+        assert!(!entry.has_ready_events());
+    }
+
+    #[test]
+    fn one_shot_edge_trigger() {
+        let in_et_os = EpollEvents::EPOLLIN | EpollEvents::EPOLLET | EpollEvents::EPOLLONESHOT;
+        let mut entry = Entry::new(in_et_os, DATA, FileState::empty());
+
+        entry.notify(
+            FileState::READABLE,
+            FileState::READABLE,
+            FileSignals::empty(),
+        );
+        assert_eq!(
+            entry.collect_ready_events(),
+            Some((EpollEvents::EPOLLIN, DATA))
+        );
+
+        // New edges don't rearm the entry.
+        entry.notify(
+            FileState::READABLE,
+            FileState::empty(),
+            FileSignals::READ_BUFFER_GREW,
+        );
+        assert!(!entry.has_ready_events());
+        entry.notify(
+            FileState::empty(),
+            FileState::READABLE,
+            FileSignals::empty(),
+        );
+        entry.notify(
+            FileState::READABLE,
+            FileState::READABLE,
+            FileSignals::empty(),
+        );
+        assert!(!entry.has_ready_events());
+
+        // Rearming reports the event once.
+        entry.modify(in_et_os, DATA, FileState::READABLE);
+        assert_eq!(
+            entry.collect_ready_events(),
+            Some((EpollEvents::EPOLLIN, DATA))
+        );
+        assert!(!entry.has_ready_events());
+        entry.notify(
+            FileState::READABLE,
+            FileState::empty(),
+            FileSignals::READ_BUFFER_GREW,
+        );
+        assert!(!entry.has_ready_events());
+    }
+
+    #[test]
+    fn exclusive_wakes_one() {
+        let wakeup = Arc::new(ExclusiveWakeup::new());
+        let mut entries: Vec<Entry> = (0..3)
+            .map(|_| {
+                let mut entry = Entry::new(EpollEvents::EPOLLIN, DATA, FileState::empty());
+                entry.set_exclusive(Arc::clone(&wakeup));
+                entry
+            })
+            .collect();
+
+        let notify_all = |entries: &mut Vec<Entry>, state| {
+            for entry in entries.iter_mut() {
+                entry.notify(state, FileState::READABLE, FileSignals::empty());
+            }
+        };
+
+        // only the first entry to be notified is woken
+        notify_all(&mut entries, FileState::READABLE);
+        let ready: Vec<bool> = entries.iter().map(|x| x.has_ready_events()).collect();
+        assert_eq!(ready, [true, false, false]);
+
+        // a level-triggered entry stays ready after reporting
+        assert!(entries[0].collect_ready_events().is_some());
+        assert!(entries[0].has_ready_events());
+
+        // the next readiness wakes an entry again
+        notify_all(&mut entries, FileState::empty());
+        assert!(entries.iter().all(|x| !x.has_ready_events()));
+        notify_all(&mut entries, FileState::READABLE);
+        let ready: Vec<bool> = entries.iter().map(|x| x.has_ready_events()).collect();
+        assert_eq!(ready, [true, false, false]);
+    }
+
+    #[test]
+    fn exclusive_woken_entry_removed() {
+        let wakeup = Arc::new(ExclusiveWakeup::new());
+        let mut entries: Vec<Entry> = (0..3)
+            .map(|_| {
+                let mut entry = Entry::new(EpollEvents::EPOLLIN, DATA, FileState::empty());
+                entry.set_exclusive(Arc::clone(&wakeup));
+                entry
+            })
+            .collect();
+
+        for entry in &mut entries {
+            entry.notify(
+                FileState::READABLE,
+                FileState::READABLE,
+                FileSignals::empty(),
+            );
+        }
+        let ready: Vec<bool> = entries.iter().map(|x| x.has_ready_events()).collect();
+        assert_eq!(ready, [true, false, false]);
+
+        // more data doesn't wake another entry while the woken entry exists
+        for entry in &mut entries {
+            entry.notify(
+                FileState::READABLE,
+                FileState::empty(),
+                FileSignals::READ_BUFFER_GREW,
+            );
+        }
+        let ready: Vec<bool> = entries.iter().map(|x| x.has_ready_events()).collect();
+        assert_eq!(ready, [true, false, false]);
+
+        // after the woken entry is removed, more data wakes one of the others
+        entries.remove(0);
+        for entry in &mut entries {
+            entry.notify(
+                FileState::READABLE,
+                FileState::empty(),
+                FileSignals::READ_BUFFER_GREW,
+            );
+        }
+        let ready: Vec<bool> = entries.iter().map(|x| x.has_ready_events()).collect();
+        assert_eq!(ready, [true, false]);
+    }
 }
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+
+use atomic_refcell::AtomicRefCell;
+
+use crate::host::descriptor::{File, FileState};
+
+/// The wakeup states of a host's files that are monitored by `EPOLLEXCLUSIVE` entries, keyed by the
+/// file's canonical handle. The entries hold a reference to the file, so a handle can't be reused
+/// by another file while a live wakeup state is stored for it.
+#[derive(Default)]
+pub struct ExclusiveWakeups {
+    wakeups: HashMap<usize, Weak<ExclusiveWakeup>>,
+}
+
+impl ExclusiveWakeups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the wakeup state for `file`, which is shared with any other exclusive entries that
+    /// monitor the same file (in any epoll).
+    pub(super) fn for_file(&mut self, file: &File) -> Arc<ExclusiveWakeup> {
+        if let Some(wakeup) = self
+            .wakeups
+            .get(&file.canonical_handle())
+            .and_then(Weak::upgrade)
+        {
+            return wakeup;
+        }
+
+        // forget any files that are no longer monitored by exclusive entries
+        self.wakeups.retain(|_, x| x.strong_count() > 0);
+
+        let wakeup = Arc::new(ExclusiveWakeup::new());
+        self.wakeups
+            .insert(file.canonical_handle(), Arc::downgrade(&wakeup));
+        wakeup
+    }
+}
+
+/// The wakeup state shared by all `EPOLLEXCLUSIVE` entries that monitor the same file, so that
+/// only one of their epolls is woken each time a file state turns on.
+pub(super) struct ExclusiveWakeup {
+    /// The file states that turned on and have already woken an epoll. A state can wake another
+    /// epoll only after it turns off and on again.
+    claimed: AtomicRefCell<FileState>,
+}
+
+impl ExclusiveWakeup {
+    pub fn new() -> Self {
+        Self {
+            claimed: AtomicRefCell::new(FileState::empty()),
+        }
+    }
+
+    /// Claim the states in `turned_on` for the caller's epoll. Returns the states that the caller
+    /// claimed, which are empty if all of them have already been claimed by another epoll since
+    /// they last turned on.
+    pub fn claim(&self, turned_on: FileState) -> FileState {
+        let mut claimed = self.claimed.borrow_mut();
+        let unclaimed = turned_on.difference(*claimed);
+        claimed.insert(turned_on);
+        unclaimed
+    }
+
+    /// Release the states in `states` so that they can wake an epoll again, either because they
+    /// turned off or because the epoll entry that claimed them was removed.
+    pub fn release(&self, states: FileState) {
+        self.claimed.borrow_mut().remove(states);
+    }
+}
@@ -10,6 +10,7 @@ use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
 use crate::host::descriptor::{File, FileMode, FileSignals, FileState, FileStatus, SyscallResult};
+use crate::host::host::Host;
 use crate::host::memory_manager::MemoryManager;
 use crate::host::syscall::io::IoVec;
 use crate::host::syscall::types::SyscallError;
@@ -19,13 +20,20 @@ use crate::utility::{HostTreePointer, ObjectCounter};
 use self::entry::Entry;
 use self::key::{Key, PriorityKey};
 
+pub use self::exclusive::ExclusiveWakeups;
+
 use super::socket::inet::InetSocket;
 use super::socket::Socket;
 
 // Private submodules to help us track the status of items we are monitoring.
 mod entry;
+mod exclusive;
 mod key;
 
+/// The maximum number of epolls that a chain of nested epolls can have below its top epoll. This is
+/// `EP_MAX_NESTS` in linux.
+const EPOLL_MAX_NESTS: usize = 4;
+
 pub struct Epoll {
     event_source: StateEventSource,
     status: FileStatus,
@@ -39,6 +47,8 @@ pub struct Epoll {
     pri_counter: u64,
     // Stores entries for all descriptors we are currently monitoring for events.
     monitoring: HashMap<Key, Entry>,
+    // The epolls that monitor this epoll, once for each of their entries for it.
+    parents: Vec<Weak<AtomicRefCell<Epoll>>>,
     // Stores keys for entries with events that are ready to be reported.
     ready: BinaryHeap<PriorityKey>,
     _counter: ObjectCounter,
@@ -53,11 +63,14 @@ impl Epoll {
             has_open_file: false,
             pri_counter: u64::MAX,
             monitoring: HashMap::new(),
+            parents: Vec::new(),
             ready: BinaryHeap::new(),
             _counter: ObjectCounter::new("Epoll"),
         };
 
-        CallbackQueue::queue_and_run_with_legacy(|cb_queue| epoll.refresh_state(cb_queue));
+        CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            epoll.refresh_state(FileSignals::empty(), cb_queue)
+        });
 
         Arc::new(AtomicRefCell::new(epoll))
     }
@@ -153,6 +166,7 @@ impl Epoll {
         events: EpollEvents,
         data: u64,
         weak_self: Weak<AtomicRefCell<Epoll>>,
+        host: &Host,
         cb_queue: &mut CallbackQueue,
     ) -> Result<(), Errno> {
         let state = target_file.borrow().state();
@@ -183,15 +197,35 @@ impl Epoll {
                     entry.set_legacy();
                 }
 
+                if events.contains(EpollEvents::EPOLLEXCLUSIVE) {
+                    let wakeup = host
+                        .epoll_exclusive_wakeups_borrow_mut()
+                        .for_file(key.file());
+                    entry.set_exclusive(wakeup);
+                }
+
                 // From epoll_ctl(2): Returns EEXIST when "op was EPOLL_CTL_ADD, and the supplied
                 // file descriptor fd is already registered with this epoll instance."
                 match self.monitoring.entry(key.clone()) {
                     HashMapEntry::Occupied(_) => return Err(Errno::EEXIST),
                     HashMapEntry::Vacant(x) => x.insert(entry),
                 };
+
+                if let File::Epoll(child) = key.file() {
+                    let parents = &mut child.borrow_mut().parents;
+                    parents.retain(|x| x.strong_count() > 0);
+                    parents.push(weak_self.clone());
+                }
             }
             EpollCtlOp::EPOLL_CTL_MOD => {
                 let entry = self.monitoring.get_mut(&key).ok_or(Errno::ENOENT)?;
+
+                // From epoll_ctl(2): Returns EINVAL when "op was EPOLL_CTL_MOD and the
+                // EPOLLEXCLUSIVE flag has previously been applied to this epfd, fd pair."
+                if entry.is_exclusive() {
+                    return Err(Errno::EINVAL);
+                }
+
                 entry.modify(events, data, state);
             }
             EpollCtlOp::EPOLL_CTL_DEL => {
@@ -203,12 +237,79 @@ impl Epoll {
                 if let Some(pri) = entry.priority() {
                     self.ready.retain(|e| e.priority() != pri)
                 }
+
+                if let File::Epoll(child) = key.file() {
+                    let parents = &mut child.borrow_mut().parents;
+                    if let Some(i) = parents.iter().position(|x| x.ptr_eq(&weak_self)) {
+                        parents.swap_remove(i);
+                    }
+                }
             }
         };
 
         self.refresh_ready(key.clone());
         self.refresh_listener(weak_self, key);
-        self.refresh_state(cb_queue);
+        self.refresh_state(FileSignals::empty(), cb_queue);
+
+        Ok(())
+    }
+
+    /// Check that the epoll `parent` can monitor the epoll `child` without creating a loop of
+    /// epolls monitoring each other, or a chain of nested epolls deeper than linux allows. Like
+    /// `ep_loop_check()` in linux, the chain includes the epolls below the child and the epolls
+    /// above the parent. Returns `ELOOP` otherwise. The parent and child must be different epolls,
+    /// and the parent must not be mutably borrowed.
+    pub fn check_nesting(
+        parent: &Arc<AtomicRefCell<Epoll>>,
+        child: &Arc<AtomicRefCell<Epoll>>,
+    ) -> Result<(), Errno> {
+        /// The number of levels of epolls below `epoll`, or `ELOOP` if `parent` is below it.
+        fn depth_below(
+            parent: &Arc<AtomicRefCell<Epoll>>,
+            epoll: &Arc<AtomicRefCell<Epoll>>,
+            depth: usize,
+        ) -> Result<usize, Errno> {
+            let mut max_depth = 0;
+
+            for key in epoll.borrow().monitoring.keys() {
+                let File::Epoll(nested) = key.file() else {
+                    continue;
+                };
+
+                if Arc::ptr_eq(nested, parent) || depth > EPOLL_MAX_NESTS {
+                    return Err(Errno::ELOOP);
+                }
+
+                max_depth = std::cmp::max(max_depth, depth_below(parent, nested, depth + 1)? + 1);
+            }
+
+            Ok(max_depth)
+        }
+
+        /// The number of levels of epolls above `epoll`.
+        fn depth_above(epoll: &Arc<AtomicRefCell<Epoll>>, depth: usize) -> usize {
+            // the existing chains aren't deeper than linux allows, so this only guards against
+            // unexpected loops
+            if depth > EPOLL_MAX_NESTS {
+                return 0;
+            }
+
+            epoll
+                .borrow()
+                .parents
+                .iter()
+                .filter_map(Weak::upgrade)
+                .map(|x| depth_above(&x, depth + 1) + 1)
+                .max()
+                .unwrap_or(0)
+        }
+
+        let below = depth_below(parent, child, 0)?;
+        let above = depth_above(parent, 0);
+
+        if below + 1 + above > EPOLL_MAX_NESTS {
+            return Err(Errno::ELOOP);
+        }
 
         Ok(())
     }
@@ -239,7 +340,7 @@ impl Epoll {
         self.state
     }
 
-    fn refresh_state(&mut self, cb_queue: &mut CallbackQueue) {
+    fn refresh_state(&mut self, signals: FileSignals, cb_queue: &mut CallbackQueue) {
         let readable = self
             .has_ready_events()
             .then_some(FileState::READABLE)
@@ -247,7 +348,7 @@ impl Epoll {
         self.update_state(
             /* mask= */ FileState::READABLE,
             readable,
-            FileSignals::empty(),
+            signals,
             cb_queue,
         );
     }
@@ -327,21 +428,29 @@ impl Epoll {
         };
 
         // Update our ready set, which removes the key if the file closed.
-        self.refresh_ready(key.clone());
+        let newly_ready = self.refresh_ready(key.clone());
 
         // Also stop monitoring if the file was closed.
         if state.contains(FileState::CLOSED) {
             self.monitoring.remove(key);
         }
 
-        // Update the readability of the epoll descriptor.
-        self.refresh_state(cb_queue);
+        // Update the readability of the epoll descriptor. If this epoll is itself monitored by
+        // another epoll, a new ready entry should wake the other epoll's edge-triggered waiters
+        // even if this epoll was already readable, as if more data arrived on a socket.
+        let signals = if newly_ready {
+            FileSignals::READ_BUFFER_GREW
+        } else {
+            FileSignals::empty()
+        };
+        self.refresh_state(signals, cb_queue);
     }
 
     /// Ensures that the entry is in the ready set if it should be, or not if it shouldn't be.
-    fn refresh_ready(&mut self, key: Key) {
+    /// Returns true if the entry was added to the ready set.
+    fn refresh_ready(&mut self, key: Key) -> bool {
         let Some(entry) = self.monitoring.get_mut(&key.clone()) else {
-            return;
+            return false;
         };
 
         // The entry will not be ready if the file closed.
@@ -352,12 +461,15 @@ impl Epoll {
                 self.pri_counter -= 1;
                 self.ready.push(PriorityKey::new(pri, key));
                 entry.set_priority(Some(pri));
+                return true;
             }
         } else if let Some(pri) = entry.priority() {
             // It's not ready anymore but it's in the ready set, so remove it.
             self.ready.retain(|e| e.priority() != pri);
             entry.set_priority(None);
         }
+
+        false
     }
 
     pub fn has_ready_events(&self) -> bool {
@@ -406,7 +518,7 @@ impl Epoll {
         self.ready.extend(keep);
 
         // We've mutated the ready list; we may need to trigger callbacks.
-        self.refresh_state(cb_queue);
+        self.refresh_state(FileSignals::empty(), cb_queue);
 
         // The events to be returned to the managed process.
         events
@@ -22,6 +22,7 @@ pub mod descriptor_table;
 pub mod epoll;
 pub mod eventfd;
 pub mod listener;
+pub mod pidfd;
 pub mod pipe;
 pub mod shared_buf;
 pub mod socket;
@@ -136,6 +137,14 @@ bitflags::bitflags! {
         /// A listening socket is allowing connections. Only applicable to connection-oriented unix
         /// sockets.
         const SOCKET_ALLOWING_CONNECT = 1 << 6;
+        /// There is urgent (priority) data waiting for the user, for example TCP out-of-band data.
+        const PRIORITY = 1 << 7;
+        /// All data written to a connection-oriented socket, including its FIN if it was shut down,
+        /// has been acknowledged by the peer. Only applicable to legacy TCP sockets.
+        const SOCKET_OUTPUT_ACKED = 1 << 8;
+        /// Reading has been shut down, for example because the peer of a connection-oriented
+        /// socket shut down writing. There may still be buffered data to read.
+        const RDHUP = 1 << 9;
     }
 }
 
@@ -157,6 +166,7 @@ pub enum File {
     Socket(Socket),
     TimerFd(Arc<AtomicRefCell<timerfd::TimerFd>>),
     Epoll(Arc<AtomicRefCell<epoll::Epoll>>),
+    PidFd(Arc<AtomicRefCell<pidfd::PidFd>>),
 }
 
 // will not compile if `File` is not Send + Sync
@@ -171,6 +181,7 @@ impl File {
             Self::Socket(ref f) => FileRef::Socket(f.borrow()),
             Self::TimerFd(ref f) => FileRef::TimerFd(f.borrow()),
             Self::Epoll(ref f) => FileRef::Epoll(f.borrow()),
+            Self::PidFd(ref f) => FileRef::PidFd(f.borrow()),
         }
     }
 
@@ -181,6 +192,7 @@ impl File {
             Self::Socket(ref f) => FileRef::Socket(f.try_borrow()?),
             Self::TimerFd(ref f) => FileRef::TimerFd(f.try_borrow()?),
             Self::Epoll(ref f) => FileRef::Epoll(f.try_borrow()?),
+            Self::PidFd(ref f) => FileRef::PidFd(f.try_borrow()?),
         })
     }
 
@@ -191,6 +203,7 @@ impl File {
             Self::Socket(ref f) => FileRefMut::Socket(f.borrow_mut()),
             Self::TimerFd(ref f) => FileRefMut::TimerFd(f.borrow_mut()),
             Self::Epoll(ref f) => FileRefMut::Epoll(f.borrow_mut()),
+            Self::PidFd(ref f) => FileRefMut::PidFd(f.borrow_mut()),
         }
     }
 
@@ -201,6 +214,7 @@ impl File {
             Self::Socket(ref f) => FileRefMut::Socket(f.try_borrow_mut()?),
             Self::TimerFd(ref f) => FileRefMut::TimerFd(f.try_borrow_mut()?),
             Self::Epoll(ref f) => FileRefMut::Epoll(f.try_borrow_mut()?),
+            Self::PidFd(ref f) => FileRefMut::PidFd(f.try_borrow_mut()?),
         })
     }
 
@@ -211,6 +225,7 @@ impl File {
             Self::Socket(ref f) => f.canonical_handle(),
             Self::TimerFd(f) => Arc::as_ptr(f) as usize,
             Self::Epoll(f) => Arc::as_ptr(f) as usize,
+            Self::PidFd(f) => Arc::as_ptr(f) as usize,
         }
     }
 }
@@ -223,6 +238,7 @@ impl std::fmt::Debug for File {
             Self::Socket(_) => write!(f, "Socket")?,
             Self::TimerFd(_) => write!(f, "TimerFd")?,
             Self::Epoll(_) => write!(f, "Epoll")?,
+            Self::PidFd(_) => write!(f, "PidFd")?,
         }
 
         if let Ok(file) = self.try_borrow() {
@@ -242,6 +258,7 @@ pub enum FileRef<'a> {
     Socket(SocketRef<'a>),
     TimerFd(atomic_refcell::AtomicRef<'a, timerfd::TimerFd>),
     Epoll(atomic_refcell::AtomicRef<'a, epoll::Epoll>),
+    PidFd(atomic_refcell::AtomicRef<'a, pidfd::PidFd>),
 }
 
 /// Wraps a mutably borrowed [`File`]. Created from [`File::borrow_mut`] or
@@ -252,61 +269,62 @@ pub enum FileRefMut<'a> {
     Socket(SocketRefMut<'a>),
     TimerFd(atomic_refcell::AtomicRefMut<'a, timerfd::TimerFd>),
     Epoll(atomic_refcell::AtomicRefMut<'a, epoll::Epoll>),
+    PidFd(atomic_refcell::AtomicRefMut<'a, pidfd::PidFd>),
 }
 
 impl FileRef<'_> {
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError>
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn supports_sa_restart(&self) -> bool
     );
 }
 
 impl FileRefMut<'_> {
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError>
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn supports_sa_restart(&self) -> bool
     );
-    enum_passthrough!(self, (val), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (val), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn set_has_open_file(&mut self, val: bool)
     );
-    enum_passthrough!(self, (cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError>
     );
-    enum_passthrough!(self, (status), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (status), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn set_status(&mut self, status: FileStatus)
     );
-    enum_passthrough!(self, (request, arg_ptr, memory_manager), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (request, arg_ptr, memory_manager), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn ioctl(&mut self, request: IoctlRequest, arg_ptr: ForeignPtr<()>, memory_manager: &mut MemoryManager) -> SyscallResult
     );
-    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn add_listener(
             &mut self,
             monitoring_state: FileState,
@@ -315,17 +333,17 @@ impl FileRefMut<'_> {
             notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) + Send + Sync + 'static,
         ) -> StateListenHandle
     );
-    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>)
     );
-    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (ptr), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener)
     );
-    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn readv(&mut self, iovs: &[IoVec], offset: Option<libc::off_t>, flags: libc::c_int,
                      mem: &mut MemoryManager, cb_queue: &mut CallbackQueue) -> Result<libc::ssize_t, SyscallError>
     );
-    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll;
+    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Pipe, EventFd, Socket, TimerFd, Epoll, PidFd;
         pub fn writev(&mut self, iovs: &[IoVec], offset: Option<libc::off_t>, flags: libc::c_int,
                       mem: &mut MemoryManager, cb_queue: &mut CallbackQueue) -> Result<libc::ssize_t, SyscallError>
     );
@@ -339,6 +357,7 @@ impl std::fmt::Debug for FileRef<'_> {
             Self::Socket(_) => write!(f, "Socket")?,
             Self::TimerFd(_) => write!(f, "TimerFd")?,
             Self::Epoll(_) => write!(f, "Epoll")?,
+            Self::PidFd(_) => write!(f, "PidFd")?,
         }
 
         let state = self.state();
@@ -355,6 +374,7 @@ impl std::fmt::Debug for FileRefMut<'_> {
             Self::Socket(_) => write!(f, "Socket")?,
             Self::TimerFd(_) => write!(f, "TimerFd")?,
             Self::Epoll(_) => write!(f, "Epoll")?,
+            Self::PidFd(_) => write!(f, "PidFd")?,
         }
 
         let state = self.state();
@@ -417,6 +437,12 @@ impl OpenFile {
         self.inner.file.as_ref().unwrap()
     }
 
+    /// Returns true if this is the only `OpenFile` for the inner `File` object, meaning that
+    /// closing this `OpenFile` will close the `File`.
+    pub fn is_last_reference(&self) -> bool {
+        Arc::strong_count(&self.inner) == 1
+    }
+
     /// Will close the inner `File` object if this is the last `OpenFile` for that `File`. This
     /// behaviour is the same as simply dropping this `OpenFile` object, but allows you to pass an
     /// event queue and get the return value of the close operation.
@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use linux_api::ioctls::IoctlRequest;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::cshadow as c;
+use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::{FileMode, FileSignals, FileState, FileStatus};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::process::{Process, ProcessId};
+use crate::host::syscall::io::IoVec;
+use crate::host::syscall::types::{SyscallError, SyscallResult};
+use crate::utility::callback_queue::{CallbackQueue, Handle};
+use crate::utility::HostTreePointer;
+
+/// A file descriptor that refers to a process, created by `pidfd_open(2)`. The file becomes
+/// readable when the process exits.
+pub struct PidFd {
+    pid: ProcessId,
+    event_source: StateEventSource,
+    state: FileState,
+    status: FileStatus,
+    // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file
+    has_open_file: bool,
+    // we stop listening for the process' exit when this is dropped
+    _exit_listener: Option<Handle<()>>,
+}
+
+impl PidFd {
+    pub fn new(process: &Process, status: FileStatus) -> Arc<AtomicRefCell<Self>> {
+        let pidfd = Arc::new(AtomicRefCell::new(Self {
+            pid: process.id(),
+            event_source: StateEventSource::new(),
+            state: FileState::ACTIVE,
+            status,
+            has_open_file: false,
+            _exit_listener: None,
+        }));
+
+        let weak = Arc::downgrade(&pidfd);
+        let handle = process.add_exit_listener(move |cb_queue| {
+            if let Some(pidfd) = weak.upgrade() {
+                pidfd.borrow_mut().mark_exited(cb_queue);
+            }
+        });
+
+        match handle {
+            Some(handle) => pidfd.borrow_mut()._exit_listener = Some(handle),
+            // the process is a zombie, so it's already exited
+            None => CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                pidfd.borrow_mut().mark_exited(cb_queue)
+            }),
+        }
+
+        pidfd
+    }
+
+    /// The id of the process that this pidfd refers to.
+    pub fn pid(&self) -> ProcessId {
+        self.pid
+    }
+
+    pub fn status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ | FileMode::WRITE
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        false
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError> {
+        // set the closed flag and remove the active and readable flags
+        self.update_state(
+            FileState::CLOSED | FileState::ACTIVE | FileState::READABLE,
+            FileState::CLOSED,
+            FileSignals::empty(),
+            cb_queue,
+        );
+
+        Ok(())
+    }
+
+    pub fn readv(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<libc::off_t>,
+        _flags: libc::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        // pidfds can only be polled, not read
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn writev(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<libc::off_t>,
+        _flags: libc::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: IoctlRequest,
+        _arg_ptr: ForeignPtr<()>,
+        _memory_manager: &mut MemoryManager,
+    ) -> SyscallResult {
+        log::warn!("We do not yet handle ioctl request {request:?} on pidfds");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
+        warn_once_then_debug!("We do not yet handle stat calls on pidfds");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue)
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.event_source
+            .add_listener(monitoring_state, monitoring_signals, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    /// Called when the process exits.
+    fn mark_exited(&mut self, cb_queue: &mut CallbackQueue) {
+        if self.state.contains(FileState::CLOSED) {
+            return;
+        }
+
+        // pidfd_open(2): "A PID file descriptor can be monitored using poll(2), select(2), and
+        // epoll(7). When the process that it refers to terminates, these interfaces indicate the
+        // file descriptor as readable."
+        self.update_state(
+            FileState::READABLE,
+            FileState::READABLE,
+            FileSignals::empty(),
+            cb_queue,
+        );
+    }
+
+    fn update_state(
+        &mut self,
+        mask: FileState,
+        state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let old_state = self.state;
+
+        // remove the masked flags, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, signals, cb_queue);
+    }
+
+    fn handle_state_change(
+        &mut self,
+        old_state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let states_changed = self.state ^ old_state;
+
+        // if nothing changed
+        if states_changed.is_empty() && signals.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, signals, cb_queue);
+    }
+}
@@ -141,27 +141,11 @@ impl Pipe {
 
         let num_bytes_to_read: libc::size_t = iovs.iter().map(|x| x.len).sum();
 
-        let mut writer = IoVecWriter::new(iovs, mem);
+        let writer = IoVecWriter::new(iovs, mem);
 
-        let (num_copied, _num_removed_from_buf) = self
-            .buffer
-            .as_ref()
-            .unwrap()
-            .borrow_mut()
-            .read(&mut writer, cb_queue)?;
+        let num_copied = self.read_from_buffer(writer, num_bytes_to_read, false, cb_queue)?;
 
-        // the read would block if all:
-        //  1. we could not read any bytes
-        //  2. we were asked to read >0 bytes
-        //  3. there are open descriptors that refer to the write end of the pipe
-        if num_copied == 0
-            && num_bytes_to_read != 0
-            && self.buffer.as_ref().unwrap().borrow().num_writers() > 0
-        {
-            Err(Errno::EWOULDBLOCK.into())
-        } else {
-            Ok(num_copied.try_into().unwrap())
-        }
+        Ok(num_copied.try_into().unwrap())
     }
 
     pub fn writev(
@@ -182,6 +166,89 @@ impl Pipe {
             return Err(linux_api::errno::Errno::EBADF.into());
         }
 
+        let len: libc::size_t = iovs.iter().map(|x| x.len).sum();
+
+        let reader = IoVecReader::new(iovs, mem);
+
+        let num_copied = self.write_to_buffer(reader, len, cb_queue)?;
+
+        Ok(num_copied.try_into().unwrap())
+    }
+
+    /// Read up to `bytes.len()` bytes from the pipe. If `peek` is true, the bytes aren't removed
+    /// from the pipe. This is used by `splice()` to move data without going through plugin memory.
+    pub fn read_bytes(
+        &mut self,
+        bytes: &mut [u8],
+        peek: bool,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<usize, SyscallError> {
+        // if the file is not open for reading, return EBADF
+        if !self.mode.contains(FileMode::READ) {
+            return Err(linux_api::errno::Errno::EBADF.into());
+        }
+
+        let len = bytes.len();
+        self.read_from_buffer(bytes, len, peek, cb_queue)
+    }
+
+    /// Write `bytes` to the pipe, returning the number of bytes written. This is used by
+    /// `splice()` to move data without going through plugin memory.
+    pub fn write_bytes(
+        &mut self,
+        bytes: &[u8],
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<usize, SyscallError> {
+        // if the file is not open for writing, return EBADF
+        if !self.mode.contains(FileMode::WRITE) {
+            return Err(linux_api::errno::Errno::EBADF.into());
+        }
+
+        self.write_to_buffer(bytes, bytes.len(), cb_queue)
+    }
+
+    /// The number of bytes that can be written to the pipe before it's full.
+    pub fn space_available(&self) -> usize {
+        self.buffer.as_ref().unwrap().borrow().space_available()
+    }
+
+    /// Does the pipe have any open read ends?
+    pub fn has_readers(&self) -> bool {
+        self.buffer.as_ref().unwrap().borrow().num_readers() > 0
+    }
+
+    fn read_from_buffer(
+        &mut self,
+        writer: impl std::io::Write,
+        num_bytes_to_read: usize,
+        peek: bool,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<usize, SyscallError> {
+        let buffer = self.buffer.as_ref().unwrap();
+
+        let (num_copied, _num_removed_from_buf) = if peek {
+            buffer.borrow().peek(writer)?
+        } else {
+            buffer.borrow_mut().read(writer, cb_queue)?
+        };
+
+        // the read would block if all:
+        //  1. we could not read any bytes
+        //  2. we were asked to read >0 bytes
+        //  3. there are open descriptors that refer to the write end of the pipe
+        if num_copied == 0 && num_bytes_to_read != 0 && buffer.borrow().num_writers() > 0 {
+            Err(Errno::EWOULDBLOCK.into())
+        } else {
+            Ok(num_copied)
+        }
+    }
+
+    fn write_to_buffer(
+        &mut self,
+        mut reader: impl std::io::Read,
+        len: usize,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<usize, SyscallError> {
         let mut buffer = self.buffer.as_ref().unwrap().borrow_mut();
 
         if buffer.num_readers() == 0 {
@@ -200,10 +267,6 @@ impl Pipe {
             }
         }
 
-        let len: libc::size_t = iovs.iter().map(|x| x.len).sum();
-
-        let mut reader = IoVecReader::new(iovs, mem);
-
         let num_copied = match self.write_mode {
             WriteMode::Stream => buffer.write_stream(&mut reader, len, cb_queue)?,
             WriteMode::Packet => {
@@ -234,7 +297,7 @@ impl Pipe {
             }
         };
 
-        Ok(num_copied.try_into().unwrap())
+        Ok(num_copied)
     }
 
     pub fn ioctl(
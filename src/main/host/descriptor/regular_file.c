@@ -23,9 +23,12 @@
 #include <syscall.h>
 #include <unistd.h>
 
+#include <linux/magic.h>
+
 #include "lib/logger/logger.h"
 #include "main/core/worker.h"
 #include "main/host/descriptor/descriptor.h"
+#include "main/host/protocol.h"
 #include "main/host/syscall/kernel_types.h"
 #include "main/routing/dns.h"
 #include "main/utility/utility.h"
@@ -51,6 +54,14 @@ struct _RegularFile {
             mode_t modeAtOpen;
             /* The path of the file when it was opened. */
             char* absPathAtOpen;
+            /* Whether the file is within one of the host's read-only mounts. */
+            bool isReadOnlyMount;
+            /* Whether the file is within one of the host's tmpfs mounts. */
+            bool isTmpfsMount;
+            /* The size and sector size of the device if the file is one of the host's block
+             * devices. The size is 0 otherwise. */
+            uint64_t blockDeviceSize;
+            uint32_t blockDeviceSectorSize;
         } osfile;
         struct {
             off_t cursor;
@@ -212,6 +223,191 @@ static char* _regularfile_getAbsolutePath(RegularFile* dir, const char* pathname
     return abspath;
 }
 
+/* Returns the real path of `abspath` if it's within one of the current host's read-only mounts,
+ * or NULL otherwise. The returned string must be freed. */
+static char* _regularfile_getReadOnlyMountPath(const char* abspath) {
+    const Host* host = worker_getCurrentHost();
+    if (!host) {
+        return NULL;
+    }
+
+    char* mountpath = host_allocReadOnlyMountPath(host, abspath);
+    if (!mountpath) {
+        return NULL;
+    }
+
+    char* copy = strdup(mountpath);
+    host_freeReadOnlyMountPath(mountpath);
+    return copy;
+}
+
+/* Like `_regularfile_getReadOnlyMountPath()`, but for a path relative to `dir` or `workingDir`. */
+static char* _regularfile_getReadOnlyMountPathAt(RegularFile* dir, const char* pathname,
+                                                 const char* workingDir) {
+    char* abspath = _regularfile_getAbsolutePath(dir, pathname, workingDir);
+    char* mountpath = _regularfile_getReadOnlyMountPath(abspath);
+    free(abspath);
+    return mountpath;
+}
+
+/* Returns the real path of `abspath` if it's within one of the current host's tmpfs mounts, or
+ * NULL otherwise. The returned string must be freed. */
+static char* _regularfile_getTmpfsMountPath(const char* abspath) {
+    const Host* host = worker_getCurrentHost();
+    if (!host) {
+        return NULL;
+    }
+
+    char* mountpath = host_allocTmpfsMountPath(host, abspath);
+    if (!mountpath) {
+        return NULL;
+    }
+
+    char* copy = strdup(mountpath);
+    host_freeTmpfsMountPath(mountpath);
+    return copy;
+}
+
+/* Returns the real path of `pathname`, relative to `dir` or `workingDir`, if it's within one of
+ * the current host's read-only or tmpfs mounts, or NULL otherwise. The returned string must be
+ * freed. */
+static char* _regularfile_getMountPathAt(RegularFile* dir, const char* pathname,
+                                         const char* workingDir) {
+    char* abspath = _regularfile_getAbsolutePath(dir, pathname, workingDir);
+    char* mountpath = _regularfile_getReadOnlyMountPath(abspath);
+    if (!mountpath) {
+        mountpath = _regularfile_getTmpfsMountPath(abspath);
+    }
+    free(abspath);
+    return mountpath;
+}
+
+/* If the real path `realpath` is within one of the current host's tmpfs mounts, gets the size of
+ * the mount and the number of bytes used by its files, and returns true. */
+static bool _regularfile_getTmpfsUsage(const char* realpath, uint64_t* size, uint64_t* used) {
+    const Host* host = worker_getCurrentHost();
+    if (!host || !realpath) {
+        return false;
+    }
+    return host_getTmpfsUsage(host, realpath, size, used);
+}
+
+/* Returns the real path of `pathname`, relative to `dir` or `workingDir`, if it's within one of
+ * the current host's tmpfs mounts, or NULL otherwise. Unlike `_regularfile_getMountPathAt()`,
+ * `dir` may itself be within a tmpfs mount. The returned string must be freed. */
+static char* _regularfile_getTmpfsRealPathAt(RegularFile* dir, const char* pathname,
+                                             const char* workingDir) {
+    char* abspath = _regularfile_getAbsolutePath(dir, pathname, workingDir);
+    char* mountpath = _regularfile_getTmpfsMountPath(abspath);
+    if (mountpath) {
+        free(abspath);
+        return mountpath;
+    }
+
+    uint64_t size = 0, used = 0;
+    if (_regularfile_getTmpfsUsage(abspath, &size, &used)) {
+        return abspath;
+    }
+
+    free(abspath);
+    return NULL;
+}
+
+/* Records the current size of `file` in the tmpfs mount containing it. Like `du`, the mount
+ * doesn't count files that have been unlinked. */
+static void _regularfile_updateTmpfsUsage(RegularFile* file) {
+    const Host* host = worker_getCurrentHost();
+    if (!host || !file->osfile.isTmpfsMount) {
+        return;
+    }
+
+    struct stat statbuf = {0};
+    if (fstat(_regularfile_getOSBackedFD(file), &statbuf) < 0) {
+        return;
+    }
+
+    if (statbuf.st_nlink == 0) {
+        host_removeTmpfsFile(host, file->osfile.absPathAtOpen, statbuf.st_ino);
+    } else {
+        host_setTmpfsFileSize(host, file->osfile.absPathAtOpen, statbuf.st_ino, statbuf.st_size);
+    }
+}
+
+/* Records that the file at the real path `realpath`, which `statbuf` describes from before it was
+ * unlinked or replaced, no longer uses space in the tmpfs mount containing it, unless it had other
+ * links. */
+static void _regularfile_removeTmpfsFile(const char* realpath, const struct stat* statbuf) {
+    const Host* host = worker_getCurrentHost();
+    if (!host || !S_ISREG(statbuf->st_mode) || statbuf->st_nlink > 1) {
+        return;
+    }
+    host_removeTmpfsFile(host, realpath, statbuf->st_ino);
+}
+
+/* Returns the real path of the file that holds the data of the block device at `abspath`, and
+ * gets the device's size and sector size, if `abspath` is one of the current host's block devices.
+ * Returns NULL otherwise. The returned string must be freed. */
+static char* _regularfile_getBlockDevicePath(const char* abspath, uint64_t* size,
+                                             uint32_t* sectorSize) {
+    const Host* host = worker_getCurrentHost();
+    if (!host) {
+        return NULL;
+    }
+
+    char* devpath = host_allocBlockDevicePath(host, abspath, size, sectorSize);
+    if (!devpath) {
+        return NULL;
+    }
+
+    char* copy = strdup(devpath);
+    host_freeReadOnlyMountPath(devpath);
+    return copy;
+}
+
+/* Like `_regularfile_getBlockDevicePath()`, but for a path relative to `dir` or `workingDir`. */
+static char* _regularfile_getBlockDevicePathAt(RegularFile* dir, const char* pathname,
+                                               const char* workingDir, uint64_t* size) {
+    uint32_t sectorSize = 0;
+    char* abspath = _regularfile_getAbsolutePath(dir, pathname, workingDir);
+    char* devpath = _regularfile_getBlockDevicePath(abspath, size, &sectorSize);
+    free(abspath);
+    return devpath;
+}
+
+/* If `size` is non-zero, replaces the info in `statbuf` of the real file that holds a block
+ * device's data with the info of the block device. */
+static void _regularfile_fillBlockDeviceStat(uint64_t size, struct stat* statbuf) {
+    if (size == 0) {
+        return;
+    }
+
+    /* Like Linux, a block device has a size of 0. Its capacity is given by BLKGETSIZE64. */
+    statbuf->st_mode = S_IFBLK | (statbuf->st_mode & ~S_IFMT);
+    statbuf->st_size = 0;
+    statbuf->st_blocks = 0;
+}
+
+/* Paths relative to a directory within a read-only mount are also within the mount, but `dir`
+ * only knows its real path. */
+static bool _regularfile_isRelativeToReadOnlyMount(RegularFile* dir, const char* pathname) {
+    return pathname[0] != '/' && dir && dir->type != FILE_TYPE_IN_MEMORY &&
+           dir->osfile.isReadOnlyMount;
+}
+
+/* Whether `pathname` is within one of the current host's read-only mounts, which can't be
+ * modified. */
+static bool _regularfile_isInReadOnlyMount(RegularFile* dir, const char* pathname,
+                                           const char* workingDir) {
+    if (_regularfile_isRelativeToReadOnlyMount(dir, pathname)) {
+        return true;
+    }
+
+    char* mountpath = _regularfile_getReadOnlyMountPathAt(dir, pathname, workingDir);
+    bool isReadOnlyMount = mountpath != NULL;
+    free(mountpath);
+    return isReadOnlyMount;
+}
+
 #ifdef DEBUG
 #define CHECK_FLAG(flag)                                                                           \
     if (flags & flag) {                                                                            \
@@ -291,7 +487,8 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
      * an absolute path to compare for special files. */
     char* abspath = _regularfile_getAbsolutePath(dir, pathname, workingDir);
 
-    /* Handle special files. */
+    /* Handle special files. Other devices such as /dev/zero and /dev/null are deterministic and
+     * never block, so we let the OS handle them. */
     if (utility_isRandomPath(abspath)) {
         file->type = FILE_TYPE_RANDOM;
     } else if (!strcmp("/etc/hosts", abspath)) {
@@ -316,8 +513,81 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
         char content[] = "0\n";
         // size - 1 to strip the \0;
         return _regularfile_initRoInMemoryFile(file, flags, mode, sizeof(content) - 1, content);
+    } else if (!strcmp("/proc/net/tcp", abspath) || !strcmp("/proc/self/net/tcp", abspath) ||
+               !strcmp("/proc/net/udp", abspath) || !strcmp("/proc/self/net/udp", abspath)) {
+        // list the simulated host's sockets rather than the real host's sockets
+        ProtocolType type = !strcmp("tcp", abspath + strlen(abspath) - 3) ? PTCP : PUDP;
+        if (abspath) {
+            free(abspath);
+        }
+        char* content = host_allocProcNetFile(worker_getCurrentHost(), type);
+        int rv = _regularfile_initRoInMemoryFile(file, flags, mode, strlen(content), content);
+        host_freeProcNetFile(content);
+        return rv;
+    } else if (!strcmp("/proc/net/dev", abspath) || !strcmp("/proc/self/net/dev", abspath)) {
+        // list the simulated host's interfaces rather than the real host's interfaces
+        if (abspath) {
+            free(abspath);
+        }
+        char* content = host_allocProcNetDevFile(worker_getCurrentHost());
+        int rv = _regularfile_initRoInMemoryFile(file, flags, mode, strlen(content), content);
+        host_freeProcNetFile(content);
+        return rv;
+    } else if (!strcmp("/proc/self/environ", abspath) || !strcmp("/proc/self/auxv", abspath)) {
+        // These should describe the managed process rather than shadow. The native process has the
+        // environment and auxiliary vector that it was exec'd with, so use its files.
+        const Process* process = worker_getCurrentProcess();
+        if (process != NULL) {
+            char* nativepath = NULL;
+            if (asprintf(&nativepath, "/proc/%d/%s", (int)process_getNativePid(process),
+                         strrchr(abspath, '/') + 1) < 0) {
+                utility_panic("asprintf could not allocate a buffer, error %i: %s", errno,
+                              strerror(errno));
+            }
+            free(abspath);
+            abspath = nativepath;
+        }
+        file->type = FILE_TYPE_REGULAR;
     } else {
         file->type = FILE_TYPE_REGULAR;
+
+        /* Open the real file of a block device. Like Linux, O_TRUNC has no effect on it. */
+        char* devpath = _regularfile_getBlockDevicePath(
+            abspath, &file->osfile.blockDeviceSize, &file->osfile.blockDeviceSectorSize);
+        if (devpath) {
+            free(abspath);
+            abspath = devpath;
+            flags &= ~O_TRUNC;
+        }
+
+        /* Open the real file of a read-only mount. */
+        char* mountpath = _regularfile_getReadOnlyMountPath(abspath);
+        if (mountpath) {
+            free(abspath);
+            abspath = mountpath;
+            file->osfile.isReadOnlyMount = true;
+        } else {
+            file->osfile.isReadOnlyMount = _regularfile_isRelativeToReadOnlyMount(dir, pathname);
+        }
+
+        /* Open the real file of a tmpfs mount. */
+        mountpath = _regularfile_getTmpfsMountPath(abspath);
+        if (mountpath) {
+            free(abspath);
+            abspath = mountpath;
+        }
+    }
+
+    /* Read-only mounts can't be written to, truncated, or have files created in them. */
+    if (file->osfile.isReadOnlyMount &&
+        ((flags & O_ACCMODE) != O_RDONLY || (flags & O_TRUNC) ||
+         (flags & O_TMPFILE) == O_TMPFILE || ((flags & O_CREAT) && access(abspath, F_OK) != 0))) {
+        trace("RegularFile %p can't open read-only mount path '%s' with flags %i", file, abspath,
+              flags);
+        free(abspath);
+        file->type = FILE_TYPE_NOTSET;
+        file->osfile.isReadOnlyMount = false;
+        return -EROFS;
     }
 
     int originalFlags = flags;
@@ -342,6 +612,8 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
             free(abspath);
         }
         file->type = FILE_TYPE_NOTSET;
+        file->osfile.blockDeviceSize = 0;
+        file->osfile.blockDeviceSectorSize = 0;
         return -errcode;
     }
 
@@ -351,6 +623,16 @@ int regularfile_openat(RegularFile* file, RegularFile* dir, const char* pathname
     file->osfile.flagsAtOpen = flags;
     file->osfile.modeAtOpen = mode;
 
+    /* Only regular files use space in a tmpfs mount. Files opened relative to a directory in a
+     * tmpfs mount already have a real path. */
+    uint64_t tmpfsSize = 0, tmpfsUsed = 0;
+    struct stat statbuf = {0};
+    file->osfile.isTmpfsMount = _regularfile_getTmpfsUsage(abspath, &tmpfsSize, &tmpfsUsed) &&
+                                fstat(osfd, &statbuf) == 0 && S_ISREG(statbuf.st_mode);
+
+    /* The file may have been created or truncated. */
+    _regularfile_updateTmpfsUsage(file);
+
     trace("RegularFile %p opened os-backed file %i at absolute path %s", file,
           _regularfile_getOSBackedFD(file), file->osfile.absPathAtOpen);
 
@@ -365,38 +647,38 @@ int regularfile_open(RegularFile* file, const char* pathname, int flags, mode_t
     return regularfile_openat(file, NULL, pathname, flags, mode, workingDir);
 }
 
-static void _regularfile_readRandomBytes(RegularFile* file, const Host* host, void* buf,
+static void _regularfile_readRandomBytes(RegularFile* file, const Process* process, void* buf,
                                          size_t numBytes) {
     utility_debugAssert(file->type == FILE_TYPE_RANDOM);
 
-    utility_debugAssert(host != NULL);
+    utility_debugAssert(process != NULL);
 
-    trace("RegularFile %p will read %zu bytes from random source for host %s", file, numBytes,
-          host_getName(host));
+    trace("RegularFile %p will read %zu bytes from random source for process %s", file, numBytes,
+          process_getName(process));
 
-    host_rngNextNBytes(host, buf, numBytes);
+    process_rngNextNBytes(process, buf, numBytes);
 }
 
-static size_t _regularfile_readvRandomBytes(RegularFile* file, const Host* host,
+static size_t _regularfile_readvRandomBytes(RegularFile* file, const Process* process,
                                             const struct iovec* iov, int iovcnt) {
     size_t total = 0;
     for (int i = 0; i < iovcnt; i++) {
-        _regularfile_readRandomBytes(file, host, iov[i].iov_base, iov[i].iov_len);
+        _regularfile_readRandomBytes(file, process, iov[i].iov_base, iov[i].iov_len);
         total += iov[i].iov_len;
     }
     return total;
 }
 
-ssize_t regularfile_read(RegularFile* file, const Host* host, void* buf, size_t bufSize) {
+ssize_t regularfile_read(RegularFile* file, const Process* process, void* buf, size_t bufSize) {
     MAGIC_ASSERT(file);
 
     if (file->type == FILE_TYPE_RANDOM) {
-        _regularfile_readRandomBytes(file, host, buf, bufSize);
+        _regularfile_readRandomBytes(file, process, buf, bufSize);
         return (ssize_t)bufSize;
     }
 
     if (file->type == FILE_TYPE_IN_MEMORY) {
-        ssize_t read = regularfile_pread(file, host, buf, bufSize, file->inMemoryFile.cursor);
+        ssize_t read = regularfile_pread(file, process, buf, bufSize, file->inMemoryFile.cursor);
         file->inMemoryFile.cursor += read;
         return read;
     }
@@ -414,12 +696,12 @@ ssize_t regularfile_read(RegularFile* file, const Host* host, void* buf, size_t
     return (result < 0) ? -errno : result;
 }
 
-ssize_t regularfile_pread(RegularFile* file, const Host* host, void* buf, size_t bufSize,
+ssize_t regularfile_pread(RegularFile* file, const Process* process, void* buf, size_t bufSize,
                           off_t offset) {
     MAGIC_ASSERT(file);
 
     if (file->type == FILE_TYPE_RANDOM) {
-        _regularfile_readRandomBytes(file, host, buf, bufSize);
+        _regularfile_readRandomBytes(file, process, buf, bufSize);
         return (ssize_t)bufSize;
     }
 
@@ -428,7 +710,7 @@ ssize_t regularfile_pread(RegularFile* file, const Host* host, void* buf, size_t
         ssize_t nwritten;
         iov[0].iov_base = buf;
         iov[0].iov_len = bufSize;
-        return regularfile_preadv(file, host, iov, 1, offset);
+        return regularfile_preadv(file, process, iov, 1, offset);
     }
 
     if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
@@ -444,12 +726,12 @@ ssize_t regularfile_pread(RegularFile* file, const Host* host, void* buf, size_t
     return (result < 0) ? -errno : result;
 }
 
-ssize_t regularfile_preadv(RegularFile* file, const Host* host, const struct iovec* iov, int iovcnt,
-                           off_t offset) {
+ssize_t regularfile_preadv(RegularFile* file, const Process* process, const struct iovec* iov,
+                           int iovcnt, off_t offset) {
     MAGIC_ASSERT(file);
 
     if (file->type == FILE_TYPE_RANDOM) {
-        return (ssize_t)_regularfile_readvRandomBytes(file, host, iov, iovcnt);
+        return (ssize_t)_regularfile_readvRandomBytes(file, process, iov, iovcnt);
     }
 
     if (file->type == FILE_TYPE_IN_MEMORY) {
@@ -497,17 +779,17 @@ ssize_t regularfile_preadv(RegularFile* file, const Host* host, const struct iov
 }
 
 #ifdef SYS_preadv2
-ssize_t regularfile_preadv2(RegularFile* file, const Host* host, const struct iovec* iov,
+ssize_t regularfile_preadv2(RegularFile* file, const Process* process, const struct iovec* iov,
                             int iovcnt, off_t offset, int flags) {
     MAGIC_ASSERT(file);
 
     if (file->type == FILE_TYPE_RANDOM) {
-        return (ssize_t)_regularfile_readvRandomBytes(file, host, iov, iovcnt);
+        return (ssize_t)_regularfile_readvRandomBytes(file, process, iov, iovcnt);
     }
 
     if (file->type == FILE_TYPE_IN_MEMORY) {
         // flags can be ignored: none really impart in memory files
-        return regularfile_preadv(file, host, iov, iovcnt, offset);
+        return regularfile_preadv(file, process, iov, iovcnt, offset);
     }
 
     if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
@@ -525,6 +807,92 @@ ssize_t regularfile_preadv2(RegularFile* file, const Host* host, const struct io
 }
 #endif
 
+/* Returns the number of bytes that would be added to the tmpfs mount containing `file` if it were
+ * extended to `end` bytes, or 0 if it's not within a tmpfs mount. Returns -ENOSPC if the mount
+ * doesn't have room for all of them, and sets `avail` to the number of bytes it does have room
+ * for. */
+static ssize_t _regularfile_getTmpfsGrowth(RegularFile* file, off_t end, uint64_t* avail) {
+    uint64_t size = 0, used = 0;
+    if (!file->osfile.isTmpfsMount ||
+        !_regularfile_getTmpfsUsage(file->osfile.absPathAtOpen, &size, &used)) {
+        return 0;
+    }
+
+    struct stat statbuf = {0};
+    if (fstat(_regularfile_getOSBackedFD(file), &statbuf) < 0) {
+        return -errno;
+    }
+
+    *avail = used < size ? size - used : 0;
+    if (end <= statbuf.st_size) {
+        return 0;
+    }
+
+    uint64_t growth = end - statbuf.st_size;
+    return growth <= *avail ? (ssize_t)growth : -ENOSPC;
+}
+
+/* Returns the number of bytes of a `bufSize` byte write at `offset` that fit within the tmpfs
+ * mount containing `file`, or -ENOSPC if none do. A negative `offset` means the file offset. */
+static ssize_t _regularfile_limitTmpfsWrite(RegularFile* file, size_t bufSize, off_t offset) {
+    if (!file->osfile.isTmpfsMount) {
+        return bufSize;
+    }
+
+    int fd = _regularfile_getOSBackedFD(file);
+
+    if (offset < 0) {
+        int flags = fcntl(fd, F_GETFL);
+        offset = lseek(fd, 0, (flags >= 0 && (flags & O_APPEND)) ? SEEK_END : SEEK_CUR);
+        if (offset < 0) {
+            return -errno;
+        }
+    }
+
+    uint64_t avail = 0;
+    ssize_t growth = _regularfile_getTmpfsGrowth(file, offset + bufSize, &avail);
+    if (growth != -ENOSPC) {
+        return growth < 0 ? growth : (ssize_t)bufSize;
+    }
+
+    /* Write as much as fits, like a filesystem that fills up during the write. */
+    struct stat statbuf = {0};
+    if (fstat(fd, &statbuf) < 0) {
+        return -errno;
+    }
+    off_t end = statbuf.st_size + avail;
+    if (end <= offset) {
+        return -ENOSPC;
+    }
+    return MIN(bufSize, (size_t)(end - offset));
+}
+
+/* Returns the number of bytes of a `bufSize` byte write at `offset` that fit within the block
+ * device `file`, or -ENOSPC if `offset` is at or past the end of the device. A negative `offset`
+ * means the file offset. Returns `bufSize` if `file` isn't a block device. */
+static ssize_t _regularfile_limitBlockDeviceWrite(RegularFile* file, size_t bufSize,
+                                                  off_t offset) {
+    uint64_t size = file->osfile.blockDeviceSize;
+    if (size == 0 || bufSize == 0) {
+        return bufSize;
+    }
+
+    if (offset < 0) {
+        int fd = _regularfile_getOSBackedFD(file);
+        int flags = fcntl(fd, F_GETFL);
+        offset = lseek(fd, 0, (flags >= 0 && (flags & O_APPEND)) ? SEEK_END : SEEK_CUR);
+        if (offset < 0) {
+            return -errno;
+        }
+    }
+
+    /* Like Linux, write as much as fits before the end of the device. */
+    if ((uint64_t)offset >= size) {
+        return -ENOSPC;
+    }
+    return MIN(bufSize, (size_t)(size - offset));
+}
+
 ssize_t regularfile_write(RegularFile* file, const void* buf, size_t bufSize) {
     MAGIC_ASSERT(file);
 
@@ -536,12 +904,24 @@ ssize_t regularfile_write(RegularFile* file, const void* buf, size_t bufSize) {
         return -EBADF;
     }
 
+    ssize_t limit = _regularfile_limitTmpfsWrite(file, bufSize, -1);
+    if (limit >= 0) {
+        limit = _regularfile_limitBlockDeviceWrite(file, limit, -1);
+    }
+    if (limit < 0) {
+        return limit;
+    }
+    bufSize = limit;
+
     trace("RegularFile %p will write %zu bytes to os-backed file %i at path '%s'", file, bufSize,
           _regularfile_getOSBackedFD(file), file->osfile.absPathAtOpen);
 
     /* TODO: this may block the shadow thread until we properly handle
      * os-backed files in non-blocking mode. */
     ssize_t result = write(_regularfile_getOSBackedFD(file), buf, bufSize);
+    if (result > 0) {
+        _regularfile_updateTmpfsUsage(file);
+    }
     return (result < 0) ? -errno : result;
 }
 
@@ -556,12 +936,24 @@ ssize_t regularfile_pwrite(RegularFile* file, const void* buf, size_t bufSize, o
         return -EBADF;
     }
 
+    ssize_t limit = _regularfile_limitTmpfsWrite(file, bufSize, offset);
+    if (limit >= 0) {
+        limit = _regularfile_limitBlockDeviceWrite(file, limit, offset);
+    }
+    if (limit < 0) {
+        return limit;
+    }
+    bufSize = limit;
+
     trace("RegularFile %p will pwrite %zu bytes to os-backed file %i offset %ld at path '%s'", file,
           bufSize, _regularfile_getOSBackedFD(file), offset, file->osfile.absPathAtOpen);
 
     /* TODO: this may block the shadow thread until we properly handle
      * os-backed files in non-blocking mode. */
     ssize_t result = pwrite(_regularfile_getOSBackedFD(file), buf, bufSize, offset);
+    if (result > 0) {
+        _regularfile_updateTmpfsUsage(file);
+    }
     return (result < 0) ? -errno : result;
 }
 
@@ -582,6 +974,9 @@ ssize_t regularfile_pwritev(RegularFile* file, const struct iovec* iov, int iovc
     /* TODO: this may block the shadow thread until we properly handle
      * os-backed files in non-blocking mode. */
     ssize_t result = pwritev(_regularfile_getOSBackedFD(file), iov, iovcnt, offset);
+    if (result > 0) {
+        _regularfile_updateTmpfsUsage(file);
+    }
     return (result < 0) ? -errno : result;
 }
 
@@ -605,6 +1000,9 @@ ssize_t regularfile_pwritev2(RegularFile* file, const struct iovec* iov, int iov
      * os-backed files in non-blocking mode. */
     ssize_t result =
         pwritev2(_regularfile_getOSBackedFD(file), iov, iovcnt, offset, flags);
+    if (result > 0) {
+        _regularfile_updateTmpfsUsage(file);
+    }
     return (result < 0) ? -errno : result;
 }
 #endif
@@ -619,7 +1017,30 @@ int regularfile_fstat(RegularFile* file, struct stat* statbuf) {
     trace("RegularFile %p fstat os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
     int result = fstat(_regularfile_getOSBackedFD(file), statbuf);
-    return (result < 0) ? -errno : result;
+    if (result < 0) {
+        return -errno;
+    }
+
+    _regularfile_fillBlockDeviceStat(file->osfile.blockDeviceSize, statbuf);
+    return result;
+}
+
+/* If the real path `realpath` is within one of the current host's tmpfs mounts, replaces the
+ * filesystem info in `statbuf` with the mount's info. */
+static void _regularfile_fillTmpfsStatfs(const char* realpath, struct statfs* statbuf) {
+    uint64_t size = 0, used = 0;
+    if (!_regularfile_getTmpfsUsage(realpath, &size, &used)) {
+        return;
+    }
+
+    /* Describe the mount rather than the filesystem that holds its files. */
+    const uint64_t blockSize = 4096;
+    statbuf->f_type = TMPFS_MAGIC;
+    statbuf->f_bsize = blockSize;
+    statbuf->f_frsize = blockSize;
+    statbuf->f_blocks = (size + blockSize - 1) / blockSize;
+    statbuf->f_bfree = (used < size ? size - used : 0) / blockSize;
+    statbuf->f_bavail = statbuf->f_bfree;
 }
 
 int regularfile_fstatfs(RegularFile* file, struct statfs* statbuf) {
@@ -632,7 +1053,12 @@ int regularfile_fstatfs(RegularFile* file, struct statfs* statbuf) {
     trace("RegularFile %p fstatfs os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
     int result = fstatfs(_regularfile_getOSBackedFD(file), statbuf);
-    return (result < 0) ? -errno : result;
+    if (result < 0) {
+        return -errno;
+    }
+
+    _regularfile_fillTmpfsStatfs(file->osfile.absPathAtOpen, statbuf);
+    return result;
 }
 
 int regularfile_fsync(RegularFile* file) {
@@ -655,6 +1081,10 @@ int regularfile_fchown(RegularFile* file, uid_t owner, gid_t group) {
         return -EBADF;
     }
 
+    if (file->osfile.isReadOnlyMount) {
+        return -EROFS;
+    }
+
     trace("RegularFile %p fchown os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
     int result = fchown(_regularfile_getOSBackedFD(file), owner, group);
@@ -668,6 +1098,10 @@ int regularfile_fchmod(RegularFile* file, mode_t mode) {
         return -EBADF;
     }
 
+    if (file->osfile.isReadOnlyMount) {
+        return -EROFS;
+    }
+
     trace("RegularFile %p fchmod os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
     int result = fchmod(_regularfile_getOSBackedFD(file), mode);
@@ -683,7 +1117,22 @@ int regularfile_ftruncate(RegularFile* file, off_t length) {
 
     trace("RegularFile %p ftruncate os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
+    /* Block devices have a fixed size. */
+    if (file->osfile.blockDeviceSize) {
+        return -EINVAL;
+    }
+
+    uint64_t avail = 0;
+    ssize_t growth = _regularfile_getTmpfsGrowth(file, length, &avail);
+    if (growth < 0) {
+        /* The mount counts the apparent size of its files, so they can't grow past its size. */
+        return growth == -ENOSPC ? -EFBIG : growth;
+    }
+
     int result = ftruncate(_regularfile_getOSBackedFD(file), length);
+    if (result == 0) {
+        _regularfile_updateTmpfsUsage(file);
+    }
     return (result < 0) ? -errno : result;
 }
 
@@ -696,7 +1145,23 @@ int regularfile_fallocate(RegularFile* file, int mode, off_t offset, off_t lengt
 
     trace("RegularFile %p fallocate os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
+    /* Block devices have a fixed size. */
+    if (file->osfile.blockDeviceSize && (uint64_t)offset + length > file->osfile.blockDeviceSize) {
+        return -EINVAL;
+    }
+
+    if (!(mode & FALLOC_FL_KEEP_SIZE)) {
+        uint64_t avail = 0;
+        ssize_t growth = _regularfile_getTmpfsGrowth(file, offset + length, &avail);
+        if (growth < 0) {
+            return growth;
+        }
+    }
+
     int result = fallocate(_regularfile_getOSBackedFD(file), mode, offset, length);
+    if (result == 0) {
+        _regularfile_updateTmpfsUsage(file);
+    }
     return (result < 0) ? -errno : result;
 }
 
@@ -734,6 +1199,10 @@ int regularfile_fsetxattr(RegularFile* file, const char* name, const void* value
         return -EBADF;
     }
 
+    if (file->osfile.isReadOnlyMount) {
+        return -EROFS;
+    }
+
     trace("RegularFile %p fsetxattr os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
     int result = fsetxattr(_regularfile_getOSBackedFD(file), name, value, size, flags);
@@ -773,6 +1242,10 @@ int regularfile_fremovexattr(RegularFile* file, const char* name) {
         return -EBADF;
     }
 
+    if (file->osfile.isReadOnlyMount) {
+        return -EROFS;
+    }
+
     trace("RegularFile %p fremovexattr os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
     int result = fremovexattr(_regularfile_getOSBackedFD(file), name);
@@ -823,12 +1296,19 @@ off_t regularfile_lseek(RegularFile* file, off_t offset, int whence) {
 int regularfile_getdents(RegularFile* file, struct linux_dirent* dirp, unsigned int count) {
     MAGIC_ASSERT(file);
 
+    // in-memory files are never directories
+    if (file->type == FILE_TYPE_IN_MEMORY) {
+        return -ENOTDIR;
+    }
+
     if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
         return -EBADF;
     }
 
     trace("RegularFile %p getdents os-backed file %i", file, _regularfile_getOSBackedFD(file));
 
+    // The directory position is kept by the native file description, so entries that are added
+    // or removed during iteration are handled the same way as in Linux.
     // getdents is not available for a direct call
     int result =
         (int)syscall(SYS_getdents, _regularfile_getOSBackedFD(file), dirp, count);
@@ -839,6 +1319,11 @@ int regularfile_getdents64(RegularFile* file, struct linux_dirent64* dirp,
                     unsigned int count) {
     MAGIC_ASSERT(file);
 
+    // in-memory files are never directories
+    if (file->type == FILE_TYPE_IN_MEMORY) {
+        return -ENOTDIR;
+    }
+
     if (!_fd_isValid(_regularfile_getOSBackedFD(file))) {
         return -EBADF;
     }
@@ -850,6 +1335,23 @@ int regularfile_getdents64(RegularFile* file, struct linux_dirent64* dirp,
     return (result < 0) ? -errno : result;
 }
 
+bool regularfile_getBlockDevice(RegularFile* file, uint64_t* size, uint32_t* sectorSize) {
+    MAGIC_ASSERT(file);
+
+    if (file->type == FILE_TYPE_IN_MEMORY || file->osfile.blockDeviceSize == 0) {
+        return false;
+    }
+
+    *size = file->osfile.blockDeviceSize;
+    *sectorSize = file->osfile.blockDeviceSectorSize;
+    return true;
+}
+
+bool regularfile_isInTmpfsMount(RegularFile* file) {
+    MAGIC_ASSERT(file);
+    return file->type != FILE_TYPE_IN_MEMORY && file->osfile.isTmpfsMount;
+}
+
 int regularfile_ioctl(RegularFile* file, unsigned long request, void* arg) {
     MAGIC_ASSERT(file);
 
@@ -937,7 +1439,24 @@ int regularfile_fstatat(RegularFile* dir, const char* pathname, struct stat* sta
 
     trace("RegularFile %p fstatat os-backed file %i, flags %d", dir, osFd, flags);
 
-    if (osFd == AT_FDCWD) {
+    /* Use the real file of a block device, or of a read-only or tmpfs mount. */
+    uint64_t blockDeviceSize = 0;
+    char* mountpath =
+        _regularfile_getBlockDevicePathAt(dir, pathname, workingDir, &blockDeviceSize);
+    if (!mountpath) {
+        mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    }
+
+    /* An empty path with AT_EMPTY_PATH refers to `dir` itself. */
+    if ((flags & AT_EMPTY_PATH) && pathname[0] == '\0' && dir &&
+        dir->type != FILE_TYPE_IN_MEMORY) {
+        blockDeviceSize = dir->osfile.blockDeviceSize;
+    }
+
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -948,7 +1467,12 @@ int regularfile_fstatat(RegularFile* dir, const char* pathname, struct stat* sta
         free((char*)pathnameTmp);
     }
 
-    return (result < 0) ? -errno : result;
+    if (result < 0) {
+        return -errno;
+    }
+
+    _regularfile_fillBlockDeviceStat(blockDeviceSize, statbuf);
+    return result;
 }
 
 int regularfile_fchownat(RegularFile* dir, const char* pathname, uid_t owner, gid_t group,
@@ -958,7 +1482,16 @@ int regularfile_fchownat(RegularFile* dir, const char* pathname, uid_t owner, gi
 
     trace("RegularFile %p fchownat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(dir, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -979,7 +1512,16 @@ int regularfile_fchmodat(RegularFile* dir, const char* pathname, mode_t mode, in
 
     trace("RegularFile %p fchmodat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(dir, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -1000,7 +1542,16 @@ int regularfile_futimesat(RegularFile* dir, const char* pathname, const struct t
 
     trace("RegularFile %p futimesat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(dir, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -1021,7 +1572,16 @@ int regularfile_utimensat(RegularFile* dir, const char* pathname, const struct t
 
     trace("RegularFile %p utimesat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(dir, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -1042,7 +1602,28 @@ int regularfile_faccessat(RegularFile* dir, const char* pathname, int mode, int
 
     trace("RegularFile %p faccessat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (dir && dir->type == FILE_TYPE_IN_MEMORY && pathname[0] == '\0' &&
+        (flags & AT_EMPTY_PATH)) {
+        /* Checking the in-memory file itself. Its contents are emulated by shadow and can only be
+         * read. */
+        return (mode & (W_OK | X_OK)) ? -EACCES : 0;
+    }
+
+    if ((mode & W_OK) && _regularfile_isInReadOnlyMount(dir, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a block device, or of a read-only or tmpfs mount. */
+    uint64_t blockDeviceSize = 0;
+    char* mountpath =
+        _regularfile_getBlockDevicePathAt(dir, pathname, workingDir, &blockDeviceSize);
+    if (!mountpath) {
+        mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    }
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -1063,7 +1644,16 @@ int regularfile_mkdirat(RegularFile* dir, const char* pathname, mode_t mode,
 
     trace("RegularFile %p mkdirat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(dir, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -1084,7 +1674,16 @@ int regularfile_mknodat(RegularFile* dir, const char* pathname, mode_t mode, dev
 
     trace("RegularFile %p mknodat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(dir, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -1107,11 +1706,25 @@ int regularfile_linkat(RegularFile* oldDir, const char* oldPath, RegularFile* ne
 
     trace("RegularFiles %p, %p linkat os-backed files %i, %i", oldDir, newDir, oldOsFd, newOsFd);
 
-    if (oldOsFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(newDir, newPath, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real files of read-only or tmpfs mounts. */
+    char* oldMountPath = _regularfile_getMountPathAt(oldDir, oldPath, workingDir);
+    if (oldMountPath) {
+        oldOsFd = -1;
+        oldPathTmp = oldMountPath;
+    } else if (oldOsFd == AT_FDCWD) {
         oldOsFd = -1;
         oldPathTmp = _regularfile_getAbsolutePath(NULL, oldPath, workingDir);
     }
-    if (newOsFd == AT_FDCWD) {
+
+    char* newMountPath = _regularfile_getMountPathAt(newDir, newPath, workingDir);
+    if (newMountPath) {
+        newOsFd = -1;
+        newPathTmp = newMountPath;
+    } else if (newOsFd == AT_FDCWD) {
         newOsFd = -1;
         newPathTmp = _regularfile_getAbsolutePath(NULL, newPath, workingDir);
     }
@@ -1135,13 +1748,32 @@ int regularfile_unlinkat(RegularFile* dir, const char* pathname, int flags,
 
     trace("RegularFile %p unlinkat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(dir, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
 
+    /* Unlinking a file in a tmpfs mount frees its space, unless it has other links. */
+    struct stat statbuf = {0};
+    char* tmpfsPath = _regularfile_getTmpfsRealPathAt(dir, pathname, workingDir);
+    bool removesTmpfsFile = tmpfsPath && lstat(tmpfsPath, &statbuf) == 0;
+
     int result = unlinkat(osFd, pathnameTmp, flags);
 
+    if (result == 0 && removesTmpfsFile) {
+        _regularfile_removeTmpfsFile(tmpfsPath, &statbuf);
+    }
+    free(tmpfsPath);
+
     if (pathnameTmp != pathname) {
         free((char*)pathnameTmp);
     }
@@ -1156,7 +1788,16 @@ int regularfile_symlinkat(RegularFile* dir, const char* linkpath, const char* ta
 
     trace("RegularFile %p symlinkat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(dir, linkpath, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real file of a tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, linkpath, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        linkpathTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         linkpathTmp = _regularfile_getAbsolutePath(NULL, linkpath, workingDir);
     }
@@ -1177,7 +1818,12 @@ ssize_t regularfile_readlinkat(RegularFile* dir, const char* pathname, char* buf
 
     trace("RegularFile %p readlinkat os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    /* Use the real file of a read-only or tmpfs mount. */
+    char* mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -1200,18 +1846,47 @@ int regularfile_renameat2(RegularFile* oldDir, const char* oldPath, RegularFile*
 
     trace("RegularFiles %p, %p renameat2 os-backed files %i, %i", oldDir, newDir, oldOsFd, newOsFd);
 
-    if (oldOsFd == AT_FDCWD) {
+    if (_regularfile_isInReadOnlyMount(oldDir, oldPath, workingDir) ||
+        _regularfile_isInReadOnlyMount(newDir, newPath, workingDir)) {
+        return -EROFS;
+    }
+
+    /* Use the real files of read-only or tmpfs mounts. */
+    char* oldMountPath = _regularfile_getMountPathAt(oldDir, oldPath, workingDir);
+    if (oldMountPath) {
+        oldOsFd = -1;
+        oldPathTmp = oldMountPath;
+    } else if (oldOsFd == AT_FDCWD) {
         oldOsFd = -1;
         oldPathTmp = _regularfile_getAbsolutePath(NULL, oldPath, workingDir);
     }
 
-    if (newOsFd == AT_FDCWD) {
+    char* newMountPath = _regularfile_getMountPathAt(newDir, newPath, workingDir);
+    if (newMountPath) {
+        newOsFd = -1;
+        newPathTmp = newMountPath;
+    } else if (newOsFd == AT_FDCWD) {
         newOsFd = -1;
         newPathTmp = _regularfile_getAbsolutePath(NULL, newPath, workingDir);
     }
 
+    /* Renaming over a file in a tmpfs mount frees its space, unless the two are exchanged. */
+    struct stat oldStatbuf = {0}, newStatbuf = {0};
+    char* tmpfsPath = (flags & RENAME_EXCHANGE)
+                          ? NULL
+                          : _regularfile_getTmpfsRealPathAt(newDir, newPath, workingDir);
+    bool removesTmpfsFile =
+        tmpfsPath && lstat(tmpfsPath, &newStatbuf) == 0 &&
+        fstatat(oldOsFd, oldPathTmp, &oldStatbuf, AT_SYMLINK_NOFOLLOW) == 0 &&
+        oldStatbuf.st_ino != newStatbuf.st_ino;
+
     int result = (int)syscall(SYS_renameat2, oldOsFd, oldPathTmp, newOsFd, newPathTmp, flags);
 
+    if (result == 0 && removesTmpfsFile) {
+        _regularfile_removeTmpfsFile(tmpfsPath, &newStatbuf);
+    }
+    free(tmpfsPath);
+
     if (oldPathTmp != oldPath) {
         free((char*)oldPathTmp);
     }
@@ -1230,7 +1905,24 @@ int regularfile_statx(RegularFile* dir, const char* pathname, int flags, unsigne
 
     trace("RegularFile %p statx os-backed file %i", dir, osFd);
 
-    if (osFd == AT_FDCWD) {
+    /* Use the real file of a block device, or of a read-only or tmpfs mount. */
+    uint64_t blockDeviceSize = 0;
+    char* mountpath =
+        _regularfile_getBlockDevicePathAt(dir, pathname, workingDir, &blockDeviceSize);
+    if (!mountpath) {
+        mountpath = _regularfile_getMountPathAt(dir, pathname, workingDir);
+    }
+
+    /* An empty path with AT_EMPTY_PATH refers to `dir` itself. */
+    if ((flags & AT_EMPTY_PATH) && pathname[0] == '\0' && dir &&
+        dir->type != FILE_TYPE_IN_MEMORY) {
+        blockDeviceSize = dir->osfile.blockDeviceSize;
+    }
+
+    if (mountpath) {
+        osFd = -1;
+        pathnameTmp = mountpath;
+    } else if (osFd == AT_FDCWD) {
         osFd = -1;
         pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
     }
@@ -1241,6 +1933,101 @@ int regularfile_statx(RegularFile* dir, const char* pathname, int flags, unsigne
         free((char*)pathnameTmp);
     }
 
-    return (result < 0) ? -errno : result;
+    if (result < 0) {
+        return -errno;
+    }
+
+    /* Like `_regularfile_fillBlockDeviceStat()`. */
+    if (blockDeviceSize) {
+        statxbuf->stx_mode = S_IFBLK | (statxbuf->stx_mode & ~S_IFMT);
+        statxbuf->stx_size = 0;
+        statxbuf->stx_blocks = 0;
+    }
+    return result;
 }
 #endif
+
+int regularfile_statfs(const char* pathname, struct statfs* statbuf, const char* workingDir) {
+    trace("RegularFile statfs path '%s'", pathname);
+
+    /* Use the real file of a read-only or tmpfs mount. */
+    char* pathnameTmp = _regularfile_getMountPathAt(NULL, pathname, workingDir);
+    if (!pathnameTmp) {
+        pathnameTmp = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
+    }
+
+    int result = statfs(pathnameTmp, statbuf);
+    if (result == 0) {
+        _regularfile_fillTmpfsStatfs(pathnameTmp, statbuf);
+    }
+
+    free(pathnameTmp);
+
+    return (result < 0) ? -errno : result;
+}
+
+/* Returns the path of `pathname`, relative to `workingDir`, on the real filesystem: its real path
+ * within a read-only or tmpfs mount, or otherwise its absolute path. The returned string must be
+ * freed. */
+static char* _regularfile_getRealPath(const char* pathname, const char* workingDir) {
+    char* realpath = _regularfile_getMountPathAt(NULL, pathname, workingDir);
+    if (!realpath) {
+        realpath = _regularfile_getAbsolutePath(NULL, pathname, workingDir);
+    }
+    return realpath;
+}
+
+int regularfile_setxattr(const char* pathname, const char* name, const void* value, size_t size,
+                         int flags, bool followSymlinks, const char* workingDir) {
+    trace("RegularFile setxattr path '%s'", pathname);
+
+    if (_regularfile_isInReadOnlyMount(NULL, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    char* realpath = _regularfile_getRealPath(pathname, workingDir);
+    int result = followSymlinks ? setxattr(realpath, name, value, size, flags)
+                                : lsetxattr(realpath, name, value, size, flags);
+    free(realpath);
+
+    return (result < 0) ? -errno : result;
+}
+
+ssize_t regularfile_getxattr(const char* pathname, const char* name, void* value, size_t size,
+                             bool followSymlinks, const char* workingDir) {
+    trace("RegularFile getxattr path '%s'", pathname);
+
+    char* realpath = _regularfile_getRealPath(pathname, workingDir);
+    ssize_t result = followSymlinks ? getxattr(realpath, name, value, size)
+                                    : lgetxattr(realpath, name, value, size);
+    free(realpath);
+
+    return (result < 0) ? -errno : result;
+}
+
+ssize_t regularfile_listxattr(const char* pathname, char* list, size_t size, bool followSymlinks,
+                              const char* workingDir) {
+    trace("RegularFile listxattr path '%s'", pathname);
+
+    char* realpath = _regularfile_getRealPath(pathname, workingDir);
+    ssize_t result =
+        followSymlinks ? listxattr(realpath, list, size) : llistxattr(realpath, list, size);
+    free(realpath);
+
+    return (result < 0) ? -errno : result;
+}
+
+int regularfile_removexattr(const char* pathname, const char* name, bool followSymlinks,
+                            const char* workingDir) {
+    trace("RegularFile removexattr path '%s'", pathname);
+
+    if (_regularfile_isInReadOnlyMount(NULL, pathname, workingDir)) {
+        return -EROFS;
+    }
+
+    char* realpath = _regularfile_getRealPath(pathname, workingDir);
+    int result = followSymlinks ? removexattr(realpath, name) : lremovexattr(realpath, name);
+    free(realpath);
+
+    return (result < 0) ? -errno : result;
+}
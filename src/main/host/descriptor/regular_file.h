@@ -7,7 +7,9 @@
 #define SRC_MAIN_HOST_DESCRIPTOR_FILE_H_
 
 #include <poll.h>
+#include <stdbool.h>
 #include <stddef.h>
+#include <stdint.h>
 #include <sys/stat.h>
 #include <sys/statfs.h>
 #include <sys/syscall.h>
@@ -76,6 +78,14 @@ int regularfile_getShadowFlags(RegularFile* file);
 /* Get the type of file. */
 FileType regularfile_getType(RegularFile* file);
 
+/* If the file is one of the host's block devices, gets the size and sector size of the device and
+ * returns true. Returns false otherwise. */
+bool regularfile_getBlockDevice(RegularFile* file, uint64_t* size, uint32_t* sectorSize);
+
+/* Returns true if the file is within one of the host's tmpfs mounts. Writes to such files must go
+ * through the `regularfile_*write*()` functions so that the mount's usage is kept up to date. */
+bool regularfile_isInTmpfsMount(RegularFile* file);
+
 /* Returns the linux-backed fd that shadow uses to perform the file operations.  */
 int regularfile_getOSBackedFD(RegularFile* file);
 
@@ -83,13 +93,13 @@ int regularfile_getOSBackedFD(RegularFile* file);
 // Operations that require a non-null RegularFile*
 // ****************************************
 
-ssize_t regularfile_read(RegularFile* file, const Host* host, void* buf, size_t bufSize);
-ssize_t regularfile_pread(RegularFile* file, const Host* host, void* buf, size_t bufSize,
+ssize_t regularfile_read(RegularFile* file, const Process* process, void* buf, size_t bufSize);
+ssize_t regularfile_pread(RegularFile* file, const Process* process, void* buf, size_t bufSize,
                           off_t offset);
-ssize_t regularfile_preadv(RegularFile* file, const Host* host, const struct iovec* iov, int iovcnt,
-                           off_t offset);
+ssize_t regularfile_preadv(RegularFile* file, const Process* process, const struct iovec* iov,
+                           int iovcnt, off_t offset);
 #ifdef SYS_preadv2
-ssize_t regularfile_preadv2(RegularFile* file, const Host* host, const struct iovec* iov,
+ssize_t regularfile_preadv2(RegularFile* file, const Process* process, const struct iovec* iov,
                             int iovcnt, off_t offset, int flags);
 #endif
 ssize_t regularfile_write(RegularFile* file, const void* buf, size_t bufSize);
@@ -155,5 +165,14 @@ int regularfile_renameat2(RegularFile* olddir, const char* oldpath, RegularFile*
 int regularfile_statx(RegularFile* dir, const char* pathname, int flags, unsigned int mask,
                       struct statx* statxbuf, const char* workingDir);
 #endif
+int regularfile_statfs(const char* pathname, struct statfs* statbuf, const char* workingDir);
+int regularfile_setxattr(const char* pathname, const char* name, const void* value, size_t size,
+                         int flags, bool followSymlinks, const char* workingDir);
+ssize_t regularfile_getxattr(const char* pathname, const char* name, void* value, size_t size,
+                             bool followSymlinks, const char* workingDir);
+ssize_t regularfile_listxattr(const char* pathname, char* list, size_t size, bool followSymlinks,
+                              const char* workingDir);
+int regularfile_removexattr(const char* pathname, const char* name, bool followSymlinks,
+                            const char* workingDir);
 
 #endif /* SRC_MAIN_HOST_DESCRIPTOR_FILE_H_ */
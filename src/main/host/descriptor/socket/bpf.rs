@@ -0,0 +1,377 @@
+//! A classic BPF (cBPF) interpreter for socket filters attached with `SO_ATTACH_FILTER`.
+//!
+//! See "Documentation/networking/filter.rst" and "net/core/filter.c" in the linux sources. Linux's
+//! ancillary loads (offsets at or above `SKF_AD_OFF`) aren't supported and are treated as loads
+//! past the end of the packet.
+
+use linux_api::errno::Errno;
+
+/// The maximum number of instructions in a program.
+pub const BPF_MAXINSNS: usize = 4096;
+
+/// The number of words in the scratch memory.
+const BPF_MEMWORDS: usize = 16;
+
+// instruction classes
+const BPF_LD: u16 = 0x00;
+const BPF_LDX: u16 = 0x01;
+const BPF_ST: u16 = 0x02;
+const BPF_STX: u16 = 0x03;
+const BPF_ALU: u16 = 0x04;
+const BPF_JMP: u16 = 0x05;
+const BPF_RET: u16 = 0x06;
+const BPF_MISC: u16 = 0x07;
+
+// load sizes
+const BPF_W: u16 = 0x00;
+const BPF_H: u16 = 0x08;
+const BPF_B: u16 = 0x10;
+
+// load modes
+const BPF_IMM: u16 = 0x00;
+const BPF_ABS: u16 = 0x20;
+const BPF_IND: u16 = 0x40;
+const BPF_MEM: u16 = 0x60;
+const BPF_LEN: u16 = 0x80;
+const BPF_MSH: u16 = 0xa0;
+
+// alu operations
+const BPF_ADD: u16 = 0x00;
+const BPF_SUB: u16 = 0x10;
+const BPF_MUL: u16 = 0x20;
+const BPF_DIV: u16 = 0x30;
+const BPF_OR: u16 = 0x40;
+const BPF_AND: u16 = 0x50;
+const BPF_LSH: u16 = 0x60;
+const BPF_RSH: u16 = 0x70;
+const BPF_NEG: u16 = 0x80;
+const BPF_MOD: u16 = 0x90;
+const BPF_XOR: u16 = 0xa0;
+
+// jump operations
+const BPF_JA: u16 = 0x00;
+const BPF_JEQ: u16 = 0x10;
+const BPF_JGT: u16 = 0x20;
+const BPF_JGE: u16 = 0x30;
+const BPF_JSET: u16 = 0x40;
+
+// operand sources
+const BPF_K: u16 = 0x00;
+const BPF_X: u16 = 0x08;
+
+// return value sources
+const BPF_RVAL_A: u16 = 0x10;
+
+// misc operations
+const BPF_TAX: u16 = 0x00;
+const BPF_TXA: u16 = 0x80;
+
+/// A validated classic BPF program.
+#[derive(Debug, Clone)]
+pub struct BpfProgram {
+    insns: Vec<libc::sock_filter>,
+}
+
+impl BpfProgram {
+    /// Validate a program. Like linux, returns `EINVAL` if the program is empty or too long, has
+    /// an unknown opcode, jumps past the end of the program, divides by a constant zero, uses an
+    /// out-of-range scratch memory slot, or doesn't end with a return instruction.
+    pub fn new(insns: Vec<libc::sock_filter>) -> Result<Self, Errno> {
+        if insns.is_empty() || insns.len() > BPF_MAXINSNS {
+            return Err(Errno::EINVAL);
+        }
+
+        for (pc, insn) in insns.iter().enumerate() {
+            // the number of instructions following this one
+            let remaining = insns.len() - pc - 1;
+
+            match insn.code {
+                // loads and stores
+                x if x == BPF_LD | BPF_W | BPF_ABS
+                    || x == BPF_LD | BPF_H | BPF_ABS
+                    || x == BPF_LD | BPF_B | BPF_ABS
+                    || x == BPF_LD | BPF_W | BPF_IND
+                    || x == BPF_LD | BPF_H | BPF_IND
+                    || x == BPF_LD | BPF_B | BPF_IND
+                    || x == BPF_LD | BPF_W | BPF_LEN
+                    || x == BPF_LD | BPF_IMM
+                    || x == BPF_LDX | BPF_W | BPF_LEN
+                    || x == BPF_LDX | BPF_B | BPF_MSH
+                    || x == BPF_LDX | BPF_IMM => {}
+                x if x == BPF_LD | BPF_MEM
+                    || x == BPF_LDX | BPF_MEM
+                    || x == BPF_ST
+                    || x == BPF_STX =>
+                {
+                    if insn.k as usize >= BPF_MEMWORDS {
+                        return Err(Errno::EINVAL);
+                    }
+                }
+                // alu
+                x if x == BPF_ALU | BPF_NEG => {}
+                x if x & !0xf8 == BPF_ALU => {
+                    let op = x & 0xf0;
+                    if ![
+                        BPF_ADD, BPF_SUB, BPF_MUL, BPF_DIV, BPF_OR, BPF_AND, BPF_LSH, BPF_RSH,
+                        BPF_MOD, BPF_XOR,
+                    ]
+                    .contains(&op)
+                    {
+                        return Err(Errno::EINVAL);
+                    }
+                    if x & BPF_X == BPF_K && (op == BPF_DIV || op == BPF_MOD) && insn.k == 0 {
+                        return Err(Errno::EINVAL);
+                    }
+                }
+                // jumps
+                x if x == BPF_JMP | BPF_JA => {
+                    if insn.k as usize >= remaining {
+                        return Err(Errno::EINVAL);
+                    }
+                }
+                x if x & !0xf8 == BPF_JMP => {
+                    if ![BPF_JEQ, BPF_JGT, BPF_JGE, BPF_JSET].contains(&(x & 0xf0)) {
+                        return Err(Errno::EINVAL);
+                    }
+                    if insn.jt as usize >= remaining || insn.jf as usize >= remaining {
+                        return Err(Errno::EINVAL);
+                    }
+                }
+                // returns and misc
+                x if x == BPF_RET | BPF_K
+                    || x == BPF_RET | BPF_RVAL_A
+                    || x == BPF_MISC | BPF_TAX
+                    || x == BPF_MISC | BPF_TXA => {}
+                _ => return Err(Errno::EINVAL),
+            }
+        }
+
+        // the program must always terminate with a return
+        if insns.last().unwrap().code & 0x07 != BPF_RET {
+            return Err(Errno::EINVAL);
+        }
+
+        Ok(Self { insns })
+    }
+
+    /// Run the program on a packet, returning the number of bytes of the packet to keep. A return
+    /// value of 0 means that the packet should be dropped.
+    pub fn run(&self, packet: &[u8]) -> u32 {
+        let mut a: u32 = 0;
+        let mut x: u32 = 0;
+        let mut mem = [0u32; BPF_MEMWORDS];
+        let mut pc = 0;
+
+        // loads past the end of the packet abort the program and drop the packet
+        let load = |offset: u32, size: u16| -> Option<u32> {
+            let offset = usize::try_from(offset).ok()?;
+            let len = match size {
+                BPF_W => 4,
+                BPF_H => 2,
+                _ => 1,
+            };
+            let bytes = packet.get(offset..offset.checked_add(len)?)?;
+            Some(bytes.iter().fold(0, |acc, b| (acc << 8) | u32::from(*b)))
+        };
+
+        loop {
+            // the program was validated so the pc will always be in range
+            let insn = &self.insns[pc];
+            pc += 1;
+
+            let code = insn.code;
+            let k = insn.k;
+
+            match code & 0x07 {
+                BPF_LD => {
+                    a = match code & 0xe0 {
+                        BPF_ABS => match load(k, code & 0x18) {
+                            Some(val) => val,
+                            None => return 0,
+                        },
+                        BPF_IND => match load(x.wrapping_add(k), code & 0x18) {
+                            Some(val) => val,
+                            None => return 0,
+                        },
+                        BPF_LEN => packet.len() as u32,
+                        BPF_MEM => mem[k as usize],
+                        _ => k,
+                    };
+                }
+                BPF_LDX => {
+                    x = match code & 0xe0 {
+                        BPF_LEN => packet.len() as u32,
+                        BPF_MEM => mem[k as usize],
+                        // the length of the IPv4 header at offset k
+                        BPF_MSH => match load(k, BPF_B) {
+                            Some(val) => (val & 0xf) << 2,
+                            None => return 0,
+                        },
+                        _ => k,
+                    };
+                }
+                BPF_ST => mem[k as usize] = a,
+                BPF_STX => mem[k as usize] = x,
+                BPF_ALU => {
+                    let operand = if code & BPF_X != 0 { x } else { k };
+                    a = match code & 0xf0 {
+                        BPF_ADD => a.wrapping_add(operand),
+                        BPF_SUB => a.wrapping_sub(operand),
+                        BPF_MUL => a.wrapping_mul(operand),
+                        BPF_DIV | BPF_MOD if operand == 0 => return 0,
+                        BPF_DIV => a / operand,
+                        BPF_MOD => a % operand,
+                        BPF_OR => a | operand,
+                        BPF_AND => a & operand,
+                        BPF_LSH => a.checked_shl(operand).unwrap_or(0),
+                        BPF_RSH => a.checked_shr(operand).unwrap_or(0),
+                        BPF_NEG => a.wrapping_neg(),
+                        BPF_XOR => a ^ operand,
+                        _ => unreachable!(),
+                    };
+                }
+                BPF_JMP => {
+                    let operand = if code & BPF_X != 0 { x } else { k };
+                    let cond = match code & 0xf0 {
+                        BPF_JA => {
+                            pc += k as usize;
+                            continue;
+                        }
+                        BPF_JEQ => a == operand,
+                        BPF_JGT => a > operand,
+                        BPF_JGE => a >= operand,
+                        BPF_JSET => a & operand != 0,
+                        _ => unreachable!(),
+                    };
+                    pc += usize::from(if cond { insn.jt } else { insn.jf });
+                }
+                BPF_RET => {
+                    return if code & BPF_RVAL_A != 0 { a } else { k };
+                }
+                BPF_MISC => {
+                    if code & 0xf8 == BPF_TXA {
+                        a = x;
+                    } else {
+                        x = a;
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stmt(code: u16, k: u32) -> libc::sock_filter {
+        libc::sock_filter {
+            code,
+            jt: 0,
+            jf: 0,
+            k,
+        }
+    }
+
+    fn jump(code: u16, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+        libc::sock_filter { code, jt, jf, k }
+    }
+
+    /// A filter matching IPv4 udp packets (without an ethernet header) with a destination port of
+    /// 53, as would be generated by tcpdump for "udp dst port 53".
+    fn udp_dst_port_53() -> BpfProgram {
+        BpfProgram::new(vec![
+            // protocol
+            stmt(BPF_LD | BPF_B | BPF_ABS, 9),
+            jump(BPF_JMP | BPF_JEQ | BPF_K, libc::IPPROTO_UDP as u32, 0, 6),
+            // fragment offset
+            stmt(BPF_LD | BPF_H | BPF_ABS, 6),
+            jump(BPF_JMP | BPF_JSET | BPF_K, 0x1fff, 4, 0),
+            // header length, then destination port
+            stmt(BPF_LDX | BPF_B | BPF_MSH, 0),
+            stmt(BPF_LD | BPF_H | BPF_IND, 2),
+            jump(BPF_JMP | BPF_JEQ | BPF_K, 53, 0, 1),
+            stmt(BPF_RET | BPF_K, 262144),
+            stmt(BPF_RET | BPF_K, 0),
+        ])
+        .unwrap()
+    }
+
+    fn udp_packet(dst_port: u16) -> Vec<u8> {
+        let mut packet = vec![0u8; 28];
+        packet[0] = 0x45;
+        packet[9] = libc::IPPROTO_UDP as u8;
+        packet[22..24].copy_from_slice(&dst_port.to_be_bytes());
+        packet
+    }
+
+    #[test]
+    fn filter_port() {
+        let filter = udp_dst_port_53();
+        assert_eq!(filter.run(&udp_packet(53)), 262144);
+        assert_eq!(filter.run(&udp_packet(54)), 0);
+
+        // a tcp packet
+        let mut packet = udp_packet(53);
+        packet[9] = libc::IPPROTO_TCP as u8;
+        assert_eq!(filter.run(&packet), 0);
+
+        // a truncated packet
+        assert_eq!(filter.run(&udp_packet(53)[..22]), 0);
+    }
+
+    #[test]
+    fn alu_and_scratch_memory() {
+        let filter = BpfProgram::new(vec![
+            stmt(BPF_LD | BPF_W | BPF_LEN, 0),
+            stmt(BPF_ALU | BPF_MUL | BPF_K, 3),
+            stmt(BPF_ST, 4),
+            stmt(BPF_LDX | BPF_IMM, 2),
+            stmt(BPF_LD | BPF_MEM, 4),
+            stmt(BPF_ALU | BPF_SUB | BPF_X, 0),
+            stmt(BPF_RET | BPF_RVAL_A, 0),
+        ])
+        .unwrap();
+        assert_eq!(filter.run(&[0; 10]), 28);
+    }
+
+    #[test]
+    fn invalid_programs() {
+        let ret = stmt(BPF_RET | BPF_K, 0);
+
+        // empty and too long
+        assert_eq!(BpfProgram::new(vec![]).unwrap_err(), Errno::EINVAL);
+        assert_eq!(
+            BpfProgram::new(vec![ret; BPF_MAXINSNS + 1]).unwrap_err(),
+            Errno::EINVAL
+        );
+        // unknown opcode
+        assert_eq!(
+            BpfProgram::new(vec![stmt(0xff, 0), ret]).unwrap_err(),
+            Errno::EINVAL
+        );
+        // doesn't end with a return
+        assert_eq!(
+            BpfProgram::new(vec![stmt(BPF_LD | BPF_IMM, 0)]).unwrap_err(),
+            Errno::EINVAL
+        );
+        // jumps past the end
+        assert_eq!(
+            BpfProgram::new(vec![jump(BPF_JMP | BPF_JEQ | BPF_K, 0, 1, 0), ret]).unwrap_err(),
+            Errno::EINVAL
+        );
+        // division by a constant zero
+        assert_eq!(
+            BpfProgram::new(vec![stmt(BPF_ALU | BPF_DIV | BPF_K, 0), ret]).unwrap_err(),
+            Errno::EINVAL
+        );
+        // scratch memory out of range
+        assert_eq!(
+            BpfProgram::new(vec![stmt(BPF_ST, BPF_MEMWORDS as u32), ret]).unwrap_err(),
+            Errno::EINVAL
+        );
+
+        assert!(BpfProgram::new(vec![ret]).is_ok());
+    }
+}
@@ -1,20 +1,24 @@
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::net::{Ipv4Addr, SocketAddrV4};
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
 use linux_api::errno::Errno;
 use linux_api::ioctls::IoctlRequest;
+use linux_api::signal::{siginfo_t, Signal};
 use linux_api::socket::Shutdown;
 use nix::sys::socket::{MsgFlags, SockaddrIn};
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::core::worker::Worker;
 use crate::cshadow as c;
 use crate::host::descriptor::listener::{StateListenHandle, StateListenerFilter};
 use crate::host::descriptor::socket::inet::{self, InetSocket};
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket};
+use crate::host::descriptor::socket::{
+    IgnoredSockopts, RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket,
+};
 use crate::host::descriptor::{
     CompatFile, File, FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
 };
@@ -22,6 +26,7 @@ use crate::host::host::Host;
 use crate::host::memory_manager::MemoryManager;
 use crate::host::network::interface::FifoPacketPriority;
 use crate::host::network::namespace::NetworkNamespace;
+use crate::host::process::ProcessId;
 use crate::host::syscall::io::{write_partial, IoVec};
 use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
 use crate::host::thread::ThreadId;
@@ -37,6 +42,21 @@ pub struct LegacyTcpSocket {
     has_open_file: bool,
     /// Did the last connect() call block, and if so what thread?
     thread_of_blocked_connect: Option<ThreadId>,
+    /// The process (or process group if negative) that is sent a SIGURG when urgent data arrives,
+    /// as set by `fcntl(F_SETOWN)`. A value of 0 means there is no owner.
+    owner: libc::pid_t,
+    /// The `SO_RCVTIMEO` timeout for blocking receives.
+    recv_timeout: Option<SimulationTime>,
+    /// The `SO_SNDTIMEO` timeout for blocking sends.
+    send_timeout: Option<SimulationTime>,
+    /// The `SO_LINGER` timeout. If set, `close()` blocks until the sent data has been acknowledged
+    /// or the timeout expires. A zero timeout aborts the connection instead.
+    linger: Option<SimulationTime>,
+    /// Was `SO_REUSEPORT` set? If so, binding allows the socket to share its address with other
+    /// `SO_REUSEPORT` sockets.
+    reuse_port: bool,
+    /// Unsupported socket options that were set, if they're being ignored.
+    ignored_sockopts: IgnoredSockopts,
     _counter: ObjectCounter,
 }
 
@@ -65,6 +85,12 @@ impl LegacyTcpSocket {
             socket: HostTreePointer::new(legacy_tcp),
             has_open_file: false,
             thread_of_blocked_connect: None,
+            owner: 0,
+            recv_timeout: None,
+            send_timeout: None,
+            linger: None,
+            reuse_port: false,
+            ignored_sockopts: IgnoredSockopts::default(),
             _counter: ObjectCounter::new("LegacyTcpSocket"),
         };
 
@@ -119,6 +145,14 @@ impl LegacyTcpSocket {
         FileMode::READ | FileMode::WRITE
     }
 
+    pub fn owner(&self) -> libc::pid_t {
+        self.owner
+    }
+
+    pub fn set_owner(&mut self, owner: libc::pid_t) {
+        self.owner = owner;
+    }
+
     pub fn has_open_file(&self) -> bool {
         self.has_open_file
     }
@@ -135,7 +169,7 @@ impl LegacyTcpSocket {
     pub fn push_in_packet(
         &mut self,
         packet: PacketRc,
-        _cb_queue: &mut CallbackQueue,
+        cb_queue: &mut CallbackQueue,
         _recv_time: EmulatedTime,
     ) {
         Worker::with_active_host(|host| {
@@ -145,6 +179,30 @@ impl LegacyTcpSocket {
             };
         })
         .unwrap();
+
+        let new_urgent_data = unsafe { c::tcp_takeUrgentSignal(self.as_legacy_tcp()) } != 0;
+
+        if new_urgent_data && self.owner != 0 {
+            // Currently every emulated process is in its own process group, where pgid=pid (see
+            // the `kill` syscall handler).
+            let Ok(owner) = ProcessId::try_from(self.owner.unsigned_abs()) else {
+                return;
+            };
+
+            // signal the owner after the socket is no longer borrowed
+            cb_queue.add(move |_| {
+                Worker::with_active_host(|host| {
+                    let Some(process) = host.process_borrow(owner) else {
+                        log::debug!("Can't send SIGURG to process {owner}; it no longer exists");
+                        return;
+                    };
+                    let process = process.borrow(host.root());
+                    let siginfo = siginfo_t::new_for_kill(Signal::SIGURG, 0, 0);
+                    process.signal(host, None, &siginfo);
+                })
+                .unwrap();
+            });
+        }
     }
 
     pub fn pull_out_packet(&mut self, _cb_queue: &mut CallbackQueue) -> Option<PacketRc> {
@@ -185,6 +243,42 @@ impl LegacyTcpSocket {
         self.peek_packet().is_some()
     }
 
+    pub fn recv_timeout(&self) -> Option<SimulationTime> {
+        self.recv_timeout
+    }
+
+    pub fn send_timeout(&self) -> Option<SimulationTime> {
+        self.send_timeout
+    }
+
+    /// Start a graceful close if `SO_LINGER` was set with a non-zero timeout. This shuts down the
+    /// socket for writing, and returns the linger timeout if `close()` should block until the
+    /// socket has the [`FileState::SOCKET_OUTPUT_ACKED`] state.
+    pub fn start_linger(&mut self) -> Option<SimulationTime> {
+        let timeout = self.linger.filter(|x| !x.is_zero())?;
+
+        // closing with unread data resets the connection, so there's nothing to wait for
+        if unsafe { c::legacysocket_getInputBufferLength(self.as_legacy_socket()) } > 0 {
+            return None;
+        }
+
+        let errcode = Worker::with_active_host(|host| unsafe {
+            c::tcp_shutdown(self.as_legacy_tcp(), host, libc::SHUT_WR)
+        })
+        .unwrap();
+
+        // the socket isn't connected
+        if errcode < 0 {
+            return None;
+        }
+
+        if self.state().contains(FileState::SOCKET_OUTPUT_ACKED) {
+            return None;
+        }
+
+        Some(timeout)
+    }
+
     pub fn getsockname(&self) -> Result<Option<SockaddrIn>, Errno> {
         let mut ip: libc::in_addr_t = 0;
         let mut port: libc::in_port_t = 0;
@@ -221,6 +315,40 @@ impl LegacyTcpSocket {
         Ok(Some(addr.into()))
     }
 
+    pub fn proc_net_entry(&self) -> inet::ProcNetEntry {
+        let unspecified = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+
+        let local_addr = self.getsockname().unwrap().unwrap();
+        let peer_addr = match self.getpeername() {
+            Ok(addr) => addr.unwrap().into(),
+            Err(_) => unspecified,
+        };
+
+        let mut info: c::tcp_info = shadow_pod::zeroed();
+        unsafe { c::tcp_getInfo(self.as_legacy_tcp(), &mut info) };
+
+        inet::ProcNetEntry {
+            local_addr: local_addr.into(),
+            peer_addr,
+            state: info.tcpi_state,
+            // like linux, this includes both unsent and unacknowledged bytes
+            tx_queue: unsafe { c::tcp_getOutputBufferLength(self.as_legacy_tcp()) }
+                .try_into()
+                .unwrap(),
+            // like linux, this doesn't include out-of-order bytes
+            rx_queue: unsafe { c::legacysocket_getInputBufferLength(self.as_legacy_socket()) }
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    /// The state of the socket's congestion control algorithm.
+    pub fn congestion_state(&self) -> c::ShadowTcpCcState {
+        let mut state = shadow_pod::zeroed();
+        unsafe { c::tcp_getCongestionState(self.as_legacy_tcp(), &mut state) };
+        state
+    }
+
     pub fn address_family(&self) -> linux_api::socket::AddressFamily {
         linux_api::socket::AddressFamily::AF_INET
     }
@@ -274,12 +402,15 @@ impl LegacyTcpSocket {
         // this will allow us to receive packets from any peer
         let peer_addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
 
+        let reuse_port = socket.borrow().reuse_port;
+
         // associate the socket
         let (addr, handle) = inet::associate_socket(
             InetSocket::LegacyTcp(Arc::clone(socket)),
             addr,
             peer_addr,
             /* check_generic_peer= */ true,
+            reuse_port,
             net_ns,
             rng,
         )?;
@@ -360,11 +491,14 @@ impl LegacyTcpSocket {
             flags.insert(MsgFlags::MSG_DONTWAIT);
         }
 
+        // for MSG_OOB, the last byte of the message is the urgent byte
+        let message_len: usize = args.iovs.iter().map(|iov| iov.len).sum();
+
         // run in a closure so that an early return doesn't skip checking if we should block
         let result = (|| {
             let mut bytes_sent = 0;
 
-            for iov in args.iovs {
+            for iov in args.iovs.iter() {
                 let errcode = unsafe { c::tcp_getConnectionError(tcp) };
 
                 log::trace!("Connection error state is currently {errcode}");
@@ -392,8 +526,22 @@ impl LegacyTcpSocket {
                     } else {
                         break;
                     }
+                } else if errcode == -libc::ECONNRESET {
+                    // the peer reset the connection (this is only reported once, and later sends
+                    // will return EPIPE)
+                    if bytes_sent == 0 {
+                        return Err(Errno::ECONNRESET);
+                    } else {
+                        break;
+                    }
                 }
 
+                // the iov holds the urgent byte if it ends the message (the C code only marks the
+                // byte as urgent if all of the iov is sent)
+                let is_urgent = flags.contains(MsgFlags::MSG_OOB)
+                    && iov.len > 0
+                    && usize::try_from(bytes_sent).unwrap() + iov.len == message_len;
+
                 // SAFETY: We're passing an immutable pointer to the memory manager. We should not
                 // have any other mutable references to the memory manager at this point.
                 let rv = Worker::with_active_host(|host| unsafe {
@@ -404,6 +552,7 @@ impl LegacyTcpSocket {
                         iov.len.try_into().unwrap(),
                         0,
                         0,
+                        is_urgent.into(),
                         mem,
                     )
                 })
@@ -471,6 +620,36 @@ impl LegacyTcpSocket {
             flags.insert(MsgFlags::MSG_DONTWAIT);
         }
 
+        if flags.contains(MsgFlags::MSG_OOB) {
+            let mut byte = 0u8;
+            let peek = flags.contains(MsgFlags::MSG_PEEK);
+
+            // receiving urgent data never blocks
+            let rv = unsafe { c::tcp_receiveUrgentData(tcp, &mut byte, peek.into()) };
+
+            if rv < 0 {
+                return Err(Errno::try_from(-rv).unwrap().into());
+            }
+
+            // the urgent byte is written to the first non-empty iov (if any), and the connection
+            // was closed if there is no urgent byte
+            let (return_val, msg_flags) = match args.iovs.iter().find(|iov| iov.len > 0) {
+                _ if rv == 0 => (0, 0),
+                Some(iov) => {
+                    mem.write(iov.base, &byte)?;
+                    (1, libc::MSG_OOB)
+                }
+                None => (0, libc::MSG_OOB | libc::MSG_TRUNC),
+            };
+
+            return Ok(RecvmsgReturn {
+                return_val,
+                addr: None,
+                msg_flags,
+                control_len: 0,
+            });
+        }
+
         // run in a closure so that an early return doesn't skip checking if we should block
         let result = (|| {
             let mut bytes_read = 0;
@@ -501,20 +680,41 @@ impl LegacyTcpSocket {
                     } else {
                         break;
                     }
+                } else if errcode == -libc::ECONNRESET
+                    && unsafe { c::tcp_getInputBufferLength(tcp) } == 0
+                {
+                    // the peer reset the connection and there's no buffered data left to read
+                    if bytes_read == 0 {
+                        return Err(Errno::ECONNRESET);
+                    } else {
+                        break;
+                    }
                 }
 
                 // SAFETY: We're passing a mutable pointer to the memory manager. We should not have
                 // any other mutable references to the memory manager at this point.
                 let rv = Worker::with_active_host(|host| unsafe {
-                    c::tcp_receiveUserData(
-                        tcp,
-                        host,
-                        iov.base.cast::<()>(),
-                        iov.len.try_into().unwrap(),
-                        std::ptr::null_mut(),
-                        std::ptr::null_mut(),
-                        mem,
-                    )
+                    if flags.contains(MsgFlags::MSG_PEEK) {
+                        // skip over the data that was peeked into the previous iovs
+                        c::tcp_peekUserData(
+                            tcp,
+                            host,
+                            iov.base.cast::<()>(),
+                            iov.len.try_into().unwrap(),
+                            bytes_read.try_into().unwrap(),
+                            mem,
+                        )
+                    } else {
+                        c::tcp_receiveUserData(
+                            tcp,
+                            host,
+                            iov.base.cast::<()>(),
+                            iov.len.try_into().unwrap(),
+                            std::ptr::null_mut(),
+                            std::ptr::null_mut(),
+                            mem,
+                        )
+                    }
                 })
                 .unwrap();
 
@@ -596,7 +796,7 @@ impl LegacyTcpSocket {
                 Ok(0.into())
             }
             // this isn't supported by tcp
-            IoctlRequest::SIOCGSTAMP => Err(Errno::ENOENT.into()),
+            IoctlRequest::SIOCGSTAMP | IoctlRequest::SIOCGSTAMPNS => Err(Errno::ENOENT.into()),
             IoctlRequest::FIONBIO => {
                 panic!("This should have been handled by the ioctl syscall handler");
             }
@@ -669,6 +869,7 @@ impl LegacyTcpSocket {
                 local_addr,
                 peer_addr,
                 /* check_generic_peer= */ true,
+                /* reuse_port= */ false,
                 net_ns,
                 rng,
             )?;
@@ -750,6 +951,9 @@ impl LegacyTcpSocket {
                 log::warn!(
                     "Attempting to connect to address '{peer_addr}' for which no host exists"
                 );
+                if Worker::use_strict_unreachable() {
+                    return Err(Errno::ENETUNREACH.into());
+                }
                 return Err(Errno::ECONNREFUSED.into());
             }
         }
@@ -773,6 +977,7 @@ impl LegacyTcpSocket {
                 local_addr,
                 peer_addr,
                 /* check_generic_peer= */ true,
+                /* reuse_port= */ false,
                 net_ns,
                 rng,
             )?;
@@ -820,9 +1025,10 @@ impl LegacyTcpSocket {
 
         if !socket_ref.status().contains(FileStatus::NONBLOCK) {
             // this is a blocking connect call
-            if errcode == Err(Errno::EINPROGRESS) {
-                // This is the first time we ever called connect, and so we need to wait for the
-                // 3-way handshake to complete. We will wait indefinitely for a success or failure.
+            if errcode == Err(Errno::EINPROGRESS) || errcode == Err(Errno::EALREADY) {
+                // Either this is the first time we ever called connect, or a previous non-blocking
+                // connect is still in progress, and so we need to wait for the 3-way handshake to
+                // complete. We will wait indefinitely for a success or failure.
 
                 let err = SyscallError::new_blocked_on_file(
                     File::Socket(Socket::Inet(InetSocket::LegacyTcp(Arc::clone(socket)))),
@@ -846,11 +1052,6 @@ impl LegacyTcpSocket {
         if errcode == Err(Errno::ECONNRESET) || errcode == Err(Errno::ENOTCONN) {
             errcode = Err(Errno::EISCONN);
         }
-        // EALREADY is well defined in man page, but Linux returns EINPROGRESS
-        if errcode == Err(Errno::EALREADY) {
-            errcode = Err(Errno::EINPROGRESS);
-        }
-
         socket_ref.thread_of_blocked_connect = None;
         errcode.map_err(Into::into)
     }
@@ -990,6 +1191,47 @@ impl LegacyTcpSocket {
 
                 Ok(bytes_written as libc::socklen_t)
             }
+            (libc::SOL_TCP, libc::TCP_CC_INFO) => {
+                let mut info = shadow_pod::zeroed();
+                let info_len = unsafe { c::tcp_getCongestionInfo(self.as_legacy_tcp(), &mut info) };
+
+                // like linux, only the part of the union used by the algorithm is returned
+                let optlen = std::cmp::min(optlen as usize, info_len as usize);
+
+                let optval_ptr = optval_ptr.cast::<crate::cshadow::tcp_cc_info>();
+                let bytes_written = write_partial(memory_manager, &info, optval_ptr, optlen)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
+            (libc::SOL_TCP, libc::TCP_DEFER_ACCEPT) => {
+                let secs = unsafe { c::tcp_getDeferAccept(self.as_legacy_tcp()) };
+                let secs = libc::c_int::try_from(secs).unwrap();
+
+                let optval_ptr = optval_ptr.cast::<libc::c_int>();
+                let bytes_written =
+                    write_partial(memory_manager, &secs, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
+            (libc::SOL_TCP, libc::TCP_QUICKACK) => {
+                let enabled = unsafe { c::tcp_getQuickAck(self.as_legacy_tcp()) };
+
+                let optval_ptr = optval_ptr.cast::<libc::c_int>();
+                let bytes_written =
+                    write_partial(memory_manager, &enabled, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
+            (libc::SOL_TCP, libc::TCP_MAXSEG) => {
+                let mss = unsafe { c::tcp_getMaxSegmentSize(self.as_legacy_tcp()) };
+                let mss = libc::c_int::try_from(mss).unwrap();
+
+                let optval_ptr = optval_ptr.cast::<libc::c_int>();
+                let bytes_written =
+                    write_partial(memory_manager, &mss, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
             (libc::SOL_TCP, libc::TCP_NODELAY) => {
                 // shadow doesn't support nagle's algorithm, so shadow always behaves as if
                 // TCP_NODELAY is enabled
@@ -1005,10 +1247,6 @@ impl LegacyTcpSocket {
                 // the value of TCP_CA_NAME_MAX in linux
                 const CONG_NAME_MAX: usize = 16;
 
-                if optval_ptr.is_null() {
-                    return Err(Errno::EINVAL.into());
-                }
-
                 let name: *const libc::c_char =
                     unsafe { c::tcpcong_nameStr(c::tcp_cong(self.as_legacy_tcp())) };
                 assert!(!name.is_null(), "shadow's congestion type has no name");
@@ -1054,15 +1292,9 @@ impl LegacyTcpSocket {
                 Ok(bytes_written as libc::socklen_t)
             }
             (libc::SOL_SOCKET, libc::SO_ERROR) => {
-                // return error for failed connect() attempts
-                let conn_err = unsafe { c::tcp_getConnectionError(self.as_legacy_tcp()) };
-
-                let error = if conn_err == -libc::ECONNRESET || conn_err == -libc::ECONNREFUSED {
-                    // result is a positive errcode
-                    -conn_err
-                } else {
-                    0
-                };
+                // return error for failed connect() attempts; this must not consume the result of
+                // a non-blocking connect(), which is returned by the next connect() call
+                let error = unsafe { c::tcp_getSocketError(self.as_legacy_tcp()) };
 
                 let optval_ptr = optval_ptr.cast::<libc::c_int>();
                 let bytes_written =
@@ -1106,6 +1338,59 @@ impl LegacyTcpSocket {
 
                 Ok(bytes_written as libc::socklen_t)
             }
+            (libc::SOL_SOCKET, libc::SO_OOBINLINE) => {
+                let is_inline = unsafe { c::tcp_getUrgentInline(self.as_legacy_tcp()) };
+
+                let optval_ptr = optval_ptr.cast::<libc::c_int>();
+                let bytes_written =
+                    write_partial(memory_manager, &is_inline, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
+            (libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE) => {
+                let rate = unsafe { c::tcp_getMaxPacingRate(self.as_legacy_tcp()) };
+                Ok(inet::write_max_pacing_rate(
+                    rate,
+                    optval_ptr,
+                    optlen,
+                    memory_manager,
+                )?)
+            }
+            (libc::SOL_SOCKET, libc::SO_REUSEPORT) => {
+                let reuse_port = libc::c_int::from(self.reuse_port);
+
+                let optval_ptr = optval_ptr.cast::<libc::c_int>();
+                let bytes_written =
+                    write_partial(memory_manager, &reuse_port, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
+            (libc::SOL_SOCKET, libc::SO_RCVTIMEO) => Ok(inet::write_timeout(
+                self.recv_timeout,
+                optval_ptr,
+                optlen,
+                memory_manager,
+            )?),
+            (libc::SOL_SOCKET, libc::SO_SNDTIMEO) => Ok(inet::write_timeout(
+                self.send_timeout,
+                optval_ptr,
+                optlen,
+                memory_manager,
+            )?),
+            (libc::SOL_SOCKET, libc::SO_LINGER) => {
+                let linger = libc::linger {
+                    l_onoff: self.linger.is_some().into(),
+                    l_linger: self
+                        .linger
+                        .map_or(0, |x| x.as_secs().try_into().unwrap_or(libc::c_int::MAX)),
+                };
+
+                let optval_ptr = optval_ptr.cast::<libc::linger>();
+                let bytes_written =
+                    write_partial(memory_manager, &linger, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
             _ => {
                 log_once_per_value_at_level!(
                     (level, optname),
@@ -1128,6 +1413,58 @@ impl LegacyTcpSocket {
         memory_manager: &MemoryManager,
     ) -> Result<(), SyscallError> {
         match (level, optname) {
+            (libc::SOL_TCP, libc::TCP_DEFER_ACCEPT) => {
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let secs = memory_manager.read(optval_ptr)?;
+
+                let secs = defer_accept_secs(secs);
+                unsafe { c::tcp_setDeferAccept(self.as_legacy_tcp(), secs) };
+            }
+            (libc::SOL_TCP, libc::TCP_MAXSEG) => {
+                // the values of TCP_MIN_MSS and MAX_TCP_WINDOW in linux
+                const MIN_MSS: libc::c_int = 88;
+                const MAX_MSS: libc::c_int = 32767;
+
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let mss = memory_manager.read(optval_ptr)?;
+
+                // a value of 0 resets the mss to the default
+                if mss != 0 && !(MIN_MSS..=MAX_MSS).contains(&mss) {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                // the mss is bounded by the path mtu when sending
+                let mss = mss.try_into().unwrap();
+                unsafe { c::tcp_setMaxSegmentSize(self.as_legacy_tcp(), mss) };
+            }
+            (libc::SOL_TCP, libc::TCP_QUICKACK) => {
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let enable = memory_manager.read(optval_ptr)? != 0;
+
+                // enabling quick acks may send a delayed ack
+                Worker::with_active_host(|host| unsafe {
+                    c::tcp_setQuickAck(self.as_legacy_tcp(), host, enable.into())
+                })
+                .unwrap();
+            }
             (libc::SOL_TCP, libc::TCP_NODELAY) => {
                 // Shadow doesn't support nagle's algorithm, so Shadow always behaves as if
                 // TCP_NODELAY is enabled. Some programs will fail if `setsockopt(fd, SOL_TCP,
@@ -1156,6 +1493,10 @@ impl LegacyTcpSocket {
                 // the value of TCP_CA_NAME_MAX in linux
                 const CONG_NAME_MAX: usize = 16;
 
+                if optlen < 1 {
+                    return Err(Errno::EINVAL.into());
+                }
+
                 let mut name = [0u8; CONG_NAME_MAX];
 
                 let optlen = std::cmp::min(optlen as usize, CONG_NAME_MAX);
@@ -1173,14 +1514,14 @@ impl LegacyTcpSocket {
                     .map(|x| &name[..x])
                     .unwrap_or(name);
 
-                let reno = unsafe { CStr::from_ptr(c::TCP_CONG_RENO_NAME) }.to_bytes();
+                // the name can't contain a NUL, so this can't fail
+                let name = CString::new(name).unwrap();
 
-                if name != reno {
-                    log::warn!("Shadow sockets only support '{reno:?}' for TCP_CONGESTION");
+                let tcp = self.as_legacy_tcp();
+                if unsafe { c::tcp_setCongestionControl(tcp, name.as_ptr()) } == 0 {
+                    log::warn!("Shadow sockets don't support {name:?} for TCP_CONGESTION");
                     return Err(Errno::ENOENT.into());
                 }
-
-                // shadow doesn't support other congestion types, so do nothing
             }
             (libc::SOL_SOCKET, libc::SO_SNDBUF) => {
                 type OptType = libc::c_int;
@@ -1240,13 +1581,63 @@ impl LegacyTcpSocket {
                 unsafe { c::legacysocket_setInputBufferSize(self.as_legacy_socket(), val) };
                 unsafe { c::tcp_disableReceiveBufferAutotuning(self.as_legacy_tcp()) };
             }
+            (libc::SOL_SOCKET, libc::SO_OOBINLINE) => {
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let enable = memory_manager.read(optval_ptr)? != 0;
+
+                unsafe { c::tcp_setUrgentInline(self.as_legacy_tcp(), enable.into()) };
+            }
+            (libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE) => {
+                let rate = inet::read_max_pacing_rate(optval_ptr, optlen, memory_manager)?;
+                unsafe { c::tcp_setMaxPacingRate(self.as_legacy_tcp(), rate) };
+            }
+            (libc::SOL_SOCKET, libc::SO_RCVTIMEO) => {
+                self.recv_timeout = inet::read_timeout(optval_ptr, optlen, memory_manager)?;
+            }
+            (libc::SOL_SOCKET, libc::SO_SNDTIMEO) => {
+                self.send_timeout = inet::read_timeout(optval_ptr, optlen, memory_manager)?;
+            }
+            (libc::SOL_SOCKET, libc::SO_LINGER) => {
+                type OptType = libc::linger;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let linger = memory_manager.read(optval_ptr)?;
+
+                self.linger = (linger.l_onoff != 0).then(|| {
+                    // like linux, a negative timeout never expires
+                    u64::try_from(linger.l_linger)
+                        .map_or(SimulationTime::MAX, SimulationTime::from_secs)
+                });
+
+                let abort_on_close = self.linger == Some(SimulationTime::ZERO);
+                unsafe { c::tcp_setAbortOnClose(self.as_legacy_tcp(), abort_on_close.into()) };
+            }
             (libc::SOL_SOCKET, libc::SO_REUSEADDR) => {
                 // TODO: implement this, tor and tgen use it
                 log::trace!("setsockopt SO_REUSEADDR not yet implemented");
             }
             (libc::SOL_SOCKET, libc::SO_REUSEPORT) => {
-                // TODO: implement this, tgen uses it
-                log::trace!("setsockopt SO_REUSEPORT not yet implemented");
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let val = memory_manager.read(optval_ptr)?;
+
+                // only affects later calls to `bind()`
+                self.reuse_port = val != 0;
             }
             (libc::SOL_SOCKET, libc::SO_KEEPALIVE) => {
                 // TODO: implement this, libevent uses it in
@@ -1272,6 +1663,10 @@ impl LegacyTcpSocket {
         Ok(())
     }
 
+    pub fn ignored_sockopts_mut(&mut self) -> &mut IgnoredSockopts {
+        &mut self.ignored_sockopts
+    }
+
     pub fn add_listener(
         &mut self,
         monitoring_state: FileState,
@@ -1310,3 +1705,33 @@ impl std::ops::Drop for LegacyTcpSocket {
         unsafe { c::legacyfile_unref(self.socket.ptr() as *mut libc::c_void) };
     }
 }
+
+/// Round a `TCP_DEFER_ACCEPT` timeout up to the value that Linux would use. Linux stores the timeout
+/// as a number of SYN-ACK retransmissions, where the retransmission timeout starts at 1 second and
+/// doubles up to 120 seconds.
+fn defer_accept_secs(secs: libc::c_int) -> u32 {
+    // the initial timeout, max timeout, and max number of retransmissions in linux
+    const TIMEOUT_INIT: u32 = 1;
+    const TIMEOUT_MAX: u32 = 120;
+    const MAX_RETRANS: u32 = 255;
+
+    let Ok(secs) = u32::try_from(secs) else {
+        return 0;
+    };
+
+    if secs == 0 {
+        return 0;
+    }
+
+    let mut timeout = TIMEOUT_INIT;
+    let mut period = timeout;
+    let mut retrans = 1;
+
+    while secs > period && retrans < MAX_RETRANS {
+        retrans += 1;
+        timeout = std::cmp::min(timeout * 2, TIMEOUT_MAX);
+        period += timeout;
+    }
+
+    period
+}
@@ -6,18 +6,19 @@ use linux_api::errno::Errno;
 use linux_api::ioctls::IoctlRequest;
 use linux_api::socket::Shutdown;
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
 use crate::host::descriptor::listener::{StateListenHandle, StateListenerFilter};
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs};
+use crate::host::descriptor::socket::{IgnoredSockopts, RecvmsgArgs, RecvmsgReturn, SendmsgArgs};
 use crate::host::descriptor::{
     FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
 };
 use crate::host::memory_manager::MemoryManager;
 use crate::host::network::interface::FifoPacketPriority;
 use crate::host::network::namespace::{AssociationHandle, NetworkNamespace};
-use crate::host::syscall::io::IoVec;
+use crate::host::syscall::io::{write_partial, IoVec};
 use crate::host::syscall::types::SyscallError;
 use crate::network::packet::PacketRc;
 use crate::utility::callback_queue::CallbackQueue;
@@ -308,6 +309,15 @@ impl InetSocketRef<'_> {
     enum_passthrough!(self, (), LegacyTcp, Tcp, Udp;
         pub fn has_data_to_send(&self) -> bool
     );
+    enum_passthrough!(self, (), LegacyTcp, Tcp, Udp;
+        pub fn recv_timeout(&self) -> Option<SimulationTime>
+    );
+    enum_passthrough!(self, (), LegacyTcp, Tcp, Udp;
+        pub fn send_timeout(&self) -> Option<SimulationTime>
+    );
+    enum_passthrough!(self, (), LegacyTcp, Tcp, Udp;
+        pub fn proc_net_entry(&self) -> ProcNetEntry
+    );
 }
 
 // file functions
@@ -401,6 +411,10 @@ impl InetSocketRefMut<'_> {
         -> Result<(), SyscallError>
     );
 
+    enum_passthrough!(self, (), LegacyTcp, Tcp, Udp;
+        pub fn ignored_sockopts_mut(&mut self) -> &mut IgnoredSockopts
+    );
+
     pub fn accept(
         &mut self,
         net_ns: &NetworkNamespace,
@@ -492,12 +506,15 @@ impl InetSocketWeak {
 /// unspecified and has a port of 0, the socket will receive packets from every peer address. The
 /// socket will be automatically disassociated when the returned [`AssociationHandle`] is dropped.
 /// If `check_generic_peer` is true, the association will also fail if there is already a socket
-/// associated with the local address `local_addr` and peer address 0.0.0.0:0.
+/// associated with the local address `local_addr` and peer address 0.0.0.0:0. If `reuse_port` is
+/// true (the socket has `SO_REUSEPORT` set), the association may share the addresses with other
+/// `SO_REUSEPORT` sockets, and incoming packets will be distributed across them.
 fn associate_socket(
     socket: InetSocket,
     local_addr: SocketAddrV4,
     peer_addr: SocketAddrV4,
     check_generic_peer: bool,
+    reuse_port: bool,
     net_ns: &NetworkNamespace,
     rng: impl rand::Rng,
 ) -> Result<(SocketAddrV4, AssociationHandle), Errno> {
@@ -534,8 +551,19 @@ fn associate_socket(
         SocketAddrV4::new(*local_addr.ip(), new_port)
     };
 
+    // is the address in use by a socket that we can't share it with?
+    let is_addr_in_use = |peer_addr| {
+        if reuse_port {
+            net_ns
+                .can_reuse_port(protocol, local_addr, peer_addr)
+                .map(|x| !x)
+        } else {
+            net_ns.is_addr_in_use(protocol, local_addr, peer_addr)
+        }
+    };
+
     // make sure the port is available at this address for this protocol
-    match net_ns.is_addr_in_use(protocol, local_addr, peer_addr) {
+    match is_addr_in_use(peer_addr) {
         Ok(true) => {
             log::debug!(
                 "The provided addresses (local={local_addr}, peer={peer_addr}) are not available"
@@ -547,11 +575,7 @@ fn associate_socket(
     }
 
     if check_generic_peer {
-        match net_ns.is_addr_in_use(
-            protocol,
-            local_addr,
-            SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0),
-        ) {
+        match is_addr_in_use(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)) {
             Ok(true) => {
                 log::debug!(
                     "The generic addresses (local={local_addr}, peer={}) are not available",
@@ -565,11 +589,133 @@ fn associate_socket(
     }
 
     // associate the interfaces corresponding to addr with socket
-    let handle = unsafe { net_ns.associate_interface(&socket, protocol, local_addr, peer_addr) };
+    let handle =
+        unsafe { net_ns.associate_interface(&socket, protocol, local_addr, peer_addr, reuse_port) };
 
     Ok((local_addr, handle))
 }
 
+/// Read a `SO_MAX_PACING_RATE` socket option value (in bytes per second) from the plugin. Like
+/// Linux, the value may be 32 or 64 bits, and a 32-bit value of `u32::MAX` means "unlimited".
+fn read_max_pacing_rate(
+    optval_ptr: ForeignPtr<()>,
+    optlen: libc::socklen_t,
+    mem: &MemoryManager,
+) -> Result<u64, Errno> {
+    let optlen = usize::try_from(optlen).unwrap();
+
+    if optlen >= std::mem::size_of::<u64>() {
+        return mem.read(optval_ptr.cast::<u64>());
+    }
+
+    if optlen < std::mem::size_of::<u32>() {
+        return Err(Errno::EINVAL);
+    }
+
+    match mem.read(optval_ptr.cast::<u32>())? {
+        u32::MAX => Ok(u64::MAX),
+        x => Ok(x.into()),
+    }
+}
+
+/// Write a `SO_MAX_PACING_RATE` socket option value (in bytes per second) to the plugin. Like
+/// Linux, the value is capped to 32 bits if the plugin's buffer is smaller than 64 bits.
+fn write_max_pacing_rate(
+    rate: u64,
+    optval_ptr: ForeignPtr<()>,
+    optlen: libc::socklen_t,
+    mem: &mut MemoryManager,
+) -> Result<libc::socklen_t, Errno> {
+    let optlen = usize::try_from(optlen).unwrap();
+
+    let bytes_written = if optlen >= std::mem::size_of::<u64>() {
+        write_partial(mem, &rate, optval_ptr.cast::<u64>(), optlen)?
+    } else {
+        let rate = u32::try_from(rate).unwrap_or(u32::MAX);
+        write_partial(mem, &rate, optval_ptr.cast::<u32>(), optlen)?
+    };
+
+    Ok(bytes_written.try_into().unwrap())
+}
+
+/// Read a `SO_RCVTIMEO` or `SO_SNDTIMEO` socket option value from the plugin. Like Linux, a zero
+/// timeout means that blocking calls never time out, and a negative timeout means that they time
+/// out immediately.
+fn read_timeout(
+    optval_ptr: ForeignPtr<()>,
+    optlen: libc::socklen_t,
+    mem: &MemoryManager,
+) -> Result<Option<SimulationTime>, Errno> {
+    type OptType = linux_api::time::timeval;
+
+    if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+        return Err(Errno::EINVAL);
+    }
+
+    let timeout = mem.read(optval_ptr.cast::<OptType>())?;
+
+    if !(0..1_000_000).contains(&timeout.tv_usec) {
+        return Err(Errno::EDOM);
+    }
+
+    if timeout.tv_sec < 0 {
+        return Ok(Some(SimulationTime::ZERO));
+    }
+
+    if timeout.tv_sec == 0 && timeout.tv_usec == 0 {
+        return Ok(None);
+    }
+
+    // a timeout too large to represent will never expire
+    Ok(SimulationTime::try_from(timeout).ok())
+}
+
+/// Write a `SO_RCVTIMEO` or `SO_SNDTIMEO` socket option value to the plugin. No timeout is written
+/// as a zero timeout.
+fn write_timeout(
+    timeout: Option<SimulationTime>,
+    optval_ptr: ForeignPtr<()>,
+    optlen: libc::socklen_t,
+    mem: &mut MemoryManager,
+) -> Result<libc::socklen_t, Errno> {
+    let timeout = timeout.unwrap_or(SimulationTime::ZERO);
+    let timeout = linux_api::time::timeval::try_from(timeout).unwrap();
+
+    let optval_ptr = optval_ptr.cast::<linux_api::time::timeval>();
+    let bytes_written = write_partial(mem, &timeout, optval_ptr, optlen.try_into().unwrap())?;
+
+    Ok(bytes_written.try_into().unwrap())
+}
+
+/// A socket's row in linux's `/proc/net/tcp` or `/proc/net/udp` tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProcNetEntry {
+    pub local_addr: SocketAddrV4,
+    /// The peer address, or 0.0.0.0:0 if the socket isn't connected.
+    pub peer_addr: SocketAddrV4,
+    /// A value of linux's `enum tcp_states`, which linux also uses for udp sockets.
+    pub state: u8,
+    /// The number of bytes that have been written but not yet sent or acknowledged.
+    pub tx_queue: usize,
+    /// The number of bytes that have been received but not yet read.
+    pub rx_queue: usize,
+}
+
+impl ProcNetEntry {
+    pub const TCP_ESTABLISHED: u8 = 1;
+    pub const TCP_SYN_SENT: u8 = 2;
+    pub const TCP_CLOSE: u8 = 7;
+    pub const TCP_LISTEN: u8 = 10;
+}
+
+/// The time it takes to send `len` bytes at a pacing rate of `rate` bytes per second.
+fn pacing_delay(len: usize, rate: u64) -> SimulationTime {
+    // a rate of 0 would stop the socket from sending, so use the lowest non-zero rate
+    let rate = std::cmp::max(rate, 1);
+    let nanos = u128::try_from(len).unwrap() * 1_000_000_000 / u128::from(rate);
+    SimulationTime::from_nanos(nanos.try_into().unwrap())
+}
+
 mod export {
     use super::*;
 
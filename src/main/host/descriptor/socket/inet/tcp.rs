@@ -16,7 +16,9 @@ use crate::core::worker::Worker;
 use crate::cshadow as c;
 use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
 use crate::host::descriptor::socket::inet;
-use crate::host::descriptor::socket::{InetSocket, RecvmsgArgs, RecvmsgReturn, SendmsgArgs};
+use crate::host::descriptor::socket::{
+    IgnoredSockopts, InetSocket, RecvmsgArgs, RecvmsgReturn, SendmsgArgs,
+};
 use crate::host::descriptor::{File, Socket};
 use crate::host::descriptor::{
     FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
@@ -43,6 +45,15 @@ pub struct TcpSocket {
     // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
     // this file
     has_open_file: bool,
+    /// The `SO_RCVTIMEO` timeout for blocking receives.
+    recv_timeout: Option<SimulationTime>,
+    /// The `SO_SNDTIMEO` timeout for blocking sends.
+    send_timeout: Option<SimulationTime>,
+    /// Was `SO_REUSEPORT` set? If so, binding allows the socket to share its address with other
+    /// `SO_REUSEPORT` sockets.
+    reuse_port: bool,
+    /// Unsupported socket options that were set, if they're being ignored.
+    ignored_sockopts: IgnoredSockopts,
     _counter: ObjectCounter,
 }
 
@@ -68,6 +79,10 @@ impl TcpSocket {
                 connect_result_is_pending: false,
                 shutdown_status: None,
                 has_open_file: false,
+                recv_timeout: None,
+                send_timeout: None,
+                reuse_port: false,
+                ignored_sockopts: IgnoredSockopts::default(),
                 _counter: ObjectCounter::new("TcpSocket"),
             })
         });
@@ -160,6 +175,10 @@ impl TcpSocket {
         if poll_state.intersects(tcp::PollState::READABLE | tcp::PollState::RECV_CLOSED) {
             read_write_flags.insert(FileState::READABLE);
         }
+        if poll_state.intersects(tcp::PollState::RECV_CLOSED) {
+            // there may still be buffered data, but the peer won't send anything more
+            read_write_flags.insert(FileState::RDHUP);
+        }
         if poll_state.intersects(tcp::PollState::WRITABLE) {
             read_write_flags.insert(FileState::WRITABLE);
         }
@@ -176,9 +195,9 @@ impl TcpSocket {
             read_write_flags = FileState::empty();
         }
 
-        // overwrite readable/writable flags
+        // overwrite readable/writable/rdhup flags
         self.update_state(
-            FileState::READABLE | FileState::WRITABLE,
+            FileState::READABLE | FileState::WRITABLE | FileState::RDHUP,
             read_write_flags,
             rv.1,
             cb_queue,
@@ -288,6 +307,14 @@ impl TcpSocket {
         self.tcp_state.wants_to_send()
     }
 
+    pub fn recv_timeout(&self) -> Option<SimulationTime> {
+        self.recv_timeout
+    }
+
+    pub fn send_timeout(&self) -> Option<SimulationTime> {
+        self.send_timeout
+    }
+
     pub fn getsockname(&self) -> Result<Option<SockaddrIn>, Errno> {
         // The socket state won't always have the local address. For example if the socket was bound
         // but connect() hasn't yet been called, the socket state will not have a local or remote
@@ -319,6 +346,36 @@ impl TcpSocket {
         // connection is successfully established.
     }
 
+    pub fn proc_net_entry(&self) -> inet::ProcNetEntry {
+        let local_addr = self.getsockname().unwrap().unwrap();
+        let peer_addr = self
+            .tcp_state
+            .local_remote_addrs()
+            .map(|x| x.1)
+            .unwrap_or(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+
+        // the tcp state doesn't expose its exact state or buffer lengths, so we show the closest
+        // linux state and empty queues
+        let poll_state = self.tcp_state.poll();
+        let state = if poll_state.contains(tcp::PollState::LISTENING) {
+            inet::ProcNetEntry::TCP_LISTEN
+        } else if poll_state.contains(tcp::PollState::CONNECTING) {
+            inet::ProcNetEntry::TCP_SYN_SENT
+        } else if poll_state.contains(tcp::PollState::CONNECTED) {
+            inet::ProcNetEntry::TCP_ESTABLISHED
+        } else {
+            inet::ProcNetEntry::TCP_CLOSE
+        };
+
+        inet::ProcNetEntry {
+            local_addr: local_addr.into(),
+            peer_addr,
+            state,
+            tx_queue: 0,
+            rx_queue: 0,
+        }
+    }
+
     pub fn address_family(&self) -> linux_api::socket::AddressFamily {
         linux_api::socket::AddressFamily::AF_INET
     }
@@ -373,6 +430,7 @@ impl TcpSocket {
             addr,
             peer_addr,
             /* check_generic_peer= */ true,
+            socket_ref.reuse_port,
             net_ns,
             rng,
         )?;
@@ -493,7 +551,8 @@ impl TcpSocket {
         let result = (|| {
             let writer = IoVecWriter::new(args.iovs, mem);
 
-            let rv = socket_ref.with_tcp_state(cb_queue, |state| state.recv(writer, len));
+            let peek = flags.contains(MsgFlags::MSG_PEEK);
+            let rv = socket_ref.with_tcp_state(cb_queue, |state| state.recv(writer, len, peek));
 
             let num_recv = match rv {
                 Ok(x) => x,
@@ -584,6 +643,7 @@ impl TcpSocket {
                     local_addr,
                     peer_addr,
                     /* check_generic_peer= */ true,
+                    /* reuse_port= */ false,
                     net_ns,
                     rng,
                 )?;
@@ -658,6 +718,15 @@ impl TcpSocket {
             peer_addr.set_ip(std::net::Ipv4Addr::LOCALHOST);
         }
 
+        // make sure the connection won't leave the simulation
+        if !peer_addr.ip().is_loopback()
+            && Worker::use_strict_unreachable()
+            && !Worker::is_routable(net_ns.default_ip.into(), (*peer_addr.ip()).into())
+        {
+            log::warn!("Attempting to connect to address '{peer_addr}' for which no host exists");
+            return Err(Errno::ENETUNREACH.into());
+        }
+
         let local_addr = socket_ref.association.as_ref().map(|x| x.local_addr());
 
         let rv = if let Some(mut local_addr) = local_addr {
@@ -693,6 +762,7 @@ impl TcpSocket {
                     local_addr,
                     peer_addr,
                     /* check_generic_peer= */ true,
+                    /* reuse_port= */ false,
                     net_ns,
                     rng,
                 )?;
@@ -791,6 +861,10 @@ impl TcpSocket {
                 connect_result_is_pending: false,
                 shutdown_status: None,
                 has_open_file: false,
+                recv_timeout: None,
+                send_timeout: None,
+                reuse_port: false,
+                ignored_sockopts: IgnoredSockopts::default(),
                 _counter: ObjectCounter::new("TcpSocket"),
             })
         });
@@ -809,6 +883,7 @@ impl TcpSocket {
             local_addr,
             remote_addr,
             /* check_generic_peer= */ false,
+            /* reuse_port= */ false,
             net_ns,
             rng,
         )?;
@@ -825,6 +900,19 @@ impl TcpSocket {
         how: Shutdown,
         cb_queue: &mut CallbackQueue,
     ) -> Result<(), SyscallError> {
+        // Linux handles listening sockets separately. Shutting down reading stops the socket from
+        // listening (resetting any connections that haven't been accepted yet), which wakes any
+        // blocked accept() calls and makes them fail with EINVAL. Shutting down writing has no
+        // effect.
+        if self.tcp_state.poll().contains(tcp::PollState::LISTENING) {
+            if how != Shutdown::SHUT_WR {
+                // closing a listening tcp state doesn't return an error
+                self.with_tcp_state(cb_queue, |state| state.close())
+                    .unwrap();
+            }
+            return Ok(());
+        }
+
         // Update `how` based on any previous shutdown() calls. For example if shutdown(RD) was
         // previously called and now shutdown(WR) has been called, we should call shutdown(RDWR) on
         // the tcp state.
@@ -930,6 +1018,26 @@ impl TcpSocket {
 
                 Ok(bytes_written as libc::socklen_t)
             }
+            (libc::SOL_SOCKET, libc::SO_REUSEPORT) => {
+                let reuse_port = libc::c_int::from(self.reuse_port);
+
+                let optval_ptr = optval_ptr.cast::<libc::c_int>();
+                let bytes_written = write_partial(mem, &reuse_port, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
+            (libc::SOL_SOCKET, libc::SO_RCVTIMEO) => Ok(inet::write_timeout(
+                self.recv_timeout,
+                optval_ptr,
+                optlen,
+                mem,
+            )?),
+            (libc::SOL_SOCKET, libc::SO_SNDTIMEO) => Ok(inet::write_timeout(
+                self.send_timeout,
+                optval_ptr,
+                optlen,
+                mem,
+            )?),
             _ => {
                 log_once_per_value_at_level!(
                     (level, optname),
@@ -947,18 +1055,33 @@ impl TcpSocket {
         &mut self,
         level: libc::c_int,
         optname: libc::c_int,
-        _optval_ptr: ForeignPtr<()>,
-        _optlen: libc::socklen_t,
-        _mem: &MemoryManager,
+        optval_ptr: ForeignPtr<()>,
+        optlen: libc::socklen_t,
+        mem: &MemoryManager,
     ) -> Result<(), SyscallError> {
         match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_RCVTIMEO) => {
+                self.recv_timeout = inet::read_timeout(optval_ptr, optlen, mem)?;
+            }
+            (libc::SOL_SOCKET, libc::SO_SNDTIMEO) => {
+                self.send_timeout = inet::read_timeout(optval_ptr, optlen, mem)?;
+            }
             (libc::SOL_SOCKET, libc::SO_REUSEADDR) => {
                 // TODO: implement this, tor and tgen use it
                 log::trace!("setsockopt SO_REUSEADDR not yet implemented");
             }
             (libc::SOL_SOCKET, libc::SO_REUSEPORT) => {
-                // TODO: implement this, tgen uses it
-                log::trace!("setsockopt SO_REUSEPORT not yet implemented");
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let val = mem.read(optval_ptr)?;
+
+                // only affects later calls to `bind()`
+                self.reuse_port = val != 0;
             }
             (libc::SOL_SOCKET, libc::SO_KEEPALIVE) => {
                 // TODO: implement this, libevent uses it in evconnlistener_new_bind()
@@ -983,6 +1106,10 @@ impl TcpSocket {
         Ok(())
     }
 
+    pub fn ignored_sockopts_mut(&mut self) -> &mut IgnoredSockopts {
+        &mut self.ignored_sockopts
+    }
+
     pub fn add_listener(
         &mut self,
         monitoring_state: FileState,
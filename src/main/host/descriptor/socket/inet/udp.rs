@@ -10,13 +10,17 @@ use linux_api::ioctls::IoctlRequest;
 use linux_api::socket::Shutdown;
 use nix::sys::socket::{MsgFlags, SockaddrIn};
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
+use crate::core::work::task::TaskRef;
 use crate::core::worker::Worker;
 use crate::cshadow as c;
 use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
 use crate::host::descriptor::socket::inet::{self, InetSocket};
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, ShutdownFlags};
+use crate::host::descriptor::socket::{
+    IgnoredSockopts, RecvmsgArgs, RecvmsgReturn, SendmsgArgs, ShutdownFlags,
+};
 use crate::host::descriptor::{
     File, FileMode, FileSignals, FileState, FileStatus, OpenFile, Socket, SyscallResult,
 };
@@ -45,8 +49,22 @@ pub struct UdpSocket {
     bound_addr: Option<SocketAddrV4>,
     association: Option<AssociationHandle>,
     /// The receive time of the last packet returned to the managed process during a call to
-    /// `recvmsg()`. Used for `SIOCGSTAMP`.
+    /// `recvmsg()`. Used for `SIOCGSTAMP` and `SIOCGSTAMPNS`.
     recv_time_of_last_read_packet: Option<EmulatedTime>,
+    /// The maximum sending rate in bytes per second, or `u64::MAX` if unlimited. Set using
+    /// `SO_MAX_PACING_RATE`.
+    max_pacing_rate: u64,
+    /// The earliest time at which the next datagram can leave the socket when pacing.
+    pacing_next_send_time: Option<EmulatedTime>,
+    /// The `SO_RCVTIMEO` timeout for blocking receives.
+    recv_timeout: Option<SimulationTime>,
+    /// The `SO_SNDTIMEO` timeout for blocking sends.
+    send_timeout: Option<SimulationTime>,
+    /// Was `SO_REUSEPORT` set? If so, binding allows the socket to share its address with other
+    /// `SO_REUSEPORT` sockets.
+    reuse_port: bool,
+    /// Unsupported socket options that were set, if they're being ignored.
+    ignored_sockopts: IgnoredSockopts,
     // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
     // this file
     has_open_file: bool,
@@ -70,6 +88,12 @@ impl UdpSocket {
             bound_addr: None,
             association: None,
             recv_time_of_last_read_packet: None,
+            max_pacing_rate: u64::MAX,
+            pacing_next_send_time: None,
+            recv_timeout: None,
+            send_timeout: None,
+            reuse_port: false,
+            ignored_sockopts: IgnoredSockopts::default(),
             has_open_file: false,
             _counter: ObjectCounter::new("UdpSocket"),
         };
@@ -168,6 +192,15 @@ impl UdpSocket {
     }
 
     pub fn pull_out_packet(&mut self, cb_queue: &mut CallbackQueue) -> Option<PacketRc> {
+        // if we're pacing, the next message may not be allowed to leave yet; a task will notify the
+        // host again once it can
+        if let Some((_, header)) = self.send_buffer.buffer.front() {
+            if header.send_time > Worker::current_time().unwrap() {
+                log::trace!("The UDP socket's next message is being held back for pacing");
+                return None;
+            }
+        }
+
         // pop the message from the send buffer
         let Some((message, header)) = self.send_buffer.pop_message() else {
             log::debug!(
@@ -202,6 +235,28 @@ impl UdpSocket {
         !self.send_buffer.is_empty()
     }
 
+    pub fn recv_timeout(&self) -> Option<SimulationTime> {
+        self.recv_timeout
+    }
+
+    pub fn send_timeout(&self) -> Option<SimulationTime> {
+        self.send_timeout
+    }
+
+    /// Choose the time at which a message of `len` bytes can leave the socket so that the socket
+    /// doesn't send faster than its maximum pacing rate.
+    fn reserve_send_time(&mut self, now: EmulatedTime, len: usize) -> EmulatedTime {
+        if self.max_pacing_rate == u64::MAX {
+            return now;
+        }
+
+        let send_time = std::cmp::max(now, self.pacing_next_send_time.unwrap_or(now));
+        self.pacing_next_send_time =
+            Some(send_time + inet::pacing_delay(len, self.max_pacing_rate));
+
+        send_time
+    }
+
     pub fn getsockname(&self) -> Result<Option<SockaddrIn>, Errno> {
         let mut addr = self
             .bound_addr
@@ -222,6 +277,23 @@ impl UdpSocket {
         Ok(Some(self.peer_addr.ok_or(Errno::ENOTCONN)?.into()))
     }
 
+    pub fn proc_net_entry(&self) -> inet::ProcNetEntry {
+        let unspecified = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+
+        inet::ProcNetEntry {
+            local_addr: self.getsockname().unwrap().unwrap().into(),
+            peer_addr: self.peer_addr.unwrap_or(unspecified),
+            // linux shows connected udp sockets as "established" and all others as "closed"
+            state: if self.peer_addr.is_some() {
+                inet::ProcNetEntry::TCP_ESTABLISHED
+            } else {
+                inet::ProcNetEntry::TCP_CLOSE
+            },
+            tx_queue: self.send_buffer.len_bytes(),
+            rx_queue: self.recv_buffer.len_bytes(),
+        }
+    }
+
     pub fn address_family(&self) -> linux_api::socket::AddressFamily {
         linux_api::socket::AddressFamily::AF_INET
     }
@@ -257,7 +329,7 @@ impl UdpSocket {
 
         let addr: SocketAddrV4 = (*addr).into();
 
-        {
+        let reuse_port = {
             let socket = socket.borrow();
 
             // if the socket is already bound
@@ -271,7 +343,9 @@ impl UdpSocket {
 
             // must not have been associated with the network interface
             assert!(socket.association.is_none());
-        }
+
+            socket.reuse_port
+        };
 
         // this will allow us to receive packets from any peer
         let unspecified_addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
@@ -282,6 +356,7 @@ impl UdpSocket {
             addr,
             unspecified_addr,
             /* check_generic_peer= */ true,
+            reuse_port,
             net_ns,
             rng,
         )?;
@@ -358,6 +433,15 @@ impl UdpSocket {
             },
         };
 
+        // make sure the datagram won't leave the simulation
+        if !dst_addr.ip().is_loopback()
+            && Worker::use_strict_unreachable()
+            && !Worker::is_routable(net_ns.default_ip.into(), (*dst_addr.ip()).into())
+        {
+            log::debug!("Attempting to send to address '{dst_addr}' for which no host exists");
+            return Err(Errno::ENETUNREACH.into());
+        }
+
         if socket_ref.status().contains(FileStatus::NONBLOCK) {
             flags.insert(MsgFlags::MSG_DONTWAIT);
         }
@@ -394,6 +478,7 @@ impl UdpSocket {
                 local_addr,
                 unspecified_addr,
                 /* check_generic_peer= */ true,
+                /* reuse_port= */ false,
                 net_ns,
                 rng,
             )?;
@@ -433,10 +518,14 @@ impl UdpSocket {
                 src_addr
             };
 
+            let now = Worker::current_time().unwrap();
+            let send_time = socket_ref.reserve_send_time(now, len);
+
             let header = MessageSendHeader {
                 src: src_addr,
                 dst: dst_addr,
                 packet_priority,
+                send_time,
             };
 
             // push the message to the send buffer (shouldn't fail since we checked for available
@@ -449,13 +538,30 @@ impl UdpSocket {
             // notify the host that this socket has packets to send
             let socket = Arc::clone(socket);
             let interface_ip = *socket_ref.bound_addr.unwrap().ip();
-            cb_queue.add(move |_cb_queue| {
+            if send_time > now {
+                // we're pacing, so wait until the message is allowed to leave the socket
                 Worker::with_active_host(|host| {
-                    let socket = InetSocket::Udp(socket);
-                    host.notify_socket_has_packets(interface_ip, &socket);
+                    let task = TaskRef::new(move |host| {
+                        // the message may have already been sent while the host was notified for
+                        // an earlier message
+                        if !socket.borrow().has_data_to_send() {
+                            return;
+                        }
+                        let socket = InetSocket::Udp(Arc::clone(&socket));
+                        host.notify_socket_has_packets(interface_ip, &socket);
+                    });
+                    host.schedule_task_at_emulated_time(task, send_time);
                 })
                 .unwrap();
-            });
+            } else {
+                cb_queue.add(move |_cb_queue| {
+                    Worker::with_active_host(|host| {
+                        let socket = InetSocket::Udp(socket);
+                        host.notify_socket_has_packets(interface_ip, &socket);
+                    })
+                    .unwrap();
+                });
+            }
 
             Ok(len)
         })();
@@ -602,7 +708,7 @@ impl UdpSocket {
 
                 Ok(0.into())
             }
-            IoctlRequest::SIOCGSTAMP => {
+            IoctlRequest::SIOCGSTAMP | IoctlRequest::SIOCGSTAMPNS => {
                 // socket(7): "Return a struct timeval with the receive timestamp of the last packet
                 // passed to the user. [...] This ioctl should only be used if the socket option
                 // SO_TIMESTAMP is not set on the socket. Otherwise, it returns the timestamp of the
@@ -613,12 +719,16 @@ impl UdpSocket {
                     return Err(Errno::ENOENT.into());
                 };
 
-                let last_recv_time = (last_recv_time - EmulatedTime::UNIX_EPOCH)
-                    .try_into()
-                    .unwrap();
+                let last_recv_time = last_recv_time - EmulatedTime::UNIX_EPOCH;
 
-                let arg_ptr = arg_ptr.cast::<libc::timeval>();
-                mem.write(arg_ptr, &last_recv_time)?;
+                // SIOCGSTAMPNS is the same, but returns a struct timespec
+                if request == IoctlRequest::SIOCGSTAMP {
+                    let last_recv_time: libc::timeval = last_recv_time.try_into().unwrap();
+                    mem.write(arg_ptr.cast::<libc::timeval>(), &last_recv_time)?;
+                } else {
+                    let last_recv_time: libc::timespec = last_recv_time.try_into().unwrap();
+                    mem.write(arg_ptr.cast::<libc::timespec>(), &last_recv_time)?;
+                }
 
                 Ok(0.into())
             }
@@ -697,6 +807,9 @@ impl UdpSocket {
                 log::warn!(
                     "Attempting to connect to address '{peer_addr}' for which no host exists"
                 );
+                if Worker::use_strict_unreachable() {
+                    return Err(Errno::ENETUNREACH.into());
+                }
                 return Err(Errno::ECONNREFUSED.into());
             }
         }
@@ -746,6 +859,7 @@ impl UdpSocket {
                     local_addr,
                     unspecified_addr,
                     /* check_generic_peer= */ true,
+                    /* reuse_port= */ false,
                     net_ns,
                     rng,
                 )?;
@@ -856,6 +970,32 @@ impl UdpSocket {
 
                 Ok(bytes_written as libc::socklen_t)
             }
+            (libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE) => Ok(inet::write_max_pacing_rate(
+                self.max_pacing_rate,
+                optval_ptr,
+                optlen,
+                mem,
+            )?),
+            (libc::SOL_SOCKET, libc::SO_REUSEPORT) => {
+                let reuse_port = libc::c_int::from(self.reuse_port);
+
+                let optval_ptr = optval_ptr.cast::<libc::c_int>();
+                let bytes_written = write_partial(mem, &reuse_port, optval_ptr, optlen as usize)?;
+
+                Ok(bytes_written as libc::socklen_t)
+            }
+            (libc::SOL_SOCKET, libc::SO_RCVTIMEO) => Ok(inet::write_timeout(
+                self.recv_timeout,
+                optval_ptr,
+                optlen,
+                mem,
+            )?),
+            (libc::SOL_SOCKET, libc::SO_SNDTIMEO) => Ok(inet::write_timeout(
+                self.send_timeout,
+                optval_ptr,
+                optlen,
+                mem,
+            )?),
             (libc::SOL_SOCKET, _) => {
                 log_once_per_value_at_level!(
                     (level, optname),
@@ -940,15 +1080,32 @@ impl UdpSocket {
                 self.recv_buffer
                     .set_soft_limit_bytes(val.try_into().unwrap());
             }
+            (libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE) => {
+                self.max_pacing_rate = inet::read_max_pacing_rate(optval_ptr, optlen, mem)?;
+            }
+            (libc::SOL_SOCKET, libc::SO_RCVTIMEO) => {
+                self.recv_timeout = inet::read_timeout(optval_ptr, optlen, mem)?;
+            }
+            (libc::SOL_SOCKET, libc::SO_SNDTIMEO) => {
+                self.send_timeout = inet::read_timeout(optval_ptr, optlen, mem)?;
+            }
             (libc::SOL_SOCKET, libc::SO_REUSEADDR) => {
                 // TODO: implement this
                 warn_once_then_debug!("setsockopt SO_REUSEADDR not yet implemented for udp");
                 return Err(Errno::ENOPROTOOPT.into());
             }
             (libc::SOL_SOCKET, libc::SO_REUSEPORT) => {
-                // TODO: implement this
-                warn_once_then_debug!("setsockopt SO_REUSEPORT not yet implemented for udp");
-                return Err(Errno::ENOPROTOOPT.into());
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let val = mem.read(optval_ptr)?;
+
+                // only affects later calls to `bind()`
+                self.reuse_port = val != 0;
             }
             (libc::SOL_SOCKET, libc::SO_KEEPALIVE) => {
                 // TODO: implement this
@@ -976,6 +1133,10 @@ impl UdpSocket {
         Ok(())
     }
 
+    pub fn ignored_sockopts_mut(&mut self) -> &mut IgnoredSockopts {
+        &mut self.ignored_sockopts
+    }
+
     pub fn add_listener(
         &mut self,
         monitoring_state: FileState,
@@ -1061,6 +1222,8 @@ struct MessageSendHeader {
     dst: SocketAddrV4,
     /// The priority for the packet that we'll create in the future, given to us by the host.
     packet_priority: FifoPacketPriority,
+    /// The earliest time at which the message can leave the socket, due to pacing.
+    send_time: EmulatedTime,
 }
 
 /// Non-payload data for a message in the receive buffer.
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
@@ -6,6 +7,8 @@ use linux_api::errno::Errno;
 use linux_api::ioctls::IoctlRequest;
 use linux_api::socket::Shutdown;
 use netlink::NetlinkSocket;
+use packet::PacketSocket;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 use unix::UnixSocket;
 
@@ -23,8 +26,10 @@ use crate::utility::sockaddr::SockaddrStorage;
 use crate::utility::HostTreePointer;
 
 pub mod abstract_unix_ns;
+pub mod bpf;
 pub mod inet;
 pub mod netlink;
+pub mod packet;
 pub mod unix;
 
 bitflags::bitflags! {
@@ -37,11 +42,34 @@ bitflags::bitflags! {
     }
 }
 
+/// The values of socket options that shadow doesn't support, but that were accepted (and otherwise
+/// ignored) by `setsockopt()` because `experimental.ignore_unsupported_sockopts` is enabled. The
+/// stored values are returned by `getsockopt()`.
+#[derive(Debug, Default)]
+pub struct IgnoredSockopts {
+    values: HashMap<(libc::c_int, libc::c_int), Vec<u8>>,
+}
+
+impl IgnoredSockopts {
+    /// The largest option value that will be stored.
+    pub const MAX_LEN: usize = 1024;
+
+    pub fn get(&self, level: libc::c_int, optname: libc::c_int) -> Option<&[u8]> {
+        self.values.get(&(level, optname)).map(Vec::as_slice)
+    }
+
+    pub fn insert(&mut self, level: libc::c_int, optname: libc::c_int, value: Vec<u8>) {
+        assert!(value.len() <= Self::MAX_LEN);
+        self.values.insert((level, optname), value);
+    }
+}
+
 #[derive(Clone)]
 pub enum Socket {
     Unix(Arc<AtomicRefCell<UnixSocket>>),
     Inet(InetSocket),
     Netlink(Arc<AtomicRefCell<NetlinkSocket>>),
+    Packet(Arc<AtomicRefCell<PacketSocket>>),
 }
 
 impl Socket {
@@ -50,6 +78,7 @@ impl Socket {
             Self::Unix(ref f) => SocketRef::Unix(f.borrow()),
             Self::Inet(ref f) => SocketRef::Inet(f.borrow()),
             Self::Netlink(ref f) => SocketRef::Netlink(f.borrow()),
+            Self::Packet(ref f) => SocketRef::Packet(f.borrow()),
         }
     }
 
@@ -58,6 +87,7 @@ impl Socket {
             Self::Unix(ref f) => SocketRef::Unix(f.try_borrow()?),
             Self::Inet(ref f) => SocketRef::Inet(f.try_borrow()?),
             Self::Netlink(ref f) => SocketRef::Netlink(f.try_borrow()?),
+            Self::Packet(ref f) => SocketRef::Packet(f.try_borrow()?),
         })
     }
 
@@ -66,6 +96,7 @@ impl Socket {
             Self::Unix(ref f) => SocketRefMut::Unix(f.borrow_mut()),
             Self::Inet(ref f) => SocketRefMut::Inet(f.borrow_mut()),
             Self::Netlink(ref f) => SocketRefMut::Netlink(f.borrow_mut()),
+            Self::Packet(ref f) => SocketRefMut::Packet(f.borrow_mut()),
         }
     }
 
@@ -74,6 +105,7 @@ impl Socket {
             Self::Unix(ref f) => SocketRefMut::Unix(f.try_borrow_mut()?),
             Self::Inet(ref f) => SocketRefMut::Inet(f.try_borrow_mut()?),
             Self::Netlink(ref f) => SocketRefMut::Netlink(f.try_borrow_mut()?),
+            Self::Packet(ref f) => SocketRefMut::Packet(f.try_borrow_mut()?),
         })
     }
 
@@ -82,6 +114,7 @@ impl Socket {
             Self::Unix(f) => Arc::as_ptr(f) as usize,
             Self::Inet(ref f) => f.canonical_handle(),
             Self::Netlink(f) => Arc::as_ptr(f) as usize,
+            Self::Packet(f) => Arc::as_ptr(f) as usize,
         }
     }
 
@@ -95,6 +128,7 @@ impl Socket {
             Self::Unix(socket) => UnixSocket::bind(socket, addr, net_ns, rng),
             Self::Inet(socket) => InetSocket::bind(socket, addr, net_ns, rng),
             Self::Netlink(socket) => NetlinkSocket::bind(socket, addr, net_ns, rng),
+            Self::Packet(socket) => PacketSocket::bind(socket, addr, net_ns, rng),
         }
     }
 
@@ -109,6 +143,7 @@ impl Socket {
             Self::Unix(socket) => UnixSocket::listen(socket, backlog, net_ns, rng, cb_queue),
             Self::Inet(socket) => InetSocket::listen(socket, backlog, net_ns, rng, cb_queue),
             Self::Netlink(socket) => NetlinkSocket::listen(socket, backlog, net_ns, rng, cb_queue),
+            Self::Packet(socket) => PacketSocket::listen(socket, backlog, net_ns, rng, cb_queue),
         }
     }
 
@@ -123,6 +158,7 @@ impl Socket {
             Self::Unix(socket) => UnixSocket::connect(socket, addr, net_ns, rng, cb_queue),
             Self::Inet(socket) => InetSocket::connect(socket, addr, net_ns, rng, cb_queue),
             Self::Netlink(socket) => NetlinkSocket::connect(socket, addr, net_ns, rng, cb_queue),
+            Self::Packet(socket) => PacketSocket::connect(socket, addr, net_ns, rng, cb_queue),
         }
     }
 
@@ -144,6 +180,9 @@ impl Socket {
             Self::Netlink(socket) => {
                 NetlinkSocket::sendmsg(socket, args, memory_manager, net_ns, rng, cb_queue)
             }
+            Self::Packet(socket) => {
+                PacketSocket::sendmsg(socket, args, memory_manager, net_ns, rng, cb_queue)
+            }
         }
     }
 
@@ -157,6 +196,7 @@ impl Socket {
             Self::Unix(socket) => UnixSocket::recvmsg(socket, args, memory_manager, cb_queue),
             Self::Inet(socket) => InetSocket::recvmsg(socket, args, memory_manager, cb_queue),
             Self::Netlink(socket) => NetlinkSocket::recvmsg(socket, args, memory_manager, cb_queue),
+            Self::Packet(socket) => PacketSocket::recvmsg(socket, args, memory_manager, cb_queue),
         }
     }
 }
@@ -167,6 +207,7 @@ impl std::fmt::Debug for Socket {
             Self::Unix(_) => write!(f, "Unix")?,
             Self::Inet(_) => write!(f, "Inet")?,
             Self::Netlink(_) => write!(f, "Netlink")?,
+            Self::Packet(_) => write!(f, "Packet")?,
         }
 
         if let Ok(file) = self.try_borrow() {
@@ -186,32 +227,34 @@ pub enum SocketRef<'a> {
     Unix(atomic_refcell::AtomicRef<'a, UnixSocket>),
     Inet(InetSocketRef<'a>),
     Netlink(atomic_refcell::AtomicRef<'a, NetlinkSocket>),
+    Packet(atomic_refcell::AtomicRef<'a, PacketSocket>),
 }
 
 pub enum SocketRefMut<'a> {
     Unix(atomic_refcell::AtomicRefMut<'a, UnixSocket>),
     Inet(InetSocketRefMut<'a>),
     Netlink(atomic_refcell::AtomicRefMut<'a, NetlinkSocket>),
+    Packet(atomic_refcell::AtomicRefMut<'a, PacketSocket>),
 }
 
 // file functions
 impl SocketRef<'_> {
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError>
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn supports_sa_restart(&self) -> bool
     );
 }
@@ -223,6 +266,7 @@ impl SocketRef<'_> {
             Self::Unix(socket) => socket.getpeername().map(|opt| opt.map(Into::into)),
             Self::Inet(socket) => socket.getpeername().map(|opt| opt.map(Into::into)),
             Self::Netlink(socket) => socket.getpeername().map(|opt| opt.map(Into::into)),
+            Self::Packet(socket) => socket.getpeername().map(|opt| opt.map(Into::into)),
         }
     }
 
@@ -231,47 +275,66 @@ impl SocketRef<'_> {
             Self::Unix(socket) => socket.getsockname().map(|opt| opt.map(Into::into)),
             Self::Inet(socket) => socket.getsockname().map(|opt| opt.map(Into::into)),
             Self::Netlink(socket) => socket.getsockname().map(|opt| opt.map(Into::into)),
+            Self::Packet(socket) => socket.getsockname().map(|opt| opt.map(Into::into)),
         }
     }
 
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn address_family(&self) -> linux_api::socket::AddressFamily
     );
+
+    /// The socket's `SO_RCVTIMEO` timeout for blocking receives.
+    pub fn recv_timeout(&self) -> Option<SimulationTime> {
+        match self {
+            Self::Inet(socket) => socket.recv_timeout(),
+            // we don't support this option for other socket types
+            Self::Unix(_) | Self::Netlink(_) | Self::Packet(_) => None,
+        }
+    }
+
+    /// The socket's `SO_SNDTIMEO` timeout for blocking sends.
+    pub fn send_timeout(&self) -> Option<SimulationTime> {
+        match self {
+            Self::Inet(socket) => socket.send_timeout(),
+            // we don't support this option for other socket types
+            Self::Unix(_) | Self::Netlink(_) | Self::Packet(_) => None,
+        }
+    }
 }
 
 // file functions
 impl SocketRefMut<'_> {
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn state(&self) -> FileState
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn mode(&self) -> FileMode
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn status(&self) -> FileStatus
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError>
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn has_open_file(&self) -> bool
     );
-    enum_passthrough!(self, (val), Unix, Inet, Netlink;
+    enum_passthrough!(self, (val), Unix, Inet, Netlink, Packet;
         pub fn set_has_open_file(&mut self, val: bool)
     );
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn supports_sa_restart(&self) -> bool
     );
-    enum_passthrough!(self, (cb_queue), Unix, Inet, Netlink;
+    enum_passthrough!(self, (cb_queue), Unix, Inet, Netlink, Packet;
         pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError>
     );
-    enum_passthrough!(self, (status), Unix, Inet, Netlink;
+    enum_passthrough!(self, (status), Unix, Inet, Netlink, Packet;
         pub fn set_status(&mut self, status: FileStatus)
     );
-    enum_passthrough!(self, (request, arg_ptr, memory_manager), Unix, Inet, Netlink;
+    enum_passthrough!(self, (request, arg_ptr, memory_manager), Unix, Inet, Netlink, Packet;
         pub fn ioctl(&mut self, request: IoctlRequest, arg_ptr: ForeignPtr<()>, memory_manager: &mut MemoryManager) -> SyscallResult
     );
-    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), Unix, Inet, Netlink;
+    enum_passthrough!(self, (monitoring_state, monitoring_signals, filter, notify_fn), Unix, Inet, Netlink, Packet;
         pub fn add_listener(
             &mut self,
             monitoring_state: FileState,
@@ -280,17 +343,17 @@ impl SocketRefMut<'_> {
             notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue) + Send + Sync + 'static,
         ) -> StateListenHandle
     );
-    enum_passthrough!(self, (ptr), Unix, Inet, Netlink;
+    enum_passthrough!(self, (ptr), Unix, Inet, Netlink, Packet;
         pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>)
     );
-    enum_passthrough!(self, (ptr), Unix, Inet, Netlink;
+    enum_passthrough!(self, (ptr), Unix, Inet, Netlink, Packet;
         pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener)
     );
-    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Unix, Inet, Netlink;
+    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Unix, Inet, Netlink, Packet;
         pub fn readv(&mut self, iovs: &[IoVec], offset: Option<libc::off_t>, flags: libc::c_int,
                      mem: &mut MemoryManager, cb_queue: &mut CallbackQueue) -> Result<libc::ssize_t, SyscallError>
     );
-    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Unix, Inet, Netlink;
+    enum_passthrough!(self, (iovs, offset, flags, mem, cb_queue), Unix, Inet, Netlink, Packet;
         pub fn writev(&mut self, iovs: &[IoVec], offset: Option<libc::off_t>, flags: libc::c_int,
                       mem: &mut MemoryManager, cb_queue: &mut CallbackQueue) -> Result<libc::ssize_t, SyscallError>
     );
@@ -303,6 +366,7 @@ impl SocketRefMut<'_> {
             Self::Unix(socket) => socket.getpeername().map(|opt| opt.map(Into::into)),
             Self::Inet(socket) => socket.getpeername().map(|opt| opt.map(Into::into)),
             Self::Netlink(socket) => socket.getpeername().map(|opt| opt.map(Into::into)),
+            Self::Packet(socket) => socket.getpeername().map(|opt| opt.map(Into::into)),
         }
     }
 
@@ -311,25 +375,37 @@ impl SocketRefMut<'_> {
             Self::Unix(socket) => socket.getsockname().map(|opt| opt.map(Into::into)),
             Self::Inet(socket) => socket.getsockname().map(|opt| opt.map(Into::into)),
             Self::Netlink(socket) => socket.getsockname().map(|opt| opt.map(Into::into)),
+            Self::Packet(socket) => socket.getsockname().map(|opt| opt.map(Into::into)),
         }
     }
 
-    enum_passthrough!(self, (), Unix, Inet, Netlink;
+    enum_passthrough!(self, (), Unix, Inet, Netlink, Packet;
         pub fn address_family(&self) -> linux_api::socket::AddressFamily
     );
 
-    enum_passthrough!(self, (level, optname, optval_ptr, optlen, memory_manager, cb_queue), Unix, Inet, Netlink;
+    enum_passthrough!(self, (level, optname, optval_ptr, optlen, memory_manager, cb_queue), Unix, Inet, Netlink, Packet;
         pub fn getsockopt(&mut self, level: libc::c_int, optname: libc::c_int, optval_ptr: ForeignPtr<()>,
                           optlen: libc::socklen_t, memory_manager: &mut MemoryManager, cb_queue: &mut CallbackQueue)
         -> Result<libc::socklen_t, SyscallError>
     );
 
-    enum_passthrough!(self, (level, optname, optval_ptr, optlen, memory_manager), Unix, Inet, Netlink;
+    enum_passthrough!(self, (level, optname, optval_ptr, optlen, memory_manager), Unix, Inet, Netlink, Packet;
         pub fn setsockopt(&mut self, level: libc::c_int, optname: libc::c_int, optval_ptr: ForeignPtr<()>,
                           optlen: libc::socklen_t, memory_manager: &MemoryManager)
         -> Result<(), SyscallError>
     );
 
+    /// The unsupported socket options that were ignored by `setsockopt()`, or `None` if this type
+    /// of socket doesn't store them.
+    pub fn ignored_sockopts_mut(&mut self) -> Option<&mut IgnoredSockopts> {
+        match self {
+            Self::Unix(socket) => Some(socket.ignored_sockopts_mut()),
+            Self::Inet(socket) => Some(socket.ignored_sockopts_mut()),
+            Self::Netlink(_) => None,
+            Self::Packet(_) => None,
+        }
+    }
+
     pub fn accept(
         &mut self,
         net_ns: &NetworkNamespace,
@@ -340,10 +416,11 @@ impl SocketRefMut<'_> {
             Self::Unix(socket) => socket.accept(net_ns, rng, cb_queue),
             Self::Inet(socket) => socket.accept(net_ns, rng, cb_queue),
             Self::Netlink(socket) => socket.accept(net_ns, rng, cb_queue),
+            Self::Packet(socket) => socket.accept(net_ns, rng, cb_queue),
         }
     }
 
-    enum_passthrough!(self, (how, cb_queue), Unix, Inet, Netlink;
+    enum_passthrough!(self, (how, cb_queue), Unix, Inet, Netlink, Packet;
         pub fn shutdown(&mut self, how: Shutdown, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError>
     );
 }
@@ -354,6 +431,7 @@ impl std::fmt::Debug for SocketRef<'_> {
             Self::Unix(_) => write!(f, "Unix")?,
             Self::Inet(_) => write!(f, "Inet")?,
             Self::Netlink(_) => write!(f, "Netlink")?,
+            Self::Packet(_) => write!(f, "Packet")?,
         }
 
         write!(
@@ -371,6 +449,7 @@ impl std::fmt::Debug for SocketRefMut<'_> {
             Self::Unix(_) => write!(f, "Unix")?,
             Self::Inet(_) => write!(f, "Inet")?,
             Self::Netlink(_) => write!(f, "Netlink")?,
+            Self::Packet(_) => write!(f, "Packet")?,
         }
 
         write!(
@@ -722,10 +722,13 @@ impl InitialState {
             total_copied
         };
 
+        let mut return_flags = MsgFlags::empty();
+        return_flags.set(MsgFlags::MSG_TRUNC, total_copied < buffer.len());
+
         Ok(RecvmsgReturn {
             return_val: return_val.try_into().unwrap(),
             addr: Some(src_addr),
-            msg_flags: 0,
+            msg_flags: return_flags.bits(),
             control_len: 0,
         })
     }
@@ -0,0 +1,683 @@
+//! `AF_PACKET` sockets, which receive a copy of the packets that are sent and received on the
+//! host's network interfaces. Sending packets isn't supported.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use linux_api::ioctls::IoctlRequest;
+use linux_api::socket::Shutdown;
+use nix::sys::socket::MsgFlags;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::cshadow as c;
+use crate::host::descriptor::listener::{StateEventSource, StateListenHandle, StateListenerFilter};
+use crate::host::descriptor::socket::bpf::{BpfProgram, BPF_MAXINSNS};
+use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket};
+use crate::host::descriptor::{
+    File, FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
+};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::network::interface::NetworkInterface;
+use crate::host::network::namespace::NetworkNamespace;
+use crate::host::syscall::io::{write_partial, IoVec, IoVecWriter};
+use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
+use crate::network::packet::PacketRc;
+use crate::utility::callback_queue::CallbackQueue;
+use crate::utility::pcap_writer::PacketDisplay;
+use crate::utility::sockaddr::SockaddrStorage;
+use crate::utility::HostTreePointer;
+
+// this constant is copied from UNIX_SOCKET_DEFAULT_BUFFER_SIZE
+const PACKET_SOCKET_DEFAULT_BUFFER_SIZE: usize = 212_992;
+
+/// The length of the ethernet header that's added to packets for `SOCK_RAW` sockets.
+const ETH_HEADER_LEN: usize = 14;
+
+/// Shadow doesn't simulate hardware addresses, so all interfaces use the same zero address.
+const HW_ADDR: [u8; 6] = [0; 6];
+
+pub struct PacketSocket {
+    event_source: StateEventSource,
+    status: FileStatus,
+    state: FileState,
+    socket_type: PacketSocketType,
+    /// The ethernet protocol (in host byte order) of the packets to capture. Nothing is captured if
+    /// the protocol is 0.
+    protocol: u16,
+    /// The index of the interface to capture packets from, or `None` for all interfaces.
+    ifindex: Option<u32>,
+    /// The hardware type of the interface the socket is bound to, or 0 if not bound.
+    hatype: u16,
+    recv_buffer: VecDeque<CapturedPacket>,
+    /// The number of packet bytes in the receive buffer.
+    recv_buffer_len: usize,
+    /// A soft limit for the number of packet bytes in the receive buffer.
+    recv_buffer_limit: usize,
+    /// A filter attached with `SO_ATTACH_FILTER`.
+    filter: Option<BpfProgram>,
+    // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
+    // this file
+    has_open_file: bool,
+}
+
+impl PacketSocket {
+    /// A new packet socket capturing packets of the ethernet `protocol` (in network byte order).
+    /// The socket won't receive any packets until it's registered with the network namespace
+    /// using [`NetworkNamespace::add_packet_socket`].
+    pub fn new(
+        status: FileStatus,
+        socket_type: PacketSocketType,
+        protocol: u16,
+    ) -> Arc<AtomicRefCell<Self>> {
+        let mut socket = Self {
+            event_source: StateEventSource::new(),
+            status,
+            state: FileState::ACTIVE,
+            socket_type,
+            protocol: u16::from_be(protocol),
+            ifindex: None,
+            hatype: 0,
+            recv_buffer: VecDeque::new(),
+            recv_buffer_len: 0,
+            recv_buffer_limit: PACKET_SOCKET_DEFAULT_BUFFER_SIZE,
+            filter: None,
+            has_open_file: false,
+        };
+
+        CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            socket.refresh_readable(FileSignals::empty(), cb_queue)
+        });
+
+        Arc::new(AtomicRefCell::new(socket))
+    }
+
+    pub fn status(&self) -> FileStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: FileStatus) {
+        self.status = status;
+    }
+
+    pub fn mode(&self) -> FileMode {
+        FileMode::READ | FileMode::WRITE
+    }
+
+    pub fn has_open_file(&self) -> bool {
+        self.has_open_file
+    }
+
+    pub fn supports_sa_restart(&self) -> bool {
+        true
+    }
+
+    pub fn set_has_open_file(&mut self, val: bool) {
+        self.has_open_file = val;
+    }
+
+    /// Should the socket capture a packet of this direction on the interface?
+    fn wants_packet(&self, interface: &NetworkInterface, is_outgoing: bool) -> bool {
+        if self.state.contains(FileState::CLOSED) {
+            return false;
+        }
+
+        if self.ifindex.is_some_and(|x| x != interface.index()) {
+            return false;
+        }
+
+        // all of shadow's packets are IPv4, and like linux, sent packets are only given to sockets
+        // capturing all protocols
+        match self.protocol as libc::c_int {
+            libc::ETH_P_ALL => true,
+            libc::ETH_P_IP => !is_outgoing,
+            _ => false,
+        }
+    }
+
+    /// Add a copy of a packet that was sent (`is_outgoing`) or received on `interface` to the
+    /// receive buffer, if it matches the socket's protocol and interface.
+    pub fn push_in_packet(
+        &mut self,
+        packet: &PacketRc,
+        interface: &NetworkInterface,
+        is_outgoing: bool,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        if !self.wants_packet(interface, is_outgoing) {
+            return;
+        }
+
+        // like udp, the packet is dropped if the buffer is full
+        if self.recv_buffer_len >= self.recv_buffer_limit {
+            log::trace!("Dropped a packet since the packet socket's recv buffer is full");
+            return;
+        }
+
+        let mut bytes = Vec::with_capacity(ETH_HEADER_LEN + packet.total_size());
+
+        if self.socket_type == PacketSocketType::Raw {
+            // destination address, source address, and ethertype
+            bytes.extend_from_slice(&HW_ADDR);
+            bytes.extend_from_slice(&HW_ADDR);
+            bytes.extend_from_slice(&(libc::ETH_P_IP as u16).to_be_bytes());
+        }
+
+        packet.display_bytes(&mut bytes).unwrap();
+
+        // the filter returns the number of bytes to keep, or 0 to drop the packet
+        if let Some(filter) = &self.filter {
+            let keep_len = filter.run(&bytes);
+            if keep_len == 0 {
+                log::trace!("Dropped a packet that didn't match the packet socket's filter");
+                return;
+            }
+            bytes.truncate(keep_len.try_into().unwrap());
+        }
+
+        let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as u16;
+        addr.sll_protocol = (libc::ETH_P_IP as u16).to_be();
+        addr.sll_ifindex = interface.index().try_into().unwrap();
+        addr.sll_hatype = interface.hardware_type();
+        addr.sll_pkttype = if is_outgoing {
+            libc::PACKET_OUTGOING
+        } else {
+            libc::PACKET_HOST
+        };
+        addr.sll_halen = HW_ADDR.len() as u8;
+
+        self.recv_buffer_len += bytes.len();
+        self.recv_buffer.push_back(CapturedPacket { bytes, addr });
+
+        self.refresh_readable(FileSignals::READ_BUFFER_GREW, cb_queue);
+    }
+
+    pub fn getsockname(&self) -> Result<Option<libc::sockaddr_ll>, Errno> {
+        let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as u16;
+        addr.sll_protocol = self.protocol.to_be();
+        addr.sll_ifindex = self.ifindex.unwrap_or(0).try_into().unwrap();
+        addr.sll_hatype = self.hatype;
+        if self.ifindex.is_some() {
+            addr.sll_halen = HW_ADDR.len() as u8;
+        }
+
+        Ok(Some(addr))
+    }
+
+    pub fn getpeername(&self) -> Result<Option<libc::sockaddr_ll>, Errno> {
+        Err(Errno::EOPNOTSUPP)
+    }
+
+    pub fn address_family(&self) -> linux_api::socket::AddressFamily {
+        linux_api::socket::AddressFamily::AF_PACKET
+    }
+
+    pub fn close(&mut self, cb_queue: &mut CallbackQueue) -> Result<(), SyscallError> {
+        self.recv_buffer.clear();
+        self.recv_buffer_len = 0;
+
+        self.update_state(
+            /* mask= */ FileState::all(),
+            FileState::CLOSED,
+            FileSignals::empty(),
+            cb_queue,
+        );
+        Ok(())
+    }
+
+    pub fn shutdown(
+        &mut self,
+        _how: Shutdown,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<(), SyscallError> {
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    pub fn getsockopt(
+        &mut self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: ForeignPtr<()>,
+        optlen: libc::socklen_t,
+        mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<libc::socklen_t, SyscallError> {
+        let val: libc::c_int = match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_RCVBUF) => self.recv_buffer_limit.try_into().unwrap(),
+            (libc::SOL_SOCKET, libc::SO_DOMAIN) => libc::AF_PACKET,
+            (libc::SOL_SOCKET, libc::SO_TYPE) => self.socket_type.into(),
+            (libc::SOL_SOCKET, libc::SO_PROTOCOL) => self.protocol.to_be().into(),
+            (libc::SOL_SOCKET, libc::SO_ERROR) => 0,
+            _ => {
+                warn_once_then_debug!(
+                    "getsockopt called with unsupported level {level} and opt {optname}"
+                );
+                return Err(Errno::ENOPROTOOPT.into());
+            }
+        };
+
+        let optval_ptr = optval_ptr.cast::<libc::c_int>();
+        let bytes_written = write_partial(mem, &val, optval_ptr, optlen as usize)?;
+
+        Ok(bytes_written as libc::socklen_t)
+    }
+
+    pub fn setsockopt(
+        &mut self,
+        level: libc::c_int,
+        optname: libc::c_int,
+        optval_ptr: ForeignPtr<()>,
+        optlen: libc::socklen_t,
+        mem: &MemoryManager,
+    ) -> Result<(), SyscallError> {
+        match (level, optname) {
+            (libc::SOL_SOCKET, libc::SO_RCVBUF) => {
+                type OptType = libc::c_int;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let val: usize = mem.read(optval_ptr)?.try_into().or(Err(Errno::EINVAL))?;
+
+                // the same limits as for udp sockets, and linux doubles the value upon setting
+                let val = std::cmp::max(val * 2, 2048);
+                let val = std::cmp::min(val, 268435456); // 2^28 = 256 MiB
+
+                self.recv_buffer_limit = val;
+            }
+            (libc::SOL_SOCKET, libc::SO_ATTACH_FILTER) => {
+                type OptType = libc::sock_fprog;
+
+                if usize::try_from(optlen).unwrap() < std::mem::size_of::<OptType>() {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let optval_ptr = optval_ptr.cast::<OptType>();
+                let fprog = mem.read(optval_ptr)?;
+
+                let len = usize::from(fprog.len);
+                if len == 0 || len > BPF_MAXINSNS {
+                    return Err(Errno::EINVAL.into());
+                }
+
+                let mut insns: Vec<libc::sock_filter> = vec![unsafe { std::mem::zeroed() }; len];
+                mem.copy_from_ptr(
+                    &mut insns,
+                    ForeignArrayPtr::new(ForeignPtr::from_raw_ptr(fprog.filter), len),
+                )?;
+
+                // replaces any existing filter
+                self.filter = Some(BpfProgram::new(insns)?);
+            }
+            (libc::SOL_SOCKET, libc::SO_DETACH_FILTER) => {
+                if self.filter.take().is_none() {
+                    return Err(Errno::ENOENT.into());
+                }
+            }
+            _ => {
+                warn_once_then_debug!(
+                    "setsockopt called with unsupported level {level} and opt {optname}"
+                );
+                return Err(Errno::ENOPROTOOPT.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn bind(
+        socket: &Arc<AtomicRefCell<Self>>,
+        addr: Option<&SockaddrStorage>,
+        net_ns: &NetworkNamespace,
+        _rng: impl rand::Rng,
+    ) -> Result<(), SyscallError> {
+        let socket = &mut *socket.borrow_mut();
+
+        // if the address pointer was NULL
+        let Some(addr) = addr else {
+            return Err(Errno::EFAULT.into());
+        };
+
+        // if not a link-layer socket address
+        let Some(addr) = addr.as_link() else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        // an interface index of 0 captures from all interfaces
+        let ifindex = match u32::try_from(addr.sll_ifindex) {
+            Ok(0) => None,
+            Ok(x) => Some(x),
+            Err(_) => return Err(Errno::ENODEV.into()),
+        };
+
+        let hatype = match ifindex {
+            Some(ifindex) => net_ns
+                .interface_hardware_type(ifindex)
+                .ok_or(Errno::ENODEV)?,
+            None => 0,
+        };
+
+        socket.ifindex = ifindex;
+        socket.hatype = hatype;
+
+        // a protocol of 0 keeps the socket's existing protocol
+        if addr.sll_protocol != 0 {
+            socket.protocol = u16::from_be(addr.sll_protocol);
+        }
+
+        Ok(())
+    }
+
+    pub fn readv(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<libc::off_t>,
+        _flags: libc::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        // we could call PacketSocket::recvmsg() here, but for now we expect that there are no code
+        // paths that would call PacketSocket::readv() since the readv() syscall handler should have
+        // called PacketSocket::recvmsg() instead
+        panic!("Called PacketSocket::readv() on a packet socket");
+    }
+
+    pub fn writev(
+        &mut self,
+        _iovs: &[IoVec],
+        _offset: Option<libc::off_t>,
+        _flags: libc::c_int,
+        _mem: &mut MemoryManager,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        // we could call PacketSocket::sendmsg() here, but for now we expect that there are no code
+        // paths that would call PacketSocket::writev() since the writev() syscall handler should
+        // have called PacketSocket::sendmsg() instead
+        panic!("Called PacketSocket::writev() on a packet socket");
+    }
+
+    pub fn sendmsg(
+        _socket: &Arc<AtomicRefCell<Self>>,
+        _args: SendmsgArgs,
+        _mem: &mut MemoryManager,
+        _net_ns: &NetworkNamespace,
+        _rng: impl rand::Rng,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        warn_once_then_debug!("Sending on packet sockets is not supported; Returning EOPNOTSUPP");
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    pub fn recvmsg(
+        socket: &Arc<AtomicRefCell<Self>>,
+        args: RecvmsgArgs,
+        mem: &mut MemoryManager,
+        cb_queue: &mut CallbackQueue,
+    ) -> Result<RecvmsgReturn, SyscallError> {
+        let socket_ref = &mut *socket.borrow_mut();
+
+        if !args.control_ptr.ptr().is_null() {
+            log::debug!("Packet sockets don't yet support control data for recvmsg()");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let Some(mut flags) = MsgFlags::from_bits(args.flags) else {
+            log::debug!("Unrecognized recv flags: {:#b}", args.flags);
+            return Err(Errno::EINVAL.into());
+        };
+
+        if socket_ref.status.contains(FileStatus::NONBLOCK) {
+            flags.insert(MsgFlags::MSG_DONTWAIT);
+        }
+
+        let len: libc::size_t = args.iovs.iter().map(|x| x.len).sum();
+
+        // run in a closure so that an early return doesn't skip checking if we should block
+        let result = (|| {
+            let packet = socket_ref.recv_buffer.front().ok_or(Errno::EWOULDBLOCK)?;
+
+            // truncate the packet if it's larger than the user-provided buffers
+            let truncated_packet = &packet.bytes[..std::cmp::min(len, packet.bytes.len())];
+
+            let mut writer = IoVecWriter::new(args.iovs, mem);
+            writer
+                .write_all(truncated_packet)
+                .map_err(|e| Errno::try_from(e).unwrap())?;
+
+            let return_val = if flags.contains(MsgFlags::MSG_TRUNC) {
+                packet.bytes.len()
+            } else {
+                truncated_packet.len()
+            };
+
+            let mut return_flags = MsgFlags::empty();
+            return_flags.set(
+                MsgFlags::MSG_TRUNC,
+                truncated_packet.len() < packet.bytes.len(),
+            );
+
+            let rv = RecvmsgReturn {
+                return_val: return_val.try_into().unwrap(),
+                addr: Some(packet.addr.into()),
+                msg_flags: return_flags.bits(),
+                control_len: 0,
+            };
+
+            if !flags.contains(MsgFlags::MSG_PEEK) {
+                let packet = socket_ref.recv_buffer.pop_front().unwrap();
+                socket_ref.recv_buffer_len -= packet.bytes.len();
+            }
+
+            Ok(rv)
+        })();
+
+        socket_ref.refresh_readable(FileSignals::empty(), cb_queue);
+
+        // if the syscall would block and we don't have the MSG_DONTWAIT flag
+        if result.as_ref().err() == Some(&Errno::EWOULDBLOCK)
+            && !flags.contains(MsgFlags::MSG_DONTWAIT)
+        {
+            return Err(SyscallError::new_blocked_on_file(
+                File::Socket(Socket::Packet(socket.clone())),
+                FileState::READABLE,
+                socket_ref.supports_sa_restart(),
+            ));
+        }
+
+        Ok(result?)
+    }
+
+    pub fn listen(
+        _socket: &Arc<AtomicRefCell<Self>>,
+        _backlog: i32,
+        _net_ns: &NetworkNamespace,
+        _rng: impl rand::Rng,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<(), Errno> {
+        Err(Errno::EOPNOTSUPP)
+    }
+
+    pub fn connect(
+        _socket: &Arc<AtomicRefCell<Self>>,
+        _addr: &SockaddrStorage,
+        _net_ns: &NetworkNamespace,
+        _rng: impl rand::Rng,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<(), SyscallError> {
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    pub fn accept(
+        &mut self,
+        _net_ns: &NetworkNamespace,
+        _rng: impl rand::Rng,
+        _cb_queue: &mut CallbackQueue,
+    ) -> Result<OpenFile, SyscallError> {
+        Err(Errno::EOPNOTSUPP.into())
+    }
+
+    pub fn ioctl(
+        &mut self,
+        request: IoctlRequest,
+        arg_ptr: ForeignPtr<()>,
+        mem: &mut MemoryManager,
+    ) -> SyscallResult {
+        match request {
+            // equivalent to SIOCINQ
+            IoctlRequest::FIONREAD => {
+                let len = self
+                    .recv_buffer
+                    .front()
+                    .map(|x| x.bytes.len())
+                    .unwrap_or(0)
+                    .try_into()
+                    .unwrap();
+
+                let arg_ptr = arg_ptr.cast::<libc::c_int>();
+                mem.write(arg_ptr, &len)?;
+
+                Ok(0.into())
+            }
+            _ => {
+                warn_once_then_debug!(
+                    "We do not yet handle ioctl request {request:?} on packet sockets"
+                );
+                Err(Errno::EINVAL.into())
+            }
+        }
+    }
+
+    pub fn stat(&self) -> Result<linux_api::stat::stat, SyscallError> {
+        warn_once_then_debug!("We do not yet handle stat calls on packet sockets");
+        Err(Errno::EINVAL.into())
+    }
+
+    pub fn add_listener(
+        &mut self,
+        monitoring_state: FileState,
+        monitoring_signals: FileSignals,
+        filter: StateListenerFilter,
+        notify_fn: impl Fn(FileState, FileState, FileSignals, &mut CallbackQueue)
+            + Send
+            + Sync
+            + 'static,
+    ) -> StateListenHandle {
+        self.event_source
+            .add_listener(monitoring_state, monitoring_signals, filter, notify_fn)
+    }
+
+    pub fn add_legacy_listener(&mut self, ptr: HostTreePointer<c::StatusListener>) {
+        self.event_source.add_legacy_listener(ptr);
+    }
+
+    pub fn remove_legacy_listener(&mut self, ptr: *mut c::StatusListener) {
+        self.event_source.remove_legacy_listener(ptr);
+    }
+
+    pub fn state(&self) -> FileState {
+        self.state
+    }
+
+    fn refresh_readable(&mut self, signals: FileSignals, cb_queue: &mut CallbackQueue) {
+        let readable = !self.recv_buffer.is_empty();
+        let readable = readable.then_some(FileState::READABLE).unwrap_or_default();
+
+        self.update_state(
+            /* mask= */ FileState::READABLE,
+            readable,
+            signals,
+            cb_queue,
+        );
+    }
+
+    fn update_state(
+        &mut self,
+        mask: FileState,
+        state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let old_state = self.state;
+
+        // remove the masked flags, then copy the masked flags
+        self.state.remove(mask);
+        self.state.insert(state & mask);
+
+        self.handle_state_change(old_state, signals, cb_queue);
+    }
+
+    fn handle_state_change(
+        &mut self,
+        old_state: FileState,
+        signals: FileSignals,
+        cb_queue: &mut CallbackQueue,
+    ) {
+        let states_changed = self.state ^ old_state;
+
+        // if nothing changed
+        if states_changed.is_empty() && signals.is_empty() {
+            return;
+        }
+
+        self.event_source
+            .notify_listeners(self.state, states_changed, signals, cb_queue);
+    }
+}
+
+/// A copy of a packet in the receive buffer.
+struct CapturedPacket {
+    /// The packet bytes, including the link-layer header for `SOCK_RAW` sockets.
+    bytes: Vec<u8>,
+    /// The address returned by `recvfrom()`, which describes the interface the packet was captured
+    /// on.
+    addr: libc::sockaddr_ll,
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PacketSocketType {
+    /// "Cooked" packets without a link-layer header.
+    Dgram,
+    /// Packets with a link-layer header.
+    Raw,
+}
+
+impl TryFrom<libc::c_int> for PacketSocketType {
+    type Error = PacketSocketTypeConversionError;
+    fn try_from(val: libc::c_int) -> Result<Self, Self::Error> {
+        match val {
+            libc::SOCK_DGRAM => Ok(Self::Dgram),
+            libc::SOCK_RAW => Ok(Self::Raw),
+            x => Err(PacketSocketTypeConversionError(x)),
+        }
+    }
+}
+
+impl From<PacketSocketType> for libc::c_int {
+    fn from(val: PacketSocketType) -> Self {
+        match val {
+            PacketSocketType::Dgram => libc::SOCK_DGRAM,
+            PacketSocketType::Raw => libc::SOCK_RAW,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PacketSocketTypeConversionError(libc::c_int);
+
+impl std::error::Error for PacketSocketTypeConversionError {}
+
+impl std::fmt::Display for PacketSocketTypeConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid socket type {}; packet sockets only support SOCK_DGRAM and SOCK_RAW",
+            self.0
+        )
+    }
+}
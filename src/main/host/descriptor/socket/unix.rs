@@ -16,7 +16,9 @@ use crate::host::descriptor::shared_buf::{
     BufferHandle, BufferSignals, BufferState, ReaderHandle, SharedBuf, WriterHandle,
 };
 use crate::host::descriptor::socket::abstract_unix_ns::AbstractUnixNamespace;
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket};
+use crate::host::descriptor::socket::{
+    IgnoredSockopts, RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket,
+};
 use crate::host::descriptor::{
     File, FileMode, FileSignals, FileState, FileStatus, OpenFile, SyscallResult,
 };
@@ -61,6 +63,7 @@ impl UnixSocket {
                 socket_type,
                 namespace: Arc::clone(namespace),
                 has_open_file: false,
+                ignored_sockopts: IgnoredSockopts::default(),
             };
 
             // may generate new events
@@ -119,6 +122,10 @@ impl UnixSocket {
         linux_api::socket::AddressFamily::AF_UNIX
     }
 
+    pub fn socket_type(&self) -> UnixSocketType {
+        self.common.socket_type
+    }
+
     fn recv_buffer(&self) -> &Arc<AtomicRefCell<SharedBuf>> {
         &self.common.recv_buffer
     }
@@ -278,14 +285,24 @@ impl UnixSocket {
 
     pub fn setsockopt(
         &mut self,
-        _level: libc::c_int,
-        _optname: libc::c_int,
+        level: libc::c_int,
+        optname: libc::c_int,
         _optval_ptr: ForeignPtr<()>,
         _optlen: libc::socklen_t,
         _memory_manager: &MemoryManager,
     ) -> Result<(), SyscallError> {
-        log::warn!("setsockopt() syscall not yet supported for unix sockets; Returning ENOSYS");
-        Err(Errno::ENOSYS.into())
+        log_once_per_value_at_level!(
+            (level, optname),
+            (i32, i32),
+            log::Level::Warn,
+            log::Level::Debug,
+            "setsockopt called with unsupported level {level} and opt {optname} for unix sockets"
+        );
+        Err(Errno::ENOPROTOOPT.into())
+    }
+
+    pub fn ignored_sockopts_mut(&mut self) -> &mut IgnoredSockopts {
+        &mut self.common.ignored_sockopts
     }
 
     pub fn pair(
@@ -1528,6 +1545,8 @@ impl Protocol for ConnOrientedConnected {
                 FileState::READABLE,
                 recv_buffer.has_data() || recv_buffer.num_writers() == 0,
             );
+            // the peer has closed, but there may still be buffered data to read
+            new_state.set(FileState::RDHUP, recv_buffer.num_writers() == 0);
             new_state.set(
                 FileState::WRITABLE,
                 common.sent_len < common.send_limit || send_buffer.num_readers() == 0,
@@ -1594,11 +1613,8 @@ impl Protocol for ConnOrientedConnected {
         mem: &mut MemoryManager,
         cb_queue: &mut CallbackQueue,
     ) -> Result<RecvmsgReturn, SyscallError> {
-        if !args.control_ptr.ptr().is_null() {
-            log::debug!("Unix sockets don't yet support control data for recvmsg()");
-            return Err(Errno::EINVAL.into());
-        }
-
+        // We don't support sending control data, so there's never any to receive. Any control
+        // buffer is left empty.
         let (rv, num_removed_from_buf, msg_flags) =
             common.recvmsg(socket, args.iovs, args.flags, mem, cb_queue)?;
         let num_removed_from_buf = u64::try_from(num_removed_from_buf).unwrap();
@@ -1817,15 +1833,23 @@ impl Protocol for ConnLessInitial {
         mem: &mut MemoryManager,
         cb_queue: &mut CallbackQueue,
     ) -> Result<RecvmsgReturn, SyscallError> {
-        if !args.control_ptr.ptr().is_null() {
-            log::debug!("Unix sockets don't yet support control data for recvmsg()");
-            return Err(Errno::EINVAL.into());
-        }
-
+        // We don't support sending control data, so there's never any to receive. Any control
+        // buffer is left empty.
         let (rv, num_removed_from_buf, msg_flags) =
             common.recvmsg(socket, args.iovs, args.flags, mem, cb_queue)?;
         let num_removed_from_buf = u64::try_from(num_removed_from_buf).unwrap();
 
+        if args.flags & libc::MSG_PEEK != 0 {
+            // the message is still in the buffer
+            let byte_data = self.recv_data.front().unwrap();
+            return Ok(RecvmsgReturn {
+                return_val: rv.try_into().unwrap(),
+                addr: byte_data.from_addr.map(Into::into),
+                msg_flags,
+                control_len: 0,
+            });
+        }
+
         let byte_data = self.recv_data.pop_front().unwrap();
         assert!(num_removed_from_buf == byte_data.num_bytes);
 
@@ -1977,6 +2001,8 @@ struct UnixSocketCommon {
     // should only be used by `OpenFile` to make sure there is only ever one `OpenFile` instance for
     // this file
     has_open_file: bool,
+    /// Unsupported socket options that were set, if they're being ignored.
+    ignored_sockopts: IgnoredSockopts,
 }
 
 impl UnixSocketCommon {
@@ -2226,7 +2252,12 @@ impl UnixSocketCommon {
         mem: &mut MemoryManager,
         cb_queue: &mut CallbackQueue,
     ) -> Result<(usize, usize, libc::c_int), SyscallError> {
-        let supported_flags = MsgFlags::MSG_DONTWAIT | MsgFlags::MSG_TRUNC;
+        // MSG_WAITALL is handled by the syscall handler for stream sockets, and is ignored for
+        // message-based sockets
+        let supported_flags = MsgFlags::MSG_DONTWAIT
+            | MsgFlags::MSG_PEEK
+            | MsgFlags::MSG_TRUNC
+            | MsgFlags::MSG_WAITALL;
 
         // if there's a flag we don't support, it's probably best to raise an error rather than do
         // the wrong thing
@@ -2260,22 +2291,36 @@ impl UnixSocketCommon {
 
             let writer = IoVecWriter::new(iovs, mem);
 
-            let (num_copied, num_removed_from_buf) = recv_buffer
-                .read(writer, cb_queue)
-                .map_err(|e| Errno::try_from(e).unwrap())?;
+            // for message-based sockets, `msg_len` is the size of the message even if it didn't
+            // fit in the iovs
+            let (num_copied, msg_len) = if flags.contains(MsgFlags::MSG_PEEK) {
+                recv_buffer.peek(writer)
+            } else {
+                recv_buffer.read(writer, cb_queue)
+            }
+            .map_err(|e| Errno::try_from(e).unwrap())?;
+
+            // a peek doesn't remove anything from the buffer
+            let num_removed_from_buf = if flags.contains(MsgFlags::MSG_PEEK) {
+                0
+            } else {
+                msg_len
+            };
 
             let mut msg_flags = 0;
 
-            if flags.contains(MsgFlags::MSG_TRUNC)
-                && [UnixSocketType::Dgram, UnixSocketType::SeqPacket].contains(&self.socket_type)
-            {
-                if num_copied < num_removed_from_buf {
-                    msg_flags |= libc::MSG_TRUNC;
-                }
+            let is_message_based =
+                [UnixSocketType::Dgram, UnixSocketType::SeqPacket].contains(&self.socket_type);
+
+            // report a truncated message even if the MSG_TRUNC flag wasn't given
+            if is_message_based && num_copied < msg_len {
+                msg_flags |= libc::MSG_TRUNC;
+            }
 
+            if flags.contains(MsgFlags::MSG_TRUNC) && is_message_based {
                 // we're a message-based socket and MSG_TRUNC is set, so return the total size of
                 // the message, not the number of bytes we read
-                Ok((num_removed_from_buf, num_removed_from_buf, msg_flags))
+                Ok((msg_len, num_removed_from_buf, msg_flags))
             } else {
                 // We're a stream-based socket. Unlike TCP sockets, unix stream sockets ignore the
                 // MSG_TRUNC flag.
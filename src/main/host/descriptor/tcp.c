@@ -26,7 +26,6 @@
 #include "main/host/descriptor/descriptor.h"
 #include "main/host/descriptor/socket.h"
 #include "main/host/descriptor/tcp_cong.h"
-#include "main/host/descriptor/tcp_cong_reno.h"
 #include "main/host/descriptor/tcp_retransmit_tally.h"
 #include "main/host/protocol.h"
 #include "main/host/tracker.h"
@@ -35,6 +34,10 @@
 #include "main/utility/priority_queue.h"
 #include "main/utility/utility.h"
 
+/* 0 is saved for representing control packets, so the first packet (the SYN packet) has this
+ * sequence number */
+#define TCP_INITIAL_SEQUENCE_NUMBER 1
+
 enum TCPState {
     TCPS_CLOSED, TCPS_LISTEN,
     TCPS_SYNSENT, TCPS_SYNRECEIVED, TCPS_ESTABLISHED,
@@ -74,7 +77,7 @@ enum TCPError {
 };
 
 enum TCPChildState {
-    TCPCS_NONE, TCPCS_INCOMPLETE, TCPCS_PENDING, TCPCS_ACCEPTED
+    TCPCS_NONE, TCPCS_INCOMPLETE, TCPCS_DEFERRED, TCPCS_PENDING, TCPCS_ACCEPTED
 };
 
 typedef enum TCPReceiveState TCPReceiveState;
@@ -173,6 +176,10 @@ struct _TCP {
         guint32 numQuickACKsSent;
         gboolean delayedACKIsScheduled;
         guint32 delayedACKCounter;
+        /* the next segment with data is acknowledged immediately (TCP_QUICKACK) */
+        gboolean quickACKPending;
+        /* the user disabled TCP_QUICKACK */
+        gboolean quickACKDisabled;
         /* list of selective ACKs, packets received after a missing packet */
         GList* selectiveACKs;
     } send;
@@ -211,6 +218,8 @@ struct _TCP {
     struct {
       gint rttSmoothed;
       gint rttVariance;
+      /* the most recent rtt measurement, or 0 if there hasn't been one */
+      CSimulationTime rttLastSample;
     } timing;
 
     /* TODO: these should probably be stamped when the network interface sends
@@ -224,6 +233,59 @@ struct _TCP {
         guint32 rtt;
     } info;
 
+    /* pacing of outgoing data packets (SO_MAX_PACING_RATE) */
+    struct {
+        /* maximum sending rate in bytes per second, or G_MAXUINT64 if unlimited */
+        guint64 maxRate;
+        /* the earliest time at which the next data packet can be sent */
+        CSimulationTime nextSendTime;
+        gboolean flushIsScheduled;
+    } pacing;
+
+    /* urgent (out-of-band) data sent with MSG_OOB */
+    struct {
+        /* the urgent byte is left in the normal data stream (SO_OOBINLINE) */
+        gboolean isInline;
+        /* sequence number of the last urgent packet we received */
+        guint32 sequence;
+        gboolean sequenceIsSet;
+        /* the urgent byte, if it is waiting to be read with MSG_OOB */
+        guint8 byte;
+        gboolean byteIsValid;
+        /* new urgent data arrived, but the socket owner has not been sent a SIGURG yet */
+        gboolean signalPending;
+    } urgent;
+
+    /* seconds that a child of this listening socket waits for data before it can be accepted
+     * (TCP_DEFER_ACCEPT), or 0 if not deferred */
+    guint deferAcceptSecs;
+
+    /* closing the socket aborts the connection with a RST (SO_LINGER with a zero timeout) */
+    gboolean abortOnClose;
+
+    /* explicit congestion notification (RFC 3168) */
+    struct {
+        /* ecn was negotiated during the handshake */
+        gboolean isEnabled;
+        /* we received at least one ECN-capable data packet */
+        gboolean ectSeen;
+        /* we received a congestion-experienced packet, so we set ECE on our acks until the peer
+         * tells us that it reduced its congestion window */
+        gboolean echoPending;
+        /* we reacted to an ECE, so we set CWR on our next new data packet */
+        gboolean cwrPending;
+        /* we don't react to another ECE until this sequence has been acked */
+        guint32 recoveryPoint;
+    } ecn;
+
+    /* maximum segment size */
+    struct {
+        /* the mss requested by the user with TCP_MAXSEG, or 0 if not set */
+        guint32 user;
+        /* the largest segment received, which is our estimate of the peer's mss */
+        guint32 received;
+    } mss;
+
     /* TCP throttles outgoing data packets if too many are in flight */
     PriorityQueue* throttledOutput;
     /* track amount of queued application data */
@@ -390,6 +452,42 @@ struct TCPCong_ *tcp_cong(TCP *tcp) {
     return &tcp->cong;
 }
 
+gboolean tcp_setCongestionControl(TCP* tcp, const char* name) {
+    MAGIC_ASSERT(tcp);
+
+    TCPCongInit init = tcpcong_getInit(name);
+    if (init == NULL) {
+        return FALSE;
+    }
+
+    /* like linux, setting the current algorithm again doesn't reset it */
+    if (strcmp(tcpcong_nameStr(&tcp->cong), name) == 0) {
+        return TRUE;
+    }
+
+    guint32 cwnd = tcp->cong.cwnd;
+    tcp->cong.hooks->tcp_cong_delete(tcp);
+    init(tcp);
+    tcp->cong.cwnd = cwnd;
+
+    return TRUE;
+}
+
+gint tcp_getSmoothedRTT(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return tcp->timing.rttSmoothed;
+}
+
+CSimulationTime tcp_getLastRTTSample(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return tcp->timing.rttLastSample;
+}
+
+guint32 tcp_getPacketsInFlight(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return tcp->send.next - tcp->send.unacked;
+}
+
 void tcp_clearAllChildrenIfServer(TCP* tcp) {
     MAGIC_ASSERT(tcp);
     if(tcp->server && tcp->server->children) {
@@ -643,6 +741,77 @@ static void _tcp_autotuneSendBuffer(TCP* tcp, const Host* host) {
     }
 }
 
+guint64 tcp_getMaxPacingRate(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return tcp->pacing.maxRate;
+}
+
+/* the rate in bytes per second at which data packets are sent, or G_MAXUINT64 if unlimited */
+static guint64 _tcp_getPacingRate(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return MIN(tcp->pacing.maxRate, tcp->cong.hooks->tcp_cong_pacing_rate(tcp));
+}
+
+void tcp_setMaxPacingRate(TCP* tcp, guint64 rate) {
+    MAGIC_ASSERT(tcp);
+    tcp->pacing.maxRate = rate;
+}
+
+gboolean tcp_getUrgentInline(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return tcp->urgent.isInline;
+}
+
+void tcp_setUrgentInline(TCP* tcp, gboolean isInline) {
+    MAGIC_ASSERT(tcp);
+    tcp->urgent.isInline = isInline;
+}
+
+void tcp_setAbortOnClose(TCP* tcp, gboolean abortOnClose) {
+    MAGIC_ASSERT(tcp);
+    tcp->abortOnClose = abortOnClose;
+}
+
+gboolean tcp_takeUrgentSignal(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    gboolean signalPending = tcp->urgent.signalPending;
+    tcp->urgent.signalPending = FALSE;
+    return signalPending;
+}
+
+/* the size of the data in a segment, bounded by the path mtu */
+static gsize _tcp_getEffectiveMaxSegmentSize(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    if (tcp->mss.user == 0) {
+        return CONFIG_TCP_MAX_SEGMENT_SIZE;
+    }
+    return MIN(tcp->mss.user, CONFIG_TCP_MAX_SEGMENT_SIZE);
+}
+
+guint32 tcp_getMaxSegmentSize(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    if (tcp->state == TCPS_CLOSED || tcp->state == TCPS_LISTEN) {
+        /* like linux, return the user's mss if there is no connection */
+        return (tcp->mss.user != 0) ? tcp->mss.user : CONFIG_TCP_DEFAULT_SEGMENT_SIZE;
+    }
+    return (guint32)_tcp_getEffectiveMaxSegmentSize(tcp);
+}
+
+void tcp_setMaxSegmentSize(TCP* tcp, guint32 mss) {
+    MAGIC_ASSERT(tcp);
+    tcp->mss.user = mss;
+}
+
+guint tcp_getDeferAccept(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return tcp->deferAcceptSecs;
+}
+
+void tcp_setDeferAccept(TCP* tcp, guint seconds) {
+    MAGIC_ASSERT(tcp);
+    tcp->deferAcceptSecs = seconds;
+}
+
 void tcp_disableSendBufferAutotuning(TCP* tcp) {
     MAGIC_ASSERT(tcp);
     tcp->autotune.userDisabledSend = TRUE;
@@ -657,6 +826,21 @@ void tcp_disableReceiveBufferAutotuning(TCP* tcp) {
 static void _tcp_runCloseTimerExpiredTask(const Host* host, gpointer tcp, gpointer userData);
 static void _tcp_clearRetransmit(TCP* tcp, guint sequence);
 
+/* Update whether all of the data that the user wrote, and our FIN if we sent one, has been
+ * acknowledged by the peer. A lingering close (SO_LINGER) waits for this. */
+static void _tcp_updateOutputAckedStatus(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+
+    gboolean finIsUnacked = (tcp->flags & TCPF_SHOULD_SEND_WR_FIN) ||
+                            tcp->state == TCPS_FINWAIT1 || tcp->state == TCPS_CLOSING ||
+                            tcp->state == TCPS_LASTACK;
+    /* nothing more will be sent after the connection has closed */
+    gboolean isAcked = tcp->state == TCPS_CLOSED ||
+                       (tcp_getOutputBufferLength(tcp) == 0 && !finIsUnacked);
+
+    legacyfile_adjustStatus((LegacyFile*)tcp, FileState_SOCKET_OUTPUT_ACKED, isAcked, 0);
+}
+
 static void _tcp_setState(TCP* tcp, const Host* host, enum TCPState state) {
     MAGIC_ASSERT(tcp);
 
@@ -727,15 +911,16 @@ static void _tcp_setState(TCP* tcp, const Host* host, enum TCPState state) {
                     if((parent->state == TCPS_CLOSED) && (g_hash_table_size(parent->server->children) <= 0)) {
                         if (disassociate) {
                             /* this will unbind from the network interface and free socket */
-                            host_disassociateInterface(
-                                host, PTCP, sock_ip, sock_port, peer_ip, peer_port);
+                            host_disassociateInterface(host, (uintptr_t)parent, PTCP, sock_ip,
+                                                       sock_port, peer_ip, peer_port);
                         }
                     }
                 }
 
                 if (disassociate) {
                     /* TODO: we should only be disassociating non-child sockets */
-                    host_disassociateInterface(host, PTCP, sock_ip, sock_port, peer_ip, peer_port);
+                    host_disassociateInterface(
+                        host, (uintptr_t)tcp, PTCP, sock_ip, sock_port, peer_ip, peer_port);
                 }
             }
             break;
@@ -766,6 +951,8 @@ static void _tcp_setState(TCP* tcp, const Host* host, enum TCPState state) {
         default:
             break;
     }
+
+    _tcp_updateOutputAckedStatus(tcp);
 }
 
 static void _tcp_runCloseTimerExpiredTask(const Host* host, gpointer voidInetSocket,
@@ -1138,6 +1325,7 @@ static void _tcp_updateRTTEstimate(TCP* tcp, const Host* host, CSimulationTime t
     MAGIC_ASSERT(tcp);
 
     CSimulationTime now = worker_getCurrentSimulationTime();
+    tcp->timing.rttLastSample = now - timestamp;
     gint rtt = (gint)((now - timestamp) / SIMTIME_ONE_MILLISECOND);
 
     if(rtt <= 0) {
@@ -1234,6 +1422,49 @@ static void _tcp_sendShutdownFin(TCP* tcp, const Host* host) {
     }
 }
 
+/* Abort the connection: tell the peer with a RST and close without the FIN handshake. Like
+ * Linux, we do this when the user closes the socket without reading all of the data that the peer
+ * sent, when new data arrives after the user closed the socket, or when the user closes the socket
+ * with a zero SO_LINGER timeout. */
+static void _tcp_sendResetAndClose(TCP* tcp, const Host* host) {
+    MAGIC_ASSERT(tcp);
+
+    trace("%s <-> %s: aborting connection", tcp->super.boundString, tcp->super.peerString);
+
+    /* data that we haven't sent yet is discarded */
+    priorityqueue_clear(tcp->throttledOutput);
+    tcp->throttledOutputLength = 0;
+
+    /* the RST has no sequence number, so it's sent right away and never retransmitted */
+    _tcp_sendControlPacket(tcp, host, PTCP_RST);
+    _tcp_setState(tcp, host, TCPS_CLOSED);
+}
+
+/* set the ecn fields of an outgoing packet on a connection that negotiated ecn */
+static void _tcp_updateECN(TCP* tcp, Packet* packet, PacketTCPHeader* header) {
+    MAGIC_ASSERT(tcp);
+
+    /* a retransmitted packet still has the flags from when it was first sent */
+    header->flags &= ~(PTCP_ECE | PTCP_CWR);
+
+    if ((header->flags & PTCP_ACK) && tcp->ecn.echoPending) {
+        header->flags |= PTCP_ECE;
+    }
+
+    /* only new data packets are ECN-capable (RFC 3168, section 6.1.5) */
+    gboolean isRetransmit = (packet_getDeliveryStatus(packet) & PDS_SND_TCP_RETRANSMITTED) != 0;
+    if (packet_getPayloadSize(packet) > 0 && !isRetransmit) {
+        packet_setECN(packet, PECN_ECT0);
+
+        if (tcp->ecn.cwrPending) {
+            header->flags |= PTCP_CWR;
+            tcp->ecn.cwrPending = FALSE;
+        }
+    } else {
+        packet_setECN(packet, PECN_NOT_ECT);
+    }
+}
+
 void tcp_networkInterfaceIsAboutToSendPacket(TCP* tcp, const Host* host, Packet* packet) {
     MAGIC_ASSERT(tcp);
 
@@ -1250,6 +1481,10 @@ void tcp_networkInterfaceIsAboutToSendPacket(TCP* tcp, const Host* host, Packet*
 
     PacketTCPHeader* header = packet_getTCPHeader(packet);
 
+    if (tcp->ecn.isEnabled && !(header->flags & PTCP_SYN)) {
+        _tcp_updateECN(tcp, packet, header);
+    }
+
     if(header->flags & PTCP_ACK) {
         /* we are sending an ACK already, so we may not need any delayed ACK */
         tcp->send.delayedACKCounter = 0;
@@ -1266,6 +1501,42 @@ void tcp_networkInterfaceIsAboutToSendPacket(TCP* tcp, const Host* host, Packet*
     }
 }
 
+static void _tcp_runPacingTimerExpiredTask(const Host* host, gpointer voidInetSocket,
+                                           gpointer unused) {
+    const InetSocket* inetSocket = voidInetSocket;
+    utility_alwaysAssert(inetSocket != NULL);
+    TCP* tcp = inetsocket_asLegacyTcp(inetSocket);
+    MAGIC_ASSERT(tcp);
+
+    tcp->pacing.flushIsScheduled = FALSE;
+
+    /* if we are closed, we don't care */
+    if (tcp->state == TCPS_CLOSED) {
+        return;
+    }
+
+    /* send the packets that were held back */
+    _tcp_flush(tcp, host);
+}
+
+static void _tcp_schedulePacingTimer(TCP* tcp, const Host* host, CSimulationTime now) {
+    MAGIC_ASSERT(tcp);
+
+    if (tcp->pacing.flushIsScheduled) {
+        return;
+    }
+
+    utility_alwaysAssert(tcp->rustSocket != NULL);
+    const InetSocket* inetSocket = inetsocketweak_upgrade(tcp->rustSocket);
+    utility_alwaysAssert(inetSocket != NULL);
+    TaskRef* pacingTask = taskref_new_bound(host_getID(host), _tcp_runPacingTimerExpiredTask,
+                                            (void*)inetSocket, NULL, inetsocket_dropVoid, NULL);
+    host_scheduleTaskWithDelay(host, pacingTask, tcp->pacing.nextSendTime - now);
+    taskref_drop(pacingTask);
+
+    tcp->pacing.flushIsScheduled = TRUE;
+}
+
 static void _tcp_flush(TCP* tcp, const Host* host) {
     MAGIC_ASSERT(tcp);
 
@@ -1332,9 +1603,25 @@ static void _tcp_flush(TCP* tcp, const Host* host) {
                 _rswlog(tcp, "Can't retransmit %d, inWindow=%d, inBuffer=%d\n", header->sequence, fitsInWindow, fitsInBuffer);
                 /* we cant send the packet yet */
                 break;
-            } else {
-                /* we will send the data packet */
-                tcp->info.lastDataSent = now;
+            }
+
+            /* we cant send it until the pacing rate allows it */
+            guint64 pacingRate = _tcp_getPacingRate(tcp);
+            if (pacingRate != G_MAXUINT64 && now < tcp->pacing.nextSendTime) {
+                _tcp_schedulePacingTimer(tcp, host, now);
+                break;
+            }
+
+            /* we will send the data packet */
+            tcp->info.lastDataSent = now;
+
+            if (pacingRate != G_MAXUINT64) {
+                /* the next data packet must wait until this one has been sent at the pacing rate
+                 * (a rate of 0 would stop the socket from sending, so use the lowest non-zero rate) */
+                guint64 rate = MAX(pacingRate, 1);
+                CSimulationTime delay =
+                    (CSimulationTime)((length * (guint64)SIMTIME_ONE_SECOND) / rate);
+                tcp->pacing.nextSendTime = MAX(now, tcp->pacing.nextSendTime) + delay;
             }
         }
 
@@ -1377,6 +1664,21 @@ static void _tcp_flush(TCP* tcp, const Host* host) {
             priorityqueue_pop(tcp->unorderedInput);
             tcp->unorderedInputLength -= packet_getPayloadSize(packet);
             packet_unref(packet);
+        } else if (header->sequence == tcp->receive.next && (header->flags & PTCP_URG) &&
+                   !tcp->urgent.isInline) {
+            /* the urgent byte is kept out-of-band instead of in the user input buffer */
+            guint copied = packet_copyPayloadShadow(packet, 0, &tcp->urgent.byte, 1);
+            utility_debugAssert(copied == 1);
+            tcp->urgent.byteIsValid = TRUE;
+            legacyfile_adjustStatus((LegacyFile*)tcp, FileState_PRIORITY, TRUE, 0);
+
+            tcp->receive.lastSequence = header->sequence;
+            priorityqueue_pop(tcp->unorderedInput);
+            tcp->unorderedInputLength -= packet_getPayloadSize(packet);
+            packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DELIVERED);
+            packet_unref(packet);
+            (tcp->receive.next)++;
+            continue;
         } else if (header->sequence == tcp->receive.next) {
             /* move from the unordered buffer to user input buffer */
             gboolean fitInBuffer = legacysocket_addToInputBuffer(&(tcp->super), host, packet);
@@ -1390,6 +1692,11 @@ static void _tcp_flush(TCP* tcp, const Host* host) {
                 legacyfile_adjustStatus((LegacyFile*)tcp, FileState_READABLE, TRUE, signals);
             }
 
+            if (fitInBuffer && (header->flags & PTCP_URG)) {
+                /* inline urgent data is pending until the user reads past it */
+                legacyfile_adjustStatus((LegacyFile*)tcp, FileState_PRIORITY, TRUE, 0);
+            }
+
             if(fitInBuffer) {
                 // fprintf(stderr, "SND/RCV Recv %s %s %d @ %f\n", tcp->super.boundString, tcp->super.peerString, header.sequence, dtime);
                 tcp->receive.lastSequence = header->sequence;
@@ -1436,6 +1743,9 @@ static void _tcp_flush(TCP* tcp, const Host* host) {
     /* we said no more reads, or they said no more writes, or reset */
     if((tcp->flags & TCPF_LOCAL_CLOSED_RD) || (tcp->flags & TCPF_REMOTE_CLOSED) ||
             (tcp->error & TCPE_CONNECTION_RESET)) {
+        /* like linux, report this even if there is still buffered data to read */
+        legacyfile_adjustStatus((LegacyFile*)tcp, FileState_RDHUP, TRUE, 0);
+
         if((tcp->receive.next >= tcp->receive.end) && !(tcp->flags & TCPF_EOF_RD_SIGNALED)) {
             /* user needs to read a 0 so it knows we closed */
             tcp->error |= TCPE_RECEIVE_EOF;
@@ -1452,6 +1762,8 @@ static void _tcp_flush(TCP* tcp, const Host* host) {
     } else if (legacyfile_getStatus((LegacyFile*)tcp) & FileState_ACTIVE) {
         legacyfile_adjustStatus((LegacyFile*)tcp, FileState_WRITABLE, TRUE, 0);
     }
+
+    _tcp_updateOutputAckedStatus(tcp);
 }
 
 static void _tcp_runRetransmitTimerExpiredTask(const Host* host, gpointer voidInetSocket,
@@ -1551,8 +1863,9 @@ gint tcp_getConnectionError(TCP* tcp) {
     MAGIC_ASSERT(tcp);
 
     if (tcp->flags & TCPF_WAS_ESTABLISHED) {
-        /* The 3-way handshake completed at some point. */
-        if (tcp->error & TCPE_CONNECTION_RESET) {
+        /* The 3-way handshake completed at some point. Like Linux, we only report a reset
+         * once, and afterwards the socket behaves like one that the peer closed. */
+        if ((tcp->error & TCPE_CONNECTION_RESET) && !(tcp->flags & TCPF_RESET_SIGNALED)) {
             tcp->flags |= TCPF_RESET_SIGNALED;
             return -ECONNRESET;
         }
@@ -1591,6 +1904,21 @@ gint tcp_getConnectionError(TCP* tcp) {
     }
 }
 
+gint tcp_getSocketError(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+
+    /* like linux, the pending error is cleared once it's been reported */
+    if ((tcp->error & TCPE_CONNECTION_RESET) && !(tcp->flags & TCPF_RESET_SIGNALED)) {
+        tcp->flags |= TCPF_RESET_SIGNALED;
+        return (tcp->flags & TCPF_WAS_ESTABLISHED) ? ECONNRESET : ECONNREFUSED;
+    }
+
+    /* Unlike tcp_getConnectionError(), we don't consume the connect() success signal here. A
+     * non-blocking connect() that completed should still return 0 the next time connect() is
+     * called, even if the user checked SO_ERROR first. */
+    return 0;
+}
+
 static guint8 _tcp_getTCPInfoState(TCP* tcp) {
     switch(tcp->state) {
         case TCPS_ESTABLISHED: return (guint8) TCP_ESTABLISHED;
@@ -1618,14 +1946,20 @@ void tcp_getInfo(TCP* tcp, struct tcp_info *tcpinfo) {
 //  tcpinfo->tcpi_retransmits;
 //  tcpinfo->tcpi_probes;
 //  tcpinfo->tcpi_backoff;
-//  tcpinfo->tcpi_options;
+    if (tcp->ecn.isEnabled) {
+        tcpinfo->tcpi_options |= TCPI_OPT_ECN;
+    }
+    if (tcp->ecn.ectSeen) {
+        tcpinfo->tcpi_options |= TCPI_OPT_ECN_SEEN;
+    }
 //  tcpinfo->tcpi_snd_wscale;
 //  tcpinfo->tcpi_rcv_wscale;
 
-//  tcpinfo->tcpi_rto;
+    /* shadow tracks the rto and rtt in milliseconds, but linux reports them in microseconds */
+    tcpinfo->tcpi_rto = (u_int32_t)tcp->retransmit.timeout * 1000;
 //  tcpinfo->tcpi_ato;
-    tcpinfo->tcpi_snd_mss = (u_int32_t)CONFIG_TCP_MAX_SEGMENT_SIZE;
-    tcpinfo->tcpi_rcv_mss = (u_int32_t)CONFIG_TCP_MAX_SEGMENT_SIZE;
+    tcpinfo->tcpi_snd_mss = (u_int32_t)_tcp_getEffectiveMaxSegmentSize(tcp);
+    tcpinfo->tcpi_rcv_mss = (u_int32_t)tcp->mss.received;
 
     tcpinfo->tcpi_unacked = (u_int32_t)(tcp->send.next - tcp->send.unacked);
 //  tcpinfo->tcpi_sacked;
@@ -1647,11 +1981,11 @@ void tcp_getInfo(TCP* tcp, struct tcp_info *tcpinfo) {
     /* Metrics. */
     tcpinfo->tcpi_pmtu = (u_int32_t)(CONFIG_MTU);
 //  tcpinfo->tcpi_rcv_ssthresh;
-    tcpinfo->tcpi_rtt = (u_int32_t)tcp->timing.rttSmoothed;
-    tcpinfo->tcpi_rttvar = (u_int32_t)tcp->timing.rttVariance;
+    tcpinfo->tcpi_rtt = (u_int32_t)tcp->timing.rttSmoothed * 1000;
+    tcpinfo->tcpi_rttvar = (u_int32_t)tcp->timing.rttVariance * 1000;
     tcpinfo->tcpi_snd_ssthresh = (u_int32_t)tcp->cong.hooks->tcp_cong_ssthresh(tcp);
     tcpinfo->tcpi_snd_cwnd = (u_int32_t)tcp->cong.cwnd;
-    tcpinfo->tcpi_advmss = (u_int32_t)CONFIG_TCP_MAX_SEGMENT_SIZE;
+    tcpinfo->tcpi_advmss = (u_int32_t)_tcp_getEffectiveMaxSegmentSize(tcp);
     //  tcpinfo->tcpi_reordering;
 
     tcpinfo->tcpi_rcv_rtt = (u_int32_t)tcp->info.rtt;
@@ -1660,6 +1994,24 @@ void tcp_getInfo(TCP* tcp, struct tcp_info *tcpinfo) {
     tcpinfo->tcpi_total_retrans = (u_int32_t)tcp->info.retransmitCount;
 }
 
+gsize tcp_getCongestionInfo(TCP* tcp, union tcp_cc_info *info) {
+    MAGIC_ASSERT(tcp);
+
+    memset(info, 0, sizeof(union tcp_cc_info));
+    return tcp->cong.hooks->tcp_cong_get_info(tcp, info);
+}
+
+void tcp_getCongestionState(TCP* tcp, ShadowTcpCcState* state) {
+    MAGIC_ASSERT(tcp);
+
+    memset(state, 0, sizeof(ShadowTcpCcState));
+    state->time_ns = worker_getCurrentSimulationTime() / SIMTIME_ONE_NANOSECOND;
+    state->cwnd = tcp->cong.cwnd;
+    state->ssthresh = tcp->cong.hooks->tcp_cong_ssthresh(tcp);
+    state->bytes_in_flight = tcp->retransmit.queueLength;
+    state->phase = tcp->cong.hooks->tcp_cong_phase(tcp);
+}
+
 /* Address and port must be in network byte order. */
 static gint _tcp_connectToPeer(LegacySocket* socket, const Host* host, in_addr_t ip, in_port_t port,
                                sa_family_t family) {
@@ -1678,7 +2030,12 @@ static gint _tcp_connectToPeer(LegacySocket* socket, const Host* host, in_addr_t
     }
 
     /* send 1st part of 3-way handshake, state->syn_sent */
-    _tcp_sendControlPacket(tcp, host, PTCP_SYN);
+    enum ProtocolTCPFlags synFlags = PTCP_SYN;
+    if (host_useECN(host)) {
+        /* request ecn with an "ECN-setup SYN" (RFC 3168, section 6.1.1) */
+        synFlags |= PTCP_ECE | PTCP_CWR;
+    }
+    _tcp_sendControlPacket(tcp, host, synFlags);
 
     trace("%s <-> %s: user initiated connection", tcp->super.boundString, tcp->super.peerString);
     _tcp_setState(tcp, host, TCPS_SYNSENT);
@@ -1706,6 +2063,47 @@ void tcp_updateServerBacklog(TCP* tcp, gint backlog) {
     _tcpserver_updateBacklog(tcp->server, backlog);
 }
 
+/* Add an established child to its parent's accept queue. */
+static void _tcp_queueChildForAccept(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    MAGIC_ASSERT(tcp->child);
+
+    tcp->child->state = TCPCS_PENDING;
+    g_queue_push_tail(tcp->child->parent->server->pending, tcp);
+    /* user should accept new child from parent */
+    legacyfile_adjustStatus(&(tcp->child->parent->super.super), FileState_READABLE, TRUE, 0);
+}
+
+static void _tcp_runDeferAcceptTimerExpiredTask(const Host* host, gpointer voidInetSocket,
+                                                gpointer userData) {
+    const InetSocket* inetSocket = voidInetSocket;
+    utility_alwaysAssert(inetSocket != NULL);
+    TCP* tcp = inetsocket_asLegacyTcp(inetSocket);
+    MAGIC_ASSERT(tcp);
+
+    /* the child may have received data or been closed since the timer was scheduled */
+    if (tcp->child == NULL || tcp->child->state != TCPCS_DEFERRED || tcp->state == TCPS_CLOSED) {
+        return;
+    }
+
+    /* linux accepts the connection without data once the defer period is over */
+    trace("%s <-> %s: no data received before the TCP_DEFER_ACCEPT timeout",
+          tcp->super.boundString, tcp->super.peerString);
+    _tcp_queueChildForAccept(tcp);
+}
+
+static void _tcp_scheduleDeferAcceptTimer(TCP* tcp, const Host* host, CSimulationTime delay) {
+    MAGIC_ASSERT(tcp);
+
+    utility_alwaysAssert(tcp->rustSocket != NULL);
+    const InetSocket* inetSocket = inetsocketweak_upgrade(tcp->rustSocket);
+    utility_alwaysAssert(inetSocket != NULL);
+    TaskRef* deferTask = taskref_new_bound(host_getID(host), _tcp_runDeferAcceptTimerExpiredTask,
+                                           (void*)inetSocket, NULL, inetsocket_dropVoid, NULL);
+    host_scheduleTaskWithDelay(host, deferTask, delay);
+    taskref_drop(deferTask);
+}
+
 /* Address and port must be in network byte order. */
 gint tcp_acceptServerPeer(TCP* tcp, const Host* host, in_addr_t* ip, in_port_t* port,
                           gint* acceptedHandle) {
@@ -1881,6 +2279,19 @@ TCPProcessFlags _tcp_dataProcessing(TCP* tcp, Packet* packet, PacketTCPHeader *h
             _tcp_bufferPacketIn(tcp, packet);
             tcp->info.lastDataReceived = now;
             flags |= TCP_PF_DATA_RECEIVED;
+
+            if (packetLength > tcp->mss.received) {
+                tcp->mss.received = (guint32)packetLength;
+            }
+
+            if ((header->flags & PTCP_URG) &&
+                (!tcp->urgent.sequenceIsSet || header->sequence > tcp->urgent.sequence)) {
+                /* like linux, the owner is signalled when the urgent data arrives rather than
+                 * when it becomes readable */
+                tcp->urgent.sequence = header->sequence;
+                tcp->urgent.sequenceIsSet = TRUE;
+                tcp->urgent.signalPending = TRUE;
+            }
         } else {
             trace("no space for packet even though its in our window");
             packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DROPPED);
@@ -1942,13 +2353,23 @@ TCPProcessFlags _tcp_ackProcessing(TCP* tcp, const Host* host, Packet* packet,
 
         /* some data we sent got acknowledged */
         nPacketsAcked = header->acknowledgment - (guint)tcp->send.unacked;
+
+        /* like linux, the ack of our SYN doesn't grow the congestion window, so that the first
+         * flight of data is the initial window */
+        gint nDataPacketsAcked = nPacketsAcked;
+        if (tcp->send.unacked == TCP_INITIAL_SEQUENCE_NUMBER) {
+            nDataPacketsAcked--;
+        }
+
         tcp->send.unacked = (guint32)header->acknowledgment;
 
         if(nPacketsAcked > 0) {
             flags |= TCP_PF_DATA_ACKED;
 
             debug("[CONG] %i packets were acked", nPacketsAcked);
-            tcp->cong.hooks->tcp_cong_new_ack_ev(tcp, nPacketsAcked);
+            if (nDataPacketsAcked > 0) {
+                tcp->cong.hooks->tcp_cong_new_ack_ev(tcp, nDataPacketsAcked);
+            }
 
             /* increase send buffer size with autotuning */
             if (tcp->autotune.isEnabled && !tcp->autotune.userDisabledSend &&
@@ -1971,6 +2392,22 @@ TCPProcessFlags _tcp_ackProcessing(TCP* tcp, const Host* host, Packet* packet,
         tcp->receive.lastWindow = (guint32) header->window;
     }
 
+    /* the peer received data that experienced congestion, so reduce our congestion window at
+     * most once per window of data (RFC 3168, section 6.1.2) */
+    if (tcp->ecn.isEnabled && (header->flags & PTCP_ECE) && !(header->flags & PTCP_SYN) &&
+        tcp->send.unacked >= tcp->ecn.recoveryPoint) {
+        debug("[CONG] ECN-Echo received");
+        _tcp_logCongestionInfo(tcp);
+
+        /* fast recovery has already reduced the congestion window */
+        if (!tcp->cong.hooks->tcp_cong_fast_recovery(tcp)) {
+            tcp->cong.hooks->tcp_cong_ecn_ev(tcp);
+        }
+
+        tcp->ecn.recoveryPoint = tcp->send.next;
+        tcp->ecn.cwrPending = TRUE;
+    }
+
     /* update retransmit state (rfc 6298, section 5.2-5.3) */
     if(tcp->retransmit.queueLength == 0) {
         /* all outstanding data has been acked */
@@ -2019,6 +2456,26 @@ static void _tcp_sendACKTaskCallback(const Host* host, gpointer voidInetSocket,
     }
 }
 
+gboolean tcp_getQuickAck(TCP* tcp) {
+    MAGIC_ASSERT(tcp);
+    return !tcp->send.quickACKDisabled;
+}
+
+void tcp_setQuickAck(TCP* tcp, const Host* host, gboolean enabled) {
+    MAGIC_ASSERT(tcp);
+
+    tcp->send.quickACKDisabled = !enabled;
+    tcp->send.quickACKPending = enabled;
+
+    /* like linux, enabling quick acks also sends any ACK that we've delayed. the scheduled task
+     * will see that there's nothing left to send. */
+    if (enabled && tcp->send.delayedACKCounter > 0) {
+        trace("sending a delayed ACK now for TCP_QUICKACK");
+        _tcp_sendControlPacket(tcp, host, PTCP_ACK);
+        tcp->send.delayedACKCounter = 0;
+    }
+}
+
 /* return TRUE if the packet should be retransmitted */
 static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* packet) {
     TCP* tcp = _tcp_fromLegacyFile((LegacyFile*)socket);
@@ -2039,14 +2496,41 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
         /* @todo: not sure if this is handled correctly */
         trace("received RESET packet");
 
-        if(!(tcp->state & TCPS_LISTEN) && !(tcp->error & TCPE_CONNECTION_RESET)) {
+        /* a deferred child was never queued for accept(), so drop it rather than letting the
+         * defer timer queue it */
+        if (tcp->child && tcp->child->state == TCPCS_DEFERRED && tcp->state != TCPS_CLOSED) {
+            trace("%s <-> %s: dropping deferred child that was reset before it was accepted",
+                  tcp->super.boundString, tcp->super.peerString);
+            tcp->child->parent->server->pendingCount -= 1;
+            _tcp_setState(tcp, host, TCPS_CLOSED);
+            return;
+        }
+
+        /* listening and closed sockets have no connection to reset, sockets in TIMEWAIT ignore
+         * resets (RFC 1337), and we don't yet clean up the server's accept queue accounting
+         * for children that were reset before completing the handshake */
+        gboolean canReset = tcp->state != TCPS_LISTEN && tcp->state != TCPS_CLOSED &&
+                            tcp->state != TCPS_TIMEWAIT &&
+                            !(tcp->child && tcp->child->state == TCPCS_INCOMPLETE);
+
+        if(canReset && !(tcp->error & TCPE_CONNECTION_RESET)) {
             tcp->error |= TCPE_CONNECTION_RESET;
             tcp->flags |= TCPF_REMOTE_CLOSED;
 
+            /* linux reports EPIPE rather than ECONNRESET if the peer had already closed */
+            if (tcp->state == TCPS_CLOSEWAIT) {
+                tcp->flags |= TCPF_RESET_SIGNALED;
+            }
+
             _tcp_setState(tcp, host, TCPS_TIMEWAIT);
 
             /* it will send no more user data after what we have now */
             tcp->receive.end = tcp->receive.next;
+
+            /* wake up anyone blocked on the socket so that they see the reset */
+            tcp->error |= TCPE_SEND_EOF;
+            legacyfile_adjustStatus(
+                (LegacyFile*)tcp, FileState_READABLE | FileState_WRITABLE, TRUE, 0);
         }
         return;
     }
@@ -2109,6 +2593,10 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
 
                 /* we need to multiplex a new child */
                 TCP* multiplexed = tcp_new(host, recvBufSize, sendBufSize);
+                /* like linux, the child inherits the listening socket's TCP_MAXSEG and
+                 * congestion control algorithm */
+                multiplexed->mss.user = tcp->mss.user;
+                tcp_setCongestionControl(multiplexed, tcpcong_nameStr(&tcp->cong));
                 Descriptor* desc = descriptor_fromLegacyTcp(multiplexed, /* flags= */ 0);
                 int handle = thread_registerDescriptor(registerInThread, desc);
 
@@ -2137,6 +2625,13 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
                 tcp = multiplexed;
                 responseFlags = PTCP_SYN|PTCP_ACK;
 
+                if (host_useECN(host) && (header->flags & PTCP_ECE) &&
+                    (header->flags & PTCP_CWR)) {
+                    /* accept the peer's ecn request with an "ECN-setup SYN-ACK" */
+                    tcp->ecn.isEnabled = TRUE;
+                    responseFlags |= PTCP_ECE;
+                }
+
                 trace("new child state %s", _tcp_stateToAscii(tcp->state));
             }
             break;
@@ -2149,6 +2644,12 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
                 tcp->receive.start = header->sequence;
                 tcp->receive.next = tcp->receive.start + 1;
 
+                /* the peer accepted our ecn request with an "ECN-setup SYN-ACK" */
+                if (host_useECN(host) && (header->flags & PTCP_ECE) &&
+                    !(header->flags & PTCP_CWR)) {
+                    tcp->ecn.isEnabled = TRUE;
+                }
+
                 responseFlags |= PTCP_ACK;
                 _tcp_setState(tcp, host, TCPS_ESTABLISHED);
             }
@@ -2172,14 +2673,20 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
                 _tcp_setState(tcp, host, TCPS_ESTABLISHED);
 
                 /* if this is a child, mark it accordingly */
-                if(tcp->child) {
-                    tcp->child->state = TCPCS_PENDING;
-                    g_queue_push_tail(tcp->child->parent->server->pending, tcp);
-                    /* user should accept new child from parent */
-                    legacyfile_adjustStatus(
-                        &(tcp->child->parent->super.super), FileState_READABLE, TRUE, 0);
+                if (tcp->child && tcp->child->parent->deferAcceptSecs > 0) {
+                    /* the child can't be accepted until data arrives or the timer expires */
+                    tcp->child->state = TCPCS_DEFERRED;
+                    _tcp_scheduleDeferAcceptTimer(
+                        tcp, host, tcp->child->parent->deferAcceptSecs * SIMTIME_ONE_SECOND);
+                } else if (tcp->child) {
+                    _tcp_queueChildForAccept(tcp);
                 }
             }
+            /* receive retransmitted SYN during a simultaneous open, send ACK again */
+            else if((header->flags & PTCP_SYN) && !tcp->child) {
+                flags |= TCP_PF_PROCESSED;
+                responseFlags |= PTCP_ACK;
+            }
             break;
         }
 
@@ -2196,6 +2703,12 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
                 /* remote will send us no more user data after this sequence */
                 tcp->receive.end = header->sequence;
             }
+            /* receive retransmitted SYN, send ACK (the peer never got our ACK of its SYN, which
+             * can happen after a simultaneous open) */
+            else if((header->flags & PTCP_SYN) && !(header->flags & PTCP_ACK)) {
+                flags |= TCP_PF_PROCESSED;
+                responseFlags |= PTCP_ACK;
+            }
             break;
         }
 
@@ -2282,11 +2795,43 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
 
     trace("state after switch is %s", _tcp_stateToAscii(tcp->state));
 
+    /* the user closed the socket, so new data can never be read; tell the peer instead of
+     * silently dropping it so that the peer doesn't keep a half-open connection */
+    if (packetLength > 0 && header->sequence >= tcp->receive.next &&
+        (tcp->flags & TCPF_LOCAL_CLOSED_RD) &&
+        (tcp->state == TCPS_FINWAIT1 || tcp->state == TCPS_FINWAIT2)) {
+        packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DROPPED);
+        _tcp_sendResetAndClose(tcp, host);
+        return;
+    }
+
     /* if TCPE_RECEIVE_EOF, we are not supposed to receive any more */
     if(packetLength > 0 && !(tcp->error & TCPE_RECEIVE_EOF)) {
         flags |= _tcp_dataProcessing(tcp, packet, header);
     }
 
+    /* track congestion experienced by the peer's data (RFC 3168, section 6.1.3) */
+    if (tcp->ecn.isEnabled && packetLength > 0) {
+        /* the peer reduced its congestion window, so we can stop echoing the congestion */
+        if (header->flags & PTCP_CWR) {
+            tcp->ecn.echoPending = FALSE;
+        }
+
+        enum ProtocolECN ecn = packet_getECN(packet);
+        if (ecn != PECN_NOT_ECT) {
+            tcp->ecn.ectSeen = TRUE;
+        }
+        if (ecn == PECN_CE) {
+            tcp->ecn.echoPending = TRUE;
+        }
+    }
+
+    /* a deferred child can be accepted once the peer has sent something */
+    if (tcp->child && tcp->child->state == TCPCS_DEFERRED &&
+        ((flags & TCP_PF_DATA_RECEIVED) || (header->flags & PTCP_FIN))) {
+        _tcp_queueChildForAccept(tcp);
+    }
+
     if(header->flags & PTCP_ACK) {
         flags |= _tcp_ackProcessing(tcp, host, packet, header);
     }
@@ -2353,6 +2898,11 @@ static void _tcp_processPacket(LegacySocket* socket, const Host* host, Packet* p
             /* just send the response now */
             trace("sending ACK control packet now");
             _tcp_sendControlPacket(tcp, host, responseFlags);
+        } else if(tcp->send.quickACKPending) {
+            /* the user asked for the next ACK to be sent without a delay */
+            trace("sending quick ACK control packet now");
+            tcp->send.quickACKPending = FALSE;
+            _tcp_sendControlPacket(tcp, host, responseFlags);
         } else {
             trace("waiting for delayed ACK control packet");
             if(tcp->send.delayedACKIsScheduled == FALSE) {
@@ -2423,7 +2973,7 @@ static void _tcp_endOfFileSignalled(TCP* tcp, enum TCPFlags flags) {
 
 /* Address and port must be in network byte order. */
 gssize tcp_sendUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer, gsize nBytes,
-                        in_addr_t ip, in_port_t port, const MemoryManager* mem) {
+                        in_addr_t ip, in_port_t port, gboolean isUrgent, const MemoryManager* mem) {
     MAGIC_ASSERT(tcp);
 
     /* return 0 to signal close, if necessary */
@@ -2443,8 +2993,12 @@ gssize tcp_sendUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer, gs
     gsize space = _tcp_getBufferSpaceOut(tcp);
     gsize remaining = MIN(acceptable, space);
 
+    /* only the last byte of the user's message is urgent, so if only part of the message fits,
+     * none of it is urgent */
+    isUrgent = isUrgent && remaining == nBytes;
+
     /* break data into segments and send each in a packet */
-    gsize maxPacketLength = CONFIG_TCP_MAX_SEGMENT_SIZE;
+    gsize maxPacketLength = _tcp_getEffectiveMaxSegmentSize(tcp);
     gsize bytesCopied = 0;
 
     /* Need non-NULL buffer. */
@@ -2461,9 +3015,20 @@ gssize tcp_sendUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer, gs
     /* create as many packets as needed */
     while(remaining > 0) {
         gsize copyLength = MIN(maxPacketLength, remaining);
+        enum ProtocolTCPFlags flags = PTCP_ACK;
+
+        if (isUrgent && copyLength == remaining) {
+            /* the last byte is the urgent byte, and it's always sent in its own packet so that the
+             * receiver can easily remove it from the data stream */
+            if (copyLength > 1) {
+                copyLength -= 1;
+            } else {
+                flags |= PTCP_URG;
+            }
+        }
 
         /* use helper to create the packet */
-        Packet* packet = _tcp_createDataPacket(tcp, host, PTCP_ACK,
+        Packet* packet = _tcp_createDataPacket(tcp, host, flags,
                                                (UntypedForeignPtr){.val = buffer.val + bytesCopied},
                                                copyLength, mem);
 
@@ -2608,6 +3173,11 @@ gssize tcp_receiveUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer,
             break;
         }
 
+        /* the user has read past the inline urgent byte */
+        if (packet_getTCPHeader(packet)->flags & PTCP_URG) {
+            legacyfile_adjustStatus(&(tcp->super.super), FileState_PRIORITY, FALSE, 0);
+        }
+
         /* we read the entire packet, and are now finished with it */
         packet_addDeliveryStatus(packet, PDS_RCV_SOCKET_DELIVERED);
         packet_unref(packet);
@@ -2682,6 +3252,96 @@ gssize tcp_receiveUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer,
     return totalCopied;
 }
 
+gssize tcp_peekUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer, gsize nBytes,
+                        gsize skip, MemoryManager* mem) {
+    MAGIC_ASSERT(tcp);
+
+    /* make sure we pull in all readable user data */
+    _tcp_flush(tcp, host);
+
+    if ((legacysocket_getInputBufferLength(&tcp->super) == 0) &&
+        (tcp->partialUserDataPacket == NULL)) {
+        /* unlike a normal read, peeking at the EOF doesn't signal it */
+        return (tcp->error & TCPE_RECEIVE_EOF) ? 0 : -EWOULDBLOCK;
+    }
+
+    if (buffer.val == 0 && nBytes > 0) {
+        debug("Can't recv >0 bytes into NULL buffer on socket");
+        return -EFAULT;
+    }
+
+    gsize totalCopied = 0;
+
+    /* the readable data starts with the partial packet (if any), followed by the input buffer */
+    const Packet* packet = tcp->partialUserDataPacket;
+    gsize packetOffset = tcp->partialOffset;
+    GList* next = tcp->super.inputBuffer->head;
+
+    if (packet == NULL && next != NULL) {
+        packet = next->data;
+        next = next->next;
+    }
+
+    while (packet != NULL && totalCopied < nBytes) {
+        gsize packetBytes = packet_getPayloadSize(packet) - packetOffset;
+
+        if (skip >= packetBytes) {
+            /* this data was already peeked by the caller */
+            skip -= packetBytes;
+        } else {
+            gsize copyLength = MIN(packetBytes - skip, nBytes - totalCopied);
+            gssize bytesCopied = packet_copyPayloadWithMemoryManager(
+                packet, packetOffset + skip,
+                (UntypedForeignPtr){.val = buffer.val + totalCopied}, copyLength, mem);
+            if (bytesCopied < 0) {
+                return bytesCopied;
+            }
+            totalCopied += bytesCopied;
+            skip = 0;
+        }
+
+        packet = (next != NULL) ? next->data : NULL;
+        packetOffset = 0;
+        next = (next != NULL) ? next->next : NULL;
+    }
+
+    trace("%s <-> %s: peeking %" G_GSIZE_FORMAT " user bytes", tcp->super.boundString,
+          tcp->super.peerString, totalCopied);
+
+    return (gssize)totalCopied;
+}
+
+gssize tcp_receiveUrgentData(TCP* tcp, guint8* byte, gboolean peek) {
+    MAGIC_ASSERT(tcp);
+    utility_debugAssert(byte != NULL);
+
+    if (tcp->urgent.isInline || !tcp->urgent.sequenceIsSet) {
+        /* there is no out-of-band data */
+        return -EINVAL;
+    }
+
+    if (tcp->urgent.byteIsValid) {
+        *byte = tcp->urgent.byte;
+        if (!peek) {
+            tcp->urgent.byteIsValid = FALSE;
+            legacyfile_adjustStatus(&(tcp->super.super), FileState_PRIORITY, FALSE, 0);
+        }
+        return 1;
+    }
+
+    if (tcp->urgent.sequence < tcp->receive.next) {
+        /* the urgent byte was already read */
+        return -EINVAL;
+    }
+
+    /* the urgent packet has arrived, but there is missing data before it */
+    if (tcp->state == TCPS_CLOSED || (tcp->flags & TCPF_LOCAL_CLOSED_RD)) {
+        return 0;
+    }
+
+    return -EWOULDBLOCK;
+}
+
 static void _tcp_cleanup(LegacyFile* descriptor) {
     TCP* tcp = _tcp_fromLegacyFile(descriptor);
     MAGIC_ASSERT(tcp);
@@ -2756,7 +3416,12 @@ static void _tcp_close(LegacyFile* descriptor, const Host* host) {
         case TCPS_SYNRECEIVED:
         case TCPS_ESTABLISHED:
         case TCPS_CLOSEWAIT: {
-            if(tcp_getOutputBufferLength(tcp) == 0) {
+            if (tcp->abortOnClose || legacysocket_getInputBufferLength(&(tcp->super)) > 0 ||
+                tcp->partialUserDataPacket != NULL) {
+                /* unread data would be lost (or the user asked for an abortive close), so the
+                 * peer must not think that we closed cleanly */
+                _tcp_sendResetAndClose(tcp, host);
+            } else if(tcp_getOutputBufferLength(tcp) == 0) {
                 _tcp_sendShutdownFin(tcp, host);
             } else {
                 /* we still have data. send that first, and then finish with fin */
@@ -2766,7 +3431,14 @@ static void _tcp_close(LegacyFile* descriptor, const Host* host) {
         }
 
         case TCPS_FINWAIT1:
-        case TCPS_FINWAIT2:
+        case TCPS_FINWAIT2: {
+            /* we shut down writing, but the peer may still be sending data */
+            if (tcp->abortOnClose) {
+                _tcp_sendResetAndClose(tcp, host);
+            }
+            return;
+        }
+
         case TCPS_CLOSING:
         case TCPS_TIMEWAIT:
         case TCPS_LASTACK: {
@@ -2783,11 +3455,57 @@ static void _tcp_close(LegacyFile* descriptor, const Host* host) {
     }
 }
 
+/* Reset the server's connections that haven't been accepted yet. */
+static void _tcp_resetUnacceptedChildren(TCP* tcp, const Host* host) {
+    MAGIC_ASSERT(tcp);
+    MAGIC_ASSERT(tcp->server);
+
+    /* closing a child removes it from the server's children, so collect them first */
+    GList* unaccepted = NULL;
+    GHashTableIter iter;
+    gpointer value;
+    g_hash_table_iter_init(&iter, tcp->server->children);
+    while (g_hash_table_iter_next(&iter, NULL, &value)) {
+        TCP* child = value;
+        MAGIC_ASSERT(child);
+        MAGIC_ASSERT(child->child);
+        if (child->child->state != TCPCS_ACCEPTED) {
+            legacyfile_ref(child);
+            unaccepted = g_list_prepend(unaccepted, child);
+        }
+    }
+
+    g_queue_clear(tcp->server->pending);
+    tcp->server->pendingCount = 0;
+
+    for (GList* item = unaccepted; item != NULL; item = item->next) {
+        TCP* child = item->data;
+        _tcp_sendResetAndClose(child, host);
+        legacyfile_unref(child);
+    }
+    g_list_free(unaccepted);
+}
+
 gint tcp_shutdown(TCP* tcp, const Host* host, gint how) {
     MAGIC_ASSERT(tcp);
 
+    if (tcp->state == TCPS_LISTEN) {
+        /* Like linux, shutting down reading stops the socket from listening and resets the
+         * connections that haven't been accepted yet. Shutting down writing has no effect. */
+        if (how == SHUT_RD || how == SHUT_RDWR) {
+            tcp->flags |= TCPF_LOCAL_CLOSED_RD;
+            _tcp_resetUnacceptedChildren(tcp, host);
+            _tcp_setState(tcp, host, TCPS_CLOSED);
+            /* reading was shut down, so like linux the socket is readable, which wakes any blocked
+             * accept() calls so that they fail with EINVAL */
+            legacyfile_adjustStatus(
+                (LegacyFile*)tcp, FileState_READABLE | FileState_RDHUP, TRUE, 0);
+        }
+        return 0;
+    }
+
     if(tcp->state == TCPS_SYNSENT || tcp->state == TCPS_SYNRECEIVED ||
-            tcp->state == TCPS_LISTEN || tcp->state == TCPS_CLOSED) {
+            tcp->state == TCPS_CLOSED) {
         return -ENOTCONN;
     }
 
@@ -2807,6 +3525,8 @@ gint tcp_shutdown(TCP* tcp, const Host* host, gint how) {
         } else {
             tcp->flags |= TCPF_SHOULD_SEND_WR_FIN;
         }
+
+        _tcp_updateOutputAckedStatus(tcp);
     }
 
     return 0;
@@ -2856,17 +3576,17 @@ TCP* tcp_new(const Host* host, guint receiveBufferSize, guint sendBufferSize) {
     guint32 initial_window = 10;
     gint tcpSSThresh = 0;
 
-    /* in the future we'd like to support more congestion control types
-     * and allow it to be set as a host option */
-    tcp_cong_reno_init(tcp);
+    TCPCongInit congInit = tcpcong_getInit(host_getTCPCongestionControl(host));
+    utility_alwaysAssert(congInit != NULL);
+    congInit(tcp);
+    tcp->cong.cwnd = host_getTCPInitialCwnd(host);
 
     tcp->send.window = initial_window;
     tcp->send.lastWindow = initial_window;
     tcp->receive.window = initial_window;
     tcp->receive.lastWindow = initial_window;
 
-    /* 0 is saved for representing control packets */
-    guint32 initialSequenceNumber = 1;
+    guint32 initialSequenceNumber = TCP_INITIAL_SEQUENCE_NUMBER;
 
     /* the first packet (the SYN packet) has a sequence number of 'initialSequenceNumber' */
     tcp->send.unacked = initialSequenceNumber;
@@ -2879,6 +3599,8 @@ TCP* tcp_new(const Host* host, guint receiveBufferSize, guint sendBufferSize) {
     tcp->receive.lastAcknowledgment = initialSequenceNumber;
 
     tcp->autotune.isEnabled = TRUE;
+    tcp->pacing.maxRate = G_MAXUINT64;
+    tcp->mss.received = CONFIG_TCP_DEFAULT_SEGMENT_SIZE;
 
     tcp->throttledOutput = priorityqueue_new((GCompareDataFunc)packet_compareTCPSequence, NULL,
                                              (GDestroyNotify)packet_unref, NULL, NULL);
@@ -8,12 +8,14 @@
 #define SHD_TCP_H_
 
 #include <glib.h>
+#include <linux/inet_diag.h>
 #include <netinet/in.h>
 #include <netinet/tcp.h>
 #include <sys/un.h>
 
 #include "main/bindings/c/bindings-opaque.h"
 #include "main/core/definitions.h"
+#include "main/host/syscall_numbers.h"
 #include "main/routing/packet.minimal.h"
 
 #define TCP_MIN_CWND 10
@@ -35,11 +37,6 @@ enum TCPProcessFlags {
     TCP_PF_RWND_UPDATED = 1 << 5,
 };
 
-typedef enum _TCPCongestionType TCPCongestionType;
-enum _TCPCongestionType {
-    TCP_CC_UNKNOWN, TCP_CC_AIMD, TCP_CC_RENO, TCP_CC_CUBIC,
-};
-
 TCP* tcp_new(const Host* host, guint receiveBufferSize, guint sendBufferSize);
 
 void tcp_setRustSocket(TCP* tcp, InetSocketWeak* rustSocket);
@@ -64,7 +61,17 @@ void tcp_setRustSocket(TCP* tcp, InetSocketWeak* rustSocket);
 gint tcp_getConnectionError(TCP* tcp);
 // clang-format on
 
+/* Returns the pending socket error as a positive errno value (ECONNRESET or ECONNREFUSED), or 0 if
+ * there is no error. Intended for SO_ERROR, and doesn't consume the one-time 0 return of
+ * tcp_getConnectionError(). */
+gint tcp_getSocketError(TCP* tcp);
+
 void tcp_getInfo(TCP* tcp, struct tcp_info *tcpinfo);
+/* Fills in the congestion control stats for TCP_CC_INFO, and returns the number of bytes used,
+ * or 0 if the congestion control algorithm doesn't have any stats. */
+gsize tcp_getCongestionInfo(TCP* tcp, union tcp_cc_info *info);
+/* Fills in the congestion control state for SYS_shadow_tcp_cc_state. */
+void tcp_getCongestionState(TCP* tcp, ShadowTcpCcState* state);
 void tcp_enterServerMode(TCP* tcp, const Host* host, pid_t process, gint backlog);
 void tcp_updateServerBacklog(TCP* tcp, gint backlog);
 /* Address and port must be in network byte order. */
@@ -72,6 +79,16 @@ gint tcp_acceptServerPeer(TCP* tcp, const Host* host, in_addr_t* ip, in_port_t*
                           gint* acceptedHandle);
 
 struct TCPCong_ *tcp_cong(TCP *tcp);
+/* Switch to the congestion control algorithm with the given linux name. The congestion window
+ * is kept, but any other state of the previous algorithm is lost. Returns FALSE if shadow
+ * doesn't support the algorithm. */
+gboolean tcp_setCongestionControl(TCP* tcp, const char* name);
+/* The smoothed round-trip time in milliseconds, or 0 if not yet measured. */
+gint tcp_getSmoothedRTT(TCP* tcp);
+/* The most recent round-trip time measurement, or 0 if not yet measured. */
+CSimulationTime tcp_getLastRTTSample(TCP* tcp);
+/* The number of data packets that were sent but not yet acknowledged. */
+guint32 tcp_getPacketsInFlight(TCP* tcp);
 
 void tcp_clearAllChildrenIfServer(TCP* tcp);
 
@@ -79,6 +96,10 @@ gsize tcp_getOutputBufferLength(TCP* tcp);
 gsize tcp_getInputBufferLength(TCP* tcp);
 gsize tcp_getNotSentBytes(TCP* tcp);
 
+/* The maximum pacing rate in bytes per second (SO_MAX_PACING_RATE), or G_MAXUINT64 if unlimited. */
+guint64 tcp_getMaxPacingRate(TCP* tcp);
+void tcp_setMaxPacingRate(TCP* tcp, guint64 rate);
+
 void tcp_disableSendBufferAutotuning(TCP* tcp);
 void tcp_disableReceiveBufferAutotuning(TCP* tcp);
 
@@ -86,14 +107,44 @@ gboolean tcp_isValidListener(TCP* tcp);
 gboolean tcp_isListeningAllowed(TCP* tcp);
 
 gssize tcp_sendUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer, gsize nBytes,
-                        in_addr_t ip, in_port_t port, const MemoryManager* mem);
+                        in_addr_t ip, in_port_t port, gboolean isUrgent, const MemoryManager* mem);
 gssize tcp_receiveUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer, gsize nBytes,
                            in_addr_t* ip, in_port_t* port, MemoryManager* mem);
 
+/* Copy up to `nBytes` of readable user data (MSG_PEEK), starting `skip` bytes into the readable
+ * data, without consuming it. Returns the number of bytes copied, or a negative errno. */
+gssize tcp_peekUserData(TCP* tcp, const Host* host, UntypedForeignPtr buffer, gsize nBytes,
+                        gsize skip, MemoryManager* mem);
+/* Receive the out-of-band urgent byte (MSG_OOB). Returns 1 and writes the byte to `byte` on
+ * success, 0 if the connection was closed, or a negative errno. */
+gssize tcp_receiveUrgentData(TCP* tcp, guint8* byte, gboolean peek);
+/* Whether urgent data is left inline in the normal data stream (SO_OOBINLINE). */
+gboolean tcp_getUrgentInline(TCP* tcp);
+void tcp_setUrgentInline(TCP* tcp, gboolean isInline);
+/* Returns TRUE if new urgent data arrived since the last call, meaning that the socket owner should
+ * be sent a SIGURG. */
+gboolean tcp_takeUrgentSignal(TCP* tcp);
+
+/* Whether closing the socket aborts the connection with a RST and discards unsent data, as with a
+ * zero SO_LINGER timeout. */
+void tcp_setAbortOnClose(TCP* tcp, gboolean abortOnClose);
+
+/* The maximum segment size (TCP_MAXSEG). The user's value is clamped by the path mtu when sending. */
+guint32 tcp_getMaxSegmentSize(TCP* tcp);
+void tcp_setMaxSegmentSize(TCP* tcp, guint32 mss);
+
+/* The number of seconds that new connections wait for data before they can be accepted
+ * (TCP_DEFER_ACCEPT), or 0 if accepting isn't deferred. */
+guint tcp_getDeferAccept(TCP* tcp);
+void tcp_setDeferAccept(TCP* tcp, guint seconds);
+
+/* Whether delayed ACKs are disabled (TCP_QUICKACK). Enabling it sends any delayed ACK now, and the
+ * ACK for the next segment that has data won't be delayed. */
+gboolean tcp_getQuickAck(TCP* tcp);
+void tcp_setQuickAck(TCP* tcp, const Host* host, gboolean enabled);
+
 gint tcp_shutdown(TCP* tcp, const Host* host, gint how);
 
 void tcp_networkInterfaceIsAboutToSendPacket(TCP* tcp, const Host* host, Packet* packet);
 
-TCPCongestionType tcpCongestion_getType(const gchar* type);
-
 #endif /* SHD_TCP_H_ */
@@ -1,5 +1,22 @@
 #include "main/host/descriptor/tcp_cong.h"
 
+#include <string.h>
+
+#include "main/host/descriptor/tcp_cong_bbr.h"
+#include "main/host/descriptor/tcp_cong_cubic.h"
+#include "main/host/descriptor/tcp_cong_reno.h"
+
 const char* tcpcong_nameStr(const TCPCong *cong) {
     return cong->hooks->tcp_cong_name_str();
 }
+
+TCPCongInit tcpcong_getInit(const char* name) {
+    if (strcmp(name, TCP_CONG_RENO_NAME) == 0) {
+        return tcp_cong_reno_init;
+    } else if (strcmp(name, TCP_CONG_CUBIC_NAME) == 0) {
+        return tcp_cong_cubic_init;
+    } else if (strcmp(name, TCP_CONG_BBR_NAME) == 0) {
+        return tcp_cong_bbr_init;
+    }
+    return NULL;
+}
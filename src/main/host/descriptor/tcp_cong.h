@@ -4,6 +4,7 @@
 #include <stdbool.h>
 
 #include "main/host/descriptor/tcp.h"
+#include "main/host/syscall_numbers.h"
 
 // congestion event hooks
 
@@ -12,8 +13,12 @@ typedef void (*TCPCongDuplicateAckEv)(TCP *tcp);
 typedef bool (*TCPCongFastRecovery)(TCP *tcp);
 typedef void (*TCPCongNewAckEv)(TCP *tcp, guint32 n);
 typedef void (*TCPCongTimeoutEv)(TCP *tcp);
+typedef void (*TCPCongEcnEv)(TCP *tcp);
 typedef guint32 (*TCPCongSSThresh)(TCP *tcp);
 typedef const char* (*TCPCongNameStr)();
+typedef guint64 (*TCPCongPacingRate)(TCP *tcp);
+typedef gsize (*TCPCongGetInfo)(TCP *tcp, union tcp_cc_info *info);
+typedef ShadowTcpCcPhase (*TCPCongPhase)(TCP *tcp);
 
 typedef struct TCPCongHooks_ {
     TCPCongDelete tcp_cong_delete;
@@ -21,8 +26,17 @@ typedef struct TCPCongHooks_ {
     TCPCongFastRecovery tcp_cong_fast_recovery;
     TCPCongNewAckEv tcp_cong_new_ack_ev;
     TCPCongTimeoutEv tcp_cong_timeout_ev;
+    TCPCongEcnEv tcp_cong_ecn_ev;
     TCPCongSSThresh tcp_cong_ssthresh;
     TCPCongNameStr tcp_cong_name_str;
+    // the rate in bytes per second at which to send data packets, or G_MAXUINT64 if the
+    // algorithm doesn't pace
+    TCPCongPacingRate tcp_cong_pacing_rate;
+    // fills in the algorithm-specific stats returned by TCP_CC_INFO, and returns the number of
+    // bytes used, or 0 if the algorithm has no stats
+    TCPCongGetInfo tcp_cong_get_info;
+    // the current phase of the algorithm, as returned by SYS_shadow_tcp_cc_state
+    TCPCongPhase tcp_cong_phase;
 } TCPCongHooks;
 
 typedef struct TCPCong_ {
@@ -31,6 +45,12 @@ typedef struct TCPCong_ {
     void *ca;
 } TCPCong;
 
+typedef void (*TCPCongInit)(TCP *tcp);
+
 const char* tcpcong_nameStr(const TCPCong *cong);
 
+/* Returns the function that initializes the congestion control algorithm with the given linux
+ * name (for example "reno"), or NULL if shadow doesn't support that algorithm. */
+TCPCongInit tcpcong_getInit(const char* name);
+
 #endif // SHD_TCP_CONG_H_
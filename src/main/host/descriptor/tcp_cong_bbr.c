@@ -0,0 +1,391 @@
+#include "main/host/descriptor/tcp_cong_bbr.h"
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "lib/logger/logger.h"
+#include "lib/shadow-shim-helper-rs/shim_helper.h"
+#include "main/core/definitions.h"
+#include "main/core/worker.h"
+#include "main/host/descriptor/descriptor.h"
+#include "main/host/descriptor/tcp.h"
+#include "main/host/descriptor/tcp_cong.h"
+#include "main/utility/utility.h"
+
+/*
+ * BBR congestion control, based on the description in the IETF draft "BBR Congestion Control"
+ * (draft-cardwell-iccrg-bbr-congestion-control-00) and linux's tcp_bbr.c. BBR models the path
+ * using the bottleneck bandwidth (the max recent delivery rate) and the round-trip propagation
+ * time (the min recent rtt), and sends at a pacing rate derived from the bandwidth estimate. The
+ * congestion window only limits the data in flight to a small multiple of the bandwidth-delay
+ * product. Unlike reno and cubic, packet loss doesn't reduce the sending rate.
+ *
+ * Some simplifications compared to linux: delivery rates are sampled once per round trip rather
+ * than for each acked packet, the connection is never considered to be application-limited, and
+ * the probe_bw gain cycle always starts at the same phase.
+ */
+
+const char* TCP_CONG_BBR_NAME = "bbr";
+
+/* the gain used to double the sending rate each round trip in startup: 2/ln(2) */
+#define BBR_HIGH_GAIN 2.885
+/* the congestion window gain in probe_bw */
+#define BBR_CWND_GAIN 2.0
+/* the number of round trips in the bottleneck bandwidth max filter */
+#define BBR_BW_FILTER_LEN 10
+/* how long a min rtt estimate is valid before we enter probe_rtt */
+#define BBR_MIN_RTT_FILTER_LEN (10 * SIMTIME_ONE_SECOND)
+/* how long we stay in probe_rtt once the data in flight is small */
+#define BBR_PROBE_RTT_DURATION (200 * SIMTIME_ONE_MILLISECOND)
+/* the smallest congestion window, which is also used in probe_rtt */
+#define BBR_MIN_CWND 4
+/* the pipe is full once the bandwidth grows by less than 25% for 3 round trips */
+#define BBR_FULL_BW_THRESH 1.25
+#define BBR_FULL_BW_COUNT 3
+
+/* the pacing gains used in probe_bw, each for one min rtt */
+static const gdouble bbr_pacing_gain_cycle_[] = {1.25, 0.75, 1, 1, 1, 1, 1, 1};
+#define BBR_GAIN_CYCLE_LEN (sizeof(bbr_pacing_gain_cycle_) / sizeof(bbr_pacing_gain_cycle_[0]))
+
+typedef enum BBRMode_ {
+    BBR_STARTUP,
+    BBR_DRAIN,
+    BBR_PROBE_BW,
+    BBR_PROBE_RTT,
+} BBRMode;
+
+typedef struct CABBR_ {
+
+    BBRMode mode;
+    gdouble pacing_gain;
+    gdouble cwnd_gain;
+
+    /* the total number of packets acked */
+    guint64 delivered;
+
+    /* a round trip ends once a min rtt has passed since it started */
+    guint64 round_count;
+    bool round_started;
+    CSimulationTime round_start;
+    guint64 round_start_delivered;
+
+    /* the delivery rate in packets per second measured in each of the last round trips */
+    gdouble bw_samples[BBR_BW_FILTER_LEN];
+
+    /* the min rtt, or 0 if not yet measured */
+    CSimulationTime min_rtt;
+    CSimulationTime min_rtt_stamp;
+
+    /* detecting when the bottleneck bandwidth stops growing in startup */
+    bool full_bw_reached;
+    gdouble full_bw;
+    guint32 full_bw_count;
+
+    /* the current phase of the probe_bw gain cycle */
+    size_t cycle_index;
+    CSimulationTime cycle_stamp;
+
+    /* probe_rtt ends at this time, or 0 if the data in flight isn't small enough yet */
+    CSimulationTime probe_rtt_done_stamp;
+    /* the congestion window to restore after probe_rtt */
+    guint32 prior_cwnd;
+
+} CABBR;
+
+/* HELPERS *******************************************************/
+
+/* The estimated bottleneck bandwidth in packets per second, or 0 if not yet measured. */
+static gdouble bbr_btl_bw_(CABBR *bbr) {
+    gdouble btl_bw = 0;
+    for (size_t i = 0; i < BBR_BW_FILTER_LEN; i++) {
+        btl_bw = MAX(btl_bw, bbr->bw_samples[i]);
+    }
+    return btl_bw;
+}
+
+/* The estimated bandwidth-delay product in packets, or 0 if not yet measured. */
+static gdouble bbr_bdp_(CABBR *bbr) {
+    return bbr_btl_bw_(bbr) * ((gdouble)bbr->min_rtt) / ((gdouble)SIMTIME_ONE_SECOND);
+}
+
+static void bbr_set_mode_(TCP *tcp, CABBR *bbr, BBRMode mode, CSimulationTime now) {
+    bbr->mode = mode;
+
+    switch (mode) {
+        case BBR_STARTUP: {
+            bbr->pacing_gain = BBR_HIGH_GAIN;
+            bbr->cwnd_gain = BBR_HIGH_GAIN;
+            break;
+        }
+        case BBR_DRAIN: {
+            bbr->pacing_gain = 1.0 / BBR_HIGH_GAIN;
+            bbr->cwnd_gain = BBR_HIGH_GAIN;
+            break;
+        }
+        case BBR_PROBE_BW: {
+            // linux starts at a random phase other than the draining phase
+            bbr->cycle_index = 2;
+            bbr->cycle_stamp = now;
+            bbr->pacing_gain = bbr_pacing_gain_cycle_[bbr->cycle_index];
+            bbr->cwnd_gain = BBR_CWND_GAIN;
+            break;
+        }
+        case BBR_PROBE_RTT: {
+            bbr->pacing_gain = 1.0;
+            bbr->cwnd_gain = 1.0;
+            bbr->probe_rtt_done_stamp = 0;
+            break;
+        }
+    }
+
+    debug("[CONG] desc %p bbr mode %d", (LegacyFile*)tcp, mode);
+}
+
+/* Returns true if a round trip ended. */
+static bool bbr_update_bw_(TCP *tcp, CABBR *bbr, CSimulationTime now) {
+    if (!bbr->round_started) {
+        bbr->round_started = true;
+        bbr->round_start = now;
+        bbr->round_start_delivered = bbr->delivered;
+        return false;
+    }
+
+    CSimulationTime round_len = bbr->min_rtt;
+    if (round_len == 0) {
+        round_len = MAX(tcp_getLastRTTSample(tcp), SIMTIME_ONE_MILLISECOND);
+    }
+
+    CSimulationTime elapsed = now - bbr->round_start;
+    if (elapsed < round_len) {
+        return false;
+    }
+
+    gdouble delivered = bbr->delivered - bbr->round_start_delivered;
+    gdouble rate = delivered * ((gdouble)SIMTIME_ONE_SECOND) / ((gdouble)elapsed);
+
+    bbr->round_count++;
+    bbr->bw_samples[bbr->round_count % BBR_BW_FILTER_LEN] = rate;
+
+    bbr->round_start = now;
+    bbr->round_start_delivered = bbr->delivered;
+
+    return true;
+}
+
+static void bbr_check_full_bw_reached_(CABBR *bbr) {
+    gdouble btl_bw = bbr_btl_bw_(bbr);
+
+    if (btl_bw >= bbr->full_bw * BBR_FULL_BW_THRESH) {
+        bbr->full_bw = btl_bw;
+        bbr->full_bw_count = 0;
+        return;
+    }
+
+    bbr->full_bw_count++;
+    if (bbr->full_bw_count >= BBR_FULL_BW_COUNT) {
+        bbr->full_bw_reached = true;
+    }
+}
+
+static void bbr_update_min_rtt_(TCP *tcp, CABBR *bbr, CSimulationTime now) {
+    CSimulationTime sample = tcp_getLastRTTSample(tcp);
+    bool expired = bbr->min_rtt != 0 && now > bbr->min_rtt_stamp + BBR_MIN_RTT_FILTER_LEN;
+
+    if (sample != 0 && (bbr->min_rtt == 0 || sample < bbr->min_rtt || expired)) {
+        bbr->min_rtt = sample;
+        bbr->min_rtt_stamp = now;
+    }
+
+    if (expired && bbr->mode != BBR_PROBE_RTT) {
+        bbr->prior_cwnd = tcp_cong(tcp)->cwnd;
+        bbr_set_mode_(tcp, bbr, BBR_PROBE_RTT, now);
+    }
+}
+
+static void bbr_update_mode_(TCP *tcp, CABBR *bbr, CSimulationTime now) {
+    guint32 inflight = tcp_getPacketsInFlight(tcp);
+
+    switch (bbr->mode) {
+        case BBR_STARTUP: {
+            if (bbr->full_bw_reached) {
+                bbr_set_mode_(tcp, bbr, BBR_DRAIN, now);
+            }
+            break;
+        }
+        case BBR_DRAIN: {
+            // the queue that we built up in startup has drained
+            if (inflight <= bbr_bdp_(bbr)) {
+                bbr_set_mode_(tcp, bbr, BBR_PROBE_BW, now);
+            }
+            break;
+        }
+        case BBR_PROBE_BW: {
+            gdouble gain = bbr_pacing_gain_cycle_[bbr->cycle_index];
+            bool phase_over = now - bbr->cycle_stamp > bbr->min_rtt;
+
+            // we can stop draining early once the queue is gone
+            if (phase_over || (gain < 1.0 && inflight <= bbr_bdp_(bbr))) {
+                bbr->cycle_index = (bbr->cycle_index + 1) % BBR_GAIN_CYCLE_LEN;
+                bbr->cycle_stamp = now;
+                bbr->pacing_gain = bbr_pacing_gain_cycle_[bbr->cycle_index];
+            }
+            break;
+        }
+        case BBR_PROBE_RTT: {
+            if (bbr->probe_rtt_done_stamp == 0 && inflight <= BBR_MIN_CWND) {
+                bbr->probe_rtt_done_stamp = now + BBR_PROBE_RTT_DURATION;
+            } else if (bbr->probe_rtt_done_stamp != 0 && now >= bbr->probe_rtt_done_stamp) {
+                bbr->min_rtt_stamp = now;
+                tcp_cong(tcp)->cwnd = MAX(tcp_cong(tcp)->cwnd, bbr->prior_cwnd);
+                bbr_set_mode_(tcp, bbr, bbr->full_bw_reached ? BBR_PROBE_BW : BBR_STARTUP, now);
+            }
+            break;
+        }
+    }
+}
+
+static void bbr_update_cwnd_(TCP *tcp, CABBR *bbr, guint32 n) {
+    guint32 cwnd = tcp_cong(tcp)->cwnd;
+    gdouble bdp = bbr_bdp_(bbr);
+    gdouble target = MAX(bbr->cwnd_gain * bdp, BBR_MIN_CWND);
+
+    if (bbr->full_bw_reached) {
+        cwnd = MIN(cwnd + n, (guint32)target);
+    } else if (cwnd < target || bdp == 0) {
+        // grow like slow start until we reach the target
+        cwnd += n;
+    }
+
+    cwnd = MAX(cwnd, BBR_MIN_CWND);
+
+    if (bbr->mode == BBR_PROBE_RTT) {
+        cwnd = MIN(cwnd, BBR_MIN_CWND);
+    }
+
+    tcp_cong(tcp)->cwnd = cwnd;
+}
+
+/*******************************************************************/
+
+static void tcp_cong_bbr_delete_(TCP *tcp) {
+    free(tcp_cong(tcp)->ca);
+}
+
+/* BBR doesn't reduce its sending rate when packets are lost. */
+static void tcp_cong_bbr_duplicate_ack_ev_(TCP *tcp) {}
+
+static bool tcp_cong_bbr_fast_recovery_(TCP *tcp) {
+    return false;
+}
+
+static void tcp_cong_bbr_new_ack_ev_(TCP *tcp, guint32 n) {
+    CABBR *bbr = tcp_cong(tcp)->ca;
+
+    if (n == 0) {
+        return;
+    }
+
+    CSimulationTime now = worker_getCurrentSimulationTime();
+    bbr->delivered += n;
+
+    bbr_update_min_rtt_(tcp, bbr, now);
+
+    if (bbr_update_bw_(tcp, bbr, now) && !bbr->full_bw_reached) {
+        bbr_check_full_bw_reached_(bbr);
+    }
+
+    bbr_update_mode_(tcp, bbr, now);
+    bbr_update_cwnd_(tcp, bbr, n);
+}
+
+/* Like linux, only send one packet after a timeout, and then grow the window quickly back to
+ * the bandwidth-delay product. */
+static void tcp_cong_bbr_timeout_ev_(TCP *tcp) {
+    tcp_cong(tcp)->cwnd = 1;
+    debug("[CONG] desc %p bbr timeout", (LegacyFile*)tcp);
+}
+
+/* BBR ignores congestion notifications. */
+static void tcp_cong_bbr_ecn_ev_(TCP *tcp) {}
+
+/* BBR doesn't use a slow start threshold. */
+static guint32 tcp_cong_bbr_ssthresh_(TCP *tcp) {
+    return INT32_MAX;
+}
+
+static const char* tcp_cong_bbr_name_str_() {
+    return TCP_CONG_BBR_NAME;
+}
+
+static guint64 tcp_cong_bbr_pacing_rate_(TCP *tcp) {
+    CABBR *bbr = tcp_cong(tcp)->ca;
+
+    gdouble mss = tcp_getMaxSegmentSize(tcp);
+    gdouble btl_bw = bbr_btl_bw_(bbr);
+
+    if (btl_bw == 0) {
+        // before the first bandwidth sample, pace based on the window and rtt like linux
+        CSimulationTime rtt = tcp_getLastRTTSample(tcp);
+        if (rtt == 0) {
+            rtt = SIMTIME_ONE_MILLISECOND;
+        }
+        btl_bw = tcp_cong(tcp)->cwnd * ((gdouble)SIMTIME_ONE_SECOND) / ((gdouble)rtt);
+    }
+
+    return MAX((guint64)(bbr->pacing_gain * btl_bw * mss), 1);
+}
+
+static gsize tcp_cong_bbr_get_info_(TCP *tcp, union tcp_cc_info *info) {
+    CABBR *bbr = tcp_cong(tcp)->ca;
+
+    guint64 bw = (guint64)(bbr_btl_bw_(bbr) * tcp_getMaxSegmentSize(tcp));
+
+    memset(&info->bbr, 0, sizeof(info->bbr));
+    info->bbr.bbr_bw_lo = (guint32)bw;
+    info->bbr.bbr_bw_hi = (guint32)(bw >> 32);
+    info->bbr.bbr_min_rtt = (guint32)(bbr->min_rtt / SIMTIME_ONE_MICROSECOND);
+    /* the gains are fixed-point numbers scaled by BBR_UNIT (256) in linux */
+    info->bbr.bbr_pacing_gain = (guint32)(bbr->pacing_gain * 256);
+    info->bbr.bbr_cwnd_gain = (guint32)(bbr->cwnd_gain * 256);
+
+    return sizeof(info->bbr);
+}
+
+static ShadowTcpCcPhase tcp_cong_bbr_phase_(TCP *tcp) {
+    CABBR *bbr = tcp_cong(tcp)->ca;
+
+    switch (bbr->mode) {
+        case BBR_STARTUP: return SHADOW_TCP_CC_PHASE_BBR_STARTUP;
+        case BBR_DRAIN: return SHADOW_TCP_CC_PHASE_BBR_DRAIN;
+        case BBR_PROBE_BW: return SHADOW_TCP_CC_PHASE_BBR_PROBE_BW;
+        case BBR_PROBE_RTT: return SHADOW_TCP_CC_PHASE_BBR_PROBE_RTT;
+    }
+
+    utility_panic("Unexpected bbr mode %d", bbr->mode);
+}
+
+static const struct TCPCongHooks_ bbr_hooks_ = {
+    .tcp_cong_delete = tcp_cong_bbr_delete_,
+    .tcp_cong_duplicate_ack_ev = tcp_cong_bbr_duplicate_ack_ev_,
+    .tcp_cong_fast_recovery = tcp_cong_bbr_fast_recovery_,
+    .tcp_cong_new_ack_ev = tcp_cong_bbr_new_ack_ev_,
+    .tcp_cong_timeout_ev = tcp_cong_bbr_timeout_ev_,
+    .tcp_cong_ecn_ev = tcp_cong_bbr_ecn_ev_,
+    .tcp_cong_ssthresh = tcp_cong_bbr_ssthresh_,
+    .tcp_cong_name_str = tcp_cong_bbr_name_str_,
+    .tcp_cong_pacing_rate = tcp_cong_bbr_pacing_rate_,
+    .tcp_cong_get_info = tcp_cong_bbr_get_info_,
+    .tcp_cong_phase = tcp_cong_bbr_phase_,
+};
+
+void tcp_cong_bbr_init(TCP *tcp) {
+    CABBR *bbr = calloc(1, sizeof(CABBR));
+    bbr_set_mode_(tcp, bbr, BBR_STARTUP, 0);
+
+    tcp_cong(tcp)->cwnd = 1;
+    tcp_cong(tcp)->hooks = &bbr_hooks_;
+    tcp_cong(tcp)->ca = bbr;
+}
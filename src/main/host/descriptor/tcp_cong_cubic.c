@@ -0,0 +1,275 @@
+#include "main/host/descriptor/tcp_cong_cubic.h"
+
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#include "lib/logger/logger.h"
+#include "lib/shadow-shim-helper-rs/shim_helper.h"
+#include "main/core/definitions.h"
+#include "main/core/worker.h"
+#include "main/host/descriptor/descriptor.h"
+#include "main/host/descriptor/tcp.h"
+#include "main/host/descriptor/tcp_cong.h"
+#include "main/utility/utility.h"
+
+/*
+ * CUBIC congestion control as described in RFC 8312. The window is measured in packets like the
+ * rest of shadow's TCP, and slow start and fast recovery behave the same as in reno. Only the
+ * window reduction on a loss and the window growth during congestion avoidance differ.
+ */
+
+const char* TCP_CONG_CUBIC_NAME = "cubic";
+
+/* the scaling constant for the cubic function (RFC 8312, section 5) */
+#define CUBIC_C 0.4
+/* the multiplicative window decrease factor (RFC 8312, section 5) */
+#define CUBIC_BETA 0.7
+
+typedef enum CubicState_ {
+    CUBIC_SLOW_START,
+    CUBIC_FAST_RECOVERY,
+    CUBIC_CONG_AVOID,
+} CubicState;
+
+typedef struct CACubic_ {
+
+    CubicState state;
+
+    size_t duplicate_ack_n;
+
+    guint32 ssthresh;
+
+    /* the window size just before the last reduction */
+    gdouble w_max;
+
+    /* whether we've started the current congestion avoidance epoch */
+    bool epoch_started;
+    /* the time at which the current congestion avoidance epoch started */
+    CSimulationTime epoch_start;
+    /* the time in seconds the cubic function takes to grow the window back to origin */
+    gdouble k;
+    /* the window size at the plateau of the cubic function */
+    gdouble origin;
+    /* the window size reno would have, used for the tcp-friendly region */
+    gdouble w_est;
+    /* fractional window increases that haven't been applied yet */
+    gdouble cwnd_frac;
+
+} CACubic;
+
+/* HELPERS *******************************************************/
+
+/* Update w_max and ssthresh after a congestion event, and end the current epoch. */
+static void cubic_reduce_(TCP *tcp, CACubic *cubic) {
+    guint32 cwnd = tcp_cong(tcp)->cwnd;
+
+    // fast convergence (RFC 8312, section 4.6): if the window didn't grow back to the previous
+    // w_max, release some bandwidth for newer flows
+    if (cwnd < cubic->w_max) {
+        cubic->w_max = cwnd * (1.0 + CUBIC_BETA) / 2.0;
+    } else {
+        cubic->w_max = cwnd;
+    }
+
+    cubic->ssthresh = MAX((guint32)(cwnd * CUBIC_BETA), 2);
+    cubic->epoch_started = false;
+}
+
+static void cubic_cong_avoid_(TCP *tcp, CACubic *cubic, guint32 n) {
+    if (n == 0) {
+        return;
+    }
+
+    guint32 cwnd = tcp_cong(tcp)->cwnd;
+    CSimulationTime now = worker_getCurrentSimulationTime();
+
+    if (!cubic->epoch_started) {
+        cubic->epoch_started = true;
+        cubic->epoch_start = now;
+        cubic->w_est = cwnd;
+        cubic->cwnd_frac = 0;
+
+        if (cwnd < cubic->w_max) {
+            cubic->k = cbrt((cubic->w_max - cwnd) / CUBIC_C);
+            cubic->origin = cubic->w_max;
+        } else {
+            cubic->k = 0;
+            cubic->origin = cwnd;
+        }
+    }
+
+    // the window we want to reach one rtt from now (RFC 8312, section 4.1)
+    gdouble rtt = ((gdouble)tcp_getSmoothedRTT(tcp)) / 1000.0;
+    gdouble t = ((gdouble)(now - cubic->epoch_start)) / ((gdouble)SIMTIME_ONE_SECOND) + rtt;
+    gdouble target = cubic->origin + CUBIC_C * pow(t - cubic->k, 3);
+
+    // tcp-friendly region (RFC 8312, section 4.2): don't grow slower than reno would
+    cubic->w_est += (3.0 * (1.0 - CUBIC_BETA) / (1.0 + CUBIC_BETA)) * n / cwnd;
+    target = MAX(target, cubic->w_est);
+
+    // like linux, never more than increase the window by half per rtt
+    target = MIN(target, 1.5 * cwnd);
+
+    if (target > cwnd) {
+        cubic->cwnd_frac += n * (target - cwnd) / cwnd;
+    }
+
+    if (cubic->cwnd_frac >= 1) {
+        guint32 increase = (guint32)cubic->cwnd_frac;
+        cubic->cwnd_frac -= increase;
+        tcp_cong(tcp)->cwnd += increase;
+    }
+}
+
+static void transition_to_cong_avoid(TCP *tcp, CACubic *cubic, guint32 n) {
+    cubic->state = CUBIC_CONG_AVOID;
+    cubic_cong_avoid_(tcp, cubic, n);
+    debug("[CONG] desc=%p transition_to_cong_avoid", (LegacyFile*)tcp);
+}
+
+/*******************************************************************/
+
+static void tcp_cong_cubic_delete_(TCP *tcp) {
+    free(tcp_cong(tcp)->ca);
+}
+
+static void tcp_cong_cubic_duplicate_ack_ev_(TCP *tcp) {
+    CACubic *cubic = tcp_cong(tcp)->ca;
+
+    if (cubic->state == CUBIC_FAST_RECOVERY) {
+        tcp_cong(tcp)->cwnd += 1;
+        return;
+    }
+
+    cubic->duplicate_ack_n++;
+
+    if (cubic->duplicate_ack_n == 3) { // transition to fast recovery
+
+        trace("[CONG-AVOID] three duplicate acks");
+        debug("[CONG] desc %p three duplicate acks transition_to_fast_recovery", (LegacyFile*)tcp);
+
+        cubic_reduce_(tcp, cubic);
+        tcp_cong(tcp)->cwnd = cubic->ssthresh + 3;
+
+        cubic->state = CUBIC_FAST_RECOVERY;
+    }
+}
+
+static bool tcp_cong_cubic_fast_recovery_(TCP *tcp) {
+    CACubic *cubic = tcp_cong(tcp)->ca;
+    return cubic->state == CUBIC_FAST_RECOVERY;
+}
+
+static void tcp_cong_cubic_new_ack_ev_(TCP *tcp, guint32 n) {
+    CACubic *cubic = tcp_cong(tcp)->ca;
+
+    cubic->duplicate_ack_n = 0;
+
+    switch (cubic->state) {
+        case CUBIC_SLOW_START: {
+            guint32 new_cwnd = tcp_cong(tcp)->cwnd + n;
+
+            if (new_cwnd >= cubic->ssthresh) {
+                // up the cwnd to ssthresh and then transition into congestion avoidance with the
+                // leftover acks
+                guint32 nleft = new_cwnd - cubic->ssthresh;
+                tcp_cong(tcp)->cwnd = cubic->ssthresh;
+                transition_to_cong_avoid(tcp, cubic, nleft);
+            } else {
+                tcp_cong(tcp)->cwnd = new_cwnd;
+            }
+            break;
+        }
+        case CUBIC_FAST_RECOVERY: {
+            tcp_cong(tcp)->cwnd = cubic->ssthresh;
+            transition_to_cong_avoid(tcp, cubic, n);
+            break;
+        }
+        case CUBIC_CONG_AVOID: {
+            cubic_cong_avoid_(tcp, cubic, n);
+            break;
+        }
+    }
+}
+
+static void tcp_cong_cubic_timeout_ev_(TCP *tcp) {
+    CACubic *cubic = tcp_cong(tcp)->ca;
+
+    cubic->duplicate_ack_n = 0;
+    cubic_reduce_(tcp, cubic);
+    // same as reno
+    tcp_cong(tcp)->cwnd = 10;
+
+    // transition to slow start
+    cubic->state = CUBIC_SLOW_START;
+    debug("[CONG] desc %p transition_to_slow_start", (LegacyFile*)tcp);
+}
+
+/* The peer echoed a congestion notification, so we reduce the window like a fast retransmit
+ * but without retransmitting anything (RFC 3168, section 6.1.2). */
+static void tcp_cong_cubic_ecn_ev_(TCP *tcp) {
+    CACubic *cubic = tcp_cong(tcp)->ca;
+
+    cubic->duplicate_ack_n = 0;
+    cubic_reduce_(tcp, cubic);
+    tcp_cong(tcp)->cwnd = cubic->ssthresh;
+
+    transition_to_cong_avoid(tcp, cubic, 0);
+}
+
+static guint32 tcp_cong_cubic_ssthresh_(TCP *tcp) {
+    CACubic *cubic = tcp_cong(tcp)->ca;
+    return cubic->ssthresh;
+}
+
+static const char* tcp_cong_cubic_name_str_() {
+    return TCP_CONG_CUBIC_NAME;
+}
+
+static guint64 tcp_cong_cubic_pacing_rate_(TCP *tcp) {
+    return G_MAXUINT64;
+}
+
+/* Like linux, cubic doesn't report any stats for TCP_CC_INFO. */
+static gsize tcp_cong_cubic_get_info_(TCP *tcp, union tcp_cc_info *info) {
+    return 0;
+}
+
+static ShadowTcpCcPhase tcp_cong_cubic_phase_(TCP *tcp) {
+    CACubic *cubic = tcp_cong(tcp)->ca;
+
+    switch (cubic->state) {
+        case CUBIC_SLOW_START: return SHADOW_TCP_CC_PHASE_SLOW_START;
+        case CUBIC_FAST_RECOVERY: return SHADOW_TCP_CC_PHASE_FAST_RECOVERY;
+        case CUBIC_CONG_AVOID: return SHADOW_TCP_CC_PHASE_CONGESTION_AVOIDANCE;
+    }
+
+    utility_panic("Unexpected cubic state %d", cubic->state);
+}
+
+static const struct TCPCongHooks_ cubic_hooks_ = {
+    .tcp_cong_delete = tcp_cong_cubic_delete_,
+    .tcp_cong_duplicate_ack_ev = tcp_cong_cubic_duplicate_ack_ev_,
+    .tcp_cong_fast_recovery = tcp_cong_cubic_fast_recovery_,
+    .tcp_cong_new_ack_ev = tcp_cong_cubic_new_ack_ev_,
+    .tcp_cong_timeout_ev = tcp_cong_cubic_timeout_ev_,
+    .tcp_cong_ecn_ev = tcp_cong_cubic_ecn_ev_,
+    .tcp_cong_ssthresh = tcp_cong_cubic_ssthresh_,
+    .tcp_cong_name_str = tcp_cong_cubic_name_str_,
+    .tcp_cong_pacing_rate = tcp_cong_cubic_pacing_rate_,
+    .tcp_cong_get_info = tcp_cong_cubic_get_info_,
+    .tcp_cong_phase = tcp_cong_cubic_phase_,
+};
+
+void tcp_cong_cubic_init(TCP *tcp) {
+    CACubic *cubic = calloc(1, sizeof(CACubic));
+    cubic->state = CUBIC_SLOW_START;
+    cubic->ssthresh = INT32_MAX;
+
+    tcp_cong(tcp)->cwnd = 1;
+    tcp_cong(tcp)->hooks = &cubic_hooks_;
+    tcp_cong(tcp)->ca = cubic;
+}
@@ -162,6 +162,19 @@ static void tcp_cong_reno_timeout_ev_(TCP *tcp) {
     debug("[CONG] desc %p transition_to_slow_start", (LegacyFile*)tcp);
 }
 
+/* The peer echoed a congestion notification, so we reduce the window like a fast retransmit
+ * but without retransmitting anything (RFC 3168, section 6.1.2). */
+static void tcp_cong_reno_ecn_ev_(TCP *tcp) {
+
+    CAReno *reno = tcp_cong(tcp)->ca;
+
+    reno->duplicate_ack_n = 0;
+    ssthresh_halve(tcp, reno);
+    tcp_cong(tcp)->cwnd = reno->ssthresh;
+
+    transition_to_cong_avoid(tcp, reno, 0);
+}
+
 static guint32 tcp_cong_reno_ssthresh_(TCP *tcp) {
     CAReno *reno = tcp_cong(tcp)->ca;
     return reno->ssthresh;
@@ -171,14 +184,38 @@ static const char* tcp_cong_reno_name_str_() {
     return TCP_CONG_RENO_NAME;
 }
 
+static guint64 tcp_cong_reno_pacing_rate_(TCP *tcp) {
+    return G_MAXUINT64;
+}
+
+static gsize tcp_cong_reno_get_info_(TCP *tcp, union tcp_cc_info *info) {
+    return 0;
+}
+
+static ShadowTcpCcPhase tcp_cong_reno_phase_(TCP *tcp) {
+    CAReno *reno = tcp_cong(tcp)->ca;
+
+    if (reno->state_hooks == slow_start_hooks_()) {
+        return SHADOW_TCP_CC_PHASE_SLOW_START;
+    } else if (reno->state_hooks == fast_recovery_hooks_()) {
+        return SHADOW_TCP_CC_PHASE_FAST_RECOVERY;
+    } else {
+        return SHADOW_TCP_CC_PHASE_CONGESTION_AVOIDANCE;
+    }
+}
+
 static const struct TCPCongHooks_ reno_hooks_ = {
     .tcp_cong_delete = tcp_cong_reno_delete_,
     .tcp_cong_duplicate_ack_ev = tcp_cong_reno_duplicate_ack_ev_,
     .tcp_cong_fast_recovery = tcp_cong_reno_fast_recovery_,
     .tcp_cong_new_ack_ev = tcp_cong_reno_new_ack_ev_,
     .tcp_cong_timeout_ev = tcp_cong_reno_timeout_ev_,
+    .tcp_cong_ecn_ev = tcp_cong_reno_ecn_ev_,
     .tcp_cong_ssthresh = tcp_cong_reno_ssthresh_,
     .tcp_cong_name_str = tcp_cong_reno_name_str_,
+    .tcp_cong_pacing_rate = tcp_cong_reno_pacing_rate_,
+    .tcp_cong_get_info = tcp_cong_reno_get_info_,
+    .tcp_cong_phase = tcp_cong_reno_phase_,
 };
 
 void tcp_cong_reno_init(TCP *tcp) {
@@ -196,8 +233,12 @@ static const struct TCPCongHooks_ slow_start_hooks__ = {
     .tcp_cong_fast_recovery = NULL,
     .tcp_cong_new_ack_ev = ca_reno_slow_start_new_ack_ev_,
     .tcp_cong_timeout_ev = NULL,
+    .tcp_cong_ecn_ev = NULL,
     .tcp_cong_ssthresh = NULL,
     .tcp_cong_name_str = NULL,
+    .tcp_cong_pacing_rate = NULL,
+    .tcp_cong_get_info = NULL,
+    .tcp_cong_phase = NULL,
 };
 
 static const struct TCPCongHooks_ fast_recovery_hooks__ = {
@@ -206,8 +247,12 @@ static const struct TCPCongHooks_ fast_recovery_hooks__ = {
     .tcp_cong_fast_recovery = NULL,
     .tcp_cong_new_ack_ev = ca_reno_fast_recovery_new_ack_ev_,
     .tcp_cong_timeout_ev = NULL,
+    .tcp_cong_ecn_ev = NULL,
     .tcp_cong_ssthresh = NULL,
     .tcp_cong_name_str = NULL,
+    .tcp_cong_pacing_rate = NULL,
+    .tcp_cong_get_info = NULL,
+    .tcp_cong_phase = NULL,
 };
 
 /* slow start and cong avoidance have the same dupl act behavior */
@@ -217,8 +262,12 @@ static const struct TCPCongHooks_ cong_avoid_hooks__ = {
     .tcp_cong_fast_recovery = NULL,
     .tcp_cong_new_ack_ev = ca_reno_cong_avoid_new_ack_ev_,
     .tcp_cong_timeout_ev = NULL,
+    .tcp_cong_ecn_ev = NULL,
     .tcp_cong_ssthresh = NULL,
     .tcp_cong_name_str = NULL,
+    .tcp_cong_pacing_rate = NULL,
+    .tcp_cong_get_info = NULL,
+    .tcp_cong_phase = NULL,
 };
 
 static inline const struct TCPCongHooks_ *slow_start_hooks_() {
@@ -13,15 +13,27 @@
 #include "main/bindings/c/bindings-opaque.h"
 #include "main/core/definitions.h"
 #include "main/core/worker.h"
+#include "main/host/syscall/syscall_condition.h"
 #include "main/utility/utility.h"
 
+typedef struct _FutexListener FutexListener;
+struct _FutexListener {
+    // Whether or not a wakeup has already been performed on the listener
+    bool didWakeup;
+    // The FUTEX_WAIT_BITSET mask, or FUTEX_BITSET_MATCH_ANY for other waits
+    uint32_t bitset;
+    // The condition that owns the listener, which must be updated if the listener is requeued
+    SysCallCondition* cond;
+};
+
 struct _Futex {
     // The unique physical address that is used to refer to this futex
     ManagedPhysicalMemoryAddr word;
     // Listeners waiting for wakups on this futex
-    // The key is a listener of type StatusListener*, the value is a boolean that indicates
-    // whether or not a wakeup has already been performed on the listener.
+    // The key is a listener of type StatusListener*, the value is a FutexListener*.
     GHashTable* listeners;
+    // Thread IDs of the threads blocked in FUTEX_LOCK_PI, in the order that they started waiting
+    GQueue* piWaiters;
     // Manage references
     int referenceCount;
     MAGIC_DECLARE;
@@ -31,7 +43,8 @@ Futex* futex_new(ManagedPhysicalMemoryAddr word) {
     Futex* futex = malloc(sizeof(*futex));
     *futex = (Futex){.word = word,
                      .listeners = g_hash_table_new_full(
-                         g_direct_hash, g_direct_equal, (GDestroyNotify)statuslistener_unref, NULL),
+                         g_direct_hash, g_direct_equal, (GDestroyNotify)statuslistener_unref, g_free),
+                     .piWaiters = g_queue_new(),
                      .referenceCount = 1,
                      MAGIC_INITIALIZER};
 
@@ -44,6 +57,7 @@ static void _futex_free(Futex* futex) {
     MAGIC_ASSERT(futex);
 
     g_hash_table_destroy(futex->listeners);
+    g_queue_free(futex->piWaiters);
 
     MAGIC_CLEAR(futex);
     free(futex);
@@ -70,7 +84,7 @@ ManagedPhysicalMemoryAddr futex_getAddress(Futex* futex) {
     return futex->word;
 }
 
-unsigned int futex_wake(Futex* futex, unsigned int numWakeups) {
+unsigned int futex_wake(Futex* futex, unsigned int numWakeups, uint32_t bitset) {
     MAGIC_ASSERT(futex);
 
     // We cannot use an iterator here, in case the hash table is modified
@@ -95,21 +109,19 @@ unsigned int futex_wake(Futex* futex, unsigned int numWakeups) {
         StatusListener* listener = item->data;
 
         // Only call if the listener is still valid
-        if (g_hash_table_contains(futex->listeners, listener)) {
-            // If this listener was already woken up, skip it this time
-            bool did_wakeup =
-                (bool)GPOINTER_TO_UINT(g_hash_table_lookup(futex->listeners, listener));
-            if (!did_wakeup) {
-                // Tell the status listener to unblock the thread waiting on the futex
-                statuslistener_onStatusChanged(listener, FileState_FUTEX_WAKEUP, FileState_FUTEX_WAKEUP);
-
-                // Track that we did a wakeup on this listener without destroying the listener
-                g_hash_table_steal(futex->listeners, listener);
-                g_hash_table_insert(futex->listeners, listener, GUINT_TO_POINTER(true));
-
-                // Count the wake-up
-                numWoken++;
-            }
+        FutexListener* entry = g_hash_table_lookup(futex->listeners, listener);
+
+        // If this listener was already woken up or is waiting for other bits, skip it this time
+        if (entry && !entry->didWakeup && (entry->bitset & bitset) != 0) {
+            // Track that we did a wakeup on this listener without destroying the listener. Do
+            // this first since the callback may remove the listener.
+            entry->didWakeup = true;
+
+            // Tell the status listener to unblock the thread waiting on the futex
+            statuslistener_onStatusChanged(listener, FileState_FUTEX_WAKEUP, FileState_FUTEX_WAKEUP);
+
+            // Count the wake-up
+            numWoken++;
         }
 
         item = g_list_next(item);
@@ -121,11 +133,49 @@ unsigned int futex_wake(Futex* futex, unsigned int numWakeups) {
     return numWoken;
 }
 
-void futex_addListener(Futex* futex, StatusListener* listener) {
+void futex_addListener(Futex* futex, StatusListener* listener, uint32_t bitset,
+                       SysCallCondition* cond) {
     MAGIC_ASSERT(futex);
     utility_debugAssert(listener);
+    utility_debugAssert(bitset != 0);
     statuslistener_ref(listener);
-    g_hash_table_insert(futex->listeners, listener, GUINT_TO_POINTER(false));
+
+    FutexListener* entry = g_new(FutexListener, 1);
+    *entry = (FutexListener){.didWakeup = false, .bitset = bitset, .cond = cond};
+    g_hash_table_insert(futex->listeners, listener, entry);
+}
+
+unsigned int futex_requeue(Futex* futex, Futex* target, unsigned int numRequeues) {
+    MAGIC_ASSERT(futex);
+    MAGIC_ASSERT(target);
+
+    // Like in futex_wake, iterate the listeners in a deterministic order.
+    GList* listenerList = g_hash_table_get_keys(futex->listeners);
+    listenerList = g_list_sort(listenerList, status_listener_compare);
+
+    unsigned int numRequeued = 0;
+
+    for (GList* item = listenerList; item && numRequeued < numRequeues; item = g_list_next(item)) {
+        StatusListener* listener = item->data;
+        FutexListener* entry = g_hash_table_lookup(futex->listeners, listener);
+
+        // Listeners that were already woken up are no longer waiting, so don't move them
+        if (!entry || entry->didWakeup) {
+            continue;
+        }
+
+        if (futex != target) {
+            // Transfer the listener reference and the entry to the target futex.
+            g_hash_table_steal(futex->listeners, listener);
+            g_hash_table_insert(target->listeners, listener, entry);
+            syscallcondition_requeueFutex(entry->cond, target);
+        }
+
+        numRequeued++;
+    }
+
+    g_list_free(listenerList);
+    return numRequeued;
 }
 
 void futex_removeListener(Futex* futex, StatusListener* listener) {
@@ -137,3 +187,25 @@ unsigned int futex_getListenerCount(Futex* futex) {
     MAGIC_ASSERT(futex);
     return g_hash_table_size(futex->listeners);
 }
+
+void futex_addPiWaiter(Futex* futex, pid_t tid) {
+    MAGIC_ASSERT(futex);
+    utility_debugAssert(tid > 0);
+    g_queue_push_tail(futex->piWaiters, GINT_TO_POINTER(tid));
+}
+
+bool futex_removePiWaiter(Futex* futex, pid_t tid) {
+    MAGIC_ASSERT(futex);
+    return g_queue_remove(futex->piWaiters, GINT_TO_POINTER(tid));
+}
+
+pid_t futex_popPiWaiter(Futex* futex) {
+    MAGIC_ASSERT(futex);
+    // returns NULL (0) if the queue is empty
+    return GPOINTER_TO_INT(g_queue_pop_head(futex->piWaiters));
+}
+
+unsigned int futex_getPiWaiterCount(Futex* futex) {
+    MAGIC_ASSERT(futex);
+    return g_queue_get_length(futex->piWaiters);
+}
@@ -9,10 +9,14 @@
 #include <glib.h>
 #include <stdbool.h>
 #include <stdint.h>
+#include <sys/types.h>
 
 // Opaque futex object.
 typedef struct _Futex Futex;
 
+// Defined in syscall_condition.h, which includes this header.
+typedef struct _SysCallCondition SysCallCondition;
+
 #include "main/bindings/c/bindings-opaque.h"
 #include "main/host/status_listener.h"
 
@@ -29,12 +33,18 @@ void futex_unref_func(void* futex);
 // Return the unique address of this futex.
 ManagedPhysicalMemoryAddr futex_getAddress(Futex* futex);
 
-// Wakeup at most the given number of listener threads waiting on this futex; return the number of
-// threads that were woken up.
-unsigned int futex_wake(Futex* futex, unsigned int numWakeups);
+// Wakeup at most the given number of listener threads waiting on this futex whose bitset
+// intersects `bitset`; return the number of threads that were woken up.
+unsigned int futex_wake(Futex* futex, unsigned int numWakeups, uint32_t bitset);
+
+// Add a listener that will be notified when a wakup occurs with a bitset that intersects `bitset`.
+// The listener belongs to `cond`, which is updated if the listener is requeued to another futex.
+void futex_addListener(Futex* futex, StatusListener* listener, uint32_t bitset,
+                       SysCallCondition* cond);
 
-// Add a listener that will be notified when a wakup occurs
-void futex_addListener(Futex* futex, StatusListener* listener);
+// Move at most the given number of listeners that haven't been woken up yet to the `target` futex,
+// as in FUTEX_REQUEUE; return the number of listeners that were requeued.
+unsigned int futex_requeue(Futex* futex, Futex* target, unsigned int numRequeues);
 
 // Remove a listener from those that are waiting for wakeups
 void futex_removeListener(Futex* futex, StatusListener* listener);
@@ -42,4 +52,17 @@ void futex_removeListener(Futex* futex, StatusListener* listener);
 // Return the number of listers currently awaiting a wakeup
 unsigned int futex_getListenerCount(Futex* futex);
 
+// Add a thread that is blocked in FUTEX_LOCK_PI to the end of the queue of PI waiters
+void futex_addPiWaiter(Futex* futex, pid_t tid);
+
+// Remove a thread from the queue of PI waiters; return true if the thread was in the queue
+bool futex_removePiWaiter(Futex* futex, pid_t tid);
+
+// Remove and return the thread that has been waiting the longest in FUTEX_LOCK_PI, or 0 if there
+// are no PI waiters
+pid_t futex_popPiWaiter(Futex* futex);
+
+// Return the number of threads currently blocked in FUTEX_LOCK_PI
+unsigned int futex_getPiWaiterCount(Futex* futex);
+
 #endif /* SRC_MAIN_HOST_FUTEX_H_ */
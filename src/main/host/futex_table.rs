@@ -70,7 +70,19 @@ impl FutexRef {
     }
 
     pub fn wake(&self, num_wakeups: libc::c_uint) -> libc::c_uint {
-        unsafe { c::futex_wake(self.ptr(), num_wakeups) }
+        unsafe {
+            c::futex_wake(
+                self.ptr(),
+                num_wakeups,
+                linux_api::futex::FUTEX_BITSET_MATCH_ANY,
+            )
+        }
+    }
+
+    /// Remove and return the longest waiting thread of a PI futex, if any.
+    pub fn pop_pi_waiter(&self) -> Option<libc::pid_t> {
+        let tid = unsafe { c::futex_popPiWaiter(self.ptr()) };
+        (tid != 0).then_some(tid)
     }
 }
 
@@ -6,8 +6,9 @@ use std::ffi::{CStr, CString, OsString};
 use std::net::{Ipv4Addr, SocketAddrV4};
 use std::num::NonZeroU8;
 use std::ops::{Deref, DerefMut};
+use std::os::unix::fs::MetadataExt;
 use std::os::unix::prelude::OsStringExt;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use atomic_refcell::AtomicRefCell;
@@ -31,22 +32,25 @@ use shadow_shmem::allocator::ShMemBlock;
 use shadow_tsc::Tsc;
 use vasi_sync::scmutex::SelfContainedMutexGuard;
 
-use crate::core::configuration::{ProcessFinalState, QDiscMode};
-use crate::core::sim_config::PcapConfig;
+use crate::core::configuration::{PortRange, ProcessFinalState, QDiscMode, TcpCongestionControl};
+use crate::core::sim_config::{BlockDeviceConfig, PcapConfig};
 use crate::core::work::event::{Event, EventData};
 use crate::core::work::event_queue::EventQueue;
 use crate::core::work::task::TaskRef;
 use crate::core::worker::Worker;
 use crate::cshadow;
+use crate::host::clock::ClockAdjustment;
+use crate::host::descriptor::epoll::ExclusiveWakeups;
 use crate::host::descriptor::socket::abstract_unix_ns::AbstractUnixNamespace;
 use crate::host::descriptor::socket::inet::InetSocket;
 use crate::host::futex_table::FutexTable;
 use crate::host::network::interface::{FifoPacketPriority, NetworkInterface, PcapOptions};
 use crate::host::network::namespace::NetworkNamespace;
 use crate::host::process::Process;
+use crate::host::sysv_shm::SysvShm;
 use crate::host::thread::{Thread, ThreadId};
 use crate::network::relay::{RateLimit, Relay};
-use crate::network::router::Router;
+use crate::network::router::{AqmConfig, Router};
 use crate::network::PacketDevice;
 use crate::utility;
 #[cfg(feature = "perf_timers")]
@@ -70,7 +74,9 @@ pub struct HostParameters {
     pub heartbeat_log_level: LogLevel,
     pub heartbeat_log_info: cshadow::LogInfoFlags,
     pub log_level: LogLevel,
+    pub log_per_host: bool,
     pub pcap_config: Option<PcapConfig>,
+    pub tcp_congestion_control: TcpCongestionControl,
     pub qdisc: QDiscMode,
     pub init_sock_recv_buf_size: u64,
     pub autotune_recv_buf: bool,
@@ -81,11 +87,25 @@ pub struct HostParameters {
     pub max_unapplied_cpu_latency: SimulationTime,
     pub unblocked_syscall_latency: SimulationTime,
     pub unblocked_vdso_latency: SimulationTime,
+    pub cpu_migration_cost: SimulationTime,
+    pub entropy_pool_init_time: SimulationTime,
     pub strace_logging_options: Option<FmtOptions>,
     pub shim_log_level: LogLevel,
     pub use_new_tcp: bool,
+    pub ignore_unsupported_sockopts: bool,
+    pub ephemeral_port_range: PortRange,
+    pub use_ecn: bool,
+    pub ecn_mark_threshold: Option<u64>,
+    pub router_aqm: AqmConfig,
+    pub router_queue_size: Option<u64>,
+    pub tcp_initial_cwnd: u32,
     pub use_mem_mapper: bool,
     pub use_syscall_counters: bool,
+    pub use_deterministic_aslr: bool,
+    pub read_only_mounts: BTreeMap<PathBuf, PathBuf>,
+    pub tmpfs_mounts: BTreeMap<PathBuf, u64>,
+    pub block_devices: BTreeMap<PathBuf, BlockDeviceConfig>,
+    pub preloaded_files: BTreeMap<PathBuf, Vec<u8>>,
 }
 
 use super::cpu::Cpu;
@@ -99,6 +119,132 @@ pub struct HostInfo {
     pub name: String,
     pub default_ip: Ipv4Addr,
     pub log_level: Option<log::LevelFilter>,
+    /// The file that the host's log messages are also written to, if any.
+    pub log_path: Option<PathBuf>,
+}
+
+/// A size-limited in-memory filesystem mounted within a host.
+struct TmpfsMount {
+    /// The absolute path that the host's processes access the mount at.
+    target: PathBuf,
+    /// The maximum number of bytes that the files in the mount can hold.
+    size: u64,
+    /// The real directory that holds the mount's files, which is removed when dropped.
+    dir: tempfile::TempDir,
+    /// The apparent size of each file in the mount, by inode number.
+    file_sizes: RefCell<BTreeMap<u64, u64>>,
+    /// The sum of `file_sizes`.
+    used: Cell<u64>,
+}
+
+impl TmpfsMount {
+    fn new(target: PathBuf, size: u64) -> std::io::Result<Self> {
+        // Shadow's regular files are backed by real files that both Shadow and the managed
+        // processes open, for example when a process maps a file into its memory, so the mount's
+        // files can't live only in Shadow's memory. Prefer a directory backed by memory if the
+        // system has one, otherwise fall back to a temporary directory on disk; the mount's size
+        // limit and usage are tracked by Shadow either way.
+        let builder = {
+            let mut x = tempfile::Builder::new();
+            x.prefix("shadow-tmpfs-");
+            x
+        };
+        let dir = builder
+            .tempdir_in("/dev/shm")
+            .or_else(|_| builder.tempdir())?;
+
+        Ok(Self {
+            target,
+            size,
+            dir,
+            file_sizes: RefCell::new(BTreeMap::new()),
+            used: Cell::new(0),
+        })
+    }
+
+    /// Records the files that are already in the mount, such as preloaded files. Later changes
+    /// are recorded as they're made, so that we don't need to walk the mount on every write.
+    fn scan(&self) {
+        fn scan_dir(mount: &TmpfsMount, path: &Path) {
+            let Ok(entries) = std::fs::read_dir(path) else {
+                return;
+            };
+            for entry in entries.filter_map(Result::ok) {
+                match entry.metadata() {
+                    Ok(x) if x.is_dir() => scan_dir(mount, &entry.path()),
+                    Ok(x) if x.is_file() => mount.set_file_size(x.ino(), x.len()),
+                    _ => {}
+                }
+            }
+        }
+        scan_dir(self, self.dir.path());
+    }
+
+    /// Records that the file with inode number `ino` now has an apparent size of `size` bytes.
+    fn set_file_size(&self, ino: u64, size: u64) {
+        let old = self.file_sizes.borrow_mut().insert(ino, size).unwrap_or(0);
+        self.used.set(self.used.get() - old + size);
+    }
+
+    /// Records that the file with inode number `ino` was removed from the mount.
+    fn remove_file(&self, ino: u64) {
+        let old = self.file_sizes.borrow_mut().remove(&ino).unwrap_or(0);
+        self.used.set(self.used.get() - old);
+    }
+
+    /// The number of bytes used by the files in the mount.
+    fn used(&self) -> u64 {
+        self.used.get()
+    }
+
+    /// Whether the real path `path` is within the mount.
+    fn contains_real_path(&self, path: &Path) -> bool {
+        path.strip_prefix(self.dir.path())
+            .is_ok_and(|rest| !rest.components().any(|c| c == Component::ParentDir))
+    }
+}
+
+/// A fixed-size block device within a host.
+struct BlockDevice {
+    /// The absolute path that the host's processes access the device at.
+    target: PathBuf,
+    config: BlockDeviceConfig,
+    /// The real file that holds the device's data, which is removed when dropped.
+    file: tempfile::NamedTempFile,
+}
+
+impl BlockDevice {
+    fn new(target: PathBuf, config: BlockDeviceConfig) -> std::io::Result<Self> {
+        let file = tempfile::Builder::new()
+            .prefix("shadow-blockdev-")
+            .tempfile()?;
+
+        // the device starts zeroed, and the sparse file doesn't use any space until written to
+        file.as_file().set_len(config.size)?;
+
+        Ok(Self {
+            target,
+            config,
+            file,
+        })
+    }
+}
+
+/// If `path` is within the mount at `target`, returns the corresponding path within `source`.
+fn mount_subpath(target: &Path, source: &Path, path: &Path) -> Option<PathBuf> {
+    let rest = path.strip_prefix(target).ok()?;
+
+    // a ".." component could leave the mount
+    if rest.components().any(|x| x == Component::ParentDir) {
+        return None;
+    }
+
+    // joining an empty path would add a trailing '/'
+    if rest.as_os_str().is_empty() {
+        Some(source.to_path_buf())
+    } else {
+        Some(source.join(rest))
+    }
 }
 
 /// A simulated Host.
@@ -139,6 +285,15 @@ pub struct Host {
     // map address to futex objects
     futex_table: RefCell<FutexTable>,
 
+    // SysV shared memory segments, shared by all processes on the host
+    sysv_shm: RefCell<SysvShm>,
+
+    // the wakeup states of files monitored by EPOLLEXCLUSIVE epoll entries
+    epoll_exclusive_wakeups: RefCell<ExclusiveWakeups>,
+
+    // the clock's frequency adjustment, as set by adjtimex
+    clock_adjustment: RefCell<ClockAdjustment>,
+
     #[cfg(feature = "perf_timers")]
     execution_timer: RefCell<PerfTimer>,
 
@@ -200,6 +355,12 @@ pub struct Host {
 
     /// Paths to be added to LD_PRELOAD of managed processes.
     preload_paths: Arc<Vec<PathBuf>>,
+
+    // In-memory filesystems, whose files are removed when the host is dropped.
+    tmpfs_mounts: Vec<TmpfsMount>,
+
+    // Block devices, whose data is removed when the host is dropped.
+    block_devices: Vec<BlockDevice>,
 }
 
 /// Host must be `Send`.
@@ -264,6 +425,54 @@ impl Host {
 
         std::fs::create_dir_all(&data_dir_path).unwrap();
 
+        let tmpfs_mounts: Vec<_> = params
+            .tmpfs_mounts
+            .iter()
+            .map(|(target, size)| {
+                TmpfsMount::new(target.clone(), *size).unwrap_or_else(|e| {
+                    panic!("Could not create tmpfs mount '{}': {e}", target.display())
+                })
+            })
+            .collect();
+
+        let block_devices = params
+            .block_devices
+            .iter()
+            .map(|(target, config)| {
+                BlockDevice::new(target.clone(), *config).unwrap_or_else(|e| {
+                    panic!("Could not create block device '{}': {e}", target.display())
+                })
+            })
+            .collect();
+
+        // create the preloaded files before any processes start; relative paths are within the
+        // processes' working directory, and absolute paths were checked to be within a tmpfs mount
+        for (target, contents) in &params.preloaded_files {
+            let path = if target.is_absolute() {
+                tmpfs_mounts
+                    .iter()
+                    .find_map(|x| mount_subpath(&x.target, x.dir.path(), target))
+                    .unwrap()
+            } else {
+                data_dir_path.join(target)
+            };
+
+            let result = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, contents));
+            if let Err(e) = result {
+                panic!(
+                    "Could not create preloaded file '{}': {e}",
+                    target.display()
+                );
+            }
+        }
+
+        for mount in &tmpfs_mounts {
+            mount.scan();
+        }
+
         // Register using the param hints.
         // We already checked that the addresses are available, so fail if they are not.
 
@@ -288,6 +497,8 @@ impl Host {
                 public_ip,
                 pcap_options,
                 params.qdisc,
+                params.ephemeral_port_range.start..=params.ephemeral_port_range.end,
+                params.node_seed,
                 dns,
             )
         };
@@ -295,7 +506,17 @@ impl Host {
         // Packets that are not for localhost or our public ip go to the router.
         // Use `Ipv4Addr::UNSPECIFIED` for the router to encode this for our
         // routing table logic inside of `Host::get_packet_device()`.
-        let router = Router::new(Ipv4Addr::UNSPECIFIED);
+        let router = Router::new(
+            Ipv4Addr::UNSPECIFIED,
+            params.router_aqm,
+            params
+                .router_queue_size
+                .map(|x| usize::try_from(x).unwrap()),
+            params
+                .ecn_mark_threshold
+                .map(|x| usize::try_from(x).unwrap()),
+            params.node_seed,
+        );
         let relay_inet_out = Relay::new(
             RateLimit::BytesPerSecond(params.requested_bw_up_bits / 8),
             net_ns.internet.borrow().get_address(),
@@ -322,6 +543,9 @@ impl Host {
             relay_loopback: Arc::new(relay_loopback),
             tracker: RefCell::new(None),
             futex_table: RefCell::new(FutexTable::new()),
+            sysv_shm: RefCell::new(SysvShm::new()),
+            epoll_exclusive_wakeups: RefCell::new(ExclusiveWakeups::new()),
+            clock_adjustment: RefCell::new(ClockAdjustment::new()),
             random,
             shim_shmem,
             shim_shmem_lock: RefCell::new(None),
@@ -340,6 +564,8 @@ impl Host {
             execution_timer,
             in_notify_socket_has_packets,
             preload_paths,
+            tmpfs_mounts,
+            block_devices,
         };
 
         res.stop_execution_timer();
@@ -591,6 +817,10 @@ impl Host {
                 name: self.params.hostname.to_str().unwrap().to_owned(),
                 default_ip: self.default_ip(),
                 log_level: self.log_level(),
+                log_path: self
+                    .params
+                    .log_per_host
+                    .then(|| self.data_dir_path.join("shadow.log")),
             })
         })
     }
@@ -609,6 +839,63 @@ impl Host {
         u32::from_be(addr).into()
     }
 
+    /// If `path` is within one of the host's read-only mounts, returns the corresponding path on
+    /// the real filesystem.
+    pub fn read_only_mount_path(&self, path: &Path) -> Option<PathBuf> {
+        // paths are ordered by component, so a nested mount is found before its parent mount
+        self.params
+            .read_only_mounts
+            .iter()
+            .rev()
+            .find_map(|(target, source)| mount_subpath(target, source, path))
+    }
+
+    /// If `path` is within one of the host's tmpfs mounts, returns the corresponding path on the
+    /// real filesystem.
+    pub fn tmpfs_mount_path(&self, path: &Path) -> Option<PathBuf> {
+        self.tmpfs_mounts
+            .iter()
+            .find_map(|x| mount_subpath(&x.target, x.dir.path(), path))
+    }
+
+    /// The tmpfs mount that the real path `path` is within, if any.
+    fn tmpfs_mount_at(&self, path: &Path) -> Option<&TmpfsMount> {
+        self.tmpfs_mounts
+            .iter()
+            .find(|x| x.contains_real_path(path))
+    }
+
+    /// If the real path `path` is within one of the host's tmpfs mounts, returns the size of the
+    /// mount and the number of bytes currently used, in bytes.
+    pub fn tmpfs_usage(&self, path: &Path) -> Option<(u64, u64)> {
+        self.tmpfs_mount_at(path).map(|x| (x.size, x.used()))
+    }
+
+    /// If the real path `path` is within one of the host's tmpfs mounts, records that the file
+    /// with inode number `ino` now has an apparent size of `size` bytes.
+    pub fn tmpfs_set_file_size(&self, path: &Path, ino: u64, size: u64) {
+        if let Some(mount) = self.tmpfs_mount_at(path) {
+            mount.set_file_size(ino, size);
+        }
+    }
+
+    /// If the real path `path` is within one of the host's tmpfs mounts, records that the file
+    /// with inode number `ino` was removed from it.
+    pub fn tmpfs_remove_file(&self, path: &Path, ino: u64) {
+        if let Some(mount) = self.tmpfs_mount_at(path) {
+            mount.remove_file(ino);
+        }
+    }
+
+    /// If `path` is one of the host's block devices, returns the path of the real file that holds
+    /// its data and the device's configuration.
+    pub fn block_device(&self, path: &Path) -> Option<(&Path, BlockDeviceConfig)> {
+        self.block_devices
+            .iter()
+            .find(|x| x.target == path)
+            .map(|x| (x.file.path(), x.config))
+    }
+
     pub fn abstract_unix_namespace(
         &self,
     ) -> impl Deref<Target = Arc<AtomicRefCell<AbstractUnixNamespace>>> + '_ {
@@ -652,6 +939,33 @@ impl Host {
         self.futex_table.borrow_mut()
     }
 
+    #[track_caller]
+    pub fn sysv_shm_borrow(&self) -> impl Deref<Target = SysvShm> + '_ {
+        self.sysv_shm.borrow()
+    }
+
+    #[track_caller]
+    pub fn sysv_shm_borrow_mut(&self) -> impl DerefMut<Target = SysvShm> + '_ {
+        self.sysv_shm.borrow_mut()
+    }
+
+    #[track_caller]
+    pub fn epoll_exclusive_wakeups_borrow_mut(
+        &self,
+    ) -> impl DerefMut<Target = ExclusiveWakeups> + '_ {
+        self.epoll_exclusive_wakeups.borrow_mut()
+    }
+
+    #[track_caller]
+    pub fn clock_adjustment_borrow(&self) -> impl Deref<Target = ClockAdjustment> + '_ {
+        self.clock_adjustment.borrow()
+    }
+
+    #[track_caller]
+    pub fn clock_adjustment_borrow_mut(&self) -> impl DerefMut<Target = ClockAdjustment> + '_ {
+        self.clock_adjustment.borrow_mut()
+    }
+
     #[allow(non_snake_case)]
     pub fn bw_up_kiBps(&self) -> u64 {
         self.params.requested_bw_up_bits / (8 * 1024)
@@ -693,6 +1007,11 @@ impl Host {
         res
     }
 
+    /// Allocate a new thread id, which is also used as the process id for new processes. Ids are
+    /// allocated sequentially per host in the order that threads and processes are created, which
+    /// is deterministic since the host's events run in a deterministic order. Ids are never reused
+    /// (even after a process is reaped), so the id of a process doesn't depend on whether or when
+    /// earlier processes exited.
     pub fn get_new_thread_id(&self) -> ThreadId {
         let res = self.thread_id_counter.get();
         self.thread_id_counter.set(res + 1);
@@ -1049,10 +1368,12 @@ impl Drop for Host {
 }
 
 mod export {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
     use std::{os::raw::c_char, time::Duration};
 
     use libc::{in_addr_t, in_port_t};
-    use rand::{Rng, RngCore};
+    use rand::Rng;
     use shadow_shim_helper_rs::shim_shmem;
 
     use super::*;
@@ -1140,6 +1461,32 @@ mod export {
         hostrc.params.autotune_send_buf
     }
 
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_useECN(hostrc: *const Host) -> bool {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        hostrc.params.use_ecn
+    }
+
+    /// Returns the linux name of the congestion control algorithm that new TCP sockets should use.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_getTCPCongestionControl(
+        hostrc: *const Host,
+    ) -> *const libc::c_char {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        match hostrc.params.tcp_congestion_control {
+            TcpCongestionControl::Reno => unsafe { cshadow::TCP_CONG_RENO_NAME },
+            TcpCongestionControl::Cubic => unsafe { cshadow::TCP_CONG_CUBIC_NAME },
+            TcpCongestionControl::Bbr => unsafe { cshadow::TCP_CONG_BBR_NAME },
+        }
+    }
+
+    /// Returns the congestion window in segments that new TCP sockets should start with.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_getTCPInitialCwnd(hostrc: *const Host) -> u32 {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        hostrc.params.tcp_initial_cwnd
+    }
+
     #[no_mangle]
     pub unsafe extern "C-unwind" fn host_getConfiguredRecvBufSize(hostrc: *const Host) -> u64 {
         let hostrc = unsafe { hostrc.as_ref().unwrap() };
@@ -1197,6 +1544,7 @@ mod export {
     #[no_mangle]
     pub unsafe extern "C-unwind" fn host_disassociateInterface(
         hostrc: *const Host,
+        socket_handle: usize,
         protocol: cshadow::ProtocolType,
         bind_ip: in_addr_t,
         bind_port: in_port_t,
@@ -1216,7 +1564,184 @@ mod export {
         // associate the interfaces corresponding to bind_addr with socket
         hostrc
             .net_ns
-            .disassociate_interface(protocol, bind_addr, peer_addr);
+            .disassociate_interface(socket_handle, protocol, bind_addr, peer_addr);
+    }
+
+    /// Returns the contents of the host's `/proc/net/tcp` (for `PTCP`) or `/proc/net/udp` (for
+    /// `PUDP`) file. The returned string must be freed using `host_freeProcNetFile`.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_allocProcNetFile(
+        hostrc: *const Host,
+        protocol: cshadow::ProtocolType,
+    ) -> *mut c_char {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+
+        let entries = hostrc.net_ns.proc_net_entries(protocol);
+        // plugins run as the same user as shadow
+        let uid = rustix::process::getuid().as_raw();
+
+        let table = crate::host::network::proc_net::table(protocol, &entries, uid);
+        CString::new(table).unwrap().into_raw()
+    }
+
+    /// Returns the contents of the host's `/proc/net/dev` file. The returned string must be freed
+    /// using `host_freeProcNetFile`.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_allocProcNetDevFile(hostrc: *const Host) -> *mut c_char {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+
+        let stats = hostrc.net_ns.interface_stats();
+        let table = crate::host::network::proc_net::dev_table(
+            stats.iter().map(|(name, stats)| (name.as_str(), *stats)),
+        );
+        CString::new(table).unwrap().into_raw()
+    }
+
+    /// Frees a string previously returned from `host_allocProcNetFile` or
+    /// `host_allocProcNetDevFile`.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_freeProcNetFile(ptr: *mut c_char) {
+        assert!(!ptr.is_null());
+        drop(unsafe { CString::from_raw(ptr) });
+    }
+
+    /// Returns the real path of `path` if it's within one of the host's read-only mounts, or NULL
+    /// otherwise. The returned string must be freed using `host_freeReadOnlyMountPath`.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_allocReadOnlyMountPath(
+        hostrc: *const Host,
+        path: *const c_char,
+    ) -> *mut c_char {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        assert!(!path.is_null());
+        let path = unsafe { CStr::from_ptr(path) };
+        let path = Path::new(OsStr::from_bytes(path.to_bytes()));
+
+        match hostrc.read_only_mount_path(path) {
+            Some(x) => CString::new(x.into_os_string().into_vec())
+                .unwrap()
+                .into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    /// Frees a string previously returned from `host_allocReadOnlyMountPath` or
+    /// `host_allocBlockDevicePath`.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_freeReadOnlyMountPath(ptr: *mut c_char) {
+        assert!(!ptr.is_null());
+        drop(unsafe { CString::from_raw(ptr) });
+    }
+
+    /// Returns the real path of `path` if it's within one of the host's tmpfs mounts, or NULL
+    /// otherwise. The returned string must be freed using `host_freeTmpfsMountPath`.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_allocTmpfsMountPath(
+        hostrc: *const Host,
+        path: *const c_char,
+    ) -> *mut c_char {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        assert!(!path.is_null());
+        let path = unsafe { CStr::from_ptr(path) };
+        let path = Path::new(OsStr::from_bytes(path.to_bytes()));
+
+        match hostrc.tmpfs_mount_path(path) {
+            Some(x) => CString::new(x.into_os_string().into_vec())
+                .unwrap()
+                .into_raw(),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    /// Frees a string previously returned from `host_allocTmpfsMountPath`.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_freeTmpfsMountPath(ptr: *mut c_char) {
+        assert!(!ptr.is_null());
+        drop(unsafe { CString::from_raw(ptr) });
+    }
+
+    /// If the real path `path` is within one of the host's tmpfs mounts, writes the size of the
+    /// mount and the number of bytes used to `size` and `used` and returns true. Returns false
+    /// otherwise.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_getTmpfsUsage(
+        hostrc: *const Host,
+        path: *const c_char,
+        size: *mut u64,
+        used: *mut u64,
+    ) -> bool {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        assert!(!path.is_null());
+        let path = unsafe { CStr::from_ptr(path) };
+        let path = Path::new(OsStr::from_bytes(path.to_bytes()));
+
+        let Some((mount_size, mount_used)) = hostrc.tmpfs_usage(path) else {
+            return false;
+        };
+
+        unsafe { size.write(mount_size) };
+        unsafe { used.write(mount_used) };
+        true
+    }
+
+    /// If the real path `path` is within one of the host's tmpfs mounts, records that the file
+    /// with inode number `ino` now has an apparent size of `size` bytes.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_setTmpfsFileSize(
+        hostrc: *const Host,
+        path: *const c_char,
+        ino: u64,
+        size: u64,
+    ) {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        assert!(!path.is_null());
+        let path = unsafe { CStr::from_ptr(path) };
+        let path = Path::new(OsStr::from_bytes(path.to_bytes()));
+
+        hostrc.tmpfs_set_file_size(path, ino, size);
+    }
+
+    /// If the real path `path` is within one of the host's tmpfs mounts, records that the file
+    /// with inode number `ino` was removed from it.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_removeTmpfsFile(
+        hostrc: *const Host,
+        path: *const c_char,
+        ino: u64,
+    ) {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        assert!(!path.is_null());
+        let path = unsafe { CStr::from_ptr(path) };
+        let path = Path::new(OsStr::from_bytes(path.to_bytes()));
+
+        hostrc.tmpfs_remove_file(path, ino);
+    }
+
+    /// Returns the real path of the file that holds the data of the block device at `path`, and
+    /// writes the device's size and sector size to `size` and `sector_size`. Returns NULL if `path`
+    /// isn't one of the host's block devices. The returned string must be freed using
+    /// `host_freeReadOnlyMountPath`.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn host_allocBlockDevicePath(
+        hostrc: *const Host,
+        path: *const c_char,
+        size: *mut u64,
+        sector_size: *mut u32,
+    ) -> *mut c_char {
+        let hostrc = unsafe { hostrc.as_ref().unwrap() };
+        assert!(!path.is_null());
+        let path = unsafe { CStr::from_ptr(path) };
+        let path = Path::new(OsStr::from_bytes(path.to_bytes()));
+
+        let Some((real_path, config)) = hostrc.block_device(path) else {
+            return std::ptr::null_mut();
+        };
+
+        unsafe { size.write(config.size) };
+        unsafe { sector_size.write(config.sector_size) };
+        CString::new(real_path.as_os_str().as_bytes())
+            .unwrap()
+            .into_raw()
     }
 
     #[no_mangle]
@@ -1387,14 +1912,6 @@ mod export {
         host.random_mut().gen()
     }
 
-    /// Fills the buffer with pseudo-random bytes.
-    #[no_mangle]
-    pub extern "C-unwind" fn host_rngNextNBytes(host: *const Host, buf: *mut u8, len: usize) {
-        let host = unsafe { host.as_ref().unwrap() };
-        let buf = unsafe { std::slice::from_raw_parts_mut(buf, len) };
-        host.random_mut().fill_bytes(buf);
-    }
-
     #[no_mangle]
     pub extern "C-unwind" fn host_paramsCpuFrequencyHz(host: *const Host) -> u64 {
         let host = unsafe { host.as_ref().unwrap() };
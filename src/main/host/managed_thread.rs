@@ -261,6 +261,11 @@ impl ManagedThread {
                         let return_code = syscall.syscall_args.args[0].into();
                         debug!("Short-circuiting syscall exit({return_code})");
                         self.return_code.set(Some(return_code));
+                        // Like the kernel, release the thread's robust futexes when it exits. We do
+                        // it now since we can't read the process's memory after the native exit if
+                        // this is the process's last thread.
+                        ctx.process
+                            .release_exiting_thread_robust_futexes(ctx.host, ctx.thread);
                         // Tell mthread to go ahead and make the exit syscall itself.
                         // We *don't* call `_managedthread_continuePlugin` here,
                         // since that'd release the ShimSharedMemHostLock, and we
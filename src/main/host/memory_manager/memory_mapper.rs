@@ -91,6 +91,9 @@ pub struct MemoryMapper {
     /// zero-sized interval (though in the case of thread-preload that'll have already happened
     /// before we get control).
     heap: Interval,
+
+    /// The largest value of `private_writable_size()` seen so far.
+    peak_private_writable_size: usize,
 }
 
 /// Shared memory file into which we relocate parts of the plugin's address space.
@@ -425,12 +428,44 @@ impl MemoryMapper {
         let heap = get_heap(ctx, &mut shm_file, memory_manager, &mut regions);
         map_stack(memory_manager, ctx, &mut shm_file, &mut regions);
 
-        MemoryMapper {
+        let mut mapper = MemoryMapper {
             shm_file,
             regions,
             misses_by_path: RefCell::new(HashMap::new()),
             heap,
-        }
+            peak_private_writable_size: 0,
+        };
+        mapper.update_peak_private_writable_size();
+        mapper
+    }
+
+    /// The total size of the private writable mappings that aren't backed by a file, such as the
+    /// heap, the stacks, and private anonymous mappings. Unlike the native resident set size, this
+    /// depends only on the plugin's mappings, so it's deterministic.
+    fn private_writable_size(&self) -> usize {
+        self.regions
+            .iter()
+            .filter(|(_, region)| {
+                region.sharing == Sharing::Private
+                    && region.prot.contains(ProtFlags::PROT_WRITE)
+                    && !matches!(region.original_path, Some(MappingPath::Path(_)))
+            })
+            .map(|(interval, _)| interval.len())
+            .sum()
+    }
+
+    /// Should be called after the mappings change.
+    pub fn update_peak_private_writable_size(&mut self) {
+        self.peak_private_writable_size = std::cmp::max(
+            self.peak_private_writable_size,
+            self.private_writable_size(),
+        );
+    }
+
+    /// The largest total size of the private writable mappings since the mapper was created. See
+    /// `private_writable_size()`.
+    pub fn peak_private_writable_size(&self) -> usize {
+        self.peak_private_writable_size
     }
 
     /// Processes the mutations returned by an IntervalMap::insert or IntervalMap::clear operation.
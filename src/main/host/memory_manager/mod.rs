@@ -270,7 +270,16 @@ where
     }
 }
 
-fn page_size() -> usize {
+/// The highest address at which mappings are placed when using deterministic ASLR. This is below
+/// the stack and the mappings made by the loader when ASLR is disabled.
+const ASLR_MMAP_TOP: usize = 0x7f00_0000_0000;
+
+/// The number of random bits in the page offset of the deterministic ASLR base address, which is
+/// linux's default `vm.mmap_rnd_bits` on x86-64.
+const ASLR_MMAP_RND_BITS: u32 = 28;
+
+/// The system page size.
+pub fn page_size() -> usize {
     nix::unistd::sysconf(nix::unistd::SysconfVar::PAGE_SIZE)
         .unwrap()
         .unwrap()
@@ -303,6 +312,10 @@ pub struct MemoryManager {
 
     // Native pid of the plugin process.
     pid: Pid,
+
+    // The address below which the next mapping is placed when using deterministic ASLR, or `None`
+    // if it hasn't been chosen yet.
+    aslr_mmap_cursor: Option<usize>,
 }
 
 impl MemoryManager {
@@ -325,6 +338,7 @@ impl MemoryManager {
             pid,
             memory_copier: MemoryCopier::new(pid),
             memory_mapper: None,
+            aslr_mmap_cursor: None,
         }
     }
 
@@ -354,6 +368,7 @@ impl MemoryManager {
         &self,
         ptr: ForeignArrayPtr<T>,
     ) -> Result<ProcessMemoryRef<'_, T>, Errno> {
+        ptr.check_user_range()?;
         if let Some(mref) = self.mapped_ref(ptr) {
             Ok(ProcessMemoryRef::new_mapped(mref))
         } else {
@@ -371,6 +386,8 @@ impl MemoryManager {
         &self,
         ptr: ForeignArrayPtr<T>,
     ) -> Result<ProcessMemoryRef<T>, Errno> {
+        let ptr = ptr.user_range_prefix()?;
+
         // Only use the mapped ref if it's able to get the whole region,
         // since otherwise the copying version might be able to get more
         // data.
@@ -463,6 +480,7 @@ impl MemoryManager {
         dst: &mut [T],
         src: ForeignArrayPtr<T>,
     ) -> Result<(), Errno> {
+        src.check_user_range()?;
         if let Some(src) = self.mapped_ref(src) {
             dst.copy_from_slice(src);
             return Ok(());
@@ -479,6 +497,8 @@ impl MemoryManager {
         buf: &mut [T],
         ptr: ForeignArrayPtr<T>,
     ) -> Result<usize, Errno> {
+        let ptr = ptr.user_range_prefix()?;
+        let buf = &mut buf[..std::cmp::min(buf.len(), ptr.len())];
         if let Some(src) = self.mapped_ref(ptr) {
             buf.copy_from_slice(src);
             return Ok(src.len());
@@ -516,6 +536,7 @@ impl MemoryManager {
         // borrow.
         let pid = self.pid;
 
+        ptr.check_user_range()?;
         if let Some(mref) = self.mapped_mut(ptr) {
             Ok(ProcessMemoryRefMut::new_mapped(mref))
         } else {
@@ -545,6 +566,7 @@ impl MemoryManager {
         // borrow.
         let pid = self.pid;
 
+        ptr.check_user_range()?;
         let mut mref = if let Some(mref) = self.mapped_mut(ptr) {
             // Even if we haven't initialized the data from this process, the
             // data is initialized from the Rust compiler's perspective; it has
@@ -579,6 +601,7 @@ impl MemoryManager {
         dst: ForeignArrayPtr<T>,
         src: &[T],
     ) -> Result<(), Errno> {
+        dst.check_user_range()?;
         if let Some(dst) = self.mapped_mut(dst) {
             dst.copy_from_slice(src);
             return Ok(());
@@ -593,6 +616,17 @@ impl MemoryManager {
         self.pid
     }
 
+    /// An estimate of the peak resident set size of the process in KiB. We don't use the native
+    /// value since it isn't deterministic, and instead use the peak total size of the private
+    /// writable mappings (such as the heap, stacks, and anonymous mappings) tracked by the
+    /// `MemoryMapper`. Returns 0 if the `MemoryMapper` isn't being used.
+    pub fn peak_rss_kib(&self) -> u64 {
+        let Some(mm) = &self.memory_mapper else {
+            return 0;
+        };
+        (mm.peak_private_writable_size() / 1024).try_into().unwrap()
+    }
+
     /// Initialize the MemoryMapper, allowing for more efficient access. Needs a
     /// running thread.
     pub fn init_mapper(&mut self, ctx: &ThreadContext) {
@@ -620,7 +654,11 @@ impl MemoryManager {
         ptr: ForeignPtr<u8>,
     ) -> Result<ForeignPtr<u8>, SyscallError> {
         match &mut self.memory_mapper {
-            Some(mm) => Ok(mm.handle_brk(ctx, ptr)?),
+            Some(mm) => {
+                let rv = mm.handle_brk(ctx, ptr)?;
+                mm.update_peak_private_writable_size();
+                Ok(rv)
+            }
             None => Err(SyscallError::Native),
         }
     }
@@ -641,10 +679,27 @@ impl MemoryManager {
         };
         if let Some(mm) = &mut self.memory_mapper {
             mm.handle_mmap_result(ctx, ForeignArrayPtr::new(addr, length), prot, flags, fd);
+            mm.update_peak_private_writable_size();
         }
         Ok(addr)
     }
 
+    /// Choose an address hint for a new mapping of `length` bytes when using deterministic ASLR.
+    /// Like linux's top-down allocator, each mapping is placed below the previous one, starting
+    /// from a base address that's chosen using `rng` once per address space.
+    pub fn aslr_mmap_hint(&mut self, length: usize, rng: &mut impl rand::Rng) -> ForeignPtr<u8> {
+        let cursor = *self.aslr_mmap_cursor.get_or_insert_with(|| {
+            let offset_pages = rng.gen_range(0..(1usize << ASLR_MMAP_RND_BITS));
+            ASLR_MMAP_TOP - offset_pages * page_size()
+        });
+
+        let length = length.next_multiple_of(page_size());
+        let hint = cursor.saturating_sub(length);
+        self.aslr_mmap_cursor = Some(hint);
+
+        ForeignPtr::<()>::from(hint).cast::<u8>()
+    }
+
     pub fn handle_munmap(
         &mut self,
         ctx: &ThreadContext,
@@ -663,7 +718,8 @@ impl MemoryManager {
         }
     }
 
-    fn do_munmap(
+    /// Unmap the memory in the plugin, updating our mappings if it succeeded.
+    pub fn do_munmap(
         &mut self,
         ctx: &ThreadContext,
         addr: ForeignPtr<u8>,
@@ -688,7 +744,10 @@ impl MemoryManager {
     ) -> Result<ForeignPtr<u8>, SyscallError> {
         match &mut self.memory_mapper {
             Some(mm) => {
-                Ok(mm.handle_mremap(ctx, old_address, old_size, new_size, flags, new_address)?)
+                let rv =
+                    mm.handle_mremap(ctx, old_address, old_size, new_size, flags, new_address)?;
+                mm.update_peak_private_writable_size();
+                Ok(rv)
             }
             None => Err(SyscallError::Native),
         }
@@ -702,7 +761,11 @@ impl MemoryManager {
         prot: ProtFlags,
     ) -> Result<(), SyscallError> {
         match &mut self.memory_mapper {
-            Some(mm) => Ok(mm.handle_mprotect(ctx, addr, size, prot)?),
+            Some(mm) => {
+                mm.handle_mprotect(ctx, addr, size, prot)?;
+                mm.update_peak_private_writable_size();
+                Ok(())
+            }
             None => Err(SyscallError::Native),
         }
     }
@@ -4,6 +4,7 @@
 //! allows Shadow to intercept their syscalls. It also contains the emulation of Linux hosts,
 //! threads, processes, syscalls, files, network interfaces, etc.
 
+pub mod clock;
 pub mod context;
 pub mod cpu;
 pub mod descriptor;
@@ -13,8 +14,10 @@ pub mod host;
 pub mod managed_thread;
 pub mod memory_manager;
 pub mod network;
+pub mod posix_timer;
 pub mod process;
 pub mod status_listener;
 pub mod syscall;
+pub mod sysv_shm;
 pub mod thread;
 pub mod timer;
@@ -1,8 +1,11 @@
-use std::ffi::{CString, OsStr};
+use std::cell::{Cell, RefCell};
+use std::ffi::{CStr, CString, OsStr};
 use std::net::{Ipv4Addr, SocketAddrV4};
 use std::os::unix::ffi::OsStrExt;
 use std::path::PathBuf;
+use std::sync::{Arc, Weak};
 
+use atomic_refcell::AtomicRefCell;
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
 use shadow_shim_helper_rs::HostId;
 
@@ -10,8 +13,10 @@ use crate::core::configuration::QDiscMode;
 use crate::core::worker::Worker;
 use crate::cshadow as c;
 use crate::host::descriptor::socket::inet::InetSocket;
+use crate::host::descriptor::socket::packet::PacketSocket;
 use crate::network::packet::PacketRc;
 use crate::network::PacketDevice;
+use crate::utility::callback_queue::CallbackQueue;
 use crate::utility::{self, HostTreePointer};
 
 /// The priority used by the fifo qdisc to choose the next socket to send a packet from.
@@ -23,15 +28,33 @@ pub struct PcapOptions {
     pub capture_size_bytes: u32,
 }
 
+/// Packet counters of a [`NetworkInterface`], as reported by `/proc/net/dev`. Byte counts include
+/// the IP and transport headers of the packets.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InterfaceStats {
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    /// Received packets that weren't delivered since no socket was associated with their address.
+    pub rx_dropped: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+}
+
 /// Represents a network device that can send and receive packets. All accesses
 /// to the internal C implementation should be done through this module.
 pub struct NetworkInterface {
     c_ptr: HostTreePointer<c::NetworkInterface>,
     addr: Ipv4Addr,
+    name: CString,
+    index: u32,
+    stats: Cell<InterfaceStats>,
+    /// `AF_PACKET` sockets that receive a copy of each packet sent or received on this interface.
+    packet_sockets: RefCell<Vec<Weak<AtomicRefCell<PacketSocket>>>>,
 }
 
 impl NetworkInterface {
-    /// Create a new network interface for `host_id` with the assigned `addr`.
+    /// Create a new network interface for `host_id` with the assigned `addr` and interface
+    /// `index`.
     ///
     /// # Safety
     ///
@@ -43,8 +66,10 @@ impl NetworkInterface {
         host_id: HostId,
         addr: *mut c::Address,
         name: &OsStr,
+        index: u32,
         pcap_options: Option<PcapOptions>,
         qdisc: QDiscMode,
+        reuseport_seed: u64,
     ) -> NetworkInterface {
         let maybe_pcap_dir = pcap_options
             .as_ref()
@@ -63,7 +88,14 @@ impl NetworkInterface {
         let name = CString::from_vec_with_nul(name).unwrap();
 
         let c_ptr = unsafe {
-            c::networkinterface_new(addr, name.as_ptr(), pcap_dir_cptr, pcap_capture_size, qdisc)
+            c::networkinterface_new(
+                addr,
+                name.as_ptr(),
+                pcap_dir_cptr,
+                pcap_capture_size,
+                qdisc,
+                reuseport_seed,
+            )
         };
 
         let ipv4_addr: Ipv4Addr = {
@@ -74,15 +106,46 @@ impl NetworkInterface {
         NetworkInterface {
             c_ptr: HostTreePointer::new_for_host(host_id, c_ptr),
             addr: ipv4_addr,
+            name,
+            index,
+            stats: Cell::new(InterfaceStats::default()),
+            packet_sockets: RefCell::new(Vec::new()),
         }
     }
 
+    /// The name of the interface, such as "eth0".
+    pub fn name(&self) -> &CStr {
+        &self.name
+    }
+
+    /// The interface index, such as returned by `if_nametoindex()`.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The `ARPHRD_*` hardware type of the interface.
+    pub fn hardware_type(&self) -> u16 {
+        if self.addr.is_loopback() {
+            libc::ARPHRD_LOOPBACK
+        } else {
+            libc::ARPHRD_ETHER
+        }
+    }
+
+    /// The packet counters of the interface.
+    pub fn stats(&self) -> InterfaceStats {
+        self.stats.get()
+    }
+
+    /// Associate the socket with the port and peer address. If `reuse_port` is true, the socket
+    /// joins the group of `SO_REUSEPORT` sockets associated with the same port and peer address.
     pub fn associate(
         &self,
         socket_ptr: &InetSocket,
         protocol_type: c::ProtocolType,
         port: u16,
         peer_addr: SocketAddrV4,
+        reuse_port: bool,
     ) {
         let port = port.to_be();
         let peer_ip = u32::from(*peer_addr.ip()).to_be();
@@ -96,11 +159,20 @@ impl NetworkInterface {
                 port,
                 peer_ip,
                 peer_port,
+                reuse_port,
             )
         };
     }
 
-    pub fn disassociate(&self, protocol_type: c::ProtocolType, port: u16, peer_addr: SocketAddrV4) {
+    /// Disassociate the socket with canonical handle `socket_handle` from the port and peer
+    /// address.
+    pub fn disassociate(
+        &self,
+        socket_handle: usize,
+        protocol_type: c::ProtocolType,
+        port: u16,
+        peer_addr: SocketAddrV4,
+    ) {
         let port = port.to_be();
         let peer_ip = u32::from(*peer_addr.ip()).to_be();
         let peer_port = peer_addr.port().to_be();
@@ -112,6 +184,7 @@ impl NetworkInterface {
                 port,
                 peer_ip,
                 peer_port,
+                socket_handle,
             )
         };
     }
@@ -126,6 +199,83 @@ impl NetworkInterface {
         }) != 0
     }
 
+    /// Is the address in use by a group of `SO_REUSEPORT` sockets that other `SO_REUSEPORT`
+    /// sockets can join?
+    pub fn is_reuse_port_group(
+        &self,
+        protocol: c::ProtocolType,
+        port: u16,
+        peer: SocketAddrV4,
+    ) -> bool {
+        let port = port.to_be();
+        let peer_ip = u32::from(*peer.ip()).to_be();
+        let peer_port = peer.port().to_be();
+
+        (unsafe {
+            c::networkinterface_isReusePortGroup(
+                self.c_ptr.ptr(),
+                protocol,
+                port,
+                peer_ip,
+                peer_port,
+            )
+        }) != 0
+    }
+
+    /// Get all sockets associated with this interface, in an unspecified order. A socket may be
+    /// associated more than once (for example with different peers).
+    pub fn associated_sockets(&self) -> Vec<InetSocket> {
+        extern "C-unwind" fn push_socket(
+            socket: *const InetSocket,
+            sockets: *mut std::ffi::c_void,
+        ) {
+            let socket = unsafe { socket.as_ref() }.unwrap();
+            let sockets = unsafe { sockets.cast::<Vec<InetSocket>>().as_mut() }.unwrap();
+            sockets.push(socket.clone());
+        }
+
+        let mut sockets = Vec::new();
+        unsafe {
+            c::networkinterface_forEachSocket(
+                self.c_ptr.ptr(),
+                Some(push_socket),
+                std::ptr::addr_of_mut!(sockets).cast(),
+            )
+        };
+
+        sockets
+    }
+
+    /// Deliver a copy of every packet sent or received on this interface to the `AF_PACKET`
+    /// socket. The socket is removed automatically once it's dropped.
+    pub fn add_packet_socket(&self, socket: &Arc<AtomicRefCell<PacketSocket>>) {
+        self.packet_sockets
+            .borrow_mut()
+            .push(Arc::downgrade(socket));
+    }
+
+    /// Give a copy of the packet to all `AF_PACKET` sockets of this interface.
+    fn capture_packet(&self, packet: &PacketRc, is_outgoing: bool) {
+        let sockets: Vec<_> = {
+            let mut packet_sockets = self.packet_sockets.borrow_mut();
+            // the sockets may have been dropped since they were added
+            packet_sockets.retain(|x| x.strong_count() > 0);
+            packet_sockets.iter().filter_map(Weak::upgrade).collect()
+        };
+
+        if sockets.is_empty() {
+            return;
+        }
+
+        CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            for socket in sockets {
+                socket
+                    .borrow_mut()
+                    .push_in_packet(packet, self, is_outgoing, cb_queue);
+            }
+        });
+    }
+
     pub fn add_data_source(&self, socket: &InetSocket) {
         unsafe { c::networkinterface_wantsSend(self.c_ptr.ptr(), socket) };
     }
@@ -151,16 +301,32 @@ impl PacketDevice for NetworkInterface {
 
     fn pop(&self) -> Option<PacketRc> {
         let packet_ptr = unsafe { c::networkinterface_pop(self.c_ptr.ptr()) };
-        match packet_ptr.is_null() {
-            true => None,
-            false => Some(PacketRc::from_raw(packet_ptr)),
+        if packet_ptr.is_null() {
+            return None;
         }
+
+        let packet = PacketRc::from_raw(packet_ptr);
+        self.capture_packet(&packet, /* is_outgoing= */ true);
+
+        let mut stats = self.stats.get();
+        stats.tx_bytes += u64::try_from(packet.total_size()).unwrap();
+        stats.tx_packets += 1;
+        self.stats.set(stats);
+
+        Some(packet)
     }
 
     fn push(&self, packet: PacketRc) {
+        let mut stats = self.stats.get();
+        stats.rx_bytes += u64::try_from(packet.total_size()).unwrap();
+        stats.rx_packets += 1;
+        self.stats.set(stats);
+
+        self.capture_packet(&packet, /* is_outgoing= */ false);
+
         let packet_ptr = packet.into_inner();
         let current_time = Worker::current_time().unwrap();
-        unsafe {
+        let delivered = unsafe {
             c::networkinterface_push(
                 self.c_ptr.ptr(),
                 packet_ptr,
@@ -168,5 +334,12 @@ impl PacketDevice for NetworkInterface {
             )
         };
         unsafe { c::packet_unref(packet_ptr) };
+
+        if !delivered {
+            // the stats may have changed while the packet was being delivered
+            let mut stats = self.stats.get();
+            stats.rx_dropped += 1;
+            self.stats.set(stats);
+        }
     }
 }
@@ -1,2 +1,3 @@
 pub mod interface;
 pub mod namespace;
+pub mod proc_net;
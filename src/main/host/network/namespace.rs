@@ -1,8 +1,9 @@
 use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::ffi::{CString, OsStr};
 use std::net::{Ipv4Addr, SocketAddrV4};
 use std::num::NonZeroU8;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, RangeInclusive};
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
@@ -13,12 +14,9 @@ use crate::core::configuration::QDiscMode;
 use crate::core::worker::Worker;
 use crate::cshadow;
 use crate::host::descriptor::socket::abstract_unix_ns::AbstractUnixNamespace;
-use crate::host::descriptor::socket::inet::InetSocket;
-use crate::host::network::interface::{NetworkInterface, PcapOptions};
-
-// The start of our random port range in host order, used if application doesn't
-// specify the port it wants to bind to, and for client connections.
-const MIN_RANDOM_PORT: u16 = 10000;
+use crate::host::descriptor::socket::inet::{InetSocket, ProcNetEntry};
+use crate::host::descriptor::socket::packet::PacketSocket;
+use crate::host::network::interface::{InterfaceStats, NetworkInterface, PcapOptions};
 
 /// Represents a network namespace.
 ///
@@ -37,6 +35,10 @@ pub struct NetworkNamespace {
     pub default_address: SyncSendPointer<cshadow::Address>,
     pub default_ip: Ipv4Addr,
 
+    // the inclusive range of ports in host order that are used if the application doesn't specify
+    // the port it wants to bind to, and for client connections
+    ephemeral_ports: RangeInclusive<u16>,
+
     // used for debugging to make sure we've cleaned up before being dropped
     has_run_cleanup: Cell<bool>,
 }
@@ -51,17 +53,21 @@ impl NetworkNamespace {
         public_ip: Ipv4Addr,
         pcap: Option<PcapOptions>,
         qdisc: QDiscMode,
+        ephemeral_ports: RangeInclusive<u16>,
+        reuseport_seed: u64,
         dns: *mut cshadow::DNS,
     ) -> Self {
         let (localhost, local_addr) = unsafe {
             Self::setup_net_interface(
                 OsStr::new("lo"),
+                1,
                 &InterfaceOptions {
                     host_id,
                     hostname: hostname.clone(),
                     ip: Ipv4Addr::LOCALHOST,
                     pcap: pcap.clone(),
                     qdisc,
+                    reuseport_seed,
                 },
                 dns,
             )
@@ -72,12 +78,14 @@ impl NetworkNamespace {
         let (internet, public_addr) = unsafe {
             Self::setup_net_interface(
                 OsStr::new("eth0"),
+                2,
                 &InterfaceOptions {
                     host_id,
                     hostname,
                     ip: public_ip,
                     pcap,
                     qdisc,
+                    reuseport_seed,
                 },
                 dns,
             )
@@ -89,6 +97,7 @@ impl NetworkNamespace {
             internet: RefCell::new(internet),
             default_address: unsafe { SyncSendPointer::new(public_addr) },
             default_ip: public_ip,
+            ephemeral_ports,
             has_run_cleanup: Cell::new(false),
         }
     }
@@ -96,6 +105,7 @@ impl NetworkNamespace {
     /// Must free the returned `*mut cshadow::Address` using [`cshadow::address_unref`].
     unsafe fn setup_net_interface(
         name: &OsStr,
+        index: u32,
         options: &InterfaceOptions,
         dns: *mut cshadow::DNS,
     ) -> (NetworkInterface, *mut cshadow::Address) {
@@ -114,8 +124,10 @@ impl NetworkNamespace {
                 options.host_id,
                 addr,
                 name,
+                index,
                 options.pcap.clone(),
                 options.qdisc,
+                options.reuseport_seed,
             )
         };
 
@@ -209,7 +221,33 @@ impl NetworkNamespace {
         }
     }
 
-    /// Returns a random port in host byte order.
+    /// Returns true if every network interface where `src` and `dst` are in use has a group of
+    /// `SO_REUSEPORT` sockets associated with the addresses, so that another `SO_REUSEPORT` socket
+    /// can join them.
+    pub fn can_reuse_port(
+        &self,
+        protocol_type: cshadow::ProtocolType,
+        src: SocketAddrV4,
+        dst: SocketAddrV4,
+    ) -> Result<bool, NoInterface> {
+        let can_reuse = |iface: &NetworkInterface| {
+            !iface.is_addr_in_use(protocol_type, src.port(), dst)
+                || iface.is_reuse_port_group(protocol_type, src.port(), dst)
+        };
+
+        if src.ip().is_unspecified() {
+            Ok(can_reuse(&self.localhost.borrow()) && can_reuse(&self.internet.borrow()))
+        } else {
+            match self.interface_borrow(*src.ip()) {
+                Some(i) => Ok(can_reuse(&i)),
+                None => Err(NoInterface),
+            }
+        }
+    }
+
+    /// Returns a free port from the ephemeral port range in host byte order. The ports are chosen
+    /// in an order determined by `rng`, so the same rng state will always choose the same port.
+    /// Returns `None` if all ports in the range are in use.
     pub fn get_random_free_port(
         &self,
         protocol_type: cshadow::ProtocolType,
@@ -217,41 +255,14 @@ impl NetworkNamespace {
         peer: SocketAddrV4,
         mut rng: impl rand::Rng,
     ) -> Option<u16> {
-        // we need a random port that is free everywhere we need it to be.
-        // we have two modes here: first we just try grabbing a random port until we
-        // get a free one. if we cannot find one fast enough, then as a fallback we
-        // do an inefficient linear search that is guaranteed to succeed or fail.
-
-        // if choosing randomly doesn't succeed within 10 tries, then we have already
-        // allocated a lot of ports (>90% on average). then we fall back to linear search.
-        for _ in 0..10 {
-            let random_port = rng.gen_range(MIN_RANDOM_PORT..=u16::MAX);
+        let min = *self.ephemeral_ports.start();
+        let max = *self.ephemeral_ports.end();
 
+        // start from a random port in the range and search linearly (wrapping around) for a port
+        // that is free everywhere we need it to be
+        let start = rng.gen_range(min..=max);
+        for port in (start..=max).chain(min..start) {
             // `is_addr_in_use` will check all interfaces in the case of INADDR_ANY
-            let specific_in_use = self
-                .is_addr_in_use(
-                    protocol_type,
-                    SocketAddrV4::new(interface_ip, random_port),
-                    peer,
-                )
-                .unwrap_or(true);
-            let generic_in_use = self
-                .is_addr_in_use(
-                    protocol_type,
-                    SocketAddrV4::new(interface_ip, random_port),
-                    SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0),
-                )
-                .unwrap_or(true);
-            if !specific_in_use && !generic_in_use {
-                return Some(random_port);
-            }
-        }
-
-        // now if we tried too many times and still don't have a port, fall back
-        // to a linear search to make sure we get a free port if we have one.
-        // but start from a random port instead of the min.
-        let start = rng.gen_range(MIN_RANDOM_PORT..=u16::MAX);
-        for port in (start..=u16::MAX).chain(MIN_RANDOM_PORT..start) {
             let specific_in_use = self
                 .is_addr_in_use(protocol_type, SocketAddrV4::new(interface_ip, port), peer)
                 .unwrap_or(true);
@@ -272,7 +283,8 @@ impl NetworkNamespace {
     }
 
     /// Associate the socket with any applicable network interfaces. The socket will be
-    /// automatically disassociated when the returned handle is dropped.
+    /// automatically disassociated when the returned handle is dropped. If `reuse_port` is true,
+    /// the socket joins any existing group of `SO_REUSEPORT` sockets with the same addresses.
     ///
     /// # Safety
     ///
@@ -283,53 +295,126 @@ impl NetworkNamespace {
         protocol: cshadow::ProtocolType,
         bind_addr: SocketAddrV4,
         peer_addr: SocketAddrV4,
+        reuse_port: bool,
     ) -> AssociationHandle {
         if bind_addr.ip().is_unspecified() {
             // need to associate all interfaces
-            self.localhost
-                .borrow()
-                .associate(socket, protocol, bind_addr.port(), peer_addr);
-            self.internet
-                .borrow()
-                .associate(socket, protocol, bind_addr.port(), peer_addr);
+            self.localhost.borrow().associate(
+                socket,
+                protocol,
+                bind_addr.port(),
+                peer_addr,
+                reuse_port,
+            );
+            self.internet.borrow().associate(
+                socket,
+                protocol,
+                bind_addr.port(),
+                peer_addr,
+                reuse_port,
+            );
         } else {
             // TODO: return error if interface does not exist
             if let Some(iface) = self.interface_borrow(*bind_addr.ip()) {
-                iface.associate(socket, protocol, bind_addr.port(), peer_addr);
+                iface.associate(socket, protocol, bind_addr.port(), peer_addr, reuse_port);
             }
         }
 
         AssociationHandle {
+            socket_handle: socket.canonical_handle(),
             protocol,
             local_addr: bind_addr,
             remote_addr: peer_addr,
         }
     }
 
-    /// Disassociate the socket associated using the local and remote addresses from all network
-    /// interfaces.
+    /// Get the `/proc/net/tcp` or `/proc/net/udp` entries for the associated sockets with protocol
+    /// `protocol`. The entries are sorted so that their order doesn't depend on the order in which
+    /// the sockets were associated.
+    pub fn proc_net_entries(&self, protocol: cshadow::ProtocolType) -> Vec<ProcNetEntry> {
+        let mut sockets = self.localhost.borrow().associated_sockets();
+        sockets.extend(self.internet.borrow().associated_sockets());
+
+        // a socket bound to 0.0.0.0 is associated with every interface
+        let mut seen = HashSet::new();
+        sockets.retain(|socket| seen.insert(socket.canonical_handle()));
+
+        let mut entries: Vec<_> = sockets
+            .iter()
+            .filter(|socket| match socket {
+                InetSocket::LegacyTcp(_) | InetSocket::Tcp(_) => {
+                    protocol == cshadow::_ProtocolType_PTCP
+                }
+                InetSocket::Udp(_) => protocol == cshadow::_ProtocolType_PUDP,
+            })
+            .map(|socket| socket.borrow().proc_net_entry())
+            .collect();
+
+        entries.sort();
+        entries
+    }
+
+    /// The names and packet counters of the namespace's interfaces, as listed in `/proc/net/dev`.
+    pub fn interface_stats(&self) -> Vec<(String, InterfaceStats)> {
+        [&self.localhost, &self.internet]
+            .into_iter()
+            .map(|interface| {
+                let interface = interface.borrow();
+                let name = interface.name().to_string_lossy().into_owned();
+                (name, interface.stats())
+            })
+            .collect()
+    }
+
+    /// The `ARPHRD_*` hardware type of the interface with the interface index `index`, or `None` if
+    /// there's no such interface.
+    pub fn interface_hardware_type(&self, index: u32) -> Option<u16> {
+        [&self.localhost, &self.internet]
+            .into_iter()
+            .map(|interface| interface.borrow())
+            .find(|interface| interface.index() == index)
+            .map(|interface| interface.hardware_type())
+    }
+
+    /// Deliver a copy of every packet sent or received on the namespace's interfaces to the
+    /// `AF_PACKET` socket. The socket is responsible for filtering out the packets it doesn't want.
+    pub fn add_packet_socket(&self, socket: &Arc<AtomicRefCell<PacketSocket>>) {
+        for interface in [&self.localhost, &self.internet] {
+            interface.borrow().add_packet_socket(socket);
+        }
+    }
+
+    /// Disassociate the socket with canonical handle `socket_handle` that was associated using the
+    /// local and remote addresses from all network interfaces.
     ///
     /// Is only public so that it can be called from `host_disassociateInterface`. Normally this
     /// should only be called from the [`AssociationHandle`].
     pub fn disassociate_interface(
         &self,
+        socket_handle: usize,
         protocol: cshadow::ProtocolType,
         bind_addr: SocketAddrV4,
         peer_addr: SocketAddrV4,
     ) {
         if bind_addr.ip().is_unspecified() {
             // need to disassociate all interfaces
-            self.localhost
-                .borrow()
-                .disassociate(protocol, bind_addr.port(), peer_addr);
+            self.localhost.borrow().disassociate(
+                socket_handle,
+                protocol,
+                bind_addr.port(),
+                peer_addr,
+            );
 
-            self.internet
-                .borrow()
-                .disassociate(protocol, bind_addr.port(), peer_addr);
+            self.internet.borrow().disassociate(
+                socket_handle,
+                protocol,
+                bind_addr.port(),
+                peer_addr,
+            );
         } else {
             // TODO: return error if interface does not exist
             if let Some(iface) = self.interface_borrow(*bind_addr.ip()) {
-                iface.disassociate(protocol, bind_addr.port(), peer_addr);
+                iface.disassociate(socket_handle, protocol, bind_addr.port(), peer_addr);
             }
         }
     }
@@ -351,6 +436,7 @@ struct InterfaceOptions {
     pub ip: Ipv4Addr,
     pub pcap: Option<PcapOptions>,
     pub qdisc: QDiscMode,
+    pub reuseport_seed: u64,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -370,6 +456,7 @@ impl std::error::Error for NoInterface {}
 /// [`callback_queue::Handle`](crate::utility::callback_queue::Handle)).
 #[derive(Debug)]
 pub struct AssociationHandle {
+    socket_handle: usize,
     protocol: cshadow::ProtocolType,
     local_addr: SocketAddrV4,
     remote_addr: SocketAddrV4,
@@ -389,6 +476,7 @@ impl std::ops::Drop for AssociationHandle {
     fn drop(&mut self) {
         Worker::with_active_host(|host| {
             host.network_namespace_borrow().disassociate_interface(
+                self.socket_handle,
                 self.protocol,
                 self.local_addr,
                 self.remote_addr,
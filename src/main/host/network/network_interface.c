@@ -34,6 +34,12 @@ struct _NetworkInterface {
 
     /* (protocol,port)-to-socket bindings. Stores pointers to InetSocket objects. */
     GHashTable* boundSockets;
+    /* (protocol,port)-to-group bindings for sockets using SO_REUSEPORT. Stores pointers to
+     * ReusePortGroup objects. */
+    GHashTable* reusePortGroups;
+    /* Mixed into the flow hash when choosing a socket in a ReusePortGroup, so that the
+     * distribution is reproducible for a given simulation seed but varies between seeds. */
+    guint64 reusePortSeed;
 
     /* Transports wanting to send data out. */
     RrSocketQueue rrQueue;
@@ -45,6 +51,76 @@ struct _NetworkInterface {
     MAGIC_DECLARE;
 };
 
+/* A socket in a SO_REUSEPORT group. */
+typedef struct _ReusePortMember {
+    const InetSocket* socket;
+    /* Assigned in the order that sockets join the group, so that the choice of socket for an
+     * incoming packet doesn't depend on socket addresses in memory. */
+    guint64 id;
+} ReusePortMember;
+
+/* Sockets sharing an association using SO_REUSEPORT. */
+typedef struct _ReusePortGroup {
+    /* Stores pointers to ReusePortMember objects. */
+    GPtrArray* members;
+    guint64 nextMemberId;
+} ReusePortGroup;
+
+static void _reuseportmember_free(gpointer data) {
+    ReusePortMember* member = data;
+    inetsocket_drop(member->socket);
+    g_free(member);
+}
+
+static ReusePortGroup* _reuseportgroup_new() {
+    ReusePortGroup* group = g_new0(ReusePortGroup, 1);
+    group->members = g_ptr_array_new_with_free_func(_reuseportmember_free);
+    return group;
+}
+
+static void _reuseportgroup_free(gpointer data) {
+    ReusePortGroup* group = data;
+    g_ptr_array_free(group->members, TRUE);
+    g_free(group);
+}
+
+/* The splitmix64 finalizer. */
+static guint64 _reuseportgroup_mix(guint64 x) {
+    x ^= x >> 30;
+    x *= 0xbf58476d1ce4e5b9ULL;
+    x ^= x >> 27;
+    x *= 0x94d049bb133111ebULL;
+    x ^= x >> 31;
+    return x;
+}
+
+/* Choose the socket in the group that receives packets for the given 4-tuple. We use rendezvous
+ * hashing so that the same flow always maps to the same socket, and so that when a socket leaves
+ * the group only the flows that mapped to it are moved to other sockets. The address and ports
+ * must be in network byte order. */
+static const InetSocket* _reuseportgroup_select(ReusePortGroup* group, guint64 seed,
+                                                in_addr_t localIP, in_port_t localPort,
+                                                in_addr_t peerIP, in_port_t peerPort) {
+    guint64 local = ((guint64)ntohl(localIP) << 16) | ntohs(localPort);
+    guint64 peer = ((guint64)ntohl(peerIP) << 16) | ntohs(peerPort);
+    guint64 flowHash =
+        _reuseportgroup_mix(peer ^ _reuseportgroup_mix(local ^ _reuseportgroup_mix(seed)));
+
+    const ReusePortMember* best = NULL;
+    guint64 bestScore = 0;
+
+    for (guint i = 0; i < group->members->len; i++) {
+        const ReusePortMember* member = g_ptr_array_index(group->members, i);
+        guint64 score = _reuseportgroup_mix(flowHash ^ _reuseportgroup_mix(member->id + 1));
+        if (best == NULL || score > bestScore) {
+            best = member;
+            bestScore = score;
+        }
+    }
+
+    return best != NULL ? best->socket : NULL;
+}
+
 /* The address and ports must be in network byte order. */
 static gchar* _networkinterface_getAssociationKey(NetworkInterface* interface,
         ProtocolType type, in_port_t port, in_addr_t peerAddr, in_port_t peerPort) {
@@ -68,7 +144,20 @@ gboolean networkinterface_isAssociated(NetworkInterface* interface, ProtocolType
     gboolean isFound = FALSE;
 
     gchar* key = _networkinterface_getAssociationKey(interface, type, port, peerAddr, peerPort);
-    isFound = g_hash_table_contains(interface->boundSockets, key);
+    isFound = g_hash_table_contains(interface->boundSockets, key) ||
+              g_hash_table_contains(interface->reusePortGroups, key);
+    g_free(key);
+
+    return isFound;
+}
+
+/* The address and ports must be in network byte order. */
+gboolean networkinterface_isReusePortGroup(NetworkInterface* interface, ProtocolType type,
+                                           in_port_t port, in_addr_t peerAddr, in_port_t peerPort) {
+    MAGIC_ASSERT(interface);
+
+    gchar* key = _networkinterface_getAssociationKey(interface, type, port, peerAddr, peerPort);
+    gboolean isFound = g_hash_table_contains(interface->reusePortGroups, key);
     g_free(key);
 
     return isFound;
@@ -76,7 +165,7 @@ gboolean networkinterface_isAssociated(NetworkInterface* interface, ProtocolType
 
 void networkinterface_associate(NetworkInterface* interface, const InetSocket* socket,
                                 ProtocolType type, in_port_t port, in_addr_t peerIP,
-                                in_port_t peerPort) {
+                                in_port_t peerPort, bool reusePort) {
     MAGIC_ASSERT(interface);
 
     gchar* key = _networkinterface_getAssociationKey(interface, type, port, peerIP, peerPort);
@@ -87,6 +176,28 @@ void networkinterface_associate(NetworkInterface* interface, const InetSocket* s
     /* need to store our own reference to the socket object */
     const InetSocket* newSocketRef = inetsocket_cloneRef(socket);
 
+    if (reusePort) {
+        ReusePortGroup* group = g_hash_table_lookup(interface->reusePortGroups, key);
+        if (group == NULL) {
+            group = _reuseportgroup_new();
+            /* the table owns its own copy of the key */
+            g_hash_table_insert(interface->reusePortGroups, g_strdup(key), group);
+        }
+
+        ReusePortMember* member = g_new0(ReusePortMember, 1);
+        member->socket = newSocketRef;
+        member->id = group->nextMemberId++;
+        g_ptr_array_add(group->members, member);
+
+        trace("associated socket key %s with reuseport group (size %u)", key,
+              group->members->len);
+        g_free(key);
+        return;
+    }
+
+    /* make sure there is no collision with a reuseport group */
+    utility_debugAssert(!g_hash_table_contains(interface->reusePortGroups, key));
+
     /* insert to our storage, key is now owned by table */
     bool key_did_not_exist =
         g_hash_table_replace(interface->boundSockets, key, (void*)newSocketRef);
@@ -97,7 +208,7 @@ void networkinterface_associate(NetworkInterface* interface, const InetSocket* s
 }
 
 void networkinterface_disassociate(NetworkInterface* interface, ProtocolType type, in_port_t port,
-                                   in_addr_t peerIP, in_port_t peerPort) {
+                                   in_addr_t peerIP, in_port_t peerPort, uintptr_t socketHandle) {
     MAGIC_ASSERT(interface);
 
     gchar* key = _networkinterface_getAssociationKey(interface, type, port, peerIP, peerPort);
@@ -112,10 +223,51 @@ void networkinterface_disassociate(NetworkInterface* interface, ProtocolType typ
      * here. */
     g_hash_table_remove(interface->boundSockets, key);
 
+    /* the socket may instead be one of many sockets in a reuseport group, and removing it means
+     * that future flows will be balanced across the remaining sockets */
+    ReusePortGroup* group = g_hash_table_lookup(interface->reusePortGroups, key);
+    if (group != NULL) {
+        for (guint i = 0; i < group->members->len; i++) {
+            const ReusePortMember* member = g_ptr_array_index(group->members, i);
+            if (inetsocket_getCanonicalHandle(member->socket) == socketHandle) {
+                /* keep the order of the remaining members */
+                g_ptr_array_remove_index(group->members, i);
+                break;
+            }
+        }
+
+        if (group->members->len == 0) {
+            g_hash_table_remove(interface->reusePortGroups, key);
+        }
+    }
+
     trace("disassociated socket key %s", key);
     g_free(key);
 }
 
+void networkinterface_forEachSocket(NetworkInterface* interface,
+                                    void (*func)(const InetSocket* socket, void* userData),
+                                    void* userData) {
+    MAGIC_ASSERT(interface);
+
+    GHashTableIter iter;
+    gpointer value;
+
+    g_hash_table_iter_init(&iter, interface->boundSockets);
+    while (g_hash_table_iter_next(&iter, NULL, &value)) {
+        func((const InetSocket*)value, userData);
+    }
+
+    g_hash_table_iter_init(&iter, interface->reusePortGroups);
+    while (g_hash_table_iter_next(&iter, NULL, &value)) {
+        ReusePortGroup* group = value;
+        for (guint i = 0; i < group->members->len; i++) {
+            const ReusePortMember* member = g_ptr_array_index(group->members, i);
+            func(member->socket, userData);
+        }
+    }
+}
+
 static void _networkinterface_capturePacket(NetworkInterface* interface, Packet* packet) {
     utility_debugAssert(interface->pcap != NULL);
 
@@ -133,11 +285,26 @@ static void _networkinterface_capturePacket(NetworkInterface* interface, Packet*
     }
 }
 
-static const InetSocket* _boundsockets_lookup(GHashTable* table, gchar* key) {
-    return g_hash_table_lookup(table, key);
+/* Look up the socket that should receive a packet from the peer. The address and ports must be in
+ * network byte order. */
+static const InetSocket* _networkinterface_lookup(NetworkInterface* interface, gchar* key,
+                                                  in_port_t bindPort, in_addr_t peerIP,
+                                                  in_port_t peerPort) {
+    const InetSocket* socket = g_hash_table_lookup(interface->boundSockets, key);
+
+    if (socket == NULL) {
+        ReusePortGroup* group = g_hash_table_lookup(interface->reusePortGroups, key);
+        if (group != NULL) {
+            socket = _reuseportgroup_select(group, interface->reusePortSeed,
+                                            address_toNetworkIP(interface->address), bindPort,
+                                            peerIP, peerPort);
+        }
+    }
+
+    return socket;
 }
 
-void networkinterface_push(NetworkInterface* interface, Packet* packet, CEmulatedTime recvTime) {
+bool networkinterface_push(NetworkInterface* interface, Packet* packet, CEmulatedTime recvTime) {
     MAGIC_ASSERT(interface);
 
     const Host* host = worker_getCurrentHost();
@@ -158,14 +325,14 @@ void networkinterface_push(NetworkInterface* interface, Packet* packet, CEmulate
     gchar* key = _networkinterface_getAssociationKey(interface, ptype, bindPort, peerIP, peerPort);
     trace("looking for socket associated with specific key %s", key);
 
-    const InetSocket* socket = _boundsockets_lookup(interface->boundSockets, key);
+    const InetSocket* socket = _networkinterface_lookup(interface, key, bindPort, peerIP, peerPort);
     g_free(key);
 
     if (socket == NULL) {
         /* then check for a socket with a wildcard association */
         key = _networkinterface_getAssociationKey(interface, ptype, bindPort, 0, 0);
         trace("looking for socket associated with general key %s", key);
-        socket = _boundsockets_lookup(interface->boundSockets, key);
+        socket = _networkinterface_lookup(interface, key, bindPort, peerIP, peerPort);
         g_free(key);
     }
 
@@ -196,9 +363,13 @@ void networkinterface_push(NetworkInterface* interface, Packet* packet, CEmulate
         tracker_addInputBytes(tracker, packet, &compatSocket);
     }
 
+    bool delivered = socket != NULL;
+
     if (socket != NULL) {
         inetsocket_drop(socket);
     }
+
+    return delivered;
 }
 
 /* round robin queuing discipline ($ man tc)*/
@@ -379,10 +550,12 @@ void networkinterface_removeAllSockets(NetworkInterface* interface) {
     fifosocketqueue_init(&interface->fifoQueue);
 
     g_hash_table_remove_all(interface->boundSockets);
+    g_hash_table_remove_all(interface->reusePortGroups);
 }
 
 NetworkInterface* networkinterface_new(Address* address, const char* name, const gchar* pcapDir,
-                                       guint32 pcapCaptureSize, QDiscMode qdisc) {
+                                       guint32 pcapCaptureSize, QDiscMode qdisc,
+                                       guint64 reusePortSeed) {
     NetworkInterface* interface = g_new0(NetworkInterface, 1);
     MAGIC_INIT(interface);
 
@@ -392,6 +565,9 @@ NetworkInterface* networkinterface_new(Address* address, const char* name, const
     /* incoming packets get passed along to sockets */
     interface->boundSockets =
         g_hash_table_new_full(g_str_hash, g_str_equal, g_free, inetsocket_dropVoid);
+    interface->reusePortGroups =
+        g_hash_table_new_full(g_str_hash, g_str_equal, g_free, _reuseportgroup_free);
+    interface->reusePortSeed = reusePortSeed;
 
     /* sockets tell us when they want to start sending */
     rrsocketqueue_init(&interface->rrQueue);
@@ -431,6 +607,7 @@ void networkinterface_free(NetworkInterface* interface) {
     fifosocketqueue_destroy(&interface->fifoQueue, inetsocket_drop);
 
     g_hash_table_destroy(interface->boundSockets);
+    g_hash_table_destroy(interface->reusePortGroups);
 
     address_unref(interface->address);
 
@@ -9,6 +9,8 @@
 
 #include <glib.h>
 #include <netinet/in.h>
+#include <stdbool.h>
+#include <stdint.h>
 
 typedef struct _NetworkInterface NetworkInterface;
 
@@ -19,24 +21,43 @@ typedef struct _NetworkInterface NetworkInterface;
 #include "main/routing/address.h"
 #include "main/routing/packet.minimal.h"
 
+/* The reusePortSeed determines how packets are distributed across groups of SO_REUSEPORT
+ * sockets. */
 NetworkInterface* networkinterface_new(Address* address, const char* name, const gchar* pcapDir,
-                                       guint32 pcapCaptureSize, QDiscMode qdisc);
+                                       guint32 pcapCaptureSize, QDiscMode qdisc,
+                                       guint64 reusePortSeed);
 void networkinterface_free(NetworkInterface* interface);
 
 /* The address and ports must be in network byte order. */
 gboolean networkinterface_isAssociated(NetworkInterface* interface, ProtocolType type,
                                        in_port_t port, in_addr_t peerAddr, in_port_t peerPort);
 
+/* The address and ports must be in network byte order. Returns true if the association exists
+ * and is a SO_REUSEPORT group that other SO_REUSEPORT sockets can join. */
+gboolean networkinterface_isReusePortGroup(NetworkInterface* interface, ProtocolType type,
+                                           in_port_t port, in_addr_t peerAddr, in_port_t peerPort);
+
+/* If `reusePort` is true, the socket joins the SO_REUSEPORT group for the association, creating it
+ * if needed. */
 void networkinterface_associate(NetworkInterface* interface, const InetSocket* socket,
                                 ProtocolType type, in_port_t port, in_addr_t peerIP,
-                                in_port_t peerPort);
+                                in_port_t peerPort, bool reusePort);
+/* `socketHandle` is the canonical handle of the socket being disassociated, and is used to remove
+ * the socket from a SO_REUSEPORT group. */
 void networkinterface_disassociate(NetworkInterface* interface, ProtocolType type, in_port_t port,
-                                   in_addr_t peerIP, in_port_t peerPort);
+                                   in_addr_t peerIP, in_port_t peerPort, uintptr_t socketHandle);
 
 void networkinterface_wantsSend(NetworkInterface* interface, const InetSocket* socket);
 
+/* Call `func` for each socket associated with the interface, including each member of a
+ * SO_REUSEPORT group. The order of the sockets is unspecified. */
+void networkinterface_forEachSocket(NetworkInterface* interface,
+                                    void (*func)(const InetSocket* socket, void* userData),
+                                    void* userData);
+
 Packet* networkinterface_pop(NetworkInterface* interface);
-void networkinterface_push(NetworkInterface* interface, Packet* packet, CEmulatedTime recvTime);
+/* Returns true if the packet was delivered to a socket, or false if it was dropped. */
+bool networkinterface_push(NetworkInterface* interface, Packet* packet, CEmulatedTime recvTime);
 
 /* Disassociate all bound sockets and remove sockets from the sending queue. */
 void networkinterface_removeAllSockets(NetworkInterface* interface);
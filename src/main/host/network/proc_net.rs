@@ -0,0 +1,206 @@
+//! Emulation of linux's `/proc/net/tcp` and `/proc/net/udp` files, which list a host's sockets, and
+//! of the `/proc/net/dev` file, which lists the packet counters of a host's network interfaces.
+
+use std::fmt::Write;
+use std::net::SocketAddrV4;
+
+use crate::cshadow as c;
+use crate::host::descriptor::socket::inet::ProcNetEntry;
+use crate::host::network::interface::InterfaceStats;
+
+const TCP_HEADER: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode";
+const UDP_HEADER: &str = "   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops";
+
+const DEV_HEADER: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+";
+
+/// Linux pads each line of `/proc/net/tcp` to this width, not including the newline.
+const TCP_LINE_WIDTH: usize = 149;
+/// Linux pads each line of `/proc/net/udp` to this width, not including the newline.
+const UDP_LINE_WIDTH: usize = 127;
+
+/// Format an address like linux. The IP address is the hex value of its network-order bytes read
+/// as a native-endian integer, and the port is the hex value of the host-order port.
+fn format_addr(addr: SocketAddrV4) -> String {
+    let ip = u32::from_ne_bytes(addr.ip().octets());
+    format!("{ip:08X}:{:04X}", addr.port())
+}
+
+/// Build the contents of the `/proc/net/tcp` (for [`c::_ProtocolType_PTCP`]) or `/proc/net/udp`
+/// (for [`c::_ProtocolType_PUDP`]) file, listing `entries` in the given order. Shadow doesn't
+/// assign inodes to sockets or model the socket timers, so these fields are always 0.
+pub fn table(protocol: c::ProtocolType, entries: &[ProcNetEntry], uid: u32) -> String {
+    let (header, width) = match protocol {
+        c::_ProtocolType_PTCP => (TCP_HEADER, TCP_LINE_WIDTH),
+        c::_ProtocolType_PUDP => (UDP_HEADER, UDP_LINE_WIDTH),
+        _ => panic!("Unexpected protocol type {protocol}"),
+    };
+
+    let mut table = String::new();
+    writeln!(table, "{header:width$}").unwrap();
+
+    for (i, entry) in entries.iter().enumerate() {
+        let local = format_addr(entry.local_addr);
+        let peer = format_addr(entry.peer_addr);
+        let state = entry.state;
+        let tx_queue = entry.tx_queue;
+        let rx_queue = entry.rx_queue;
+        let timeout = 0;
+        let inode = 0;
+
+        let line = if protocol == c::_ProtocolType_PTCP {
+            format!(
+                "{i:4}: {local} {peer} {state:02X} {tx_queue:08X}:{rx_queue:08X} 00:00000000 \
+                 00000000 {uid:5} {timeout:8} {inode} 1 0000000000000000 0 0 0 0 0"
+            )
+        } else {
+            format!(
+                "{i:5}: {local} {peer} {state:02X} {tx_queue:08X}:{rx_queue:08X} 00:00000000 \
+                 00000000 {uid:5} {timeout:8} {inode} 2 0000000000000000 0"
+            )
+        };
+
+        writeln!(table, "{line:width$}").unwrap();
+    }
+
+    table
+}
+
+/// Build the contents of the `/proc/net/dev` file, listing the interfaces with the given names in
+/// the given order. Shadow doesn't model errors or dropped outgoing packets, so these fields are
+/// always 0.
+pub fn dev_table<'a>(interfaces: impl IntoIterator<Item = (&'a str, InterfaceStats)>) -> String {
+    let mut table = String::from(DEV_HEADER);
+
+    for (name, stats) in interfaces {
+        let InterfaceStats {
+            rx_bytes,
+            rx_packets,
+            rx_dropped,
+            tx_bytes,
+            tx_packets,
+        } = stats;
+
+        // fields that shadow doesn't model
+        let zero = 0;
+
+        writeln!(
+            table,
+            "{name:>6}: {rx_bytes:7} {rx_packets:7} {zero:4} {rx_dropped:4} {zero:4} {zero:5} \
+             {zero:10} {zero:9} {tx_bytes:8} {tx_packets:7} {zero:4} {zero:4} {zero:4} {zero:5} \
+             {zero:7} {zero:10}"
+        )
+        .unwrap();
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn test_tcp_table() {
+        let entries = [ProcNetEntry {
+            local_addr: SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 8080),
+            peer_addr: SocketAddrV4::new(Ipv4Addr::new(11, 0, 0, 2), 443),
+            state: ProcNetEntry::TCP_ESTABLISHED,
+            tx_queue: 10,
+            rx_queue: 300,
+        }];
+
+        let table = table(c::_ProtocolType_PTCP, &entries, 1000);
+        let lines: Vec<_> = table.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|x| x.len() == TCP_LINE_WIDTH));
+        assert_eq!(lines[0].trim_end(), TCP_HEADER);
+
+        let fields: Vec<_> = lines[1].split_whitespace().collect();
+        assert_eq!(
+            fields[..10],
+            [
+                "0:",
+                "0100007F:1F90",
+                "0200000B:01BB",
+                "01",
+                "0000000A:0000012C",
+                "00:00000000",
+                "00000000",
+                "1000",
+                "0",
+                "0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_udp_table() {
+        let entries = [ProcNetEntry {
+            local_addr: SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 53),
+            peer_addr: SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0),
+            state: ProcNetEntry::TCP_CLOSE,
+            tx_queue: 0,
+            rx_queue: 0,
+        }];
+
+        let table = table(c::_ProtocolType_PUDP, &entries, 0);
+        let lines: Vec<_> = table.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|x| x.len() == UDP_LINE_WIDTH));
+        assert_eq!(lines[0].trim_end(), UDP_HEADER);
+        assert!(lines[1].starts_with("    0: 00000000:0035 00000000:0000 07 00000000:00000000 "));
+    }
+
+    #[test]
+    fn test_dev_table() {
+        let stats = InterfaceStats {
+            rx_bytes: 1500,
+            rx_packets: 2,
+            rx_dropped: 1,
+            tx_bytes: 123456789,
+            tx_packets: 3,
+        };
+
+        let table = dev_table([("lo", InterfaceStats::default()), ("eth0", stats)]);
+        let lines: Vec<_> = table.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Inter-|"));
+        assert_eq!(
+            lines[2],
+            "    lo:       0       0    0    0    0     0          0         0        0       0    \
+             0    0    0     0       0          0"
+        );
+
+        let fields: Vec<_> = lines[3].split_whitespace().collect();
+        assert_eq!(
+            fields,
+            [
+                "eth0:",
+                "1500",
+                "2",
+                "0",
+                "1",
+                "0",
+                "0",
+                "0",
+                "0",
+                "123456789",
+                "3",
+                "0",
+                "0",
+                "0",
+                "0",
+                "0",
+                "0",
+            ]
+        );
+    }
+}
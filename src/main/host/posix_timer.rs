@@ -0,0 +1,151 @@
+//! Per-process POSIX interval timers, as created by `timer_create(2)`.
+
+use std::collections::BTreeMap;
+
+use linux_api::errno::Errno;
+use linux_api::signal::{siginfo_t, sigval, Signal};
+use linux_api::time::ClockId;
+use log::debug;
+
+use super::host::Host;
+use super::process::ProcessId;
+use super::thread::ThreadId;
+use super::timer::Timer;
+
+/// How the process is notified when a [`PosixTimer`] expires.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PosixTimerNotify {
+    /// Nothing is delivered; the process can still poll the timer with `timer_gettime(2)`.
+    None,
+    /// The signal is sent to the process, with `si_value` set to `value`. If `thread` is set
+    /// (`SIGEV_THREAD_ID`), the signal is sent only to that thread of the process.
+    Signal {
+        signal: Signal,
+        // `sigval` holds a raw pointer and isn't `Send`, so we store its bits instead.
+        value: usize,
+        thread: Option<ThreadId>,
+    },
+}
+
+/// A timer created by `timer_create(2)`.
+pub struct PosixTimer {
+    clock_id: ClockId,
+    timer: Timer,
+}
+
+impl PosixTimer {
+    pub fn clock_id(&self) -> ClockId {
+        self.clock_id
+    }
+
+    pub fn timer(&self) -> &Timer {
+        &self.timer
+    }
+
+    pub fn timer_mut(&mut self) -> &mut Timer {
+        &mut self.timer
+    }
+}
+
+/// The POSIX timers of a single process, keyed by the timer id returned to the process.
+///
+/// Unlike timerfds, these timers aren't descriptors and so aren't shared with other processes.
+/// They aren't inherited across `fork(2)` and are deleted on `execve(2)`.
+pub struct PosixTimers {
+    timers: BTreeMap<i32, PosixTimer>,
+    next_id: i32,
+}
+
+impl PosixTimers {
+    pub fn new() -> Self {
+        Self {
+            timers: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Create a new disarmed timer for process `pid`, returning its id. If `notify` is `None`, the
+    /// timer sends `SIGALRM` with the timer id as the signal value, as in Linux when
+    /// `timer_create(2)` is given a NULL `sevp`.
+    pub fn create(
+        &mut self,
+        pid: ProcessId,
+        clock_id: ClockId,
+        notify: Option<PosixTimerNotify>,
+    ) -> Result<i32, Errno> {
+        // Like Linux, allocate ids sequentially rather than reusing the lowest free id.
+        let start_id = self.next_id;
+        let id = loop {
+            let id = self.next_id;
+            self.next_id = self.next_id.checked_add(1).unwrap_or(0);
+            if !self.timers.contains_key(&id) {
+                break id;
+            }
+            if self.next_id == start_id {
+                return Err(Errno::EAGAIN);
+            }
+        };
+
+        let notify = notify.unwrap_or(PosixTimerNotify::Signal {
+            signal: Signal::SIGALRM,
+            // `sival_int` overlaps the low bytes of `sival_ptr`.
+            value: id as u32 as usize,
+            thread: None,
+        });
+        let timer = Timer::new(move |host| posix_timer_expiration(host, pid, id, notify));
+        self.timers.insert(id, PosixTimer { clock_id, timer });
+
+        Ok(id)
+    }
+
+    pub fn get(&self, id: i32) -> Option<&PosixTimer> {
+        self.timers.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: i32) -> Option<&mut PosixTimer> {
+        self.timers.get_mut(&id)
+    }
+
+    /// Delete the timer, returning it if it existed. Dropping the timer disarms it.
+    pub fn delete(&mut self, id: i32) -> Option<PosixTimer> {
+        self.timers.remove(&id)
+    }
+
+    /// Delete all timers.
+    pub fn clear(&mut self) {
+        self.timers.clear();
+    }
+}
+
+impl Default for PosixTimers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn posix_timer_expiration(host: &Host, pid: ProcessId, id: i32, notify: PosixTimerNotify) {
+    let PosixTimerNotify::Signal {
+        signal,
+        value,
+        thread,
+    } = notify
+    else {
+        return;
+    };
+
+    let Some(process) = host.process_borrow(pid) else {
+        debug!("Process {:?} no longer exists", pid);
+        return;
+    };
+    let process = process.borrow(host.root());
+
+    let sigval = sigval {
+        sival_ptr: value as *mut std::ffi::c_void,
+    };
+    // We don't track overruns; see `timer_getoverrun(2)`.
+    let siginfo = siginfo_t::new_for_timer(signal, id, 0, sigval);
+    match thread {
+        Some(tid) => process.signal_thread(host, None, tid, &siginfo),
+        None => process.signal(host, None, &siginfo),
+    }
+}
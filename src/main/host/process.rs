@@ -22,6 +22,8 @@ use linux_api::signal::{
     SignalFromI32Error,
 };
 use log::{debug, trace, warn};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 use rustix::process::{WaitOptions, WaitStatus};
 use shadow_shim_helper_rs::explicit_drop::{ExplicitDrop, ExplicitDropper};
 use shadow_shim_helper_rs::rootedcell::rc::RootedRc;
@@ -38,6 +40,7 @@ use super::descriptor::listener::StateEventSource;
 use super::descriptor::{FileSignals, FileState};
 use super::host::Host;
 use super::memory_manager::{MemoryManager, ProcessMemoryRef, ProcessMemoryRefMut};
+use super::posix_timer::PosixTimers;
 use super::syscall::formatter::StraceFmtMode;
 use super::syscall::types::ForeignArrayPtr;
 use super::thread::{Thread, ThreadId};
@@ -50,7 +53,7 @@ use crate::host::context::ProcessContext;
 use crate::host::descriptor::Descriptor;
 use crate::host::managed_thread::ManagedThread;
 use crate::host::syscall::formatter::FmtOptions;
-use crate::utility::callback_queue::CallbackQueue;
+use crate::utility::callback_queue::{CallbackQueue, EventSource, Handle};
 #[cfg(feature = "perf_timers")]
 use crate::utility::perf_timer::PerfTimer;
 use crate::utility::{self, debug_assert_cloexec};
@@ -130,6 +133,65 @@ pub enum ExitStatus {
     StoppedByShadow,
 }
 
+/// Resource usage of a thread, process, or group of processes that Shadow is able to model.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ResourceUsage {
+    /// Simulated CPU time spent running in user mode.
+    pub user_time: SimulationTime,
+    /// Simulated CPU time spent running in kernel mode (handling syscalls).
+    pub system_time: SimulationTime,
+    /// Peak resident set size in KiB.
+    pub max_rss_kib: u64,
+}
+
+impl ResourceUsage {
+    pub const ZERO: Self = Self {
+        user_time: SimulationTime::ZERO,
+        system_time: SimulationTime::ZERO,
+        max_rss_kib: 0,
+    };
+
+    /// Combine with the usage of another thread or process. CPU times are summed, and the peak
+    /// RSS is the largest of the two (as Linux does for `RUSAGE_CHILDREN`).
+    pub fn add(&mut self, other: &Self) {
+        self.user_time += other.user_time;
+        self.system_time += other.system_time;
+        self.max_rss_kib = std::cmp::max(self.max_rss_kib, other.max_rss_kib);
+    }
+
+    /// The total simulated CPU time, in both user and kernel mode.
+    pub fn cpu_time(&self) -> SimulationTime {
+        self.user_time + self.system_time
+    }
+
+    /// The usage as a `struct rusage`. Fields that Shadow doesn't model are zeroed.
+    pub fn to_rusage(&self) -> linux_api::resource::rusage {
+        let timeval = |t: SimulationTime| linux_api::time::kernel_old_timeval {
+            tv_sec: t.as_secs().try_into().unwrap(),
+            tv_usec: t.subsec_micros().into(),
+        };
+
+        linux_api::resource::rusage {
+            ru_utime: timeval(self.user_time),
+            ru_stime: timeval(self.system_time),
+            ru_maxrss: self.max_rss_kib.try_into().unwrap(),
+            ru_ixrss: 0,
+            ru_idrss: 0,
+            ru_isrss: 0,
+            ru_minflt: 0,
+            ru_majflt: 0,
+            ru_nswap: 0,
+            ru_inblock: 0,
+            ru_oublock: 0,
+            ru_msgsnd: 0,
+            ru_msgrcv: 0,
+            ru_nsignals: 0,
+            ru_nvcsw: 0,
+            ru_nivcsw: 0,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct StraceLogging {
     file: RootedRefCell<std::fs::File>,
@@ -164,6 +226,10 @@ struct Common {
     // This must remain in sync with the actual working dir of the native process.
     // See https://github.com/shadow/shadow/issues/2960
     working_dir: CString,
+
+    // Combined resource usage of the children that this process has reaped, including the usage
+    // of the children that they reaped, as returned e.g. by `getrusage(RUSAGE_CHILDREN)`.
+    reaped_children_usage: Cell<ResourceUsage>,
 }
 
 impl Common {
@@ -259,6 +325,13 @@ pub struct RunnableProcess {
 
     itimer_real: RefCell<Timer>,
 
+    // Timers created with `timer_create(2)`.
+    posix_timers: RefCell<PosixTimers>,
+
+    // Source of random bytes for `getrandom` and `/dev/urandom`. Not shared with forked
+    // processes.
+    random: RefCell<ChaCha20Rng>,
+
     // The `RootedRc` lets us hold a reference to a thread without holding a
     // reference to the thread list. e.g. this lets us implement the `clone`
     // syscall, which adds a thread to the list while we have a reference to the
@@ -285,6 +358,13 @@ pub struct RunnableProcess {
     // Listeners for child-events.
     // e.g. these listeners are notified when a child of this process exits.
     child_process_event_listeners: RefCell<StateEventSource>,
+
+    // Listeners for this process' exit.
+    // e.g. pidfds referring to this process are notified when it exits.
+    exit_listeners: RefCell<EventSource<()>>,
+
+    // Combined resource usage of the process's threads that have exited.
+    exited_threads_usage: Cell<ResourceUsage>,
 }
 
 impl RunnableProcess {
@@ -314,6 +394,18 @@ impl RunnableProcess {
         )
     }
 
+    /// Add the resource usage of an exited thread to the process's usage.
+    fn add_exited_thread_usage(&self, host: &Host, thread: &Thread) {
+        // The shmem lock isn't held when the remaining processes are stopped at the end of the
+        // simulation, but at that point the usage can no longer be observed anyway.
+        let Some(host_shmem) = host.shim_shmem_lock_borrow() else {
+            return;
+        };
+        let mut usage = self.exited_threads_usage.get();
+        usage.add(&thread.resource_usage(&host_shmem));
+        self.exited_threads_usage.set(usage);
+    }
+
     /// Call after a thread has exited. Removes the thread and does corresponding cleanup and notifications.
     fn reap_thread(&self, host: &Host, threadrc: RootedRc<RootedRefCell<Thread>>) {
         let threadrc = ExplicitDropper::new(threadrc, |t| {
@@ -323,6 +415,15 @@ impl RunnableProcess {
 
         assert!(!thread.is_running());
 
+        self.add_exited_thread_usage(host, &thread);
+
+        // Like the kernel, release the thread's robust futexes before clearing `clear_child_tid`.
+        // If no threads are left, the native process has exited and its memory can't be read, but
+        // `exit` and `exit_group` already released the futexes of the threads that exited with it.
+        if self.threads.borrow().len() > 0 {
+            self.release_robust_futexes(host, &thread);
+        }
+
         // If the `clear_child_tid` attribute on the thread is set, and there are
         // any other threads left alive in the process, perform a futex wake on
         // that address. This mechanism is typically used in `pthread_join` etc.
@@ -346,6 +447,135 @@ impl RunnableProcess {
         }
     }
 
+    /// Release the robust futexes of `thread`, which is about to exit. Unlike when the thread is
+    /// reaped, the process's memory can still be read even if the process is exiting too.
+    fn release_exiting_thread_robust_futexes(&self, host: &Host, thread: &Thread) {
+        self.release_robust_futexes(host, thread);
+        // don't release them again when the thread is reaped
+        thread.set_robust_list(ForeignPtr::null());
+    }
+
+    /// Walk the robust futex list of an exited thread, as the kernel does. Each futex that the
+    /// thread still holds is marked with `FUTEX_OWNER_DIED` and one of its waiters is woken, so
+    /// that the next owner learns that the previous owner died. See `set_robust_list(2)`.
+    fn release_robust_futexes(&self, host: &Host, thread: &Thread) {
+        // The kernel's limit on the number of entries, in case the list is circular.
+        const ROBUST_LIST_LIMIT: usize = 2048;
+
+        let head_ptr = thread.get_robust_list();
+        if head_ptr.is_null() {
+            return;
+        }
+
+        // like the kernel, stop at the first entry that can't be read
+        let Ok(head) = self.memory_manager.borrow().read(head_ptr) else {
+            return;
+        };
+
+        // Entries point at the `list` field at the start of a userspace struct, and the futex
+        // word is at `futex_offset` from there. The lowest bit of an entry marks a PI futex.
+        let futex_addr = |entry: usize| entry.wrapping_add_signed(head.futex_offset as isize);
+        let split_entry = |entry: usize| (entry & !1, entry & 1 != 0);
+
+        let (pending, pending_pi) = split_entry(head.list_op_pending as usize);
+        let (mut entry, mut pi) = split_entry(head.list.next as usize);
+
+        // the list is circular and ends at the head's `list` field
+        let mut remaining = ROBUST_LIST_LIMIT;
+        while entry != usize::from(head_ptr) && remaining > 0 {
+            // read the next entry first, since releasing the futex may let another thread
+            // remove this entry
+            let next_ptr = ForeignPtr::<()>::from(entry).cast::<usize>();
+            let Ok(next) = self.memory_manager.borrow().read(next_ptr) else {
+                return;
+            };
+
+            // the pending entry is handled last
+            if entry != pending {
+                self.release_robust_futex(host, thread, futex_addr(entry), pi, false);
+            }
+
+            (entry, pi) = split_entry(next);
+            remaining -= 1;
+        }
+
+        if pending != 0 {
+            self.release_robust_futex(host, thread, futex_addr(pending), pending_pi, true);
+        }
+    }
+
+    /// Release a robust futex of an exited thread, if the thread still holds it. `pending_op` is
+    /// whether the thread was in the middle of locking or unlocking the futex.
+    fn release_robust_futex(
+        &self,
+        host: &Host,
+        thread: &Thread,
+        addr: usize,
+        pi: bool,
+        pending_op: bool,
+    ) {
+        use linux_api::futex::{FUTEX_OWNER_DIED, FUTEX_TID_MASK, FUTEX_WAITERS};
+
+        // futex words must be aligned
+        if addr % std::mem::align_of::<u32>() != 0 {
+            return;
+        }
+
+        let ptr = ForeignPtr::<()>::from(addr).cast::<u32>();
+        let Ok(val) = self.memory_manager.borrow().read(ptr) else {
+            return;
+        };
+
+        let futexes = host.futextable_borrow();
+        let futex = futexes.get(self.common.physical_address(ptr.cast::<()>()));
+
+        // The thread may have been woken to take the futex, but died before taking it. Wake
+        // another waiter instead.
+        if pending_op && !pi && val == 0 {
+            if let Some(futex) = futex {
+                futex.wake(1);
+            }
+            return;
+        }
+
+        if val & FUTEX_TID_MASK != libc::pid_t::from(thread.id()) as u32 {
+            return;
+        }
+
+        // Like FUTEX_UNLOCK_PI, hand a PI futex directly to the longest waiting thread.
+        let new_owner = if pi {
+            futex.and_then(|x| x.pop_pi_waiter())
+        } else {
+            None
+        };
+
+        let new_val = match new_owner {
+            Some(tid) => tid as u32 | FUTEX_WAITERS | FUTEX_OWNER_DIED,
+            None => (val & FUTEX_WAITERS) | FUTEX_OWNER_DIED,
+        };
+
+        if self
+            .memory_manager
+            .borrow_mut()
+            .write(ptr, &new_val)
+            .is_err()
+        {
+            return;
+        }
+
+        let Some(futex) = futex else {
+            return;
+        };
+
+        if new_owner.is_some() {
+            // We can't wake a specific waiter, so wake all of them. The waiters that weren't given
+            // the lock will block again.
+            futex.wake(libc::c_uint::MAX);
+        } else if !pi && val & FUTEX_WAITERS != 0 {
+            futex.wake(1);
+        }
+    }
+
     /// This cleans up memory references left over from legacy C code; usually
     /// a syscall handler.
     ///
@@ -385,6 +615,12 @@ impl RunnableProcess {
         self.memory_manager.borrow_mut()
     }
 
+    /// The process' pseudo-random number generator, which is deterministic given the
+    /// simulation seed.
+    pub fn random_mut(&self) -> impl DerefMut<Target = ChaCha20Rng> + '_ {
+        self.random.borrow_mut()
+    }
+
     pub fn strace_logging_options(&self) -> Option<FmtOptions> {
         self.strace_logging.as_ref().map(|x| x.options)
     }
@@ -568,6 +804,84 @@ impl RunnableProcess {
         self.interrupt_with_signal(host, signal);
     }
 
+    /// Send the signal described in `siginfo` to the thread `tid` of this process. Unlike
+    /// [`Self::signal`], the signal is left pending on the thread itself, so it can't be handled
+    /// by any other thread in the process. `current_thread` has the same meaning as in
+    /// [`Self::signal`].
+    ///
+    /// Does nothing if the thread no longer exists.
+    pub fn signal_thread(
+        &self,
+        host: &Host,
+        current_thread: Option<&Thread>,
+        tid: ThreadId,
+        siginfo_t: &siginfo_t,
+    ) {
+        let signal = match siginfo_t.signal() {
+            Ok(s) => s,
+            Err(SignalFromI32Error(0)) => return,
+            Err(SignalFromI32Error(n)) => panic!("Bad signo {n}"),
+        };
+
+        let threads = self.threads.borrow();
+        let Some(thread) = threads.get(&tid) else {
+            debug!("Thread {tid} no longer exists");
+            return;
+        };
+        let thread = thread.borrow(host.root());
+
+        // Scope for the shmem lock, since `wakeup_for_signal` takes its own.
+        {
+            let host_shmem = host.shim_shmem_lock_borrow().unwrap();
+            let process_shmem_protected = self
+                .shim_shared_mem_block
+                .protected
+                .borrow(&host_shmem.root);
+            // SAFETY: We don't try to call any of the function pointers.
+            let action = unsafe { process_shmem_protected.signal_action(signal) };
+            match unsafe { action.handler() } {
+                linux_api::signal::SignalHandler::Handler(_) => (),
+                linux_api::signal::SignalHandler::Action(_) => (),
+                linux_api::signal::SignalHandler::SigIgn => return,
+                linux_api::signal::SignalHandler::SigDfl => {
+                    if defaultaction(signal) == LinuxDefaultAction::IGN {
+                        return;
+                    }
+                }
+            }
+
+            let thread_shmem = thread.shmem();
+            let mut thread_protected = thread_shmem.protected.borrow_mut(&host_shmem.root);
+            if thread_protected.pending_signals.has(signal) {
+                // As in `signal`, a pending standard signal's siginfo isn't overwritten.
+                return;
+            }
+            thread_protected.pending_signals.add(signal);
+            thread_protected.set_pending_standard_siginfo(signal, siginfo_t);
+
+            if current_thread.is_some_and(|t| t.id() == tid) {
+                // It'll be delivered synchronously when the current syscall returns.
+                return;
+            }
+            if thread_protected.blocked_signals.has(signal) {
+                // It'll be delivered once the thread unblocks it.
+                return;
+            }
+        }
+
+        let Some(mut cond) = thread.syscall_condition_mut() else {
+            // We may be able to get here if a thread is signalled before it runs for the first
+            // time. The signal will be delivered when the thread runs.
+            return;
+        };
+
+        let was_scheduled = cond.wakeup_for_signal(host, signal);
+
+        // it won't be scheduled if the signal is blocked, but we previously checked if the signal
+        // was blocked above
+        assert!(was_scheduled);
+    }
+
     /// Adds a new thread to the process and schedules it to run.
     /// Intended for use by `clone`.
     pub fn add_thread(&self, host: &Host, thread: RootedRc<RootedRefCell<Thread>>) {
@@ -629,6 +943,7 @@ impl RunnableProcess {
             group_id: Cell::new(process_group_id),
             session_id: Cell::new(session_id),
             exit_signal,
+            reaped_children_usage: Cell::new(ResourceUsage::ZERO),
         };
 
         // The child will log to the same strace log file. Entries contain thread IDs,
@@ -642,6 +957,9 @@ impl RunnableProcess {
 
         let threads = RefCell::new(BTreeMap::from([(new_tgl_tid, new_thread_group_leader)]));
 
+        // `shmop(2)`: After a fork(2), the child inherits the attached shared memory segments.
+        host.sysv_shm_borrow_mut().fork(self.common.id, pid);
+
         let shim_shared_mem = ProcessShmem::new(
             &host.shim_shmem_lock_borrow().unwrap().root,
             host.shim_shmem().serialize(),
@@ -664,11 +982,15 @@ impl RunnableProcess {
             #[cfg(feature = "perf_timers")]
             total_run_time: Cell::new(Duration::ZERO),
             itimer_real,
+            posix_timers: RefCell::new(PosixTimers::new()),
+            random: RefCell::new(new_process_rng(host, pid)),
             threads,
             unsafe_borrow_mut: RefCell::new(None),
             unsafe_borrows: RefCell::new(Vec::new()),
             memory_manager: Box::new(RefCell::new(unsafe { MemoryManager::new(native_pid) })),
             child_process_event_listeners: Default::default(),
+            exit_listeners: Default::default(),
+            exited_threads_usage: Cell::new(ResourceUsage::ZERO),
             shimlog_file: self.shimlog_file.clone(),
         };
         let child_process = Process {
@@ -700,6 +1022,9 @@ pub struct ZombieProcess {
     common: Common,
 
     exit_status: ExitStatus,
+
+    // Resource usage of the process's threads over the process's lifetime.
+    resource_usage: ResourceUsage,
 }
 
 impl ZombieProcess {
@@ -707,6 +1032,14 @@ impl ZombieProcess {
         self.exit_status
     }
 
+    /// Resource usage of the process and of the children that it reaped, as returned e.g. by
+    /// `wait4`, and as accumulated by the parent when this zombie is reaped.
+    pub fn resource_usage(&self) -> ResourceUsage {
+        let mut usage = self.resource_usage;
+        usage.add(&self.common.reaped_children_usage.get());
+        usage
+    }
+
     /// Process that can reap this zombie process, if any.
     pub fn reaper<'host>(
         &self,
@@ -894,10 +1227,26 @@ fn itimer_real_expiration(host: &Host, pid: ProcessId) {
     // The siginfo_t structure only has an i32. Presumably we want to just truncate in
     // case of overflow.
     let expiration_count = timer.expiration_count() as i32;
-    let siginfo_t = siginfo_t::new_for_timer(Signal::SIGALRM, 0, expiration_count);
+    let siginfo_t = siginfo_t::new_for_timer(
+        Signal::SIGALRM,
+        0,
+        expiration_count,
+        linux_api::signal::sigval { sival_int: 0 },
+    );
     process.signal(host, None, &siginfo_t);
 }
 
+/// Create the pseudo-random number generator for process `pid`. The host's seed is derived from
+/// the simulation seed, so the generated bytes are the same each time the simulation is run with
+/// the same seed.
+fn new_process_rng(host: &Host, pid: ProcessId) -> ChaCha20Rng {
+    let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+    seed[0..8].copy_from_slice(&host.params.node_seed.to_le_bytes());
+    seed[8..12].copy_from_slice(&u32::from(host.id()).to_le_bytes());
+    seed[12..16].copy_from_slice(&u32::from(pid).to_le_bytes());
+    ChaCha20Rng::from_seed(seed)
+}
+
 impl Process {
     fn common(&self) -> Ref<Common> {
         Ref::map(self.state.borrow(), |state| {
@@ -1106,6 +1455,7 @@ impl Process {
             // Exit signal is moot; since parent is INIT there will never
             // be a valid target for it.
             exit_signal: None,
+            reaped_children_usage: Cell::new(ResourceUsage::ZERO),
         };
         Ok(RootedRc::new(
             host.root(),
@@ -1118,6 +1468,8 @@ impl Process {
                         shim_shared_mem_block,
                         memory_manager: Box::new(RefCell::new(memory_manager)),
                         itimer_real,
+                        posix_timers: RefCell::new(PosixTimers::new()),
+                        random: RefCell::new(new_process_rng(host, process_id)),
                         strace_logging,
                         dumpable: Cell::new(SuidDump::SUID_DUMP_USER),
                         native_pid,
@@ -1129,6 +1481,8 @@ impl Process {
                         #[cfg(feature = "perf_timers")]
                         total_run_time: Cell::new(Duration::ZERO),
                         child_process_event_listeners: Default::default(),
+                        exit_listeners: Default::default(),
+                        exited_threads_usage: Cell::new(ResourceUsage::ZERO),
                         shimlog_file,
                     }))),
                 },
@@ -1331,6 +1685,44 @@ impl Process {
         }
     }
 
+    /// Release the robust futexes of `thread` before it calls `exit`, as the kernel does when it
+    /// exits. This is needed if it's the process's last thread, since the process's memory can't
+    /// be read after it exits.
+    pub fn release_exiting_thread_robust_futexes(&self, host: &Host, thread: &Thread) {
+        if let Some(runnable) = self.as_runnable() {
+            runnable.release_exiting_thread_robust_futexes(host, thread);
+        }
+    }
+
+    /// Like [`Self::release_exiting_thread_robust_futexes`], but for all of the process's threads
+    /// before one of them calls `exit_group`.
+    pub fn release_all_robust_futexes(&self, host: &Host) {
+        let Some(runnable) = self.as_runnable() else {
+            return;
+        };
+        for threadrc in runnable.threads.borrow().values() {
+            runnable.release_exiting_thread_robust_futexes(host, &threadrc.borrow(host.root()));
+        }
+    }
+
+    /// See `RunnableProcess::signal_thread`.
+    ///
+    /// No-op if the `self` is a `ZombieProcess`.
+    pub fn signal_thread(
+        &self,
+        host: &Host,
+        current_thread: Option<&Thread>,
+        tid: ThreadId,
+        siginfo_t: &siginfo_t,
+    ) {
+        match self.state.borrow().as_ref().unwrap() {
+            ProcessState::Runnable(r) => r.signal_thread(host, current_thread, tid, siginfo_t),
+            ProcessState::Zombie(_) => {
+                debug!("Process {} no longer running", &*self.name());
+            }
+        }
+    }
+
     fn open_stdio_file_helper(
         descriptor_table: &mut DescriptorTable,
         fd: DescriptorHandle,
@@ -1403,6 +1795,13 @@ impl Process {
         })
     }
 
+    /// Deprecated wrapper for `RunnableProcess::random_mut`
+    pub fn random_mut(&self) -> impl DerefMut<Target = ChaCha20Rng> + '_ {
+        std_util::nested_ref::NestedRefMut::map(self.as_runnable().unwrap(), |runnable| {
+            runnable.random.borrow_mut()
+        })
+    }
+
     /// Deprecated wrapper for `RunnableProcess::memory_borrow`
     #[track_caller]
     pub fn memory_borrow(&self) -> impl Deref<Target = MemoryManager> + '_ {
@@ -1442,6 +1841,23 @@ impl Process {
         })
     }
 
+    /// Borrow the timers created with `timer_create(2)`. Panics if the process isn't runnable.
+    #[track_caller]
+    pub fn posix_timers_borrow(&self) -> impl Deref<Target = PosixTimers> + '_ {
+        std_util::nested_ref::NestedRef::map(self.as_runnable().unwrap(), |runnable| {
+            runnable.posix_timers.borrow()
+        })
+    }
+
+    /// Mutably borrow the timers created with `timer_create(2)`. Panics if the process isn't
+    /// runnable.
+    #[track_caller]
+    pub fn posix_timers_borrow_mut(&self) -> impl DerefMut<Target = PosixTimers> + '_ {
+        std_util::nested_ref::NestedRefMut::map(self.as_runnable().unwrap(), |runnable| {
+            runnable.posix_timers.borrow_mut()
+        })
+    }
+
     /// Deprecated wrapper for `RunnableProcess::first_live_thread_borrow`
     #[track_caller]
     pub fn first_live_thread_borrow(
@@ -1541,6 +1957,9 @@ impl Process {
             );
         };
 
+        let mut resource_usage = runnable.exited_threads_usage.get();
+        resource_usage.max_rss_kib = runnable.memory_manager.borrow().peak_rss_kib();
+
         let (main_result_string, log_level) = {
             let mut s = format!(
                 "process '{name}' exited with status {exit_status:?}",
@@ -1570,13 +1989,37 @@ impl Process {
         };
         log::log!(log_level, "{}", main_result_string);
 
+        let mut exit_listeners = runnable.exit_listeners.take();
+
+        // `shmop(2)`: Upon _exit(2), all attached shared memory segments are detached.
+        host.sysv_shm_borrow_mut()
+            .detach_all(runnable.common.id, Worker::current_time().unwrap());
+
         let zombie = ZombieProcess {
             common: runnable.into_common(),
             exit_status,
+            resource_usage,
         };
         zombie.notify_parent_of_exit(host);
 
         *opt_state = Some(ProcessState::Zombie(zombie));
+        drop(opt_state);
+
+        CallbackQueue::queue_and_run_with_legacy(|q| exit_listeners.notify_listeners((), q));
+    }
+
+    /// Add a listener that will be notified when the process exits. Returns `None` if the process
+    /// has already exited.
+    pub fn add_exit_listener(
+        &self,
+        notify_fn: impl Fn(&mut CallbackQueue) + Send + Sync + 'static,
+    ) -> Option<Handle<()>> {
+        let runnable = self.as_runnable()?;
+        let handle = runnable
+            .exit_listeners
+            .borrow_mut()
+            .add_listener(move |(), cb_queue| notify_fn(cb_queue));
+        Some(handle)
     }
 
     /// Deprecated wrapper for `RunnableProcess::add_thread`
@@ -1599,41 +2042,53 @@ impl Process {
         Ref::map(self.as_runnable().unwrap(), |r| &r.shim_shared_mem_block)
     }
 
-    /// Resource usage, as returned e.g. by the `getrusage` syscall.
-    pub fn rusage(&self) -> linux_api::resource::rusage {
-        warn_once_then_debug!(
-            "resource usage (rusage) tracking unimplemented; Returning bogus zeroed values"
-        );
-        // TODO: Actually track some of these.
-        // Assuming we want to support `RUSAGE_THREAD` in the `getrusage`
-        // syscall, we'll actually want to track at the thread level, and either
-        // increment at both thread and process level at the points where we do
-        // the tracking, or dynamically iterate over the threads here and sum
-        // the results.
-        linux_api::resource::rusage {
-            ru_utime: linux_api::time::kernel_old_timeval {
-                tv_sec: 0,
-                tv_usec: 0,
-            },
-            ru_stime: linux_api::time::kernel_old_timeval {
-                tv_sec: 0,
-                tv_usec: 0,
-            },
-            ru_maxrss: 0,
-            ru_ixrss: 0,
-            ru_idrss: 0,
-            ru_isrss: 0,
-            ru_minflt: 0,
-            ru_majflt: 0,
-            ru_nswap: 0,
-            ru_inblock: 0,
-            ru_oublock: 0,
-            ru_msgsnd: 0,
-            ru_msgrcv: 0,
-            ru_nsignals: 0,
-            ru_nvcsw: 0,
-            ru_nivcsw: 0,
+    /// Resource usage of the process's threads, as returned e.g. by `getrusage(RUSAGE_SELF)`.
+    /// The peak RSS is estimated from the process's mappings (see
+    /// [`MemoryManager::peak_rss_kib`]).
+    pub fn rusage_self(&self, host: &Host) -> ResourceUsage {
+        if let Some(zombie) = self.as_zombie() {
+            return zombie.resource_usage;
+        }
+
+        let mut usage = self.threads_cpu_usage(host);
+        usage.max_rss_kib = self.memory_borrow().peak_rss_kib();
+        usage
+    }
+
+    /// Simulated CPU time used by the process's threads, including threads that have exited, as
+    /// returned e.g. by `clock_gettime(CLOCK_PROCESS_CPUTIME_ID)`.
+    pub fn cpu_time(&self, host: &Host) -> SimulationTime {
+        if let Some(zombie) = self.as_zombie() {
+            return zombie.resource_usage.cpu_time();
+        }
+
+        self.threads_cpu_usage(host).cpu_time()
+    }
+
+    /// The CPU usage of the live and exited threads of a runnable process. The peak RSS is 0.
+    fn threads_cpu_usage(&self, host: &Host) -> ResourceUsage {
+        let runnable = self.as_runnable().unwrap();
+        let mut usage = runnable.exited_threads_usage.get();
+        let host_shmem = host.shim_shmem_lock_borrow().unwrap();
+        for thread in runnable.threads.borrow().values() {
+            usage.add(&thread.borrow(host.root()).resource_usage(&host_shmem));
         }
+        usage
+    }
+
+    /// Resource usage of the children that this process has reaped, as returned e.g. by
+    /// `getrusage(RUSAGE_CHILDREN)`.
+    pub fn rusage_children(&self) -> ResourceUsage {
+        self.common().reaped_children_usage.get()
+    }
+
+    /// Accumulate the resource usage of a reaped child process (see
+    /// [`ZombieProcess::resource_usage`]).
+    pub fn add_reaped_child_usage(&self, usage: &ResourceUsage) {
+        let common = self.common();
+        let mut children_usage = common.reaped_children_usage.get();
+        children_usage.add(usage);
+        common.reaped_children_usage.set(children_usage);
     }
 
     /// Signal that will be sent to parent process on exit. Typically `Some(SIGCHLD)`.
@@ -1686,6 +2141,7 @@ impl Process {
         for (_tid, thread) in runnable.threads.replace(BTreeMap::new()) {
             // Notify the ManagedThread that the native process has exited.
             thread.borrow(host.root()).mthread().handle_process_exit();
+            runnable.add_exited_thread_usage(host, &thread.borrow(host.root()));
 
             thread.explicit_drop_recursive(host.root(), host);
         }
@@ -1724,6 +2180,13 @@ impl Process {
         // Exit signal is reset to SIGCHLD.
         runnable.common.exit_signal = Some(Signal::SIGCHLD);
 
+        // `execve(2)`: POSIX timers are not preserved (timer_create(2)).
+        runnable.posix_timers.borrow_mut().clear();
+
+        // `shmop(2)`: After an execve(2), all attached shared memory segments are detached.
+        host.sysv_shm_borrow_mut()
+            .detach_all(runnable.common.id, Worker::current_time().unwrap());
+
         // Reset signal actions to default.
         // `execve(2)`:
         // POSIX.1 specifies that the dispositions of any signals that
@@ -2176,6 +2639,18 @@ mod export {
         proc.native_pid().as_raw_nonzero().get()
     }
 
+    /// Fills the buffer with pseudo-random bytes from the process' random number generator.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn process_rngNextNBytes(
+        proc: *const Process,
+        buf: *mut u8,
+        len: usize,
+    ) {
+        let proc = unsafe { proc.as_ref().unwrap() };
+        let buf = unsafe { std::slice::from_raw_parts_mut(buf, len) };
+        rand::RngCore::fill_bytes(&mut *proc.random_mut(), buf);
+    }
+
     /// Flushes and invalidates all previously returned readable/writable plugin
     /// pointers, as if returning control to the plugin. This can be useful in
     /// conjunction with `thread_nativeSyscall` operations that touch memory, or
@@ -22,6 +22,17 @@ enum ProtocolTCPFlags {
     PTCP_SACK = 1 << 4,
     PTCP_FIN =  1 << 5,
     PTCP_DUPACK =  1 << 6,
+    PTCP_URG =  1 << 7,
+    PTCP_ECE =  1 << 8,
+    PTCP_CWR =  1 << 9,
+};
+
+/* the ECN codepoint of the IP header (RFC 3168) */
+enum ProtocolECN {
+    PECN_NOT_ECT = 0,
+    PECN_ECT1 = 1,
+    PECN_ECT0 = 2,
+    PECN_CE = 3,
 };
 
 #endif /* SHD_PROTOCOL_H_ */
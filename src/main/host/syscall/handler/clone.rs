@@ -222,6 +222,7 @@ impl SyscallHandler {
             child_pid,
             child_tid,
         )?;
+        child_thread.set_sched_policy(ctx.objs.thread.sched_policy().for_child());
 
         let childrc = ExplicitDropper::new(
             RootedRc::new(
@@ -0,0 +1,149 @@
+use linux_api::errno::Errno;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::cshadow as c;
+use crate::host::descriptor::{CompatFile, FileState};
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+
+impl SyscallHandler {
+    log_syscall!(
+        copy_file_range,
+        /* rv */ isize,
+        /* fd_in */ std::ffi::c_int,
+        /* off_in */ *const libc::loff_t,
+        /* fd_out */ std::ffi::c_int,
+        /* off_out */ *const libc::loff_t,
+        /* len */ usize,
+        /* flags */ std::ffi::c_uint,
+    );
+    pub fn copy_file_range(
+        ctx: &mut SyscallContext,
+        fd_in: std::ffi::c_int,
+        off_in_ptr: ForeignPtr<libc::loff_t>,
+        fd_out: std::ffi::c_int,
+        off_out_ptr: ForeignPtr<libc::loff_t>,
+        len: usize,
+        flags: std::ffi::c_uint,
+    ) -> Result<isize, Errno> {
+        // copy_file_range(2):
+        // > The flags argument is provided to allow for future extensions and currently must be
+        // > set to 0.
+        if flags != 0 {
+            log::debug!("Invalid copy_file_range flags: {flags}");
+            return Err(Errno::EINVAL);
+        }
+
+        let (file_in, file_out) = {
+            let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+            let file_in = Self::get_descriptor(&desc_table, fd_in)?.file();
+            let file_out = Self::get_descriptor(&desc_table, fd_out)?.file();
+
+            // copy_file_range(2):
+            // > EINVAL: Either fd_in or fd_out is not a regular file.
+            let (CompatFile::Legacy(file_in), CompatFile::Legacy(file_out)) = (file_in, file_out)
+            else {
+                log::debug!("Descriptors {fd_in} and {fd_out} are not both regular files");
+                return Err(Errno::EINVAL);
+            };
+
+            (file_in.ptr(), file_out.ptr())
+        };
+
+        for (fd, file) in [(fd_in, file_in), (fd_out, file_out)] {
+            assert!(!file.is_null());
+
+            if unsafe { c::legacyfile_getStatus(file) }.contains(FileState::CLOSED) {
+                // see the similar check in `mmap` for why this isn't a panic
+                log::warn!("File {file:p} (fd={fd}) is closed");
+                return Err(Errno::EBADF);
+            }
+
+            if unsafe { c::legacyfile_getType(file) } != c::_LegacyFileType_DT_FILE {
+                log::debug!("Descriptor exists for fd {fd}, but is not a regular file type");
+                return Err(Errno::EINVAL);
+            }
+        }
+
+        let file_in = file_in as *mut c::RegularFile;
+        let file_out = file_out as *mut c::RegularFile;
+
+        // Special files like `/dev/urandom` and in-memory files like `/sys/*` are emulated by
+        // shadow, so we can't let the kernel copy from or to their os-backed files. Linux returns
+        // EXDEV when it can't copy between the two files, and callers (for example glibc, coreutils,
+        // and rust's `std::fs::copy`) then fall back to a read/write loop, which we do emulate.
+        for file in [file_in, file_out] {
+            let file_type = unsafe { c::regularfile_getType(file) };
+            if file_type != c::_FileType_FILE_TYPE_REGULAR
+                && file_type != c::_FileType_FILE_TYPE_HOSTS
+                && file_type != c::_FileType_FILE_TYPE_LOCALTIME
+            {
+                log::debug!("copy_file_range is not supported for emulated file type {file_type}");
+                return Err(Errno::EXDEV);
+            }
+        }
+
+        // the kernel wouldn't limit the copy to the size of a tmpfs mount or update its usage
+        if unsafe { c::regularfile_isInTmpfsMount(file_out) } {
+            log::debug!("copy_file_range is not supported for files in a tmpfs mount");
+            return Err(Errno::EXDEV);
+        }
+
+        let native_fd_in = unsafe { c::regularfile_getOSBackedFD(file_in) };
+        let native_fd_out = unsafe { c::regularfile_getOSBackedFD(file_out) };
+
+        if native_fd_in < 0 || native_fd_out < 0 {
+            return Err(Errno::EBADF);
+        }
+
+        // when an offset pointer is NULL, the kernel uses and updates the file offset of the
+        // os-backed file, which is also the file offset that the plugin sees
+        let mut off_in = (!off_in_ptr.is_null())
+            .then(|| ctx.objs.process.memory_borrow().read(off_in_ptr))
+            .transpose()?;
+        let mut off_out = (!off_out_ptr.is_null())
+            .then(|| ctx.objs.process.memory_borrow().read(off_out_ptr))
+            .transpose()?;
+
+        if off_in.is_some_and(|x| x < 0) || off_out.is_some_and(|x| x < 0) {
+            return Err(Errno::EINVAL);
+        }
+
+        let off_in_native = off_in
+            .as_mut()
+            .map_or(std::ptr::null_mut(), std::ptr::from_mut);
+        let off_out_native = off_out
+            .as_mut()
+            .map_or(std::ptr::null_mut(), std::ptr::from_mut);
+
+        // TODO: this may block the shadow thread until we properly handle os-backed files in
+        // non-blocking mode
+        let rv = unsafe {
+            libc::copy_file_range(
+                native_fd_in,
+                off_in_native,
+                native_fd_out,
+                off_out_native,
+                len,
+                0,
+            )
+        };
+        let bytes_copied = Errno::result_from_libc_errno(-1, rv)?;
+
+        log::trace!(
+            "copy_file_range copied {bytes_copied} of {len} requested bytes from fd {fd_in} to fd \
+            {fd_out}"
+        );
+
+        // copy_file_range(2):
+        // > If off_in is not NULL, [...] off_in is adjusted by the number of bytes copied.
+        let mut mem = ctx.objs.process.memory_borrow_mut();
+        if let Some(off_in) = off_in {
+            mem.write(off_in_ptr, &off_in)?;
+        }
+        if let Some(off_out) = off_out {
+            mem.write(off_out_ptr, &off_out)?;
+        }
+
+        Ok(bytes_copied)
+    }
+}
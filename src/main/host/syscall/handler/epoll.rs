@@ -179,13 +179,48 @@ impl SyscallHandler {
             (events, ev.data)
         };
 
+        // From epoll_ctl(2): Returns EINVAL when "EPOLLEXCLUSIVE was specified in event and fd
+        // refers to an epoll instance", or when it's used with EPOLL_CTL_MOD or with flags that
+        // aren't allowed alongside it. Like linux, this is checked before looking up the entry.
+        if events.contains(EpollEvents::EPOLLEXCLUSIVE) {
+            let allowed = EpollEvents::EPOLLIN
+                | EpollEvents::EPOLLOUT
+                | EpollEvents::EPOLLERR
+                | EpollEvents::EPOLLHUP
+                | EpollEvents::EPOLLWAKEUP
+                | EpollEvents::EPOLLET
+                | EpollEvents::EPOLLEXCLUSIVE;
+
+            if op == EpollCtlOp::EPOLL_CTL_MOD
+                || matches!(target, File::Epoll(_))
+                || !allowed.contains(events)
+            {
+                return Err(Errno::EINVAL);
+            }
+        }
+
+        // An epoll can monitor another epoll, but not if that would create a loop or a chain of
+        // nested epolls that's too deep. Like linux, this is only checked when adding.
+        if op == EpollCtlOp::EPOLL_CTL_ADD {
+            if let File::Epoll(child) = &target {
+                Epoll::check_nesting(epoll, child)?;
+            }
+        }
+
         log::trace!("Calling epoll_ctl on epoll {epfd} with child {fd}");
 
         CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
             let weak_epoll = Arc::downgrade(epoll);
-            epoll
-                .borrow_mut()
-                .ctl(op, fd, target, events, data, weak_epoll, cb_queue)
+            epoll.borrow_mut().ctl(
+                op,
+                fd,
+                target,
+                events,
+                data,
+                weak_epoll,
+                ctx.objs.host,
+                cb_queue,
+            )
         })?;
         Ok(())
     }
@@ -3,7 +3,10 @@ use linux_api::fcntl::{DescriptorFlags, FcntlCommand, OFlag};
 use log::debug;
 
 use crate::cshadow;
+use crate::host::descriptor::socket::inet::InetSocket;
+use crate::host::descriptor::socket::Socket;
 use crate::host::descriptor::{CompatFile, File, FileStatus};
+use crate::host::process::ProcessId;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::SyscallError;
 
@@ -176,6 +179,41 @@ impl SyscallHandler {
                     return Err(Errno::EINVAL.into());
                 }
             }
+            FcntlCommand::F_GETOWN | FcntlCommand::F_SETOWN => {
+                // the owner is currently only used for sending SIGURG signals for TCP urgent data
+                let tcp = match desc.file() {
+                    CompatFile::New(file) => match file.inner_file() {
+                        File::Socket(Socket::Inet(InetSocket::LegacyTcp(tcp))) => Some(tcp),
+                        _ => None,
+                    },
+                    CompatFile::Legacy(_) => None,
+                };
+
+                let Some(tcp) = tcp else {
+                    warn_once_then_debug!("fcntl({cmd:?}) unimplemented for {:?}", desc.file());
+                    return Err(Errno::EINVAL.into());
+                };
+
+                if let FcntlCommand::F_GETOWN = cmd {
+                    tcp.borrow().owner().into()
+                } else {
+                    // the kernel also truncates the argument to an int
+                    let owner = arg as libc::pid_t;
+
+                    // a negative owner is a process group, but currently every emulated process is
+                    // in its own process group where pgid=pid (see the `kill` syscall handler)
+                    if owner != 0 {
+                        let pid =
+                            ProcessId::try_from(owner.unsigned_abs()).or(Err(Errno::ESRCH))?;
+                        if ctx.objs.host.process_borrow(pid).is_none() {
+                            return Err(Errno::ESRCH.into());
+                        }
+                    }
+
+                    tcp.borrow_mut().set_owner(owner);
+                    0
+                }
+            }
             cmd => {
                 warn_once_then_debug!("Unhandled fcntl command: {cmd:?}");
                 return Err(Errno::EINVAL.into());
@@ -89,6 +89,131 @@ static SyscallReturn _syscallhandler_fsyncHelper(SyscallHandler* sys, int fd) {
     return syscallreturn_makeDoneI64(regularfile_fsync(file_desc));
 }
 
+static SyscallReturn _syscallhandler_setxattrHelper(SyscallHandler* sys, const SyscallArgs* args,
+                                                    bool followSymlinks) {
+    UntypedForeignPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    UntypedForeignPtr namePtr = args->args[1].as_ptr;     // const char*
+    UntypedForeignPtr valuePtr = args->args[2].as_ptr;    // const void*
+    size_t size = args->args[3].as_u64;
+    int flags = args->args[4].as_i64;
+
+    /* Get the path/name/value strings from the plugin. */
+    char pathname[PATH_MAX];
+    ssize_t errcode =
+        process_readString(rustsyscallhandler_getProcess(sys), pathname, pathnamePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    char name[PATH_MAX];
+    errcode = process_readString(rustsyscallhandler_getProcess(sys), name, namePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    const void* value =
+        (valuePtr.val && size > 0)
+            ? process_getReadablePtr(rustsyscallhandler_getProcess(sys), valuePtr, size)
+            : NULL;
+    if (valuePtr.val && size > 0 && !value) {
+        return syscallreturn_makeDoneErrno(EFAULT);
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
+
+    return syscallreturn_makeDoneI64(
+        regularfile_setxattr(pathname, name, value, size, flags, followSymlinks, plugin_cwd));
+}
+
+static SyscallReturn _syscallhandler_getxattrHelper(SyscallHandler* sys, const SyscallArgs* args,
+                                                    bool followSymlinks) {
+    UntypedForeignPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    UntypedForeignPtr namePtr = args->args[1].as_ptr;     // const char*
+    UntypedForeignPtr valuePtr = args->args[2].as_ptr;    // void*
+    size_t size = args->args[3].as_u64;
+
+    /* Copy the strings rather than getting references, so that the MemoryManager will still
+     * allow us to get a mutable reference to memory below. */
+    char pathname[PATH_MAX];
+    ssize_t errcode =
+        process_readString(rustsyscallhandler_getProcess(sys), pathname, pathnamePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    char name[PATH_MAX];
+    errcode = process_readString(rustsyscallhandler_getProcess(sys), name, namePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    void* value = (valuePtr.val && size > 0)
+                      ? process_getWriteablePtr(rustsyscallhandler_getProcess(sys), valuePtr, size)
+                      : NULL;
+    if (valuePtr.val && size > 0 && !value) {
+        return syscallreturn_makeDoneErrno(EFAULT);
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
+
+    return syscallreturn_makeDoneI64(
+        regularfile_getxattr(pathname, name, value, size, followSymlinks, plugin_cwd));
+}
+
+static SyscallReturn _syscallhandler_listxattrHelper(SyscallHandler* sys, const SyscallArgs* args,
+                                                     bool followSymlinks) {
+    UntypedForeignPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    UntypedForeignPtr listPtr = args->args[1].as_ptr;     // char*
+    size_t size = args->args[2].as_u64;
+
+    /* Copy the path rather than getting a reference, so that the MemoryManager will still allow
+     * us to get a mutable reference to memory below. */
+    char pathname[PATH_MAX];
+    ssize_t errcode =
+        process_readString(rustsyscallhandler_getProcess(sys), pathname, pathnamePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    void* list = (listPtr.val && size > 0)
+                     ? process_getWriteablePtr(rustsyscallhandler_getProcess(sys), listPtr, size)
+                     : NULL;
+    if (listPtr.val && size > 0 && !list) {
+        return syscallreturn_makeDoneErrno(EFAULT);
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
+
+    return syscallreturn_makeDoneI64(
+        regularfile_listxattr(pathname, list, size, followSymlinks, plugin_cwd));
+}
+
+static SyscallReturn _syscallhandler_removexattrHelper(SyscallHandler* sys,
+                                                       const SyscallArgs* args,
+                                                       bool followSymlinks) {
+    UntypedForeignPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    UntypedForeignPtr namePtr = args->args[1].as_ptr;     // const char*
+
+    /* Get the path/name strings from the plugin. */
+    char pathname[PATH_MAX];
+    ssize_t errcode =
+        process_readString(rustsyscallhandler_getProcess(sys), pathname, pathnamePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    char name[PATH_MAX];
+    errcode = process_readString(rustsyscallhandler_getProcess(sys), name, namePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
+
+    return syscallreturn_makeDoneI64(
+        regularfile_removexattr(pathname, name, followSymlinks, plugin_cwd));
+}
+
 ///////////////////////////////////////////////////////////
 // System Calls
 ///////////////////////////////////////////////////////////
@@ -212,6 +337,34 @@ SyscallReturn syscallhandler_ftruncate(SyscallHandler* sys, const SyscallArgs* a
     return syscallreturn_makeDoneI64(regularfile_ftruncate(file_desc, args->args[1].as_u64));
 }
 
+SyscallReturn syscallhandler_truncate(SyscallHandler* sys, const SyscallArgs* args) {
+    UntypedForeignPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    off_t length = args->args[1].as_i64;
+
+    /* Get the path string from the plugin. */
+    const char* pathname;
+    int errcode = process_getReadableString(
+        rustsyscallhandler_getProcess(sys), pathnamePtr, PATH_MAX, &pathname, NULL);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    /* Truncate through a temporary file, so that the limits of tmpfs mounts and block devices
+     * are applied like they are for ftruncate. */
+    RegularFile* file_desc = regularfile_new();
+    errcode = regularfile_open(file_desc, pathname, O_WRONLY | O_NONBLOCK, 0,
+                               process_getWorkingDir(rustsyscallhandler_getProcess(sys)));
+    if (errcode == 0) {
+        errcode = regularfile_ftruncate(file_desc, length);
+    }
+
+    /* This will unref/free the RegularFile. */
+    legacyfile_close((LegacyFile*)file_desc, rustsyscallhandler_getHost(sys));
+    legacyfile_unref(file_desc);
+
+    return syscallreturn_makeDoneI64(errcode);
+}
+
 SyscallReturn syscallhandler_fadvise64(SyscallHandler* sys, const SyscallArgs* args) {
     int fd = args->args[0].as_i64;
 
@@ -427,3 +580,35 @@ SyscallReturn syscallhandler_getdents64(SyscallHandler* sys, const SyscallArgs*
 
     return syscallreturn_makeDoneI64(regularfile_getdents64(file_desc, dirp, count));
 }
+
+SyscallReturn syscallhandler_setxattr(SyscallHandler* sys, const SyscallArgs* args) {
+    return _syscallhandler_setxattrHelper(sys, args, true);
+}
+
+SyscallReturn syscallhandler_lsetxattr(SyscallHandler* sys, const SyscallArgs* args) {
+    return _syscallhandler_setxattrHelper(sys, args, false);
+}
+
+SyscallReturn syscallhandler_getxattr(SyscallHandler* sys, const SyscallArgs* args) {
+    return _syscallhandler_getxattrHelper(sys, args, true);
+}
+
+SyscallReturn syscallhandler_lgetxattr(SyscallHandler* sys, const SyscallArgs* args) {
+    return _syscallhandler_getxattrHelper(sys, args, false);
+}
+
+SyscallReturn syscallhandler_listxattr(SyscallHandler* sys, const SyscallArgs* args) {
+    return _syscallhandler_listxattrHelper(sys, args, true);
+}
+
+SyscallReturn syscallhandler_llistxattr(SyscallHandler* sys, const SyscallArgs* args) {
+    return _syscallhandler_listxattrHelper(sys, args, false);
+}
+
+SyscallReturn syscallhandler_removexattr(SyscallHandler* sys, const SyscallArgs* args) {
+    return _syscallhandler_removexattrHelper(sys, args, true);
+}
+
+SyscallReturn syscallhandler_lremovexattr(SyscallHandler* sys, const SyscallArgs* args) {
+    return _syscallhandler_removexattrHelper(sys, args, false);
+}
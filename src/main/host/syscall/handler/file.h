@@ -25,10 +25,19 @@ SYSCALL_HANDLER(fsync);
 SYSCALL_HANDLER(ftruncate);
 SYSCALL_HANDLER(getdents);
 SYSCALL_HANDLER(getdents64);
+SYSCALL_HANDLER(getxattr);
+SYSCALL_HANDLER(lgetxattr);
+SYSCALL_HANDLER(listxattr);
+SYSCALL_HANDLER(llistxattr);
+SYSCALL_HANDLER(lremovexattr);
 SYSCALL_HANDLER(lseek);
+SYSCALL_HANDLER(lsetxattr);
 SYSCALL_HANDLER(open);
 SYSCALL_HANDLER(readahead);
+SYSCALL_HANDLER(removexattr);
+SYSCALL_HANDLER(setxattr);
 SYSCALL_HANDLER(sync_file_range);
 SYSCALL_HANDLER(syncfs);
+SYSCALL_HANDLER(truncate);
 
 #endif /* SRC_MAIN_HOST_SYSCALL_FILE_H_ */
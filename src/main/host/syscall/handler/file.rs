@@ -101,6 +101,31 @@ impl SyscallHandler {
         Self::legacy_syscall(cshadow::syscallhandler_getdents64, ctx)
     }
 
+    log_syscall!(getxattr, /* rv */ std::ffi::c_int);
+    pub fn getxattr(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_getxattr, ctx)
+    }
+
+    log_syscall!(lgetxattr, /* rv */ std::ffi::c_int);
+    pub fn lgetxattr(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_lgetxattr, ctx)
+    }
+
+    log_syscall!(listxattr, /* rv */ std::ffi::c_int);
+    pub fn listxattr(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_listxattr, ctx)
+    }
+
+    log_syscall!(llistxattr, /* rv */ std::ffi::c_int);
+    pub fn llistxattr(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_llistxattr, ctx)
+    }
+
+    log_syscall!(lremovexattr, /* rv */ std::ffi::c_int);
+    pub fn lremovexattr(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_lremovexattr, ctx)
+    }
+
     log_syscall!(
         lseek,
         /* rv */ std::ffi::c_int,
@@ -136,11 +161,26 @@ impl SyscallHandler {
         }
     }
 
+    log_syscall!(lsetxattr, /* rv */ std::ffi::c_int);
+    pub fn lsetxattr(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_lsetxattr, ctx)
+    }
+
     log_syscall!(readahead, /* rv */ std::ffi::c_int);
     pub fn readahead(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_readahead, ctx)
     }
 
+    log_syscall!(removexattr, /* rv */ std::ffi::c_int);
+    pub fn removexattr(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_removexattr, ctx)
+    }
+
+    log_syscall!(setxattr, /* rv */ std::ffi::c_int);
+    pub fn setxattr(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_setxattr, ctx)
+    }
+
     log_syscall!(sync_file_range, /* rv */ std::ffi::c_int);
     pub fn sync_file_range(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_sync_file_range, ctx)
@@ -150,4 +190,9 @@ impl SyscallHandler {
     pub fn syncfs(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_syncfs, ctx)
     }
+
+    log_syscall!(truncate, /* rv */ std::ffi::c_int);
+    pub fn truncate(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_truncate, ctx)
+    }
 }
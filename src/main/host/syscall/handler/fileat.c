@@ -12,6 +12,7 @@
 #include <sys/time.h>
 #include <sys/types.h>
 #include <unistd.h>
+#include <utime.h>
 
 #include "lib/logger/logger.h"
 #include "main/bindings/c/bindings.h"
@@ -100,6 +101,25 @@ static SyscallReturn _syscallhandler_renameatHelper(SyscallHandler* sys, int old
         regularfile_renameat2(olddir_desc, oldpath, newdir_desc, newpath, flags, plugin_cwd));
 }
 
+static SyscallReturn _syscallhandler_faccessatHelper(SyscallHandler* sys, int dirfd,
+                                                     UntypedForeignPtr pathnamePtr, int mode,
+                                                     int flags) {
+    /* Validate params. */
+    RegularFile* dir_desc = NULL;
+    const char* pathname;
+
+    int errcode = _syscallhandler_validateDirAndPathnameHelper(
+        sys, dirfd, pathnamePtr, &dir_desc, &pathname);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
+
+    return syscallreturn_makeDoneI64(
+        regularfile_faccessat(dir_desc, pathname, mode, flags, plugin_cwd));
+}
+
 ///////////////////////////////////////////////////////////
 // System Calls
 ///////////////////////////////////////////////////////////
@@ -258,10 +278,14 @@ SyscallReturn syscallhandler_futimesat(SyscallHandler* sys, const SyscallArgs* a
         return syscallreturn_makeDoneErrno(-errcode);
     }
 
-    const struct timeval* times =
-        process_getReadablePtr(rustsyscallhandler_getProcess(sys), timesPtr, 2 * sizeof(*times));
-    if (!times) {
-        return syscallreturn_makeDoneErrno(EFAULT);
+    /* NULL times set both timestamps to the current time. */
+    const struct timeval* times = NULL;
+    if (timesPtr.val) {
+        times = process_getReadablePtr(
+            rustsyscallhandler_getProcess(sys), timesPtr, 2 * sizeof(*times));
+        if (!times) {
+            return syscallreturn_makeDoneErrno(EFAULT);
+        }
     }
 
     const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
@@ -269,6 +293,37 @@ SyscallReturn syscallhandler_futimesat(SyscallHandler* sys, const SyscallArgs* a
     return syscallreturn_makeDoneI64(regularfile_futimesat(dir_desc, pathname, times, plugin_cwd));
 }
 
+SyscallReturn syscallhandler_utime(SyscallHandler* sys, const SyscallArgs* args) {
+    UntypedForeignPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    UntypedForeignPtr timesPtr = args->args[1].as_ptr;    // const struct utimbuf*
+
+    /* Get the path string from the plugin. */
+    const char* pathname;
+    int errcode = process_getReadableString(
+        rustsyscallhandler_getProcess(sys), pathnamePtr, PATH_MAX, &pathname, NULL);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    /* NULL times set both timestamps to the current time. */
+    struct timeval times[2] = {0};
+    const struct timeval* timesArg = NULL;
+    if (timesPtr.val) {
+        const struct utimbuf* buf =
+            process_getReadablePtr(rustsyscallhandler_getProcess(sys), timesPtr, sizeof(*buf));
+        if (!buf) {
+            return syscallreturn_makeDoneErrno(EFAULT);
+        }
+        times[0].tv_sec = buf->actime;
+        times[1].tv_sec = buf->modtime;
+        timesArg = times;
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
+
+    return syscallreturn_makeDoneI64(regularfile_futimesat(NULL, pathname, timesArg, plugin_cwd));
+}
+
 SyscallReturn syscallhandler_utimensat(SyscallHandler* sys, const SyscallArgs* args) {
     int dirfd = args->args[0].as_i64;
     UntypedForeignPtr pathnamePtr = args->args[1].as_ptr; // const char*
@@ -301,22 +356,18 @@ SyscallReturn syscallhandler_faccessat(SyscallHandler* sys, const SyscallArgs* a
     int dirfd = args->args[0].as_i64;
     UntypedForeignPtr pathnamePtr = args->args[1].as_ptr; // const char*
     int mode = args->args[2].as_i64;
-    int flags = args->args[3].as_i64;
 
-    /* Validate params. */
-    RegularFile* dir_desc = NULL;
-    const char* pathname;
-
-    int errcode = _syscallhandler_validateDirAndPathnameHelper(
-        sys, dirfd, pathnamePtr, &dir_desc, &pathname);
-    if (errcode < 0) {
-        return syscallreturn_makeDoneErrno(-errcode);
-    }
+    /* The faccessat syscall doesn't take a flags argument, unlike the libc function. */
+    return _syscallhandler_faccessatHelper(sys, dirfd, pathnamePtr, mode, 0);
+}
 
-    const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
+SyscallReturn syscallhandler_faccessat2(SyscallHandler* sys, const SyscallArgs* args) {
+    int dirfd = args->args[0].as_i64;
+    UntypedForeignPtr pathnamePtr = args->args[1].as_ptr; // const char*
+    int mode = args->args[2].as_i64;
+    int flags = args->args[3].as_i64;
 
-    return syscallreturn_makeDoneI64(
-        regularfile_faccessat(dir_desc, pathname, mode, flags, plugin_cwd));
+    return _syscallhandler_faccessatHelper(sys, dirfd, pathnamePtr, mode, flags);
 }
 
 SyscallReturn syscallhandler_mkdirat(SyscallHandler* sys, const SyscallArgs* args) {
@@ -527,3 +578,29 @@ SyscallReturn syscallhandler_statx(SyscallHandler* sys, const SyscallArgs* args)
         regularfile_statx(dir_desc, pathname, flags, mask, statxbuf, plugin_cwd));
 }
 #endif
+
+SyscallReturn syscallhandler_statfs(SyscallHandler* sys, const SyscallArgs* args) {
+    UntypedForeignPtr pathnamePtr = args->args[0].as_ptr; // const char*
+    UntypedForeignPtr bufPtr = args->args[1].as_ptr;      // struct statfs*
+
+    /* Copy the path rather than getting a reference, so that the MemoryManager
+     * will still allow us to get a mutable reference to memory below.
+     */
+    char pathname[PATH_MAX];
+    ssize_t errcode =
+        process_readString(rustsyscallhandler_getProcess(sys), pathname, pathnamePtr, PATH_MAX);
+    if (errcode < 0) {
+        return syscallreturn_makeDoneErrno(-errcode);
+    }
+
+    /* Get some memory in which to return the result. */
+    struct statfs* buf =
+        process_getWriteablePtr(rustsyscallhandler_getProcess(sys), bufPtr, sizeof(*buf));
+    if (!buf) {
+        return syscallreturn_makeDoneErrno(EFAULT);
+    }
+
+    const char* plugin_cwd = process_getWorkingDir(rustsyscallhandler_getProcess(sys));
+
+    return syscallreturn_makeDoneI64(regularfile_statfs(pathname, buf, plugin_cwd));
+}
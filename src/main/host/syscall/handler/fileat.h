@@ -9,6 +9,7 @@
 #include "main/host/syscall/protected.h"
 
 SYSCALL_HANDLER(faccessat);
+SYSCALL_HANDLER(faccessat2);
 SYSCALL_HANDLER(fchmodat);
 SYSCALL_HANDLER(fchmodat2);
 SYSCALL_HANDLER(fchownat);
@@ -21,9 +22,11 @@ SYSCALL_HANDLER(openat);
 SYSCALL_HANDLER(readlinkat);
 SYSCALL_HANDLER(renameat);
 SYSCALL_HANDLER(renameat2);
+SYSCALL_HANDLER(statfs);
 SYSCALL_HANDLER(statx);
 SYSCALL_HANDLER(symlinkat);
 SYSCALL_HANDLER(unlinkat);
+SYSCALL_HANDLER(utime);
 SYSCALL_HANDLER(utimensat);
 
 #endif /* SRC_MAIN_HOST_SYSCALL_FILEAT_H_ */
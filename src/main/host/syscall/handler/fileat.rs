@@ -1,7 +1,9 @@
+use linux_api::errno::Errno;
 use linux_api::posix_types::kernel_mode_t;
-use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+use shadow_shim_helper_rs::syscall_types::{ForeignPtr, SyscallReg};
 
 use crate::cshadow;
+use crate::host::descriptor::{CompatFile, File};
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::type_formatting::SyscallStringArg;
 use crate::host::syscall::types::SyscallResult;
@@ -25,11 +27,75 @@ impl SyscallHandler {
         Self::legacy_syscall(cshadow::syscallhandler_openat, ctx)
     }
 
-    log_syscall!(faccessat, /* rv */ std::ffi::c_int);
-    pub fn faccessat(ctx: &mut SyscallContext) -> SyscallResult {
+    log_syscall!(
+        faccessat,
+        /* rv */ std::ffi::c_int,
+        /* dirfd */ std::ffi::c_int,
+        /* pathname */ SyscallStringArg,
+        /* mode */ std::ffi::c_int,
+    );
+    pub fn faccessat(
+        ctx: &mut SyscallContext,
+        _dir_fd: std::ffi::c_int,
+        _path: ForeignPtr<()>,
+        mode: std::ffi::c_int,
+    ) -> SyscallResult {
+        check_access_mode(mode)?;
         Self::legacy_syscall(cshadow::syscallhandler_faccessat, ctx)
     }
 
+    log_syscall!(
+        faccessat2,
+        /* rv */ std::ffi::c_int,
+        /* dirfd */ std::ffi::c_int,
+        /* pathname */ SyscallStringArg,
+        /* mode */ std::ffi::c_int,
+        /* flags */ std::ffi::c_int,
+    );
+    pub fn faccessat2(
+        ctx: &mut SyscallContext,
+        dir_fd: std::ffi::c_int,
+        path: ForeignPtr<()>,
+        mode: std::ffi::c_int,
+        flags: std::ffi::c_int,
+    ) -> SyscallResult {
+        check_access_mode(mode)?;
+
+        if flags & !(libc::AT_EACCESS | libc::AT_SYMLINK_NOFOLLOW | libc::AT_EMPTY_PATH) != 0 {
+            log::debug!("Invalid faccessat2 flags: {flags}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        if flags & libc::AT_EMPTY_PATH != 0 && dir_fd != libc::AT_FDCWD {
+            let first_byte: u8 = ctx.objs.process.memory_borrow().read(path.cast::<u8>())?;
+
+            // with an empty path, check the descriptor itself
+            if first_byte == 0 {
+                let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+                let file = match Self::get_descriptor(&desc_table, dir_fd)?.file() {
+                    CompatFile::New(file) => file,
+                    // let the C syscall handler check the regular file
+                    CompatFile::Legacy(_) => {
+                        drop(desc_table);
+                        return Self::legacy_syscall(cshadow::syscallhandler_faccessat2, ctx);
+                    }
+                };
+
+                // Linux checks the mode of the descriptor's inode. Sockets are created with mode
+                // 0777, while pipes and anonymous inodes (eventfd, timerfd, epoll, and pidfd) are
+                // created with mode 0600.
+                let executable = matches!(file.inner_file(), File::Socket(_));
+                if mode & libc::X_OK != 0 && !executable {
+                    return Err(Errno::EACCES.into());
+                }
+
+                return Ok(0.into());
+            }
+        }
+
+        Self::legacy_syscall(cshadow::syscallhandler_faccessat2, ctx)
+    }
+
     log_syscall!(fchmodat, /* rv */ std::ffi::c_int);
     pub fn fchmodat(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_fchmodat, ctx)
@@ -94,4 +160,146 @@ impl SyscallHandler {
     pub fn utimensat(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_utimensat, ctx)
     }
+
+    // The legacy path syscalls below are emulated with their `*at` versions so that their paths
+    // are resolved the same way, for example within the host's tmpfs mounts.
+
+    log_syscall!(
+        access,
+        /* rv */ std::ffi::c_int,
+        /* pathname */ SyscallStringArg,
+        /* mode */ std::ffi::c_int,
+    );
+    pub fn access(
+        ctx: &mut SyscallContext,
+        path: ForeignPtr<()>,
+        mode: std::ffi::c_int,
+    ) -> SyscallResult {
+        check_access_mode(mode)?;
+        let args: [SyscallReg; 3] = [libc::AT_FDCWD.into(), path.into(), mode.into()];
+        Self::legacy_at_syscall(cshadow::syscallhandler_faccessat, ctx, &args)
+    }
+
+    log_syscall!(chmod, /* rv */ std::ffi::c_int);
+    pub fn chmod(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [libc::AT_FDCWD.into(), ctx.args.get(0), ctx.args.get(1)];
+        Self::legacy_at_syscall(cshadow::syscallhandler_fchmodat, ctx, &args)
+    }
+
+    log_syscall!(chown, /* rv */ std::ffi::c_int);
+    pub fn chown(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            ctx.args.get(1),
+            ctx.args.get(2),
+            0.into(),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_fchownat, ctx, &args)
+    }
+
+    log_syscall!(lchown, /* rv */ std::ffi::c_int);
+    pub fn lchown(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            ctx.args.get(1),
+            ctx.args.get(2),
+            libc::AT_SYMLINK_NOFOLLOW.into(),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_fchownat, ctx, &args)
+    }
+
+    log_syscall!(link, /* rv */ std::ffi::c_int);
+    pub fn link(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            libc::AT_FDCWD.into(),
+            ctx.args.get(1),
+            0.into(),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_linkat, ctx, &args)
+    }
+
+    log_syscall!(mkdir, /* rv */ std::ffi::c_int);
+    pub fn mkdir(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [libc::AT_FDCWD.into(), ctx.args.get(0), ctx.args.get(1)];
+        Self::legacy_at_syscall(cshadow::syscallhandler_mkdirat, ctx, &args)
+    }
+
+    log_syscall!(mknod, /* rv */ std::ffi::c_int);
+    pub fn mknod(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            ctx.args.get(1),
+            ctx.args.get(2),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_mknodat, ctx, &args)
+    }
+
+    log_syscall!(readlink, /* rv */ std::ffi::c_int);
+    pub fn readlink(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            ctx.args.get(1),
+            ctx.args.get(2),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_readlinkat, ctx, &args)
+    }
+
+    log_syscall!(rename, /* rv */ std::ffi::c_int);
+    pub fn rename(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            libc::AT_FDCWD.into(),
+            ctx.args.get(1),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_renameat, ctx, &args)
+    }
+
+    log_syscall!(rmdir, /* rv */ std::ffi::c_int);
+    pub fn rmdir(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            libc::AT_REMOVEDIR.into(),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_unlinkat, ctx, &args)
+    }
+
+    log_syscall!(symlink, /* rv */ std::ffi::c_int);
+    pub fn symlink(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [ctx.args.get(0), libc::AT_FDCWD.into(), ctx.args.get(1)];
+        Self::legacy_at_syscall(cshadow::syscallhandler_symlinkat, ctx, &args)
+    }
+
+    log_syscall!(unlink, /* rv */ std::ffi::c_int);
+    pub fn unlink(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [libc::AT_FDCWD.into(), ctx.args.get(0), 0.into()];
+        Self::legacy_at_syscall(cshadow::syscallhandler_unlinkat, ctx, &args)
+    }
+
+    log_syscall!(utime, /* rv */ std::ffi::c_int);
+    pub fn utime(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_utime, ctx)
+    }
+
+    log_syscall!(utimes, /* rv */ std::ffi::c_int);
+    pub fn utimes(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [libc::AT_FDCWD.into(), ctx.args.get(0), ctx.args.get(1)];
+        Self::legacy_at_syscall(cshadow::syscallhandler_futimesat, ctx, &args)
+    }
+}
+
+/// Checks that the `faccessat` mode is `F_OK` or a combination of `R_OK`, `W_OK`, and `X_OK`.
+fn check_access_mode(mode: std::ffi::c_int) -> Result<(), Errno> {
+    if mode & !(libc::F_OK | libc::R_OK | libc::W_OK | libc::X_OK) != 0 {
+        log::debug!("Invalid faccessat mode: {mode}");
+        return Err(Errno::EINVAL);
+    }
+    Ok(())
 }
@@ -7,6 +7,7 @@
 
 #include <errno.h>
 #include <inttypes.h>
+#include <limits.h>
 #include <linux/futex.h>
 #include <stdbool.h>
 #include <sys/time.h>
@@ -22,10 +23,46 @@
 // Helpers
 ///////////////////////////////////////////////////////////
 
+// Remove the futex from the table if no threads are waiting on it anymore.
+static void _syscallhandler_futexRemoveIfUnused(FutexTable* ftable, Futex* futex) {
+    if (futex_getListenerCount(futex) != 0 || futex_getPiWaiterCount(futex) != 0) {
+        return;
+    }
+
+    ManagedPhysicalMemoryAddr futexPPtr = futex_getAddress(futex);
+    if (futextable_get(ftable, futexPPtr) == futex) {
+        trace("Dynamically freed a futex object for futex addr %p", (void*)futexPPtr.val);
+        futextable_remove(ftable, futexPPtr);
+    }
+}
+
+// Get the futex at the address, creating it and adding it to the table if it doesn't exist. The
+// returned futex is borrowed from the table.
+static Futex* _syscallhandler_futexGetOrCreate(FutexTable* ftable,
+                                               ManagedPhysicalMemoryAddr futexPPtr) {
+    Futex* futex = futextable_get(ftable, futexPPtr);
+
+    if (!futex) {
+        trace("Dynamically created a new futex object for futex addr %p", (void*)futexPPtr.val);
+        futex = futex_new(futexPPtr);
+        bool success = futextable_add(ftable, futex);
+        utility_debugAssert(success);
+        // the table holds its own reference
+        futex_unref(futex);
+    }
+
+    return futex;
+}
+
 static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
                                                      UntypedForeignPtr futexVPtr, int expectedVal,
                                                      UntypedForeignPtr timeoutVPtr,
-                                                     TimeoutType type) {
+                                                     TimeoutType type, uint32_t bitset) {
+    // `man 2 futex`: EINVAL The operation is FUTEX_WAIT_BITSET and the bitset is zero
+    if (bitset == 0) {
+        return syscallreturn_makeDoneErrno(EINVAL);
+    }
+
     // This is a new wait operation on the futex for this thread.
     // Check if a timeout was given in the syscall args.
     CSimulationTime timeoutSimTime = SIMTIME_INVALID;
@@ -63,16 +100,16 @@ static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
     ManagedPhysicalMemoryAddr futexPPtr =
         process_getPhysicalAddress(rustsyscallhandler_getProcess(sys), futexVPtr);
 
-    // Check if we already have a futex
     FutexTable* ftable = host_getFutexTable(rustsyscallhandler_getHost(sys));
-    Futex* futex = futextable_get(ftable, futexPPtr);
-
-    if (futex != NULL) {
-        futex_ref(futex);
-    }
 
     if (rustsyscallhandler_wasBlocked(sys)) {
+        // A FUTEX_REQUEUE may have moved us from the futex at `futexVPtr` to a different futex, so
+        // use the futex that our condition was last waiting on.
+        SysCallCondition* cond = thread_getSysCallCondition(rustsyscallhandler_getThread(sys));
+        Futex* futex = cond ? syscallcondition_getFutex(cond) : NULL;
         utility_debugAssert(futex != NULL);
+        futex_ref(futex);
+        futexPPtr = futex_getAddress(futex);
         int result = 0;
 
         // We already blocked on wait, so this is either a timeout or wakeup
@@ -92,16 +129,29 @@ static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
         }
 
         // Dynamically clean up the futex if needed
-        if (futex_getListenerCount(futex) == 0) {
-            trace("Dynamically freed a futex object for futex addr %p", (void*)futexPPtr.val);
-            bool success = futextable_remove(ftable, futexPPtr);
-            utility_debugAssert(success);
-        }
+        _syscallhandler_futexRemoveIfUnused(ftable, futex);
 
         futex_unref(futex);
         return syscallreturn_makeDoneI64(result);
     }
 
+    // Check if we already have a futex
+    Futex* futex = futextable_get(ftable, futexPPtr);
+
+    if (futex != NULL) {
+        futex_ref(futex);
+    }
+
+    // An absolute timeout may have already passed, in which case we shouldn't block at all.
+    if (timeoutSimTime != SIMTIME_INVALID && type == TIMEOUT_ABSOLUTE &&
+        timeoutSimTime <= worker_getCurrentEmulatedTime()) {
+        trace("Futex %p timeout already expired", (void*)futexPPtr.val);
+        if (futex != NULL) {
+            futex_unref(futex);
+        }
+        return syscallreturn_makeDoneErrno(ETIMEDOUT);
+    }
+
     // We'll need to block, dynamically create a futex if one does not yet exist
     if (!futex) {
         trace("Dynamically created a new futex object for futex addr %p", (void*)futexPPtr.val);
@@ -116,6 +166,7 @@ static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
     Trigger trigger =
         (Trigger){.type = TRIGGER_FUTEX, .object = futex, .state = FileState_FUTEX_WAKEUP};
     SysCallCondition* cond = syscallcondition_new(trigger);
+    syscallcondition_setFutexBitset(cond, bitset);
     if (timeoutSimTime != SIMTIME_INVALID) {
         CEmulatedTime timeoutEmulatedTime = (type == TIMEOUT_RELATIVE)
                                                 ? timeoutSimTime + worker_getCurrentEmulatedTime()
@@ -128,7 +179,13 @@ static SyscallReturn _syscallhandler_futexWaitHelper(SyscallHandler* sys,
 }
 
 static SyscallReturn _syscallhandler_futexWakeHelper(SyscallHandler* sys,
-                                                     UntypedForeignPtr futexVPtr, int numWakeups) {
+                                                     UntypedForeignPtr futexVPtr, int numWakeups,
+                                                     uint32_t bitset) {
+    // `man 2 futex`: EINVAL The operation is FUTEX_WAKE_BITSET and the bitset is zero
+    if (bitset == 0) {
+        return syscallreturn_makeDoneErrno(EINVAL);
+    }
+
     // Convert the virtual ptr to a physical ptr that can uniquely identify the futex
     ManagedPhysicalMemoryAddr futexPPtr =
         process_getPhysicalAddress(rustsyscallhandler_getProcess(sys), futexVPtr);
@@ -142,13 +199,305 @@ static SyscallReturn _syscallhandler_futexWakeHelper(SyscallHandler* sys,
     unsigned int numWoken = 0;
     if (futex && numWakeups > 0) {
         trace("Futex trying to perform %i wakeups", numWakeups);
-        numWoken = futex_wake(futex, (unsigned int)numWakeups);
+        numWoken = futex_wake(futex, (unsigned int)numWakeups, bitset);
         trace("Futex was able to perform %i/%i wakeups", numWoken, numWakeups);
     }
 
     return syscallreturn_makeDoneU64(numWoken);
 }
 
+static SyscallReturn _syscallhandler_futexRequeueHelper(SyscallHandler* sys,
+                                                        UntypedForeignPtr futexVPtr, int numWakeups,
+                                                        int numRequeues,
+                                                        UntypedForeignPtr futex2VPtr,
+                                                        bool compare, uint32_t expectedVal) {
+    // Linux rejects negative counts rather than treating them as large unsigned values
+    if (numWakeups < 0 || numRequeues < 0) {
+        return syscallreturn_makeDoneErrno(EINVAL);
+    }
+
+    const Process* proc = rustsyscallhandler_getProcess(sys);
+
+    // `man 2 futex`: EAGAIN (FUTEX_CMP_REQUEUE) The value pointed to by uaddr is not equal to
+    // the expected value val3.
+    if (compare) {
+        uint32_t futexVal;
+        int result = process_readPtr(proc, &futexVal, futexVPtr, sizeof(futexVal));
+        if (result) {
+            warning("Couldn't read futex address %p", (void*)futexVPtr.val);
+            return syscallreturn_makeDoneErrno(-result);
+        }
+
+        if (futexVal != expectedVal) {
+            trace("Futex value %" PRIu32 " doesn't match expected value %" PRIu32, futexVal,
+                  expectedVal);
+            return syscallreturn_makeDoneErrno(EAGAIN);
+        }
+    }
+
+    FutexTable* ftable = host_getFutexTable(rustsyscallhandler_getHost(sys));
+    Futex* futex = futextable_get(ftable, process_getPhysicalAddress(proc, futexVPtr));
+
+    // No one is waiting, so there's nothing to wake or requeue
+    if (!futex) {
+        return syscallreturn_makeDoneU64(0);
+    }
+
+    // Requeueing from or to a PI futex requires FUTEX_CMP_REQUEUE_PI
+    ManagedPhysicalMemoryAddr futex2PPtr = process_getPhysicalAddress(proc, futex2VPtr);
+    Futex* target = futextable_get(ftable, futex2PPtr);
+    if (futex_getPiWaiterCount(futex) > 0 || (target && futex_getPiWaiterCount(target) > 0)) {
+        return syscallreturn_makeDoneErrno(EINVAL);
+    }
+
+    futex_ref(futex);
+
+    unsigned int numWoken = 0;
+    if (numWakeups > 0) {
+        numWoken = futex_wake(futex, (unsigned int)numWakeups, FUTEX_BITSET_MATCH_ANY);
+    }
+
+    unsigned int numRequeued = 0;
+    if (numRequeues > 0) {
+        target = _syscallhandler_futexGetOrCreate(ftable, futex2PPtr);
+        futex_ref(target);
+
+        numRequeued = futex_requeue(futex, target, (unsigned int)numRequeues);
+
+        _syscallhandler_futexRemoveIfUnused(ftable, target);
+        futex_unref(target);
+    }
+
+    trace("Futex woke %u and requeued %u waiters", numWoken, numRequeued);
+
+    // the threads that were woken up will clean up the futex, but if every waiter was requeued
+    // then no one else will
+    _syscallhandler_futexRemoveIfUnused(ftable, futex);
+    futex_unref(futex);
+
+    // FUTEX_REQUEUE only returns the number of woken waiters
+    return syscallreturn_makeDoneU64(compare ? numWoken + numRequeued : numWoken);
+}
+
+// Try to take the PI futex for the calling thread. Follows the kernel's convention that the futex
+// word holds the TID of the owner, or 0 if it's unowned. Returns 0 if the lock was taken, -EAGAIN
+// if another thread owns it, or another negative errno on error. The futex word is returned in
+// `futexValOut`.
+static int _syscallhandler_futexPiTryAcquire(SyscallHandler* sys, UntypedForeignPtr futexVPtr,
+                                             bool isPrivate, uint32_t* futexValOut) {
+    const Process* proc = rustsyscallhandler_getProcess(sys);
+    pid_t tid = thread_getID(rustsyscallhandler_getThread(sys));
+
+    uint32_t futexVal;
+    int rv = process_readPtr(proc, &futexVal, futexVPtr, sizeof(futexVal));
+    if (rv) {
+        warning("Couldn't read futex address %p", (void*)futexVPtr.val);
+        return rv;
+    }
+    *futexValOut = futexVal;
+
+    pid_t owner = futexVal & FUTEX_TID_MASK;
+
+    if (owner == 0) {
+        // Keep the owner-died bit so that a robust mutex can report that the previous owner died.
+        uint32_t newVal = (uint32_t)tid | (futexVal & FUTEX_OWNER_DIED);
+        rv = process_writePtr(proc, futexVPtr, &newVal, sizeof(newVal));
+        if (rv) {
+            return rv;
+        }
+        *futexValOut = newVal;
+        return 0;
+    }
+
+    if (owner == tid) {
+        return -EDEADLK;
+    }
+
+    // We can only look up the owner for futexes that aren't shared with other processes.
+    if (isPrivate && process_getThread(proc, owner) == NULL) {
+        trace("PI futex owner %d doesn't exist", owner);
+        return -ESRCH;
+    }
+
+    return -EAGAIN;
+}
+
+static SyscallReturn _syscallhandler_futexBlockPi(Futex* futex, CSimulationTime timeoutSimTime) {
+    Trigger trigger =
+        (Trigger){.type = TRIGGER_FUTEX, .object = futex, .state = FileState_FUTEX_WAKEUP};
+    SysCallCondition* cond = syscallcondition_new(trigger);
+    if (timeoutSimTime != SIMTIME_INVALID) {
+        // the FUTEX_LOCK_PI timeout is always absolute
+        syscallcondition_setTimeout(cond, timeoutSimTime);
+    }
+
+    // the kernel always restarts FUTEX_LOCK_PI after a signal handler rather than returning EINTR
+    return syscallreturn_makeBlocked(cond, true);
+}
+
+static SyscallReturn _syscallhandler_futexLockPiHelper(SyscallHandler* sys,
+                                                       UntypedForeignPtr futexVPtr,
+                                                       UntypedForeignPtr timeoutVPtr,
+                                                       bool isPrivate) {
+    const Process* proc = rustsyscallhandler_getProcess(sys);
+    pid_t tid = thread_getID(rustsyscallhandler_getThread(sys));
+
+    CSimulationTime timeoutSimTime = SIMTIME_INVALID;
+    if (timeoutVPtr.val) {
+        struct timespec ts = {0};
+        int rv = process_readPtr(proc, &ts, timeoutVPtr, sizeof(ts));
+        if (rv < 0) {
+            return syscallreturn_makeDoneErrno(-rv);
+        }
+        timeoutSimTime = simtime_from_timespec(ts);
+        if (timeoutSimTime == SIMTIME_INVALID) {
+            return syscallreturn_makeDoneErrno(EINVAL);
+        }
+    }
+
+    ManagedPhysicalMemoryAddr futexPPtr = process_getPhysicalAddress(proc, futexVPtr);
+    FutexTable* ftable = host_getFutexTable(rustsyscallhandler_getHost(sys));
+    Futex* futex = futextable_get(ftable, futexPPtr);
+
+    if (rustsyscallhandler_wasBlocked(sys)) {
+        utility_debugAssert(futex != NULL);
+        futex_ref(futex);
+
+        uint32_t futexVal = 0;
+        int result = process_readPtr(proc, &futexVal, futexVPtr, sizeof(futexVal));
+
+        if (result != 0) {
+            warning("Couldn't read futex address %p", (void*)futexVPtr.val);
+            futex_removePiWaiter(futex, tid);
+        } else if ((futexVal & FUTEX_TID_MASK) == tid) {
+            // FUTEX_UNLOCK_PI handed the lock to us, even if we also timed out or were interrupted
+            trace("PI futex %p was handed to thread %d", (void*)futexPPtr.val, tid);
+        } else if (timeoutSimTime != SIMTIME_INVALID &&
+                   (rustsyscallhandler_didListenTimeoutExpire(sys) ||
+                    timeoutSimTime <= worker_getCurrentEmulatedTime())) {
+            trace("PI futex %p timed out while waiting", (void*)futexPPtr.val);
+            futex_removePiWaiter(futex, tid);
+            result = -ETIMEDOUT;
+        } else if (thread_unblockedSignalPending(
+                       rustsyscallhandler_getThread(sys),
+                       host_getShimShmemLock(rustsyscallhandler_getHost(sys)))) {
+            trace("PI futex %p has been interrupted by a signal", (void*)futexPPtr.val);
+            futex_removePiWaiter(futex, tid);
+            result = -EINTR;
+        } else {
+            // The lock was handed to a different waiter, so keep waiting.
+            utility_debugAssert(futex_getPiWaiterCount(futex) > 0);
+            SyscallReturn ret = _syscallhandler_futexBlockPi(futex, timeoutSimTime);
+            futex_unref(futex);
+            return ret;
+        }
+
+        if (futex_getListenerCount(futex) == 0 && futex_getPiWaiterCount(futex) == 0) {
+            trace("Dynamically freed a futex object for futex addr %p", (void*)futexPPtr.val);
+            bool success = futextable_remove(ftable, futexPPtr);
+            utility_debugAssert(success);
+        }
+        futex_unref(futex);
+
+        if (result == -EINTR) {
+            return syscallreturn_makeInterrupted(true);
+        }
+        return syscallreturn_makeDoneI64(result);
+    }
+
+    uint32_t futexVal = 0;
+    int result = _syscallhandler_futexPiTryAcquire(sys, futexVPtr, isPrivate, &futexVal);
+    if (result != -EAGAIN) {
+        return syscallreturn_makeDoneI64(result);
+    }
+
+    // Set the waiters bit so that the owner's unlock goes through FUTEX_UNLOCK_PI rather than
+    // just clearing the futex word.
+    if (!(futexVal & FUTEX_WAITERS)) {
+        futexVal |= FUTEX_WAITERS;
+        result = process_writePtr(proc, futexVPtr, &futexVal, sizeof(futexVal));
+        if (result) {
+            return syscallreturn_makeDoneErrno(-result);
+        }
+    }
+
+    // Like FUTEX_WAIT_BITSET, don't block at all if the absolute timeout already passed. Linux
+    // still leaves the waiters bit set.
+    if (timeoutSimTime != SIMTIME_INVALID && timeoutSimTime <= worker_getCurrentEmulatedTime()) {
+        trace("PI futex %p timeout already expired", (void*)futexPPtr.val);
+        return syscallreturn_makeDoneErrno(ETIMEDOUT);
+    }
+
+    if (!futex) {
+        trace("Dynamically created a new futex object for futex addr %p", (void*)futexPPtr.val);
+        futex = futex_new(futexPPtr);
+        bool success = futextable_add(ftable, futex);
+        utility_debugAssert(success);
+    } else {
+        futex_ref(futex);
+    }
+
+    trace("PI futex %p owned by %d, thread %d blocking %s timeout", (void*)futexPPtr.val,
+          futexVal & FUTEX_TID_MASK, tid, timeoutSimTime != SIMTIME_INVALID ? "with" : "without");
+    futex_addPiWaiter(futex, tid);
+    SyscallReturn ret = _syscallhandler_futexBlockPi(futex, timeoutSimTime);
+
+    futex_unref(futex);
+    return ret;
+}
+
+static SyscallReturn _syscallhandler_futexTryLockPiHelper(SyscallHandler* sys,
+                                                          UntypedForeignPtr futexVPtr,
+                                                          bool isPrivate) {
+    uint32_t futexVal = 0;
+    int result = _syscallhandler_futexPiTryAcquire(sys, futexVPtr, isPrivate, &futexVal);
+    return syscallreturn_makeDoneI64(result);
+}
+
+static SyscallReturn _syscallhandler_futexUnlockPiHelper(SyscallHandler* sys,
+                                                         UntypedForeignPtr futexVPtr) {
+    const Process* proc = rustsyscallhandler_getProcess(sys);
+    pid_t tid = thread_getID(rustsyscallhandler_getThread(sys));
+
+    uint32_t futexVal;
+    int result = process_readPtr(proc, &futexVal, futexVPtr, sizeof(futexVal));
+    if (result) {
+        warning("Couldn't read futex address %p", (void*)futexVPtr.val);
+        return syscallreturn_makeDoneErrno(-result);
+    }
+
+    if ((futexVal & FUTEX_TID_MASK) != tid) {
+        return syscallreturn_makeDoneErrno(EPERM);
+    }
+
+    ManagedPhysicalMemoryAddr futexPPtr = process_getPhysicalAddress(proc, futexVPtr);
+    FutexTable* ftable = host_getFutexTable(rustsyscallhandler_getHost(sys));
+    Futex* futex = futextable_get(ftable, futexPPtr);
+
+    // Like the kernel, hand the lock directly to the longest waiting thread rather than letting
+    // the waiters race for it.
+    pid_t newOwner = futex ? futex_popPiWaiter(futex) : 0;
+    uint32_t newVal = 0;
+    if (newOwner != 0) {
+        newVal = (uint32_t)newOwner | FUTEX_WAITERS;
+    }
+
+    result = process_writePtr(proc, futexVPtr, &newVal, sizeof(newVal));
+    if (result) {
+        return syscallreturn_makeDoneErrno(-result);
+    }
+
+    if (newOwner != 0) {
+        trace("PI futex %p handed from thread %d to thread %d", (void*)futexPPtr.val, tid,
+              newOwner);
+        // We can't wake a specific listener, so wake all of them. The waiters that weren't given
+        // the lock will block again.
+        futex_wake(futex, UINT_MAX, FUTEX_BITSET_MATCH_ANY);
+    }
+
+    return syscallreturn_makeDoneI64(0);
+}
+
 ///////////////////////////////////////////////////////////
 // System Calls
 ///////////////////////////////////////////////////////////
@@ -178,38 +527,61 @@ SyscallReturn syscallhandler_futex(SyscallHandler* sys, const SyscallArgs* args)
         case FUTEX_WAIT: {
             trace("Handling FUTEX_WAIT operation %i", operation);
             return _syscallhandler_futexWaitHelper(
-                sys, uaddrptr, val, timeoutptr, TIMEOUT_RELATIVE);
+                sys, uaddrptr, val, timeoutptr, TIMEOUT_RELATIVE, FUTEX_BITSET_MATCH_ANY);
         }
 
         case FUTEX_WAKE: {
             trace("Handling FUTEX_WAKE operation %i", operation);
-            return _syscallhandler_futexWakeHelper(sys, uaddrptr, val);
+            return _syscallhandler_futexWakeHelper(sys, uaddrptr, val, FUTEX_BITSET_MATCH_ANY);
         }
 
+        // The bitset operations use an absolute timeout, measured against CLOCK_REALTIME if
+        // FUTEX_CLOCK_REALTIME is set and CLOCK_MONOTONIC otherwise, which are the same in shadow.
         case FUTEX_WAIT_BITSET: {
-            trace("Handling FUTEX_WAIT_BITSET operation %i bitset %d", operation, val3);
-            if (val3 == FUTEX_BITSET_MATCH_ANY) {
-                return _syscallhandler_futexWaitHelper(
-                    sys, uaddrptr, val, timeoutptr, TIMEOUT_ABSOLUTE);
-            }
-            // Other bitsets not yet handled.
-            break;
+            trace("Handling FUTEX_WAIT_BITSET operation %i bitset %#x", operation, (uint32_t)val3);
+            return _syscallhandler_futexWaitHelper(
+                sys, uaddrptr, val, timeoutptr, TIMEOUT_ABSOLUTE, (uint32_t)val3);
         }
         case FUTEX_WAKE_BITSET: {
-            trace("Handling FUTEX_WAKE_BITSET operation %i bitset %d", operation, val3);
-            if (val3 == FUTEX_BITSET_MATCH_ANY) {
-                return _syscallhandler_futexWakeHelper(sys, uaddrptr, val);
-            }
-            // Other bitsets not yet handled.
-            break;
+            trace("Handling FUTEX_WAKE_BITSET operation %i bitset %#x", operation, (uint32_t)val3);
+            return _syscallhandler_futexWakeHelper(sys, uaddrptr, val, (uint32_t)val3);
+        }
+
+#ifdef FUTEX_LOCK_PI2
+        // FUTEX_LOCK_PI2 only differs in measuring the timeout against CLOCK_MONOTONIC, which in
+        // shadow is the same as CLOCK_REALTIME.
+        case FUTEX_LOCK_PI2:
+#endif
+        case FUTEX_LOCK_PI: {
+            trace("Handling FUTEX_LOCK_PI operation %i", operation);
+            return _syscallhandler_futexLockPiHelper(
+                sys, uaddrptr, timeoutptr, options & FUTEX_PRIVATE_FLAG);
+        }
+        case FUTEX_TRYLOCK_PI: {
+            trace("Handling FUTEX_TRYLOCK_PI operation %i", operation);
+            return _syscallhandler_futexTryLockPiHelper(
+                sys, uaddrptr, options & FUTEX_PRIVATE_FLAG);
+        }
+        case FUTEX_UNLOCK_PI: {
+            trace("Handling FUTEX_UNLOCK_PI operation %i", operation);
+            return _syscallhandler_futexUnlockPiHelper(sys, uaddrptr);
+        }
+
+        // For the requeue operations, the `timeout` argument is used as `val2`, the maximum
+        // number of waiters to requeue.
+        case FUTEX_REQUEUE: {
+            trace("Handling FUTEX_REQUEUE operation %i", operation);
+            return _syscallhandler_futexRequeueHelper(
+                sys, uaddrptr, val, (int)(uint32_t)timeoutptr.val, uaddr2ptr, false, 0);
+        }
+        case FUTEX_CMP_REQUEUE: {
+            trace("Handling FUTEX_CMP_REQUEUE operation %i", operation);
+            return _syscallhandler_futexRequeueHelper(
+                sys, uaddrptr, val, (int)(uint32_t)timeoutptr.val, uaddr2ptr, true, (uint32_t)val3);
         }
 
         case FUTEX_FD:
-        case FUTEX_REQUEUE:
         case FUTEX_WAKE_OP:
-        case FUTEX_LOCK_PI:
-        case FUTEX_TRYLOCK_PI:
-        case FUTEX_UNLOCK_PI:
         case FUTEX_CMP_REQUEUE_PI:
         case FUTEX_WAIT_REQUEUE_PI: break;
     }
@@ -1,9 +1,11 @@
 use linux_api::errno::Errno;
+use shadow_shim_helper_rs::explicit_drop::{ExplicitDrop, ExplicitDropper};
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::SyscallError;
+use crate::host::thread::ThreadId;
 
 impl SyscallHandler {
     log_syscall!(
@@ -36,13 +38,32 @@ impl SyscallHandler {
         /* len_ptr */ *const libc::size_t,
     );
     pub fn get_robust_list(
-        _ctx: &mut SyscallContext,
-        _pid: std::ffi::c_int,
-        _head_ptr: ForeignPtr<ForeignPtr<linux_api::futex::robust_list_head>>,
-        _len_ptr: ForeignPtr<libc::size_t>,
+        ctx: &mut SyscallContext,
+        pid: std::ffi::c_int,
+        head_ptr: ForeignPtr<ForeignPtr<linux_api::futex::robust_list_head>>,
+        len_ptr: ForeignPtr<libc::size_t>,
     ) -> Result<(), Errno> {
-        warn_once_then_debug!("get_robust_list was called but we don't yet support it");
-        Err(Errno::ENOSYS)
+        let head = if pid == 0 {
+            ctx.objs.thread.get_robust_list()
+        } else {
+            let tid = ThreadId::try_from(pid).or(Err(Errno::ESRCH))?;
+            let Some(thread) = ctx.objs.host.thread_cloned_rc(tid) else {
+                return Err(Errno::ESRCH);
+            };
+            let thread =
+                ExplicitDropper::new(thread, |value| value.explicit_drop(ctx.objs.host.root()));
+            let thread = thread.borrow(ctx.objs.host.root());
+            thread.get_robust_list()
+        };
+
+        let mut mem = ctx.objs.process.memory_borrow_mut();
+        mem.write(head_ptr, &head)?;
+        mem.write(
+            len_ptr,
+            &std::mem::size_of::<linux_api::futex::robust_list_head>(),
+        )?;
+
+        Ok(())
     }
 
     log_syscall!(
@@ -52,11 +73,17 @@ impl SyscallHandler {
         /* len */ libc::size_t,
     );
     pub fn set_robust_list(
-        _ctx: &mut SyscallContext,
-        _head: ForeignPtr<linux_api::futex::robust_list_head>,
-        _len: libc::size_t,
+        ctx: &mut SyscallContext,
+        head: ForeignPtr<linux_api::futex::robust_list_head>,
+        len: libc::size_t,
     ) -> Result<(), Errno> {
-        warn_once_then_debug!("set_robust_list was called but we don't yet support it");
-        Err(Errno::ENOSYS)
+        if len != std::mem::size_of::<linux_api::futex::robust_list_head>() {
+            return Err(Errno::EINVAL);
+        }
+
+        // the list is only read when the thread exits
+        ctx.objs.thread.set_robust_list(head);
+
+        Ok(())
     }
 }
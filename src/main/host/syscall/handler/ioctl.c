@@ -9,6 +9,7 @@
 #include <linux/sockios.h>
 #include <stdbool.h>
 #include <sys/ioctl.h>
+#include <sys/mount.h>
 
 #include "lib/logger/logger.h"
 #include "main/bindings/c/bindings.h"
@@ -45,6 +46,33 @@ static int _syscallhandler_ioctlFileHelper(SyscallHandler* sys, RegularFile* fil
             break;
         }
 
+        case BLKGETSIZE64: {
+            uint64_t size = 0;
+            uint32_t sectorSize = 0;
+            if (!regularfile_getBlockDevice(file, &size, &sectorSize)) {
+                // not a block device
+                result = -ENOTTY;
+                break;
+            }
+            result = process_writePtr(
+                rustsyscallhandler_getProcess(sys), argPtr, &size, sizeof(size));
+            break;
+        }
+
+        case BLKSSZGET: {
+            uint64_t size = 0;
+            uint32_t sectorSize = 0;
+            if (!regularfile_getBlockDevice(file, &size, &sectorSize)) {
+                // not a block device
+                result = -ENOTTY;
+                break;
+            }
+            int value = sectorSize;
+            result = process_writePtr(
+                rustsyscallhandler_getProcess(sys), argPtr, &value, sizeof(value));
+            break;
+        }
+
         default: {
             result = -EINVAL;
             warning("We do not yet handle ioctl request %lu on file %i",
@@ -258,6 +258,19 @@ impl SyscallHandler {
 
         // delegate execution of the mmap itself to the memory manager
         let mut memory_manager = ctx.objs.process.memory_borrow_mut();
+
+        // with deterministic ASLR, mappings that don't request an address are placed at addresses
+        // derived from the host's seeded rng rather than where the kernel would place them
+        let fixed_flags = MapFlags::MAP_FIXED | MapFlags::MAP_FIXED_NOREPLACE;
+        let addr = if ctx.objs.host.params.use_deterministic_aslr
+            && addr.is_null()
+            && !flags.intersects(fixed_flags)
+        {
+            memory_manager.aslr_mmap_hint(len, &mut *ctx.objs.host.random_mut())
+        } else {
+            addr
+        };
+
         let mmap_result = memory_manager.do_mmap(
             ctx.objs,
             addr,
@@ -5,7 +5,10 @@ use std::time::Duration;
 
 use linux_api::errno::Errno;
 use linux_api::syscall::SyscallNum;
+use shadow_pod::Pod;
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
 use shadow_shim_helper_rs::simulation_time::SimulationTime;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 use shadow_shim_helper_rs::syscall_types::SyscallArgs;
 use shadow_shim_helper_rs::syscall_types::SyscallReg;
 use shadow_shim_helper_rs::util::SendPointer;
@@ -19,6 +22,7 @@ use crate::host::descriptor::Descriptor;
 use crate::host::process::ProcessId;
 use crate::host::syscall::formatter::log_syscall_simple;
 use crate::host::syscall::is_shadow_syscall;
+use crate::host::syscall::types::ForeignArrayPtr;
 use crate::host::syscall::types::SyscallReturn;
 use crate::host::syscall::types::{SyscallError, SyscallResult};
 use crate::host::thread::ThreadId;
@@ -29,6 +33,7 @@ use crate::utility::perf_timer::PerfTimer;
 
 mod clone;
 mod close_range;
+mod copy_file_range;
 mod epoll;
 mod eventfd;
 mod fcntl;
@@ -37,15 +42,19 @@ mod fileat;
 mod futex;
 mod ioctl;
 mod mman;
+mod pidfd;
 mod poll;
+mod posix_timer;
 mod prctl;
 mod random;
 mod resource;
 mod sched;
 mod select;
 mod shadow;
+mod shm;
 mod signal;
 mod socket;
+mod splice;
 mod stat;
 mod sysinfo;
 mod time;
@@ -69,6 +78,9 @@ pub struct SyscallHandler {
     num_syscalls: u64,
     /// A counter for individual syscalls.
     syscall_counter: Option<Counter>,
+    /// The names of the host and process that `syscall_counter` belongs to. Set when the first
+    /// syscall is counted.
+    syscall_counter_owner: Option<(String, String)>,
     /// If we are currently blocking a specific syscall, i.e., waiting for a socket to be
     /// readable/writable or waiting for a timeout, the syscall number of that function is stored
     /// here. Will be `None` if a syscall is not currently blocked.
@@ -77,6 +89,16 @@ pub struct SyscallHandler {
     /// forward. This stores the result of the completed syscall, to be returned when the caller
     /// resumes.
     pending_result: Option<SyscallResult>,
+    /// The number of bytes that a blocked `MSG_WAITALL` recv has received so far. Will be 0 if a
+    /// syscall is not currently blocked.
+    waitall_bytes_received: libc::size_t,
+    /// The number of bytes that a blocked write or send to a pipe or stream socket has written so
+    /// far. Will be 0 if a syscall is not currently blocked.
+    bytes_written: libc::size_t,
+    /// The time at which a blocked send, recv, or close gives up due to the socket's
+    /// `SO_SNDTIMEO`, `SO_RCVTIMEO`, or `SO_LINGER` timeout. Will be `None` if a syscall is not
+    /// currently blocked.
+    socket_deadline: Option<EmulatedTime>,
     /// We use this epoll to service syscalls that need to block on the status of multiple
     /// descriptors, like poll.
     epoll: SendPointer<c::Epoll>,
@@ -102,8 +124,12 @@ impl SyscallHandler {
             thread_id,
             num_syscalls: 0,
             syscall_counter: count_syscalls.then(Counter::new),
+            syscall_counter_owner: None,
             blocked_syscall: None,
             pending_result: None,
+            waitall_bytes_received: 0,
+            bytes_written: 0,
+            socket_deadline: None,
             epoll: unsafe { SendPointer::new(c::epoll_new()) },
             #[cfg(feature = "perf_timers")]
             perf_duration_current: Duration::ZERO,
@@ -164,6 +190,11 @@ impl SyscallHandler {
             if !was_blocked {
                 syscall_counter.add_one(syscall_name);
             }
+
+            if self.syscall_counter_owner.is_none() {
+                let process_name = format!("{}.{}", &*ctx.process.plugin_name(), self.process_id);
+                self.syscall_counter_owner = Some((ctx.host.name().to_string(), process_name));
+            }
         }
 
         #[cfg(feature = "perf_timers")]
@@ -275,10 +306,16 @@ impl SyscallHandler {
             // latter are part of Shadow's internal plumbing; they shouldn't necessarily "consume"
             // time
             if !is_shadow_syscall(syscall) {
-                ctx.host
-                    .shim_shmem_lock_borrow_mut()
-                    .unwrap()
-                    .unapplied_cpu_latency += ctx.host.shim_shmem().unblocked_syscall_latency;
+                let latency = ctx.host.shim_shmem().unblocked_syscall_latency;
+                let mut host_shmem = ctx.host.shim_shmem_lock_borrow_mut().unwrap();
+                host_shmem.unapplied_cpu_latency += latency;
+
+                // the latency is also the CPU time that the thread used for the syscall
+                let thread_shmem = ctx.thread.shmem();
+                thread_shmem
+                    .protected
+                    .borrow_mut(&host_shmem.root)
+                    .system_cpu_time += latency;
             }
 
             let unapplied_cpu_latency = ctx
@@ -323,6 +360,9 @@ impl SyscallHandler {
             self.blocked_syscall = Some(syscall);
         } else {
             self.blocked_syscall = None;
+            self.waitall_bytes_received = 0;
+            self.bytes_written = 0;
+            self.socket_deadline = None;
         }
 
         rv
@@ -335,6 +375,14 @@ impl SyscallHandler {
             SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_init_memory_manager);
         const NR_shadow_hostname_to_addr_ipv4: SyscallNum =
             SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_hostname_to_addr_ipv4);
+        const NR_shadow_tcp_cc_state: SyscallNum =
+            SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_tcp_cc_state);
+        const NR_shadow_query_feature: SyscallNum =
+            SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_query_feature);
+        const NR_shadow_get_host_name: SyscallNum =
+            SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_get_host_name);
+        const NR_shadow_pin_cpu: SyscallNum =
+            SyscallNum::new(c::ShadowSyscallNum_SYS_shadow_pin_cpu);
 
         let mut ctx = SyscallContext {
             objs: ctx,
@@ -373,19 +421,26 @@ impl SyscallHandler {
             //
             SyscallNum::NR_accept => handle!(accept),
             SyscallNum::NR_accept4 => handle!(accept4),
+            SyscallNum::NR_access => handle!(access),
+            SyscallNum::NR_adjtimex => handle!(adjtimex),
             SyscallNum::NR_alarm => handle!(alarm),
             SyscallNum::NR_bind => handle!(bind),
             SyscallNum::NR_brk => handle!(brk),
             SyscallNum::NR_capget => handle!(capget),
             SyscallNum::NR_capset => handle!(capset),
             SyscallNum::NR_chdir => handle!(chdir),
+            SyscallNum::NR_chmod => handle!(chmod),
+            SyscallNum::NR_chown => handle!(chown),
+            SyscallNum::NR_clock_adjtime => handle!(clock_adjtime),
             SyscallNum::NR_clock_getres => handle!(clock_getres),
+            SyscallNum::NR_clock_gettime => handle!(clock_gettime),
             SyscallNum::NR_clock_nanosleep => handle!(clock_nanosleep),
             SyscallNum::NR_clone => handle!(clone),
             SyscallNum::NR_clone3 => handle!(clone3),
             SyscallNum::NR_close => handle!(close),
             SyscallNum::NR_close_range => handle!(close_range),
             SyscallNum::NR_connect => handle!(connect),
+            SyscallNum::NR_copy_file_range => handle!(copy_file_range),
             SyscallNum::NR_creat => handle!(creat),
             SyscallNum::NR_dup => handle!(dup),
             SyscallNum::NR_dup2 => handle!(dup2),
@@ -402,6 +457,7 @@ impl SyscallHandler {
             SyscallNum::NR_execveat => handle!(execveat),
             SyscallNum::NR_exit_group => handle!(exit_group),
             SyscallNum::NR_faccessat => handle!(faccessat),
+            SyscallNum::NR_faccessat2 => handle!(faccessat2),
             SyscallNum::NR_fadvise64 => handle!(fadvise64),
             SyscallNum::NR_fallocate => handle!(fallocate),
             SyscallNum::NR_fchmod => handle!(fchmod),
@@ -424,6 +480,7 @@ impl SyscallHandler {
             SyscallNum::NR_futex => handle!(futex),
             SyscallNum::NR_futimesat => handle!(futimesat),
             SyscallNum::NR_get_robust_list => handle!(get_robust_list),
+            SyscallNum::NR_getcpu => handle!(getcpu),
             SyscallNum::NR_getdents => handle!(getdents),
             SyscallNum::NR_getdents64 => handle!(getdents64),
             SyscallNum::NR_getitimer => handle!(getitimer),
@@ -433,16 +490,28 @@ impl SyscallHandler {
             SyscallNum::NR_getpid => handle!(getpid),
             SyscallNum::NR_getppid => handle!(getppid),
             SyscallNum::NR_getrandom => handle!(getrandom),
+            SyscallNum::NR_getrusage => handle!(getrusage),
             SyscallNum::NR_getsid => handle!(getsid),
             SyscallNum::NR_getsockname => handle!(getsockname),
             SyscallNum::NR_getsockopt => handle!(getsockopt),
             SyscallNum::NR_gettid => handle!(gettid),
+            SyscallNum::NR_getxattr => handle!(getxattr),
             SyscallNum::NR_ioctl => handle!(ioctl),
             SyscallNum::NR_kill => handle!(kill),
+            SyscallNum::NR_lchown => handle!(lchown),
+            SyscallNum::NR_lgetxattr => handle!(lgetxattr),
+            SyscallNum::NR_link => handle!(link),
             SyscallNum::NR_linkat => handle!(linkat),
             SyscallNum::NR_listen => handle!(listen),
+            SyscallNum::NR_listxattr => handle!(listxattr),
+            SyscallNum::NR_llistxattr => handle!(llistxattr),
+            SyscallNum::NR_lremovexattr => handle!(lremovexattr),
             SyscallNum::NR_lseek => handle!(lseek),
+            SyscallNum::NR_lsetxattr => handle!(lsetxattr),
+            SyscallNum::NR_lstat => handle!(lstat),
+            SyscallNum::NR_mkdir => handle!(mkdir),
             SyscallNum::NR_mkdirat => handle!(mkdirat),
+            SyscallNum::NR_mknod => handle!(mknod),
             SyscallNum::NR_mknodat => handle!(mknodat),
             SyscallNum::NR_mmap => handle!(mmap),
             SyscallNum::NR_mprotect => handle!(mprotect),
@@ -452,6 +521,8 @@ impl SyscallHandler {
             SyscallNum::NR_newfstatat => handle!(newfstatat),
             SyscallNum::NR_open => handle!(open),
             SyscallNum::NR_openat => handle!(openat),
+            SyscallNum::NR_pidfd_open => handle!(pidfd_open),
+            SyscallNum::NR_pidfd_send_signal => handle!(pidfd_send_signal),
             SyscallNum::NR_pipe => handle!(pipe),
             SyscallNum::NR_pipe2 => handle!(pipe2),
             SyscallNum::NR_poll => handle!(poll),
@@ -461,24 +532,38 @@ impl SyscallHandler {
             SyscallNum::NR_preadv => handle!(preadv),
             SyscallNum::NR_preadv2 => handle!(preadv2),
             SyscallNum::NR_prlimit64 => handle!(prlimit64),
+            SyscallNum::NR_process_vm_readv => handle!(process_vm_readv),
+            SyscallNum::NR_process_vm_writev => handle!(process_vm_writev),
             SyscallNum::NR_pselect6 => handle!(pselect6),
             SyscallNum::NR_pwrite64 => handle!(pwrite64),
             SyscallNum::NR_pwritev => handle!(pwritev),
             SyscallNum::NR_pwritev2 => handle!(pwritev2),
             SyscallNum::NR_read => handle!(read),
             SyscallNum::NR_readahead => handle!(readahead),
+            SyscallNum::NR_readlink => handle!(readlink),
             SyscallNum::NR_readlinkat => handle!(readlinkat),
             SyscallNum::NR_readv => handle!(readv),
             SyscallNum::NR_recvfrom => handle!(recvfrom),
+            SyscallNum::NR_recvmmsg => handle!(recvmmsg),
             SyscallNum::NR_recvmsg => handle!(recvmsg),
+            SyscallNum::NR_removexattr => handle!(removexattr),
+            SyscallNum::NR_rename => handle!(rename),
             SyscallNum::NR_renameat => handle!(renameat),
             SyscallNum::NR_renameat2 => handle!(renameat2),
+            SyscallNum::NR_rmdir => handle!(rmdir),
             SyscallNum::NR_rseq => handle!(rseq),
             SyscallNum::NR_rt_sigaction => handle!(rt_sigaction),
             SyscallNum::NR_rt_sigprocmask => handle!(rt_sigprocmask),
+            SyscallNum::NR_sched_get_priority_max => handle!(sched_get_priority_max),
+            SyscallNum::NR_sched_get_priority_min => handle!(sched_get_priority_min),
             SyscallNum::NR_sched_getaffinity => handle!(sched_getaffinity),
+            SyscallNum::NR_sched_getparam => handle!(sched_getparam),
+            SyscallNum::NR_sched_getscheduler => handle!(sched_getscheduler),
             SyscallNum::NR_sched_setaffinity => handle!(sched_setaffinity),
+            SyscallNum::NR_sched_setparam => handle!(sched_setparam),
+            SyscallNum::NR_sched_setscheduler => handle!(sched_setscheduler),
             SyscallNum::NR_select => handle!(select),
+            SyscallNum::NR_sendmmsg => handle!(sendmmsg),
             SyscallNum::NR_sendmsg => handle!(sendmsg),
             SyscallNum::NR_sendto => handle!(sendto),
             SyscallNum::NR_set_robust_list => handle!(set_robust_list),
@@ -487,23 +572,42 @@ impl SyscallHandler {
             SyscallNum::NR_setpgid => handle!(setpgid),
             SyscallNum::NR_setsid => handle!(setsid),
             SyscallNum::NR_setsockopt => handle!(setsockopt),
+            SyscallNum::NR_setxattr => handle!(setxattr),
+            SyscallNum::NR_shmat => handle!(shmat),
+            SyscallNum::NR_shmctl => handle!(shmctl),
+            SyscallNum::NR_shmdt => handle!(shmdt),
+            SyscallNum::NR_shmget => handle!(shmget),
             SyscallNum::NR_shutdown => handle!(shutdown),
             SyscallNum::NR_sigaltstack => handle!(sigaltstack),
             SyscallNum::NR_socket => handle!(socket),
             SyscallNum::NR_socketpair => handle!(socketpair),
+            SyscallNum::NR_splice => handle!(splice),
+            SyscallNum::NR_stat => handle!(stat),
+            SyscallNum::NR_statfs => handle!(statfs),
             SyscallNum::NR_statx => handle!(statx),
+            SyscallNum::NR_symlink => handle!(symlink),
             SyscallNum::NR_symlinkat => handle!(symlinkat),
             SyscallNum::NR_sync_file_range => handle!(sync_file_range),
             SyscallNum::NR_syncfs => handle!(syncfs),
             SyscallNum::NR_sysinfo => handle!(sysinfo),
+            SyscallNum::NR_tee => handle!(tee),
             SyscallNum::NR_tgkill => handle!(tgkill),
+            SyscallNum::NR_timer_create => handle!(timer_create),
+            SyscallNum::NR_timer_delete => handle!(timer_delete),
+            SyscallNum::NR_timer_getoverrun => handle!(timer_getoverrun),
+            SyscallNum::NR_timer_gettime => handle!(timer_gettime),
+            SyscallNum::NR_timer_settime => handle!(timer_settime),
             SyscallNum::NR_timerfd_create => handle!(timerfd_create),
             SyscallNum::NR_timerfd_gettime => handle!(timerfd_gettime),
             SyscallNum::NR_timerfd_settime => handle!(timerfd_settime),
             SyscallNum::NR_tkill => handle!(tkill),
+            SyscallNum::NR_truncate => handle!(truncate),
             SyscallNum::NR_uname => handle!(uname),
+            SyscallNum::NR_unlink => handle!(unlink),
             SyscallNum::NR_unlinkat => handle!(unlinkat),
+            SyscallNum::NR_utime => handle!(utime),
             SyscallNum::NR_utimensat => handle!(utimensat),
+            SyscallNum::NR_utimes => handle!(utimes),
             SyscallNum::NR_vfork => handle!(vfork),
             SyscallNum::NR_waitid => handle!(waitid),
             SyscallNum::NR_wait4 => handle!(wait4),
@@ -512,16 +616,17 @@ impl SyscallHandler {
             //
             // CUSTOM SHADOW-SPECIFIC SYSCALLS
             //
+            NR_shadow_get_host_name => handle!(shadow_get_host_name),
             NR_shadow_hostname_to_addr_ipv4 => handle!(shadow_hostname_to_addr_ipv4),
             NR_shadow_init_memory_manager => handle!(shadow_init_memory_manager),
+            NR_shadow_pin_cpu => handle!(shadow_pin_cpu),
+            NR_shadow_query_feature => handle!(shadow_query_feature),
+            NR_shadow_tcp_cc_state => handle!(shadow_tcp_cc_state),
             NR_shadow_yield => handle!(shadow_yield),
             //
             // SHIM-ONLY SYSCALLS
             //
-            SyscallNum::NR_clock_gettime
-            | SyscallNum::NR_gettimeofday
-            | SyscallNum::NR_sched_yield
-            | SyscallNum::NR_time => {
+            SyscallNum::NR_gettimeofday | SyscallNum::NR_sched_yield | SyscallNum::NR_time => {
                 panic!(
                     "Syscall {} ({}) should have been handled in the shim",
                     syscall_name, ctx.args.number,
@@ -530,10 +635,7 @@ impl SyscallHandler {
             //
             // NATIVE LINUX-HANDLED SYSCALLS
             //
-            SyscallNum::NR_access
-            | SyscallNum::NR_arch_prctl
-            | SyscallNum::NR_chmod
-            | SyscallNum::NR_chown
+            SyscallNum::NR_arch_prctl
             | SyscallNum::NR_exit
             | SyscallNum::NR_getcwd
             | SyscallNum::NR_geteuid
@@ -544,22 +646,7 @@ impl SyscallHandler {
             | SyscallNum::NR_getresuid
             | SyscallNum::NR_getrlimit
             | SyscallNum::NR_getuid
-            | SyscallNum::NR_getxattr
-            | SyscallNum::NR_lchown
-            | SyscallNum::NR_lgetxattr
-            | SyscallNum::NR_link
-            | SyscallNum::NR_listxattr
-            | SyscallNum::NR_llistxattr
-            | SyscallNum::NR_lremovexattr
-            | SyscallNum::NR_lsetxattr
-            | SyscallNum::NR_lstat
             | SyscallNum::NR_madvise
-            | SyscallNum::NR_mkdir
-            | SyscallNum::NR_mknod
-            | SyscallNum::NR_readlink
-            | SyscallNum::NR_removexattr
-            | SyscallNum::NR_rename
-            | SyscallNum::NR_rmdir
             | SyscallNum::NR_rt_sigreturn
             | SyscallNum::NR_setfsgid
             | SyscallNum::NR_setfsuid
@@ -569,15 +656,7 @@ impl SyscallHandler {
             | SyscallNum::NR_setresuid
             | SyscallNum::NR_setreuid
             | SyscallNum::NR_setrlimit
-            | SyscallNum::NR_setuid
-            | SyscallNum::NR_setxattr
-            | SyscallNum::NR_stat
-            | SyscallNum::NR_statfs
-            | SyscallNum::NR_symlink
-            | SyscallNum::NR_truncate
-            | SyscallNum::NR_unlink
-            | SyscallNum::NR_utime
-            | SyscallNum::NR_utimes => {
+            | SyscallNum::NR_setuid => {
                 log::trace!("Native syscall {} ({})", syscall_name, ctx.args.number);
 
                 let rv = Err(SyscallError::Native);
@@ -695,6 +774,28 @@ impl SyscallHandler {
 
         rv.map(Into::into)
     }
+
+    /// Emulate a legacy path syscall (for example `unlink`) by running the C syscall handler of
+    /// its `*at` version (for example `unlinkat`) with the arguments `at_args`. This resolves the
+    /// path like the `*at` syscalls do, for example within the host's mounts.
+    fn legacy_at_syscall<T: From<SyscallReg>>(
+        syscall: LegacySyscallFn,
+        ctx: &mut SyscallContext,
+        at_args: &[SyscallReg],
+    ) -> Result<T, SyscallError> {
+        let mut args = SyscallArgs {
+            number: ctx.args.number,
+            args: [0u64.into(); 6],
+        };
+        args.args[..at_args.len()].copy_from_slice(at_args);
+
+        let mut ctx = SyscallContext {
+            objs: ctx.objs,
+            args: &args,
+            handler: ctx.handler,
+        };
+        Self::legacy_syscall(syscall, &mut ctx)
+    }
 }
 
 impl std::ops::Drop for SyscallHandler {
@@ -718,6 +819,10 @@ impl std::ops::Drop for SyscallHandler {
 
             // add up the counts at the worker level
             Worker::add_syscall_counts(syscall_counter);
+
+            if let Some((host_name, process_name)) = &self.syscall_counter_owner {
+                Worker::add_process_syscall_counts(host_name, process_name, syscall_counter);
+            }
         }
 
         unsafe { c::legacyfile_unref(self.epoll.ptr() as *mut std::ffi::c_void) };
@@ -730,6 +835,61 @@ pub struct SyscallContext<'a, 'b> {
     pub handler: &'a mut SyscallHandler,
 }
 
+impl SyscallContext<'_, '_> {
+    /// Reads a `T` from the process's memory at `ptr`. Returns `EFAULT` if `ptr` is NULL, or if
+    /// the memory isn't within the user address space or isn't readable.
+    pub fn read_ptr<T: Pod + std::fmt::Debug>(&self, ptr: ForeignPtr<T>) -> Result<T, Errno> {
+        if ptr.is_null() {
+            return Err(Errno::EFAULT);
+        }
+        self.objs.process.memory_borrow().read(ptr)
+    }
+
+    /// Writes `val` to the process's memory at `ptr`. Returns `EFAULT` if `ptr` is NULL, or if the
+    /// memory isn't within the user address space or isn't writable.
+    pub fn write_ptr<T: Pod + std::fmt::Debug>(
+        &self,
+        ptr: ForeignPtr<T>,
+        val: &T,
+    ) -> Result<(), Errno> {
+        if ptr.is_null() {
+            return Err(Errno::EFAULT);
+        }
+        self.objs.process.memory_borrow_mut().write(ptr, val)
+    }
+
+    /// Reads a slice of `T` from the process's memory. Returns `EFAULT` if the memory isn't
+    /// within the user address space or isn't readable. An empty slice is never accessed.
+    pub fn read_slice<T: Pod + std::fmt::Debug>(
+        &self,
+        ptr: ForeignArrayPtr<T>,
+    ) -> Result<Vec<T>, Errno> {
+        if ptr.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut vals = vec![shadow_pod::zeroed(); ptr.len()];
+        self.objs
+            .process
+            .memory_borrow()
+            .copy_from_ptr(&mut vals, ptr)?;
+        Ok(vals)
+    }
+
+    /// Writes `vals` to the process's memory at `ptr`. Returns `EFAULT` if the memory isn't within
+    /// the user address space or isn't writable. An empty slice is never accessed.
+    pub fn write_slice<T: Pod + std::fmt::Debug>(
+        &self,
+        ptr: ForeignPtr<T>,
+        vals: &[T],
+    ) -> Result<(), Errno> {
+        if vals.is_empty() {
+            return Ok(());
+        }
+        let ptr = ForeignArrayPtr::new(ptr, vals.len());
+        self.objs.process.memory_borrow_mut().copy_to_ptr(ptr, vals)
+    }
+}
+
 pub trait SyscallHandlerFn<T> {
     fn call(self, ctx: &mut SyscallContext) -> SyscallResult;
 }
@@ -0,0 +1,64 @@
+use linux_api::errno::Errno;
+use linux_api::fcntl::{DescriptorFlags, OFlag};
+
+use crate::host::descriptor::descriptor_table::DescriptorHandle;
+use crate::host::descriptor::pidfd::PidFd;
+use crate::host::descriptor::{CompatFile, Descriptor, File, FileStatus, OpenFile};
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+
+impl SyscallHandler {
+    log_syscall!(
+        pidfd_open,
+        /* rv */ std::ffi::c_int,
+        /* pid */ linux_api::posix_types::kernel_pid_t,
+        /* flags */ std::ffi::c_uint,
+    );
+    pub fn pidfd_open(
+        ctx: &mut SyscallContext,
+        pid: linux_api::posix_types::kernel_pid_t,
+        flags: std::ffi::c_uint,
+    ) -> Result<DescriptorHandle, Errno> {
+        log::trace!("pidfd_open() called with pid {pid} and flags {flags}");
+
+        // PIDFD_NONBLOCK has the same value as O_NONBLOCK
+        let pidfd_nonblock = OFlag::O_NONBLOCK.bits() as std::ffi::c_uint;
+
+        if flags & !pidfd_nonblock != 0 {
+            log::debug!("Invalid pidfd_open flags: {flags}");
+            return Err(Errno::EINVAL);
+        }
+
+        if pid <= 0 {
+            return Err(Errno::EINVAL);
+        }
+        let pid = pid.try_into().or(Err(Errno::ESRCH))?;
+
+        let Some(process) = ctx.objs.host.process_borrow(pid) else {
+            log::debug!("Process {pid:?} not found");
+            return Err(Errno::ESRCH);
+        };
+        let process = &*process.borrow(ctx.objs.host.root());
+
+        let mut file_flags = FileStatus::empty();
+        if flags & pidfd_nonblock != 0 {
+            file_flags.insert(FileStatus::NONBLOCK);
+        }
+
+        let file = PidFd::new(process, file_flags);
+
+        // pidfd_open(2): "The close-on-exec flag is set on the file descriptor."
+        let mut desc = Descriptor::new(CompatFile::New(OpenFile::new(File::PidFd(file))));
+        desc.set_flags(DescriptorFlags::FD_CLOEXEC);
+
+        let fd = ctx
+            .objs
+            .thread
+            .descriptor_table_borrow_mut(ctx.objs.host)
+            .register_descriptor(desc)
+            .or(Err(Errno::ENFILE))?;
+
+        log::trace!("pidfd_open() returning fd {}", fd);
+
+        Ok(fd)
+    }
+}
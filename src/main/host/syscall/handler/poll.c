@@ -57,6 +57,14 @@ static void _syscallhandler_getPollEventsHelper(const Descriptor* cdesc, struct
             (dstat & FileState_WRITABLE)) {
             pfd->revents |= POLLOUT;
         }
+        if ((pfd->events & POLLPRI) && (dstat & FileState_ACTIVE) &&
+            (dstat & FileState_PRIORITY)) {
+            pfd->revents |= POLLPRI;
+        }
+        if ((pfd->events & POLLRDHUP) && (dstat & FileState_ACTIVE) &&
+            (dstat & FileState_RDHUP)) {
+            pfd->revents |= POLLRDHUP;
+        }
     }
 }
 
@@ -113,6 +121,12 @@ static void _syscallhandler_registerPollFDs(SyscallHandler* sys, struct pollfd*
         if (pfd->events & POLLOUT) {
             epev.events |= EPOLLOUT;
         }
+        if (pfd->events & POLLPRI) {
+            epev.events |= EPOLLPRI;
+        }
+        if (pfd->events & POLLRDHUP) {
+            epev.events |= EPOLLRDHUP;
+        }
 
         if (epev.events) {
             epoll_control(rustsyscallhandler_getEpoll(sys), EPOLL_CTL_ADD, pfd->fd, desc, &epev,
@@ -0,0 +1,279 @@
+use linux_api::errno::Errno;
+use linux_api::signal::{SigEvNotify, Signal};
+use linux_api::time::{itimerspec, ClockId, TimerSettimeFlags};
+use log::*;
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::core::worker::Worker;
+use crate::host::posix_timer::{PosixTimer, PosixTimerNotify};
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::types::SyscallError;
+use crate::host::thread::ThreadId;
+
+fn itimerspec_from_timer(timer: &PosixTimer) -> itimerspec {
+    let timer = timer.timer();
+    itimerspec {
+        it_interval: timer
+            .expire_interval()
+            .unwrap_or(SimulationTime::ZERO)
+            .try_into()
+            .unwrap(),
+        it_value: timer
+            .remaining_time()
+            .unwrap_or(SimulationTime::ZERO)
+            .try_into()
+            .unwrap(),
+    }
+}
+
+impl SyscallHandler {
+    log_syscall!(
+        timer_create,
+        /* rv */ std::ffi::c_int,
+        /* clockid */ linux_api::time::ClockId,
+        /* sevp */ *const std::ffi::c_void,
+        /* timerid */ *const std::ffi::c_void,
+    );
+    pub fn timer_create(
+        ctx: &mut SyscallContext,
+        clock_id: linux_api::time::linux___kernel_clockid_t,
+        sevp_ptr: ForeignPtr<libc::sigevent>,
+        timerid_ptr: ForeignPtr<std::ffi::c_int>,
+    ) -> Result<(), SyscallError> {
+        let Ok(clock_id) = ClockId::try_from(clock_id) else {
+            debug!("Bad clock id {clock_id}");
+            return Err(Errno::EINVAL.into());
+        };
+
+        // All of these clocks are the emulated time in Shadow.
+        if ![
+            ClockId::CLOCK_MONOTONIC,
+            ClockId::CLOCK_REALTIME,
+            ClockId::CLOCK_BOOTTIME,
+        ]
+        .contains(&clock_id)
+        {
+            warn_once_then_debug!("Unsupported clock id {clock_id:?} for timer_create");
+            return Err(Errno::EINVAL.into());
+        }
+
+        // A NULL sevp uses the default notification; see `PosixTimers::create`.
+        let sevp = if sevp_ptr.is_null() {
+            None
+        } else {
+            Some(ctx.objs.process.memory_borrow().read(sevp_ptr)?)
+        };
+
+        let notify = match sevp {
+            None => None,
+            Some(sevp) => match SigEvNotify::try_from(sevp.sigev_notify) {
+                Ok(SigEvNotify::SIGEV_NONE) => Some(PosixTimerNotify::None),
+                Ok(SigEvNotify::SIGEV_SIGNAL) => {
+                    let Ok(signal) = Signal::try_from(sevp.sigev_signo) else {
+                        debug!("Bad signal {}", sevp.sigev_signo);
+                        return Err(Errno::EINVAL.into());
+                    };
+                    Some(PosixTimerNotify::Signal {
+                        signal,
+                        value: sevp.sigev_value.sival_ptr as usize,
+                        thread: None,
+                    })
+                }
+                Ok(SigEvNotify::SIGEV_THREAD_ID) => {
+                    let Ok(signal) = Signal::try_from(sevp.sigev_signo) else {
+                        debug!("Bad signal {}", sevp.sigev_signo);
+                        return Err(Errno::EINVAL.into());
+                    };
+                    if signal.is_realtime() {
+                        // As in `tkill`, we don't support thread-directed realtime signals.
+                        warn_once_then_debug!("Unimplemented signal {signal:?}");
+                        return Err(Errno::EINVAL.into());
+                    }
+
+                    // Like Linux, the target must be a thread in the calling process.
+                    let tid = ThreadId::try_from(sevp.sigev_notify_thread_id).ok();
+                    let Some(tid) =
+                        tid.filter(|tid| ctx.objs.process.thread_borrow(*tid).is_some())
+                    else {
+                        debug!("Bad thread id {}", sevp.sigev_notify_thread_id);
+                        return Err(Errno::EINVAL.into());
+                    };
+
+                    Some(PosixTimerNotify::Signal {
+                        signal,
+                        value: sevp.sigev_value.sival_ptr as usize,
+                        thread: Some(tid),
+                    })
+                }
+                Ok(notify @ SigEvNotify::SIGEV_THREAD) => {
+                    // libc implements SIGEV_THREAD using SIGEV_THREAD_ID with a realtime signal
+                    // and a helper thread waiting in `sigwaitinfo(3)`, so like Linux we reject it
+                    // here. Those timers still fail in the SIGEV_THREAD_ID case above, since we
+                    // don't support realtime signals.
+                    warn_once_then_debug!("Unsupported notification method {notify:?}");
+                    return Err(Errno::EINVAL.into());
+                }
+                Err(_) => {
+                    debug!("Bad notification method {}", sevp.sigev_notify);
+                    return Err(Errno::EINVAL.into());
+                }
+            },
+        };
+
+        let pid = ctx.objs.process.id();
+        let id = ctx
+            .objs
+            .process
+            .posix_timers_borrow_mut()
+            .create(pid, clock_id, notify)?;
+
+        if let Err(e) = ctx.objs.process.memory_borrow_mut().write(timerid_ptr, &id) {
+            ctx.objs.process.posix_timers_borrow_mut().delete(id);
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    log_syscall!(
+        timer_settime,
+        /* rv */ std::ffi::c_int,
+        /* timerid */ std::ffi::c_int,
+        /* flags */ std::ffi::c_int,
+        /* new_value */ *const std::ffi::c_void,
+        /* old_value */ *const std::ffi::c_void,
+    );
+    pub fn timer_settime(
+        ctx: &mut SyscallContext,
+        timer_id: std::ffi::c_int,
+        flags: std::ffi::c_int,
+        new_value_ptr: ForeignPtr<itimerspec>,
+        old_value_ptr: ForeignPtr<itimerspec>,
+    ) -> Result<(), SyscallError> {
+        // Linux ignores unknown flags.
+        let flags = TimerSettimeFlags::from_bits_truncate(flags);
+
+        let new_value = ctx.objs.process.memory_borrow().read(new_value_ptr)?;
+        let value = SimulationTime::try_from(new_value.it_value).or(Err(Errno::EINVAL))?;
+        let interval = SimulationTime::try_from(new_value.it_interval).or(Err(Errno::EINVAL))?;
+
+        let mut timers = ctx.objs.process.posix_timers_borrow_mut();
+        let Some(timer) = timers.get_mut(timer_id) else {
+            debug!("Bad timer id {timer_id}");
+            return Err(Errno::EINVAL.into());
+        };
+
+        // The old value is always relative, even if TIMER_ABSTIME is set.
+        let old_value = itimerspec_from_timer(timer);
+
+        if value.is_zero() {
+            // A value of 0 disarms the timer; it_interval is ignored.
+            timer.timer_mut().disarm();
+        } else {
+            let now = Worker::current_time().unwrap();
+
+            let base = if flags.contains(TimerSettimeFlags::TIMER_ABSTIME) {
+                EmulatedTime::UNIX_EPOCH
+            } else {
+                now
+            };
+            // An absolute time in the past expires the timer immediately.
+            let expire_time = EmulatedTime::max(base + value, now);
+
+            timer.timer_mut().arm(
+                ctx.objs.host,
+                expire_time,
+                interval.is_positive().then_some(interval),
+            );
+        }
+
+        drop(timers);
+
+        if !old_value_ptr.is_null() {
+            ctx.objs
+                .process
+                .memory_borrow_mut()
+                .write(old_value_ptr, &old_value)?;
+        }
+
+        Ok(())
+    }
+
+    log_syscall!(
+        timer_gettime,
+        /* rv */ std::ffi::c_int,
+        /* timerid */ std::ffi::c_int,
+        /* curr_value */ *const std::ffi::c_void,
+    );
+    pub fn timer_gettime(
+        ctx: &mut SyscallContext,
+        timer_id: std::ffi::c_int,
+        curr_value_ptr: ForeignPtr<itimerspec>,
+    ) -> Result<(), SyscallError> {
+        let curr_value = {
+            let timers = ctx.objs.process.posix_timers_borrow();
+            let Some(timer) = timers.get(timer_id) else {
+                debug!("Bad timer id {timer_id}");
+                return Err(Errno::EINVAL.into());
+            };
+            itimerspec_from_timer(timer)
+        };
+
+        ctx.objs
+            .process
+            .memory_borrow_mut()
+            .write(curr_value_ptr, &curr_value)?;
+
+        Ok(())
+    }
+
+    log_syscall!(
+        timer_getoverrun,
+        /* rv */ std::ffi::c_int,
+        /* timerid */ std::ffi::c_int,
+    );
+    pub fn timer_getoverrun(
+        ctx: &mut SyscallContext,
+        timer_id: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        if ctx
+            .objs
+            .process
+            .posix_timers_borrow()
+            .get(timer_id)
+            .is_none()
+        {
+            debug!("Bad timer id {timer_id}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        // We don't track overruns, which only happen when a timer expires again while its
+        // signal is still pending.
+        Ok(0)
+    }
+
+    log_syscall!(
+        timer_delete,
+        /* rv */ std::ffi::c_int,
+        /* timerid */ std::ffi::c_int,
+    );
+    pub fn timer_delete(
+        ctx: &mut SyscallContext,
+        timer_id: std::ffi::c_int,
+    ) -> Result<(), SyscallError> {
+        if ctx
+            .objs
+            .process
+            .posix_timers_borrow_mut()
+            .delete(timer_id)
+            .is_none()
+        {
+            debug!("Bad timer id {timer_id}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        Ok(())
+    }
+}
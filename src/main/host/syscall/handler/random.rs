@@ -1,8 +1,10 @@
 use linux_api::errno::Errno;
 use log::*;
 use rand::RngCore;
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
+use crate::core::worker::Worker;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::ForeignArrayPtr;
 
@@ -18,10 +20,33 @@ impl SyscallHandler {
         ctx: &mut SyscallContext,
         buf_ptr: ForeignPtr<u8>,
         count: usize,
-        _flags: std::ffi::c_uint,
+        flags: std::ffi::c_uint,
     ) -> Result<isize, Errno> {
-        // We ignore the flags arg, because we use the same random source for both
-        // random and urandom, and it never blocks anyway.
+        // We accept all of the flags, but other than `GRND_NONBLOCK` they have no effect. We use
+        // the same deterministic random source for both random and urandom, and we never block.
+        let valid_flags = libc::GRND_NONBLOCK | libc::GRND_RANDOM | libc::GRND_INSECURE;
+        if flags & !valid_flags != 0 {
+            debug!("Invalid getrandom flags: {flags}");
+            return Err(Errno::EINVAL);
+        }
+
+        // getrandom(2):
+        // > EINVAL: Both GRND_INSECURE and GRND_RANDOM were specified in flags.
+        if flags & libc::GRND_INSECURE != 0 && flags & libc::GRND_RANDOM != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        // If configured, the entropy pool isn't initialized during early boot. `GRND_INSECURE`
+        // returns bytes even if the pool isn't initialized. We don't model blocking until the pool
+        // is initialized, so blocking calls always succeed.
+        let time_since_boot = Worker::current_time()
+            .unwrap()
+            .duration_since(&EmulatedTime::SIMULATION_START);
+        let pool_ready = time_since_boot >= ctx.objs.host.params.entropy_pool_init_time;
+        if !pool_ready && flags & libc::GRND_NONBLOCK != 0 && flags & libc::GRND_INSECURE == 0 {
+            trace!("Entropy pool is not yet initialized");
+            return Err(Errno::EAGAIN);
+        }
 
         trace!("Trying to read {count} random bytes.");
 
@@ -36,8 +61,9 @@ impl SyscallHandler {
             }
         };
 
-        // Get random bytes using host rng to maintain determinism.
-        let mut rng = ctx.objs.host.random_mut();
+        // Get random bytes using the process' rng to maintain determinism. The rng is seeded from
+        // the host's seed (derived from the global simulation seed) and the process id.
+        let mut rng = ctx.objs.process.random_mut();
         rng.fill_bytes(&mut mem_ref);
 
         // We must flush the memory reference to write it back.
@@ -1,10 +1,45 @@
 use linux_api::errno::Errno;
+use linux_api::resource::rusage;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::SyscallError;
 
 impl SyscallHandler {
+    log_syscall!(
+        getrusage,
+        /* rv */ std::ffi::c_int,
+        /* who */ std::ffi::c_int,
+        /* usage */ *const std::ffi::c_void,
+    );
+    pub fn getrusage(
+        ctx: &mut SyscallContext,
+        who: std::ffi::c_int,
+        usage_ptr: ForeignPtr<rusage>,
+    ) -> Result<(), SyscallError> {
+        let usage = match who {
+            libc::RUSAGE_SELF => ctx.objs.process.rusage_self(ctx.objs.host),
+            libc::RUSAGE_CHILDREN => ctx.objs.process.rusage_children(),
+            libc::RUSAGE_THREAD => {
+                let mut usage = ctx
+                    .objs
+                    .thread
+                    .resource_usage(&ctx.objs.host.shim_shmem_lock_borrow().unwrap());
+                // linux reports the process's peak RSS for the thread
+                usage.max_rss_kib = ctx.objs.process.rusage_self(ctx.objs.host).max_rss_kib;
+                usage
+            }
+            _ => return Err(Errno::EINVAL.into()),
+        };
+
+        ctx.objs
+            .process
+            .memory_borrow_mut()
+            .write(usage_ptr, &usage.to_rusage())?;
+
+        Ok(())
+    }
+
     log_syscall!(
         prlimit64,
         /* rv */ std::ffi::c_int,
@@ -4,17 +4,21 @@ use linux_api::errno::Errno;
 use linux_api::posix_types::kernel_pid_t;
 use linux_api::rseq::rseq;
 use log::warn;
+use shadow_shim_helper_rs::explicit_drop::{ExplicitDrop, ExplicitDropper};
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::ForeignArrayPtr;
-use crate::host::thread::ThreadId;
+use crate::host::thread::{SchedPolicy, Thread, ThreadId};
 
-// We always report that the thread is running on CPU 0, Node 0
-const CURRENT_CPU: u32 = 0;
+// Shadow only simulates a single NUMA node
+const CURRENT_NODE: u32 = 0;
 
 const RSEQ_FLAG_UNREGISTER: i32 = 1;
 
+// Not defined by the libc crate.
+const SCHED_DEADLINE: std::ffi::c_int = 6;
+
 impl SyscallHandler {
     log_syscall!(
         sched_getaffinity,
@@ -101,6 +105,38 @@ impl SyscallHandler {
         Ok(())
     }
 
+    log_syscall!(
+        getcpu,
+        /* rv */ std::ffi::c_int,
+        /* cpu */ *const std::ffi::c_uint,
+        /* node */ *const std::ffi::c_uint,
+        /* tcache */ *const std::ffi::c_void,
+    );
+    pub fn getcpu(
+        ctx: &mut SyscallContext,
+        cpu_ptr: ForeignPtr<std::ffi::c_uint>,
+        node_ptr: ForeignPtr<std::ffi::c_uint>,
+        // getcpu(2):
+        // > The third argument to this system call is nowadays unused, and should be specified as
+        // > NULL unless portability to Linux 2.6.23 or earlier is required
+        _tcache_ptr: ForeignPtr<std::ffi::c_void>,
+    ) -> Result<(), Errno> {
+        // Report the simulated CPU rather than the host CPU that the worker happens to be running
+        // on (which may or may not be pinned), so that the result is deterministic.
+        let cpu = ctx.objs.thread.simulated_cpu();
+
+        let mut mem = ctx.objs.process.memory_borrow_mut();
+
+        if !cpu_ptr.is_null() {
+            mem.write(cpu_ptr, &cpu)?;
+        }
+        if !node_ptr.is_null() {
+            mem.write(node_ptr, &CURRENT_NODE)?;
+        }
+
+        Ok(())
+    }
+
     log_syscall!(
         rseq,
         /* rv */ i32,
@@ -130,21 +166,10 @@ impl SyscallHandler {
             // * Validate that `sig` matches registration
             // * Set the cpu_id of the previously registerd rseq to the uninitialized
             //   state.
+            ctx.objs.thread.set_rseq(None);
             return Ok(());
         }
 
-        // The `rseq` struct is designed to grow as linux needs to add more features, so we can't
-        // assume that the application making the rseq syscall is using the exact same struct as we
-        // have available in the linux_api crate (the calling application's rseq struct may have
-        // more or fewer fields). Furthermore, the rseq struct ends with a "flexible array member",
-        // which means that the rseq struct cannot be `Copy` and therefore not `Pod`.
-        //
-        // Instead, we should treat the rseq struct as a bunch of bytes and write to individual
-        // fields if possible without making assumptions about the size of the data.
-        let mut mem = ctx.objs.process.memory_borrow_mut();
-        let mut rseq_mem = mem.memory_ref_mut(ForeignArrayPtr::new(rseq_ptr, rseq_len))?;
-        let rseq_bytes = &mut *rseq_mem;
-
         // rseq is mostly unimplemented, but also mostly unneeded in Shadow.
         // We'd only need to implement the "real" functionality if we ever implement
         // true preemption, in which case we'd need to do something if we ever pre-empted
@@ -159,18 +184,228 @@ impl SyscallHandler {
         // in a handler.
         // https://github.com/shadow/shadow/issues/2139
         //
-        // For now we just update to reflect that the thread is running on CPU 0.
+        // For now we just update to reflect the thread's current simulated CPU, and update it again
+        // whenever the thread's simulated CPU changes.
+
+        let rseq_ptr = ForeignArrayPtr::new(rseq_ptr, rseq_len);
+        Self::write_rseq_cpu(ctx, rseq_ptr)?;
+        ctx.objs.thread.set_rseq(Some(rseq_ptr));
+
+        Ok(())
+    }
+
+    /// Write the thread's current simulated CPU to the `cpu_id` and `cpu_id_start` fields of the
+    /// rseq area at `rseq_ptr`.
+    pub(super) fn write_rseq_cpu(
+        ctx: &SyscallContext,
+        rseq_ptr: ForeignArrayPtr<MaybeUninit<u8>>,
+    ) -> Result<(), Errno> {
+        // The `rseq` struct is designed to grow as linux needs to add more features, so we can't
+        // assume that the application making the rseq syscall is using the exact same struct as we
+        // have available in the linux_api crate (the calling application's rseq struct may have
+        // more or fewer fields). Furthermore, the rseq struct ends with a "flexible array member",
+        // which means that the rseq struct cannot be `Copy` and therefore not `Pod`.
+        //
+        // Instead, we should treat the rseq struct as a bunch of bytes and write to individual
+        // fields if possible without making assumptions about the size of the data.
+        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut rseq_mem = mem.memory_ref_mut(rseq_ptr)?;
+        let rseq_bytes = &mut *rseq_mem;
 
         let Some((cpu_id, cpu_id_start)) = field_project!(rseq_bytes, rseq, (cpu_id, cpu_id_start))
         else {
             return Err(Errno::EINVAL);
         };
 
-        cpu_id.write(CURRENT_CPU);
-        cpu_id_start.write(CURRENT_CPU);
+        let cpu = ctx.objs.thread.simulated_cpu();
+        cpu_id.write(cpu);
+        cpu_id_start.write(cpu);
 
         rseq_mem.flush()?;
 
         Ok(())
     }
+
+    log_syscall!(
+        sched_setscheduler,
+        /* rv */ std::ffi::c_int,
+        /* pid */ kernel_pid_t,
+        /* policy */ std::ffi::c_int,
+        /* param */ *const std::ffi::c_void,
+    );
+    pub fn sched_setscheduler(
+        ctx: &mut SyscallContext,
+        tid: kernel_pid_t,
+        policy: std::ffi::c_int,
+        param_ptr: ForeignPtr<libc::sched_param>,
+    ) -> Result<(), Errno> {
+        let reset_on_fork = policy & libc::SCHED_RESET_ON_FORK != 0;
+        let policy = policy & !libc::SCHED_RESET_ON_FORK;
+
+        if param_ptr.is_null() || tid < 0 {
+            return Err(Errno::EINVAL);
+        }
+        let param = ctx.objs.process.memory_borrow().read(param_ptr)?;
+
+        let policy = SchedPolicy {
+            policy,
+            priority: param.sched_priority,
+            reset_on_fork,
+        };
+        check_sched_policy(&policy)?;
+
+        // Shadow doesn't have users, so no need to check for permissions. We only record the
+        // policy; Shadow's scheduling isn't affected by it.
+        Self::with_sched_thread(ctx, tid, |thread| thread.set_sched_policy(policy))
+    }
+
+    log_syscall!(
+        sched_getscheduler,
+        /* rv */ std::ffi::c_int,
+        /* pid */ kernel_pid_t,
+    );
+    pub fn sched_getscheduler(
+        ctx: &mut SyscallContext,
+        tid: kernel_pid_t,
+    ) -> Result<std::ffi::c_int, Errno> {
+        if tid < 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let policy = Self::with_sched_thread(ctx, tid, |thread| thread.sched_policy())?;
+
+        if policy.reset_on_fork {
+            Ok(policy.policy | libc::SCHED_RESET_ON_FORK)
+        } else {
+            Ok(policy.policy)
+        }
+    }
+
+    log_syscall!(
+        sched_setparam,
+        /* rv */ std::ffi::c_int,
+        /* pid */ kernel_pid_t,
+        /* param */ *const std::ffi::c_void,
+    );
+    pub fn sched_setparam(
+        ctx: &mut SyscallContext,
+        tid: kernel_pid_t,
+        param_ptr: ForeignPtr<libc::sched_param>,
+    ) -> Result<(), Errno> {
+        if param_ptr.is_null() || tid < 0 {
+            return Err(Errno::EINVAL);
+        }
+        let param = ctx.objs.process.memory_borrow().read(param_ptr)?;
+
+        Self::with_sched_thread(ctx, tid, |thread| {
+            let policy = SchedPolicy {
+                priority: param.sched_priority,
+                ..thread.sched_policy()
+            };
+            check_sched_policy(&policy)?;
+            thread.set_sched_policy(policy);
+            Ok(())
+        })?
+    }
+
+    log_syscall!(
+        sched_getparam,
+        /* rv */ std::ffi::c_int,
+        /* pid */ kernel_pid_t,
+        /* param */ *const std::ffi::c_void,
+    );
+    pub fn sched_getparam(
+        ctx: &mut SyscallContext,
+        tid: kernel_pid_t,
+        param_ptr: ForeignPtr<libc::sched_param>,
+    ) -> Result<(), Errno> {
+        if param_ptr.is_null() || tid < 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let policy = Self::with_sched_thread(ctx, tid, |thread| thread.sched_policy())?;
+
+        let param = libc::sched_param {
+            sched_priority: policy.priority,
+        };
+        ctx.objs
+            .process
+            .memory_borrow_mut()
+            .write(param_ptr, &param)?;
+
+        Ok(())
+    }
+
+    log_syscall!(
+        sched_get_priority_max,
+        /* rv */ std::ffi::c_int,
+        /* policy */ std::ffi::c_int,
+    );
+    pub fn sched_get_priority_max(
+        _ctx: &mut SyscallContext,
+        policy: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, Errno> {
+        sched_priority_range(policy).map(|(_min, max)| max)
+    }
+
+    log_syscall!(
+        sched_get_priority_min,
+        /* rv */ std::ffi::c_int,
+        /* policy */ std::ffi::c_int,
+    );
+    pub fn sched_get_priority_min(
+        _ctx: &mut SyscallContext,
+        policy: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, Errno> {
+        sched_priority_range(policy).map(|(min, _max)| min)
+    }
+
+    /// Run `f` on the thread with id `tid`, or the calling thread if `tid` is 0.
+    fn with_sched_thread<T>(
+        ctx: &mut SyscallContext,
+        tid: kernel_pid_t,
+        f: impl FnOnce(&Thread) -> T,
+    ) -> Result<T, Errno> {
+        if tid == 0 {
+            return Ok(f(ctx.objs.thread));
+        }
+
+        let tid = ThreadId::try_from(tid).or(Err(Errno::ESRCH))?;
+        let Some(thread) = ctx.objs.host.thread_cloned_rc(tid) else {
+            return Err(Errno::ESRCH);
+        };
+        let thread =
+            ExplicitDropper::new(thread, |value| value.explicit_drop(ctx.objs.host.root()));
+        let thread = &*thread.borrow(ctx.objs.host.root());
+
+        Ok(f(thread))
+    }
+}
+
+/// Returns the range of valid static priorities for a scheduling policy, or `EINVAL` if the
+/// policy is unknown.
+fn sched_priority_range(
+    policy: std::ffi::c_int,
+) -> Result<(std::ffi::c_int, std::ffi::c_int), Errno> {
+    match policy {
+        libc::SCHED_FIFO | libc::SCHED_RR => Ok((1, 99)),
+        libc::SCHED_OTHER | libc::SCHED_BATCH | libc::SCHED_IDLE => Ok((0, 0)),
+        SCHED_DEADLINE => Ok((0, 0)),
+        _ => Err(Errno::EINVAL),
+    }
+}
+
+/// Checks that the policy can be set with `sched_setscheduler` and that its priority is valid.
+fn check_sched_policy(policy: &SchedPolicy) -> Result<(), Errno> {
+    // SCHED_DEADLINE can only be set with `sched_setattr`, which we don't support.
+    if policy.policy == SCHED_DEADLINE {
+        return Err(Errno::EINVAL);
+    }
+
+    let (min, max) = sched_priority_range(policy.policy)?;
+    if !(min..=max).contains(&policy.priority) {
+        return Err(Errno::EINVAL);
+    }
+
+    Ok(())
 }
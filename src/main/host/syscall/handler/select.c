@@ -71,9 +71,10 @@ static SyscallReturn _syscallhandler_select_helper(SyscallHandler* sys, int nfds
             pfd->events |= POLLOUT;
         }
         if (FD_ISSET(i, &exceptfds)) {
-            // We need poll to process this slot to check for EBADF
+            // We also need poll to process this slot to check for EBADF
             trace("select wanting exceptions for fd %i", i);
             pfd->fd = i; // poll will process this slot
+            pfd->events |= POLLPRI;
         }
     }
 
@@ -102,8 +103,8 @@ static SyscallReturn _syscallhandler_select_helper(SyscallHandler* sys, int nfds
             continue;
         }
 
-        // The exceptional states listed in `man select` don't apply in Shadow,
-        // but POLLNVAL corresponds to an EBADF error.
+        // The only exceptional state listed in `man select` that applies in Shadow is
+        // out-of-band data on a TCP socket. POLLNVAL corresponds to an EBADF error.
         if (pfd->revents & POLLIN) {
             trace("select found fd %i readable", i);
             FD_SET(i, &readfds);
@@ -114,6 +115,11 @@ static SyscallReturn _syscallhandler_select_helper(SyscallHandler* sys, int nfds
             FD_SET(i, &writefds);
             num_set_bits++;
         }
+        if (pfd->revents & POLLPRI) {
+            trace("select found fd %i has an exceptional condition", i);
+            FD_SET(i, &exceptfds);
+            num_set_bits++;
+        }
         if (pfd->revents & POLLNVAL) {
             trace("select found bad fd %i", i);
             num_bad_fds++;
@@ -2,6 +2,11 @@ use linux_api::errno::Errno;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::core::worker::Worker;
+use crate::cshadow as c;
+use crate::host::descriptor::socket::inet::InetSocket;
+use crate::host::descriptor::socket::Socket;
+use crate::host::descriptor::{CompatFile, File};
+use crate::host::host::HostParameters;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::types::ForeignArrayPtr;
 use crate::utility::case_insensitive_eq;
@@ -106,4 +111,193 @@ impl SyscallHandler {
 
         Ok(())
     }
+
+    log_syscall!(
+        shadow_tcp_cc_state,
+        /* rv */ std::ffi::c_int,
+        /* fd */ std::ffi::c_int,
+        /* state_ptr */ *const std::ffi::c_void,
+        /* state_len */ u64,
+    );
+    pub fn shadow_tcp_cc_state(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        state_ptr: ForeignPtr<c::ShadowTcpCcState>,
+        state_len: u64,
+    ) -> Result<(), Errno> {
+        if state_len < std::mem::size_of::<c::ShadowTcpCcState>() as u64 {
+            log::trace!("Invalid state_len {state_len}, returning EINVAL");
+            return Err(Errno::EINVAL);
+        }
+
+        let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+        let desc = Self::get_descriptor(&desc_table, fd)?;
+
+        let CompatFile::New(file) = desc.file() else {
+            return Err(Errno::ENOTSOCK);
+        };
+
+        let File::Socket(socket) = file.inner_file() else {
+            return Err(Errno::ENOTSOCK);
+        };
+
+        // only the legacy tcp stack models congestion control
+        let Socket::Inet(InetSocket::LegacyTcp(socket)) = socket else {
+            return Err(Errno::EOPNOTSUPP);
+        };
+
+        let state = {
+            let socket = socket.borrow();
+            if socket.getpeername().is_err() {
+                return Err(Errno::ENOTCONN);
+            }
+            socket.congestion_state()
+        };
+
+        ctx.objs
+            .process
+            .memory_borrow_mut()
+            .write(state_ptr, &state)?;
+
+        Ok(())
+    }
+
+    log_syscall!(
+        shadow_get_host_name,
+        /* rv */ std::ffi::c_int,
+        /* buf_ptr */ *const std::ffi::c_char,
+        /* buf_len */ u64,
+    );
+    pub fn shadow_get_host_name(
+        ctx: &mut SyscallContext,
+        buf_ptr: ForeignPtr<std::ffi::c_char>,
+        buf_len: u64,
+    ) -> Result<std::ffi::c_int, Errno> {
+        let name = ctx.objs.host.info().name.as_bytes();
+        let buf_len: usize = buf_len.try_into().unwrap();
+
+        // like snprintf, truncate the name so that the NUL fits
+        if buf_len > 0 {
+            let len = std::cmp::min(name.len(), buf_len - 1);
+            let mut truncated = name[..len].to_vec();
+            truncated.push(0);
+
+            let buf_ptr = ForeignArrayPtr::new(buf_ptr.cast::<u8>(), truncated.len());
+            ctx.objs
+                .process
+                .memory_borrow_mut()
+                .copy_to_ptr(buf_ptr, &truncated)?;
+        }
+
+        Ok(name.len().try_into().unwrap())
+    }
+
+    log_syscall!(
+        shadow_pin_cpu,
+        /* rv */ std::ffi::c_int,
+        /* cpu */ std::ffi::c_uint,
+    );
+    pub fn shadow_pin_cpu(
+        ctx: &mut SyscallContext,
+        cpu: std::ffi::c_uint,
+    ) -> Result<std::ffi::c_int, Errno> {
+        // shadow doesn't model the simulated CPUs individually, but limit them to the CPUs that a
+        // `cpu_set_t` can represent
+        if cpu >= libc::CPU_SETSIZE as u32 {
+            return Err(Errno::EINVAL);
+        }
+
+        let prev_cpu = ctx.objs.thread.set_simulated_cpu(cpu);
+
+        if prev_cpu != cpu {
+            // like linux, update the cpu of the thread's rseq area so that userspace (for example
+            // glibc's `sched_getcpu()`) sees the new cpu
+            if let Some(rseq_ptr) = ctx.objs.thread.rseq() {
+                if let Err(e) = Self::write_rseq_cpu(ctx, rseq_ptr) {
+                    log::debug!("Unable to update the thread's rseq area: {e}");
+                }
+            }
+
+            let cost = ctx.objs.host.params.cpu_migration_cost;
+            log::debug!(
+                "Thread {} migrated from simulated CPU {prev_cpu} to {cpu} with cost {cost:?}",
+                ctx.objs.thread.id(),
+            );
+
+            let mut host_shmem = ctx.objs.host.shim_shmem_lock_borrow_mut().unwrap();
+
+            // the migration cost is CPU time used by the thread
+            ctx.objs
+                .thread
+                .shmem()
+                .protected
+                .borrow_mut(&host_shmem.root)
+                .system_cpu_time += cost;
+
+            // like the latency of an unblocked syscall, the syscall handler will move time forward
+            // once enough latency has accumulated
+            if ctx.objs.host.shim_shmem().model_unblocked_syscall_latency {
+                host_shmem.unapplied_cpu_latency += cost;
+            }
+        }
+
+        Ok(prev_cpu.try_into().unwrap())
+    }
+
+    log_syscall!(
+        shadow_query_feature,
+        /* rv */ std::ffi::c_int,
+        /* name_ptr */ *const std::ffi::c_char,
+        /* name_len */ u64,
+    );
+    pub fn shadow_query_feature(
+        ctx: &mut SyscallContext,
+        name_ptr: ForeignPtr<std::ffi::c_char>,
+        name_len: u64,
+    ) -> Result<std::ffi::c_int, Errno> {
+        let status = if name_len > MAX_FEATURE_NAME_LEN {
+            // can't be a known feature, so don't bother reading it
+            c::ShadowFeatureStatus_SHADOW_FEATURE_UNSUPPORTED
+        } else {
+            let name_ptr =
+                ForeignArrayPtr::new(name_ptr.cast::<u8>(), name_len.try_into().unwrap());
+            let mem = ctx.objs.process.memory_borrow();
+            let name = mem.memory_ref(name_ptr)?;
+
+            match std::str::from_utf8(&name) {
+                Ok(name) => feature_status(name, &ctx.objs.host.params),
+                Err(_) => c::ShadowFeatureStatus_SHADOW_FEATURE_UNSUPPORTED,
+            }
+        };
+
+        Ok(status.try_into().unwrap())
+    }
+}
+
+/// Longer than the name of any feature.
+const MAX_FEATURE_NAME_LEN: u64 = 64;
+
+/// The status of a shadow feature, as returned by `shadow_query_feature`.
+fn feature_status(name: &str, params: &HostParameters) -> c::ShadowFeatureStatus {
+    let enabled = match name {
+        // always available in this version of shadow
+        "packet_sockets" | "socket_filters" | "tcp_cc_state" => true,
+        // depend on the simulation's configuration
+        "pcap" => params.pcap_config.is_some(),
+        "strace_logging" => params.strace_logging_options.is_some(),
+        "memory_manager" => params.use_mem_mapper,
+        "new_tcp" => params.use_new_tcp,
+        "ecn" => params.use_ecn,
+        "syscall_counters" => params.use_syscall_counters,
+        "deterministic_aslr" => params.use_deterministic_aslr,
+        // the optional cargo features listed in the build info
+        name if crate::shadow::compiled_features().contains(&name) => true,
+        _ => return c::ShadowFeatureStatus_SHADOW_FEATURE_UNSUPPORTED,
+    };
+
+    if enabled {
+        c::ShadowFeatureStatus_SHADOW_FEATURE_ENABLED
+    } else {
+        c::ShadowFeatureStatus_SHADOW_FEATURE_DISABLED
+    }
 }
@@ -0,0 +1,245 @@
+use std::os::fd::AsRawFd;
+
+use linux_api::errno::Errno;
+use linux_api::mman::{MapFlags, ProtFlags};
+use linux_api::shm::{shmid64_ds, ShmatFlags, ShmctlCmd, ShmgetFlags, IPC_64, SHMLBA};
+use log::*;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::core::worker::Worker;
+use crate::host::context::ThreadContext;
+use crate::host::memory_manager::AllocdMem;
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::types::SyscallError;
+
+impl SyscallHandler {
+    log_syscall!(
+        shmget,
+        /* rv */ std::ffi::c_int,
+        /* key */ std::ffi::c_int,
+        /* size */ usize,
+        /* shmflg */ std::ffi::c_int,
+    );
+    pub fn shmget(
+        ctx: &mut SyscallContext,
+        key: std::ffi::c_int,
+        size: usize,
+        shmflg: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        // the lower 9 bits are the permissions of a new segment
+        let mode = (shmflg & 0o777) as u32;
+
+        let Some(flags) = ShmgetFlags::from_bits(shmflg & !0o777) else {
+            debug!("Unrecognized shmget flags: {shmflg:#o}");
+            return Err(Errno::EINVAL.into());
+        };
+
+        if flags.contains(ShmgetFlags::SHM_HUGETLB) {
+            warn_once_then_debug!("shmget with SHM_HUGETLB is not supported");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let id = ctx.objs.host.sysv_shm_borrow_mut().get(
+            key,
+            size,
+            flags,
+            mode,
+            ctx.objs.process.id(),
+            Worker::current_time().unwrap(),
+        )?;
+
+        Ok(id)
+    }
+
+    log_syscall!(
+        shmat,
+        /* rv */ *const std::ffi::c_void,
+        /* shmid */ std::ffi::c_int,
+        /* shmaddr */ *const std::ffi::c_void,
+        /* shmflg */ std::ffi::c_int,
+    );
+    pub fn shmat(
+        ctx: &mut SyscallContext,
+        shmid: std::ffi::c_int,
+        shmaddr: ForeignPtr<u8>,
+        shmflg: std::ffi::c_int,
+    ) -> Result<ForeignPtr<u8>, SyscallError> {
+        let Some(flags) = ShmatFlags::from_bits(shmflg) else {
+            debug!("Unrecognized shmat flags: {shmflg:#o}");
+            return Err(Errno::EINVAL.into());
+        };
+
+        let mut addr = usize::from(shmaddr);
+        let mut map_flags = MapFlags::MAP_SHARED;
+
+        if addr != 0 {
+            if flags.contains(ShmatFlags::SHM_RND) {
+                addr -= addr % SHMLBA;
+            } else if addr % SHMLBA != 0 {
+                return Err(Errno::EINVAL.into());
+            }
+
+            // without SHM_REMAP, linux won't replace an existing mapping
+            map_flags |= if flags.contains(ShmatFlags::SHM_REMAP) {
+                MapFlags::MAP_FIXED
+            } else {
+                MapFlags::MAP_FIXED_NOREPLACE
+            };
+        } else if flags.contains(ShmatFlags::SHM_REMAP) {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let read_only = flags.contains(ShmatFlags::SHM_RDONLY);
+
+        let mut prot = ProtFlags::PROT_READ;
+        if !read_only {
+            prot |= ProtFlags::PROT_WRITE;
+        }
+        if flags.contains(ShmatFlags::SHM_EXEC) {
+            prot |= ProtFlags::PROT_EXEC;
+        }
+
+        let (native_fd, len) = {
+            let shm = ctx.objs.host.sysv_shm_borrow();
+            let segment = shm.segment(shmid).ok_or(Errno::EINVAL)?;
+            (segment.file().as_raw_fd(), segment.mapped_len())
+        };
+
+        // map the segment's memfd into the plugin, where it's shared with all other attachments
+        let plugin_fd = Self::open_shm_in_plugin(ctx.objs, native_fd, read_only)?;
+
+        let mmap_result = ctx.objs.process.memory_borrow_mut().do_mmap(
+            ctx.objs,
+            ForeignPtr::<()>::from(addr).cast::<u8>(),
+            len,
+            prot,
+            map_flags,
+            plugin_fd,
+            0,
+        );
+
+        let (process_ctx, thread) = ctx.objs.split_thread();
+        if let Err(e) = thread.native_close(&process_ctx, plugin_fd) {
+            trace!("Failed to close file at fd {plugin_fd} in plugin, error {e}");
+        }
+
+        let mapped = match mmap_result {
+            Ok(x) => x,
+            // the requested address overlaps an existing mapping
+            Err(Errno::EEXIST) => return Err(Errno::EINVAL.into()),
+            Err(e) => return Err(e.into()),
+        };
+
+        ctx.objs.host.sysv_shm_borrow_mut().attach(
+            shmid,
+            ctx.objs.process.id(),
+            usize::from(mapped),
+            Worker::current_time().unwrap(),
+        );
+
+        Ok(mapped)
+    }
+
+    log_syscall!(
+        shmdt,
+        /* rv */ std::ffi::c_int,
+        /* shmaddr */ *const std::ffi::c_void,
+    );
+    pub fn shmdt(ctx: &mut SyscallContext, shmaddr: ForeignPtr<u8>) -> Result<(), SyscallError> {
+        let Some(len) = ctx.objs.host.sysv_shm_borrow_mut().detach(
+            ctx.objs.process.id(),
+            usize::from(shmaddr),
+            Worker::current_time().unwrap(),
+        ) else {
+            return Err(Errno::EINVAL.into());
+        };
+
+        ctx.objs
+            .process
+            .memory_borrow_mut()
+            .do_munmap(ctx.objs, shmaddr, len)?;
+
+        Ok(())
+    }
+
+    log_syscall!(
+        shmctl,
+        /* rv */ std::ffi::c_int,
+        /* shmid */ std::ffi::c_int,
+        /* cmd */ std::ffi::c_int,
+        /* buf */ *const std::ffi::c_void,
+    );
+    pub fn shmctl(
+        ctx: &mut SyscallContext,
+        shmid: std::ffi::c_int,
+        cmd: std::ffi::c_int,
+        buf: ForeignPtr<shmid64_ds>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let Ok(cmd) = ShmctlCmd::try_from(cmd & !IPC_64) else {
+            debug!("Unrecognized shmctl command {cmd}");
+            return Err(Errno::EINVAL.into());
+        };
+
+        match cmd {
+            ShmctlCmd::IPC_STAT => {
+                let stat = {
+                    let shm = ctx.objs.host.sysv_shm_borrow();
+                    shm.segment(shmid).ok_or(Errno::EINVAL)?.stat()
+                };
+                ctx.objs.process.memory_borrow_mut().write(buf, &stat)?;
+            }
+            ShmctlCmd::IPC_RMID => {
+                ctx.objs
+                    .host
+                    .sysv_shm_borrow_mut()
+                    .remove(shmid, Worker::current_time().unwrap())?;
+            }
+            _ => {
+                warn_once_then_debug!("shmctl command {cmd:?} is not supported");
+                return Err(Errno::EINVAL.into());
+            }
+        }
+
+        Ok(0)
+    }
+
+    /// Open the shadow-owned memfd `native_fd` in the plugin. The caller must close the returned
+    /// plugin fd.
+    fn open_shm_in_plugin(
+        ctx: &ThreadContext,
+        native_fd: std::ffi::c_int,
+        read_only: bool,
+    ) -> Result<i32, Errno> {
+        let path = format!("/proc/{}/fd/{native_fd}\0", std::process::id());
+
+        // must free this, but will panic if borrowing the memory manager
+        let plugin_buffer = AllocdMem::<u8>::new(ctx, path.len());
+
+        let res = ctx
+            .process
+            .memory_borrow_mut()
+            .copy_to_ptr(plugin_buffer.ptr(), path.as_bytes());
+        if let Err(e) = res {
+            plugin_buffer.free(ctx);
+            return Err(e);
+        }
+
+        let access = if read_only {
+            libc::O_RDONLY
+        } else {
+            libc::O_RDWR
+        };
+
+        let (process_ctx, thread) = ctx.split_thread();
+        let open_result = thread.native_open(
+            &process_ctx,
+            plugin_buffer.ptr().ptr(),
+            access | libc::O_CLOEXEC,
+            0,
+        );
+
+        plugin_buffer.free(ctx);
+
+        open_result
+    }
+}
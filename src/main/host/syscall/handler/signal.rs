@@ -1,9 +1,10 @@
 use linux_api::errno::Errno;
-use linux_api::signal::{defaultaction, siginfo_t, LinuxDefaultAction, Signal, SignalHandler};
+use linux_api::signal::{siginfo_t, Signal};
 use shadow_shim_helper_rs::explicit_drop::{ExplicitDrop, ExplicitDropper};
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
+use crate::host::descriptor::{CompatFile, File};
 use crate::host::process::Process;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler, ThreadContext};
 use crate::host::syscall::types::SyscallError;
@@ -62,6 +63,60 @@ impl SyscallHandler {
         Self::signal_process(ctx.objs, target_process, sig)
     }
 
+    log_syscall!(
+        pidfd_send_signal,
+        /* rv */ std::ffi::c_int,
+        /* pidfd */ std::ffi::c_int,
+        /* sig */ std::ffi::c_int,
+        /* info */ *const std::ffi::c_void,
+        /* flags */ std::ffi::c_uint,
+    );
+    pub fn pidfd_send_signal(
+        ctx: &mut SyscallContext,
+        pidfd: std::ffi::c_int,
+        sig: std::ffi::c_int,
+        info: ForeignPtr<siginfo_t>,
+        flags: std::ffi::c_uint,
+    ) -> Result<(), Errno> {
+        log::trace!("pidfd_send_signal called on pidfd {pidfd} with signal {sig}");
+
+        // pidfd_send_signal(2): "The flags argument is reserved for future use; currently, this
+        // argument must be specified as 0."
+        if flags != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        if !info.is_null() {
+            log::warn!("pidfd_send_signal with a non-null siginfo is unimplemented");
+            return Err(Errno::ENOTSUP);
+        }
+
+        let pid = {
+            let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+            let desc = Self::get_descriptor(&desc_table, pidfd)?;
+
+            // pidfd_send_signal(2): "EINVAL: pidfd is not a valid PID file descriptor."
+            let CompatFile::New(file) = desc.file() else {
+                return Err(Errno::EINVAL);
+            };
+            let File::PidFd(pidfd) = file.inner_file() else {
+                return Err(Errno::EINVAL);
+            };
+
+            let pid = pidfd.borrow().pid();
+            pid
+        };
+
+        // the process has been reaped
+        let Some(target_process) = ctx.objs.host.process_borrow(pid) else {
+            log::debug!("Process {pid:?} not found");
+            return Err(Errno::ESRCH);
+        };
+        let target_process = &*target_process.borrow(ctx.objs.host.root());
+
+        Self::signal_process(ctx.objs, target_process, sig)
+    }
+
     /// Send a signal to `target_process` from the thread and process in `objs`. A signal of 0 will
     /// be ignored.
     fn signal_process(
@@ -169,80 +224,14 @@ impl SyscallHandler {
             return Err(Errno::ENOTSUP);
         }
 
-        // need to scope the shmem lock since `wakeup_for_signal` below takes its own shmem lock
-        let mut cond = {
-            let shmem_lock = &*objs.host.shim_shmem_lock_borrow().unwrap();
-
-            let target_process = objs
-                .host
-                .process_borrow(target_thread.process_id())
-                .unwrap();
-            let target_process = &*target_process.borrow(objs.host.root());
-
-            let process_shmem = target_process.borrow_as_runnable().unwrap();
-            let process_shmem = &*process_shmem.shmem();
-            let process_protected = process_shmem.protected.borrow(&shmem_lock.root);
-
-            let thread_shmem = target_thread.shmem();
-            let mut thread_protected = thread_shmem.protected.borrow_mut(&shmem_lock.root);
-
-            let action = unsafe { process_protected.signal_action(signal) };
-            let action_handler = unsafe { action.handler() };
-
-            let signal_is_ignored = match action_handler {
-                SignalHandler::SigIgn => true,
-                SignalHandler::SigDfl => defaultaction(signal) == LinuxDefaultAction::IGN,
-                _ => false,
-            };
-
-            if signal_is_ignored {
-                // don't deliver an ignored signal
-                return Ok(());
-            }
-
-            if thread_protected.pending_signals.has(signal) {
-                // Signal is already pending. From signal(7): In the case where a standard signal is
-                // already pending, the siginfo_t structure (see sigaction(2)) associated with that
-                // signal is not overwritten on arrival of subsequent instances of the same signal.
-                return Ok(());
-            }
-
-            thread_protected.pending_signals.add(signal);
-
-            let sender_pid = objs.process.id();
-            let sender_tid = objs.thread.id();
-
-            let siginfo = siginfo_t::new_for_tkill(signal, sender_pid.into(), 0);
-
-            thread_protected.set_pending_standard_siginfo(signal, &siginfo);
-
-            if sender_tid == target_thread.id() {
-                // Target is the current thread. It'll be handled synchronously when the current
-                // syscall returns (if it's unblocked).
-                return Ok(());
-            }
-
-            if thread_protected.blocked_signals.has(signal) {
-                // Target thread has the signal blocked. We'll leave it pending, but no need to
-                // schedule an event to process the signal. It'll get processed synchronously when
-                // the thread executes a syscall that would unblock the signal.
-                return Ok(());
-            }
-
-            let Some(cond) = target_thread.syscall_condition_mut() else {
-                // We may be able to get here if a thread is signalled before it runs for the first
-                // time. Just return; the signal will be delivered when the thread runs.
-                return Ok(());
-            };
-
-            cond
-        };
-
-        let was_scheduled = cond.wakeup_for_signal(objs.host, signal);
+        let target_process = objs
+            .host
+            .process_borrow(target_thread.process_id())
+            .unwrap();
+        let target_process = &*target_process.borrow(objs.host.root());
 
-        // it won't be scheduled if the signal is blocked, but we previously checked if the signal
-        // was blocked above
-        assert!(was_scheduled);
+        let siginfo = siginfo_t::new_for_tkill(signal, objs.process.id().into(), 0);
+        target_process.signal_thread(objs.host, Some(objs.thread), target_thread.id(), &siginfo);
 
         Ok(())
     }
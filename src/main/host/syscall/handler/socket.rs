@@ -3,17 +3,26 @@ use linux_api::fcntl::DescriptorFlags;
 use linux_api::socket::Shutdown;
 use log::*;
 use nix::sys::socket::SockFlag;
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
+use crate::core::worker::Worker;
+use crate::host::context::ThreadContext;
 use crate::host::descriptor::descriptor_table::DescriptorHandle;
 use crate::host::descriptor::socket::inet::legacy_tcp::LegacyTcpSocket;
 use crate::host::descriptor::socket::inet::tcp::TcpSocket;
 use crate::host::descriptor::socket::inet::udp::UdpSocket;
 use crate::host::descriptor::socket::inet::InetSocket;
 use crate::host::descriptor::socket::netlink::{NetlinkFamily, NetlinkSocket, NetlinkSocketType};
+use crate::host::descriptor::socket::packet::{PacketSocket, PacketSocketType};
 use crate::host::descriptor::socket::unix::{UnixSocket, UnixSocketType};
-use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket};
+use crate::host::descriptor::socket::{
+    IgnoredSockopts, RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket, SocketRefMut,
+};
 use crate::host::descriptor::{CompatFile, Descriptor, File, FileState, FileStatus, OpenFile};
+use crate::host::memory_manager::MemoryManager;
+use crate::host::network::namespace::NetworkNamespace;
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::io::{self, IoVec};
 use crate::host::syscall::type_formatting::{SyscallBufferArg, SyscallSockAddrArg};
@@ -22,6 +31,10 @@ use crate::host::syscall::types::SyscallError;
 use crate::utility::callback_queue::CallbackQueue;
 use crate::utility::sockaddr::SockaddrStorage;
 
+// Not defined by the libc crate. From linux's "include/linux/net.h".
+const SOCK_TYPE_MASK: std::ffi::c_int = 0xf;
+const SOCK_MAX: std::ffi::c_int = libc::SOCK_PACKET + 1;
+
 impl SyscallHandler {
     log_syscall!(
         socket,
@@ -124,6 +137,28 @@ impl SyscallHandler {
                 };
                 Socket::Netlink(NetlinkSocket::new(file_flags, socket_type, family))
             }
+            libc::AF_PACKET => {
+                let socket_type = match PacketSocketType::try_from(socket_type) {
+                    Ok(x) => x,
+                    Err(e) => {
+                        warn!("{}", e);
+                        return Err(Errno::ESOCKTNOSUPPORT);
+                    }
+                };
+
+                // the protocol is an ethernet protocol in network byte order
+                let Ok(protocol) = u16::try_from(protocol) else {
+                    return Err(Errno::EINVAL);
+                };
+
+                let socket = PacketSocket::new(file_flags, socket_type, protocol);
+                ctx.objs
+                    .host
+                    .network_namespace_borrow()
+                    .add_packet_socket(&socket);
+
+                Socket::Packet(socket)
+            }
             _ => return Err(Errno::EAFNOSUPPORT),
         };
 
@@ -247,10 +282,17 @@ impl SyscallHandler {
             flags,
         };
 
-        // call the socket's sendmsg(), and run any resulting events
-        let mut result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-            Socket::sendmsg(socket, args, &mut mem, &net_ns, &mut *rng, cb_queue)
-        });
+        let deadline = ctx.handler.socket_deadline(socket.borrow().send_timeout());
+
+        let sendall = SendallState {
+            objs: ctx.objs,
+            bytes_sent: &mut ctx.handler.bytes_written,
+            timed_out: is_past_deadline(deadline),
+        };
+
+        let result =
+            Self::socket_sendmsg(socket, args, &mut mem, &net_ns, &mut *rng, Some(sendall));
+        let mut result = apply_socket_deadline(result, deadline);
 
         // if the syscall will block, keep the file open until the syscall restarts
         if let Some(err) = result.as_mut().err() {
@@ -308,20 +350,24 @@ impl SyscallHandler {
         let mut rng = ctx.objs.host.random_mut();
         let net_ns = ctx.objs.host.network_namespace_borrow();
 
-        let msg = io::read_msghdr(&mem, msg_ptr)?;
+        let deadline = ctx.handler.socket_deadline(socket.borrow().send_timeout());
 
-        let args = SendmsgArgs {
-            addr: io::read_sockaddr(&mem, msg.name, msg.name_len)?,
-            iovs: &msg.iovs,
-            control_ptr: ForeignArrayPtr::new(msg.control, msg.control_len),
-            // note: "the msg_flags field is ignored" for sendmsg; see send(2)
-            flags,
+        let sendall = SendallState {
+            objs: ctx.objs,
+            bytes_sent: &mut ctx.handler.bytes_written,
+            timed_out: is_past_deadline(deadline),
         };
 
-        // call the socket's sendmsg(), and run any resulting events
-        let mut result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-            Socket::sendmsg(socket, args, &mut mem, &net_ns, &mut *rng, cb_queue)
-        });
+        let result = Self::sendmsg_helper(
+            socket,
+            msg_ptr,
+            flags,
+            &mut mem,
+            &net_ns,
+            &mut *rng,
+            Some(sendall),
+        );
+        let mut result = apply_socket_deadline(result, deadline);
 
         // if the syscall will block, keep the file open until the syscall restarts
         if let Some(err) = result.as_mut().err() {
@@ -334,6 +380,118 @@ impl SyscallHandler {
         Ok(bytes_written)
     }
 
+    /// Send the message described by the plugin's `msghdr` at `msg_ptr`. Shared by `sendmsg()`
+    /// and `sendmmsg()`.
+    fn sendmsg_helper(
+        socket: &Socket,
+        msg_ptr: ForeignPtr<libc::msghdr>,
+        flags: std::ffi::c_int,
+        mem: &mut MemoryManager,
+        net_ns: &NetworkNamespace,
+        rng: impl rand::Rng,
+        sendall: Option<SendallState>,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        let msg = io::read_msghdr(mem, msg_ptr)?;
+
+        let args = SendmsgArgs {
+            addr: io::read_sockaddr(mem, msg.name, msg.name_len)?,
+            iovs: &msg.iovs,
+            control_ptr: ForeignArrayPtr::new(msg.control, msg.control_len),
+            // note: "the msg_flags field is ignored" for sendmsg; see send(2)
+            flags,
+        };
+
+        Self::socket_sendmsg(socket, args, mem, net_ns, rng, sendall)
+    }
+
+    /// Call the socket's `sendmsg()`. If `sendall` is provided and this is a blocking send on a
+    /// stream socket, keep sending until all of the data has been sent, or until the send is
+    /// interrupted or times out after sending some of the data.
+    pub(super) fn socket_sendmsg(
+        socket: &Socket,
+        args: SendmsgArgs,
+        mem: &mut MemoryManager,
+        net_ns: &NetworkNamespace,
+        mut rng: impl rand::Rng,
+        sendall: Option<SendallState>,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        let is_stream = match socket {
+            Socket::Inet(InetSocket::LegacyTcp(_) | InetSocket::Tcp(_)) => true,
+            Socket::Unix(socket) => socket.borrow().socket_type() == UnixSocketType::Stream,
+            _ => false,
+        };
+
+        let sendall = sendall.filter(|_| {
+            is_stream
+                && args.flags & libc::MSG_DONTWAIT == 0
+                && !socket.borrow().status().contains(FileStatus::NONBLOCK)
+        });
+
+        let Some(SendallState {
+            objs,
+            bytes_sent,
+            timed_out,
+        }) = sendall
+        else {
+            // call the socket's sendmsg(), and run any resulting events
+            return CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                Socket::sendmsg(socket, args, mem, net_ns, rng, cb_queue)
+            });
+        };
+
+        let total_len: libc::size_t = args.iovs.iter().map(|x| x.len).sum();
+
+        // A blocked syscall is restarted from the beginning, so if we block after sending some of
+        // the data, we continue from `bytes_sent` when the socket becomes writable again.
+        loop {
+            let iovs = io::skip_iovs(args.iovs, *bytes_sent);
+            let args = SendmsgArgs {
+                addr: args.addr,
+                iovs: &iovs,
+                // the control data was sent along with the first bytes
+                control_ptr: if *bytes_sent == 0 {
+                    args.control_ptr
+                } else {
+                    ForeignArrayPtr::new(ForeignPtr::null(), 0)
+                },
+                flags: args.flags,
+            };
+
+            // call the socket's sendmsg(), and run any resulting events
+            let result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                Socket::sendmsg(socket, args, mem, net_ns, &mut rng, cb_queue)
+            });
+
+            let num_sent = match result {
+                Ok(x) => usize::try_from(x).unwrap(),
+                // we would block, but we can't return EINTR or EAGAIN if we already sent some data
+                Err(SyscallError::Blocked(_))
+                    if *bytes_sent > 0
+                        && (timed_out
+                            || objs.thread.unblocked_signal_pending(
+                                objs.process,
+                                &objs.host.shim_shmem_lock_borrow().unwrap(),
+                            )) =>
+                {
+                    break;
+                }
+                Err(e @ SyscallError::Blocked(_)) => return Err(e),
+                // return the data we already sent, and the socket will return the error again on
+                // the next call
+                Err(_) if *bytes_sent > 0 => break,
+                Err(e) => return Err(e),
+            };
+
+            *bytes_sent += num_sent;
+
+            if num_sent == 0 || *bytes_sent >= total_len {
+                break;
+            }
+        }
+
+        Ok((*bytes_sent).try_into().unwrap())
+    }
+
     log_syscall!(
         recvfrom,
         /* rv */ libc::ssize_t,
@@ -395,10 +553,16 @@ impl SyscallHandler {
             flags,
         };
 
-        // call the socket's recvmsg(), and run any resulting events
-        let mut result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-            Socket::recvmsg(socket, args, &mut mem, cb_queue)
-        });
+        let deadline = ctx.handler.socket_deadline(socket.borrow().recv_timeout());
+
+        let waitall = WaitallState {
+            objs: ctx.objs,
+            bytes_received: &mut ctx.handler.waitall_bytes_received,
+            timed_out: is_past_deadline(deadline),
+        };
+
+        let result = Self::socket_recvmsg(socket, args, &mut mem, Some(waitall));
+        let mut result = apply_socket_deadline(result, deadline);
 
         // if the syscall will block, keep the file open until the syscall restarts
         if let Some(err) = result.as_mut().err() {
@@ -463,18 +627,16 @@ impl SyscallHandler {
 
         let mut mem = ctx.objs.process.memory_borrow_mut();
 
-        let mut msg = io::read_msghdr(&mem, msg_ptr)?;
+        let deadline = ctx.handler.socket_deadline(socket.borrow().recv_timeout());
 
-        let args = RecvmsgArgs {
-            iovs: &msg.iovs,
-            control_ptr: ForeignArrayPtr::new(msg.control, msg.control_len),
-            flags,
+        let waitall = WaitallState {
+            objs: ctx.objs,
+            bytes_received: &mut ctx.handler.waitall_bytes_received,
+            timed_out: is_past_deadline(deadline),
         };
 
-        // call the socket's recvmsg(), and run any resulting events
-        let mut result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-            Socket::recvmsg(socket, args, &mut mem, cb_queue)
-        });
+        let result = Self::recvmsg_helper(socket, msg_ptr, flags, &mut mem, Some(waitall));
+        let mut result = apply_socket_deadline(result, deadline);
 
         // if the syscall will block, keep the file open until the syscall restarts
         if let Some(err) = result.as_mut().err() {
@@ -483,12 +645,33 @@ impl SyscallHandler {
             }
         }
 
-        let result = result?;
+        let bytes_read = result?;
+        Ok(bytes_read)
+    }
+
+    /// Receive a message into the plugin's `msghdr` at `msg_ptr`, and update the `msghdr` fields
+    /// that the kernel would update. Shared by `recvmsg()` and `recvmmsg()`.
+    fn recvmsg_helper(
+        socket: &Socket,
+        msg_ptr: ForeignPtr<libc::msghdr>,
+        flags: std::ffi::c_int,
+        mem: &mut MemoryManager,
+        waitall: Option<WaitallState>,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        let mut msg = io::read_msghdr(mem, msg_ptr)?;
+
+        let args = RecvmsgArgs {
+            iovs: &msg.iovs,
+            control_ptr: ForeignArrayPtr::new(msg.control, msg.control_len),
+            flags,
+        };
+
+        let result = Self::socket_recvmsg(socket, args, mem, waitall)?;
 
         // write the socket address to the plugin and update the length in msg
         if !msg.name.is_null() {
             if let Some(from_addr) = result.addr.as_ref() {
-                msg.name_len = io::write_sockaddr(&mut mem, from_addr, msg.name, msg.name_len)?;
+                msg.name_len = io::write_sockaddr(mem, from_addr, msg.name, msg.name_len)?;
             } else {
                 msg.name_len = 0;
             }
@@ -499,11 +682,407 @@ impl SyscallHandler {
         msg.flags = result.msg_flags;
 
         // write msg back to the plugin
-        io::update_msghdr(&mut mem, msg_ptr, msg)?;
+        io::update_msghdr(mem, msg_ptr, msg)?;
 
         Ok(result.return_val)
     }
 
+    /// Call the socket's `recvmsg()`, and run any resulting events. If `waitall` is given and this
+    /// is a blocking stream socket receiving with `MSG_WAITALL`, keep receiving until the iovs are
+    /// full, the peer closes the connection, or a signal interrupts the syscall. `MSG_WAITALL` is
+    /// ignored for datagram and seqpacket sockets.
+    fn socket_recvmsg(
+        socket: &Socket,
+        args: RecvmsgArgs,
+        mem: &mut MemoryManager,
+        waitall: Option<WaitallState>,
+    ) -> Result<RecvmsgReturn, SyscallError> {
+        let is_stream = match socket {
+            Socket::Inet(InetSocket::LegacyTcp(_) | InetSocket::Tcp(_)) => true,
+            Socket::Unix(socket) => socket.borrow().socket_type() == UnixSocketType::Stream,
+            _ => false,
+        };
+
+        // linux ignores MSG_WAITALL for non-blocking recvs, and we don't support waiting for a
+        // peek or for urgent data
+        let waitall = waitall.filter(|_| {
+            is_stream
+                && args.flags & libc::MSG_WAITALL != 0
+                && args.flags & (libc::MSG_DONTWAIT | libc::MSG_PEEK | libc::MSG_OOB) == 0
+                && !socket.borrow().status().contains(FileStatus::NONBLOCK)
+        });
+
+        let Some(WaitallState {
+            objs,
+            bytes_received,
+            timed_out,
+        }) = waitall
+        else {
+            return CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                Socket::recvmsg(socket, args, mem, cb_queue)
+            });
+        };
+
+        let total_len: libc::size_t = args.iovs.iter().map(|x| x.len).sum();
+
+        // A blocked syscall is restarted from the beginning, so if we block after receiving some of
+        // the data, we continue from `bytes_received` when the socket becomes readable again. Since
+        // we receive all of the available data before blocking, the socket won't be readable until
+        // more data arrives or the peer closes the connection, so we won't busy-loop.
+        loop {
+            let iovs = io::skip_iovs(args.iovs, *bytes_received);
+            let args = RecvmsgArgs {
+                iovs: &iovs,
+                control_ptr: args.control_ptr,
+                flags: args.flags,
+            };
+
+            // call the socket's recvmsg(), and run any resulting events
+            let result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                Socket::recvmsg(socket, args, mem, cb_queue)
+            });
+
+            let num_recv = match result {
+                Ok(x) => usize::try_from(x.return_val).unwrap(),
+                // we would block, but we can't return EINTR or EAGAIN if we already received some
+                // data
+                Err(SyscallError::Blocked(_))
+                    if *bytes_received > 0
+                        && (timed_out
+                            || objs.thread.unblocked_signal_pending(
+                                objs.process,
+                                &objs.host.shim_shmem_lock_borrow().unwrap(),
+                            )) =>
+                {
+                    break;
+                }
+                Err(e @ SyscallError::Blocked(_)) => return Err(e),
+                // return the data we already received, and the socket will return the error again
+                // on the next call
+                Err(_) if *bytes_received > 0 => break,
+                Err(e) => return Err(e),
+            };
+
+            *bytes_received += num_recv;
+
+            // stop if the peer closed the connection or the iovs are full
+            if num_recv == 0 || *bytes_received >= total_len {
+                break;
+            }
+        }
+
+        Ok(RecvmsgReturn {
+            return_val: (*bytes_received).try_into().unwrap(),
+            addr: None,
+            msg_flags: 0,
+            control_len: 0,
+        })
+    }
+
+    /// Returns the time at which a blocking send or recv gives up, given the socket's
+    /// `SO_SNDTIMEO` or `SO_RCVTIMEO` timeout. The deadline is kept across invocations of a blocked
+    /// syscall.
+    pub(super) fn socket_deadline(
+        &mut self,
+        timeout: Option<SimulationTime>,
+    ) -> Option<EmulatedTime> {
+        if self.socket_deadline.is_none() {
+            let now = Worker::current_time().unwrap();
+            self.socket_deadline = timeout.and_then(|x| now.checked_add(x));
+        }
+        self.socket_deadline
+    }
+
+    /// Called by `close()` after the descriptor has been removed from the descriptor table, but
+    /// before it's closed. If this is the last descriptor for a TCP socket with a non-zero
+    /// `SO_LINGER` timeout, block until the socket's sent data has been acknowledged by the peer or
+    /// the timeout expires. Otherwise returns the descriptor so that it can be closed.
+    ///
+    /// Like linux, the descriptor is released even if the linger is interrupted by a signal, in
+    /// which case `close()` returns `EINTR`.
+    pub(super) fn linger_before_close(
+        ctx: &mut SyscallContext,
+        desc: Descriptor,
+    ) -> Result<Descriptor, SyscallError> {
+        let open_file = match desc.file() {
+            // if there are other references to the file, closing won't close the socket
+            CompatFile::New(file) if file.is_last_reference() => file.clone(),
+            _ => return Ok(desc),
+        };
+
+        let File::Socket(Socket::Inet(InetSocket::LegacyTcp(socket))) = open_file.inner_file()
+        else {
+            return Ok(desc);
+        };
+
+        let Some(timeout) = socket.borrow_mut().start_linger() else {
+            return Ok(desc);
+        };
+
+        let deadline = ctx.handler.socket_deadline(Some(timeout));
+        if is_past_deadline(deadline) {
+            return Ok(desc);
+        }
+
+        let mut err = SyscallError::new_blocked_on_file(
+            open_file.inner_file().clone(),
+            FileState::SOCKET_OUTPUT_ACKED,
+            /* restartable= */ false,
+        );
+        let cond = err.blocked_condition().unwrap();
+        cond.set_timeout(deadline);
+        // the condition keeps the socket open until the syscall completes
+        cond.set_active_file(open_file);
+
+        // this isn't the last reference to the socket, so this won't close it
+        let rv = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            desc.close(ctx.objs.host, cb_queue)
+        });
+        assert!(rv.is_none());
+
+        Err(err)
+    }
+
+    log_syscall!(
+        sendmmsg,
+        /* rv */ std::ffi::c_int,
+        /* sockfd */ std::ffi::c_int,
+        /* msgvec */ *const libc::mmsghdr,
+        /* vlen */ std::ffi::c_uint,
+        /* flags */ nix::sys::socket::MsgFlags,
+    );
+    pub fn sendmmsg(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        msgvec_ptr: ForeignPtr<libc::mmsghdr>,
+        vlen: std::ffi::c_uint,
+        flags: std::ffi::c_int,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        // if we were previously blocked, get the active file from the last syscall handler
+        // invocation since it may no longer exist in the descriptor table
+        let file = ctx
+            .objs
+            .thread
+            .syscall_condition()
+            // if this was for a C descriptor, then there won't be an active file object
+            .and_then(|x| x.active_file().cloned());
+
+        let file = match file {
+            // we were previously blocked, so re-use the file from the previous syscall invocation
+            Some(x) => x,
+            // get the file from the descriptor table, or return early if it doesn't exist
+            None => {
+                let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+                match Self::get_descriptor(&desc_table, fd)?.file() {
+                    CompatFile::New(file) => file.clone(),
+                    CompatFile::Legacy(_file) => {
+                        return Err(Errno::ENOTSOCK.into());
+                    }
+                }
+            }
+        };
+
+        let File::Socket(ref socket) = file.inner_file() else {
+            return Err(Errno::ENOTSOCK.into());
+        };
+
+        // like linux, silently limit the number of messages
+        let vlen = std::cmp::min(vlen, libc::UIO_MAXIOV.try_into().unwrap());
+
+        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut rng = ctx.objs.host.random_mut();
+        let net_ns = ctx.objs.host.network_namespace_borrow();
+
+        let mut num_sent = 0;
+
+        for i in 0..usize::try_from(vlen).unwrap() {
+            let entry_ptr = msgvec_ptr.add(i);
+
+            // if a syscall blocks, shadow restarts it from the beginning, so we must only block
+            // if we haven't sent any messages yet
+            let flags = if num_sent == 0 {
+                flags
+            } else {
+                flags | libc::MSG_DONTWAIT
+            };
+
+            let result = Self::sendmsg_helper(
+                socket,
+                entry_ptr.cast::<libc::msghdr>(),
+                flags,
+                &mut mem,
+                &net_ns,
+                &mut *rng,
+                None,
+            );
+
+            let bytes_sent = match result {
+                Ok(x) => x,
+                // sendmmsg(2): "If an error occurs after at least one message has been sent, the
+                // call succeeds, and returns the number of messages sent."
+                Err(_) if num_sent > 0 => break,
+                Err(mut err) => {
+                    // if the syscall will block, keep the file open until the syscall restarts
+                    if let Some(cond) = err.blocked_condition() {
+                        cond.set_active_file(file);
+                    }
+                    return Err(err);
+                }
+            };
+
+            Self::write_mmsghdr_len(&mut mem, entry_ptr, bytes_sent)?;
+            num_sent += 1;
+        }
+
+        Ok(num_sent)
+    }
+
+    log_syscall!(
+        recvmmsg,
+        /* rv */ std::ffi::c_int,
+        /* sockfd */ std::ffi::c_int,
+        /* msgvec */ *const libc::mmsghdr,
+        /* vlen */ std::ffi::c_uint,
+        /* flags */ nix::sys::socket::MsgFlags,
+        /* timeout */ *const linux_api::time::timespec,
+    );
+    pub fn recvmmsg(
+        ctx: &mut SyscallContext,
+        fd: std::ffi::c_int,
+        msgvec_ptr: ForeignPtr<libc::mmsghdr>,
+        vlen: std::ffi::c_uint,
+        flags: std::ffi::c_int,
+        timeout_ptr: ForeignPtr<linux_api::time::timespec>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        // if we were previously blocked, get the active file from the last syscall handler
+        // invocation since it may no longer exist in the descriptor table
+        let file = ctx
+            .objs
+            .thread
+            .syscall_condition()
+            // if this was for a C descriptor, then there won't be an active file object
+            .and_then(|x| x.active_file().cloned());
+
+        let file = match file {
+            // we were previously blocked, so re-use the file from the previous syscall invocation
+            Some(x) => x,
+            // get the file from the descriptor table, or return early if it doesn't exist
+            None => {
+                let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+                match Self::get_descriptor(&desc_table, fd)?.file() {
+                    CompatFile::New(file) => file.clone(),
+                    CompatFile::Legacy(_file) => {
+                        return Err(Errno::ENOTSOCK.into());
+                    }
+                }
+            }
+        };
+
+        let File::Socket(ref socket) = file.inner_file() else {
+            return Err(Errno::ENOTSOCK.into());
+        };
+
+        if vlen == 0 {
+            return Ok(0);
+        }
+
+        // like linux, silently limit the number of messages
+        let vlen = std::cmp::min(vlen, libc::UIO_MAXIOV.try_into().unwrap());
+
+        // we never block after the first message (see below), so we always behave as if
+        // MSG_WAITFORONE was set; the sockets don't know about this flag
+        let flags = flags & !libc::MSG_WAITFORONE;
+
+        let now = Worker::current_time().unwrap();
+
+        // the absolute time at which we stop waiting for the first message
+        let deadline = match ctx.objs.thread.syscall_condition() {
+            // we were previously blocked, so use the deadline from the previous invocation
+            Some(cond) if !timeout_ptr.is_null() => cond.timeout(),
+            _ if !timeout_ptr.is_null() => {
+                let timeout = ctx.read_ptr(timeout_ptr)?;
+                let timeout = SimulationTime::try_from(timeout).map_err(|_| Errno::EINVAL)?;
+                Some(now.checked_add(timeout).ok_or(Errno::EINVAL)?)
+            }
+            _ => None,
+        };
+
+        let mut mem = ctx.objs.process.memory_borrow_mut();
+
+        let mut num_received = 0;
+
+        for i in 0..usize::try_from(vlen).unwrap() {
+            let entry_ptr = msgvec_ptr.add(i);
+
+            // recvmmsg(2): "MSG_WAITFORONE: Turns on MSG_DONTWAIT after the first message has
+            // been received." If a syscall blocks, shadow restarts it from the beginning, so we
+            // also can't block after receiving a message without MSG_WAITFORONE. In that case
+            // linux would keep waiting for more messages, but we return the messages we have.
+            let flags = if num_received == 0 {
+                flags
+            } else {
+                flags | libc::MSG_DONTWAIT
+            };
+
+            let result = Self::recvmsg_helper(
+                socket,
+                entry_ptr.cast::<libc::msghdr>(),
+                flags,
+                &mut mem,
+                None,
+            );
+
+            let bytes_read = match result {
+                Ok(x) => x,
+                // errors after the first message are reported by the next call on the socket
+                Err(_) if num_received > 0 => break,
+                // the timeout expired before the first message arrived
+                Err(SyscallError::Blocked(_)) if deadline.is_some_and(|x| now >= x) => {
+                    return Err(Errno::EAGAIN.into());
+                }
+                Err(mut err) => {
+                    // if the syscall will block, keep the file open until the syscall restarts
+                    if let Some(cond) = err.blocked_condition() {
+                        cond.set_active_file(file);
+                        // wake up at the deadline if no message arrives before then
+                        if deadline.is_some() {
+                            cond.set_timeout(deadline);
+                        }
+                    }
+                    return Err(err);
+                }
+            };
+
+            Self::write_mmsghdr_len(&mut mem, entry_ptr, bytes_read)?;
+            num_received += 1;
+        }
+
+        drop(mem);
+
+        // recvmmsg(2): "the timeout is updated to reflect the remaining time"; linux only does
+        // this when it returns messages
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(&now);
+            let remaining = linux_api::time::timespec::try_from(remaining).unwrap();
+            ctx.write_ptr(timeout_ptr, &remaining)?;
+        }
+
+        Ok(num_received)
+    }
+
+    /// Write the `msg_len` field of the plugin's `mmsghdr`.
+    fn write_mmsghdr_len(
+        mem: &mut MemoryManager,
+        entry_ptr: ForeignPtr<libc::mmsghdr>,
+        len: libc::ssize_t,
+    ) -> Result<(), Errno> {
+        let len_ptr = entry_ptr
+            .cast::<u8>()
+            .add(memoffset::offset_of!(libc::mmsghdr, msg_len))
+            .cast::<std::ffi::c_uint>();
+        mem.write(len_ptr, &std::ffi::c_uint::try_from(len).unwrap())
+    }
+
     log_syscall!(
         getsockname,
         /* rv */ std::ffi::c_int,
@@ -923,8 +1502,23 @@ impl SyscallHandler {
         fd_ptr: ForeignPtr<[std::ffi::c_int; 2]>,
     ) -> Result<(), SyscallError> {
         // remove any flags from the socket type
-        let flags = socket_type & (libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC);
-        let socket_type = socket_type & !flags;
+        let flags = socket_type & !SOCK_TYPE_MASK;
+        let socket_type = socket_type & SOCK_TYPE_MASK;
+
+        if flags & !(libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC) != 0 {
+            log::debug!("Invalid socketpair() type flags {flags:#x}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        if !(0..libc::AF_MAX).contains(&domain) {
+            log::debug!("Invalid socketpair() domain {domain}");
+            return Err(Errno::EAFNOSUPPORT.into());
+        }
+
+        if !(0..SOCK_MAX).contains(&socket_type) {
+            log::debug!("Invalid socketpair() type {socket_type}");
+            return Err(Errno::EINVAL.into());
+        }
 
         // only AF_UNIX (AF_LOCAL) is supported on Linux (and technically AF_TIPC)
         if domain != libc::AF_UNIX {
@@ -932,20 +1526,27 @@ impl SyscallHandler {
             return Err(Errno::EOPNOTSUPP.into());
         }
 
+        // unix sockets only support the default protocol, which can also be given explicitly
+        if protocol != 0 && protocol != libc::PF_UNIX {
+            warn!("Unsupported socket protocol {protocol}, we only support default protocol 0");
+            return Err(Errno::EPROTONOSUPPORT.into());
+        }
+
+        // linux treats raw unix sockets as datagram sockets
+        let socket_type = if socket_type == libc::SOCK_RAW {
+            libc::SOCK_DGRAM
+        } else {
+            socket_type
+        };
+
         let socket_type = match UnixSocketType::try_from(socket_type) {
             Ok(x) => x,
             Err(e) => {
                 warn!("Not a unix socket type: {e}");
-                return Err(Errno::EPROTONOSUPPORT.into());
+                return Err(Errno::ESOCKTNOSUPPORT.into());
             }
         };
 
-        // unix sockets don't support any protocols
-        if protocol != 0 {
-            warn!("Unsupported socket protocol {protocol}, we only support default protocol 0");
-            return Err(Errno::EPROTONOSUPPORT.into());
-        }
-
         let mut file_flags = FileStatus::empty();
         let mut descriptor_flags = DescriptorFlags::empty();
 
@@ -987,7 +1588,7 @@ impl SyscallHandler {
 
         // try to write them to the caller
         let fds = [i32::from(fd_1), i32::from(fd_2)];
-        let write_res = ctx.objs.process.memory_borrow_mut().write(fd_ptr, &fds);
+        let write_res = ctx.write_ptr(fd_ptr, &fds);
 
         // clean up in case of error
         match write_res {
@@ -1037,15 +1638,30 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let mut mem = ctx.objs.process.memory_borrow_mut();
-
         // get the provided optlen
-        let optlen = mem.read(optlen_ptr)?;
+        let optlen = ctx.read_ptr(optlen_ptr)?;
+
+        // linux treats the optlen of socket-level options as a signed int, but other levels (for
+        // example `SOL_TCP`) clamp it as an unsigned int
+        if level == libc::SOL_SOCKET && libc::c_int::try_from(optlen).is_err() {
+            return Err(Errno::EINVAL.into());
+        }
 
         let mut optlen_new = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-            socket
-                .borrow_mut()
-                .getsockopt(level, optname, optval_ptr, optlen, &mut mem, cb_queue)
+            let mut socket = socket.borrow_mut();
+
+            // return the stored value of an unsupported option that was ignored by `setsockopt()`
+            if let Some(value) = socket
+                .ignored_sockopts_mut()
+                .and_then(|x| x.get(level, optname))
+            {
+                let len = std::cmp::min(value.len(), usize::try_from(optlen).unwrap());
+                ctx.write_slice(optval_ptr.cast::<u8>(), &value[..len])?;
+                return Ok(libc::socklen_t::try_from(len).unwrap());
+            }
+
+            let mut mem = ctx.objs.process.memory_borrow_mut();
+            socket.getsockopt(level, optname, optval_ptr, optlen, &mut mem, cb_queue)
         })?;
 
         if optlen_new > optlen {
@@ -1059,7 +1675,7 @@ impl SyscallHandler {
         }
 
         // write the new optlen back to the plugin
-        mem.write(optlen_ptr, &optlen_new)?;
+        ctx.write_ptr(optlen_ptr, &optlen_new)?;
 
         Ok(())
     }
@@ -1094,12 +1710,111 @@ impl SyscallHandler {
             return Err(Errno::ENOTSOCK.into());
         };
 
-        let mem = ctx.objs.process.memory_borrow();
+        // linux treats the optlen as a signed int, and rejects a negative optlen for all levels
+        if libc::c_int::try_from(optlen).is_err() {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let mut socket = socket.borrow_mut();
+
+        let result = socket.setsockopt(
+            level,
+            optname,
+            optval_ptr,
+            optlen,
+            &ctx.objs.process.memory_borrow(),
+        );
+
+        match result {
+            Err(e)
+                if e == Errno::ENOPROTOOPT.into()
+                    && ctx.objs.host.params.ignore_unsupported_sockopts =>
+            {
+                Self::ignore_sockopt(ctx, &mut socket, level, optname, optval_ptr, optlen)
+                    .unwrap_or(Err(e))
+            }
+            x => x,
+        }
+    }
 
-        socket
-            .borrow_mut()
-            .setsockopt(level, optname, optval_ptr, optlen, &mem)?;
+    /// Store the value of an unsupported socket option so that it can be returned by
+    /// `getsockopt()`, but otherwise ignore it. Returns `None` if the option can't be stored.
+    fn ignore_sockopt(
+        ctx: &SyscallContext,
+        socket: &mut SocketRefMut,
+        level: std::ffi::c_int,
+        optname: std::ffi::c_int,
+        optval_ptr: ForeignPtr<()>,
+        optlen: libc::socklen_t,
+    ) -> Option<Result<(), SyscallError>> {
+        let ignored_sockopts = socket.ignored_sockopts_mut()?;
 
-        Ok(())
+        let optlen = usize::try_from(optlen).unwrap();
+        if optlen > IgnoredSockopts::MAX_LEN {
+            return None;
+        }
+
+        let optval_ptr = ForeignArrayPtr::new(optval_ptr.cast::<u8>(), optlen);
+        let value = match ctx.read_slice(optval_ptr) {
+            Ok(x) => x,
+            Err(e) => return Some(Err(e.into())),
+        };
+
+        log::debug!("Ignoring setsockopt() with unsupported level {level} and opt {optname}");
+        ignored_sockopts.insert(level, optname, value);
+
+        Some(Ok(()))
+    }
+}
+
+/// The state of a `MSG_WAITALL` recv, which may span several invocations of a blocked syscall.
+struct WaitallState<'a, 'b> {
+    objs: &'a ThreadContext<'b>,
+    /// The number of bytes received by previous invocations of the syscall.
+    bytes_received: &'a mut libc::size_t,
+    /// Has the socket's `SO_RCVTIMEO` timeout expired?
+    timed_out: bool,
+}
+
+/// The state of a blocking send on a stream socket, which may span several invocations of a
+/// blocked syscall.
+pub(super) struct SendallState<'a, 'b> {
+    pub(super) objs: &'a ThreadContext<'b>,
+    /// The number of bytes sent by previous invocations of the syscall.
+    pub(super) bytes_sent: &'a mut libc::size_t,
+    /// Has the socket's `SO_SNDTIMEO` timeout expired?
+    pub(super) timed_out: bool,
+}
+
+/// Has the deadline of a blocking send, recv, or lingering close passed?
+pub(super) fn is_past_deadline(deadline: Option<EmulatedTime>) -> bool {
+    deadline.is_some_and(|x| Worker::current_time().unwrap() >= x)
+}
+
+/// If a send or recv would block, wake up the thread at the socket's `SO_SNDTIMEO` or
+/// `SO_RCVTIMEO` deadline, or return `EAGAIN` if the deadline has passed.
+pub(super) fn apply_socket_deadline<T>(
+    result: Result<T, SyscallError>,
+    deadline: Option<EmulatedTime>,
+) -> Result<T, SyscallError> {
+    let Some(deadline) = deadline else {
+        return result;
+    };
+
+    match result {
+        Err(SyscallError::Blocked(_)) if is_past_deadline(Some(deadline)) => {
+            Err(Errno::EAGAIN.into())
+        }
+        Err(mut err) => {
+            if let Some(cond) = err.blocked_condition() {
+                // the condition may already have an earlier timeout (for example when pacing)
+                let timeout = cond
+                    .timeout()
+                    .map_or(deadline, |x| std::cmp::min(x, deadline));
+                cond.set_timeout(Some(timeout));
+            }
+            Err(err)
+        }
+        x => x,
     }
 }
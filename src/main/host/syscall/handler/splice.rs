@@ -0,0 +1,540 @@
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use linux_api::errno::Errno;
+use shadow_shim_helper_rs::syscall_types::ForeignPtr;
+
+use crate::cshadow as c;
+use crate::host::descriptor::pipe::Pipe;
+use crate::host::descriptor::socket::{RecvmsgArgs, SendmsgArgs, Socket};
+use crate::host::descriptor::{CompatFile, File, FileMode, FileState, FileStatus, OpenFile};
+use crate::host::memory_manager::AllocdMem;
+use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
+use crate::host::syscall::io::IoVec;
+use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
+use crate::utility::callback_queue::CallbackQueue;
+
+/// One end of a `splice()`.
+enum SpliceEnd {
+    Pipe(OpenFile, Arc<AtomicRefCell<Pipe>>),
+    Socket(OpenFile, Socket),
+    /// A regular file, which is kept open by the descriptor table for the duration of the syscall.
+    RegularFile(*mut c::RegularFile),
+}
+
+impl SpliceEnd {
+    fn is_pipe(&self) -> bool {
+        matches!(self, Self::Pipe(..))
+    }
+
+    fn is_socket(&self) -> bool {
+        matches!(self, Self::Socket(..))
+    }
+
+    /// Whether the file has `O_NONBLOCK` set. We don't track this for regular files, which never
+    /// block.
+    fn is_nonblocking(&self) -> bool {
+        match self {
+            Self::Pipe(_, pipe) => pipe.borrow().status().contains(FileStatus::NONBLOCK),
+            Self::Socket(_, socket) => socket.borrow().status().contains(FileStatus::NONBLOCK),
+            Self::RegularFile(_) => false,
+        }
+    }
+}
+
+/// The flags accepted by `splice()` and `tee()`.
+const VALID_FLAGS: std::ffi::c_uint =
+    libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK | libc::SPLICE_F_MORE | libc::SPLICE_F_GIFT;
+
+/// The error to return when an operation on `pipe` can't continue until the pipe has `state`.
+fn pipe_would_block(
+    open_file: &OpenFile,
+    pipe: &Arc<AtomicRefCell<Pipe>>,
+    state: FileState,
+    nonblock: bool,
+) -> SyscallError {
+    if nonblock {
+        return Errno::EAGAIN.into();
+    }
+
+    let mut err = SyscallError::new_blocked_on_file(
+        File::Pipe(Arc::clone(pipe)),
+        state,
+        pipe.borrow().supports_sa_restart(),
+    );
+    err.blocked_condition()
+        .unwrap()
+        .set_active_file(open_file.clone());
+    err
+}
+
+impl SyscallHandler {
+    log_syscall!(
+        splice,
+        /* rv */ isize,
+        /* fd_in */ std::ffi::c_int,
+        /* off_in */ *const libc::loff_t,
+        /* fd_out */ std::ffi::c_int,
+        /* off_out */ *const libc::loff_t,
+        /* len */ usize,
+        /* flags */ std::ffi::c_uint,
+    );
+    pub fn splice(
+        ctx: &mut SyscallContext,
+        fd_in: std::ffi::c_int,
+        off_in_ptr: ForeignPtr<libc::loff_t>,
+        fd_out: std::ffi::c_int,
+        off_out_ptr: ForeignPtr<libc::loff_t>,
+        len: usize,
+        flags: std::ffi::c_uint,
+    ) -> Result<isize, SyscallError> {
+        if flags & !VALID_FLAGS != 0 {
+            log::debug!("Invalid splice flags: {flags}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let end_in = Self::splice_end(ctx, fd_in)?;
+        let end_out = Self::splice_end(ctx, fd_out)?;
+
+        // splice(2):
+        // > EINVAL: Neither of the file descriptors refers to a pipe.
+        if !end_in.is_pipe() && !end_out.is_pipe() {
+            log::debug!("Neither fd {fd_in} nor fd {fd_out} is a pipe");
+            return Err(Errno::EINVAL.into());
+        }
+
+        // splice(2):
+        // > EINVAL: fd_in and fd_out refer to the same pipe.
+        if let (SpliceEnd::Pipe(_, pipe_in), SpliceEnd::Pipe(_, pipe_out)) = (&end_in, &end_out) {
+            if Arc::ptr_eq(pipe_in, pipe_out) {
+                return Err(Errno::EINVAL.into());
+            }
+        }
+
+        // splice(2):
+        // > EBADF: One or both file descriptors are not valid, or do not have proper read-write
+        // > mode.
+        if let SpliceEnd::Pipe(_, pipe) = &end_in {
+            if !pipe.borrow().mode().contains(FileMode::READ) {
+                return Err(Errno::EBADF.into());
+            }
+        }
+        if let SpliceEnd::Pipe(_, pipe) = &end_out {
+            if !pipe.borrow().mode().contains(FileMode::WRITE) {
+                return Err(Errno::EBADF.into());
+            }
+        }
+
+        // splice(2):
+        // > If fd_in refers to a pipe, then off_in must be NULL.
+        // Sockets aren't seekable either.
+        let offset = |end: &SpliceEnd, ptr: ForeignPtr<libc::loff_t>| {
+            if ptr.is_null() {
+                return Ok(None);
+            }
+            if let SpliceEnd::Pipe(..) | SpliceEnd::Socket(..) = end {
+                return Err(SyscallError::from(Errno::ESPIPE));
+            }
+            let offset: libc::loff_t = ctx.objs.process.memory_borrow().read(ptr)?;
+            if offset < 0 {
+                return Err(Errno::EINVAL.into());
+            }
+            Ok(Some(offset))
+        };
+        let mut off_in = offset(&end_in, off_in_ptr)?;
+        let mut off_out = offset(&end_out, off_out_ptr)?;
+
+        if len == 0 {
+            return Ok(0);
+        }
+
+        // Like linux, the pipe operations don't block if either file has O_NONBLOCK set. The
+        // socket operations only depend on the socket's own O_NONBLOCK status, so
+        // SPLICE_F_NONBLOCK doesn't prevent blocking on a socket.
+        let nonblock = flags & libc::SPLICE_F_NONBLOCK != 0
+            || end_in.is_nonblocking()
+            || end_out.is_nonblocking();
+
+        // we can't transfer more than the destination pipe has space for, or more than the source
+        // pipe can hold
+        let mut max_len = len;
+        if let SpliceEnd::Pipe(open_file, pipe) = &end_out {
+            let pipe_ref = pipe.borrow();
+
+            if !pipe_ref.has_readers() {
+                return Err(Errno::EPIPE.into());
+            }
+
+            let space = pipe_ref.space_available();
+            if space == 0 {
+                return Err(pipe_would_block(
+                    open_file,
+                    pipe,
+                    FileState::WRITABLE,
+                    nonblock,
+                ));
+            }
+
+            max_len = std::cmp::min(max_len, space);
+        }
+        if let SpliceEnd::Pipe(_, pipe) = &end_in {
+            max_len = std::cmp::min(max_len, pipe.borrow().max_size());
+        }
+
+        // sockets only send and receive data in plugin memory, so we move the data through a
+        // buffer that we allocate in the plugin; this must be freed, and will panic if borrowing
+        // the memory manager
+        let plugin_buf = (end_in.is_socket() || end_out.is_socket())
+            .then(|| AllocdMem::<u8>::new(ctx.objs, max_len));
+
+        let result = Self::splice_transfer(
+            ctx,
+            &end_in,
+            &end_out,
+            off_in,
+            off_out,
+            max_len,
+            nonblock,
+            plugin_buf.as_ref().map(AllocdMem::ptr),
+        );
+
+        if let Some(plugin_buf) = plugin_buf {
+            plugin_buf.free(ctx.objs);
+        }
+
+        let num_written = result?;
+
+        log::trace!(
+            "splice moved {num_written} of {len} requested bytes from fd {fd_in} to fd {fd_out}"
+        );
+
+        // splice(2):
+        // > If off_in is not NULL, [...] off_in is adjusted appropriately.
+        let mut mem = ctx.objs.process.memory_borrow_mut();
+        if let Some(off_in) = off_in.as_mut() {
+            *off_in += num_written as libc::loff_t;
+            mem.write(off_in_ptr, off_in)?;
+        }
+        if let Some(off_out) = off_out.as_mut() {
+            *off_out += num_written as libc::loff_t;
+            mem.write(off_out_ptr, off_out)?;
+        }
+
+        Ok(num_written.try_into().unwrap())
+    }
+
+    log_syscall!(
+        tee,
+        /* rv */ isize,
+        /* fd_in */ std::ffi::c_int,
+        /* fd_out */ std::ffi::c_int,
+        /* len */ usize,
+        /* flags */ std::ffi::c_uint,
+    );
+    pub fn tee(
+        ctx: &mut SyscallContext,
+        fd_in: std::ffi::c_int,
+        fd_out: std::ffi::c_int,
+        len: usize,
+        flags: std::ffi::c_uint,
+    ) -> Result<isize, SyscallError> {
+        if flags & !VALID_FLAGS != 0 {
+            log::debug!("Invalid tee flags: {flags}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let end_in = Self::splice_end(ctx, fd_in)?;
+        let end_out = Self::splice_end(ctx, fd_out)?;
+
+        // tee(2):
+        // > EINVAL: fd_in or fd_out does not refer to a pipe; or fd_in and fd_out refer to the
+        // > same pipe.
+        let (SpliceEnd::Pipe(open_file_in, pipe_in), SpliceEnd::Pipe(open_file_out, pipe_out)) =
+            (&end_in, &end_out)
+        else {
+            log::debug!("Fd {fd_in} or fd {fd_out} is not a pipe");
+            return Err(Errno::EINVAL.into());
+        };
+        if Arc::ptr_eq(pipe_in, pipe_out) {
+            return Err(Errno::EINVAL.into());
+        }
+
+        // tee(2):
+        // > EBADF: One or both file descriptors are not valid, or do not have proper read-write
+        // > mode.
+        if !pipe_in.borrow().mode().contains(FileMode::READ)
+            || !pipe_out.borrow().mode().contains(FileMode::WRITE)
+        {
+            return Err(Errno::EBADF.into());
+        }
+
+        if len == 0 {
+            return Ok(0);
+        }
+
+        // like splice, the pipe operations don't block if either pipe has O_NONBLOCK set
+        let nonblock = flags & libc::SPLICE_F_NONBLOCK != 0
+            || end_in.is_nonblocking()
+            || end_out.is_nonblocking();
+
+        if !pipe_out.borrow().has_readers() {
+            return Err(Errno::EPIPE.into());
+        }
+
+        let space = pipe_out.borrow().space_available();
+        if space == 0 {
+            return Err(pipe_would_block(
+                open_file_out,
+                pipe_out,
+                FileState::WRITABLE,
+                nonblock,
+            ));
+        }
+
+        // copy the bytes without removing them from the source pipe
+        let mut buf = vec![0u8; std::cmp::min(len, space)];
+        let result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            pipe_in
+                .borrow_mut()
+                .read_bytes(&mut buf, /* peek= */ true, cb_queue)
+        });
+        let num_read = match result {
+            Err(e) if e == Errno::EWOULDBLOCK.into() => {
+                return Err(pipe_would_block(
+                    open_file_in,
+                    pipe_in,
+                    FileState::READABLE,
+                    nonblock,
+                ));
+            }
+            x => x?,
+        };
+
+        // the source pipe has no writers
+        if num_read == 0 {
+            return Ok(0);
+        }
+
+        let num_written = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+            pipe_out
+                .borrow_mut()
+                .write_bytes(&buf[..num_read], cb_queue)
+        })?;
+
+        log::trace!(
+            "tee copied {num_written} of {len} requested bytes from fd {fd_in} to fd {fd_out}"
+        );
+
+        Ok(num_written.try_into().unwrap())
+    }
+
+    /// Move up to `max_len` bytes from `end_in` to `end_out`, and return the number of bytes
+    /// moved. The destination must already have space for `max_len` bytes if it's a pipe.
+    /// `plugin_buf` must be a buffer of at least `max_len` bytes in plugin memory if either end is
+    /// a socket.
+    #[allow(clippy::too_many_arguments)]
+    fn splice_transfer(
+        ctx: &mut SyscallContext,
+        end_in: &SpliceEnd,
+        end_out: &SpliceEnd,
+        off_in: Option<libc::loff_t>,
+        off_out: Option<libc::loff_t>,
+        max_len: usize,
+        nonblock: bool,
+        plugin_buf: Option<ForeignArrayPtr<u8>>,
+    ) -> Result<usize, SyscallError> {
+        let mut mem = ctx.objs.process.memory_borrow_mut();
+        let mut buf = vec![0u8; max_len];
+
+        // read from the source; if it's a pipe we only peek, and later remove only the bytes that
+        // were written to the destination
+        let num_read = match end_in {
+            SpliceEnd::Pipe(open_file, pipe) => {
+                let result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                    pipe.borrow_mut()
+                        .read_bytes(&mut buf, /* peek= */ true, cb_queue)
+                });
+
+                match result {
+                    Err(e) if e == Errno::EWOULDBLOCK.into() => {
+                        return Err(pipe_would_block(
+                            open_file,
+                            pipe,
+                            FileState::READABLE,
+                            nonblock,
+                        ));
+                    }
+                    x => x?,
+                }
+            }
+            SpliceEnd::Socket(open_file, socket) => {
+                // the destination is a pipe with space for all of the bytes, so we don't need to
+                // peek
+                let plugin_buf = plugin_buf.unwrap();
+                let args = RecvmsgArgs {
+                    iovs: &[IoVec {
+                        base: plugin_buf.ptr(),
+                        len: max_len,
+                    }],
+                    control_ptr: ForeignArrayPtr::new(ForeignPtr::null(), 0),
+                    flags: 0,
+                };
+
+                let mut result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                    Socket::recvmsg(socket, args, &mut mem, cb_queue)
+                });
+
+                // if the syscall will block, keep the file open until the syscall restarts
+                if let Some(err) = result.as_mut().err() {
+                    if let Some(cond) = err.blocked_condition() {
+                        cond.set_active_file(open_file.clone());
+                    }
+                }
+
+                let num_read = result?.return_val.try_into().unwrap();
+                mem.copy_from_ptr(&mut buf[..num_read], plugin_buf.slice(..num_read))?;
+                num_read
+            }
+            SpliceEnd::RegularFile(file) => {
+                let native_fd = unsafe { c::regularfile_getOSBackedFD(*file) };
+                // TODO: this may block the shadow thread until we properly handle os-backed files
+                // in non-blocking mode
+                let rv = match off_in {
+                    Some(off) => unsafe {
+                        libc::pread(native_fd, buf.as_mut_ptr().cast(), buf.len(), off)
+                    },
+                    None => unsafe { libc::read(native_fd, buf.as_mut_ptr().cast(), buf.len()) },
+                };
+                Errno::result_from_libc_errno(-1, rv)? as usize
+            }
+        };
+
+        // end of file, a pipe with no writers, or a socket whose peer has shut down
+        if num_read == 0 {
+            return Ok(0);
+        }
+
+        let num_written = match end_out {
+            SpliceEnd::Pipe(_, pipe) => CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                pipe.borrow_mut().write_bytes(&buf[..num_read], cb_queue)
+            })?,
+            SpliceEnd::Socket(open_file, socket) => {
+                let plugin_buf = plugin_buf.unwrap();
+                mem.copy_to_ptr(plugin_buf.slice(..num_read), &buf[..num_read])?;
+
+                let args = SendmsgArgs {
+                    addr: None,
+                    iovs: &[IoVec {
+                        base: plugin_buf.ptr(),
+                        len: num_read,
+                    }],
+                    control_ptr: ForeignArrayPtr::new(ForeignPtr::null(), 0),
+                    flags: 0,
+                };
+
+                let mut rng = ctx.objs.host.random_mut();
+                let net_ns = ctx.objs.host.network_namespace_borrow();
+
+                let mut result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                    Socket::sendmsg(socket, args, &mut mem, &net_ns, &mut *rng, cb_queue)
+                });
+
+                // if the syscall will block, keep the file open until the syscall restarts
+                if let Some(err) = result.as_mut().err() {
+                    if let Some(cond) = err.blocked_condition() {
+                        cond.set_active_file(open_file.clone());
+                    }
+                }
+
+                result?.try_into().unwrap()
+            }
+            SpliceEnd::RegularFile(file) => {
+                // write through the file so that writes to tmpfs mounts and block devices are
+                // limited like other writes
+                let rv = match off_out {
+                    Some(off) => unsafe {
+                        c::regularfile_pwrite(*file, buf.as_ptr().cast(), num_read, off)
+                    },
+                    None => unsafe { c::regularfile_write(*file, buf.as_ptr().cast(), num_read) },
+                };
+                if rv < 0 {
+                    return Err(Errno::try_from(-rv).unwrap().into());
+                }
+                rv as usize
+            }
+        };
+
+        // remove the written bytes from the source pipe
+        if let SpliceEnd::Pipe(_, pipe) = end_in {
+            let num_removed = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                pipe.borrow_mut().read_bytes(
+                    &mut buf[..num_written],
+                    /* peek= */ false,
+                    cb_queue,
+                )
+            })?;
+            assert_eq!(num_removed, num_written);
+        }
+
+        Ok(num_written)
+    }
+
+    /// Get the pipe, socket, or regular file that `fd` refers to. Other file types are not
+    /// supported by `splice()` in shadow.
+    fn splice_end(ctx: &SyscallContext, fd: std::ffi::c_int) -> Result<SpliceEnd, SyscallError> {
+        let desc_table = ctx.objs.thread.descriptor_table_borrow(ctx.objs.host);
+
+        let file = match Self::get_descriptor(&desc_table, fd)?.file() {
+            CompatFile::New(open_file) => {
+                return match open_file.inner_file() {
+                    File::Pipe(pipe) => Ok(SpliceEnd::Pipe(open_file.clone(), Arc::clone(pipe))),
+                    File::Socket(socket) => {
+                        Ok(SpliceEnd::Socket(open_file.clone(), socket.clone()))
+                    }
+                    _ => {
+                        warn_once_then_debug!(
+                            "splice only supports pipes, sockets, and regular files (fd={fd})"
+                        );
+                        Err(Errno::EINVAL.into())
+                    }
+                };
+            }
+            CompatFile::Legacy(file) => file.ptr(),
+        };
+
+        assert!(!file.is_null());
+
+        if unsafe { c::legacyfile_getStatus(file) }.contains(FileState::CLOSED) {
+            // see the similar check in `mmap` for why this isn't a panic
+            log::warn!("File {file:p} (fd={fd}) is closed");
+            return Err(Errno::EBADF.into());
+        }
+
+        if unsafe { c::legacyfile_getType(file) } != c::_LegacyFileType_DT_FILE {
+            warn_once_then_debug!(
+                "splice only supports pipes, sockets, and regular files (fd={fd})"
+            );
+            return Err(Errno::EINVAL.into());
+        }
+
+        let file = file as *mut c::RegularFile;
+
+        // special files like `/dev/urandom` and in-memory files like `/sys/*` are emulated by
+        // shadow, so we can't use their os-backed files
+        let file_type = unsafe { c::regularfile_getType(file) };
+        if file_type != c::_FileType_FILE_TYPE_REGULAR
+            && file_type != c::_FileType_FILE_TYPE_HOSTS
+            && file_type != c::_FileType_FILE_TYPE_LOCALTIME
+        {
+            warn_once_then_debug!("splice is not supported for emulated file type {file_type}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        let native_fd = unsafe { c::regularfile_getOSBackedFD(file) };
+        if native_fd < 0 {
+            return Err(Errno::EBADF.into());
+        }
+
+        Ok(SpliceEnd::RegularFile(file))
+    }
+}
@@ -49,8 +49,38 @@ impl SyscallHandler {
         Self::legacy_syscall(cshadow::syscallhandler_fstatfs, ctx)
     }
 
+    log_syscall!(statfs, /* rv */ std::ffi::c_int);
+    pub fn statfs(ctx: &mut SyscallContext) -> SyscallResult {
+        Self::legacy_syscall(cshadow::syscallhandler_statfs, ctx)
+    }
+
     log_syscall!(newfstatat, /* rv */ std::ffi::c_int);
     pub fn newfstatat(ctx: &mut SyscallContext) -> SyscallResult {
         Self::legacy_syscall(cshadow::syscallhandler_newfstatat, ctx)
     }
+
+    // `stat` and `lstat` are emulated with `newfstatat` so that their paths are resolved the same
+    // way, for example within the host's tmpfs mounts.
+
+    log_syscall!(stat, /* rv */ std::ffi::c_int);
+    pub fn stat(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            ctx.args.get(1),
+            0.into(),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_newfstatat, ctx, &args)
+    }
+
+    log_syscall!(lstat, /* rv */ std::ffi::c_int);
+    pub fn lstat(ctx: &mut SyscallContext) -> SyscallResult {
+        let args = [
+            libc::AT_FDCWD.into(),
+            ctx.args.get(0),
+            ctx.args.get(1),
+            libc::AT_SYMLINK_NOFOLLOW.into(),
+        ];
+        Self::legacy_at_syscall(cshadow::syscallhandler_newfstatat, ctx, &args)
+    }
 }
@@ -1,5 +1,5 @@
 use linux_api::errno::Errno;
-use linux_api::time::{ClockId, ClockNanosleepFlags, ITimerId};
+use linux_api::time::{timex, AdjtimexModes, ClockId, ClockNanosleepFlags, ClockState, ITimerId};
 use log::*;
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
 use shadow_shim_helper_rs::simulation_time::SimulationTime;
@@ -48,10 +48,7 @@ impl SyscallHandler {
         }
 
         let itimerval = itimerval_from_timer(&ctx.objs.process.realtime_timer_borrow());
-        ctx.objs
-            .process
-            .memory_borrow_mut()
-            .write(curr_value_ptr, &itimerval)?;
+        ctx.write_ptr(curr_value_ptr, &itimerval)?;
 
         Ok(())
     }
@@ -81,13 +78,10 @@ impl SyscallHandler {
 
         if !old_value_ptr.is_null() {
             let itimerval = itimerval_from_timer(&ctx.objs.process.realtime_timer_borrow());
-            ctx.objs
-                .process
-                .memory_borrow_mut()
-                .write(old_value_ptr, &itimerval)?;
+            ctx.write_ptr(old_value_ptr, &itimerval)?;
         }
 
-        let new_value = ctx.objs.process.memory_borrow().read(new_value_ptr)?;
+        let new_value = ctx.read_ptr(new_value_ptr)?;
         let new_value_value =
             SimulationTime::try_from(new_value.it_value).map_err(|_| Errno::EINVAL)?;
         let new_value_interval =
@@ -162,6 +156,135 @@ impl SyscallHandler {
         Ok(prev_remaining_secs)
     }
 
+    log_syscall!(
+        adjtimex,
+        /* rv */ std::ffi::c_int,
+        /* buf */ *const std::ffi::c_void,
+    );
+    pub fn adjtimex(
+        ctx: &mut SyscallContext,
+        buf_ptr: ForeignPtr<timex>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        Self::clock_adjtime_helper(ctx, ClockId::CLOCK_REALTIME, buf_ptr)
+    }
+
+    log_syscall!(
+        clock_adjtime,
+        /* rv */ std::ffi::c_int,
+        /* clock_id */ linux_api::time::ClockId,
+        /* buf */ *const std::ffi::c_void,
+    );
+    pub fn clock_adjtime(
+        ctx: &mut SyscallContext,
+        clock_id: linux_api::time::linux___kernel_clockid_t,
+        buf_ptr: ForeignPtr<timex>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        let clock_id = ClockId::try_from(clock_id).map_err(|_| Errno::EINVAL)?;
+        Self::clock_adjtime_helper(ctx, clock_id, buf_ptr)
+    }
+
+    fn clock_adjtime_helper(
+        ctx: &mut SyscallContext,
+        clock_id: ClockId,
+        buf_ptr: ForeignPtr<timex>,
+    ) -> Result<std::ffi::c_int, SyscallError> {
+        // Linux only supports adjusting the realtime clocks.
+        if ![ClockId::CLOCK_REALTIME, ClockId::CLOCK_TAI].contains(&clock_id) {
+            return Err(Errno::EOPNOTSUPP.into());
+        }
+
+        let mut timex = ctx.read_ptr(buf_ptr)?;
+        let now = Worker::current_time().unwrap();
+
+        let Some(modes) = AdjtimexModes::from_bits(timex.modes) else {
+            log::debug!("Unrecognized adjtimex modes {:#x}", timex.modes);
+            return Err(Errno::EINVAL.into());
+        };
+
+        // `ADJ_OFFSET_SS_READ` only reads the clock state, and the resolution modes only select
+        // the units of the time offset.
+        let modes = if modes == AdjtimexModes::ADJ_OFFSET_SS_READ {
+            AdjtimexModes::empty()
+        } else {
+            modes - AdjtimexModes::ADJ_MICRO - AdjtimexModes::ADJ_NANO
+        };
+
+        // Shadow can slew the clock, but it can't step it or discipline it with a PLL.
+        if !(modes - AdjtimexModes::ADJ_FREQUENCY).is_empty() {
+            warn_once_then_debug!("Unsupported adjtimex modes {modes:?}");
+            return Err(Errno::EINVAL.into());
+        }
+
+        if modes.contains(AdjtimexModes::ADJ_FREQUENCY) {
+            ctx.objs
+                .host
+                .clock_adjustment_borrow_mut()
+                .set_freq(now, timex.freq);
+        }
+
+        // Report a synchronized clock with no pending phase adjustments.
+        let now = now.duration_since(&EmulatedTime::UNIX_EPOCH);
+        timex.offset = 0;
+        timex.freq = ctx.objs.host.clock_adjustment_borrow().freq();
+        timex.maxerror = 0;
+        timex.esterror = 0;
+        timex.status = 0;
+        timex.constant = 0;
+        timex.precision = 1;
+        // 500 ppm, the kernel's `MAXFREQ_SCALED`.
+        timex.tolerance = 500 << 16;
+        timex.time = linux_api::time::timeval {
+            tv_sec: now.as_secs().try_into().unwrap(),
+            tv_usec: now.subsec_micros().into(),
+        };
+        // The length of a clock tick in microseconds, with the kernel's `USER_HZ` of 100.
+        timex.tick = 10_000;
+        timex.tai = 0;
+
+        ctx.write_ptr(buf_ptr, &timex)?;
+
+        Ok(ClockState::TIME_OK.into())
+    }
+
+    log_syscall!(
+        clock_gettime,
+        /* rv */ std::ffi::c_int,
+        /* clock_id */ linux_api::time::ClockId,
+        /* tp */ *const std::ffi::c_void,
+    );
+    pub fn clock_gettime(
+        ctx: &mut SyscallContext,
+        clock_id: linux_api::time::linux___kernel_clockid_t,
+        tp_ptr: ForeignPtr<linux_api::time::timespec>,
+    ) -> Result<(), SyscallError> {
+        let clock_id = ClockId::try_from(clock_id).map_err(|_| Errno::EINVAL)?;
+
+        // The shim handles the other clocks without a syscall, but only shadow can sum the CPU
+        // time of all of the process's threads, or knows how the clock has been slewed.
+        let time = match clock_id {
+            ClockId::CLOCK_PROCESS_CPUTIME_ID => ctx.objs.process.cpu_time(ctx.objs.host),
+            ClockId::CLOCK_THREAD_CPUTIME_ID => ctx
+                .objs
+                .thread
+                .resource_usage(&ctx.objs.host.shim_shmem_lock_borrow().unwrap())
+                .cpu_time(),
+            ClockId::CLOCK_MONOTONIC_RAW => ctx
+                .objs
+                .host
+                .clock_adjustment_borrow()
+                .raw_time(Worker::current_time().unwrap())
+                .duration_since(&EmulatedTime::UNIX_EPOCH),
+            _ => Worker::current_time()
+                .unwrap()
+                .duration_since(&EmulatedTime::UNIX_EPOCH),
+        };
+
+        let time = linux_api::time::timespec::try_from(time).unwrap();
+        ctx.write_ptr(tp_ptr, &time)?;
+
+        Ok(())
+    }
+
     log_syscall!(
         clock_getres,
         /* rv */ std::ffi::c_int,
@@ -179,10 +302,7 @@ impl SyscallHandler {
         // All clocks have nanosecond resolution.
         if !res_ptr.is_null() {
             let res_time = linux_api::time::timespec::try_from(SimulationTime::NANOSECOND).unwrap();
-            ctx.objs
-                .process
-                .memory_borrow_mut()
-                .write(res_ptr, &res_time)?;
+            ctx.write_ptr(res_ptr, &res_time)?;
         }
 
         Ok(())
@@ -265,7 +385,7 @@ impl SyscallHandler {
         remain_ptr: ForeignPtr<linux_api::time::timespec>,
         allow_unspec_bitflags: bool,
     ) -> Result<(), SyscallError> {
-        let request = ctx.objs.process.memory_borrow().read(request_ptr)?;
+        let request = ctx.read_ptr(request_ptr)?;
         let request_time = SimulationTime::try_from(request).or(Err(Errno::EINVAL))?;
         let flags = if allow_unspec_bitflags {
             ClockNanosleepFlags::from_bits_truncate(flags)
@@ -304,10 +424,7 @@ impl SyscallHandler {
             if !remain_ptr.is_null() && !flags.contains(ClockNanosleepFlags::TIMER_ABSTIME) {
                 let remain_time =
                     linux_api::time::timespec::try_from(expected_wakeup_time - now).unwrap();
-                ctx.objs
-                    .process
-                    .memory_borrow_mut()
-                    .write(remain_ptr, &remain_time)?;
+                ctx.write_ptr(remain_ptr, &remain_time)?;
             }
 
             // Encodes that we were interrupted but will return EINTR to the plugin.
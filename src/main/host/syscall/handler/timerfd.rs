@@ -147,10 +147,15 @@ impl SyscallHandler {
         new_value_ptr: ForeignPtr<linux_api::time::itimerspec>,
         old_value_ptr: ForeignPtr<linux_api::time::itimerspec>,
     ) -> Result<(), SyscallError> {
-        let Some(flags) = TimerSetTimeFlags::from_bits(flags) else {
+        if flags & !(libc::TFD_TIMER_ABSTIME | libc::TFD_TIMER_CANCEL_ON_SET) != 0 {
             log::debug!("Invalid timerfd_settime flags: {flags}");
             return Err(Errno::EINVAL.into());
-        };
+        }
+
+        // `TFD_TIMER_CANCEL_ON_SET` cancels an absolute `CLOCK_REALTIME` timer if the realtime
+        // clock is set discontinuously. Shadow's realtime clock never jumps, so the timer is never
+        // cancelled and we can ignore the flag.
+        let flags = TimerSetTimeFlags::from_bits_truncate(flags);
 
         // Get the TimerFd object.
         let file = get_cloned_file(ctx, fd)?;
@@ -183,6 +188,8 @@ impl SyscallHandler {
             let now = Worker::current_time().unwrap();
 
             let expire_time = {
+                // In Shadow all of the supported clocks report the emulated time since the unix
+                // epoch, so an absolute value is always relative to the epoch.
                 let base = match flags.contains(TimerSetTimeFlags::TFD_TIMER_ABSTIME) {
                     true => EmulatedTime::UNIX_EPOCH,
                     false => now,
@@ -211,11 +218,22 @@ impl SyscallHandler {
     }
 }
 
-/// Checks the clockid; returns `Ok(())` if the clockid is `CLOCK_REALTIME` or
-/// `CLOCK_MONOTONIC`, or the appropriate errno if the clockid is unknown or
-/// unsupported.
+/// Checks the clockid; returns `Ok(())` if the clockid is `CLOCK_REALTIME`,
+/// `CLOCK_MONOTONIC`, or `CLOCK_BOOTTIME` (or one of their `*_ALARM` variants),
+/// or the appropriate errno if the clockid is unknown or unsupported.
 fn check_clockid(clockid: ClockId) -> Result<(), Errno> {
-    if clockid == ClockId::CLOCK_MONOTONIC || clockid == ClockId::CLOCK_REALTIME {
+    // Shadow has no suspend, so `CLOCK_BOOTTIME` is the same as
+    // `CLOCK_MONOTONIC`, and the alarm clocks are the same as their non-alarm
+    // counterparts.
+    if [
+        ClockId::CLOCK_MONOTONIC,
+        ClockId::CLOCK_REALTIME,
+        ClockId::CLOCK_BOOTTIME,
+        ClockId::CLOCK_REALTIME_ALARM,
+        ClockId::CLOCK_BOOTTIME_ALARM,
+    ]
+    .contains(&clockid)
+    {
         return Ok(());
     }
 
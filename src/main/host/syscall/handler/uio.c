@@ -7,6 +7,7 @@
 
 #include <assert.h>
 #include <errno.h>
+#include <limits.h>
 #include <sys/syscall.h>
 #include <sys/uio.h>
 
@@ -19,12 +20,16 @@
 #include "main/host/syscall/protected.h"
 #include "main/host/syscall/syscall_condition.h"
 
+/* The maximum number of bytes that a single read or write will transfer. This is
+ * MAX_RW_COUNT in Linux. */
+#define MAX_RW_COUNT ((size_t)INT_MAX & ~(size_t)0xfff)
+
 ///////////////////////////////////////////////////////////
 // Helpers
 ///////////////////////////////////////////////////////////
 
 static int _syscallhandler_validateVecParams(SyscallHandler* sys, int fd, UntypedForeignPtr iovPtr,
-                                             unsigned long iovlen, off_t offset,
+                                             unsigned long iovlen, bool positioned,
                                              LegacyFile** desc_out, struct iovec** iov_out) {
     /* Get the descriptor. */
     LegacyFile* desc = thread_getRegisteredLegacyFile(rustsyscallhandler_getThread(sys), fd);
@@ -32,6 +37,12 @@ static int _syscallhandler_validateVecParams(SyscallHandler* sys, int fd, Untype
         return -EBADF;
     }
 
+    /* We can only seek on files, otherwise its a pipe error. Like Linux, this is checked before
+     * the vector, and regardless of the offset's value. */
+    if (legacyfile_getType(desc) != DT_FILE && positioned) {
+        return -ESPIPE;
+    }
+
     /* Validate vector length param. */
     if (iovlen == 0) {
         return 0;
@@ -44,11 +55,6 @@ static int _syscallhandler_validateVecParams(SyscallHandler* sys, int fd, Untype
         return -EFAULT;
     }
 
-    /* We can only seek on files, otherwise its a pipe error. */
-    if (legacyfile_getType(desc) != DT_FILE && offset != 0) {
-        return -ESPIPE;
-    }
-
     /* Get the vector of pointers. */
     struct iovec* iov = malloc(iovlen * sizeof(*iov));
     if (process_readPtr(rustsyscallhandler_getProcess(sys), iov, iovPtr, iovlen * sizeof(*iov)) != 0) {
@@ -57,7 +63,18 @@ static int _syscallhandler_validateVecParams(SyscallHandler* sys, int fd, Untype
         return -EFAULT;
     }
 
-    /* Check that all of the buf pointers are valid. */
+    /* Like Linux, fail if any of the lengths overflow an ssize_t. */
+    for (unsigned long i = 0; i < iovlen; i++) {
+        if ((ssize_t)iov[i].iov_len < 0) {
+            debug("Invalid length %zu in iovec[%ld]", iov[i].iov_len, i);
+            free(iov);
+            return -EINVAL;
+        }
+    }
+
+    /* Check that all of the buf pointers are valid, and like Linux, truncate the
+     * lengths so that the total doesn't exceed MAX_RW_COUNT. */
+    size_t totalLen = 0;
     for (unsigned long i = 0; i < iovlen; i++) {
         UntypedForeignPtr bufPtr = (UntypedForeignPtr){.val = (uint64_t)iov[i].iov_base};
         size_t bufSize = iov[i].iov_len;
@@ -67,6 +84,11 @@ static int _syscallhandler_validateVecParams(SyscallHandler* sys, int fd, Untype
             free(iov);
             return -EFAULT;
         }
+
+        if (bufSize > MAX_RW_COUNT - totalLen) {
+            iov[i].iov_len = MAX_RW_COUNT - totalLen;
+        }
+        totalLen += iov[i].iov_len;
     }
 
     if (desc_out) {
@@ -104,7 +126,7 @@ static SyscallReturn _syscallhandler_readvHelper(SyscallHandler* sys, int fd,
     LegacyFile* desc = NULL;
     struct iovec* iov = NULL;
     int errcode = _syscallhandler_validateVecParams(
-        sys, fd, iovPtr, iovlen, offset, &desc, &iov);
+        sys, fd, iovPtr, iovlen, doPreadv, &desc, &iov);
     if (errcode < 0 || iovlen == 0) {
         if (iov != NULL) {
             free(iov);
@@ -235,7 +257,7 @@ static SyscallReturn _syscallhandler_writevHelper(SyscallHandler* sys, int fd,
     LegacyFile* desc = NULL;
     struct iovec* iov = NULL;
     int errcode = _syscallhandler_validateVecParams(
-        sys, fd, iovPtr, iovlen, offset, &desc, &iov);
+        sys, fd, iovPtr, iovlen, doPwritev, &desc, &iov);
     if (errcode < 0 || iovlen == 0) {
         if (iov != NULL) {
             free(iov);
@@ -1,9 +1,14 @@
 use linux_api::errno::Errno;
+use linux_api::sched::SuidDump;
 use shadow_shim_helper_rs::syscall_types::ForeignPtr;
 
 use crate::cshadow as c;
 use crate::host::descriptor::socket::{RecvmsgArgs, RecvmsgReturn, SendmsgArgs, Socket};
 use crate::host::descriptor::{CompatFile, File, FileState, FileStatus};
+use crate::host::memory_manager::page_size;
+use crate::host::syscall::handler::socket::{
+    apply_socket_deadline, is_past_deadline, SendallState,
+};
 use crate::host::syscall::handler::{SyscallContext, SyscallHandler};
 use crate::host::syscall::io::{self, IoVec};
 use crate::host::syscall::types::{ForeignArrayPtr, SyscallError};
@@ -122,6 +127,8 @@ impl SyscallHandler {
             return Err(Errno::EINVAL.into());
         }
 
+        Self::check_positioned_io(file.inner_file())?;
+
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
 
         let iovs = {
@@ -196,11 +203,13 @@ impl SyscallHandler {
         // file offset is used and updated."
         let offset = (offset != -1).then_some(offset);
 
-        // if the offset is set, make sure it's not negative
+        // if the offset is set, make sure it's not negative and that the file supports it
         if let Some(offset) = offset {
             if offset < 0 {
                 return Err(Errno::EINVAL.into());
             }
+
+            Self::check_positioned_io(file.inner_file())?;
         }
 
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
@@ -224,6 +233,21 @@ impl SyscallHandler {
         Ok(bytes_read)
     }
 
+    /// Returns `ESPIPE` if the file doesn't support reading or writing at an offset. Like linux,
+    /// this is checked before the iovecs are read.
+    pub fn check_positioned_io(file: &File) -> Result<(), Errno> {
+        match file {
+            // None of the file types implemented in rust are seekable. Regular files are handled
+            // by the C syscall handlers.
+            File::Pipe(_)
+            | File::EventFd(_)
+            | File::Socket(_)
+            | File::TimerFd(_)
+            | File::Epoll(_)
+            | File::PidFd(_) => Err(Errno::ESPIPE),
+        }
+    }
+
     pub fn readv_helper(
         ctx: &mut SyscallContext,
         file: &File,
@@ -254,11 +278,13 @@ impl SyscallHandler {
                 flags: 0,
             };
 
+            let deadline = ctx.handler.socket_deadline(socket.borrow().recv_timeout());
+
             // call the socket's recvmsg(), and run any resulting events
-            let RecvmsgReturn { return_val, .. } =
-                CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-                    Socket::recvmsg(socket, args, &mut mem, cb_queue)
-                })?;
+            let result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                Socket::recvmsg(socket, args, &mut mem, cb_queue)
+            });
+            let RecvmsgReturn { return_val, .. } = apply_socket_deadline(result, deadline)?;
 
             return Ok(return_val);
         }
@@ -401,6 +427,8 @@ impl SyscallHandler {
             return Err(Errno::EINVAL.into());
         }
 
+        Self::check_positioned_io(file.inner_file())?;
+
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
 
         let iovs = {
@@ -475,11 +503,13 @@ impl SyscallHandler {
         // file offset is used and updated."
         let offset = (offset != -1).then_some(offset);
 
-        // if the offset is set, make sure it's not negative
+        // if the offset is set, make sure it's not negative and that the file supports it
         if let Some(offset) = offset {
             if offset < 0 {
                 return Err(Errno::EINVAL.into());
             }
+
+            Self::check_positioned_io(file.inner_file())?;
         }
 
         let iov_count = iov_count.try_into().or(Err(Errno::EINVAL))?;
@@ -528,16 +558,74 @@ impl SyscallHandler {
                 flags: 0,
             };
 
-            // call the socket's sendmsg(), and run any resulting events
-            let bytes_written = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
-                Socket::sendmsg(socket, args, &mut mem, &net_ns, &mut *rng, cb_queue)
-            })?;
+            let deadline = ctx.handler.socket_deadline(socket.borrow().send_timeout());
+
+            let sendall = SendallState {
+                objs: ctx.objs,
+                bytes_sent: &mut ctx.handler.bytes_written,
+                timed_out: is_past_deadline(deadline),
+            };
+
+            let result =
+                Self::socket_sendmsg(socket, args, &mut mem, &net_ns, &mut *rng, Some(sendall));
+            let bytes_written = apply_socket_deadline(result, deadline)?;
 
             return Ok(bytes_written);
         }
 
         let file_status = file.borrow().status();
 
+        // a blocking write to a pipe doesn't return until all of the bytes have been written
+        if matches!(file, File::Pipe(_)) && !file_status.contains(FileStatus::NONBLOCK) {
+            let total_len: libc::size_t = iovs.iter().map(|x| x.len).sum();
+            let bytes_written = &mut ctx.handler.bytes_written;
+
+            // A blocked syscall is restarted from the beginning, so if we block after writing some
+            // of the bytes, we continue from `bytes_written` when the pipe becomes writable again.
+            loop {
+                let iovs = io::skip_iovs(iovs, *bytes_written);
+
+                // call the file's write(), and run any resulting events
+                let result = CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
+                    file.borrow_mut()
+                        .writev(&iovs, offset, flags, &mut mem, cb_queue)
+                });
+
+                let num_written = match result {
+                    Ok(x) => usize::try_from(x).unwrap(),
+                    // we would block, but we can't return EINTR if we already wrote some bytes
+                    Err(e) if e == SyscallError::from(Errno::EWOULDBLOCK) => {
+                        if *bytes_written > 0
+                            && ctx.objs.thread.unblocked_signal_pending(
+                                ctx.objs.process,
+                                &ctx.objs.host.shim_shmem_lock_borrow().unwrap(),
+                            )
+                        {
+                            break;
+                        }
+
+                        return Err(SyscallError::new_blocked_on_file(
+                            file.clone(),
+                            FileState::WRITABLE,
+                            file.borrow().supports_sa_restart(),
+                        ));
+                    }
+                    // return the number of bytes we already wrote, and the pipe will return the
+                    // error again on the next call
+                    Err(_) if *bytes_written > 0 => break,
+                    Err(e) => return Err(e),
+                };
+
+                *bytes_written += num_written;
+
+                if num_written == 0 || *bytes_written >= total_len {
+                    break;
+                }
+            }
+
+            return Ok((*bytes_written).try_into().unwrap());
+        }
+
         let result =
             // call the file's write(), and run any resulting events
             CallbackQueue::queue_and_run_with_legacy(|cb_queue| {
@@ -561,4 +649,196 @@ impl SyscallHandler {
 
         result
     }
+
+    log_syscall!(
+        process_vm_readv,
+        /* rv */ libc::ssize_t,
+        /* pid */ linux_api::posix_types::kernel_pid_t,
+        /* local_iov */ *const libc::iovec,
+        /* liovcnt */ std::ffi::c_ulong,
+        /* remote_iov */ *const libc::iovec,
+        /* riovcnt */ std::ffi::c_ulong,
+        /* flags */ std::ffi::c_ulong,
+    );
+    pub fn process_vm_readv(
+        ctx: &mut SyscallContext,
+        pid: linux_api::posix_types::kernel_pid_t,
+        local_iov_ptr: ForeignPtr<libc::iovec>,
+        local_iov_count: std::ffi::c_ulong,
+        remote_iov_ptr: ForeignPtr<libc::iovec>,
+        remote_iov_count: std::ffi::c_ulong,
+        flags: std::ffi::c_ulong,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        Self::process_vm_helper(
+            ctx,
+            pid,
+            local_iov_ptr,
+            local_iov_count,
+            remote_iov_ptr,
+            remote_iov_count,
+            flags,
+            /* write_remote= */ false,
+        )
+    }
+
+    log_syscall!(
+        process_vm_writev,
+        /* rv */ libc::ssize_t,
+        /* pid */ linux_api::posix_types::kernel_pid_t,
+        /* local_iov */ *const libc::iovec,
+        /* liovcnt */ std::ffi::c_ulong,
+        /* remote_iov */ *const libc::iovec,
+        /* riovcnt */ std::ffi::c_ulong,
+        /* flags */ std::ffi::c_ulong,
+    );
+    pub fn process_vm_writev(
+        ctx: &mut SyscallContext,
+        pid: linux_api::posix_types::kernel_pid_t,
+        local_iov_ptr: ForeignPtr<libc::iovec>,
+        local_iov_count: std::ffi::c_ulong,
+        remote_iov_ptr: ForeignPtr<libc::iovec>,
+        remote_iov_count: std::ffi::c_ulong,
+        flags: std::ffi::c_ulong,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        Self::process_vm_helper(
+            ctx,
+            pid,
+            local_iov_ptr,
+            local_iov_count,
+            remote_iov_ptr,
+            remote_iov_count,
+            flags,
+            /* write_remote= */ true,
+        )
+    }
+
+    /// Copy data between the local iovs of the calling process and the remote iovs of the
+    /// process `pid`. If `write_remote` is true the data is copied from the local iovs to the
+    /// remote iovs, otherwise from the remote iovs to the local iovs.
+    #[allow(clippy::too_many_arguments)]
+    fn process_vm_helper(
+        ctx: &mut SyscallContext,
+        pid: linux_api::posix_types::kernel_pid_t,
+        local_iov_ptr: ForeignPtr<libc::iovec>,
+        local_iov_count: std::ffi::c_ulong,
+        remote_iov_ptr: ForeignPtr<libc::iovec>,
+        remote_iov_count: std::ffi::c_ulong,
+        flags: std::ffi::c_ulong,
+        write_remote: bool,
+    ) -> Result<libc::ssize_t, SyscallError> {
+        // process_vm_readv(2): "The flags argument is currently unused and must be set to 0."
+        if flags != 0 {
+            return Err(Errno::EINVAL.into());
+        }
+
+        let local_iov_count = local_iov_count.try_into().or(Err(Errno::EINVAL))?;
+        let remote_iov_count = remote_iov_count.try_into().or(Err(Errno::EINVAL))?;
+
+        // both iov arrays are in the memory of the calling process
+        let (local_iovs, remote_iovs) = {
+            let mem = ctx.objs.process.memory_borrow();
+            (
+                io::read_iovecs(&mem, local_iov_ptr, local_iov_count)?,
+                io::read_iovecs(&mem, remote_iov_ptr, remote_iov_count)?,
+            )
+        };
+
+        // the total length of each iov array must fit in an ssize_t
+        for iovs in [&local_iovs, &remote_iovs] {
+            let total_len = iovs
+                .iter()
+                .try_fold(0usize, |total, iov| total.checked_add(iov.len));
+            if total_len.map_or(true, |x| libc::ssize_t::try_from(x).is_err()) {
+                return Err(Errno::EINVAL.into());
+            }
+        }
+
+        // processes on other hosts have their own pid namespace, so they won't be found here
+        let pid = pid.try_into().or(Err(Errno::ESRCH))?;
+        let Some(target_process) = ctx.objs.host.process_borrow(pid) else {
+            log::debug!("Process {pid:?} not found");
+            return Err(Errno::ESRCH.into());
+        };
+        let target_process = &*target_process.borrow(ctx.objs.host.root());
+
+        // a zombie process no longer has any memory
+        if !target_process.is_running() {
+            return Err(Errno::ESRCH.into());
+        }
+
+        // We don't model users or capabilities, so the only access check is the one based on the
+        // target's "dumpable" state (see "Ptrace access mode checking" in ptrace(2)).
+        if target_process.id() != ctx.objs.process.id()
+            && target_process.dumpable() != SuidDump::SUID_DUMP_USER
+        {
+            return Err(Errno::EPERM.into());
+        }
+
+        let (src_process, src_iovs, dst_process, dst_iovs) = if write_remote {
+            (ctx.objs.process, &local_iovs, target_process, &remote_iovs)
+        } else {
+            (target_process, &remote_iovs, ctx.objs.process, &local_iovs)
+        };
+
+        let mut src_ptrs = src_iovs
+            .iter()
+            .map(|x| ForeignArrayPtr::from(*x))
+            .filter(|x| !x.is_empty());
+        let mut dst_ptrs = dst_iovs
+            .iter()
+            .map(|x| ForeignArrayPtr::from(*x))
+            .filter(|x| !x.is_empty());
+
+        let page_size = page_size();
+        let mut buf = vec![0u8; page_size];
+        let mut bytes_copied: usize = 0;
+
+        let mut src = src_ptrs.next();
+        let mut dst = dst_ptrs.next();
+
+        // Copy at most a page at a time, so that if we reach the end of a mapped region we can
+        // return the number of bytes that were copied up to that point. The source and
+        // destination processes may be the same, so we don't hold both memory borrows at once.
+        while let (Some(src_ptr), Some(dst_ptr)) = (src, dst) {
+            let len = [
+                src_ptr.len(),
+                dst_ptr.len(),
+                page_size - usize::from(src_ptr.ptr()) % page_size,
+                page_size - usize::from(dst_ptr.ptr()) % page_size,
+            ]
+            .into_iter()
+            .min()
+            .unwrap();
+
+            // the source memory borrow must be dropped before borrowing the destination memory
+            let result = src_process
+                .memory_borrow()
+                .copy_from_ptr(&mut buf[..len], src_ptr.slice(..len));
+            let result = result.and_then(|()| {
+                dst_process
+                    .memory_borrow_mut()
+                    .copy_to_ptr(dst_ptr.slice(..len), &buf[..len])
+            });
+
+            match (result, bytes_copied) {
+                // we successfully copied the bytes
+                (Ok(()), _) => {}
+                // we haven't yet copied any bytes, so return the error
+                (Err(e), 0) => return Err(e.into()),
+                // return how many bytes we've copied
+                (Err(_), _) => break,
+            }
+
+            bytes_copied += len;
+
+            src = Some(src_ptr.slice(len..))
+                .filter(|x| !x.is_empty())
+                .or_else(|| src_ptrs.next());
+            dst = Some(dst_ptr.slice(len..))
+                .filter(|x| !x.is_empty())
+                .or_else(|| dst_ptrs.next());
+        }
+
+        Ok(bytes_copied.try_into().unwrap())
+    }
 }
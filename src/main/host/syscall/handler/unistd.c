@@ -37,7 +37,7 @@ SyscallReturn _syscallhandler_readHelper(SyscallHandler* sys, int fd, UntypedFor
     LegacyFileType dType = legacyfile_getType(desc);
 
     /* We can only seek on files, otherwise its a pipe error. */
-    if (dType != DT_FILE && offset != 0) {
+    if (dType != DT_FILE && doPread) {
         return syscallreturn_makeDoneErrno(ESPIPE);
     }
 
@@ -63,12 +63,12 @@ SyscallReturn _syscallhandler_readHelper(SyscallHandler* sys, int fd, UntypedFor
             if (!doPread) {
                 utility_debugAssert(offset == 0);
                 result = regularfile_read(
-                    (RegularFile*)desc, rustsyscallhandler_getHost(sys),
+                    (RegularFile*)desc, rustsyscallhandler_getProcess(sys),
                     process_getWriteablePtr(rustsyscallhandler_getProcess(sys), bufPtr, sizeNeeded),
                     sizeNeeded);
             } else {
                 result = regularfile_pread(
-                    (RegularFile*)desc, rustsyscallhandler_getHost(sys),
+                    (RegularFile*)desc, rustsyscallhandler_getProcess(sys),
                     process_getWriteablePtr(rustsyscallhandler_getProcess(sys), bufPtr, sizeNeeded),
                     sizeNeeded, offset);
             }
@@ -118,7 +118,7 @@ SyscallReturn _syscallhandler_writeHelper(SyscallHandler* sys, int fd, UntypedFo
     LegacyFileType dType = legacyfile_getType(desc);
 
     /* We can only seek on files, otherwise its a pipe error. */
-    if (dType != DT_FILE && offset != 0) {
+    if (dType != DT_FILE && doPwrite) {
         return syscallreturn_makeDoneErrno(ESPIPE);
     }
 
@@ -36,6 +36,20 @@ impl SyscallHandler {
     pub fn close(ctx: &mut SyscallContext, fd: std::ffi::c_int) -> Result<(), SyscallError> {
         trace!("Trying to close fd {}", fd);
 
+        // if we were previously blocked lingering on a socket, the descriptor has already been
+        // released and the blocked condition holds the last reference to the socket, which will be
+        // closed when the condition is dropped
+        if let Some(file) = ctx
+            .objs
+            .thread
+            .syscall_condition()
+            .and_then(|x| x.active_file().cloned())
+        {
+            trace!("Finished lingering before closing fd {}", fd);
+            return CallbackQueue::queue_and_run_with_legacy(|cb_queue| file.close(cb_queue))
+                .unwrap_or(Ok(()));
+        }
+
         let fd = fd.try_into().or(Err(linux_api::errno::Errno::EBADF))?;
 
         // according to "man 2 close", in Linux any errors that may occur will happen after the fd is
@@ -48,6 +62,9 @@ impl SyscallHandler {
             .deregister_descriptor(fd)
             .ok_or(linux_api::errno::Errno::EBADF)?;
 
+        // a socket with the `SO_LINGER` option may need to wait before being closed
+        let desc = Self::linger_before_close(ctx, desc)?;
+
         // if there are still valid descriptors to the open file, close() will do nothing
         // and return None
         CallbackQueue::queue_and_run_with_legacy(|cb_queue| desc.close(ctx.objs.host, cb_queue))
@@ -101,6 +118,10 @@ impl SyscallHandler {
 
         // duplicate the descriptor
         let new_desc = desc.dup(DescriptorFlags::empty());
+        // Replacing the descriptor is a single table operation, so other threads of this process
+        // (which can't run during this syscall) never see `new_fd` as closed or referring to a
+        // closed file. The replaced descriptor is only closed after it's been removed from the
+        // table. Threads blocked on the replaced file are woken by its close, as with `close()`.
         let replaced_desc = desc_table.register_descriptor_with_fd(new_desc, new_fd);
 
         // close the replaced descriptor
@@ -163,6 +184,7 @@ impl SyscallHandler {
 
         // duplicate the descriptor
         let new_desc = desc.dup(descriptor_flags);
+        // atomically replaces any existing descriptor; see `dup2()`
         let replaced_desc = desc_table.register_descriptor_with_fd(new_desc, new_fd);
 
         // close the replaced descriptor
@@ -271,6 +293,8 @@ impl SyscallHandler {
             }
         };
 
+        Self::check_positioned_io(file.inner_file())?;
+
         let mut result = Self::read_helper(ctx, file.inner_file(), buf_ptr, buf_size, Some(offset));
 
         // if the syscall will block, keep the file open until the syscall restarts
@@ -391,6 +415,8 @@ impl SyscallHandler {
             }
         };
 
+        Self::check_positioned_io(file.inner_file())?;
+
         let mut result =
             Self::write_helper(ctx, file.inner_file(), buf_ptr, buf_size, Some(offset));
 
@@ -471,9 +497,11 @@ impl SyscallHandler {
                 x if x == OFlag::empty() => {
                     // The "empty" flag is always present. Ignore.
                 }
+                // linux also supports O_NOTIFICATION_PIPE if built with CONFIG_WATCH_QUEUE, but
+                // rejects all other flags
                 unhandled => {
-                    // TODO: return an error and change this to `warn_once_then_debug`?
-                    warn!("Ignoring pipe flag {unhandled:?}");
+                    debug!("Invalid pipe flag {unhandled:?}");
+                    return Err(Errno::EINVAL.into());
                 }
             }
         }
@@ -889,10 +917,13 @@ impl SyscallHandler {
         /* error_code */ std::ffi::c_int,
     );
     pub fn exit_group(
-        _ctx: &mut SyscallContext,
+        ctx: &mut SyscallContext,
         error_code: std::ffi::c_int,
     ) -> Result<(), SyscallError> {
         log::trace!("Exit group with exit code {error_code}");
+        // Like the kernel, release the robust futexes of the exiting threads. We do it now since
+        // we can't read the process's memory after the native exit.
+        ctx.objs.process.release_all_robust_futexes(ctx.objs.host);
         Err(SyscallError::Native)
     }
 
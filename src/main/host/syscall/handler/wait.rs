@@ -166,8 +166,9 @@ impl SyscallHandler {
             let info = zombie.exit_siginfo(Signal::SIGCHLD);
             memory.write(infop, &info)?;
         }
+        let zombie_usage = zombie.resource_usage();
         if !usage.is_null() {
-            memory.write(usage, &ctx.objs.process.rusage())?;
+            memory.write(usage, &zombie_usage.to_rusage())?;
         }
 
         let matching_child_zombie_pid: ProcessId = *matching_child_zombie_pid;
@@ -178,6 +179,10 @@ impl SyscallHandler {
         drop(processes);
 
         if !options.contains(WaitFlags::WNOWAIT) {
+            // `getrusage(2)`: RUSAGE_CHILDREN: Return resource usage statistics for all children
+            // of the calling process that have terminated and been waited for.
+            ctx.objs.process.add_reaped_child_usage(&zombie_usage);
+
             let zombie_process = ctx
                 .objs
                 .host
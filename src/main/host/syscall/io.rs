@@ -346,7 +346,16 @@ impl<'a, I: Iterator<Item = &'a IoVec>> std::io::Write for IoVecWriter<'a, I> {
     }
 }
 
+/// The maximum number of bytes that a single read or write will transfer. This is `MAX_RW_COUNT` in
+/// Linux.
+const MAX_RW_COUNT: libc::size_t = libc::c_int::MAX as libc::size_t & !0xfff;
+
 /// Read a plugin's array of [`libc::iovec`] into a [`Vec<IoVec>`].
+///
+/// Like Linux, returns `EINVAL` if there are more than `UIO_MAXIOV` iovecs or if the length of any
+/// iovec overflows an `ssize_t`, returns `EFAULT` if any iovec extends beyond the user address
+/// space, and truncates the iovecs so that their total length is at most
+/// `MAX_RW_COUNT`.
 pub fn read_iovecs(
     mem: &MemoryManager,
     iov_ptr: ForeignPtr<libc::iovec>,
@@ -356,22 +365,50 @@ pub fn read_iovecs(
         return Err(Errno::EINVAL);
     }
 
+    // the iovec pointer isn't accessed if there are no iovecs, so it may be invalid
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
     let mut iovs = Vec::with_capacity(count);
 
     let iov_ptr = ForeignArrayPtr::new(iov_ptr, count);
     let mem_ref = mem.memory_ref(iov_ptr)?;
     let plugin_iovs = mem_ref.deref();
 
+    let mut total_len: libc::size_t = 0;
+
     for plugin_iov in plugin_iovs {
-        iovs.push(IoVec {
-            base: ForeignPtr::from_raw_ptr(plugin_iov.iov_base as *mut u8),
-            len: plugin_iov.iov_len,
-        });
+        if libc::ssize_t::try_from(plugin_iov.iov_len).is_err() {
+            return Err(Errno::EINVAL);
+        }
+
+        let base = ForeignPtr::from_raw_ptr(plugin_iov.iov_base as *mut u8);
+        ForeignArrayPtr::new(base, plugin_iov.iov_len).check_user_range()?;
+
+        let len = std::cmp::min(plugin_iov.iov_len, MAX_RW_COUNT - total_len);
+        total_len += len;
+
+        iovs.push(IoVec { base, len });
     }
 
     Ok(iovs)
 }
 
+/// Returns the iovs with the first `n` bytes removed.
+pub fn skip_iovs(iovs: &[IoVec], mut n: libc::size_t) -> Vec<IoVec> {
+    iovs.iter()
+        .filter_map(|iov| {
+            let skip = std::cmp::min(n, iov.len);
+            n -= skip;
+            (skip < iov.len).then(|| IoVec {
+                base: iov.base.add(skip),
+                len: iov.len - skip,
+            })
+        })
+        .collect()
+}
+
 /// Read a plugin's [`libc::msghdr`] into a [`MsgHdr`].
 pub fn read_msghdr(
     mem: &MemoryManager,
@@ -5,7 +5,9 @@
 
 #include "main/host/syscall/syscall_condition.h"
 
+#include <linux/futex.h>
 #include <stdbool.h>
+#include <stdint.h>
 #include <stdlib.h>
 
 #include "lib/logger/logger.h"
@@ -36,6 +38,8 @@ struct _SysCallCondition {
     pid_t proc;
     // The thread waiting for the condition
     pid_t threadId;
+    // The bitset of a FUTEX_WAIT_BITSET, if the trigger is a futex.
+    uint32_t futexBitset;
     // Whether a wakeup event has already been scheduled.
     // Used to avoid scheduling multiple events when multiple triggers fire.
     bool wakeupScheduled;
@@ -53,6 +57,7 @@ SysCallCondition* syscallcondition_new(Trigger trigger) {
     *cond = (SysCallCondition){.timeoutExpiration = EMUTIME_INVALID,
                                .timeout = NULL,
                                .trigger = trigger,
+                               .futexBitset = FUTEX_BITSET_MATCH_ANY,
                                .referenceCount = 1,
                                MAGIC_INITIALIZER};
 
@@ -103,6 +108,24 @@ void syscallcondition_setTimeout(SysCallCondition* cond, CEmulatedTime t) {
     cond->timeoutExpiration = t;
 }
 
+void syscallcondition_setFutexBitset(SysCallCondition* cond, uint32_t bitset) {
+    MAGIC_ASSERT(cond);
+    utility_debugAssert(cond->trigger.type == TRIGGER_FUTEX);
+    utility_debugAssert(bitset != 0);
+
+    cond->futexBitset = bitset;
+}
+
+void syscallcondition_requeueFutex(SysCallCondition* cond, Futex* futex) {
+    MAGIC_ASSERT(cond);
+    utility_debugAssert(cond->trigger.type == TRIGGER_FUTEX);
+    utility_debugAssert(futex);
+
+    futex_ref(futex);
+    futex_unref(cond->trigger.object.as_futex);
+    cond->trigger.object.as_futex = futex;
+}
+
 void syscallcondition_setActiveFile(SysCallCondition* cond, OpenFile* file) {
     MAGIC_ASSERT(cond);
 
@@ -503,7 +526,8 @@ void syscallcondition_waitNonblock(SysCallCondition* cond, const Host* host, con
                     cond->triggerListener, cond->trigger.state, SLF_ALWAYS);
 
                 /* Attach the listener to the descriptor. */
-                futex_addListener(cond->trigger.object.as_futex, cond->triggerListener);
+                futex_addListener(cond->trigger.object.as_futex, cond->triggerListener,
+                                  cond->futexBitset, cond);
                 break;
             }
             case TRIGGER_CHILD: {
@@ -561,3 +585,8 @@ CEmulatedTime syscallcondition_getTimeout(SysCallCondition* cond) {
 }
 
 OpenFile* syscallcondition_getActiveFile(SysCallCondition* cond) { return cond->activeFile; }
+
+Futex* syscallcondition_getFutex(SysCallCondition* cond) {
+    MAGIC_ASSERT(cond);
+    return cond->trigger.type == TRIGGER_FUTEX ? cond->trigger.object.as_futex : NULL;
+}
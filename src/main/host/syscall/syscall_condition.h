@@ -62,6 +62,14 @@ SysCallCondition* syscallcondition_newWithAbsTimeout(CEmulatedTime abs_trigger_t
  * `worker_getCurrentEmulatedTime`. */
 void syscallcondition_setTimeout(SysCallCondition* cond, CEmulatedTime t);
 
+/* Only wake the condition's futex trigger for wakeups with a bitset that intersects `bitset`, as
+ * in FUTEX_WAIT_BITSET. By default any wakeup of the futex triggers the condition. */
+void syscallcondition_setFutexBitset(SysCallCondition* cond, uint32_t bitset);
+
+/* Change the condition's futex trigger to `futex`. Only futex_requeue() should call this, since it
+ * also moves the condition's listener to the new futex. */
+void syscallcondition_requeueFutex(SysCallCondition* cond, Futex* futex);
+
 /* Add a file to the condition which can be used in the syscall handler once it becomes unblocked,
  * without needing to lookup the file again in the descriptor table (since it may no longer exist in
  * the descriptor table). */
@@ -91,6 +99,9 @@ CEmulatedTime syscallcondition_getTimeout(SysCallCondition* cond);
 /* Get the active file for the condition, or NULL if there isn't one. */
 OpenFile* syscallcondition_getActiveFile(SysCallCondition* cond);
 
+/* Get the futex that the condition is waiting on, or NULL if it doesn't have a futex trigger. */
+Futex* syscallcondition_getFutex(SysCallCondition* cond);
+
 /* If the condition's thread doesn't have `signo` blocked, schedule a wakeup.
  *
  * Returns whether a wakeup was scheduled.
@@ -14,6 +14,10 @@ use crate::host::descriptor::{File, FileState};
 use crate::host::syscall::condition::SyscallCondition;
 use crate::host::syscall::Trigger;
 
+/// The end of the largest user address space on x86-64 (with 5-level paging), like the kernel's
+/// `TASK_SIZE_MAX`.
+const USER_ADDRESS_SPACE_END: usize = (1 << 56) - 4096;
+
 /// Wrapper around a [`ForeignPtr`] that encapsulates its size and current position.
 #[derive(Copy, Clone)]
 pub struct ForeignArrayPtr<T> {
@@ -76,6 +80,38 @@ impl<T> ForeignArrayPtr<T> {
         self.base.is_null()
     }
 
+    /// Returns `EFAULT` if the pointed-to memory doesn't fit within the user address space, like
+    /// the kernel's `access_ok()`. This doesn't check whether the memory is mapped.
+    pub fn check_user_range(&self) -> Result<(), Errno> {
+        self.user_range_prefix().and_then(|x| {
+            if x.count == self.count {
+                Ok(())
+            } else {
+                Err(Errno::EFAULT)
+            }
+        })
+    }
+
+    /// Returns the longest prefix of this pointer that's within the user address space, or
+    /// `EFAULT` if the pointer itself isn't.
+    pub fn user_range_prefix(&self) -> Result<Self, Errno> {
+        let start = usize::from(self.base);
+        if start > USER_ADDRESS_SPACE_END {
+            return Err(Errno::EFAULT);
+        }
+
+        // zero-sized types always fit
+        let max_count = (USER_ADDRESS_SPACE_END - start)
+            .checked_div(size_of::<T>())
+            .unwrap_or(usize::MAX);
+
+        Ok(ForeignArrayPtr {
+            base: self.base,
+            count: std::cmp::min(self.count, max_count),
+            _phantom: PhantomData,
+        })
+    }
+
     /// Cast to type `U`. Fails if the total size isn't a multiple of `sizeof<U>`.
     pub fn cast<U>(&self) -> Option<ForeignArrayPtr<U>> {
         let count_bytes = self.count * size_of::<T>();
@@ -7,6 +7,7 @@
 #define SRC_MAIN_HOST_SYSCALL_NUMBERS_H_
 
 #include <stdbool.h>
+#include <stdint.h>
 
 // We comment-out old syscall numbers instead of removing them for a few reasons:
 // - If shadow accidentally tries using an old version of the shim, it could lead to very confusing
@@ -28,9 +29,63 @@ typedef enum {
     // debugging purposes, so that it doesn't appear that the managed code
     // issues a SYS_sched_yield.
     SYS_shadow_yield = 1005,
-    SYS_shadow_max = 1005,
+    // Get the congestion control state of a TCP socket. The arguments are the socket's fd, a
+    // pointer to a ShadowTcpCcState, and the size of the ShadowTcpCcState.
+    SYS_shadow_tcp_cc_state = 1006,
+    // Query whether a shadow feature is available. The arguments are a pointer to the feature's
+    // name (not NUL-terminated) and the name's length. Returns a ShadowFeatureStatus.
+    SYS_shadow_query_feature = 1007,
+    // Get the name of the simulated host from shadow's config, which isn't changed by
+    // sethostname(). The arguments are a buffer and its length. The name is written to the buffer
+    // like snprintf(): truncated if necessary, and NUL-terminated if the buffer isn't empty.
+    // Returns the length of the full name, excluding the NUL.
+    SYS_shadow_get_host_name = 1008,
+    // Pin the calling thread to a simulated CPU. The argument is the CPU number. If the CPU differs
+    // from the thread's previous CPU, the thread is charged the configured CPU migration cost.
+    // Returns the thread's previous CPU.
+    SYS_shadow_pin_cpu = 1009,
+    SYS_shadow_max = 1009,
 } ShadowSyscallNum;
 
+// The status of a feature, as returned by SYS_shadow_query_feature. The values must not be changed
+// since plugins may depend on them.
+typedef enum {
+    // the feature is unknown, or wasn't compiled into this build of shadow
+    SHADOW_FEATURE_UNSUPPORTED = 0,
+    // the feature is supported, but was disabled by the simulation's configuration
+    SHADOW_FEATURE_DISABLED = 1,
+    SHADOW_FEATURE_ENABLED = 2,
+} ShadowFeatureStatus;
+
+// The phase of a TCP socket's congestion control algorithm. The values must not be changed since
+// plugins may depend on them.
+typedef enum {
+    // reno and cubic
+    SHADOW_TCP_CC_PHASE_SLOW_START = 0,
+    SHADOW_TCP_CC_PHASE_CONGESTION_AVOIDANCE = 1,
+    SHADOW_TCP_CC_PHASE_FAST_RECOVERY = 2,
+    // bbr
+    SHADOW_TCP_CC_PHASE_BBR_STARTUP = 3,
+    SHADOW_TCP_CC_PHASE_BBR_DRAIN = 4,
+    SHADOW_TCP_CC_PHASE_BBR_PROBE_BW = 5,
+    SHADOW_TCP_CC_PHASE_BBR_PROBE_RTT = 6,
+} ShadowTcpCcPhase;
+
+// The congestion control state of a TCP socket, as returned by SYS_shadow_tcp_cc_state. The layout
+// must not be changed since plugins may depend on it.
+typedef struct {
+    // the simulated time at which the state was read, in nanoseconds since the simulation start
+    uint64_t time_ns;
+    // the congestion window and slow start threshold, in segments
+    uint32_t cwnd;
+    uint32_t ssthresh;
+    // the number of payload bytes that have been sent but not yet acknowledged
+    uint64_t bytes_in_flight;
+    // a ShadowTcpCcPhase
+    uint32_t phase;
+    uint32_t _padding;
+} ShadowTcpCcState;
+
 static inline bool syscall_num_is_shadow(long n) {
     return n >= SYS_shadow_min && n <= SYS_shadow_max;
 };
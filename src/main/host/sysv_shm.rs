@@ -0,0 +1,245 @@
+//! Per-host SysV shared memory segments, as created by `shmget(2)`.
+
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::fs::File;
+
+use linux_api::errno::Errno;
+use linux_api::shm::{shmid64_ds, ShmgetFlags, IPC_PRIVATE, SHM_DEST};
+use log::{debug, warn};
+use rustix::fs::MemfdFlags;
+use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+
+use super::memory_manager::page_size;
+use super::process::ProcessId;
+
+/// A shared memory segment. The memory is backed by a memfd in the shadow process, which is
+/// mapped into each process that attaches the segment.
+pub struct SysvShmSegment {
+    file: File,
+    key: i32,
+    size: usize,
+    mode: u32,
+    /// Whether the segment was removed with `IPC_RMID`. It's freed once it has no attachments.
+    removed: bool,
+    creator_pid: ProcessId,
+    last_pid: Option<ProcessId>,
+    attach_count: u64,
+    attach_time: Option<EmulatedTime>,
+    detach_time: Option<EmulatedTime>,
+    change_time: EmulatedTime,
+}
+
+impl SysvShmSegment {
+    /// The memfd backing the segment.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// The length of the segment's mappings, which is the size rounded up to a whole page.
+    pub fn mapped_len(&self) -> usize {
+        self.size.next_multiple_of(page_size())
+    }
+
+    /// The segment's status, as returned by `shmctl(IPC_STAT)`.
+    pub fn stat(&self) -> shmid64_ds {
+        let secs = |t: EmulatedTime| {
+            let secs = t.duration_since(&EmulatedTime::UNIX_EPOCH).as_secs();
+            i64::try_from(secs).unwrap()
+        };
+
+        // shadow runs all processes as the user running shadow
+        let uid = rustix::process::getuid().as_raw();
+        let gid = rustix::process::getgid().as_raw();
+
+        let mut ds = shadow_pod::zeroed::<shmid64_ds>();
+        ds.shm_perm.key = if self.removed { IPC_PRIVATE } else { self.key };
+        ds.shm_perm.uid = uid;
+        ds.shm_perm.gid = gid;
+        ds.shm_perm.cuid = uid;
+        ds.shm_perm.cgid = gid;
+        ds.shm_perm.mode = self.mode | if self.removed { SHM_DEST } else { 0 };
+        ds.shm_segsz = self.size.try_into().unwrap();
+        ds.shm_atime = self.attach_time.map(secs).unwrap_or(0);
+        ds.shm_dtime = self.detach_time.map(secs).unwrap_or(0);
+        ds.shm_ctime = secs(self.change_time);
+        ds.shm_cpid = self.creator_pid.into();
+        ds.shm_lpid = self.last_pid.map(Into::into).unwrap_or(0);
+        ds.shm_nattch = self.attach_count;
+        ds
+    }
+}
+
+/// The SysV shared memory segments of a host, keyed by the segment id returned by `shmget(2)`.
+/// Segments are shared by all processes on the host, and outlive the processes that use them
+/// until they're removed with `IPC_RMID`.
+pub struct SysvShm {
+    segments: BTreeMap<i32, SysvShmSegment>,
+    /// The segment attached at each address, for each process.
+    attachments: BTreeMap<(ProcessId, usize), i32>,
+    next_id: i32,
+}
+
+impl SysvShm {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            segments: BTreeMap::new(),
+            attachments: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Get the id of the segment with the given key, creating it if needed as described in
+    /// `shmget(2)`.
+    pub fn get(
+        &mut self,
+        key: i32,
+        size: usize,
+        flags: ShmgetFlags,
+        mode: u32,
+        pid: ProcessId,
+        now: EmulatedTime,
+    ) -> Result<i32, Errno> {
+        if key != IPC_PRIVATE {
+            let existing = self
+                .segments
+                .iter()
+                .find(|(_, seg)| seg.key == key && !seg.removed);
+
+            if let Some((id, seg)) = existing {
+                if flags.contains(ShmgetFlags::IPC_CREAT | ShmgetFlags::IPC_EXCL) {
+                    return Err(Errno::EEXIST);
+                }
+                if size > seg.size {
+                    return Err(Errno::EINVAL);
+                }
+                return Ok(*id);
+            }
+
+            if !flags.contains(ShmgetFlags::IPC_CREAT) {
+                return Err(Errno::ENOENT);
+            }
+        }
+
+        if size == 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.checked_add(1).ok_or(Errno::ENOSPC)?;
+
+        let name = CString::new(format!("shadow_sysv_shm_{id}")).unwrap();
+        let mapped_len = size.next_multiple_of(page_size());
+        let file = rustix::fs::memfd_create(&name, MemfdFlags::CLOEXEC)
+            .map(File::from)
+            .and_then(|file| {
+                rustix::fs::ftruncate(&file, mapped_len.try_into().unwrap())?;
+                Ok(file)
+            })
+            .map_err(|e| {
+                warn!("Unable to allocate shm segment of size {size}: {e}");
+                Errno::ENOMEM
+            })?;
+
+        debug!("Created shm segment {id} with key {key} and size {size}");
+
+        self.segments.insert(
+            id,
+            SysvShmSegment {
+                file,
+                key,
+                size,
+                mode,
+                removed: false,
+                creator_pid: pid,
+                last_pid: None,
+                attach_count: 0,
+                attach_time: None,
+                detach_time: None,
+                change_time: now,
+            },
+        );
+
+        Ok(id)
+    }
+
+    pub fn segment(&self, id: i32) -> Option<&SysvShmSegment> {
+        self.segments.get(&id)
+    }
+
+    /// Record that the segment was attached at `addr` in the process.
+    pub fn attach(&mut self, id: i32, pid: ProcessId, addr: usize, now: EmulatedTime) {
+        let seg = self.segments.get_mut(&id).unwrap();
+        seg.attach_count += 1;
+        seg.attach_time = Some(now);
+        seg.last_pid = Some(pid);
+
+        let prev = self.attachments.insert((pid, addr), id);
+        assert!(prev.is_none());
+    }
+
+    /// Record that the segment attached at `addr` in the process was detached, freeing the segment
+    /// if it was removed and this was its last attachment. Returns the length of the mapping, or
+    /// `None` if no segment was attached at `addr`.
+    pub fn detach(&mut self, pid: ProcessId, addr: usize, now: EmulatedTime) -> Option<usize> {
+        let id = self.attachments.remove(&(pid, addr))?;
+
+        let seg = self.segments.get_mut(&id).unwrap();
+        let len = seg.mapped_len();
+        seg.attach_count -= 1;
+        seg.detach_time = Some(now);
+        seg.last_pid = Some(pid);
+
+        self.free_if_unused(id);
+        Some(len)
+    }
+
+    /// Mark the segment as removed so that it can't be found by its key. It's freed once it has
+    /// no attachments.
+    pub fn remove(&mut self, id: i32, now: EmulatedTime) -> Result<(), Errno> {
+        let seg = self.segments.get_mut(&id).ok_or(Errno::EINVAL)?;
+        seg.removed = true;
+        seg.change_time = now;
+
+        self.free_if_unused(id);
+        Ok(())
+    }
+
+    /// The forked child inherits the parent's attachments (`fork(2)`).
+    pub fn fork(&mut self, parent: ProcessId, child: ProcessId) {
+        let inherited: Vec<_> = self
+            .attachments
+            .range((parent, 0)..=(parent, usize::MAX))
+            .map(|(&(_, addr), &id)| (addr, id))
+            .collect();
+
+        for (addr, id) in inherited {
+            self.segments.get_mut(&id).unwrap().attach_count += 1;
+            self.attachments.insert((child, addr), id);
+        }
+    }
+
+    /// Detach all segments from the process, which happens when it exits or execs. The mappings
+    /// themselves are already gone.
+    pub fn detach_all(&mut self, pid: ProcessId, now: EmulatedTime) {
+        let addrs: Vec<_> = self
+            .attachments
+            .range((pid, 0)..=(pid, usize::MAX))
+            .map(|(&(_, addr), _)| addr)
+            .collect();
+
+        for addr in addrs {
+            self.detach(pid, addr, now);
+        }
+    }
+
+    fn free_if_unused(&mut self, id: i32) {
+        let seg = &self.segments[&id];
+        if seg.removed && seg.attach_count == 0 {
+            debug!("Freeing shm segment {id}");
+            // closes the memfd; the memory is freed since no process has it mapped
+            self.segments.remove(&id);
+        }
+    }
+}
@@ -1,10 +1,10 @@
 //! An emulated Linux thread.
 
 use std::cell::{Cell, RefCell};
+use std::mem::MaybeUninit;
 use std::ops::{Deref, DerefMut};
 
 use linux_api::errno::Errno;
-use linux_api::fcntl::DescriptorFlags;
 use linux_api::mman::{MapFlags, ProtFlags};
 use linux_api::posix_types::Pid;
 use linux_api::signal::stack_t;
@@ -18,13 +18,14 @@ use shadow_shim_helper_rs::HostId;
 use shadow_shmem::allocator::{shmalloc, ShMemBlock};
 
 use super::context::ProcessContext;
-use super::descriptor::descriptor_table::{DescriptorHandle, DescriptorTable};
+use super::descriptor::descriptor_table::DescriptorTable;
 use super::host::Host;
 use super::managed_thread::{self, ManagedThread};
-use super::process::{Process, ProcessId};
+use super::process::{Process, ProcessId, ResourceUsage};
 use crate::cshadow as c;
 use crate::host::syscall::condition::{SyscallConditionRef, SyscallConditionRefMut};
 use crate::host::syscall::handler::SyscallHandler;
+use crate::host::syscall::types::ForeignArrayPtr;
 use crate::utility::callback_queue::CallbackQueue;
 use crate::utility::{syscall, IsSend, ObjectCounter};
 
@@ -49,6 +50,15 @@ pub struct Thread {
     // If non-NULL, this address should be cleared and futex-awoken on thread exit.
     // See set_tid_address(2).
     tid_address: Cell<ForeignPtr<libc::pid_t>>,
+    // If non-NULL, the head of the thread's list of robust futexes, which are released on thread
+    // exit. See set_robust_list(2).
+    robust_list: Cell<ForeignPtr<linux_api::futex::robust_list_head>>,
+    // The simulated CPU that the thread was last pinned to with `shadow_pin_cpu`.
+    simulated_cpu: Cell<u32>,
+    // The rseq area registered with rseq(2), which is updated when the simulated CPU changes.
+    rseq: Cell<Option<ForeignArrayPtr<MaybeUninit<u8>>>>,
+    // The scheduling policy set with `sched_setscheduler`.
+    sched_policy: Cell<SchedPolicy>,
     shim_shared_memory: ShMemBlock<'static, ThreadShmem>,
     syscallhandler: RootedRefCell<SyscallHandler>,
     /// Descriptor table; potentially shared with other threads and processes.
@@ -68,6 +78,50 @@ pub struct Thread {
 
 impl IsSend for Thread {}
 
+/// A thread's scheduling policy and priority, as set by `sched_setscheduler(2)`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SchedPolicy {
+    /// One of the `SCHED_*` policies, without the `SCHED_RESET_ON_FORK` flag.
+    pub policy: std::ffi::c_int,
+    /// The static priority, which is 0 for non-real-time policies.
+    pub priority: std::ffi::c_int,
+    /// Whether children should be reset to the default policy.
+    pub reset_on_fork: bool,
+}
+
+impl SchedPolicy {
+    /// The policy that a child created by `clone` inherits. See sched(7).
+    pub fn for_child(&self) -> Self {
+        if !self.reset_on_fork {
+            return *self;
+        }
+
+        // > If the calling thread has a scheduling policy of SCHED_FIFO or SCHED_RR, the policy is
+        // > reset to SCHED_OTHER in child processes.
+        let policy = if [libc::SCHED_FIFO, libc::SCHED_RR].contains(&self.policy) {
+            libc::SCHED_OTHER
+        } else {
+            self.policy
+        };
+
+        Self {
+            policy,
+            priority: 0,
+            reset_on_fork: false,
+        }
+    }
+}
+
+impl Default for SchedPolicy {
+    fn default() -> Self {
+        Self {
+            policy: libc::SCHED_OTHER,
+            priority: 0,
+            reset_on_fork: false,
+        }
+    }
+}
+
 impl Thread {
     /// Minimal wrapper around the native managed thread.
     pub fn mthread(&self) -> impl Deref<Target = ManagedThread> + '_ {
@@ -80,6 +134,8 @@ impl Thread {
     pub fn update_for_exec(&mut self, host: &Host, mthread: ManagedThread, new_tid: ThreadId) {
         self.mthread.replace(mthread).handle_process_exit();
         self.tid_address.set(ForeignPtr::null());
+        self.robust_list.set(ForeignPtr::null());
+        self.rseq.set(None);
 
         // Update shmem
         {
@@ -130,25 +186,12 @@ impl Thread {
             desc_table_rc.explicit_drop_recursive(host.root(), host);
 
             // Any descriptors with CLOEXEC are closed.
-            let to_close: Vec<DescriptorHandle> = desc_table
-                .iter()
-                .filter_map(|(handle, descriptor)| {
-                    if descriptor.flags().contains(DescriptorFlags::FD_CLOEXEC) {
-                        Some(*handle)
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+            let to_close: Vec<_> = desc_table.remove_cloexec().collect();
 
             CallbackQueue::queue_and_run_with_legacy(|q| {
-                for handle in to_close {
+                for (handle, descriptor) in to_close {
                     log::trace!("Unregistering FD_CLOEXEC descriptor {handle:?}");
-                    if let Some(Err(e)) = desc_table
-                        .deregister_descriptor(handle)
-                        .unwrap()
-                        .close(host, q)
-                    {
+                    if let Some(Err(e)) = descriptor.close(host, q) {
                         log::debug!("Error closing {handle:?}: {e:?}");
                     };
                 }
@@ -453,6 +496,10 @@ impl Thread {
             host_id: host.id(),
             process_id: pid,
             tid_address: Cell::new(ForeignPtr::null()),
+            robust_list: Cell::new(ForeignPtr::null()),
+            simulated_cpu: Cell::new(0),
+            rseq: Cell::new(None),
+            sched_policy: Cell::new(SchedPolicy::default()),
             shim_shared_memory: shmalloc(ThreadShmem::new(
                 &host.shim_shmem_lock_borrow().unwrap(),
                 tid.into(),
@@ -468,6 +515,17 @@ impl Thread {
         &self.shim_shared_memory
     }
 
+    /// Simulated CPU time used by this thread. The peak RSS is tracked per process rather than per
+    /// thread, so is always 0.
+    pub fn resource_usage(&self, host_shmem: &HostShmemProtected) -> ResourceUsage {
+        let protected = self.shim_shared_memory.protected.borrow(&host_shmem.root);
+        ResourceUsage {
+            user_time: protected.user_cpu_time,
+            system_time: protected.system_cpu_time,
+            max_rss_kib: 0,
+        }
+    }
+
     pub fn resume(&self, ctx: &ProcessContext) -> ResumeResult {
         // Ensure the condition isn't triggered again, but don't clear it yet.
         // Syscall handler can still access.
@@ -530,6 +588,46 @@ impl Thread {
         self.tid_address.set(ptr)
     }
 
+    pub fn get_robust_list(&self) -> ForeignPtr<linux_api::futex::robust_list_head> {
+        self.robust_list.get()
+    }
+
+    /// Sets the head of the thread's robust futex list as for `set_robust_list(2)`. The futexes in
+    /// the list that the thread still holds will be released on termination.
+    pub fn set_robust_list(&self, ptr: ForeignPtr<linux_api::futex::robust_list_head>) {
+        self.robust_list.set(ptr)
+    }
+
+    /// The simulated CPU that the thread is pinned to. Threads start on CPU 0.
+    pub fn simulated_cpu(&self) -> u32 {
+        self.simulated_cpu.get()
+    }
+
+    /// Pin the thread to the simulated CPU `cpu`, returning the CPU it was previously pinned to.
+    pub fn set_simulated_cpu(&self, cpu: u32) -> u32 {
+        self.simulated_cpu.replace(cpu)
+    }
+
+    /// The rseq area registered with `rseq(2)`, if any.
+    pub fn rseq(&self) -> Option<ForeignArrayPtr<MaybeUninit<u8>>> {
+        self.rseq.get()
+    }
+
+    /// Register or unregister the thread's rseq area as for `rseq(2)`.
+    pub fn set_rseq(&self, rseq: Option<ForeignArrayPtr<MaybeUninit<u8>>>) {
+        self.rseq.set(rseq)
+    }
+
+    /// The thread's scheduling policy. Shadow doesn't use this when scheduling the thread.
+    pub fn sched_policy(&self) -> SchedPolicy {
+        self.sched_policy.get()
+    }
+
+    /// Set the thread's scheduling policy. The policy must have already been validated.
+    pub fn set_sched_policy(&self, policy: SchedPolicy) {
+        self.sched_policy.set(policy)
+    }
+
     pub fn unblocked_signal_pending(
         &self,
         process: &Process,
@@ -706,6 +804,16 @@ mod export {
         thread.cleanup_syscall_condition();
     }
 
+    /// Returns a borrowed pointer to the thread's syscall condition, or NULL if it doesn't have
+    /// one.
+    #[no_mangle]
+    pub unsafe extern "C-unwind" fn thread_getSysCallCondition(
+        thread: *const Thread,
+    ) -> *mut c::SysCallCondition {
+        let thread = unsafe { thread.as_ref().unwrap() };
+        thread.cond.get().ptr()
+    }
+
     /// Returns true iff there is an unblocked, unignored signal pending for this
     /// thread (or its process).
     #[no_mangle]
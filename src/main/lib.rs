@@ -45,6 +45,9 @@ extern crate shadow_tsc;
 // shadow re-exports this definition from /usr/include/linux/tcp.h
 // TODO: Provide this via the linux-api crate instead.
 unsafe impl shadow_pod::Pod for crate::cshadow::tcp_info {}
+// shadow re-exports this definition from /usr/include/linux/inet_diag.h
+unsafe impl shadow_pod::Pod for crate::cshadow::tcp_cc_info {}
+unsafe impl shadow_pod::Pod for crate::cshadow::ShadowTcpCcState {}
 
 // check that the size and alignment of `CompatUntypedForeignPtr` and `ForeignPtr<()>` are the same`
 static_assertions::assert_eq_size!(
@@ -332,6 +332,23 @@ impl PacketRc {
         unsafe { c::packet_getPriority(self.c_ptr.ptr()) }
     }
 
+    /// Returns true if the packet's IP header marks it as ECN-capable.
+    pub fn is_ecn_capable(&self) -> bool {
+        let ecn = unsafe { c::packet_getECN(self.c_ptr.ptr()) };
+        ecn != c::ProtocolECN_PECN_NOT_ECT
+    }
+
+    /// Mark the packet as having experienced congestion, if the packet is ECN-capable. Returns
+    /// true if the packet is ECN-capable, or false if the packet wasn't changed.
+    pub fn mark_congestion_experienced(&mut self) -> bool {
+        if !self.is_ecn_capable() {
+            return false;
+        }
+
+        unsafe { c::packet_setECN(self.c_ptr.ptr(), c::ProtocolECN_PECN_CE) };
+        true
+    }
+
     /// Transfers ownership of the given c_ptr reference into a new rust packet
     /// object.
     pub fn from_raw(c_ptr: *mut c::Packet) -> Self {
@@ -386,7 +403,8 @@ impl PacketDisplay for *const c::Packet {
         // write the IP header
 
         let version_and_header_length: u8 = 0x45;
-        let fields: u8 = 0x0;
+        // the DSCP is always 0, and the ECN codepoint is in the lowest 2 bits
+        let fields: u8 = u8::try_from(unsafe { c::packet_getECN(*self) }).unwrap();
         let total_length: u16 = header_len + payload_len;
         let identification: u16 = 0x0;
         let flags_and_fragment: u16 = 0x4000;
@@ -527,9 +545,24 @@ fn display_tcp_bytes(packet: *const c::Packet, mut writer: impl Write) -> std::i
     if tcp_header.flags & c::ProtocolTCPFlags_PTCP_FIN != 0 {
         tcp_flags |= 0x01;
     }
+    if tcp_header.flags & c::ProtocolTCPFlags_PTCP_URG != 0 {
+        tcp_flags |= 0x20;
+    }
+    if tcp_header.flags & c::ProtocolTCPFlags_PTCP_ECE != 0 {
+        tcp_flags |= 0x40;
+    }
+    if tcp_header.flags & c::ProtocolTCPFlags_PTCP_CWR != 0 {
+        tcp_flags |= 0x80;
+    }
     let window: [u8; 2] = u16::try_from(tcp_header.window).unwrap().to_be_bytes();
     let checksum: u16 = 0x0;
-    let urgent_pointer: u16 = 0x0;
+    // urgent data is always sent as the only byte of its packet, so the urgent pointer (the offset
+    // of the byte following the urgent data) is always 1
+    let urgent_pointer: u16 = if tcp_header.flags & c::ProtocolTCPFlags_PTCP_URG != 0 {
+        0x1
+    } else {
+        0x0
+    };
 
     // source port: 2 bytes
     writer.write_all(&source_port)?;
@@ -595,9 +628,9 @@ pub fn to_legacy_tcp_flags(flags: tcp::TcpFlags) -> c::ProtocolTCPFlags {
             tcp::TcpFlags::RST => new_flags |= c::ProtocolTCPFlags_PTCP_RST,
             tcp::TcpFlags::PSH => panic!("Unsupported TCP flag: {flag:?}"),
             tcp::TcpFlags::ACK => new_flags |= c::ProtocolTCPFlags_PTCP_ACK,
-            tcp::TcpFlags::URG => panic!("Unsupported TCP flag: {flag:?}"),
-            tcp::TcpFlags::ECE => panic!("Unsupported TCP flag: {flag:?}"),
-            tcp::TcpFlags::CWR => panic!("Unsupported TCP flag: {flag:?}"),
+            tcp::TcpFlags::URG => new_flags |= c::ProtocolTCPFlags_PTCP_URG,
+            tcp::TcpFlags::ECE => new_flags |= c::ProtocolTCPFlags_PTCP_ECE,
+            tcp::TcpFlags::CWR => new_flags |= c::ProtocolTCPFlags_PTCP_CWR,
             _ => unreachable!(
                 "Each bit is covered by a flag, so the iterator either returned multiple flags at \
                 once or no flags: {flag:?}"
@@ -631,6 +664,21 @@ pub fn from_legacy_tcp_flags(mut flags: c::ProtocolTCPFlags) -> tcp::TcpFlags {
         flags &= !c::ProtocolTCPFlags_PTCP_FIN;
     }
 
+    if flags & c::ProtocolTCPFlags_PTCP_URG != 0 {
+        new_flags.insert(tcp::TcpFlags::URG);
+        flags &= !c::ProtocolTCPFlags_PTCP_URG;
+    }
+
+    if flags & c::ProtocolTCPFlags_PTCP_ECE != 0 {
+        new_flags.insert(tcp::TcpFlags::ECE);
+        flags &= !c::ProtocolTCPFlags_PTCP_ECE;
+    }
+
+    if flags & c::ProtocolTCPFlags_PTCP_CWR != 0 {
+        new_flags.insert(tcp::TcpFlags::CWR);
+        flags &= !c::ProtocolTCPFlags_PTCP_CWR;
+    }
+
     assert_eq!(flags, c::ProtocolTCPFlags_PTCP_NONE, "Unexpected TCP flags");
 
     new_flags
@@ -17,12 +17,13 @@ use shadow_shim_helper_rs::{emulated_time::EmulatedTime, simulation_time::Simula
 use crate::cshadow as c;
 use crate::network::packet::{PacketRc, PacketStatus};
 
-/// The target minimum standing queue delay time, corresponding to the "TARGET"
-/// parameter in the RFC. This is recommended to be set to 5 milliseconds in
-/// internet routers, but in Shadow we increase it to 10 milliseconds.
+/// The default target minimum standing queue delay time, corresponding to the
+/// "TARGET" parameter in the RFC. This is recommended to be set to 5
+/// milliseconds in internet routers, but in Shadow we increase it to 10
+/// milliseconds.
 const TARGET: SimulationTime = SimulationTime::from_duration(Duration::from_millis(10));
 
-/// The most recent time interval over which the standing delay is computed,
+/// The default time interval over which the standing delay is computed,
 /// corresponding to the "INTERVAL" parameter in the RFC. This is recommended to
 /// be set to 100 milliseconds in internet routers.
 const INTERVAL: SimulationTime = SimulationTime::from_duration(Duration::from_millis(100));
@@ -78,11 +79,26 @@ pub struct CoDelQueue {
     current_drop_count: usize,
     /// The number of packets dropped the last time we were in drop mode.
     previous_drop_count: usize,
+    /// If Some, ECN-capable packets are marked as having experienced congestion
+    /// when they're pushed while the queue holds more than this many bytes.
+    ecn_mark_threshold: Option<usize>,
+    /// The target minimum standing queue delay time.
+    target: SimulationTime,
+    /// The time interval over which the standing delay is computed.
+    interval: SimulationTime,
 }
 
 impl CoDelQueue {
-    /// Creates a new empty packet queue.
+    /// Creates a new empty packet queue using the default [`TARGET`] and
+    /// [`INTERVAL`] parameters.
+    #[cfg(test)]
     pub fn new() -> CoDelQueue {
+        CoDelQueue::new_with_params(TARGET, INTERVAL)
+    }
+
+    /// Creates a new empty packet queue using the given "TARGET" and "INTERVAL"
+    /// parameters from the RFC.
+    pub fn new_with_params(target: SimulationTime, interval: SimulationTime) -> CoDelQueue {
         CoDelQueue {
             elements: VecDeque::new(),
             total_bytes_stored: 0,
@@ -91,15 +107,31 @@ impl CoDelQueue {
             drop_next: None,
             current_drop_count: 0,
             previous_drop_count: 0,
+            ecn_mark_threshold: None,
+            target,
+            interval,
         }
     }
 
+    /// Sets the number of stored bytes above which ECN-capable packets will be
+    /// marked as having experienced congestion when they're pushed. Packets are
+    /// also marked instead of dropped by CoDel if they're ECN-capable,
+    /// regardless of the threshold.
+    pub fn set_ecn_mark_threshold(&mut self, threshold: Option<usize>) {
+        self.ecn_mark_threshold = threshold;
+    }
+
     /// Returns the total number of packets stored in the queue.
     #[cfg(test)]
     pub fn len(&self) -> usize {
         self.elements.len()
     }
 
+    /// Returns the running sum of the sizes of packets stored in the queue.
+    pub fn total_bytes_stored(&self) -> usize {
+        self.total_bytes_stored
+    }
+
     /// Returns true if the queue is holding zero packets, false otherwise.
     #[cfg(test)]
     pub fn is_empty(&self) -> bool {
@@ -148,12 +180,20 @@ impl CoDelQueue {
         })
     }
 
-    fn drop_from_store_mode(&mut self, now: &EmulatedTime, packet: PacketRc) -> Option<PacketRc> {
+    fn drop_from_store_mode(
+        &mut self,
+        now: &EmulatedTime,
+        mut packet: PacketRc,
+    ) -> Option<PacketRc> {
         debug_assert_eq!(self.mode, CoDelMode::Store);
 
-        // Drop one packet and move to drop mode.
-        self.drop_packet(packet);
-        let next_item = self.codel_pop(now);
+        // Drop one packet (or mark it if it's ECN-capable) and move to drop mode.
+        let next_packet = if packet.mark_congestion_experienced() {
+            Some(packet)
+        } else {
+            self.drop_packet(packet);
+            self.codel_pop(now).map(|x| x.packet)
+        };
         self.mode = CoDelMode::Drop;
 
         // Reset to the drop rate that was known to control the queue.
@@ -164,10 +204,10 @@ impl CoDelQueue {
             true => delta,
             false => 1,
         };
-        self.drop_next = Some(CoDelQueue::apply_control_law(now, self.current_drop_count));
+        self.drop_next = Some(self.apply_control_law(now, self.current_drop_count));
         self.previous_drop_count = self.current_drop_count;
 
-        next_item.map(|x| x.packet)
+        next_packet
     }
 
     fn drop_from_drop_mode(&mut self, now: &EmulatedTime, packet: PacketRc) -> Option<PacketRc> {
@@ -180,19 +220,28 @@ impl CoDelQueue {
 
         // Drop as many packets as the control law dictates.
         while item.is_some() && self.mode == CoDelMode::Drop && self.should_drop(now) {
-            self.drop_packet(item.unwrap().packet);
+            let mut packet = item.unwrap().packet;
             self.current_drop_count += 1;
 
+            // Mark an ECN-capable packet instead of dropping it, and wait until
+            // the next drop time before dropping or marking another packet.
+            if packet.mark_congestion_experienced() {
+                self.drop_next =
+                    Some(self.apply_control_law(&self.drop_next.unwrap(), self.current_drop_count));
+                return Some(packet);
+            }
+
+            self.drop_packet(packet);
+
             item = self.codel_pop(now);
 
             match item.as_ref().map_or(false, |x| x.ok_to_drop) {
                 true => {
                     // Set the next drop time based on CoDel control law.
                     // `self.drop_next` is already set in `drop_from_store_mode()`
-                    self.drop_next = Some(CoDelQueue::apply_control_law(
-                        &self.drop_next.unwrap(),
-                        self.current_drop_count,
-                    ));
+                    self.drop_next = Some(
+                        self.apply_control_law(&self.drop_next.unwrap(), self.current_drop_count),
+                    );
                 }
                 false => self.mode = CoDelMode::Store,
             }
@@ -235,7 +284,9 @@ impl CoDelQueue {
         now: &EmulatedTime,
         standing_delay: SimulationTime,
     ) -> bool {
-        if standing_delay < TARGET || self.total_bytes_stored <= c::CONFIG_MTU.try_into().unwrap() {
+        if standing_delay < self.target
+            || self.total_bytes_stored <= c::CONFIG_MTU.try_into().unwrap()
+        {
             // We are in a good state, i.e., below the target delay. We reset
             // the interval expiration, so that we wait for at least one full
             // interval if the delay exceeds the target again.
@@ -256,7 +307,7 @@ impl CoDelQueue {
                     // entered a bad state. If we stay in the bad state for a
                     // full interval, we will need to enter drop mode later.
                     // Mark the end of the interval now so we can track it.
-                    self.interval_end = Some(now.saturating_add(INTERVAL));
+                    self.interval_end = Some(now.saturating_add(self.interval));
                     false
                 }
             }
@@ -276,17 +327,17 @@ impl CoDelQueue {
         match self.drop_next {
             Some(drop_next) => {
                 // now < drop_next + interval*16
-                now.saturating_duration_since(&drop_next) < INTERVAL.saturating_mul(16)
+                now.saturating_duration_since(&drop_next) < self.interval.saturating_mul(16)
             }
             None => false, // Have not yet dropped a packet
         }
     }
 
     /// Apply the CoDel control law using the inverse sqrt of the drop count,
-    /// i.e., `time + (INTERVAL / sqrt(count));`.
-    fn apply_control_law(time: &EmulatedTime, count: usize) -> EmulatedTime {
+    /// i.e., `time + (interval / sqrt(count));`.
+    fn apply_control_law(&self, time: &EmulatedTime, count: usize) -> EmulatedTime {
         let increment = {
-            let interval = INTERVAL.as_nanos_f64();
+            let interval = self.interval.as_nanos_f64();
             let sqrt_count = match count {
                 0 => 1f64,
                 _ => (count as f64).sqrt(),
@@ -304,6 +355,13 @@ impl CoDelQueue {
     /// worker module internally.
     pub fn push(&mut self, mut packet: PacketRc, now: EmulatedTime) {
         if self.elements.len() < LIMIT {
+            if self
+                .ecn_mark_threshold
+                .is_some_and(|threshold| self.total_bytes_stored > threshold)
+            {
+                packet.mark_congestion_experienced();
+            }
+
             packet.add_status(PacketStatus::RouterEnqueued);
             self.total_bytes_stored += packet.total_size();
             self.elements.push_back(CoDelElement {
@@ -367,11 +425,12 @@ mod tests {
     #[test]
     fn control_law() {
         let now = mock_time_millis(1000);
+        let cdq = CoDelQueue::new();
 
         // The increment should be a full interval.
         for i in 0..2 {
             assert_eq!(
-                CoDelQueue::apply_control_law(&now, i).duration_since(&now),
+                cdq.apply_control_law(&now, i).duration_since(&now),
                 INTERVAL
             );
         }
@@ -379,7 +438,7 @@ mod tests {
         // The increment should reduce exponentially.
         for i in 2..20 {
             assert_eq!(
-                CoDelQueue::apply_control_law(&now, i).duration_since(&now),
+                cdq.apply_control_law(&now, i).duration_since(&now),
                 SimulationTime::from_nanos(
                     (INTERVAL.as_nanos_f64() / (i as f64).sqrt()).round() as u64
                 )
@@ -432,6 +491,33 @@ mod tests {
         assert!(cdq.interval_end.is_none());
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn custom_params() {
+        let target = SimulationTime::try_from_millis(2).unwrap();
+        let interval = SimulationTime::try_from_millis(20).unwrap();
+
+        let start = mock_time_millis(1000);
+
+        let mut cdq = CoDelQueue::new_with_params(target, interval);
+        for _ in 0..5 {
+            cdq.push(PacketRc::mock_new(), start);
+        }
+
+        // The custom target is used instead of the default.
+        let now = start + target;
+        assert!(!cdq.process_standing_delay(&now, target));
+        assert_eq!(cdq.interval_end.unwrap(), start + target + interval);
+
+        // The custom interval is used instead of the default.
+        let now = start + target + interval;
+        assert!(cdq.process_standing_delay(&now, target + interval));
+        assert_eq!(
+            cdq.apply_control_law(&now, 1).duration_since(&now),
+            interval
+        );
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn mode() {
@@ -531,4 +617,69 @@ mod tests {
         assert_eq!(cdq.current_drop_count, N - 4);
         assert_eq!(cdq.mode, CoDelMode::Store);
     }
+
+    fn mock_ecn_capable_packet() -> PacketRc {
+        let packet = PacketRc::mock_new();
+        unsafe { c::packet_setECN(packet.borrow_inner(), c::ProtocolECN_PECN_ECT0) };
+        packet
+    }
+
+    fn is_marked(packet: &PacketRc) -> bool {
+        unsafe { c::packet_getECN(packet.borrow_inner()) == c::ProtocolECN_PECN_CE }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn ecn_mark_threshold() {
+        let now = mock_time_millis(1000);
+
+        let mut cdq = CoDelQueue::new();
+        cdq.set_ecn_mark_threshold(Some(2 * PacketRc::mock_new().total_size()));
+
+        // The first three packets fit within the threshold, and the packets
+        // that aren't ECN-capable are never marked.
+        for _ in 0..3 {
+            cdq.push(mock_ecn_capable_packet(), now);
+        }
+        for _ in 0..2 {
+            cdq.push(mock_ecn_capable_packet(), now);
+            cdq.push(PacketRc::mock_new(), now);
+        }
+
+        let marked: Vec<_> = (0..7).map(|_| is_marked(&cdq.pop(now).unwrap())).collect();
+        assert_eq!(marked, [false, false, false, true, false, true, false]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn mark_instead_of_drop() {
+        let start = mock_time_millis(1000);
+        let end = mock_time_millis(1000000);
+
+        let mut cdq = CoDelQueue::new();
+        const N: usize = 20;
+        for _ in 0..N {
+            cdq.push(mock_ecn_capable_packet(), start);
+        }
+
+        // Sets the interval.
+        let packet = cdq.pop(start + TARGET).unwrap();
+        assert!(!is_marked(&packet));
+        assert_eq!(cdq.mode, CoDelMode::Store);
+
+        // Enters Drop mode, but marks the packet instead of dropping it.
+        let packet = cdq.pop(start + TARGET + INTERVAL).unwrap();
+        assert!(is_marked(&packet));
+        assert_eq!(cdq.len(), N - 2);
+        assert_eq!(cdq.current_drop_count, 1);
+        assert_eq!(cdq.mode, CoDelMode::Drop);
+
+        // In Drop mode, only one packet is marked for each pop and no packets
+        // are dropped.
+        assert!(cdq.should_drop(&end));
+        let packet = cdq.pop(end).unwrap();
+        assert!(is_marked(&packet));
+        assert_eq!(cdq.len(), N - 3);
+        assert_eq!(cdq.current_drop_count, 2);
+    }
 }
@@ -0,0 +1,115 @@
+//! A first-in-first-out packet queue without active queue management (AQM).
+//! Packets are only dropped if the router's queue capacity is exceeded (i.e.
+//! "tail-drop").
+
+use std::collections::VecDeque;
+
+use crate::network::packet::{PacketRc, PacketStatus};
+
+/// A packet queue that stores every pushed packet until it's popped.
+pub struct FifoQueue {
+    /// A queue holding packets.
+    elements: VecDeque<PacketRc>,
+    /// The running sum of the sizes of packets stored in the queue.
+    total_bytes_stored: usize,
+    /// If Some, ECN-capable packets are marked as having experienced congestion
+    /// when they're pushed while the queue holds more than this many bytes.
+    ecn_mark_threshold: Option<usize>,
+}
+
+impl FifoQueue {
+    /// Creates a new empty packet queue.
+    pub fn new() -> FifoQueue {
+        FifoQueue {
+            elements: VecDeque::new(),
+            total_bytes_stored: 0,
+            ecn_mark_threshold: None,
+        }
+    }
+
+    /// Sets the number of stored bytes above which ECN-capable packets will be
+    /// marked as having experienced congestion when they're pushed.
+    pub fn set_ecn_mark_threshold(&mut self, threshold: Option<usize>) {
+        self.ecn_mark_threshold = threshold;
+    }
+
+    /// Returns the total number of packets stored in the queue.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns the running sum of the sizes of packets stored in the queue.
+    pub fn total_bytes_stored(&self) -> usize {
+        self.total_bytes_stored
+    }
+
+    /// Returns the packet at the front of the queue, or None if the queue is
+    /// empty.
+    #[cfg(test)]
+    pub fn peek(&self) -> Option<&PacketRc> {
+        self.elements.front()
+    }
+
+    /// Returns the packet at the front of the queue, or None if the queue is
+    /// empty.
+    pub fn pop(&mut self) -> Option<PacketRc> {
+        let mut packet = self.elements.pop_front()?;
+
+        debug_assert!(packet.total_size() <= self.total_bytes_stored);
+        self.total_bytes_stored = self.total_bytes_stored.saturating_sub(packet.total_size());
+
+        packet.add_status(PacketStatus::RouterDequeued);
+        Some(packet)
+    }
+
+    /// Append a packet to the end of the queue.
+    pub fn push(&mut self, mut packet: PacketRc) {
+        if self
+            .ecn_mark_threshold
+            .is_some_and(|threshold| self.total_bytes_stored > threshold)
+        {
+            packet.mark_congestion_experienced();
+        }
+
+        packet.add_status(PacketStatus::RouterEnqueued);
+        self.total_bytes_stored += packet.total_size();
+        self.elements.push_back(packet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut fq = FifoQueue::new();
+        assert_eq!(fq.len(), 0);
+        assert_eq!(fq.total_bytes_stored(), 0);
+        assert!(fq.peek().is_none());
+        assert!(fq.pop().is_none());
+    }
+
+    #[test]
+    // Ignore in miri for use of c::packet* functions.
+    #[cfg_attr(miri, ignore)]
+    fn push_pop() {
+        let size = PacketRc::mock_new().total_size();
+        let mut fq = FifoQueue::new();
+
+        const N: usize = 1000;
+
+        for i in 1..=N {
+            fq.push(PacketRc::mock_new());
+            assert_eq!(fq.len(), i);
+            assert_eq!(fq.total_bytes_stored(), i * size);
+        }
+        for i in 1..=N {
+            assert!(fq.pop().is_some());
+            assert_eq!(fq.len(), N - i);
+        }
+        assert!(fq.pop().is_none());
+        assert_eq!(fq.total_bytes_stored(), 0);
+    }
+}
@@ -2,14 +2,83 @@ use std::cell::RefCell;
 use std::net::Ipv4Addr;
 
 use self::codel_queue::CoDelQueue;
+use self::fifo_queue::FifoQueue;
+use self::red_queue::RedQueue;
 use crate::core::worker::Worker;
 use crate::cshadow as c;
-use crate::network::packet::PacketRc;
+use crate::network::packet::{PacketRc, PacketStatus};
 use crate::network::PacketDevice;
 use crate::utility::{Magic, ObjectCounter};
 mod codel_queue;
+mod fifo_queue;
+mod red_queue;
 
 use shadow_shim_helper_rs::emulated_time::EmulatedTime;
+use shadow_shim_helper_rs::simulation_time::SimulationTime;
+
+/// The active queue management (AQM) algorithm used by a router's inbound
+/// queue, and its parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AqmConfig {
+    /// Don't drop packets, other than when the router's queue capacity is
+    /// exceeded. See [`FifoQueue`].
+    None,
+    /// Drop packets based on how long they've been in the queue. See
+    /// [`CoDelQueue`].
+    CoDel {
+        target: SimulationTime,
+        interval: SimulationTime,
+    },
+    /// Drop packets randomly based on the average queue size in bytes. See
+    /// [`RedQueue`].
+    Red {
+        min_threshold: usize,
+        max_threshold: usize,
+        max_probability: f64,
+    },
+}
+
+/// A router's inbound packet queue.
+enum InboundQueue {
+    Fifo(FifoQueue),
+    CoDel(CoDelQueue),
+    Red(RedQueue),
+}
+
+impl InboundQueue {
+    fn push(&mut self, packet: PacketRc, now: EmulatedTime) {
+        match self {
+            Self::Fifo(queue) => queue.push(packet),
+            Self::CoDel(queue) => queue.push(packet, now),
+            Self::Red(queue) => queue.push(packet),
+        }
+    }
+
+    fn pop(&mut self, now: EmulatedTime) -> Option<PacketRc> {
+        match self {
+            Self::Fifo(queue) => queue.pop(),
+            Self::CoDel(queue) => queue.pop(now),
+            Self::Red(queue) => queue.pop(),
+        }
+    }
+
+    fn total_bytes_stored(&self) -> usize {
+        match self {
+            Self::Fifo(queue) => queue.total_bytes_stored(),
+            Self::CoDel(queue) => queue.total_bytes_stored(),
+            Self::Red(queue) => queue.total_bytes_stored(),
+        }
+    }
+
+    #[cfg(test)]
+    fn peek(&self) -> Option<&PacketRc> {
+        match self {
+            Self::Fifo(queue) => queue.peek(),
+            Self::CoDel(queue) => queue.peek(),
+            Self::Red(queue) => queue.peek(),
+        }
+    }
+}
 
 /// A router assists with moving packets between hosts across the simulated
 /// network.
@@ -18,25 +87,71 @@ pub struct Router {
     _counter: ObjectCounter,
     address: Ipv4Addr,
     /// Packets inbound to the host from the simulated network.
-    inbound_packets: RefCell<CoDelQueue>,
+    inbound_packets: RefCell<InboundQueue>,
+    /// If Some, inbound packets are dropped if they would make the inbound
+    /// queue hold more than this many bytes.
+    queue_capacity: Option<usize>,
 }
 
 impl Router {
     /// Create a new router for a host that will help route packets between it
     /// and other hosts. The `address` must uniquely identify this router to the
-    /// host that owns it.
-    pub fn new(address: Ipv4Addr) -> Router {
+    /// host that owns it. The inbound queue uses the AQM algorithm given by
+    /// `aqm`, and any randomness it needs is generated from `seed`. If
+    /// `queue_capacity` is set, packets that don't fit in the inbound queue are
+    /// dropped. If `ecn_mark_threshold` is set, ECN-capable packets are marked
+    /// when the inbound queue holds more than that many bytes.
+    pub fn new(
+        address: Ipv4Addr,
+        aqm: AqmConfig,
+        queue_capacity: Option<usize>,
+        ecn_mark_threshold: Option<usize>,
+        seed: u64,
+    ) -> Router {
+        let inbound_packets = match aqm {
+            AqmConfig::None => {
+                let mut queue = FifoQueue::new();
+                queue.set_ecn_mark_threshold(ecn_mark_threshold);
+                InboundQueue::Fifo(queue)
+            }
+            AqmConfig::CoDel { target, interval } => {
+                let mut queue = CoDelQueue::new_with_params(target, interval);
+                queue.set_ecn_mark_threshold(ecn_mark_threshold);
+                InboundQueue::CoDel(queue)
+            }
+            AqmConfig::Red {
+                min_threshold,
+                max_threshold,
+                max_probability,
+            } => {
+                let mut queue = RedQueue::new(min_threshold, max_threshold, max_probability, seed);
+                queue.set_ecn_mark_threshold(ecn_mark_threshold);
+                InboundQueue::Red(queue)
+            }
+        };
+
         Router {
             magic: Magic::new(),
             address,
             _counter: ObjectCounter::new("Router"),
-            inbound_packets: RefCell::new(CoDelQueue::new()),
+            inbound_packets: RefCell::new(inbound_packets),
+            queue_capacity,
         }
     }
 
-    fn push_inner(&self, packet: PacketRc, now: EmulatedTime) {
+    fn push_inner(&self, mut packet: PacketRc, now: EmulatedTime) {
         self.magic.debug_check();
-        self.inbound_packets.borrow_mut().push(packet, now);
+        let mut inbound_packets = self.inbound_packets.borrow_mut();
+
+        // tail-drop packets that don't fit in the queue
+        if self.queue_capacity.is_some_and(|capacity| {
+            inbound_packets.total_bytes_stored() + packet.total_size() > capacity
+        }) {
+            packet.add_status(PacketStatus::RouterDropped);
+            return;
+        }
+
+        inbound_packets.push(packet, now);
     }
 
     fn pop_inner(&self, now: EmulatedTime) -> Option<PacketRc> {
@@ -54,7 +169,7 @@ impl Router {
         unsafe { c::packet_unref(cpacket) };
     }
 
-    /// Routes the packet from the virtual internet into our CoDel queue, which
+    /// Routes the packet from the virtual internet into our AQM queue, which
     /// can then be received by the destiantion host by calling pop().
     pub fn route_incoming_packet(&self, packet: PacketRc) {
         self.push_inner(packet, Worker::current_time().unwrap())
@@ -67,7 +182,7 @@ impl PacketDevice for Router {
     }
 
     fn pop(&self) -> Option<PacketRc> {
-        // When the host calls pop, we provide the next packet from the CoDel queue.
+        // When the host calls pop, we provide the next packet from the AQM queue.
         self.pop_inner(Worker::current_time().unwrap())
     }
 
@@ -82,10 +197,17 @@ mod tests {
     use super::*;
     use crate::network::tests::mock_time_millis;
 
+    fn mock_codel_config() -> AqmConfig {
+        AqmConfig::CoDel {
+            target: SimulationTime::try_from_millis(10).unwrap(),
+            interval: SimulationTime::try_from_millis(100).unwrap(),
+        }
+    }
+
     #[test]
     fn empty() {
         let now = mock_time_millis(1000);
-        let router = Router::new(Ipv4Addr::UNSPECIFIED);
+        let router = Router::new(Ipv4Addr::UNSPECIFIED, mock_codel_config(), None, None, 1);
         assert!(router.inbound_packets.borrow().peek().is_none());
         assert!(router.pop_inner(now).is_none());
     }
@@ -95,20 +217,68 @@ mod tests {
     #[cfg_attr(miri, ignore)]
     fn push_pop_simple() {
         let now = mock_time_millis(1000);
-        let router = Router::new(Ipv4Addr::UNSPECIFIED);
 
-        const N: usize = 10;
+        let red_config = AqmConfig::Red {
+            min_threshold: 1_000_000,
+            max_threshold: 2_000_000,
+            max_probability: 0.1,
+        };
+
+        for aqm in [AqmConfig::None, mock_codel_config(), red_config] {
+            let router = Router::new(Ipv4Addr::UNSPECIFIED, aqm, None, None, 1);
+
+            const N: usize = 10;
+
+            for _ in 1..=N {
+                router.push_inner(PacketRc::mock_new(), now);
+                assert!(router.inbound_packets.borrow().peek().is_some());
+            }
+            for _ in 1..=N {
+                assert!(router.inbound_packets.borrow().peek().is_some());
+                assert!(router.pop_inner(now).is_some());
+            }
+
+            assert!(router.inbound_packets.borrow().peek().is_none());
+            assert!(router.pop_inner(now).is_none());
+        }
+    }
+
+    #[test]
+    // Ignore in miri for use of c::packet* functions.
+    #[cfg_attr(miri, ignore)]
+    fn tail_drop() {
+        let now = mock_time_millis(1000);
+        let size = PacketRc::mock_new().total_size();
+
+        // room for three packets, but not four
+        let router = Router::new(
+            Ipv4Addr::UNSPECIFIED,
+            AqmConfig::None,
+            Some(4 * size - 1),
+            None,
+            1,
+        );
 
-        for _ in 1..=N {
+        for _ in 0..10 {
             router.push_inner(PacketRc::mock_new(), now);
-            assert!(router.inbound_packets.borrow().peek().is_some());
         }
-        for _ in 1..=N {
-            assert!(router.inbound_packets.borrow().peek().is_some());
+        assert_eq!(
+            router.inbound_packets.borrow().total_bytes_stored(),
+            3 * size
+        );
+
+        // popping a packet makes room for another
+        assert!(router.pop_inner(now).is_some());
+        router.push_inner(PacketRc::mock_new(), now);
+        router.push_inner(PacketRc::mock_new(), now);
+        assert_eq!(
+            router.inbound_packets.borrow().total_bytes_stored(),
+            3 * size
+        );
+
+        for _ in 0..3 {
             assert!(router.pop_inner(now).is_some());
         }
-
-        assert!(router.inbound_packets.borrow().peek().is_none());
         assert!(router.pop_inner(now).is_none());
     }
 }
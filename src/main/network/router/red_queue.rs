@@ -0,0 +1,306 @@
+//! An active queue management (AQM) algorithm implementing Random Early
+//! Detection (RED).
+//! <https://www.icir.org/floyd/papers/early.twocolumn.pdf>
+//!
+//!  The average queue size is measured in bytes, and the "gentle" variant is
+//!  not implemented.
+//!
+//!  More info:
+//!   - <https://en.wikipedia.org/wiki/Random_early_detection>
+//!   - <http://man7.org/linux/man-pages/man8/tc-red.8.html>
+
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::network::packet::{PacketRc, PacketStatus};
+
+/// The weight given to the current queue size when updating the average queue
+/// size, corresponding to the "w_q" parameter in the paper. The paper
+/// recommends a value of at least 0.001, and uses 0.002 in its simulations.
+const WEIGHT: f64 = 0.002;
+
+/// A packet queue implementing the RED active queue management (AQM)
+/// algorithm, suitable for use in network routers.
+///
+/// When a packet is pushed, the queue updates an exponentially weighted moving
+/// average of its size. If the average is between the minimum and maximum
+/// thresholds, the packet is dropped with a probability that increases with the
+/// average size and with the number of packets since the last drop. If the
+/// average is above the maximum threshold, the packet is always dropped.
+/// ECN-capable packets are marked instead of dropped.
+pub struct RedQueue {
+    /// A queue holding packets.
+    elements: VecDeque<PacketRc>,
+    /// The running sum of the sizes of packets stored in the queue.
+    total_bytes_stored: usize,
+    /// The average queue size in bytes.
+    avg_bytes_stored: f64,
+    /// The number of packets pushed since the last dropped or marked packet
+    /// while the average was above the minimum threshold, or None if the average
+    /// was last below the minimum threshold.
+    count: Option<usize>,
+    /// The average queue size in bytes at which packets start being dropped.
+    min_threshold: usize,
+    /// The average queue size in bytes at which all packets are dropped.
+    max_threshold: usize,
+    /// The drop probability when the average queue size reaches the maximum
+    /// threshold.
+    max_probability: f64,
+    /// The source of randomness for deciding which packets to drop. Seeded so
+    /// that the simulation is deterministic.
+    rng: Xoshiro256PlusPlus,
+    /// If Some, ECN-capable packets are marked as having experienced congestion
+    /// when they're pushed while the queue holds more than this many bytes.
+    ecn_mark_threshold: Option<usize>,
+}
+
+impl RedQueue {
+    /// Creates a new empty packet queue. The `min_threshold` must be smaller
+    /// than the `max_threshold`, and `max_probability` must be within (0, 1].
+    pub fn new(
+        min_threshold: usize,
+        max_threshold: usize,
+        max_probability: f64,
+        seed: u64,
+    ) -> RedQueue {
+        assert!(min_threshold < max_threshold);
+        assert!(max_probability > 0.0 && max_probability <= 1.0);
+
+        RedQueue {
+            elements: VecDeque::new(),
+            total_bytes_stored: 0,
+            avg_bytes_stored: 0.0,
+            count: None,
+            min_threshold,
+            max_threshold,
+            max_probability,
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+            ecn_mark_threshold: None,
+        }
+    }
+
+    /// Sets the number of stored bytes above which ECN-capable packets will be
+    /// marked as having experienced congestion when they're pushed. Packets are
+    /// also marked instead of dropped by RED if they're ECN-capable,
+    /// regardless of the threshold.
+    pub fn set_ecn_mark_threshold(&mut self, threshold: Option<usize>) {
+        self.ecn_mark_threshold = threshold;
+    }
+
+    /// Returns the total number of packets stored in the queue.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns the running sum of the sizes of packets stored in the queue.
+    pub fn total_bytes_stored(&self) -> usize {
+        self.total_bytes_stored
+    }
+
+    /// Returns the packet at the front of the queue, or None if the queue is
+    /// empty.
+    #[cfg(test)]
+    pub fn peek(&self) -> Option<&PacketRc> {
+        self.elements.front()
+    }
+
+    /// Returns the packet at the front of the queue, or None if the queue is
+    /// empty. Packets are only dropped when they're pushed.
+    pub fn pop(&mut self) -> Option<PacketRc> {
+        let mut packet = self.elements.pop_front()?;
+
+        debug_assert!(packet.total_size() <= self.total_bytes_stored);
+        self.total_bytes_stored = self.total_bytes_stored.saturating_sub(packet.total_size());
+
+        packet.add_status(PacketStatus::RouterDequeued);
+        Some(packet)
+    }
+
+    /// Append a packet to the end of the queue, or drop it if the RED algorithm
+    /// decides that it should be dropped.
+    pub fn push(&mut self, mut packet: PacketRc) {
+        self.avg_bytes_stored =
+            (1.0 - WEIGHT) * self.avg_bytes_stored + WEIGHT * self.total_bytes_stored as f64;
+
+        if self.should_drop() {
+            // Mark an ECN-capable packet instead of dropping it.
+            if !packet.mark_congestion_experienced() {
+                packet.add_status(PacketStatus::RouterDropped);
+                return;
+            }
+        } else if self
+            .ecn_mark_threshold
+            .is_some_and(|threshold| self.total_bytes_stored > threshold)
+        {
+            packet.mark_congestion_experienced();
+        }
+
+        packet.add_status(PacketStatus::RouterEnqueued);
+        self.total_bytes_stored += packet.total_size();
+        self.elements.push_back(packet);
+    }
+
+    /// Returns true if the packet being pushed should be dropped (or marked),
+    /// based on the current average queue size.
+    fn should_drop(&mut self) -> bool {
+        if self.avg_bytes_stored < self.min_threshold as f64 {
+            self.count = None;
+            return false;
+        }
+
+        if self.avg_bytes_stored >= self.max_threshold as f64 {
+            self.count = Some(0);
+            return true;
+        }
+
+        let count = self.count.map_or(0, |x| x + 1);
+
+        // The drop probability increases linearly between the thresholds, and
+        // is then increased based on the number of packets since the last drop
+        // so that drops are spread out more evenly.
+        let p_b = self.max_probability * (self.avg_bytes_stored - self.min_threshold as f64)
+            / (self.max_threshold - self.min_threshold) as f64;
+        let denominator = 1.0 - count as f64 * p_b;
+        let p_a = if denominator > 0.0 {
+            p_b / denominator
+        } else {
+            1.0
+        };
+
+        if self.rng.gen::<f64>() < p_a {
+            self.count = Some(0);
+            true
+        } else {
+            self.count = Some(count);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cshadow as c;
+
+    // Some of the tests here don't run in miri because they cause c::packet*
+    // functions to be called during the test.
+
+    fn mock_ecn_capable_packet() -> PacketRc {
+        let packet = PacketRc::mock_new();
+        unsafe { c::packet_setECN(packet.borrow_inner(), c::ProtocolECN_PECN_ECT0) };
+        packet
+    }
+
+    fn is_marked(packet: &PacketRc) -> bool {
+        unsafe { c::packet_getECN(packet.borrow_inner()) == c::ProtocolECN_PECN_CE }
+    }
+
+    #[test]
+    fn empty() {
+        let mut rdq = RedQueue::new(1000, 3000, 0.1, 1);
+        assert_eq!(rdq.len(), 0);
+        assert!(rdq.peek().is_none());
+        assert!(rdq.pop().is_none());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn below_min_threshold() {
+        let size = PacketRc::mock_new().total_size();
+        const N: usize = 100;
+
+        // The average can never reach the minimum threshold, so nothing is
+        // dropped.
+        let mut rdq = RedQueue::new(N * size, 2 * N * size, 1.0, 1);
+
+        for i in 1..=N {
+            rdq.push(PacketRc::mock_new());
+            assert_eq!(rdq.len(), i);
+        }
+        for i in 1..=N {
+            assert!(rdq.pop().is_some());
+            assert_eq!(rdq.len(), N - i);
+        }
+        assert!(rdq.pop().is_none());
+        assert_eq!(rdq.total_bytes_stored, 0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn above_max_threshold() {
+        let mut rdq = RedQueue::new(1, 2, 0.1, 1);
+
+        // The average decays slowly, so it stays above the maximum threshold
+        // for the rest of the test.
+        rdq.avg_bytes_stored = 10.0;
+
+        // Every packet is dropped while the average is above the maximum
+        // threshold.
+        for _ in 0..10 {
+            rdq.push(PacketRc::mock_new());
+            assert_eq!(rdq.len(), 0);
+        }
+
+        // ECN-capable packets are marked instead.
+        rdq.push(mock_ecn_capable_packet());
+        assert_eq!(rdq.len(), 1);
+        assert!(is_marked(&rdq.pop().unwrap()));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn drops_between_thresholds() {
+        let size = PacketRc::mock_new().total_size();
+
+        let mut rdq = RedQueue::new(size, 1_000_000 * size, 1.0, 1);
+
+        // Fill the queue without popping so that the average rises above the
+        // minimum threshold.
+        const N: usize = 5000;
+        for _ in 0..N {
+            rdq.push(PacketRc::mock_new());
+        }
+
+        // Some, but not all, of the packets were dropped.
+        assert!(rdq.len() < N);
+        assert!(rdq.len() > N / 2);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn deterministic() {
+        let size = PacketRc::mock_new().total_size();
+
+        let lengths: Vec<_> = (0..2)
+            .map(|_| {
+                let mut rdq = RedQueue::new(size, 1_000_000 * size, 1.0, 1234);
+                for _ in 0..5000 {
+                    rdq.push(PacketRc::mock_new());
+                }
+                rdq.len()
+            })
+            .collect();
+
+        assert_eq!(lengths[0], lengths[1]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn ecn_mark_threshold() {
+        let size = PacketRc::mock_new().total_size();
+
+        // RED never drops, so only the threshold causes marks.
+        let mut rdq = RedQueue::new(1000 * size, 2000 * size, 0.1, 1);
+        rdq.set_ecn_mark_threshold(Some(2 * size));
+
+        for _ in 0..4 {
+            rdq.push(mock_ecn_capable_packet());
+        }
+
+        let marked: Vec<_> = (0..4).map(|_| is_marked(&rdq.pop().unwrap())).collect();
+        assert_eq!(marked, [false, false, false, true]);
+    }
+}
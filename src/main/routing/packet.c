@@ -78,6 +78,9 @@ struct _Packet {
      */
     uint64_t priority;
 
+    /* the ECN codepoint of the IP header */
+    enum ProtocolECN ecn;
+
     PacketDeliveryStatusFlags allStatus;
     GQueue* orderedStatus;
 
@@ -182,6 +185,7 @@ Packet* packet_copy(Packet* packet) {
         copy->priority = packet->priority;
     }
 
+    copy->ecn = packet->ecn;
     copy->allStatus = packet->allStatus;
 
     if(packet->orderedStatus) {
@@ -407,6 +411,16 @@ uint64_t packet_getPriority(const Packet* packet) {
     return packet->priority;
 }
 
+void packet_setECN(Packet* packet, enum ProtocolECN ecn) {
+    MAGIC_ASSERT(packet);
+    packet->ecn = ecn;
+}
+
+enum ProtocolECN packet_getECN(const Packet* packet) {
+    MAGIC_ASSERT(packet);
+    return packet->ecn;
+}
+
 // The returned address will be in network byte order.
 in_addr_t packet_getDestinationIP(const Packet* packet) {
     MAGIC_ASSERT(packet);
@@ -695,6 +709,12 @@ gchar* packet_toString(Packet* packet) {
                 if(header->flags & PTCP_DUPACK) {
                     g_string_append_printf(packetString, "DUPACK");
                 }
+                if(header->flags & PTCP_ECE) {
+                    g_string_append_printf(packetString, "ECE");
+                }
+                if(header->flags & PTCP_CWR) {
+                    g_string_append_printf(packetString, "CWR");
+                }
             }
 
             g_string_append_printf(packetString, " tsval=%"G_GUINT64_FORMAT" tsechoreply=%"G_GUINT64_FORMAT,
@@ -64,6 +64,9 @@ static inline void packet_unrefTaskFreeFunc(gpointer packet) { packet_unref(pack
 void packet_setPriority(Packet *packet, uint64_t value);
 uint64_t packet_getPriority(const Packet* packet);
 
+void packet_setECN(Packet* packet, enum ProtocolECN ecn);
+enum ProtocolECN packet_getECN(const Packet* packet);
+
 // The addresses and ports must be in network byte order.
 void packet_setUDP(Packet* packet, enum ProtocolUDPFlags flags,
         in_addr_t sourceIP, in_port_t sourcePort,
@@ -106,6 +106,14 @@ pub fn run_shadow(args: Vec<&OsStr>) -> anyhow::Result<()> {
     if shadow_config.experimental.use_object_counters.unwrap() {
         worker::enable_object_counters();
     }
+    if options.dump_syscall_counts.is_some() {
+        if !shadow_config.experimental.use_syscall_counters.unwrap() {
+            return Err(anyhow::anyhow!(
+                "The '--dump-syscall-counts' option requires '--use-syscall-counters'"
+            ));
+        }
+        worker::enable_process_syscall_counters();
+    }
 
     // get the log level
     let log_level = shadow_config.general.log_level.unwrap();
@@ -114,6 +122,7 @@ pub fn run_shadow(args: Vec<&OsStr>) -> anyhow::Result<()> {
     // start up the logging subsystem to handle all future messages
     shadow_logger::init(
         log_level.to_level_filter(),
+        shadow_config.general.log_format.unwrap(),
         shadow_config.experimental.report_errors_to_stderr.unwrap(),
     )
     .unwrap();
@@ -174,8 +183,13 @@ pub fn run_shadow(args: Vec<&OsStr>) -> anyhow::Result<()> {
 
     // Disable address space layout randomization of processes forked from this
     // one to improve determinism in cases when an executable under simulation
-    // branch on memory addresses.
+    // branch on memory addresses. With deterministic ASLR, shadow's memory manager
+    // instead randomizes the addresses of new mappings using the simulation seed.
     match disable_aslr() {
+        Ok(()) if shadow_config.experimental.use_deterministic_aslr.unwrap() => log::debug!(
+            "ASLR disabled for processes forked from this parent process; mappings will be \
+             placed using deterministic ASLR"
+        ),
         Ok(()) => log::debug!("ASLR disabled for processes forked from this parent process"),
         Err(e) => log::warn!("Could not disable address space layout randomization. This may affect determinism: {:?}", e),
     };
@@ -213,7 +227,7 @@ pub fn run_shadow(args: Vec<&OsStr>) -> anyhow::Result<()> {
         .context("Failed to initialize the simulation")?;
 
     // allocate and initialize our main simulation driver
-    let controller = Controller::new(sim_config, &shadow_config);
+    let controller = Controller::new(sim_config, &shadow_config, options.dump_syscall_counts);
 
     // enable log buffering if not at trace level
     let buffer_log = !log::log_enabled!(log::Level::Trace);
@@ -245,6 +259,17 @@ pub fn version() -> String {
     s
 }
 
+/// The optional cargo features that shadow was built with.
+pub fn compiled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+
+    if cfg!(feature = "perf_timers") {
+        features.push("perf_timers");
+    }
+
+    features
+}
+
 fn write_build_info(mut w: impl std::io::Write) -> std::io::Result<()> {
     writeln!(w, "Shadow {}", version())?;
     writeln!(
@@ -261,6 +286,12 @@ fn write_build_info(mut w: impl std::io::Write) -> std::io::Result<()> {
         GIT_BRANCH.unwrap_or("<unknown>"),
     )?;
     writeln!(w, "{}", env!("SHADOW_BUILD_INFO"))?;
+    let features = compiled_features();
+    if features.is_empty() {
+        writeln!(w, "Built with features <none>")?;
+    } else {
+        writeln!(w, "Built with features {}", features.join(", "))?;
+    }
     writeln!(w, "{HELP_INFO_STR}")?;
 
     Ok(())
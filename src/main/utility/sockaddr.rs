@@ -23,6 +23,7 @@ union Addr {
     inet6: libc::sockaddr_in6,
     unix: libc::sockaddr_un,
     netlink: libc::sockaddr_nl,
+    link: libc::sockaddr_ll,
 }
 
 // verify there are no larger fields larger than `libc::sockaddr_storage`
@@ -194,6 +195,28 @@ impl SockaddrStorage {
         unsafe { Self::from_ptr(addr.as_ptr() as *const MaybeUninit<u8>, addr.len()) }.unwrap()
     }
 
+    /// If the socket address represents a valid link-layer socket address (correct family and
+    /// length), returns the link-layer socket address.
+    pub fn as_link(&self) -> Option<&libc::sockaddr_ll> {
+        if (self.len as usize) < std::mem::size_of::<libc::sockaddr_ll>() {
+            return None;
+        }
+        if self.family() != Some(AddressFamily::AF_PACKET) {
+            return None;
+        }
+
+        // SAFETY: `sockaddr_ll` has no padding bytes, and we checked the length above
+        Some(unsafe { &self.addr.link })
+    }
+
+    /// Get a new `SockaddrStorage` with a copy of the link-layer socket address.
+    pub fn from_link(addr: &libc::sockaddr_ll) -> Self {
+        let ptr = std::ptr::from_ref(addr) as *const MaybeUninit<u8>;
+        let len = std::mem::size_of_val(addr).try_into().unwrap();
+
+        unsafe { Self::from_ptr(ptr, len) }.unwrap()
+    }
+
     /// A pointer to the socket address. Some bytes may be uninitialized.
     pub fn as_ptr(&self) -> (*const MaybeUninit<u8>, libc::socklen_t) {
         (unsafe { &self.addr.slice }.as_ptr(), self.len)
@@ -301,6 +324,12 @@ impl From<nix::sys::socket::NetlinkAddr> for SockaddrStorage {
     }
 }
 
+impl From<libc::sockaddr_ll> for SockaddrStorage {
+    fn from(addr: libc::sockaddr_ll) -> Self {
+        SockaddrStorage::from_link(&addr)
+    }
+}
+
 /// A Unix socket address.
 ///
 /// Typically will be used as an owned address `SockaddrUnix<libc::sockaddr_un>` or a borrowed
@@ -619,6 +648,27 @@ mod tests {
         assert!(addr.as_unix().is_none());
     }
 
+    /// Convert from a `sockaddr_ll` to a `SockaddrStorage` and back.
+    #[test]
+    fn link_addr_round_trip() {
+        let mut addr_ll: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        addr_ll.sll_family = libc::AF_PACKET as u16;
+        addr_ll.sll_protocol = (libc::ETH_P_IP as u16).to_be();
+        addr_ll.sll_ifindex = 2;
+        addr_ll.sll_pkttype = libc::PACKET_OUTGOING;
+
+        let addr = SockaddrStorage::from_link(&addr_ll);
+
+        assert_eq!(addr.family(), Some(AddressFamily::AF_PACKET));
+        assert!(addr.as_inet().is_none());
+        assert!(addr.as_netlink().is_none());
+
+        let addr = addr.as_link().unwrap();
+        assert_eq!(addr.sll_protocol, addr_ll.sll_protocol);
+        assert_eq!(addr.sll_ifindex, addr_ll.sll_ifindex);
+        assert_eq!(addr.sll_pkttype, addr_ll.sll_pkttype);
+    }
+
     /// Convert from a `sockaddr_in` to a `SockaddrStorage` to a `SockaddrIn`.
     #[test]
     fn inet_addr_from_libc() {
@@ -0,0 +1,221 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client uploads data to a server over a congested link while a second client process measures
+//! the round-trip time of small UDP probes sent over the same link. The server's inbound router
+//! queue uses CoDel, so the greedy TCP flow should be throttled before it can build up a large
+//! standing queue, and the probes' queueing delay should stay bounded.
+//!
+//! Usage:
+//! - `test_aqm sink <port>`: receive the upload
+//! - `test_aqm echo <port>`: echo the probes
+//! - `test_aqm upload <server-ip> <port>`: upload data as fast as possible
+//! - `test_aqm probe <server-ip> <port>`: send probes and check their queueing delay
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::{Duration, Instant};
+
+use nix::errno::Errno;
+use nix::sys::socket::{
+    self, sockopt, AddressFamily, MsgFlags, Shutdown, SockFlag, SockType, SockaddrIn,
+};
+use nix::sys::time::TimeVal;
+
+/// How long the client uploads for.
+const UPLOAD_DURATION: Duration = Duration::from_secs(12);
+
+/// How long the client sends probes for. This should end before the upload ends.
+const PROBE_DURATION: Duration = Duration::from_secs(8);
+
+/// Time between probes.
+const PROBE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How long to wait for a probe's response before considering it lost.
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long the echo server waits for a probe before assuming that the client is done.
+const ECHO_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The bandwidth of the congested link, which should match the server's `bandwidth_down` in the
+/// shadow config.
+const LINK_BITS_PER_SEC: f64 = 10_000_000.0;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} (sink|echo) <port> | {0} (upload|probe) <server-ip> <port>",
+        args[0]
+    );
+
+    let parse_port = |s: &str| s.parse::<u16>().map_err(|e| format!("Bad port: {e}"));
+    let parse_addr = |ip: &str, port: &str| -> Result<SocketAddrV4, String> {
+        let ip: Ipv4Addr = ip.parse().map_err(|e| format!("Bad ip: {e}"))?;
+        Ok(SocketAddrV4::new(ip, parse_port(port)?))
+    };
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("sink"), 3) => run_sink(parse_port(&args[2])?)?,
+        (Some("echo"), 3) => run_echo(parse_port(&args[2])?)?,
+        (Some("upload"), 4) => run_upload(parse_addr(&args[2], &args[3])?)?,
+        (Some("probe"), 4) => run_probe(parse_addr(&args[2], &args[3])?)?,
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+fn new_socket(sock_type: SockType) -> Result<libc::c_int, String> {
+    socket::socket(AddressFamily::Inet, sock_type, SockFlag::empty(), None)
+        .map_err(|e| e.to_string())
+}
+
+fn set_recv_timeout(fd: libc::c_int, timeout: Duration) -> Result<(), String> {
+    socket::setsockopt(fd, sockopt::ReceiveTimeout, &TimeVal::from(timeout))
+        .map_err(|e| e.to_string())
+}
+
+/// Accept a connection and read everything that the client sends. Checks that the upload was
+/// able to use most of the link's bandwidth, so that the link was congested.
+fn run_sink(port: u16) -> Result<(), String> {
+    let fd_listen = new_socket(SockType::Stream)?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        let start = Instant::now();
+
+        let mut buf = vec![0u8; 65536];
+        let mut received = 0;
+        loop {
+            let rv = socket::recv(fd, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            if rv == 0 {
+                break;
+            }
+            received += rv;
+        }
+
+        let bits_per_sec = (received * 8) as f64 / start.elapsed().as_secs_f64();
+        println!("Received {received} bytes at {bits_per_sec:.0} bits/s");
+
+        test_utils::result_assert(
+            bits_per_sec > LINK_BITS_PER_SEC / 2.0,
+            "The upload didn't congest the link",
+        )
+    })
+}
+
+/// Send each received datagram back to its sender, until no datagrams have been received for a
+/// while.
+fn run_echo(port: u16) -> Result<(), String> {
+    let fd = new_socket(SockType::Datagram)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+        socket::bind(fd, &addr).map_err(|e| e.to_string())?;
+        set_recv_timeout(fd, ECHO_IDLE_TIMEOUT)?;
+
+        let mut buf = [0u8; 64];
+        let mut num_echoed = 0;
+        loop {
+            let (len, peer) = match socket::recvfrom::<SockaddrIn>(fd, &mut buf) {
+                Ok(x) => x,
+                Err(Errno::EAGAIN) if num_echoed > 0 => break,
+                Err(e) => return Err(e.to_string()),
+            };
+            socket::sendto(fd, &buf[..len], &peer.unwrap(), MsgFlags::empty())
+                .map_err(|e| e.to_string())?;
+            num_echoed += 1;
+        }
+
+        Ok(())
+    })
+}
+
+/// Upload data to the server as fast as possible for a fixed amount of time.
+fn run_upload(server_addr: SocketAddrV4) -> Result<(), String> {
+    let fd = new_socket(SockType::Stream)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let start = Instant::now();
+        let buf = vec![0u8; 65536];
+        while start.elapsed() < UPLOAD_DURATION {
+            socket::send(fd, &buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        }
+
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())
+    })
+}
+
+/// Send probes to the echo server and check that their queueing delay was bounded.
+fn run_probe(server_addr: SocketAddrV4) -> Result<(), String> {
+    let fd = new_socket(SockType::Datagram)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+        set_recv_timeout(fd, PROBE_TIMEOUT)?;
+
+        let start = Instant::now();
+        let mut num_sent = 0u32;
+        let mut rtts = vec![];
+
+        while start.elapsed() < PROBE_DURATION {
+            let seq = num_sent;
+            num_sent += 1;
+
+            let send_time = Instant::now();
+            socket::send(fd, &seq.to_ne_bytes(), MsgFlags::empty()).map_err(|e| e.to_string())?;
+
+            // wait for the response to this probe, ignoring late responses to earlier probes
+            let mut buf = [0u8; 4];
+            loop {
+                match socket::recv(fd, &mut buf, MsgFlags::empty()) {
+                    Ok(4) if u32::from_ne_bytes(buf) == seq => {
+                        rtts.push(send_time.elapsed());
+                        break;
+                    }
+                    Ok(_) => continue,
+                    // the probe or its response was dropped
+                    Err(Errno::EAGAIN) => break,
+                    Err(e) => return Err(e.to_string()),
+                }
+            }
+
+            std::thread::sleep(PROBE_INTERVAL);
+        }
+
+        test_utils::result_assert(
+            rtts.len() >= num_sent as usize / 2,
+            &format!("Only {} of {num_sent} probes were answered", rtts.len()),
+        )?;
+
+        // the smallest round-trip time is the delay without any queueing
+        rtts.sort();
+        let base_rtt = rtts[0];
+        let delays: Vec<_> = rtts.iter().map(|x| *x - base_rtt).collect();
+
+        let median = delays[delays.len() / 2];
+        let p95 = delays[delays.len() * 95 / 100];
+        println!("Queueing delay: median {median:?}, 95th percentile {p95:?}");
+
+        // CoDel tries to keep the queueing delay near its 10 ms target, and starts dropping
+        // packets once the delay has been above the target for 100 ms; without it the upload's
+        // congestion window would fill the queue with hundreds of milliseconds of data
+        test_utils::result_assert(
+            median < Duration::from_millis(50),
+            "The median queueing delay wasn't bounded",
+        )?;
+        test_utils::result_assert(
+            p95 < Duration::from_millis(100),
+            "The 95th percentile queueing delay wasn't bounded",
+        )
+    })
+}
@@ -0,0 +1,121 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+// Tests the host's block devices. The device doesn't exist on the real filesystem, so this only
+// runs in shadow.
+
+#include <errno.h>
+#include <fcntl.h>
+#include <glib.h>
+#include <string.h>
+#include <sys/ioctl.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#include "test/test_glib_helpers.h"
+
+// these must match the block device
+#define DEVICE_PATH "/dev/shadow-test-blk"
+#define DEVICE_SIZE (1024 * 1024)
+#define SECTOR_SIZE 4096
+
+static void _test_stat(void) {
+    struct stat statbuf = {0};
+    assert_nonneg_errno(stat(DEVICE_PATH, &statbuf));
+    g_assert_true(S_ISBLK(statbuf.st_mode));
+    g_assert_cmpint(statbuf.st_size, ==, 0);
+
+    int fd = open(DEVICE_PATH, O_RDONLY);
+    assert_nonneg_errno(fd);
+    assert_nonneg_errno(fstat(fd, &statbuf));
+    g_assert_true(S_ISBLK(statbuf.st_mode));
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_ioctl(void) {
+    int fd = open(DEVICE_PATH, O_RDONLY);
+    assert_nonneg_errno(fd);
+
+    uint64_t size = 0;
+    assert_nonneg_errno(ioctl(fd, BLKGETSIZE64, &size));
+    g_assert_cmpint(size, ==, DEVICE_SIZE);
+
+    int sectorSize = 0;
+    assert_nonneg_errno(ioctl(fd, BLKSSZGET, &sectorSize));
+    g_assert_cmpint(sectorSize, ==, SECTOR_SIZE);
+
+    // the device's capacity is also its end
+    g_assert_cmpint(lseek(fd, 0, SEEK_END), ==, DEVICE_SIZE);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_ioctl_regular_file(void) {
+    int fd = open("regular-file", O_RDWR | O_CREAT | O_TRUNC, 0600);
+    assert_nonneg_errno(fd);
+
+    uint64_t size = 0;
+    g_assert_cmpint(ioctl(fd, BLKGETSIZE64, &size), ==, -1);
+    assert_errno_is(ENOTTY);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_read_write(void) {
+    // O_TRUNC has no effect on a block device
+    int fd = open(DEVICE_PATH, O_RDWR | O_TRUNC);
+    assert_nonneg_errno(fd);
+
+    const char data[] = "block device data";
+    const off_t offset = 3 * SECTOR_SIZE + 10;
+    g_assert_cmpint(pwrite(fd, data, sizeof(data), offset), ==, sizeof(data));
+
+    char buf[sizeof(data) + 10] = {0};
+    g_assert_cmpint(pread(fd, buf, sizeof(buf), offset - 10), ==, sizeof(buf));
+
+    // the device starts zeroed
+    for (int i = 0; i < 10; i++) {
+        g_assert_cmpint(buf[i], ==, 0);
+    }
+    g_assert_cmpstr(&buf[10], ==, data);
+
+    // a write is cut short at the end of the device
+    g_assert_cmpint(lseek(fd, DEVICE_SIZE - 4, SEEK_SET), ==, DEVICE_SIZE - 4);
+    g_assert_cmpint(write(fd, data, sizeof(data)), ==, 4);
+    g_assert_cmpint(write(fd, data, sizeof(data)), ==, -1);
+    assert_errno_is(ENOSPC);
+
+    // reading at the end of the device returns EOF
+    g_assert_cmpint(read(fd, buf, sizeof(buf)), ==, 0);
+
+    // the device keeps its size
+    uint64_t size = 0;
+    assert_nonneg_errno(ioctl(fd, BLKGETSIZE64, &size));
+    g_assert_cmpint(size, ==, DEVICE_SIZE);
+    g_assert_cmpint(ftruncate(fd, 0), ==, -1);
+    assert_errno_is(EINVAL);
+
+    assert_nonneg_errno(close(fd));
+
+    // the data persists after the device is reopened
+    fd = open(DEVICE_PATH, O_RDONLY);
+    assert_nonneg_errno(fd);
+    memset(buf, 0, sizeof(buf));
+    g_assert_cmpint(pread(fd, buf, sizeof(data), offset), ==, sizeof(data));
+    g_assert_cmpstr(buf, ==, data);
+    assert_nonneg_errno(close(fd));
+}
+
+int main(int argc, char** argv) {
+    g_test_init(&argc, &argv, NULL);
+
+    g_test_add_func("/block_devices/stat", _test_stat);
+    g_test_add_func("/block_devices/ioctl", _test_ioctl);
+    g_test_add_func("/block_devices/ioctl_regular_file", _test_ioctl_regular_file);
+    g_test_add_func("/block_devices/read_write", _test_read_write);
+
+    return g_test_run();
+}
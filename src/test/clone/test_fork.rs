@@ -15,7 +15,7 @@ use linux_api::sched::{CloneFlags, CloneResult};
 use linux_api::signal::{LinuxDefaultAction, Signal};
 use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigmaskHow};
 use nix::sys::signalfd::SigSet;
-use rustix::fd::{AsFd, AsRawFd};
+use rustix::fd::{AsFd, AsRawFd, RawFd};
 use test_utils::{ensure_ord, running_in_shadow, TestEnvironment as TestEnv};
 use test_utils::{set, ShadowTest};
 
@@ -1122,6 +1122,133 @@ else:
     })
 }
 
+/// Fork and exec a python script in the child that checks that the `cloexec_fds` were closed and
+/// the `open_fds` are still open, and wait for it to exit successfully.
+fn fork_exec_check_fds(python_path: &Path, cloexec_fds: &[RawFd], open_fds: &[RawFd]) {
+    let clone_res = unsafe { linux_api::sched::fork() }.unwrap();
+    let child_pid = match clone_res {
+        CloneResult::CallerIsChild => {
+            let path = CString::new(python_path.as_os_str().as_bytes()).unwrap();
+            let script = CString::new(format!(
+                r#"
+import errno
+import fcntl
+for fd in {cloexec_fds:?}:
+    try:
+        fcntl.fcntl(fd, fcntl.F_GETFD)
+        assert False, f"fd {{fd}} unexpectedly still open"
+    except OSError as e:
+        assert e.errno == errno.EBADF, f"unexpected errno {{e.errno}} for fd {{fd}}"
+for fd in {open_fds:?}:
+    assert fcntl.fcntl(fd, fcntl.F_GETFD) == 0, f"unexpected flags for fd {{fd}}"
+                            "#
+            ))
+            .unwrap();
+            let args = vec![path.clone(), CString::new("-c").unwrap(), script];
+            unsafe { libc::execv(path.as_ptr(), execv_argvec(&args).as_ptr()) };
+            unreachable!("execv shouldn't have returned");
+        }
+        CloneResult::CallerIsParent(child_pid) => child_pid,
+    };
+
+    let child_pid = nix::unistd::Pid::from_raw(child_pid.as_raw_nonzero().get());
+    assert_eq!(
+        nix::sys::wait::waitpid(Some(child_pid), None).unwrap(),
+        nix::sys::wait::WaitStatus::Exited(child_pid, 0)
+    );
+}
+
+/// Descriptors that had FD_CLOEXEC set after they were created (via `fcntl`) should be closed on
+/// execve, while the other descriptors are left open.
+fn test_fork_exec_fcntl_cloexec(python_path: &Path) -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        let (_reader, writer) = rustix::pipe::pipe().unwrap();
+        let mut cloexec_fds = Vec::new();
+        let mut open_fds = Vec::new();
+        for i in 0..6 {
+            let fd = rustix::io::dup(&writer).unwrap();
+            if i % 2 == 0 {
+                rustix::io::fcntl_setfd(&fd, rustix::io::FdFlags::CLOEXEC).unwrap();
+                cloexec_fds.push(fd);
+            } else {
+                open_fds.push(fd);
+            }
+        }
+
+        let cloexec_raw: Vec<_> = cloexec_fds.iter().map(|fd| fd.as_raw_fd()).collect();
+        let open_raw: Vec<_> = open_fds.iter().map(|fd| fd.as_raw_fd()).collect();
+        fork_exec_check_fds(python_path, &cloexec_raw, &open_raw);
+    })
+}
+
+/// The close-on-exec flag should be honored on execve regardless of which syscall set it when
+/// creating the descriptor.
+fn test_fork_exec_cloexec_syscalls(python_path: &Path) -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        let mut cloexec_fds = Vec::new();
+        let mut open_fds = Vec::new();
+
+        // open(2), for a character device
+        for (flag, fds) in [(libc::O_CLOEXEC, &mut cloexec_fds), (0, &mut open_fds)] {
+            let path = c"/dev/null";
+            let fd = unsafe { libc::open(path.as_ptr(), libc::O_RDONLY | flag) };
+            assert!(fd >= 0);
+            fds.push(fd);
+        }
+
+        // socket(2) and accept4(2)
+        let listener =
+            unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0) };
+        assert!(listener >= 0);
+        let mut addr = libc::sockaddr_in {
+            sin_family: libc::AF_INET as u16,
+            sin_port: 0,
+            sin_addr: libc::in_addr {
+                s_addr: u32::from(std::net::Ipv4Addr::LOCALHOST).to_be(),
+            },
+            sin_zero: [0; 8],
+        };
+        let mut addr_len = std::mem::size_of_val(&addr) as libc::socklen_t;
+        let addr_ptr = std::ptr::from_mut(&mut addr).cast::<libc::sockaddr>();
+        assert_eq!(unsafe { libc::bind(listener, addr_ptr, addr_len) }, 0);
+        assert_eq!(unsafe { libc::listen(listener, 10) }, 0);
+        assert_eq!(
+            unsafe { libc::getsockname(listener, addr_ptr, &mut addr_len) },
+            0
+        );
+        cloexec_fds.push(listener);
+
+        let mut clients = Vec::new();
+        for (flag, fds) in [(libc::SOCK_CLOEXEC, &mut cloexec_fds), (0, &mut open_fds)] {
+            let client = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+            assert!(client >= 0);
+            assert_eq!(unsafe { libc::connect(client, addr_ptr, addr_len) }, 0);
+            clients.push(client);
+
+            let accepted = unsafe {
+                libc::accept4(listener, std::ptr::null_mut(), std::ptr::null_mut(), flag)
+            };
+            assert!(accepted >= 0);
+            fds.push(accepted);
+        }
+        open_fds.extend(clients);
+
+        // eventfd(2)
+        for (flag, fds) in [(libc::EFD_CLOEXEC, &mut cloexec_fds), (0, &mut open_fds)] {
+            let fd = unsafe { libc::eventfd(0, flag) };
+            assert!(fd >= 0);
+            fds.push(fd);
+        }
+
+        // dup3(2)
+        let fd = unsafe { libc::dup3(open_fds[0], 100, libc::O_CLOEXEC) };
+        assert_eq!(fd, 100);
+        cloexec_fds.push(fd);
+
+        fork_exec_check_fds(python_path, &cloexec_fds, &open_fds);
+    })
+}
+
 /// After exec, the process should get its own copy of its DescriptorTable,
 /// undoing the effect of CLONE_FILES.
 fn test_fork_exec_desc_table_unshared(python_path: &Path) -> anyhow::Result<()> {
@@ -1293,6 +1420,157 @@ fn test_clone_clear_sighand(set_clear_sighand: bool) -> anyhow::Result<()> {
     })
 }
 
+fn pidfd_open(pid: Pid, flags: libc::c_uint) -> Result<c_int, nix::errno::Errno> {
+    nix::errno::Errno::result(unsafe {
+        libc::syscall(libc::SYS_pidfd_open, pid.as_raw_nonzero().get(), flags)
+    })
+    .map(|fd| fd.try_into().unwrap())
+}
+
+fn pidfd_send_signal(
+    pidfd: c_int,
+    signal: nix::sys::signal::Signal,
+    flags: libc::c_uint,
+) -> Result<(), nix::errno::Errno> {
+    nix::errno::Errno::result(unsafe {
+        libc::syscall(
+            libc::SYS_pidfd_send_signal,
+            pidfd,
+            signal as c_int,
+            std::ptr::null::<siginfo_t>(),
+            flags,
+        )
+    })
+    .map(|rv| assert_eq!(rv, 0))
+}
+
+/// Validate that a pidfd becomes readable once the child process exits.
+fn test_pidfd_readable_on_exit() -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        let (reader, writer) = rustix::pipe::pipe().unwrap();
+
+        let child_pid = match unsafe { linux_api::sched::fork() }.unwrap() {
+            CloneResult::CallerIsChild => {
+                // Wait for the parent to tell us to exit.
+                let mut buf = [0];
+                assert_eq!(rustix::io::read(&reader, &mut buf), Ok(1));
+                unsafe { libc::exit(0) };
+            }
+            CloneResult::CallerIsParent(child_pid) => child_pid,
+        };
+
+        // Only PIDFD_NONBLOCK is a valid flag.
+        assert_eq!(pidfd_open(child_pid, 1), Err(nix::errno::Errno::EINVAL));
+
+        let pidfd = pidfd_open(child_pid, 0).unwrap();
+
+        // The close-on-exec flag is always set.
+        let fd_flags = nix::fcntl::fcntl(pidfd, nix::fcntl::FcntlArg::F_GETFD).unwrap();
+        assert_eq!(fd_flags, libc::FD_CLOEXEC);
+
+        // The child is still running.
+        let mut poll_fds = [nix::poll::PollFd::new(pidfd, nix::poll::PollFlags::POLLIN)];
+        assert_eq!(nix::poll::poll(&mut poll_fds, 0), Ok(0));
+
+        assert_eq!(rustix::io::write(&writer, &[0]), Ok(1));
+
+        // Becomes readable when the child exits, even before it's reaped.
+        assert_eq!(nix::poll::poll(&mut poll_fds, 5000), Ok(1));
+        assert_eq!(poll_fds[0].revents(), Some(nix::poll::PollFlags::POLLIN));
+
+        // Signals to a zombie are accepted and ignored.
+        assert_eq!(
+            pidfd_send_signal(pidfd, nix::sys::signal::SIGKILL, 0),
+            Ok(())
+        );
+
+        // pidfds can't be read from.
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            nix::unistd::read(pidfd, &mut buf),
+            Err(nix::errno::Errno::EINVAL)
+        );
+
+        let child_pid = nix::unistd::Pid::from_raw(child_pid.as_raw_nonzero().get());
+        assert_eq!(
+            nix::sys::wait::waitpid(Some(child_pid), None).unwrap(),
+            nix::sys::wait::WaitStatus::Exited(child_pid, 0)
+        );
+
+        // The child has been reaped.
+        assert_eq!(
+            pidfd_send_signal(pidfd, nix::sys::signal::SIGKILL, 0),
+            Err(nix::errno::Errno::ESRCH)
+        );
+
+        nix::unistd::close(pidfd).unwrap();
+    })
+}
+
+/// Validate that `pidfd_send_signal` delivers a signal to the child process.
+fn test_pidfd_send_signal() -> anyhow::Result<()> {
+    run_test_in_subprocess(|| {
+        let child_pid = match unsafe { linux_api::sched::fork() }.unwrap() {
+            CloneResult::CallerIsChild => {
+                // Wait to be killed.
+                loop {
+                    unsafe { libc::pause() };
+                }
+            }
+            CloneResult::CallerIsParent(child_pid) => child_pid,
+        };
+
+        let pidfd = pidfd_open(child_pid, libc::O_NONBLOCK as libc::c_uint).unwrap();
+
+        // No flags are valid.
+        assert_eq!(
+            pidfd_send_signal(pidfd, nix::sys::signal::SIGKILL, 1),
+            Err(nix::errno::Errno::EINVAL)
+        );
+
+        assert_eq!(
+            pidfd_send_signal(pidfd, nix::sys::signal::SIGKILL, 0),
+            Ok(())
+        );
+
+        let child_pid = nix::unistd::Pid::from_raw(child_pid.as_raw_nonzero().get());
+        assert_eq!(
+            nix::sys::wait::waitpid(Some(child_pid), None).unwrap(),
+            nix::sys::wait::WaitStatus::Signaled(child_pid, nix::sys::signal::SIGKILL, false)
+        );
+
+        let mut poll_fds = [nix::poll::PollFd::new(pidfd, nix::poll::PollFlags::POLLIN)];
+        assert_eq!(nix::poll::poll(&mut poll_fds, 0), Ok(1));
+
+        nix::unistd::close(pidfd).unwrap();
+    })
+}
+
+/// Validate that `pidfd_send_signal` rejects file descriptors that aren't pidfds.
+fn test_pidfd_send_signal_not_pidfd() -> anyhow::Result<()> {
+    let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+    let file = std::fs::File::open("/dev/null").unwrap();
+
+    // A valid file descriptor that isn't a pidfd.
+    for fd in [read_fd, file.as_raw_fd()] {
+        assert_eq!(
+            pidfd_send_signal(fd, nix::sys::signal::SIGKILL, 0),
+            Err(nix::errno::Errno::EINVAL)
+        );
+    }
+
+    nix::unistd::close(read_fd).unwrap();
+    nix::unistd::close(write_fd).unwrap();
+
+    // Not a valid file descriptor.
+    assert_eq!(
+        pidfd_send_signal(read_fd, nix::sys::signal::SIGKILL, 0),
+        Err(nix::errno::Errno::EBADF)
+    );
+
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     // FIXME: take as a command-line arg
     let python_path = Path::new("/usr/bin/python3");
@@ -1931,6 +2209,24 @@ fn main() -> Result<(), Box<dyn Error>> {
         ));
     }
 
+    tests.push(ShadowTest::new(
+        "test_fork_exec_fcntl_cloexec",
+        {
+            let python_path = python_path.to_path_buf();
+            move || test_fork_exec_fcntl_cloexec(&python_path)
+        },
+        all_envs.clone(),
+    ));
+
+    tests.push(ShadowTest::new(
+        "test_fork_exec_cloexec_syscalls",
+        {
+            let python_path = python_path.to_path_buf();
+            move || test_fork_exec_cloexec_syscalls(&python_path)
+        },
+        all_envs.clone(),
+    ));
+
     tests.push(ShadowTest::new(
         "test_fork_exec_desc_table_unshared",
         {
@@ -1957,6 +2253,22 @@ fn main() -> Result<(), Box<dyn Error>> {
         ));
     }
 
+    tests.push(ShadowTest::new(
+        stringify!(test_pidfd_readable_on_exit),
+        test_pidfd_readable_on_exit,
+        all_envs.clone(),
+    ));
+    tests.push(ShadowTest::new(
+        stringify!(test_pidfd_send_signal),
+        test_pidfd_send_signal,
+        all_envs.clone(),
+    ));
+    tests.push(ShadowTest::new(
+        stringify!(test_pidfd_send_signal_not_pidfd),
+        test_pidfd_send_signal_not_pidfd,
+        all_envs.clone(),
+    ));
+
     // It'd be good to test signal config across exec, but this is tricky since
     // python re-initializes it at startup. We might have to write specialized
     // programs in C to exec, and have them verify or otherwise output the
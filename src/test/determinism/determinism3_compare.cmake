@@ -0,0 +1,32 @@
+macro(EXEC_DIFF_CHECK FILE1 FILE2)
+    execute_process(
+        COMMAND ${CMAKE_COMMAND} -E compare_files ${FILE1} ${FILE2}
+        RESULT_VARIABLE RESULT
+        OUTPUT_VARIABLE STDOUTPUT
+        ERROR_VARIABLE STDERROR)
+    message(STATUS "Diff returned ${RESULT} for 'diff ${FILE1} ${FILE2}'")
+    if(RESULT)
+        message(STATUS "Diff stdout is: ${STDOUTPUT}")
+        message(STATUS "Diff stderr is: ${STDERROR}")
+        message(FATAL_ERROR "Differences found; test failed")
+    endif()
+endmacro()
+foreach(LOOPIDX RANGE 1 3)
+    set(HOST_DIR_A ${CMAKE_BINARY_DIR}/determinism3a-shadow.data/hosts/testnode${LOOPIDX})
+    # the output file names contain the pids, so the runs should have the same file names
+    file(GLOB FILES_A RELATIVE ${HOST_DIR_A} ${HOST_DIR_A}/test-determinism-pids.*.stdout)
+    list(LENGTH FILES_A NUM_FILES)
+    if(NOT NUM_FILES EQUAL 4)
+        message(FATAL_ERROR "Expected 4 output files in ${HOST_DIR_A}, found ${NUM_FILES}")
+    endif()
+    foreach(RUN b c)
+        set(HOST_DIR ${CMAKE_BINARY_DIR}/determinism3${RUN}-shadow.data/hosts/testnode${LOOPIDX})
+        file(GLOB FILES RELATIVE ${HOST_DIR} ${HOST_DIR}/test-determinism-pids.*.stdout)
+        if(NOT FILES_A STREQUAL FILES)
+            message(FATAL_ERROR "Process pids differ: '${FILES_A}' vs '${FILES}'")
+        endif()
+        foreach(FILE ${FILES_A})
+            exec_diff_check(${HOST_DIR_A}/${FILE} ${HOST_DIR}/${FILE})
+        endforeach(FILE)
+    endforeach(RUN)
+endforeach(LOOPIDX)
@@ -13,10 +13,15 @@
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
+#include <sys/random.h>
 #include <sys/types.h>
 #include <syscall.h>
 #include <unistd.h>
 
+#ifndef GRND_INSECURE
+#define GRND_INSECURE 0x0004
+#endif
+
 static int test_randomOpenRead(const char *filename) {
     unsigned char buf[1];
     buf[0] = 0;
@@ -86,6 +91,75 @@ static int _test_open() {
     return EXIT_SUCCESS;
 }
 
+static int test_randomReadBlock(const char* filename) {
+    unsigned char buf[4096];
+    memset(buf, 0, sizeof(buf));
+
+    int fd = open(filename, O_RDONLY);
+    if (fd < 0) {
+        return EXIT_FAILURE;
+    }
+
+    size_t total = 0;
+    while (total < sizeof(buf)) {
+        ssize_t sz = read(fd, buf + total, sizeof(buf) - total);
+        if (sz <= 0) {
+            close(fd);
+            return EXIT_FAILURE;
+        }
+        total += sz;
+    }
+    close(fd);
+
+    fprintf(stdout, "%s (%zu bytes)\t:\n", filename, sizeof(buf));
+    for (size_t i = 0; i < sizeof(buf); i++) {
+        fprintf(stdout, "%02X%s", buf[i], (i + 1) % 32 == 0 ? "\n" : "");
+    }
+
+    return EXIT_SUCCESS;
+}
+
+static int test_randomGetrandom(unsigned int flags) {
+    unsigned char buf[16];
+    memset(buf, 0, sizeof(buf));
+
+    /* use the syscall directly since getrandom() was only added in glibc 2.25 */
+    long sz = syscall(SYS_getrandom, buf, sizeof(buf), flags);
+    if (sz != sizeof(buf)) {
+        return EXIT_FAILURE;
+    }
+
+    fprintf(stdout, "getrandom(%#x)\t: ", flags);
+    for (size_t i = 0; i < sizeof(buf); i++) {
+        fprintf(stdout, "%02X", buf[i]);
+    }
+    fprintf(stdout, "\n");
+
+    return EXIT_SUCCESS;
+}
+
+static int _test_read_block() {
+    /* larger reads should also result in deterministic behavior */
+    if (test_randomReadBlock("/dev/urandom") == EXIT_FAILURE) {
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
+
+static int _test_getrandom() {
+    /* this should result in deterministic behavior, regardless of the flags */
+    if (test_randomGetrandom(0) == EXIT_FAILURE) {
+        return EXIT_FAILURE;
+    }
+    if (test_randomGetrandom(GRND_RANDOM) == EXIT_FAILURE) {
+        return EXIT_FAILURE;
+    }
+    if (test_randomGetrandom(GRND_INSECURE) == EXIT_FAILURE) {
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
+
 typedef struct _ThreadPIDs ThreadPIDs;
 struct _ThreadPIDs {
     int pid;
@@ -146,6 +220,52 @@ static int _test_getPID() {
     return EXIT_SUCCESS;
 }
 
+typedef struct _ThreadCPU ThreadCPU;
+struct _ThreadCPU {
+    unsigned int cpu;
+    unsigned int node;
+    long result;
+};
+
+static void* _test_runThreadGetcpu(void* arg) {
+    ThreadCPU* cpu = (ThreadCPU*)arg;
+    cpu->result = syscall(SYS_getcpu, &cpu->cpu, &cpu->node, NULL);
+    return NULL;
+}
+
+static int _test_getcpu() {
+    ThreadCPU cpus[NUMTHREADS + 1];
+    memset(&cpus[0], 0, (NUMTHREADS + 1) * sizeof(ThreadCPU));
+
+    // the main thread's CPU is the last entry
+    _test_runThreadGetcpu(&cpus[NUMTHREADS]);
+
+    for (int i = 0; i < NUMTHREADS; i++) {
+        pthread_t thread;
+        int retval = pthread_create(&thread, NULL, _test_runThreadGetcpu, (void*)&cpus[i]);
+        if (retval != 0) {
+            fprintf(stdout, "error %i in pthread_create: %s\n", retval, strerror(retval));
+            return EXIT_FAILURE;
+        }
+        retval = pthread_join(thread, NULL);
+        if (retval != 0) {
+            fprintf(stdout, "error %i in pthread_join: %s\n", retval, strerror(retval));
+            return EXIT_FAILURE;
+        }
+    }
+
+    for (int i = 0; i < NUMTHREADS + 1; i++) {
+        if (cpus[i].result != 0) {
+            fprintf(stdout, "getcpu() failed in thread %i\n", i);
+            return EXIT_FAILURE;
+        }
+        fprintf(stdout, "getcpu() in thread %i returned cpu=%u, node=%u\n", i, cpus[i].cpu,
+                cpus[i].node);
+    }
+
+    return EXIT_SUCCESS;
+}
+
 static int _test_nameAddress() {
     /* first get our hostname */
     char hostname[1024];
@@ -223,6 +343,20 @@ int main(int argc, char* argv[]) {
     }
     fprintf(stdout, "_test_fopen() passed\n");
 
+    fprintf(stdout, "starting _test_read_block()\n");
+    if (_test_read_block() != EXIT_SUCCESS) {
+        fprintf(stdout, "########## _test_read_block() failed\n");
+        return EXIT_FAILURE;
+    }
+    fprintf(stdout, "_test_read_block() passed\n");
+
+    fprintf(stdout, "starting _test_getrandom()\n");
+    if (_test_getrandom() != EXIT_SUCCESS) {
+        fprintf(stdout, "########## _test_getrandom() failed\n");
+        return EXIT_FAILURE;
+    }
+    fprintf(stdout, "_test_getrandom() passed\n");
+
     fprintf(stdout, "starting _test_getPID()\n");
     if (_test_getPID() < 0) {
         fprintf(stdout, "########## _test_getPID() failed\n");
@@ -230,6 +364,13 @@ int main(int argc, char* argv[]) {
     }
     fprintf(stdout, "_test_getPID() passed\n");
 
+    fprintf(stdout, "starting _test_getcpu()\n");
+    if (_test_getcpu() != EXIT_SUCCESS) {
+        fprintf(stdout, "########## _test_getcpu() failed\n");
+        return EXIT_FAILURE;
+    }
+    fprintf(stdout, "_test_getcpu() passed\n");
+
     fprintf(stdout, "starting _test_nameAddress()\n");
     if (_test_nameAddress() < 0) {
         fprintf(stdout, "########## _test_nameAddress() failed\n");
@@ -0,0 +1,80 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <pthread.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/syscall.h>
+#include <sys/types.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#define NUMCHILDREN 3
+
+static void* _thread_main(void* arg) {
+    fprintf(stdout, "thread: pid=%i, tid=%i\n", (int)getpid(), (int)syscall(SYS_gettid));
+    return NULL;
+}
+
+static int _test_thread() {
+    pthread_t thread;
+    if (pthread_create(&thread, NULL, _thread_main, NULL) != 0) {
+        fprintf(stdout, "error in pthread_create\n");
+        return EXIT_FAILURE;
+    }
+    if (pthread_join(thread, NULL) != 0) {
+        fprintf(stdout, "error in pthread_join\n");
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
+
+/* Fork children one at a time, reaping each before forking the next, so that the pids of later
+ * children would change if shadow reused pids nondeterministically. */
+static int _test_fork() {
+    for (int i = 0; i < NUMCHILDREN; i++) {
+        /* don't duplicate buffered output in the child */
+        fflush(stdout);
+
+        pid_t pid = fork();
+        if (pid < 0) {
+            fprintf(stdout, "error in fork\n");
+            return EXIT_FAILURE;
+        }
+
+        if (pid == 0) {
+            fprintf(stdout, "child %i: pid=%i, ppid=%i\n", i, (int)getpid(), (int)getppid());
+            fflush(stdout);
+            _exit(EXIT_SUCCESS);
+        }
+
+        int status = 0;
+        pid_t reaped = waitpid(pid, &status, 0);
+        if (reaped != pid || !WIFEXITED(status) || WEXITSTATUS(status) != EXIT_SUCCESS) {
+            fprintf(stdout, "error in waitpid\n");
+            return EXIT_FAILURE;
+        }
+
+        fprintf(stdout, "reaped child %i: pid=%i\n", i, (int)reaped);
+    }
+    return EXIT_SUCCESS;
+}
+
+int main(int argc, char* argv[]) {
+    fprintf(stdout, "main: pid=%i, ppid=%i\n", (int)getpid(), (int)getppid());
+
+    if (_test_thread() != EXIT_SUCCESS) {
+        return EXIT_FAILURE;
+    }
+    if (_test_fork() != EXIT_SUCCESS) {
+        return EXIT_FAILURE;
+    }
+    /* a thread created after reaping gets a new id rather than a reused one */
+    if (_test_thread() != EXIT_SUCCESS) {
+        return EXIT_FAILURE;
+    }
+
+    return EXIT_SUCCESS;
+}
@@ -63,6 +63,14 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
         ));
     }
 
+    for dup_fn in &[DupFn::Dup2, DupFn::Dup3] {
+        tests.push(test_utils::ShadowTest::new(
+            &format!("test_dup_onto_open_fd <dup_fn={:?}>", dup_fn),
+            move || test_dup_onto_open_fd(dup_fn),
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ));
+    }
+
     tests
 }
 
@@ -271,3 +279,54 @@ fn test_dup_io(dup_fn: &DupFn) -> Result<(), String> {
         })
     })
 }
+
+/// Test that duplicating onto an open fd closes the file that it previously referred to, and that
+/// the fd then refers to the duplicated file.
+fn test_dup_onto_open_fd(dup_fn: &DupFn) -> Result<(), String> {
+    let (read_fd_a, write_fd_a) = nix::unistd::pipe().unwrap();
+    let (read_fd_b, write_fd_b) = nix::unistd::pipe().unwrap();
+
+    test_utils::run_and_close_fds(&[read_fd_a, write_fd_a, read_fd_b, write_fd_b], || {
+        // make sure that the flags of the new fd don't come from the file it replaced
+        test_utils::check_system_call!(
+            || unsafe { libc::fcntl(write_fd_b, libc::F_SETFD, libc::FD_CLOEXEC) },
+            &[],
+        )?;
+
+        // replace the write end of pipe b with the write end of pipe a
+        let rv = test_utils::check_system_call!(
+            move || match dup_fn {
+                DupFn::Dup2 => unsafe { libc::dup2(write_fd_a, write_fd_b) },
+                DupFn::Dup3 => unsafe { libc::dup3(write_fd_a, write_fd_b, 0) },
+                _ => unimplemented!(),
+            },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, write_fd_b, "Unexpected fd returned")?;
+
+        let fd_flags = test_utils::check_system_call!(
+            || unsafe { libc::fcntl(write_fd_b, libc::F_GETFD) },
+            &[],
+        )?;
+        test_utils::result_assert_eq(fd_flags, 0, "Unexpected fd flags")?;
+
+        // the only write end of pipe b was closed, so its read end is at EOF
+        let mut buf = [0u8; 8];
+        let rv = nix::unistd::read(read_fd_b, &mut buf).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 0, "Expected EOF on the replaced pipe")?;
+
+        // writing to the replaced fd now writes to pipe a
+        let rv = nix::unistd::write(write_fd_b, &[1, 2, 3, 4]).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 4, "Expected to write 4 bytes")?;
+        let rv = nix::unistd::read(read_fd_a, &mut buf).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(&buf[..rv], &[1, 2, 3, 4][..], "Unexpected bytes read")?;
+
+        // the original fd still works
+        let rv = nix::unistd::write(write_fd_a, &[5, 6]).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 2, "Expected to write 2 bytes")?;
+        let rv = nix::unistd::read(read_fd_a, &mut buf).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(&buf[..rv], &[5, 6][..], "Unexpected bytes read")?;
+
+        Ok(())
+    })
+}
@@ -23,4 +23,58 @@ fn main() {
         std::env::var("LD_PRELOAD").expect("Environment variable 'LD_PRELOAD' not set");
     let ld_preload = ld_preload.split(':');
     assert!(ld_preload.last().unwrap() == "/my/custom/ld/preload/path.so");
+
+    test_proc_self_environ();
+    test_proc_self_auxv();
+}
+
+/// `/proc/self/environ` should contain the process' initial environment.
+fn test_proc_self_environ() {
+    let environ = std::fs::read("/proc/self/environ").unwrap();
+    let vars: Vec<&[u8]> = environ.split(|x| *x == 0).collect();
+    assert!(vars.contains(&&b"TESTING_ENV_VAR_1=HELLO WORLD"[..]));
+    assert!(vars.contains(&&b"TESTING_ENV_VAR_4=X=Y"[..]));
+}
+
+/// `/proc/self/auxv` should contain the process' auxiliary vector.
+fn test_proc_self_auxv() {
+    let auxv = std::fs::read("/proc/self/auxv").unwrap();
+    let word_size = std::mem::size_of::<libc::c_ulong>();
+    assert_eq!(auxv.len() % (2 * word_size), 0);
+
+    let entries: Vec<(libc::c_ulong, libc::c_ulong)> = auxv
+        .chunks_exact(2 * word_size)
+        .map(|entry| {
+            let (key, val) = entry.split_at(word_size);
+            (
+                libc::c_ulong::from_ne_bytes(key.try_into().unwrap()),
+                libc::c_ulong::from_ne_bytes(val.try_into().unwrap()),
+            )
+        })
+        .collect();
+
+    // the vector is terminated with an `AT_NULL` entry
+    assert_eq!(entries.last(), Some(&(libc::AT_NULL, 0)));
+
+    let page_size = entries
+        .iter()
+        .find(|(key, _)| *key == libc::AT_PAGESZ)
+        .map(|(_, val)| *val)
+        .expect("No AT_PAGESZ entry");
+    let expected_page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    assert_eq!(
+        page_size,
+        libc::c_ulong::try_from(expected_page_size).unwrap()
+    );
+
+    // the entries should match what the process was given on its stack
+    for key in [
+        libc::AT_PAGESZ,
+        libc::AT_HWCAP,
+        libc::AT_CLKTCK,
+        libc::AT_RANDOM,
+    ] {
+        let (_, val) = entries.iter().find(|(k, _)| *k == key).unwrap();
+        assert_eq!(*val, unsafe { libc::getauxval(key) });
+    }
 }
@@ -1,9 +1,12 @@
 use std::time::Duration;
 
 use nix::errno::Errno;
+use nix::poll::{PollFd, PollFlags};
 use nix::sys::epoll::{self, EpollFlags};
+use nix::sys::socket::Shutdown;
 use nix::unistd;
 
+use test_utils::socket_utils::{socket_init_helper, SocketInitMethod};
 use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
 
 #[derive(Debug)]
@@ -237,6 +240,82 @@ fn test_threads_level_with_early_read() -> anyhow::Result<()> {
     })
 }
 
+/// Two threads wait on a one-shot entry, and only one of them should receive each event. The entry
+/// should stay disabled until it's rearmed with `EPOLL_CTL_MOD`, even if the pipe receives more
+/// data.
+fn test_threads_oneshot_helper(flags: EpollFlags) -> anyhow::Result<()> {
+    let (readfd, writefd) = unistd::pipe()?;
+    let epollfd = epoll::epoll_create()?;
+
+    test_utils::run_and_close_fds(&[epollfd, readfd, writefd], || {
+        let mut event =
+            epoll::EpollEvent::new(flags | EpollFlags::EPOLLIN | EpollFlags::EPOLLONESHOT, 0);
+        epoll::epoll_ctl(
+            epollfd,
+            epoll::EpollOp::EpollCtlAdd,
+            readfd,
+            Some(&mut event),
+        )?;
+
+        let timeout = Duration::from_millis(100);
+
+        for round in 0..2 {
+            if round > 0 {
+                // Empty the pipe and rearm the entry.
+                ensure_ord!(unistd::read(readfd, &mut [0; 2]), ==, Ok(2));
+                epoll::epoll_ctl(
+                    epollfd,
+                    epoll::EpollOp::EpollCtlMod,
+                    readfd,
+                    Some(&mut event),
+                )?;
+            }
+
+            let threads = [
+                std::thread::spawn(move || {
+                    do_epoll_wait(epollfd, timeout, /* do_read= */ false)
+                }),
+                std::thread::spawn(move || {
+                    do_epoll_wait(epollfd, timeout, /* do_read= */ false)
+                }),
+            ];
+
+            // Wait for readers to block.
+            std::thread::sleep(timeout / 2);
+
+            // Make the read-end readable.
+            unistd::write(writefd, &[0])?;
+
+            let mut results = threads.map(|t| t.join().unwrap());
+            results.sort_by(|lhs, rhs| lhs.events.len().cmp(&rhs.events.len()));
+
+            // One thread should have timed out with no events received.
+            ensure_ord!(results[0].epoll_res, ==, Ok(0));
+            ensure_ord!(results[0].duration, >=, timeout);
+
+            // The other should have gotten a single event.
+            ensure_ord!(results[1].epoll_res, ==, Ok(1));
+            ensure_ord!(results[1].duration, <, timeout);
+            ensure_ord!(results[1].events[0], ==, epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0));
+
+            // More data doesn't rearm the entry.
+            unistd::write(writefd, &[0])?;
+            let res = do_epoll_wait(epollfd, Duration::ZERO, /* do_read= */ false);
+            ensure_ord!(res.epoll_res, ==, Ok(0));
+        }
+
+        Ok(())
+    })
+}
+
+fn test_threads_oneshot_level() -> anyhow::Result<()> {
+    test_threads_oneshot_helper(EpollFlags::empty())
+}
+
+fn test_threads_oneshot_edge() -> anyhow::Result<()> {
+    test_threads_oneshot_helper(EpollFlags::EPOLLET)
+}
+
 fn test_wait_negative_timeout() -> anyhow::Result<()> {
     let (read_fd, write_fd) = unistd::pipe()?;
     let epoll_fd = epoll::epoll_create()?;
@@ -329,6 +408,384 @@ fn test_ctl_invalid_op() -> anyhow::Result<()> {
     })
 }
 
+/// Test that an event on a file monitored by an epoll is reported through a second epoll that
+/// monitors the first.
+fn test_nested_wakeup() -> anyhow::Result<()> {
+    let (read_fd, write_fd) = unistd::pipe()?;
+    let inner_fd = epoll::epoll_create()?;
+    let outer_fd = epoll::epoll_create()?;
+
+    test_utils::run_and_close_fds(&[outer_fd, inner_fd, read_fd, write_fd], || {
+        let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, read_fd as u64);
+        epoll::epoll_ctl(
+            inner_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            read_fd,
+            Some(&mut event),
+        )?;
+
+        let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, inner_fd as u64);
+        epoll::epoll_ctl(
+            outer_fd,
+            epoll::EpollOp::EpollCtlAdd,
+            inner_fd,
+            Some(&mut event),
+        )?;
+
+        // nothing is ready yet
+        let res = do_epoll_wait(outer_fd, Duration::ZERO, /* do_read= */ false);
+        ensure_ord!(res.epoll_res, ==, Ok(0));
+
+        let timeout = Duration::from_millis(100);
+        let waiter =
+            std::thread::spawn(move || do_epoll_wait(outer_fd, timeout, /* do_read= */ false));
+
+        // Wait for the waiter to block.
+        std::thread::sleep(timeout / 2);
+
+        // Make the read-end readable.
+        unistd::write(write_fd, &[0])?;
+
+        // the outer epoll should wake up and report that the inner epoll is readable
+        let res = waiter.join().unwrap();
+        ensure_ord!(res.epoll_res, ==, Ok(1));
+        ensure_ord!(res.duration, <, timeout);
+        ensure_ord!(
+            res.events[0],
+            ==,
+            epoll::EpollEvent::new(EpollFlags::EPOLLIN, inner_fd as u64)
+        );
+
+        // the inner epoll should report the pipe
+        let res = do_epoll_wait(inner_fd, Duration::ZERO, /* do_read= */ false);
+        ensure_ord!(res.epoll_res, ==, Ok(1));
+        ensure_ord!(
+            res.events[0],
+            ==,
+            epoll::EpollEvent::new(EpollFlags::EPOLLIN, read_fd as u64)
+        );
+
+        // after reading, neither epoll is ready
+        ensure_ord!(unistd::read(read_fd, &mut [0])?, ==, 1);
+        let res = do_epoll_wait(outer_fd, Duration::ZERO, /* do_read= */ false);
+        ensure_ord!(res.epoll_res, ==, Ok(0));
+
+        Ok(())
+    })
+}
+
+/// Test that epolls can't monitor themselves, monitor each other in a loop, or be nested too
+/// deeply.
+fn test_nested_loop() -> anyhow::Result<()> {
+    let epoll_fds: Vec<i32> = (0..16)
+        .map(|_| epoll::epoll_create())
+        .collect::<Result<_, _>>()?;
+
+    test_utils::run_and_close_fds(&epoll_fds, || {
+        let add = |epoll_fd, fd| {
+            let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0);
+            epoll::epoll_ctl(epoll_fd, epoll::EpollOp::EpollCtlAdd, fd, Some(&mut event))
+        };
+
+        ensure_ord!(add(epoll_fds[0], epoll_fds[0]), ==, Err(Errno::EINVAL));
+
+        // a loop of two epolls
+        add(epoll_fds[0], epoll_fds[1])?;
+        ensure_ord!(add(epoll_fds[1], epoll_fds[0]), ==, Err(Errno::ELOOP));
+
+        // a longer loop
+        add(epoll_fds[1], epoll_fds[2])?;
+        ensure_ord!(add(epoll_fds[2], epoll_fds[0]), ==, Err(Errno::ELOOP));
+
+        // a separate chain where each epoll monitors the previous one, extended until it's too
+        // deep
+        let mut result = Ok(());
+        for pair in epoll_fds[3..].windows(2) {
+            result = add(pair[1], pair[0]);
+            if result.is_err() {
+                break;
+            }
+        }
+        ensure_ord!(result, ==, Err(Errno::ELOOP));
+
+        Ok(())
+    })
+}
+
+/// Test that the depth limit of nested epolls counts the epolls above the epoll being added to, as
+/// well as the epolls below the epoll being added.
+fn test_nested_depth() -> anyhow::Result<()> {
+    let epoll_fds: Vec<i32> = (0..12)
+        .map(|_| epoll::epoll_create())
+        .collect::<Result<_, _>>()?;
+
+    test_utils::run_and_close_fds(&epoll_fds, || {
+        let add = |epoll_fd, fd| {
+            let mut event = epoll::EpollEvent::new(EpollFlags::EPOLLIN, 0);
+            epoll::epoll_ctl(epoll_fd, epoll::EpollOp::EpollCtlAdd, fd, Some(&mut event))
+        };
+
+        // a chain built from the top down, where each epoll monitors the next one, can have at
+        // most 5 epolls
+        let chain = &epoll_fds[..6];
+        for pair in chain[..5].windows(2) {
+            add(pair[0], pair[1])?;
+        }
+        ensure_ord!(add(chain[4], chain[5]), ==, Err(Errno::ELOOP));
+
+        // joining two chains of 3 epolls would make a chain of 6 epolls
+        let (upper, lower) = epoll_fds[6..].split_at(3);
+        for part in [upper, lower] {
+            add(part[0], part[1])?;
+            add(part[1], part[2])?;
+        }
+        ensure_ord!(add(upper[2], lower[0]), ==, Err(Errno::ELOOP));
+        ensure_ord!(add(upper[2], lower[1]), ==, Ok(()));
+
+        Ok(())
+    })
+}
+
+/// Test that when several epolls monitor a file with `EPOLLEXCLUSIVE`, only one of them is woken
+/// when the file becomes ready.
+fn test_exclusive_wakeup() -> anyhow::Result<()> {
+    let (read_fd, write_fd) = unistd::pipe()?;
+    let epoll_fds = [
+        epoll::epoll_create()?,
+        epoll::epoll_create()?,
+        epoll::epoll_create()?,
+    ];
+
+    test_utils::run_and_close_fds(&[&epoll_fds[..], &[read_fd, write_fd]].concat(), || {
+        for epoll_fd in epoll_fds {
+            let mut event = epoll::EpollEvent::new(
+                EpollFlags::EPOLLIN | EpollFlags::EPOLLEXCLUSIVE,
+                read_fd as u64,
+            );
+            epoll::epoll_ctl(
+                epoll_fd,
+                epoll::EpollOp::EpollCtlAdd,
+                read_fd,
+                Some(&mut event),
+            )?;
+        }
+
+        let timeout = Duration::from_millis(100);
+
+        let threads = epoll_fds.map(|epoll_fd| {
+            std::thread::spawn(move || do_epoll_wait(epoll_fd, timeout, /* do_read= */ false))
+        });
+
+        // Wait for the waiters to block.
+        std::thread::sleep(timeout / 2);
+
+        // Make the read-end readable.
+        unistd::write(write_fd, &[0])?;
+
+        let mut results = threads.map(|t| t.join().unwrap());
+        results.sort_by(|lhs, rhs| lhs.events.len().cmp(&rhs.events.len()));
+
+        // Two threads should have timed out with no events received.
+        for res in &results[..2] {
+            ensure_ord!(res.epoll_res, ==, Ok(0));
+            ensure_ord!(res.duration, >=, timeout);
+        }
+
+        // The other should have gotten a single event.
+        ensure_ord!(results[2].epoll_res, ==, Ok(1));
+        ensure_ord!(results[2].duration, <, timeout);
+        ensure_ord!(
+            results[2].events[0],
+            ==,
+            epoll::EpollEvent::new(EpollFlags::EPOLLIN, read_fd as u64)
+        );
+
+        Ok(())
+    })
+}
+
+/// Test that after the epoll woken for an `EPOLLEXCLUSIVE` entry removes the entry, the next
+/// write to the file wakes another epoll.
+fn test_exclusive_wakeup_removed() -> anyhow::Result<()> {
+    let (read_fd, write_fd) = unistd::pipe()?;
+    let epoll_fds = [epoll::epoll_create()?, epoll::epoll_create()?];
+
+    test_utils::run_and_close_fds(&[&epoll_fds[..], &[read_fd, write_fd]].concat(), || {
+        for epoll_fd in epoll_fds {
+            let mut event = epoll::EpollEvent::new(
+                EpollFlags::EPOLLIN | EpollFlags::EPOLLEXCLUSIVE,
+                read_fd as u64,
+            );
+            epoll::epoll_ctl(
+                epoll_fd,
+                epoll::EpollOp::EpollCtlAdd,
+                read_fd,
+                Some(&mut event),
+            )?;
+        }
+
+        let timeout = Duration::from_millis(500);
+
+        // each waiter removes its entry once it's woken
+        let threads = epoll_fds.map(|epoll_fd| {
+            std::thread::spawn(move || {
+                let res = do_epoll_wait(epoll_fd, timeout, /* do_read= */ false);
+                if res.epoll_res == Ok(1) {
+                    epoll::epoll_ctl(epoll_fd, epoll::EpollOp::EpollCtlDel, read_fd, None).unwrap();
+                }
+                res
+            })
+        });
+
+        // Wait for the waiters to block, then wake one of them.
+        std::thread::sleep(Duration::from_millis(100));
+        unistd::write(write_fd, &[0])?;
+
+        // Give the woken waiter time to remove its entry, then wake the other.
+        std::thread::sleep(Duration::from_millis(100));
+        unistd::write(write_fd, &[0])?;
+
+        let results = threads.map(|t| t.join().unwrap());
+
+        for res in &results {
+            ensure_ord!(res.epoll_res, ==, Ok(1));
+            ensure_ord!(res.duration, <, timeout);
+            ensure_ord!(
+                res.events[0],
+                ==,
+                epoll::EpollEvent::new(EpollFlags::EPOLLIN, read_fd as u64)
+            );
+        }
+
+        Ok(())
+    })
+}
+
+/// Test the `epoll_ctl` operations and flags that aren't allowed with `EPOLLEXCLUSIVE`.
+fn test_exclusive_ctl_invalid() -> anyhow::Result<()> {
+    let (read_fd, write_fd) = unistd::pipe()?;
+    let epoll_fd = epoll::epoll_create()?;
+    let other_epoll_fd = epoll::epoll_create()?;
+
+    test_utils::run_and_close_fds(&[epoll_fd, other_epoll_fd, read_fd, write_fd], || {
+        let ctl = |op, fd, flags| {
+            let mut event = epoll::EpollEvent::new(flags, 0);
+            epoll::epoll_ctl(epoll_fd, op, fd, Some(&mut event))
+        };
+        let exclusive_in = EpollFlags::EPOLLIN | EpollFlags::EPOLLEXCLUSIVE;
+
+        // not allowed with one-shot entries or with epolls
+        ensure_ord!(
+            ctl(
+                epoll::EpollOp::EpollCtlAdd,
+                read_fd,
+                exclusive_in | EpollFlags::EPOLLONESHOT
+            ),
+            ==,
+            Err(Errno::EINVAL)
+        );
+        ensure_ord!(
+            ctl(epoll::EpollOp::EpollCtlAdd, other_epoll_fd, exclusive_in),
+            ==,
+            Err(Errno::EINVAL)
+        );
+
+        // not allowed when modifying, even if the entry doesn't exist
+        ensure_ord!(
+            ctl(epoll::EpollOp::EpollCtlMod, read_fd, exclusive_in),
+            ==,
+            Err(Errno::EINVAL)
+        );
+
+        // an exclusive entry can't be modified, but can be removed
+        ctl(epoll::EpollOp::EpollCtlAdd, read_fd, exclusive_in)?;
+        ensure_ord!(
+            ctl(epoll::EpollOp::EpollCtlMod, read_fd, EpollFlags::EPOLLIN),
+            ==,
+            Err(Errno::EINVAL)
+        );
+        ctl(epoll::EpollOp::EpollCtlDel, read_fd, EpollFlags::empty())?;
+
+        Ok(())
+    })
+}
+
+/// Test that a stream socket reports `EPOLLRDHUP` alongside `EPOLLIN` after its peer stops
+/// writing, and that the data buffered before then can still be read. If `close_peer` is false,
+/// the peer shuts down writing with `shutdown(SHUT_WR)`, otherwise it closes the socket.
+fn test_rdhup(init_method: SocketInitMethod, close_peer: bool) -> anyhow::Result<()> {
+    let (fd_peer, fd) = socket_init_helper(
+        init_method,
+        libc::SOCK_STREAM,
+        libc::SOCK_NONBLOCK,
+        /* bind_client= */ false,
+    );
+    let epoll_fd = epoll::epoll_create()?;
+
+    let fds_to_close = if close_peer {
+        vec![epoll_fd, fd]
+    } else {
+        vec![epoll_fd, fd, fd_peer]
+    };
+
+    test_utils::run_and_close_fds(&fds_to_close, || {
+        let mut event =
+            epoll::EpollEvent::new(EpollFlags::EPOLLIN | EpollFlags::EPOLLRDHUP, fd as u64);
+        epoll::epoll_ctl(epoll_fd, epoll::EpollOp::EpollCtlAdd, fd, Some(&mut event))?;
+
+        let rdhup_events = || -> anyhow::Result<EpollFlags> {
+            let res = do_epoll_wait(epoll_fd, Duration::ZERO, /* do_read= */ false);
+            ensure_ord!(res.epoll_res, ==, Ok(1));
+            Ok(res.events[0].events() & (EpollFlags::EPOLLIN | EpollFlags::EPOLLRDHUP))
+        };
+
+        let data = [1, 2, 3];
+        unistd::write(fd_peer, &data)?;
+
+        // only the data has been sent so far
+        std::thread::sleep(Duration::from_millis(100));
+        ensure_ord!(rdhup_events()?, ==, EpollFlags::EPOLLIN);
+
+        if close_peer {
+            unistd::close(fd_peer)?;
+        } else {
+            nix::sys::socket::shutdown(fd_peer, Shutdown::Write)?;
+        }
+
+        // wait for the peer's FIN to arrive
+        std::thread::sleep(Duration::from_millis(100));
+        ensure_ord!(
+            rdhup_events()?,
+            ==,
+            EpollFlags::EPOLLIN | EpollFlags::EPOLLRDHUP
+        );
+
+        // poll should report it too
+        let mut poll_fds = [PollFd::new(fd, PollFlags::POLLIN | PollFlags::POLLRDHUP)];
+        ensure_ord!(nix::poll::poll(&mut poll_fds, 0), ==, Ok(1));
+        ensure_ord!(
+            poll_fds[0].revents().map(|x| x & (PollFlags::POLLIN | PollFlags::POLLRDHUP)),
+            ==,
+            Some(PollFlags::POLLIN | PollFlags::POLLRDHUP)
+        );
+
+        // the buffered data can still be read
+        let mut buf = [0u8; 10];
+        ensure_ord!(unistd::read(fd, &mut buf), ==, Ok(data.len()));
+        ensure_ord!(&buf[..data.len()], ==, &data[..]);
+
+        // the socket is still reported after the data has been read, and now reads EOF
+        ensure_ord!(
+            rdhup_events()?,
+            ==,
+            EpollFlags::EPOLLIN | EpollFlags::EPOLLRDHUP
+        );
+        ensure_ord!(unistd::read(fd, &mut buf), ==, Ok(0));
+
+        Ok(())
+    })
+}
+
 fn main() -> anyhow::Result<()> {
     // should we restrict the tests we run?
     let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
@@ -351,12 +808,66 @@ fn main() -> anyhow::Result<()> {
             test_threads_level_with_early_read,
             set![TestEnvironment::Shadow],
         ),
+        ShadowTest::new(
+            "threads-oneshot-level",
+            test_threads_oneshot_level,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "threads-oneshot-edge",
+            test_threads_oneshot_edge,
+            all_envs.clone(),
+        ),
         ShadowTest::new(
             "test_wait_negative_timeout",
             test_wait_negative_timeout,
             all_envs.clone(),
         ),
-        ShadowTest::new("test_ctl_invalid_op", test_ctl_invalid_op, all_envs),
+        ShadowTest::new("test_ctl_invalid_op", test_ctl_invalid_op, all_envs.clone()),
+        ShadowTest::new("test_nested_wakeup", test_nested_wakeup, all_envs.clone()),
+        ShadowTest::new("test_nested_loop", test_nested_loop, all_envs.clone()),
+        // older linux kernels don't count the epolls above the epoll being added to
+        ShadowTest::new(
+            "test_nested_depth",
+            test_nested_depth,
+            set![TestEnvironment::Shadow],
+        ),
+        ShadowTest::new(
+            "test_exclusive_wakeup",
+            test_exclusive_wakeup,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "test_exclusive_wakeup_removed",
+            test_exclusive_wakeup_removed,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "test_exclusive_ctl_invalid",
+            test_exclusive_ctl_invalid,
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "test_rdhup_tcp_shutdown",
+            || test_rdhup(SocketInitMethod::Inet, /* close_peer= */ false),
+            all_envs.clone(),
+        ),
+        ShadowTest::new(
+            "test_rdhup_tcp_close",
+            || test_rdhup(SocketInitMethod::Inet, /* close_peer= */ true),
+            all_envs.clone(),
+        ),
+        // shadow doesn't support shutdown() for unix sockets
+        ShadowTest::new(
+            "test_rdhup_unix_shutdown",
+            || test_rdhup(SocketInitMethod::Unix, /* close_peer= */ false),
+            set![TestEnvironment::Libc],
+        ),
+        ShadowTest::new(
+            "test_rdhup_unix_close",
+            || test_rdhup(SocketInitMethod::Unix, /* close_peer= */ true),
+            all_envs,
+        ),
     ];
 
     if filter_shadow_passing {
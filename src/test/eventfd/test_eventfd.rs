@@ -53,6 +53,16 @@ fn main() -> Result<(), String> {
             test_eventfd_read_write_semaphore_nonblock,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_eventfd_readable",
+            || test_eventfd_readable(/* semaphore = */ false),
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_eventfd_readable_semaphore",
+            || test_eventfd_readable(/* semaphore = */ true),
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
 
     if filter_shadow_passing {
@@ -408,3 +418,54 @@ fn test_eventfd_read_write_semaphore_nonblock() -> Result<(), String> {
         Ok(())
     })
 }
+
+fn check_readable(efd: RawFd, expected: bool) -> Result<(), String> {
+    test_utils::result_assert_eq(
+        test_utils::is_readable(efd, 0).map_err(|e| e.to_string())?,
+        expected,
+        "Unexpected readability",
+    )
+}
+
+fn test_eventfd_readable(semaphore: bool) -> Result<(), String> {
+    let flag = if semaphore {
+        EfdFlags::EFD_NONBLOCK | EfdFlags::EFD_SEMAPHORE
+    } else {
+        EfdFlags::EFD_NONBLOCK
+    };
+    let efd: RawFd = call_eventfd(3, flag)?;
+
+    test_utils::run_and_close_fds(&[efd], || {
+        check_readable(efd, true)?;
+
+        if semaphore {
+            // each read decrements the counter by 1, and the eventfd stays readable until the
+            // counter reaches 0
+            for remaining in (0..3).rev() {
+                check_read_success(efd, 1)?;
+                check_readable(efd, remaining > 0)?;
+            }
+        } else {
+            // a read returns the full counter and resets it to 0
+            check_read_success(efd, 3)?;
+            check_readable(efd, false)?;
+        }
+        check_read_eagain(efd)?;
+
+        // writing makes the eventfd readable again
+        check_write_success(efd, 2)?;
+        check_readable(efd, true)?;
+
+        if semaphore {
+            check_read_success(efd, 1)?;
+            check_readable(efd, true)?;
+            check_read_success(efd, 1)?;
+        } else {
+            check_read_success(efd, 2)?;
+        }
+        check_readable(efd, false)?;
+        check_read_eagain(efd)?;
+
+        Ok(())
+    })
+}
@@ -0,0 +1,112 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for shadow's custom `shadow_query_feature` syscall, which lets a program check which
+//! shadow features are available. These tests only run in shadow since the syscall doesn't exist
+//! on linux.
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+/// Shadow's custom syscall number for `SYS_shadow_query_feature`.
+const SYS_SHADOW_QUERY_FEATURE: libc::c_long = 1007;
+
+/// The values of `ShadowFeatureStatus`.
+const FEATURE_UNSUPPORTED: libc::c_long = 0;
+const FEATURE_DISABLED: libc::c_long = 1;
+const FEATURE_ENABLED: libc::c_long = 2;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let shadow_only = set![TestEnv::Shadow];
+    vec![
+        test_utils::ShadowTest::new(
+            "test_enabled_features",
+            test_enabled_features,
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new(
+            "test_disabled_features",
+            test_disabled_features,
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new(
+            "test_unknown_features",
+            test_unknown_features,
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new("test_bad_pointer", test_bad_pointer, shadow_only),
+    ]
+}
+
+fn query_feature(name: &[u8]) -> Result<libc::c_long, nix::errno::Errno> {
+    let rv = unsafe { libc::syscall(SYS_SHADOW_QUERY_FEATURE, name.as_ptr(), name.len()) };
+    nix::errno::Errno::result(rv)
+}
+
+fn check_status(name: &[u8], expected: libc::c_long) -> Result<(), String> {
+    let status = query_feature(name).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(
+        status,
+        expected,
+        &format!("Unexpected status for {:?}", String::from_utf8_lossy(name)),
+    )
+}
+
+/// Test features that are always available, or that the config file enables.
+fn test_enabled_features() -> Result<(), String> {
+    check_status(b"packet_sockets", FEATURE_ENABLED)?;
+    check_status(b"tcp_cc_state", FEATURE_ENABLED)?;
+    // enabled in the config file
+    check_status(b"pcap", FEATURE_ENABLED)
+}
+
+/// Test features that are supported but disabled by default.
+fn test_disabled_features() -> Result<(), String> {
+    check_status(b"deterministic_aslr", FEATURE_DISABLED)
+}
+
+/// Test that unknown features are unsupported.
+fn test_unknown_features() -> Result<(), String> {
+    check_status(b"", FEATURE_UNSUPPORTED)?;
+    check_status(b"no_such_feature", FEATURE_UNSUPPORTED)?;
+    // names are case-sensitive
+    check_status(b"PCAP", FEATURE_UNSUPPORTED)?;
+    // a known name with trailing bytes
+    check_status(b"pcap\0", FEATURE_UNSUPPORTED)?;
+    check_status(&[0xff, 0xfe], FEATURE_UNSUPPORTED)?;
+    check_status(&[b'a'; 1000], FEATURE_UNSUPPORTED)
+}
+
+/// Test that a bad name pointer returns `EFAULT`.
+fn test_bad_pointer() -> Result<(), String> {
+    let rv = unsafe { libc::syscall(SYS_SHADOW_QUERY_FEATURE, std::ptr::null::<u8>(), 4) };
+    test_utils::result_assert_eq(rv, -1, "Expected an error")?;
+    test_utils::result_assert_eq(
+        nix::errno::Errno::last(),
+        nix::errno::Errno::EFAULT,
+        "Unexpected errno",
+    )
+}
@@ -10,11 +10,16 @@
 #include <fcntl.h>
 #include <glib.h>
 #include <libgen.h>
+#include <limits.h>
+#include <poll.h>
+#include <pthread.h>
+#include <stdbool.h>
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
 #include <sys/ioctl.h>
 #include <sys/stat.h>
+#include <sys/syscall.h>
 #include <sys/types.h>
 #include <sys/uio.h>
 #include <termios.h>
@@ -506,6 +511,118 @@ static void _test_preadv2() {
     assert_nonneg_errno(close(fd));
 }
 
+static void _test_iov_validation() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    int fd, rv;
+
+    char buf[] = "test iov validation";
+
+    struct iovec iov[IOV_MAX + 1];
+    for (int i = 0; i < IOV_MAX + 1; i++) {
+        iov[i].iov_base = buf;
+        iov[i].iov_len = 1;
+    }
+
+    assert_nonneg_errno(fd = open(adf.name, O_RDWR));
+
+    // more than IOV_MAX iovecs should be an error
+    rv = writev(fd, iov, IOV_MAX + 1);
+    g_assert_cmpint(rv, ==, -1);
+    assert_errno_is(EINVAL);
+    rv = readv(fd, iov, IOV_MAX + 1);
+    g_assert_cmpint(rv, ==, -1);
+    assert_errno_is(EINVAL);
+
+    // a length that overflows an ssize_t should be an error
+    iov[1].iov_len = (size_t)SSIZE_MAX + 1;
+    rv = writev(fd, iov, 2);
+    g_assert_cmpint(rv, ==, -1);
+    assert_errno_is(EINVAL);
+    rv = readv(fd, iov, 2);
+    g_assert_cmpint(rv, ==, -1);
+    assert_errno_is(EINVAL);
+
+    // nothing should have been written
+    assert_nonneg_errno(rv = lseek(fd, 0, SEEK_END));
+    g_assert_cmpint(rv, ==, 0);
+
+    // zero iovecs is a zero-length operation, even with a NULL iovec pointer
+    assert_nonneg_errno(rv = writev(fd, NULL, 0));
+    g_assert_cmpint(rv, ==, 0);
+    assert_nonneg_errno(rv = readv(fd, NULL, 0));
+    g_assert_cmpint(rv, ==, 0);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_pwritev_writev_offsets() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    int fd, rv;
+
+    char buf_1[] = "ab";
+    char buf_2[] = "XYZ";
+    char buf_3[] = "cd";
+    struct iovec iov[1] = {0};
+
+    assert_nonneg_errno(fd = open(adf.name, O_RDWR));
+
+    // writev should write at the position and advance it
+    iov[0] = (struct iovec){.iov_base = buf_1, .iov_len = 2};
+    assert_nonneg_errno(rv = writev(fd, iov, 1));
+    g_assert_cmpint(rv, ==, 2);
+    g_assert_cmpint(lseek(fd, 0, SEEK_CUR), ==, 2);
+
+    // pwritev should write at its offset without using or moving the position
+    iov[0] = (struct iovec){.iov_base = buf_2, .iov_len = 3};
+    assert_nonneg_errno(rv = pwritev(fd, iov, 1, 6));
+    g_assert_cmpint(rv, ==, 3);
+    g_assert_cmpint(lseek(fd, 0, SEEK_CUR), ==, 2);
+
+    // writev should continue from where the last writev ended
+    iov[0] = (struct iovec){.iov_base = buf_3, .iov_len = 2};
+    assert_nonneg_errno(rv = writev(fd, iov, 1));
+    g_assert_cmpint(rv, ==, 2);
+    g_assert_cmpint(lseek(fd, 0, SEEK_CUR), ==, 4);
+
+    // preadv shouldn't move the position either
+    char rbuf[9] = {0};
+    iov[0] = (struct iovec){.iov_base = rbuf, .iov_len = sizeof(rbuf)};
+    assert_nonneg_errno(rv = preadv(fd, iov, 1, 0));
+    g_assert_cmpint(rv, ==, sizeof(rbuf));
+    g_assert_cmpmem(rbuf, sizeof(rbuf), "abcd\0\0XYZ", sizeof(rbuf));
+    g_assert_cmpint(lseek(fd, 0, SEEK_CUR), ==, 4);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_pvec_pipe() {
+    int pipes[2] = {-1, -1};
+    char buf[] = "test";
+    struct iovec iov[1] = {{.iov_base = buf, .iov_len = sizeof(buf)}};
+
+    assert_nonneg_errno(pipe(pipes));
+
+    // positioned io isn't supported on pipes, even at offset 0 or with no iovecs
+    g_assert_cmpint(pwritev(pipes[1], iov, 1, 0), ==, -1);
+    assert_errno_is(ESPIPE);
+    g_assert_cmpint(pwritev(pipes[1], NULL, 0, 0), ==, -1);
+    assert_errno_is(ESPIPE);
+    g_assert_cmpint(preadv(pipes[0], iov, 1, 0), ==, -1);
+    assert_errno_is(ESPIPE);
+    g_assert_cmpint(preadv2(pipes[0], iov, 1, 0, 0), ==, -1);
+    assert_errno_is(ESPIPE);
+
+    // an offset of -1 uses the (non-existent) position instead
+    g_assert_cmpint(pwritev2(pipes[1], iov, 1, -1, 0), ==, sizeof(buf));
+
+    // nothing else was written to the pipe
+    char rbuf[2 * sizeof(buf)] = {0};
+    g_assert_cmpint(read(pipes[0], rbuf, sizeof(rbuf)), ==, sizeof(buf));
+
+    assert_nonneg_errno(close(pipes[0]));
+    assert_nonneg_errno(close(pipes[1]));
+}
+
 static void _test_lseek() {
     g_auto(AutoDeleteFile) adf = _create_auto_file();
     const char wbuf[] = "test file lseek";
@@ -689,6 +806,57 @@ static void _test_fstatat() {
     closedir(dir);
 }
 
+static void _test_faccessat() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    g_auto(AutoDeleteFile) dir = _create_auto_dir();
+
+    // mkstemp creates the file with mode 0600
+    assert_nonneg_errno(faccessat(AT_FDCWD, adf.name, F_OK, 0));
+    assert_nonneg_errno(faccessat(AT_FDCWD, adf.name, R_OK | W_OK, 0));
+    g_assert_cmpint(faccessat(AT_FDCWD, adf.name, X_OK, 0), ==, -1);
+    assert_errno_is(EACCES);
+    assert_nonneg_errno(faccessat(AT_FDCWD, adf.name, R_OK, AT_SYMLINK_NOFOLLOW));
+
+    // relative to a directory
+    g_assert_cmpint(faccessat(dir.fd, "nonexistent", F_OK, 0), ==, -1);
+    assert_errno_is(ENOENT);
+    assert_nonneg_errno(faccessat(dir.fd, ".", R_OK | X_OK, 0));
+
+    // check the descriptor itself
+    assert_nonneg_errno(faccessat(adf.fd, "", F_OK, AT_EMPTY_PATH));
+    assert_nonneg_errno(faccessat(adf.fd, "", R_OK | W_OK, AT_EMPTY_PATH));
+    g_assert_cmpint(faccessat(adf.fd, "", X_OK, AT_EMPTY_PATH), ==, -1);
+    assert_errno_is(EACCES);
+
+    // without AT_EMPTY_PATH the empty path doesn't exist
+    g_assert_cmpint(faccessat(adf.fd, "", F_OK, 0), ==, -1);
+    assert_errno_is(ENOENT);
+
+    // descriptors that aren't files
+    int pipes[2] = {-1, -1};
+    assert_nonneg_errno(pipe(pipes));
+    assert_nonneg_errno(faccessat(pipes[0], "", R_OK | W_OK, AT_EMPTY_PATH));
+    g_assert_cmpint(faccessat(pipes[0], "", X_OK, AT_EMPTY_PATH), ==, -1);
+    assert_errno_is(EACCES);
+    assert_nonneg_errno(close(pipes[0]));
+    assert_nonneg_errno(close(pipes[1]));
+
+    // closed descriptors
+    g_assert_cmpint(faccessat(pipes[0], "", F_OK, AT_EMPTY_PATH), ==, -1);
+    assert_errno_is(EBADF);
+    int fd;
+    assert_nonneg_errno(fd = open(adf.name, O_RDONLY));
+    assert_nonneg_errno(close(fd));
+    g_assert_cmpint(faccessat(fd, "", F_OK, AT_EMPTY_PATH), ==, -1);
+    assert_errno_is(EBADF);
+
+    // invalid arguments
+    g_assert_cmpint(faccessat(AT_FDCWD, adf.name, 0x100, 0), ==, -1);
+    assert_errno_is(EINVAL);
+    g_assert_cmpint(faccessat(AT_FDCWD, adf.name, F_OK, 0x10000000), ==, -1);
+    assert_errno_is(EINVAL);
+}
+
 static void _test_dir() {
     g_auto(AutoDeleteFile) adf = _create_auto_dir();
     DIR* dir;
@@ -713,6 +881,118 @@ static void _test_dir() {
     assert_nonneg_errno(rmdir(adf.name));
 }
 
+#define GETDENTS_NUM_FILES 64
+
+typedef struct {
+    int dirfd;
+    // the entries that the main thread saw before the unlink thread started
+    bool seen[GETDENTS_NUM_FILES];
+} GetdentsUnlinkArg;
+
+// Unlinks the odd-numbered files that the main thread hasn't seen yet.
+static void* _test_getdents_unlink_thread(void* void_arg) {
+    GetdentsUnlinkArg* arg = void_arg;
+    for (int i = 1; i < GETDENTS_NUM_FILES; i += 2) {
+        if (!arg->seen[i]) {
+            char name[16];
+            snprintf(name, sizeof(name), "f%d", i);
+            assert_nonneg_errno(unlinkat(arg->dirfd, name, 0));
+        }
+    }
+    return NULL;
+}
+
+// Reads a batch of entries, marking each one as seen. Returns the number of bytes read.
+static long _test_getdents_read(int dirfd, bool* seen) {
+    // small enough that it takes several calls to read the whole directory
+    char buf[256];
+    long nread = syscall(SYS_getdents64, dirfd, buf, sizeof(buf));
+    assert_nonneg_errno(nread);
+
+    for (long pos = 0; pos < nread;) {
+        struct dirent64* de = (struct dirent64*)(buf + pos);
+        pos += de->d_reclen;
+
+        if (strcmp(de->d_name, ".") == 0 || strcmp(de->d_name, "..") == 0) {
+            continue;
+        }
+
+        int i = -1;
+        g_assert_cmpint(sscanf(de->d_name, "f%d", &i), ==, 1);
+        g_assert_cmpint(i, >=, 0);
+        g_assert_cmpint(i, <, GETDENTS_NUM_FILES);
+        // no entry should be returned twice
+        g_assert_false(seen[i]);
+        seen[i] = true;
+    }
+
+    return nread;
+}
+
+// Entries may be unlinked while a directory is being read. Linux may or may not return the
+// removed entries, but every other entry is returned exactly once.
+static void _test_getdents_concurrent_unlink() {
+    g_auto(AutoDeleteFile) adf = _create_auto_dir();
+    GetdentsUnlinkArg arg = {.dirfd = adf.fd};
+
+    for (int i = 0; i < GETDENTS_NUM_FILES; i++) {
+        char name[16];
+        snprintf(name, sizeof(name), "f%d", i);
+        int fd;
+        assert_nonneg_errno(fd = openat(adf.fd, name, O_CREAT | O_WRONLY, 0600));
+        assert_nonneg_errno(close(fd));
+    }
+
+    g_assert_cmpint(_test_getdents_read(adf.fd, arg.seen), >, 0);
+
+    pthread_t thread;
+    assert_nonneg_errno(pthread_create(&thread, NULL, _test_getdents_unlink_thread, &arg));
+
+    // the unlink thread only reads `seen` for entries that we read before it started
+    bool seen[GETDENTS_NUM_FILES];
+    memcpy(seen, arg.seen, sizeof(seen));
+
+    // a directory of this size must be fully read well within this many calls
+    int calls = 0;
+    while (_test_getdents_read(adf.fd, seen) > 0) {
+        g_assert_cmpint(++calls, <, 10 * GETDENTS_NUM_FILES);
+    }
+
+    assert_nonneg_errno(pthread_join(thread, NULL));
+
+    // the entries that were never removed must all have been returned
+    for (int i = 0; i < GETDENTS_NUM_FILES; i += 2) {
+        g_assert_true(seen[i]);
+    }
+
+    // reading again after the end returns nothing
+    g_assert_cmpint(_test_getdents_read(adf.fd, seen), ==, 0);
+
+    // clean up so that the directory can be removed
+    for (int i = 0; i < GETDENTS_NUM_FILES; i++) {
+        char name[16];
+        snprintf(name, sizeof(name), "f%d", i);
+        if (unlinkat(adf.fd, name, 0) != 0) {
+            assert_errno_is(ENOENT);
+        }
+    }
+}
+
+static void _test_getdents_not_dir() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    char buf[256];
+
+    g_assert_cmpint(syscall(SYS_getdents64, adf.fd, buf, sizeof(buf)), ==, -1);
+    assert_errno_is(ENOTDIR);
+
+    // an in-memory file in shadow
+    int fd;
+    assert_nonneg_errno(fd = open("/sys/devices/system/cpu/online", O_RDONLY));
+    g_assert_cmpint(syscall(SYS_getdents64, fd, buf, sizeof(buf)), ==, -1);
+    assert_errno_is(ENOTDIR);
+    assert_nonneg_errno(close(fd));
+}
+
 static void _test_tmpfile() {
     const char wbuf[] = "test file tmpfile";
     char rbuf[sizeof(wbuf)] = {0};
@@ -766,6 +1046,254 @@ static void _test_dup() {
     assert_nonneg_errno(close(fd2));
 }
 
+static void _test_copy_file_range() {
+    g_auto(AutoDeleteFile) adf_in = _create_auto_file();
+    g_auto(AutoDeleteFile) adf_out = _create_auto_file();
+    const char wbuf[] = "0123456789";
+    char rbuf[sizeof(wbuf)] = {0};
+    int fd_in, fd_out;
+    ssize_t rv;
+    _set_contents(&adf_in, wbuf, sizeof(wbuf));
+    assert_nonneg_errno(fd_in = open(adf_in.name, O_RDONLY));
+    assert_nonneg_errno(fd_out = open(adf_out.name, O_WRONLY));
+
+    // copy part of the file using the file offsets
+    assert_nonneg_errno(rv = copy_file_range(fd_in, NULL, fd_out, NULL, 4, 0));
+    g_assert_cmpint(rv, ==, 4);
+
+    // both file offsets should be updated
+    assert_nonneg_errno(rv = lseek(fd_in, 0, SEEK_CUR));
+    g_assert_cmpint(rv, ==, 4);
+    assert_nonneg_errno(rv = lseek(fd_out, 0, SEEK_CUR));
+    g_assert_cmpint(rv, ==, 4);
+
+    // asking for more bytes than remain should be a partial copy
+    assert_nonneg_errno(rv = copy_file_range(fd_in, NULL, fd_out, NULL, 100, 0));
+    g_assert_cmpint(rv, ==, sizeof(wbuf) - 4);
+
+    // at the end of the input file, no bytes are copied
+    assert_nonneg_errno(rv = copy_file_range(fd_in, NULL, fd_out, NULL, 100, 0));
+    g_assert_cmpint(rv, ==, 0);
+
+    assert_nonneg_errno(close(fd_in));
+    assert_nonneg_errno(close(fd_out));
+
+    // the output file should be an exact copy
+    assert_nonneg_errno(fd_out = open(adf_out.name, O_RDONLY));
+    assert_nonneg_errno(rv = read(fd_out, rbuf, sizeof(rbuf)));
+    g_assert_cmpint(rv, ==, sizeof(wbuf));
+    g_assert_cmpstr(rbuf, ==, wbuf);
+    assert_nonneg_errno(close(fd_out));
+}
+
+static void _test_copy_file_range_offsets() {
+    g_auto(AutoDeleteFile) adf_in = _create_auto_file();
+    g_auto(AutoDeleteFile) adf_out = _create_auto_file();
+    const char wbuf[] = "0123456789";
+    char rbuf[sizeof(wbuf)] = {0};
+    int fd_in, fd_out;
+    ssize_t rv;
+    _set_contents(&adf_in, wbuf, sizeof(wbuf));
+    _set_contents(&adf_out, "abcdefghij", sizeof(wbuf));
+    assert_nonneg_errno(fd_in = open(adf_in.name, O_RDONLY));
+    assert_nonneg_errno(fd_out = open(adf_out.name, O_RDWR));
+
+    // copy "234" over "fgh"
+    loff_t off_in = 2;
+    loff_t off_out = 5;
+    assert_nonneg_errno(rv = copy_file_range(fd_in, &off_in, fd_out, &off_out, 3, 0));
+    g_assert_cmpint(rv, ==, 3);
+
+    // the offset arguments should be updated
+    g_assert_cmpint(off_in, ==, 5);
+    g_assert_cmpint(off_out, ==, 8);
+
+    // the file offsets should not be changed
+    assert_nonneg_errno(rv = lseek(fd_in, 0, SEEK_CUR));
+    g_assert_cmpint(rv, ==, 0);
+    assert_nonneg_errno(rv = lseek(fd_out, 0, SEEK_CUR));
+    g_assert_cmpint(rv, ==, 0);
+
+    assert_nonneg_errno(rv = pread(fd_out, rbuf, sizeof(rbuf), 0));
+    g_assert_cmpint(rv, ==, sizeof(wbuf));
+    g_assert_cmpstr(rbuf, ==, "abcde234ij");
+
+    // mix an explicit input offset with the output file offset
+    off_in = 8;
+    assert_nonneg_errno(rv = copy_file_range(fd_in, &off_in, fd_out, NULL, 2, 0));
+    g_assert_cmpint(rv, ==, 2);
+    g_assert_cmpint(off_in, ==, 10);
+    assert_nonneg_errno(rv = lseek(fd_out, 0, SEEK_CUR));
+    g_assert_cmpint(rv, ==, 2);
+
+    memset(rbuf, 0, sizeof(rbuf));
+    assert_nonneg_errno(rv = pread(fd_out, rbuf, sizeof(rbuf), 0));
+    g_assert_cmpint(rv, ==, sizeof(wbuf));
+    g_assert_cmpstr(rbuf, ==, "89cde234ij");
+
+    // negative offsets are invalid
+    off_in = -1;
+    g_assert_cmpint(copy_file_range(fd_in, &off_in, fd_out, NULL, 1, 0), ==, -1);
+    assert_errno_is(EINVAL);
+
+    // flags must be 0
+    g_assert_cmpint(copy_file_range(fd_in, NULL, fd_out, NULL, 1, 1), ==, -1);
+    assert_errno_is(EINVAL);
+
+    assert_nonneg_errno(close(fd_in));
+    assert_nonneg_errno(close(fd_out));
+}
+
+static void _test_copy_file_range_large() {
+    g_auto(AutoDeleteFile) adf_in = _create_auto_file();
+    g_auto(AutoDeleteFile) adf_out = _create_auto_file();
+    const size_t len = 1024 * 1024;
+    int fd_in, fd_out;
+    ssize_t rv;
+
+    char* wbuf = malloc(len);
+    char* rbuf = calloc(len, 1);
+    g_assert_nonnull(wbuf);
+    g_assert_nonnull(rbuf);
+    for (size_t i = 0; i < len; i++) {
+        wbuf[i] = (char)(i % 251);
+    }
+
+    assert_nonneg_errno(rv = write(adf_in.fd, wbuf, len));
+    g_assert_cmpint(rv, ==, len);
+    assert_nonneg_errno(fd_in = open(adf_in.name, O_RDONLY));
+    assert_nonneg_errno(fd_out = open(adf_out.name, O_WRONLY));
+
+    // the kernel may copy fewer bytes than requested, so keep copying until EOF
+    size_t total = 0;
+    do {
+        assert_nonneg_errno(rv = copy_file_range(fd_in, NULL, fd_out, NULL, len, 0));
+        total += rv;
+    } while (rv > 0);
+    g_assert_cmpint(total, ==, len);
+
+    assert_nonneg_errno(close(fd_in));
+    assert_nonneg_errno(close(fd_out));
+
+    // the output file should be an exact copy
+    assert_nonneg_errno(fd_out = open(adf_out.name, O_RDONLY));
+    size_t bytes_read = 0;
+    do {
+        assert_nonneg_errno(rv = read(fd_out, rbuf + bytes_read, len - bytes_read));
+        bytes_read += rv;
+    } while (rv > 0 && bytes_read < len);
+    g_assert_cmpint(bytes_read, ==, len);
+    g_assert_cmpmem(rbuf, len, wbuf, len);
+    assert_nonneg_errno(close(fd_out));
+
+    free(wbuf);
+    free(rbuf);
+}
+
+static void _test_copy_file_range_same_file() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    const char wbuf[] = "0123456789";
+    char rbuf[2 * sizeof(wbuf)] = {0};
+    int fd;
+    ssize_t rv;
+    _set_contents(&adf, wbuf, sizeof(wbuf));
+    assert_nonneg_errno(fd = open(adf.name, O_RDWR));
+
+    // append a copy of the file to itself
+    loff_t off_in = 0;
+    loff_t off_out = sizeof(wbuf);
+    assert_nonneg_errno(rv = copy_file_range(fd, &off_in, fd, &off_out, sizeof(wbuf), 0));
+    g_assert_cmpint(rv, ==, sizeof(wbuf));
+    g_assert_cmpint(off_in, ==, sizeof(wbuf));
+    g_assert_cmpint(off_out, ==, 2 * sizeof(wbuf));
+
+    assert_nonneg_errno(rv = pread(fd, rbuf, sizeof(rbuf), 0));
+    g_assert_cmpint(rv, ==, sizeof(rbuf));
+    g_assert_cmpmem(rbuf, sizeof(wbuf), wbuf, sizeof(wbuf));
+    g_assert_cmpmem(rbuf + sizeof(wbuf), sizeof(wbuf), wbuf, sizeof(wbuf));
+
+    // overlapping ranges within the same file are invalid
+    off_in = 0;
+    off_out = 1;
+    g_assert_cmpint(copy_file_range(fd, &off_in, fd, &off_out, 4, 0), ==, -1);
+    assert_errno_is(EINVAL);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_copy_file_range_pipe() {
+    g_auto(AutoDeleteFile) adf = _create_auto_file();
+    int pipes[2] = {-1, -1};
+    int fd;
+
+    assert_nonneg_errno(pipe(pipes));
+    assert_nonneg_errno(fd = open(adf.name, O_RDONLY));
+
+    // copy_file_range only supports regular files
+    g_assert_cmpint(copy_file_range(fd, NULL, pipes[1], NULL, 1, 0), ==, -1);
+    assert_errno_is(EINVAL);
+
+    assert_nonneg_errno(close(fd));
+    assert_nonneg_errno(close(pipes[0]));
+    assert_nonneg_errno(close(pipes[1]));
+}
+
+static void _test_dev_zero() {
+    char buf[4096];
+    memset(buf, 1, sizeof(buf));
+    int fd, rv;
+    assert_nonneg_errno(fd = open("/dev/zero", O_RDWR));
+
+    // Reads return all zeros.
+    assert_nonneg_errno(rv = read(fd, buf, sizeof(buf)));
+    g_assert_cmpint(rv, ==, sizeof(buf));
+    for (size_t i = 0; i < sizeof(buf); i++) {
+        g_assert_cmpint(buf[i], ==, 0);
+    }
+
+    // Writes succeed.
+    assert_nonneg_errno(rv = write(fd, buf, sizeof(buf)));
+    g_assert_cmpint(rv, ==, sizeof(buf));
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_dev_null() {
+    char buf[4096] = {0};
+    int fd, rv;
+    assert_nonneg_errno(fd = open("/dev/null", O_RDWR));
+
+    // Reads return EOF.
+    assert_nonneg_errno(rv = read(fd, buf, sizeof(buf)));
+    g_assert_cmpint(rv, ==, 0);
+
+    // Writes are discarded.
+    assert_nonneg_errno(rv = write(fd, buf, sizeof(buf)));
+    g_assert_cmpint(rv, ==, sizeof(buf));
+    assert_nonneg_errno(rv = read(fd, buf, sizeof(buf)));
+    g_assert_cmpint(rv, ==, 0);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_dev_poll() {
+    const char* paths[] = {"/dev/zero", "/dev/null", "/dev/urandom", "/dev/random"};
+
+    for (size_t i = 0; i < sizeof(paths) / sizeof(paths[0]); i++) {
+        struct pollfd pfd = {.events = POLLIN | POLLOUT};
+        assert_nonneg_errno(pfd.fd = open(paths[i], O_RDWR));
+
+        // These devices are always readable, so we shouldn't need to wait. (The random devices
+        // may not report POLLOUT once the kernel's entropy pool is initialized.)
+        int rv;
+        assert_nonneg_errno(rv = poll(&pfd, 1, 0));
+        g_assert_cmpint(rv, ==, 1);
+        g_assert_true(pfd.revents & POLLIN);
+
+        assert_nonneg_errno(close(pfd.fd));
+    }
+}
+
 static void _ioctl_check_enotty(int fd, int request) {
     struct termios term = {0};
     int rv = ioctl(fd, request, &term);
@@ -995,6 +1523,9 @@ int main(int argc, char* argv[]) {
     g_test_add_func("/file/readv", _test_readv);
     g_test_add_func("/file/preadv", _test_preadv);
     g_test_add_func("/file/preadv2", _test_preadv2);
+    g_test_add_func("/file/iov_validation", _test_iov_validation);
+    g_test_add_func("/file/pwritev_writev_offsets", _test_pwritev_writev_offsets);
+    g_test_add_func("/file/pvec_pipe", _test_pvec_pipe);
     g_test_add_func("/file/lseek", _test_lseek);
     g_test_add_func("/file/lseek_pipe", _test_lseek_pipe);
     g_test_add_func("/file/fopen", _test_fopen);
@@ -1009,10 +1540,21 @@ int main(int argc, char* argv[]) {
     g_test_add_func("/file/fstatat", _test_fstatat);
     g_test_add_func("/file/stat", _test_stat);
 
+    g_test_add_func("/file/faccessat", _test_faccessat);
     g_test_add_func("/file/dir", _test_dir);
+    g_test_add_func("/file/getdents_concurrent_unlink", _test_getdents_concurrent_unlink);
+    g_test_add_func("/file/getdents_not_dir", _test_getdents_not_dir);
     g_test_add_func("/file/tmpfile", _test_tmpfile);
     g_test_add_func("/file/dup", _test_dup);
+    g_test_add_func("/file/copy_file_range", _test_copy_file_range);
+    g_test_add_func("/file/copy_file_range_offsets", _test_copy_file_range_offsets);
+    g_test_add_func("/file/copy_file_range_large", _test_copy_file_range_large);
+    g_test_add_func("/file/copy_file_range_same_file", _test_copy_file_range_same_file);
+    g_test_add_func("/file/copy_file_range_pipe", _test_copy_file_range_pipe);
     g_test_add_func("/file/ioctl_tty", _test_ioctl_tty);
+    g_test_add_func("/file/dev_zero", _test_dev_zero);
+    g_test_add_func("/file/dev_null", _test_dev_null);
+    g_test_add_func("/file/dev_poll", _test_dev_poll);
 
     //    TODO: debug and fix iov test
     //    g_test_add_func("/file/iov", _test_iov);
@@ -12,9 +12,11 @@
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
+#include <sys/mman.h>
 #include <sys/syscall.h>
 #include <sys/time.h>
 #include <sys/types.h>
+#include <sys/wait.h>
 #include <unistd.h>
 
 #include "lib/logger/logger.h"
@@ -291,6 +293,257 @@ static void _futex_wait_bitset_test() {
     _wait_for_condition(&arg[4].child_finished);
 }
 
+typedef struct {
+    atomic_bool child_started;
+    atomic_bool child_finished;
+    uint32_t bitset;
+    atomic_int* futex;
+} FutexWaitSingleBitsetTestChildArg;
+
+static void* _futex_wait_single_bitset_test_child(void* void_arg) {
+    FutexWaitSingleBitsetTestChildArg* arg = void_arg;
+    atomic_store(&arg->child_started, true);
+    long rv =
+        syscall(SYS_futex, arg->futex, FUTEX_WAIT_BITSET, UNAVAILABLE, NULL, NULL, arg->bitset);
+    assert_nonneg_errno(rv);
+    atomic_store(&arg->child_finished, true);
+    return NULL;
+}
+
+// Wake waiters on `futex` matching `bitset`, retrying until one has been woken.
+static void _futex_wake_bitset_one(atomic_int* futex, uint32_t bitset) {
+    while (1) {
+        long woken = syscall(SYS_futex, futex, FUTEX_WAKE_BITSET, INT_MAX, NULL, NULL, bitset);
+        assert_nonneg_errno(woken);
+        if (woken == 1) {
+            break;
+        }
+        g_assert_cmpint(woken, ==, 0);
+        usleep(1);
+    }
+}
+
+static void _futex_wake_bitset_selective_test() {
+    atomic_int futex = UNAVAILABLE;
+    FutexWaitSingleBitsetTestChildArg arg[2];
+    pthread_t child[2];
+
+    for (int i = 0; i < 2; i++) {
+        arg[i] = (FutexWaitSingleBitsetTestChildArg){
+            .child_started = false,
+            .child_finished = false,
+            .bitset = 1 << i,
+            .futex = &futex,
+        };
+        assert_nonneg_errno(
+            pthread_create(&child[i], NULL, _futex_wait_single_bitset_test_child, &arg[i]));
+        _wait_for_condition(&arg[i].child_started);
+    }
+
+    // Wait a bit until they're (hopefully) both blocked on the futex.
+    usleep(10000);
+
+    // No waiter is waiting for this bit.
+    long woken = syscall(SYS_futex, &futex, FUTEX_WAKE_BITSET, INT_MAX, NULL, NULL, 1 << 5);
+    g_assert_cmpint(woken, ==, 0);
+
+    // Wake only the second waiter.
+    _futex_wake_bitset_one(&futex, 1 << 1);
+    _wait_for_condition(&arg[1].child_finished);
+    // FIXME: Like in `_futex_wait_bitset_test`, this is racy outside of shadow since the first
+    // waiter might not have gone to sleep yet.
+    g_assert_false(atomic_load(&arg[0].child_finished));
+
+    // A plain FUTEX_WAKE matches any bitset.
+    while (1) {
+        woken = syscall(SYS_futex, &futex, FUTEX_WAKE, INT_MAX, NULL, NULL, 0);
+        assert_nonneg_errno(woken);
+        if (woken == 1) {
+            break;
+        }
+        g_assert_cmpint(woken, ==, 0);
+        usleep(1);
+    }
+    _wait_for_condition(&arg[0].child_finished);
+
+    for (int i = 0; i < 2; i++) {
+        assert_nonneg_errno(pthread_join(child[i], NULL));
+    }
+}
+
+static void _futex_bitset_invalid_test() {
+    int futex = 0;
+
+    // The bitset can't be zero.
+    g_assert_cmpint(
+        syscall(SYS_futex, &futex, FUTEX_WAIT_BITSET, futex, NULL, NULL, 0), ==, -1);
+    assert_errno_is(EINVAL);
+    g_assert_cmpint(syscall(SYS_futex, &futex, FUTEX_WAKE_BITSET, 1, NULL, NULL, 0), ==, -1);
+    assert_errno_is(EINVAL);
+
+    // An absolute timeout in the past expires immediately.
+    struct timespec t0;
+    assert_nonneg_errno(clock_gettime(CLOCK_MONOTONIC, &t0));
+    struct timespec timeout = {.tv_sec = t0.tv_sec - 1, .tv_nsec = t0.tv_nsec};
+    g_assert_cmpint(syscall(SYS_futex, &futex, FUTEX_WAIT_BITSET, futex, &timeout, NULL,
+                            FUTEX_BITSET_MATCH_ANY),
+                    ==, -1);
+    assert_errno_is(ETIMEDOUT);
+}
+
+#define REQUEUE_NUM_THREADS 5
+
+typedef struct {
+    // the condition variable's futex word; 0 until it's signaled
+    atomic_int cond;
+    // 0 if unlocked, 1 if locked, and 2 if locked and there may be waiters
+    atomic_int mutex;
+    atomic_int num_started;
+    atomic_int num_returned;
+    atomic_int num_inside;
+    // only modified while holding the mutex
+    int counter;
+} FutexRequeueTestArg;
+
+static void _futex_requeue_test_lock(atomic_int* mutex) {
+    int val = 0;
+    if (atomic_compare_exchange_strong(mutex, &val, 1)) {
+        return;
+    }
+    while (atomic_exchange(mutex, 2) != 0) {
+        long rv = syscall(SYS_futex, mutex, FUTEX_WAIT, 2, NULL, NULL, 0);
+        if (rv != 0) {
+            assert_errno_is(EAGAIN);
+        }
+    }
+}
+
+static void _futex_requeue_test_unlock(atomic_int* mutex) {
+    if (atomic_exchange(mutex, 0) == 2) {
+        assert_nonneg_errno(syscall(SYS_futex, mutex, FUTEX_WAKE, 1, NULL, NULL, 0));
+    }
+}
+
+static void* _futex_requeue_test_child(void* void_arg) {
+    FutexRequeueTestArg* arg = void_arg;
+    atomic_fetch_add(&arg->num_started, 1);
+
+    // wait on the condition variable; after being requeued, we're woken by a mutex unlock
+    while (atomic_load(&arg->cond) == 0) {
+        long rv = syscall(SYS_futex, &arg->cond, FUTEX_WAIT, 0, NULL, NULL, 0);
+        if (rv != 0) {
+            assert_errno_is(EAGAIN);
+        }
+    }
+    atomic_fetch_add(&arg->num_returned, 1);
+
+    _futex_requeue_test_lock(&arg->mutex);
+    g_assert_cmpint(atomic_fetch_add(&arg->num_inside, 1), ==, 0);
+    int counter = arg->counter;
+    // hold the lock for a while so that the others have to wait
+    usleep(100);
+    arg->counter = counter + 1;
+    g_assert_cmpint(atomic_fetch_sub(&arg->num_inside, 1), ==, 1);
+    _futex_requeue_test_unlock(&arg->mutex);
+
+    return NULL;
+}
+
+// Models a condition variable broadcast, which wakes one waiter and requeues the rest onto the
+// mutex rather than waking all of them at once.
+static void _futex_cmp_requeue_test() {
+    FutexRequeueTestArg arg = {
+        .cond = 0, .mutex = 0, .num_started = 0, .num_returned = 0, .num_inside = 0, .counter = 0};
+
+    pthread_t threads[REQUEUE_NUM_THREADS];
+    for (int i = 0; i < REQUEUE_NUM_THREADS; i++) {
+        assert_nonneg_errno(pthread_create(&threads[i], NULL, _futex_requeue_test_child, &arg));
+    }
+    while (atomic_load(&arg.num_started) < REQUEUE_NUM_THREADS) {
+        usleep(1);
+    }
+
+    // Wait a bit until they're (hopefully) all blocked on the condition variable.
+    usleep(10000);
+
+    _futex_requeue_test_lock(&arg.mutex);
+    // The requeued waiters will be woken by the unlock.
+    atomic_store(&arg.mutex, 2);
+    atomic_store(&arg.cond, 1);
+
+    // The futex word doesn't have the expected value.
+    g_assert_cmpint(
+        syscall(SYS_futex, &arg.cond, FUTEX_CMP_REQUEUE, 1, INT_MAX, &arg.mutex, 0), ==, -1);
+    assert_errno_is(EAGAIN);
+
+    // FUTEX_CMP_REQUEUE returns the number of woken and requeued waiters.
+    // FIXME: Like in `_futex_wait_bitset_test`, this is racy outside of shadow since the waiters
+    // might not have gone to sleep yet.
+    long rv = syscall(SYS_futex, &arg.cond, FUTEX_CMP_REQUEUE, 1, INT_MAX, &arg.mutex, 1);
+    g_assert_cmpint(rv, ==, REQUEUE_NUM_THREADS);
+
+    // Only the woken waiter returns; the requeued waiters are now waiting on the mutex.
+    usleep(10000);
+    g_assert_cmpint(atomic_load(&arg.num_returned), ==, 1);
+
+    // Nothing is left waiting on the condition variable.
+    g_assert_cmpint(
+        syscall(SYS_futex, &arg.cond, FUTEX_CMP_REQUEUE, INT_MAX, INT_MAX, &arg.mutex, 1), ==, 0);
+
+    // Each unlock hands the mutex to one waiter at a time.
+    _futex_requeue_test_unlock(&arg.mutex);
+
+    for (int i = 0; i < REQUEUE_NUM_THREADS; i++) {
+        assert_nonneg_errno(pthread_join(threads[i], NULL));
+    }
+
+    g_assert_cmpint(arg.num_returned, ==, REQUEUE_NUM_THREADS);
+    g_assert_cmpint(arg.counter, ==, REQUEUE_NUM_THREADS);
+}
+
+static void* _futex_requeue_wait_child(void* void_arg) {
+    FutexWaitTestChildArg* arg = void_arg;
+    atomic_store(&arg->child_started, true);
+    long rv = syscall(SYS_futex, &arg->futex, FUTEX_WAIT, UNAVAILABLE, NULL, NULL, 0);
+    assert_nonneg_errno(rv);
+    atomic_store(&arg->child_finished, true);
+    return NULL;
+}
+
+static void _futex_requeue_test() {
+    FutexWaitTestChildArg arg = {
+        .futex = UNAVAILABLE, .child_started = false, .child_finished = false};
+    atomic_int futex2 = UNAVAILABLE;
+
+    // Negative counts are invalid.
+    g_assert_cmpint(syscall(SYS_futex, &arg.futex, FUTEX_REQUEUE, -1, 0, &futex2, 0), ==, -1);
+    assert_errno_is(EINVAL);
+    g_assert_cmpint(syscall(SYS_futex, &arg.futex, FUTEX_REQUEUE, 0, -1, &futex2, 0), ==, -1);
+    assert_errno_is(EINVAL);
+
+    // Nobody is waiting.
+    g_assert_cmpint(syscall(SYS_futex, &arg.futex, FUTEX_REQUEUE, 1, 1, &futex2, 0), ==, 0);
+
+    pthread_t child;
+    assert_nonneg_errno(pthread_create(&child, NULL, _futex_requeue_wait_child, &arg));
+    _wait_for_condition(&arg.child_started);
+    usleep(10000);
+
+    // FUTEX_REQUEUE only returns the number of woken waiters, so a requeue returns 0.
+    // FIXME: racy outside of shadow, as above.
+    g_assert_cmpint(syscall(SYS_futex, &arg.futex, FUTEX_REQUEUE, 0, 1, &futex2, 0), ==, 0);
+
+    // The waiter is no longer on the first futex.
+    g_assert_cmpint(syscall(SYS_futex, &arg.futex, FUTEX_WAKE, 1, NULL, NULL, 0), ==, 0);
+    usleep(10000);
+    g_assert_false(atomic_load(&arg.child_finished));
+
+    // Wake it from the second futex.
+    g_assert_cmpint(syscall(SYS_futex, &futex2, FUTEX_WAKE, 1, NULL, NULL, 0), ==, 1);
+    _wait_for_condition(&arg.child_finished);
+    assert_nonneg_errno(pthread_join(child, NULL));
+}
+
 // Note: this test roughly follows the example at the end of `man 2 futex`
 
 #define PTR_TO_INT(p) ((int)(long)(p))
@@ -396,6 +649,310 @@ static void _futex_stress_test() {
     g_assert_cmpint(PTR_TO_INT(aux_result), ==, 0);
 }
 
+static long _futex_pi_op(atomic_int* word, int op, const struct timespec* timeout) {
+    return syscall(SYS_futex, word, op, 0, timeout, NULL, 0);
+}
+
+static void _futex_pi_ops_test() {
+    pid_t tid = (pid_t)syscall(SYS_gettid);
+    atomic_int word = 0;
+
+    // take the unowned lock; the futex word holds our tid
+    assert_nonneg_errno(_futex_pi_op(&word, FUTEX_TRYLOCK_PI, NULL));
+    g_assert_cmpint(atomic_load(&word), ==, tid);
+
+    // we already own it
+    g_assert_cmpint(_futex_pi_op(&word, FUTEX_TRYLOCK_PI, NULL), ==, -1);
+    assert_errno_is(EDEADLK);
+    g_assert_cmpint(_futex_pi_op(&word, FUTEX_LOCK_PI, NULL), ==, -1);
+    assert_errno_is(EDEADLK);
+
+    // no waiters, so unlocking clears the word
+    assert_nonneg_errno(_futex_pi_op(&word, FUTEX_UNLOCK_PI, NULL));
+    g_assert_cmpint(atomic_load(&word), ==, 0);
+
+    // we can't unlock a lock that we don't own
+    g_assert_cmpint(_futex_pi_op(&word, FUTEX_UNLOCK_PI, NULL), ==, -1);
+    assert_errno_is(EPERM);
+
+    // the owner-died bit is kept when taking the lock
+    atomic_store(&word, FUTEX_OWNER_DIED);
+    assert_nonneg_errno(_futex_pi_op(&word, FUTEX_LOCK_PI, NULL));
+    g_assert_cmpint(atomic_load(&word), ==, tid | FUTEX_OWNER_DIED);
+    assert_nonneg_errno(_futex_pi_op(&word, FUTEX_UNLOCK_PI, NULL));
+    g_assert_cmpint(atomic_load(&word), ==, 0);
+}
+
+typedef struct {
+    atomic_int* word;
+    pid_t owner;
+} FutexLockPiTimeoutChildArg;
+
+static void* _futex_lock_pi_timeout_test_child(void* void_arg) {
+    FutexLockPiTimeoutChildArg* arg = void_arg;
+
+    // the lock is owned by another thread
+    g_assert_cmpint(_futex_pi_op(arg->word, FUTEX_TRYLOCK_PI, NULL), ==, -1);
+    assert_errno_is(EAGAIN);
+
+    // FUTEX_LOCK_PI has an absolute CLOCK_REALTIME timeout
+    struct timespec t0;
+    if (clock_gettime(CLOCK_REALTIME, &t0) < 0) {
+        panic("clock_gettime: %s", strerror(errno));
+    }
+
+    // a timeout in the past expires without blocking, though the waiters bit is still set
+    struct timespec past = {.tv_sec = t0.tv_sec - 1, .tv_nsec = t0.tv_nsec};
+    g_assert_cmpint(_futex_pi_op(arg->word, FUTEX_LOCK_PI, &past), ==, -1);
+    assert_errno_is(ETIMEDOUT);
+    struct timespec t_past;
+    if (clock_gettime(CLOCK_REALTIME, &t_past) < 0) {
+        panic("clock_gettime: %s", strerror(errno));
+    }
+    g_assert_cmpfloat(timespec_to_double(&t_past) - timespec_to_double(&t0), <=, .1);
+    g_assert_cmphex((uint32_t)atomic_load(arg->word), ==, arg->owner | FUTEX_WAITERS);
+
+    struct timespec timeout = {.tv_sec = t0.tv_sec + 1, .tv_nsec = t0.tv_nsec};
+    g_assert_cmpint(_futex_pi_op(arg->word, FUTEX_LOCK_PI, &timeout), ==, -1);
+    assert_errno_is(ETIMEDOUT);
+
+    struct timespec t1;
+    if (clock_gettime(CLOCK_REALTIME, &t1) < 0) {
+        panic("clock_gettime: %s", strerror(errno));
+    }
+    double delta = timespec_to_double(&t1) - timespec_to_double(&timeout);
+    g_assert_cmpfloat(delta, <=, .1);
+    g_assert_cmpfloat(delta, >=, -.1);
+
+    // the owner is unchanged, and the waiters bit was set when we blocked
+    g_assert_cmphex((uint32_t)atomic_load(arg->word), ==, arg->owner | FUTEX_WAITERS);
+    return NULL;
+}
+
+static void _futex_lock_pi_timeout_test() {
+    atomic_int word = 0;
+    assert_nonneg_errno(_futex_pi_op(&word, FUTEX_LOCK_PI, NULL));
+
+    FutexLockPiTimeoutChildArg arg = {.word = &word, .owner = (pid_t)syscall(SYS_gettid)};
+    pthread_t child = {0};
+    assert_nonneg_errno(pthread_create(&child, NULL, _futex_lock_pi_timeout_test_child, &arg));
+    assert_nonneg_errno(pthread_join(child, NULL));
+
+    // the waiter gave up, so the unlock just clears the word
+    assert_nonneg_errno(_futex_pi_op(&word, FUTEX_UNLOCK_PI, NULL));
+    g_assert_cmpint(atomic_load(&word), ==, 0);
+}
+
+#define PI_MUTEX_NUM_THREADS 3
+#define PI_MUTEX_NUM_LOOPS 50
+
+typedef struct {
+    pthread_mutex_t mutex;
+    // number of threads in the critical section
+    atomic_int num_inside;
+    // only modified while holding the mutex
+    int counter;
+} FutexPiMutexTestArg;
+
+static void* _futex_pi_mutex_test_thread(void* void_arg) {
+    FutexPiMutexTestArg* arg = void_arg;
+
+    for (int i = 0; i < PI_MUTEX_NUM_LOOPS; i++) {
+        g_assert_cmpint(pthread_mutex_lock(&arg->mutex), ==, 0);
+
+        g_assert_cmpint(atomic_fetch_add(&arg->num_inside, 1), ==, 0);
+        int counter = arg->counter;
+        // hold the lock for a while so that the other threads block in FUTEX_LOCK_PI
+        usleep(100);
+        arg->counter = counter + 1;
+        g_assert_cmpint(atomic_fetch_sub(&arg->num_inside, 1), ==, 1);
+
+        g_assert_cmpint(pthread_mutex_unlock(&arg->mutex), ==, 0);
+    }
+
+    return NULL;
+}
+
+static void _futex_pi_mutex_test() {
+    FutexPiMutexTestArg arg = {.num_inside = 0, .counter = 0};
+
+    // glibc implements priority-inheritance mutexes with the PI futex operations
+    pthread_mutexattr_t attr;
+    g_assert_cmpint(pthread_mutexattr_init(&attr), ==, 0);
+    g_assert_cmpint(pthread_mutexattr_setprotocol(&attr, PTHREAD_PRIO_INHERIT), ==, 0);
+    g_assert_cmpint(pthread_mutex_init(&arg.mutex, &attr), ==, 0);
+    g_assert_cmpint(pthread_mutexattr_destroy(&attr), ==, 0);
+
+    pthread_t threads[PI_MUTEX_NUM_THREADS];
+    for (int i = 0; i < PI_MUTEX_NUM_THREADS; i++) {
+        assert_nonneg_errno(
+            pthread_create(&threads[i], NULL, _futex_pi_mutex_test_thread, &arg));
+    }
+    for (int i = 0; i < PI_MUTEX_NUM_THREADS; i++) {
+        assert_nonneg_errno(pthread_join(threads[i], NULL));
+    }
+
+    g_assert_cmpint(arg.counter, ==, PI_MUTEX_NUM_THREADS * PI_MUTEX_NUM_LOOPS);
+    g_assert_cmpint(pthread_mutex_destroy(&arg.mutex), ==, 0);
+}
+
+typedef struct {
+    pthread_mutex_t mutex;
+    atomic_bool child_locked;
+    // whether the child should give the parent time to block on the mutex before exiting
+    bool wait_for_parent;
+} FutexRobustTestArg;
+
+static void* _futex_robust_test_child(void* void_arg) {
+    FutexRobustTestArg* arg = void_arg;
+
+    g_assert_cmpint(pthread_mutex_lock(&arg->mutex), ==, 0);
+    atomic_store(&arg->child_locked, true);
+
+    if (arg->wait_for_parent) {
+        usleep(10000);
+    }
+
+    // exit while still holding the mutex
+    return NULL;
+}
+
+static void _futex_robust_test_helper(int protocol, bool block) {
+    FutexRobustTestArg arg = {.child_locked = false, .wait_for_parent = block};
+
+    // glibc registers robust mutexes in the thread's robust futex list
+    pthread_mutexattr_t attr;
+    g_assert_cmpint(pthread_mutexattr_init(&attr), ==, 0);
+    g_assert_cmpint(pthread_mutexattr_setrobust(&attr, PTHREAD_MUTEX_ROBUST), ==, 0);
+    g_assert_cmpint(pthread_mutexattr_setprotocol(&attr, protocol), ==, 0);
+    g_assert_cmpint(pthread_mutex_init(&arg.mutex, &attr), ==, 0);
+    g_assert_cmpint(pthread_mutexattr_destroy(&attr), ==, 0);
+
+    pthread_t thread;
+    assert_nonneg_errno(pthread_create(&thread, NULL, _futex_robust_test_child, &arg));
+    _wait_for_condition(&arg.child_locked);
+
+    if (!block) {
+        assert_nonneg_errno(pthread_join(thread, NULL));
+    }
+
+    // the owner died while holding the mutex, which should wake us if we're blocked
+    g_assert_cmpint(pthread_mutex_lock(&arg.mutex), ==, EOWNERDEAD);
+
+    if (block) {
+        assert_nonneg_errno(pthread_join(thread, NULL));
+    }
+
+    // once made consistent, the mutex can be used normally
+    g_assert_cmpint(pthread_mutex_consistent(&arg.mutex), ==, 0);
+    g_assert_cmpint(pthread_mutex_unlock(&arg.mutex), ==, 0);
+    g_assert_cmpint(pthread_mutex_lock(&arg.mutex), ==, 0);
+    g_assert_cmpint(pthread_mutex_unlock(&arg.mutex), ==, 0);
+    g_assert_cmpint(pthread_mutex_destroy(&arg.mutex), ==, 0);
+}
+
+static void _futex_robust_test() {
+    _futex_robust_test_helper(PTHREAD_PRIO_NONE, false);
+    _futex_robust_test_helper(PTHREAD_PRIO_NONE, true);
+}
+
+static void _futex_robust_pi_test() {
+    _futex_robust_test_helper(PTHREAD_PRIO_INHERIT, false);
+    _futex_robust_test_helper(PTHREAD_PRIO_INHERIT, true);
+}
+
+static void* _futex_robust_process_test_thread(void* void_arg) {
+    FutexRobustTestArg* arg = void_arg;
+
+    if (pthread_mutex_lock(&arg->mutex) != 0) {
+        abort();
+    }
+    atomic_store(&arg->child_locked, true);
+
+    // hold the mutex until the process exits
+    while (true) {
+        usleep(1000);
+    }
+    return NULL;
+}
+
+// Test a robust mutex in memory shared with a child process that exits while holding it. If
+// `exit_group` is true, another thread holds the mutex when the child calls `exit_group`.
+// Otherwise the child's only thread holds it when calling `exit`.
+static void _futex_robust_process_test_helper(bool exit_group) {
+    FutexRobustTestArg* arg =
+        mmap(NULL, sizeof(*arg), PROT_READ | PROT_WRITE, MAP_SHARED | MAP_ANONYMOUS, -1, 0);
+    assert_true_errno(arg != MAP_FAILED);
+    arg->child_locked = false;
+
+    pthread_mutexattr_t attr;
+    g_assert_cmpint(pthread_mutexattr_init(&attr), ==, 0);
+    g_assert_cmpint(pthread_mutexattr_setrobust(&attr, PTHREAD_MUTEX_ROBUST), ==, 0);
+    g_assert_cmpint(pthread_mutexattr_setpshared(&attr, PTHREAD_PROCESS_SHARED), ==, 0);
+    g_assert_cmpint(pthread_mutex_init(&arg->mutex, &attr), ==, 0);
+    g_assert_cmpint(pthread_mutexattr_destroy(&attr), ==, 0);
+
+    pid_t pid = fork();
+    assert_nonneg_errno(pid);
+
+    if (pid == 0) {
+        if (exit_group) {
+            pthread_t thread;
+            if (pthread_create(&thread, NULL, _futex_robust_process_test_thread, arg) != 0) {
+                abort();
+            }
+            while (!atomic_load(&arg->child_locked)) {
+                usleep(1000);
+            }
+            syscall(SYS_exit_group, 0);
+        } else {
+            if (pthread_mutex_lock(&arg->mutex) != 0) {
+                abort();
+            }
+            syscall(SYS_exit, 0);
+        }
+        abort();
+    }
+
+    int status = 0;
+    g_assert_cmpint(waitpid(pid, &status, 0), ==, pid);
+    g_assert_true(WIFEXITED(status));
+    g_assert_cmpint(WEXITSTATUS(status), ==, 0);
+
+    // the owner died while holding the mutex
+    g_assert_cmpint(pthread_mutex_lock(&arg->mutex), ==, EOWNERDEAD);
+    g_assert_cmpint(pthread_mutex_consistent(&arg->mutex), ==, 0);
+    g_assert_cmpint(pthread_mutex_unlock(&arg->mutex), ==, 0);
+    g_assert_cmpint(pthread_mutex_destroy(&arg->mutex), ==, 0);
+    assert_nonneg_errno(munmap(arg, sizeof(*arg)));
+}
+
+static void _futex_robust_process_test() {
+    _futex_robust_process_test_helper(false);
+    _futex_robust_process_test_helper(true);
+}
+
+static void _futex_robust_list_syscalls_test() {
+    struct robust_list_head head = {.list = {.next = &head.list}, .futex_offset = 0};
+
+    struct robust_list_head* orig_head = NULL;
+    size_t len = 0;
+    assert_nonneg_errno(syscall(SYS_get_robust_list, 0, &orig_head, &len));
+    g_assert_cmpint(len, ==, sizeof(head));
+
+    g_assert_cmpint(syscall(SYS_set_robust_list, &head, sizeof(head) + 1), ==, -1);
+    assert_errno_is(EINVAL);
+
+    assert_nonneg_errno(syscall(SYS_set_robust_list, &head, sizeof(head)));
+
+    struct robust_list_head* new_head = NULL;
+    assert_nonneg_errno(syscall(SYS_get_robust_list, 0, &new_head, &len));
+    g_assert_true(new_head == &head);
+
+    // restore glibc's list
+    assert_nonneg_errno(syscall(SYS_set_robust_list, orig_head, sizeof(head)));
+}
+
 int main(int argc, char** argv) {
     g_test_init(&argc, &argv, NULL);
     g_test_set_nonfatal_assertions();
@@ -407,11 +964,18 @@ int main(int argc, char** argv) {
     g_test_add_func("/futex/wake_stress", _futex_stress_test);
     g_test_add_func("/futex/wait_timeout", _futex_wait_timeout_test);
     g_test_add_func("/futex/wait_bitset_timeout", _futex_wait_bitset_timeout_test);
-
-    if (!running_in_shadow()) {
-        // TODO: implement FUTEX_WAKE_BITSET in Shadow.
-        g_test_add_func("/futex/wait_bitset", _futex_wait_bitset_test);
-    }
+    g_test_add_func("/futex/pi_ops", _futex_pi_ops_test);
+    g_test_add_func("/futex/lock_pi_timeout", _futex_lock_pi_timeout_test);
+    g_test_add_func("/futex/pi_mutex", _futex_pi_mutex_test);
+    g_test_add_func("/futex/wait_bitset", _futex_wait_bitset_test);
+    g_test_add_func("/futex/wake_bitset_selective", _futex_wake_bitset_selective_test);
+    g_test_add_func("/futex/bitset_invalid", _futex_bitset_invalid_test);
+    g_test_add_func("/futex/requeue", _futex_requeue_test);
+    g_test_add_func("/futex/cmp_requeue", _futex_cmp_requeue_test);
+    g_test_add_func("/futex/robust", _futex_robust_test);
+    g_test_add_func("/futex/robust_pi", _futex_robust_pi_test);
+    g_test_add_func("/futex/robust_process", _futex_robust_process_test);
+    g_test_add_func("/futex/robust_list_syscalls", _futex_robust_list_syscalls_test);
 
     return g_test_run();
 }
@@ -0,0 +1,138 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client with a slow upstream link uploads data to a server, and then downloads the same
+//! amount of data from the server. The client's `bandwidth_up` overrides the bandwidth of its
+//! graph node, so the upload should be limited by the override while the download should not.
+//!
+//! Usage:
+//! - `test_host_bandwidth server <port>`: receive the upload and then send the download
+//! - `test_host_bandwidth client <server-ip> <port>`: send the upload and then receive the download
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::Instant;
+
+use nix::sys::socket::{self, AddressFamily, MsgFlags, Shutdown, SockFlag, SockType, SockaddrIn};
+
+/// How many bytes are transferred in each direction.
+const TRANSFER_SIZE: usize = 250_000;
+
+/// The client's upstream bandwidth, which should match its `bandwidth_up` in the shadow config.
+const UP_BITS_PER_SEC: f64 = 1_000_000.0;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} server <port> | {0} client <server-ip> <port>",
+        args[0]
+    );
+
+    let parse_port = |s: &str| s.parse::<u16>().map_err(|e| format!("Bad port: {e}"));
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("server"), 3) => run_server(parse_port(&args[2])?)?,
+        (Some("client"), 4) => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            run_client(SocketAddrV4::new(ip, parse_port(&args[3])?))?
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+fn new_tcp_socket() -> Result<libc::c_int, String> {
+    socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Receive exactly `len` bytes and return the throughput in bits per second, measured from
+/// `start`.
+fn recv_exact(fd: libc::c_int, len: usize, start: Instant) -> Result<f64, String> {
+    let mut buf = vec![0u8; 65536];
+    let mut received = 0;
+    while received < len {
+        let max = std::cmp::min(buf.len(), len - received);
+        let rv = socket::recv(fd, &mut buf[..max], MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert(rv > 0, "Unexpected end of stream")?;
+        received += rv;
+    }
+
+    Ok((received * 8) as f64 / start.elapsed().as_secs_f64())
+}
+
+fn send_all(fd: libc::c_int, len: usize) -> Result<(), String> {
+    let buf = vec![0u8; 65536];
+    let mut sent = 0;
+    while sent < len {
+        let max = std::cmp::min(buf.len(), len - sent);
+        sent += socket::send(fd, &buf[..max], MsgFlags::empty()).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Receive the upload and check that it was limited by the client's upstream bandwidth, then
+/// send the download.
+fn run_server(port: u16) -> Result<(), String> {
+    let fd_listen = new_tcp_socket()?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        let bits_per_sec = recv_exact(fd, TRANSFER_SIZE, Instant::now())?;
+        println!("Received upload at {bits_per_sec:.0} bits/s");
+
+        test_utils::result_assert(
+            bits_per_sec > UP_BITS_PER_SEC / 2.0,
+            "The upload was much slower than the client's upstream bandwidth",
+        )?;
+        // the payload is smaller than the link's bandwidth due to packet headers
+        test_utils::result_assert(
+            bits_per_sec < UP_BITS_PER_SEC,
+            "The upload was faster than the client's upstream bandwidth",
+        )?;
+
+        send_all(fd, TRANSFER_SIZE)?;
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())
+    })
+}
+
+/// Send the upload, then receive the download and check that it wasn't limited by the client's
+/// upstream bandwidth.
+fn run_client(server_addr: SocketAddrV4) -> Result<(), String> {
+    let fd = new_tcp_socket()?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        send_all(fd, TRANSFER_SIZE)?;
+
+        // wait for the first byte of the download so that we don't measure the upload
+        let mut first = [0u8; 1];
+        let rv = socket::recv(fd, &mut first, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 1, "Unexpected end of stream")?;
+
+        let bits_per_sec = recv_exact(fd, TRANSFER_SIZE - 1, Instant::now())?;
+        println!("Received download at {bits_per_sec:.0} bits/s");
+
+        // the download uses the graph's 1 Gbit, not the client's upstream bandwidth
+        test_utils::result_assert(
+            bits_per_sec > 10.0 * UP_BITS_PER_SEC,
+            "The download was limited by the client's upstream bandwidth",
+        )?;
+
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())
+    })
+}
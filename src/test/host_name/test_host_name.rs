@@ -0,0 +1,117 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for shadow's custom `shadow_get_host_name` syscall, which returns the name of the host in
+//! shadow's config. These tests only run in shadow since the syscall doesn't exist on linux.
+//!
+//! Usage: `test_host_name <expected-host-name>`
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+/// Shadow's custom syscall number for `SYS_shadow_get_host_name`.
+const SYS_SHADOW_GET_HOST_NAME: libc::c_long = 1008;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let Some(expected) = std::env::args().skip(1).find(|x| !x.starts_with("--")) else {
+        return Err("Usage: test_host_name <expected-host-name>".to_string());
+    };
+
+    let mut tests = get_tests(expected);
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests(expected: String) -> Vec<test_utils::ShadowTest<(), String>> {
+    let shadow_only = set![TestEnv::Shadow];
+    let expected_1 = expected.clone();
+    let expected_2 = expected.clone();
+    vec![
+        test_utils::ShadowTest::new(
+            "test_get_host_name",
+            move || test_get_host_name(&expected_1),
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new(
+            "test_truncated",
+            move || test_truncated(&expected_2),
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new(
+            "test_after_sethostname",
+            move || test_after_sethostname(&expected),
+            shadow_only,
+        ),
+    ]
+}
+
+/// Call the syscall with a buffer of length `len`, and return the syscall's return value and the
+/// buffer.
+fn get_host_name(len: usize) -> Result<(usize, Vec<u8>), String> {
+    // fill with a non-zero value so that we can check where the NUL was written
+    let mut buf = vec![0xff_u8; len];
+    let rv = unsafe { libc::syscall(SYS_SHADOW_GET_HOST_NAME, buf.as_mut_ptr(), buf.len()) };
+    let rv = nix::errno::Errno::result(rv).map_err(|e| e.to_string())?;
+    Ok((rv.try_into().unwrap(), buf))
+}
+
+/// Test that the name matches the config when the buffer is large enough.
+fn test_get_host_name(expected: &str) -> Result<(), String> {
+    let (len, buf) = get_host_name(256)?;
+    test_utils::result_assert_eq(len, expected.len(), "Unexpected name length")?;
+    test_utils::result_assert_eq(&buf[..len], expected.as_bytes(), "Unexpected name")?;
+    test_utils::result_assert_eq(buf[len], 0, "The name isn't NUL-terminated")?;
+
+    // exactly enough space for the NUL
+    let (len, buf) = get_host_name(expected.len() + 1)?;
+    test_utils::result_assert_eq(len, expected.len(), "Unexpected name length")?;
+    test_utils::result_assert_eq(&buf[..len], expected.as_bytes(), "Unexpected name")?;
+    test_utils::result_assert_eq(buf[len], 0, "The name isn't NUL-terminated")
+}
+
+/// Test that the name is truncated to fit in a small buffer, and that the full length is returned.
+fn test_truncated(expected: &str) -> Result<(), String> {
+    // nothing is written to an empty buffer
+    let (len, _) = get_host_name(0)?;
+    test_utils::result_assert_eq(len, expected.len(), "Unexpected name length")?;
+
+    let (len, buf) = get_host_name(1)?;
+    test_utils::result_assert_eq(len, expected.len(), "Unexpected name length")?;
+    test_utils::result_assert_eq(buf, vec![0], "Unexpected truncated name")?;
+
+    let (len, buf) = get_host_name(expected.len())?;
+    let truncated_len = expected.len() - 1;
+    test_utils::result_assert_eq(len, expected.len(), "Unexpected name length")?;
+    test_utils::result_assert_eq(
+        &buf[..truncated_len],
+        &expected.as_bytes()[..truncated_len],
+        "Unexpected truncated name",
+    )?;
+    test_utils::result_assert_eq(buf[truncated_len], 0, "The name isn't NUL-terminated")
+}
+
+/// Test that the name isn't affected by the hostname that the process sets.
+fn test_after_sethostname(expected: &str) -> Result<(), String> {
+    // shadow may not allow changing the hostname, in which case this is a no-op
+    let new_name = b"some-other-name";
+    let _ = unsafe { libc::sethostname(new_name.as_ptr().cast(), new_name.len()) };
+
+    test_get_host_name(expected)
+}
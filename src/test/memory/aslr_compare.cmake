@@ -0,0 +1,23 @@
+macro(EXEC_COMPARE FILE1 FILE2 EXPECT_EQUAL)
+    execute_process(
+        COMMAND ${CMAKE_COMMAND} -E compare_files ${FILE1} ${FILE2}
+        RESULT_VARIABLE RESULT)
+    message(STATUS "Compare returned ${RESULT} for '${FILE1}' and '${FILE2}'")
+    if(${EXPECT_EQUAL} AND RESULT)
+        message(FATAL_ERROR "Mappings differ with the same seed; test failed")
+    endif()
+    if(NOT ${EXPECT_EQUAL} AND NOT RESULT)
+        message(FATAL_ERROR "Mappings are identical with different seeds; test failed")
+    endif()
+endmacro()
+
+# the same seed must give the same layout
+exec_compare(
+    ${CMAKE_BINARY_DIR}/aslr-seed1a-shadow.data/hosts/mytesthost/test_aslr.1000.stdout
+    ${CMAKE_BINARY_DIR}/aslr-seed1b-shadow.data/hosts/mytesthost/test_aslr.1000.stdout
+    TRUE)
+# a different seed must give a different layout
+exec_compare(
+    ${CMAKE_BINARY_DIR}/aslr-seed1a-shadow.data/hosts/mytesthost/test_aslr.1000.stdout
+    ${CMAKE_BINARY_DIR}/aslr-seed2-shadow.data/hosts/mytesthost/test_aslr.1000.stdout
+    FALSE)
@@ -0,0 +1,54 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Prints the addresses that the kernel chooses for a few anonymous mappings. Run under shadow
+//! with `use_deterministic_aslr` enabled; the output is compared across runs to check that the
+//! layout depends only on the seed.
+
+use std::error::Error;
+
+/// Shadow places seeded mappings below this address.
+const ASLR_MMAP_TOP: usize = 0x7f00_0000_0000;
+
+const MAP_LENS: [usize; 4] = [1, 4096, 3 * 4096 + 1, 1 << 20];
+
+fn map_anon(len: usize) -> Result<*mut u8, Box<dyn Error>> {
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(format!("mmap failed: {}", std::io::Error::last_os_error()).into());
+    }
+    Ok(ptr as *mut u8)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+
+    for len in MAP_LENS {
+        let ptr = map_anon(len)?;
+        let addr = ptr as usize;
+
+        test_utils::result_assert(!ptr.is_null(), "mapping is null")?;
+        test_utils::result_assert(addr % page_size == 0, "mapping isn't page-aligned")?;
+        test_utils::result_assert(addr + len <= ASLR_MMAP_TOP, "mapping is above the top")?;
+
+        // the mapping should be usable
+        let buf = unsafe { std::slice::from_raw_parts_mut(ptr, len) };
+        buf.fill(0xAB);
+        test_utils::result_assert(buf.iter().all(|x| *x == 0xAB), "mapping isn't writable")?;
+
+        println!("{len} {addr:#x}");
+    }
+
+    Ok(())
+}
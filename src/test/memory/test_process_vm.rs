@@ -0,0 +1,337 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for `process_vm_readv` and `process_vm_writev`.
+
+use nix::sys::wait::WaitStatus;
+use nix::unistd::{ForkResult, Pid};
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+const PATTERN_LEN: usize = 1000;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_invalid_args",
+            test_invalid_args,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_write_into_child",
+            test_write_into_child,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_read_from_child",
+            test_read_from_child,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_partial_read",
+            test_partial_read,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ]
+}
+
+fn pattern() -> Vec<u8> {
+    (0..PATTERN_LEN).map(|x| (x % 251) as u8).collect()
+}
+
+fn iovec(ptr: *mut u8, len: usize) -> libc::iovec {
+    libc::iovec {
+        iov_base: ptr as *mut libc::c_void,
+        iov_len: len,
+    }
+}
+
+/// Read the buffer using volatile reads, since it may have been changed by another process without
+/// the compiler knowing.
+fn read_volatile_bytes(ptr: *const u8, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| unsafe { std::ptr::read_volatile(ptr.add(i)) })
+        .collect()
+}
+
+fn process_vm_readv(
+    pid: Pid,
+    local: &[libc::iovec],
+    remote: &[libc::iovec],
+    flags: libc::c_ulong,
+) -> Result<usize, nix::errno::Errno> {
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_process_vm_readv,
+            pid.as_raw(),
+            local.as_ptr(),
+            local.len(),
+            remote.as_ptr(),
+            remote.len(),
+            flags,
+        )
+    };
+    nix::errno::Errno::result(rv).map(|x| x as usize)
+}
+
+fn process_vm_writev(
+    pid: Pid,
+    local: &[libc::iovec],
+    remote: &[libc::iovec],
+    flags: libc::c_ulong,
+) -> Result<usize, nix::errno::Errno> {
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_process_vm_writev,
+            pid.as_raw(),
+            local.as_ptr(),
+            local.len(),
+            remote.as_ptr(),
+            remote.len(),
+            flags,
+        )
+    };
+    nix::errno::Errno::result(rv).map(|x| x as usize)
+}
+
+/// Fork a child that runs `f` and exits with its return value as the exit code.
+fn fork_child(f: impl FnOnce() -> i32) -> Pid {
+    match unsafe { nix::unistd::fork() }.unwrap() {
+        ForkResult::Child => {
+            let rv = f();
+            unsafe { libc::_exit(rv) };
+        }
+        ForkResult::Parent { child } => child,
+    }
+}
+
+/// Wait for the child to exit and check that it exited successfully.
+fn check_child_exit(child: Pid) -> Result<(), String> {
+    let status = nix::sys::wait::waitpid(child, None).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(status, WaitStatus::Exited(child, 0), "Child failed")
+}
+
+fn test_invalid_args() -> Result<(), String> {
+    let mut local = [0u8; 10];
+    let mut remote = [0u8; 10];
+
+    let local_iov = [iovec(local.as_mut_ptr(), local.len())];
+    let remote_iov = [iovec(remote.as_mut_ptr(), remote.len())];
+
+    // flags must be 0
+    let rv = process_vm_readv(nix::unistd::getpid(), &local_iov, &remote_iov, 1);
+    test_utils::result_assert_eq(rv, Err(nix::errno::Errno::EINVAL), "Unexpected result")?;
+
+    let rv = process_vm_writev(nix::unistd::getpid(), &local_iov, &remote_iov, 1);
+    test_utils::result_assert_eq(rv, Err(nix::errno::Errno::EINVAL), "Unexpected result")?;
+
+    // a process that doesn't exist
+    let pid = Pid::from_raw(i32::MAX);
+    let rv = process_vm_readv(pid, &local_iov, &remote_iov, 0);
+    test_utils::result_assert_eq(rv, Err(nix::errno::Errno::ESRCH), "Unexpected result")?;
+
+    let rv = process_vm_writev(pid, &local_iov, &remote_iov, 0);
+    test_utils::result_assert_eq(rv, Err(nix::errno::Errno::ESRCH), "Unexpected result")?;
+
+    Ok(())
+}
+
+/// The parent writes a known pattern into the child's buffer.
+fn test_write_into_child() -> Result<(), String> {
+    let (read_fd, write_fd) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    // the child has a copy of this buffer at the same address
+    let mut buf = vec![0u8; PATTERN_LEN];
+    let buf_ptr = buf.as_mut_ptr();
+
+    let child = fork_child(|| {
+        // wait for the parent to write the pattern
+        let mut byte = [0u8];
+        if nix::unistd::read(read_fd, &mut byte) != Ok(1) {
+            return 1;
+        }
+
+        if read_volatile_bytes(buf_ptr, PATTERN_LEN) != pattern() {
+            return 2;
+        }
+
+        0
+    });
+
+    let mut pattern = pattern();
+    let pattern_ptr = pattern.as_mut_ptr();
+
+    // split the source and destination into iovs with different boundaries
+    let local_iov = [
+        iovec(pattern_ptr, 100),
+        iovec(unsafe { pattern_ptr.add(100) }, PATTERN_LEN - 100),
+    ];
+    let remote_iov = [
+        iovec(buf_ptr, 500),
+        iovec(unsafe { buf_ptr.add(500) }, 0),
+        iovec(unsafe { buf_ptr.add(500) }, PATTERN_LEN - 500),
+    ];
+
+    let rv = process_vm_writev(child, &local_iov, &remote_iov, 0);
+    test_utils::result_assert_eq(rv, Ok(PATTERN_LEN), "Unexpected result")?;
+
+    // our own buffer wasn't changed
+    test_utils::result_assert(
+        read_volatile_bytes(buf_ptr, PATTERN_LEN)
+            .iter()
+            .all(|x| *x == 0),
+        "Local buffer was changed",
+    )?;
+
+    nix::unistd::write(write_fd, &[0]).map_err(|e| e.to_string())?;
+
+    check_child_exit(child)?;
+
+    nix::unistd::close(read_fd).map_err(|e| e.to_string())?;
+    nix::unistd::close(write_fd).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// The parent reads a known pattern from the child's buffer.
+fn test_read_from_child() -> Result<(), String> {
+    let (parent_read_fd, child_write_fd) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+    let (child_read_fd, parent_write_fd) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    // the child has a copy of this buffer at the same address
+    let mut buf = vec![0u8; PATTERN_LEN];
+    let buf_ptr = buf.as_mut_ptr();
+
+    let child = fork_child(|| {
+        let buf = unsafe { std::slice::from_raw_parts_mut(buf_ptr, PATTERN_LEN) };
+        buf.copy_from_slice(&pattern());
+
+        // tell the parent that the pattern has been written
+        if nix::unistd::write(child_write_fd, &[0]) != Ok(1) {
+            return 1;
+        }
+
+        // wait for the parent to finish reading
+        let mut byte = [0u8];
+        if nix::unistd::read(child_read_fd, &mut byte) != Ok(1) {
+            return 2;
+        }
+
+        0
+    });
+
+    let mut byte = [0u8];
+    let rv = nix::unistd::read(parent_read_fd, &mut byte);
+    test_utils::result_assert_eq(rv, Ok(1), "Unexpected read result")?;
+
+    // the local buffer is shorter than the remote buffer
+    let mut local = vec![0u8; PATTERN_LEN - 1];
+    let local_ptr = local.as_mut_ptr();
+    let local_iov = [
+        iovec(local_ptr, 10),
+        iovec(unsafe { local_ptr.add(10) }, PATTERN_LEN - 1 - 10),
+    ];
+    let remote_iov = [iovec(buf_ptr, PATTERN_LEN)];
+
+    let rv = process_vm_readv(child, &local_iov, &remote_iov, 0);
+    test_utils::result_assert_eq(rv, Ok(PATTERN_LEN - 1), "Unexpected result")?;
+
+    test_utils::result_assert_eq(
+        &read_volatile_bytes(local_ptr, PATTERN_LEN - 1)[..],
+        &pattern()[..PATTERN_LEN - 1],
+        "Unexpected data",
+    )?;
+
+    nix::unistd::write(parent_write_fd, &[0]).map_err(|e| e.to_string())?;
+
+    check_child_exit(child)?;
+
+    for fd in [
+        parent_read_fd,
+        child_write_fd,
+        child_read_fd,
+        parent_write_fd,
+    ] {
+        nix::unistd::close(fd).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// A read that runs past the end of a mapped region returns the bytes read up to that point.
+fn test_partial_read() -> Result<(), String> {
+    let page_size = nix::unistd::sysconf(nix::unistd::SysconfVar::PAGE_SIZE)
+        .unwrap()
+        .unwrap() as usize;
+
+    let mapping = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            2 * page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(mapping, libc::MAP_FAILED);
+
+    // unmap the second page
+    let rv = unsafe { libc::munmap(mapping.add(page_size), page_size) };
+    assert_eq!(rv, 0);
+
+    let remote = unsafe { std::slice::from_raw_parts_mut(mapping as *mut u8, page_size) };
+    remote.fill(0xAB);
+
+    let mut local = vec![0u8; 2 * page_size];
+    let local_ptr = local.as_mut_ptr();
+    let local_iov = [iovec(local_ptr, 2 * page_size)];
+    let remote_iov = [iovec(mapping as *mut u8, 2 * page_size)];
+
+    // read from our own process
+    let rv = process_vm_readv(nix::unistd::getpid(), &local_iov, &remote_iov, 0);
+    test_utils::result_assert_eq(rv, Ok(page_size), "Unexpected result")?;
+
+    test_utils::result_assert(
+        read_volatile_bytes(local_ptr, page_size)
+            .iter()
+            .all(|x| *x == 0xAB),
+        "Unexpected data",
+    )?;
+
+    // nothing can be read if the first byte isn't mapped
+    let remote_iov = [iovec(
+        unsafe { mapping.add(page_size) } as *mut u8,
+        page_size,
+    )];
+    let rv = process_vm_readv(nix::unistd::getpid(), &local_iov, &remote_iov, 0);
+    test_utils::result_assert_eq(rv, Err(nix::errno::Errno::EFAULT), "Unexpected result")?;
+
+    let rv = unsafe { libc::munmap(mapping, page_size) };
+    assert_eq!(rv, 0);
+
+    Ok(())
+}
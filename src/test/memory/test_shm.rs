@@ -0,0 +1,313 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for SysV shared memory (`shmget`, `shmat`, `shmdt`, and `shmctl`).
+//!
+//! Usage: `test_shm [--shadow-passing|--libc-passing] [--summarize]` to run the single-process
+//! tests, or `test_shm writer <key>` and `test_shm reader <key>` to check that two processes
+//! attaching the same key share writes.
+
+use std::time::Duration;
+
+use nix::errno::Errno;
+use test_utils::{set, ShadowTest, TestEnvironment};
+
+const SEG_LEN: usize = 8192;
+
+const MESSAGE: &[u8] = b"hello from the writer";
+const ACK: &[u8] = b"ack";
+
+fn shmget(key: libc::key_t, size: usize, flags: libc::c_int) -> Result<libc::c_int, Errno> {
+    Errno::result(unsafe { libc::shmget(key, size, flags) })
+}
+
+fn shmat(shmid: libc::c_int, flags: libc::c_int) -> Result<*mut u8, Errno> {
+    let addr = unsafe { libc::shmat(shmid, std::ptr::null(), flags) };
+    if addr as isize == -1 {
+        return Err(Errno::last());
+    }
+    Ok(addr as *mut u8)
+}
+
+fn shmdt(addr: *mut u8) -> Result<(), Errno> {
+    Errno::result(unsafe { libc::shmdt(addr as *const libc::c_void) })?;
+    Ok(())
+}
+
+fn shm_stat(shmid: libc::c_int) -> Result<libc::shmid_ds, Errno> {
+    let mut ds: libc::shmid_ds = unsafe { std::mem::zeroed() };
+    Errno::result(unsafe { libc::shmctl(shmid, libc::IPC_STAT, &mut ds) })?;
+    Ok(ds)
+}
+
+fn shm_remove(shmid: libc::c_int) -> Result<(), Errno> {
+    Errno::result(unsafe { libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut()) })?;
+    Ok(())
+}
+
+/// A key that isn't used by other tests that may be running at the same time outside of shadow.
+fn unique_key(n: u8) -> libc::key_t {
+    (nix::unistd::getpid().as_raw() << 8) | libc::key_t::from(n)
+}
+
+/// Run `f`, and then remove the segment even if `f` failed.
+fn with_segment(shmid: libc::c_int, f: impl FnOnce() -> anyhow::Result<()>) -> anyhow::Result<()> {
+    let rv = f();
+    // the segment may have already been removed by `f`
+    let _ = shm_remove(shmid);
+    rv
+}
+
+fn test_private() -> anyhow::Result<()> {
+    let id_1 = shmget(libc::IPC_PRIVATE, SEG_LEN, 0o600)?;
+    let id_2 = shmget(libc::IPC_PRIVATE, SEG_LEN, 0o600)?;
+
+    with_segment(id_1, || {
+        with_segment(id_2, || {
+            // every IPC_PRIVATE segment is a new segment
+            anyhow::ensure!(id_1 != id_2);
+
+            let addr_1 = shmat(id_1, 0)?;
+            let addr_2 = shmat(id_2, 0)?;
+
+            // new segments are zeroed
+            let seg_1 = unsafe { std::slice::from_raw_parts_mut(addr_1, SEG_LEN) };
+            let seg_2 = unsafe { std::slice::from_raw_parts_mut(addr_2, SEG_LEN) };
+            anyhow::ensure!(seg_1.iter().all(|x| *x == 0));
+            anyhow::ensure!(seg_2.iter().all(|x| *x == 0));
+
+            seg_1[..MESSAGE.len()].copy_from_slice(MESSAGE);
+            anyhow::ensure!(seg_2.iter().all(|x| *x == 0));
+
+            shmdt(addr_1)?;
+            shmdt(addr_2)?;
+            Ok(())
+        })
+    })
+}
+
+fn test_key_lookup() -> anyhow::Result<()> {
+    let key = unique_key(1);
+
+    // doesn't exist yet
+    anyhow::ensure!(shmget(key, SEG_LEN, 0o600) == Err(Errno::ENOENT));
+
+    let shmid = shmget(key, SEG_LEN, libc::IPC_CREAT | libc::IPC_EXCL | 0o600)?;
+
+    with_segment(shmid, || {
+        // the existing segment is returned for the same key
+        anyhow::ensure!(shmget(key, SEG_LEN, 0o600) == Ok(shmid));
+        anyhow::ensure!(shmget(key, SEG_LEN, libc::IPC_CREAT | 0o600) == Ok(shmid));
+        anyhow::ensure!(shmget(key, 1, 0o600) == Ok(shmid));
+
+        anyhow::ensure!(
+            shmget(key, SEG_LEN, libc::IPC_CREAT | libc::IPC_EXCL | 0o600) == Err(Errno::EEXIST)
+        );
+
+        // larger than the existing segment
+        anyhow::ensure!(shmget(key, SEG_LEN + 1, 0o600) == Err(Errno::EINVAL));
+
+        Ok(())
+    })
+}
+
+fn test_invalid_args() -> anyhow::Result<()> {
+    anyhow::ensure!(shmget(libc::IPC_PRIVATE, 0, 0o600) == Err(Errno::EINVAL));
+
+    let shmid = shmget(libc::IPC_PRIVATE, SEG_LEN, 0o600)?;
+    with_segment(shmid, || {
+        anyhow::ensure!(shmat(-1, 0) == Err(Errno::EINVAL));
+        anyhow::ensure!(shm_stat(-1).map(|_| ()) == Err(Errno::EINVAL));
+
+        // not an attached segment
+        let mut buf = [0u8; 8];
+        anyhow::ensure!(shmdt(buf.as_mut_ptr()) == Err(Errno::EINVAL));
+
+        Ok(())
+    })
+}
+
+fn test_attach_twice() -> anyhow::Result<()> {
+    let shmid = shmget(libc::IPC_PRIVATE, SEG_LEN, 0o600)?;
+
+    with_segment(shmid, || {
+        let addr_1 = shmat(shmid, 0)?;
+        let addr_2 = shmat(shmid, 0)?;
+        anyhow::ensure!(addr_1 != addr_2);
+
+        // both attachments refer to the same memory
+        let seg_1 = unsafe { std::slice::from_raw_parts_mut(addr_1, SEG_LEN) };
+        let seg_2 = unsafe { std::slice::from_raw_parts_mut(addr_2, SEG_LEN) };
+        seg_1[..MESSAGE.len()].copy_from_slice(MESSAGE);
+        anyhow::ensure!(&seg_2[..MESSAGE.len()] == MESSAGE);
+
+        // a read-only attachment sees the same memory
+        let addr_3 = shmat(shmid, libc::SHM_RDONLY)?;
+        let seg_3 = unsafe { std::slice::from_raw_parts(addr_3, SEG_LEN) };
+        anyhow::ensure!(&seg_3[..MESSAGE.len()] == MESSAGE);
+
+        shmdt(addr_1)?;
+        shmdt(addr_2)?;
+        shmdt(addr_3)?;
+
+        // already detached
+        anyhow::ensure!(shmdt(addr_1) == Err(Errno::EINVAL));
+
+        Ok(())
+    })
+}
+
+fn test_stat() -> anyhow::Result<()> {
+    let shmid = shmget(libc::IPC_PRIVATE, SEG_LEN + 1, 0o640)?;
+
+    with_segment(shmid, || {
+        let ds = shm_stat(shmid)?;
+        anyhow::ensure!(ds.shm_segsz == SEG_LEN + 1);
+        anyhow::ensure!(ds.shm_perm.mode & 0o777 == 0o640);
+        anyhow::ensure!(ds.shm_cpid == nix::unistd::getpid().as_raw());
+        anyhow::ensure!(ds.shm_nattch == 0);
+        anyhow::ensure!(ds.shm_atime == 0);
+
+        let addr_1 = shmat(shmid, 0)?;
+        let addr_2 = shmat(shmid, 0)?;
+
+        let ds = shm_stat(shmid)?;
+        anyhow::ensure!(ds.shm_nattch == 2);
+        anyhow::ensure!(ds.shm_lpid == nix::unistd::getpid().as_raw());
+        anyhow::ensure!(ds.shm_atime != 0);
+
+        shmdt(addr_1)?;
+        anyhow::ensure!(shm_stat(shmid)?.shm_nattch == 1);
+
+        shmdt(addr_2)?;
+        let ds = shm_stat(shmid)?;
+        anyhow::ensure!(ds.shm_nattch == 0);
+        anyhow::ensure!(ds.shm_dtime != 0);
+
+        Ok(())
+    })
+}
+
+fn test_remove() -> anyhow::Result<()> {
+    let key = unique_key(2);
+    let shmid = shmget(key, SEG_LEN, libc::IPC_CREAT | libc::IPC_EXCL | 0o600)?;
+
+    with_segment(shmid, || {
+        let addr = shmat(shmid, 0)?;
+
+        shm_remove(shmid)?;
+
+        // the key no longer refers to the segment
+        anyhow::ensure!(shmget(key, SEG_LEN, 0o600) == Err(Errno::ENOENT));
+
+        // but it remains usable while it's attached
+        let seg = unsafe { std::slice::from_raw_parts_mut(addr, SEG_LEN) };
+        seg[..MESSAGE.len()].copy_from_slice(MESSAGE);
+        anyhow::ensure!(shm_stat(shmid)?.shm_nattch == 1);
+
+        // the segment is freed once the last attachment is detached
+        shmdt(addr)?;
+        anyhow::ensure!(shm_stat(shmid).map(|_| ()) == Err(Errno::EINVAL));
+
+        Ok(())
+    })
+}
+
+fn test_remove_unattached() -> anyhow::Result<()> {
+    let shmid = shmget(libc::IPC_PRIVATE, SEG_LEN, 0o600)?;
+    shm_remove(shmid)?;
+
+    // nothing was attached, so the segment is freed immediately
+    anyhow::ensure!(shm_stat(shmid).map(|_| ()) == Err(Errno::EINVAL));
+    anyhow::ensure!(shmat(shmid, 0) == Err(Errno::EINVAL));
+    anyhow::ensure!(shm_remove(shmid) == Err(Errno::EINVAL));
+
+    Ok(())
+}
+
+/// Wait until the segment starts with `expected`.
+fn wait_for(seg: &[u8], expected: &[u8]) {
+    // the other process writes to the memory, so the compiler must not assume it's unchanged
+    let matches = || {
+        seg.iter()
+            .zip(expected)
+            .all(|(x, y)| unsafe { std::ptr::read_volatile(x) } == *y)
+    };
+    while !matches() {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Create the segment, write a message, and wait for the reader to acknowledge it.
+fn run_writer(key: libc::key_t) -> anyhow::Result<()> {
+    let shmid = shmget(key, SEG_LEN, libc::IPC_CREAT | libc::IPC_EXCL | 0o600)?;
+
+    with_segment(shmid, || {
+        let addr = shmat(shmid, 0)?;
+        let seg = unsafe { std::slice::from_raw_parts_mut(addr, SEG_LEN) };
+
+        seg[..MESSAGE.len()].copy_from_slice(MESSAGE);
+        wait_for(&seg[SEG_LEN / 2..], ACK);
+
+        shmdt(addr)?;
+        Ok(())
+    })
+}
+
+/// Attach the writer's segment, wait for its message, and acknowledge it.
+fn run_reader(key: libc::key_t) -> anyhow::Result<()> {
+    let shmid = shmget(key, 0, 0o600)?;
+    let addr = shmat(shmid, 0)?;
+    let seg = unsafe { std::slice::from_raw_parts_mut(addr, SEG_LEN) };
+
+    wait_for(seg, MESSAGE);
+
+    // the writer stays attached until we acknowledge the message
+    anyhow::ensure!(shm_stat(shmid)?.shm_nattch == 2);
+
+    seg[SEG_LEN / 2..][..ACK.len()].copy_from_slice(ACK);
+
+    shmdt(addr)?;
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+
+    match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some("writer"), Some(key)) => return run_writer(key.parse()?),
+        (Some("reader"), Some(key)) => return run_reader(key.parse()?),
+        _ => {}
+    }
+
+    // should we restrict the tests we run?
+    let filter_shadow_passing = args.iter().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = args.iter().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = args.iter().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("private", test_private, all_envs.clone()),
+        ShadowTest::new("key_lookup", test_key_lookup, all_envs.clone()),
+        ShadowTest::new("invalid_args", test_invalid_args, all_envs.clone()),
+        ShadowTest::new("attach_twice", test_attach_twice, all_envs.clone()),
+        ShadowTest::new("stat", test_stat, all_envs.clone()),
+        ShadowTest::new("remove", test_remove, all_envs.clone()),
+        ShadowTest::new("remove_unattached", test_remove_unattached, all_envs),
+    ];
+
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnvironment::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnvironment::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
@@ -0,0 +1,226 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for shadow's custom `shadow_pin_cpu` syscall, which pins the calling thread to a simulated
+//! CPU. These tests only run in shadow since the syscall doesn't exist on linux. The config must
+//! set `experimental.cpu_migration_cost` to [`MIGRATION_COST`].
+
+use std::time::Duration;
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+/// Shadow's custom syscall number for `SYS_shadow_pin_cpu`.
+const SYS_SHADOW_PIN_CPU: libc::c_long = 1009;
+
+/// The `experimental.cpu_migration_cost` from the config.
+const MIGRATION_COST: Duration = Duration::from_millis(5);
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let shadow_only = set![TestEnv::Shadow];
+    vec![
+        test_utils::ShadowTest::new(
+            "test_migration_cost",
+            test_migration_cost,
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new(
+            "test_new_thread_cpu",
+            test_new_thread_cpu,
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new("test_invalid_cpu", test_invalid_cpu, shadow_only.clone()),
+        test_utils::ShadowTest::new("test_getcpu", test_getcpu, shadow_only.clone()),
+        test_utils::ShadowTest::new("test_sched_getcpu", test_sched_getcpu, shadow_only),
+    ]
+}
+
+/// Pin the calling thread to `cpu`, and return the CPU it was previously pinned to.
+fn pin_cpu(cpu: libc::c_uint) -> Result<libc::c_uint, nix::errno::Errno> {
+    let rv = unsafe { libc::syscall(SYS_SHADOW_PIN_CPU, cpu) };
+    let rv = nix::errno::Errno::result(rv)?;
+    Ok(rv.try_into().unwrap())
+}
+
+/// The CPU and NUMA node reported by `getcpu`.
+fn getcpu() -> Result<(libc::c_uint, libc::c_uint), nix::errno::Errno> {
+    let mut cpu: libc::c_uint = libc::c_uint::MAX;
+    let mut node: libc::c_uint = libc::c_uint::MAX;
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_getcpu,
+            std::ptr::from_mut(&mut cpu),
+            std::ptr::from_mut(&mut node),
+            std::ptr::null_mut::<libc::c_void>(),
+        )
+    };
+    nix::errno::Errno::result(rv)?;
+    Ok((cpu, node))
+}
+
+/// The simulated CPU time used by the calling thread.
+fn thread_cpu_time() -> Result<Duration, String> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let rv = unsafe { libc::getrusage(libc::RUSAGE_THREAD, &mut usage) };
+    test_utils::result_assert_eq(rv, 0, "getrusage() failed")?;
+
+    let to_duration = |tv: libc::timeval| {
+        Duration::new(tv.tv_sec as u64, 0) + Duration::from_micros(tv.tv_usec as u64)
+    };
+    Ok(to_duration(usage.ru_utime) + to_duration(usage.ru_stime))
+}
+
+/// Run `f` and return how much simulated CPU time the calling thread used.
+fn cpu_time_of(f: impl FnOnce() -> Result<(), String>) -> Result<Duration, String> {
+    let start = thread_cpu_time()?;
+    f()?;
+    Ok(thread_cpu_time()? - start)
+}
+
+/// Test that migrating to a different CPU adds the migration cost to the thread's CPU time, and
+/// that pinning to the current CPU doesn't.
+fn test_migration_cost() -> Result<(), String> {
+    // the other syscalls may use a small amount of CPU time
+    let slack = Duration::from_millis(1);
+
+    // make sure that we start on a known CPU
+    pin_cpu(0).map_err(|e| e.to_string())?;
+
+    let used = cpu_time_of(|| {
+        let prev = pin_cpu(3).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(prev, 0, "Unexpected previous CPU")
+    })?;
+    test_utils::result_assert(
+        used >= MIGRATION_COST && used < MIGRATION_COST + slack,
+        &format!("Migrating used {used:?} of CPU time"),
+    )?;
+
+    // not a migration
+    let used = cpu_time_of(|| {
+        let prev = pin_cpu(3).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(prev, 3, "Unexpected previous CPU")
+    })?;
+    test_utils::result_assert(
+        used < slack,
+        &format!("Pinning to the same CPU used {used:?} of CPU time"),
+    )?;
+
+    // two migrations
+    let used = cpu_time_of(|| {
+        let prev = pin_cpu(1).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(prev, 3, "Unexpected previous CPU")?;
+        let prev = pin_cpu(0).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(prev, 1, "Unexpected previous CPU")
+    })?;
+    test_utils::result_assert(
+        used >= 2 * MIGRATION_COST && used < 2 * MIGRATION_COST + slack,
+        &format!("Migrating twice used {used:?} of CPU time"),
+    )
+}
+
+/// Test that a new thread starts on CPU 0 regardless of the CPU of the thread that created it.
+fn test_new_thread_cpu() -> Result<(), String> {
+    pin_cpu(2).map_err(|e| e.to_string())?;
+
+    let child_cpu = std::thread::spawn(|| pin_cpu(0)).join().unwrap();
+    test_utils::result_assert_eq(child_cpu, Ok(0), "Unexpected CPU of the new thread")?;
+
+    // the calling thread's CPU wasn't changed
+    let prev = pin_cpu(0).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(prev, 2, "Unexpected previous CPU")
+}
+
+/// Test that CPUs that don't fit in a `cpu_set_t` are rejected.
+fn test_invalid_cpu() -> Result<(), String> {
+    pin_cpu(0).map_err(|e| e.to_string())?;
+
+    let max_cpu = libc::CPU_SETSIZE as libc::c_uint - 1;
+    test_utils::result_assert_eq(pin_cpu(max_cpu + 1), Err(nix::errno::Errno::EINVAL), "")?;
+    test_utils::result_assert_eq(
+        pin_cpu(libc::c_uint::MAX),
+        Err(nix::errno::Errno::EINVAL),
+        "",
+    )?;
+
+    // the CPU wasn't changed by the failed calls
+    test_utils::result_assert_eq(pin_cpu(max_cpu), Ok(0), "Unexpected previous CPU")?;
+    test_utils::result_assert_eq(pin_cpu(0), Ok(max_cpu), "Unexpected previous CPU")
+}
+
+/// Test that `getcpu` reports the CPU that the thread is pinned to.
+fn test_getcpu() -> Result<(), String> {
+    pin_cpu(0).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(getcpu(), Ok((0, 0)), "Unexpected CPU")?;
+
+    pin_cpu(5).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(getcpu(), Ok((5, 0)), "Unexpected CPU after pinning")?;
+
+    // the arguments are optional
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_getcpu,
+            std::ptr::null_mut::<libc::c_uint>(),
+            std::ptr::null_mut::<libc::c_uint>(),
+            std::ptr::null_mut::<libc::c_void>(),
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getcpu() with NULL arguments failed")?;
+
+    // a new thread starts on CPU 0
+    let child = std::thread::spawn(getcpu).join().unwrap();
+    test_utils::result_assert_eq(child, Ok((0, 0)), "Unexpected CPU of the new thread")?;
+
+    pin_cpu(0).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Test that glibc's `sched_getcpu()` reports the CPU that the thread is pinned to. Recent versions
+/// of glibc read the CPU from the thread's rseq area rather than making a syscall, so this checks
+/// that the rseq area is updated when the thread migrates.
+fn test_sched_getcpu() -> Result<(), String> {
+    pin_cpu(0).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(unsafe { libc::sched_getcpu() }, 0, "Unexpected CPU")?;
+
+    pin_cpu(4).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(
+        unsafe { libc::sched_getcpu() },
+        4,
+        "Unexpected CPU after pinning",
+    )?;
+
+    // a new thread starts on CPU 0
+    let child = std::thread::spawn(|| unsafe { libc::sched_getcpu() })
+        .join()
+        .unwrap();
+    test_utils::result_assert_eq(child, 0, "Unexpected CPU of the new thread")?;
+
+    pin_cpu(0).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(
+        unsafe { libc::sched_getcpu() },
+        0,
+        "Unexpected CPU after pinning back",
+    )
+}
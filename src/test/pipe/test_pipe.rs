@@ -6,6 +6,7 @@
 use std::time::Duration;
 
 use nix::poll::PollFlags;
+use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
 use test_utils::TestEnvironment as TestEnv;
 use test_utils::{iov_helper, iov_helper_mut, set};
 
@@ -34,6 +35,16 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
     let tests: Vec<test_utils::ShadowTest<_, _>> = vec![
         test_utils::ShadowTest::new("test_null", test_null, set![TestEnv::Libc, TestEnv::Shadow]),
         test_utils::ShadowTest::new("test_pipe", test_pipe, set![TestEnv::Libc, TestEnv::Shadow]),
+        test_utils::ShadowTest::new(
+            "test_pipe2_flags",
+            test_pipe2_flags,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_pipe2_invalid_flags",
+            test_pipe2_invalid_flags,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         test_utils::ShadowTest::new(
             "test_read_write",
             test_read_write,
@@ -44,6 +55,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_readv_writev,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_readv_writev_iov_validation",
+            test_readv_writev_iov_validation,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         test_utils::ShadowTest::new(
             "test_large_read_write",
             test_large_read_write,
@@ -89,6 +105,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_write_after_read_close_with_nonfull_buffer,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_o_direct_packet_boundaries",
+            test_o_direct_packet_boundaries,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         test_utils::ShadowTest::new(
             "test_o_direct_large_packet",
             test_o_direct_large_packet,
@@ -149,6 +170,21 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_close_during_blocking_write,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_interrupted_write_after_progress",
+            test_interrupted_write_after_progress,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_interrupted_write_before_progress",
+            test_interrupted_write_before_progress,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_writev_invalid_pointers",
+            test_writev_invalid_pointers,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
 
     tests
@@ -280,6 +316,100 @@ fn test_readv_writev() -> Result<(), String> {
     })
 }
 
+fn test_readv_writev_iov_validation() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(|| { unsafe { libc::pipe(fds.as_mut_ptr()) } }, &[])?;
+
+    test_utils::result_assert(fds[0] > 0, "fds[0] not set")?;
+    test_utils::result_assert(fds[1] > 0, "fds[1] not set")?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        let mut buf = [0u8; 4];
+        let iov_max = usize::try_from(libc::UIO_MAXIOV).unwrap();
+        let mut iovs = vec![
+            libc::iovec {
+                iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+                iov_len: 1,
+            };
+            iov_max + 1
+        ];
+
+        // more than IOV_MAX iovecs should be an error
+        test_utils::check_system_call!(
+            || unsafe { libc::writev(write_fd, iovs.as_ptr(), libc::UIO_MAXIOV + 1) },
+            &[libc::EINVAL]
+        )?;
+        test_utils::check_system_call!(
+            || unsafe { libc::readv(read_fd, iovs.as_ptr(), libc::UIO_MAXIOV + 1) },
+            &[libc::EINVAL]
+        )?;
+
+        // a length that overflows an ssize_t should be an error
+        iovs[1].iov_len = libc::ssize_t::MAX as libc::size_t + 1;
+        test_utils::check_system_call!(
+            || unsafe { libc::writev(write_fd, iovs.as_ptr(), 2) },
+            &[libc::EINVAL]
+        )?;
+        test_utils::check_system_call!(
+            || unsafe { libc::readv(read_fd, iovs.as_ptr(), 2) },
+            &[libc::EINVAL]
+        )?;
+
+        // zero iovecs is a zero-length operation, even with a NULL iovec pointer
+        let rv = test_utils::check_system_call!(
+            || unsafe { libc::writev(write_fd, std::ptr::null(), 0) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 0, "Expected to write 0 bytes")?;
+
+        // the pipe is empty, but reading zero iovecs shouldn't block
+        let rv = test_utils::check_system_call!(
+            || unsafe { libc::readv(read_fd, std::ptr::null(), 0) },
+            &[]
+        )?;
+        test_utils::result_assert_eq(rv, 0, "Expected to read 0 bytes")?;
+
+        Ok(())
+    })
+}
+
+fn test_writev_invalid_pointers() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(|| { unsafe { libc::pipe(fds.as_mut_ptr()) } }, &[])?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        for ptr in test_utils::invalid_pointers() {
+            // an iovec array that can't be read
+            test_utils::check_system_call!(
+                || unsafe { libc::writev(write_fd, ptr as *const libc::iovec, 1) },
+                &[libc::EFAULT]
+            )?;
+
+            // an iovec whose buffer can't be read
+            let iov = libc::iovec {
+                iov_base: ptr,
+                iov_len: 16,
+            };
+            test_utils::check_system_call!(
+                || unsafe { libc::writev(write_fd, &iov, 1) },
+                &[libc::EFAULT]
+            )?;
+        }
+
+        // nothing was written
+        test_utils::result_assert(
+            !test_utils::is_readable(read_fd, 0).unwrap(),
+            "The pipe should be empty",
+        )?;
+
+        Ok(())
+    })
+}
+
 fn test_large_read_write() -> Result<(), String> {
     let mut fds = [0 as libc::c_int; 2];
     test_utils::check_system_call!(|| { unsafe { libc::pipe(fds.as_mut_ptr()) } }, &[])?;
@@ -665,6 +795,94 @@ fn test_write_after_read_close_with_nonfull_buffer() -> Result<(), String> {
     Ok(())
 }
 
+// the O_NONBLOCK, O_DIRECT, and O_CLOEXEC flags should be applied to both ends of the pipe
+fn test_pipe2_flags() -> Result<(), String> {
+    for flags in [
+        0,
+        libc::O_NONBLOCK,
+        libc::O_DIRECT,
+        libc::O_CLOEXEC,
+        libc::O_NONBLOCK | libc::O_DIRECT | libc::O_CLOEXEC,
+    ] {
+        let mut fds = [0 as libc::c_int; 2];
+        test_utils::check_system_call!(
+            || { unsafe { libc::pipe2(fds.as_mut_ptr(), flags) } },
+            &[]
+        )?;
+
+        test_utils::run_and_close_fds(&fds, || {
+            for fd in fds {
+                let status = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+                let fd_flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+                assert!(status >= 0 && fd_flags >= 0);
+
+                let status_flags = libc::O_NONBLOCK | libc::O_DIRECT;
+                assert_eq!(status & status_flags, flags & status_flags);
+                assert_eq!(
+                    fd_flags & libc::FD_CLOEXEC != 0,
+                    flags & libc::O_CLOEXEC != 0
+                );
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn test_pipe2_invalid_flags() -> Result<(), String> {
+    for flags in [
+        libc::O_APPEND,
+        libc::O_SYNC,
+        libc::O_NONBLOCK | libc::O_TRUNC,
+        0x40000000,
+    ] {
+        let mut fds = [-1 as libc::c_int; 2];
+        test_utils::check_system_call!(
+            || { unsafe { libc::pipe2(fds.as_mut_ptr(), flags) } },
+            &[libc::EINVAL]
+        )?;
+    }
+
+    Ok(())
+}
+
+// in packet mode, each write should be read as a separate packet
+fn test_o_direct_packet_boundaries() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(
+        || { unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_DIRECT) } },
+        &[]
+    )?;
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        let packets: [&[u8]; 4] = [b"abc", b"defgh", b"ij", b"klmnop"];
+        for packet in packets {
+            assert_eq!(nix::unistd::write(write_fd, packet), Ok(packet.len()));
+        }
+
+        // each read returns a single packet, even though the buffer could hold all of them
+        let mut in_buf = [0u8; 100];
+        for packet in &packets[..3] {
+            let len = nix::unistd::read(read_fd, &mut in_buf).unwrap();
+            assert_eq!(&in_buf[..len], *packet);
+        }
+
+        // a read smaller than the packet discards the rest of the packet
+        assert_eq!(nix::unistd::read(read_fd, &mut in_buf[..2]), Ok(2));
+        assert_eq!(&in_buf[..2], b"kl");
+
+        // no packets left
+        assert_eq!(
+            nix::unistd::read(read_fd, &mut in_buf).err(),
+            Some(nix::errno::Errno::EWOULDBLOCK)
+        );
+    });
+
+    Ok(())
+}
+
 // when writing large packets, they should be broken up into PIPE_BUF-sized packets
 fn test_o_direct_large_packet() -> Result<(), String> {
     let mut fds = [0 as libc::c_int; 2];
@@ -1224,3 +1442,127 @@ fn test_close_during_blocking_write() -> Result<(), String> {
 
     Ok(())
 }
+
+extern "C" fn nop_signal_handler(_signal: libc::c_int) {}
+
+/// Runs `f` with a `SIGUSR1` handler installed without `SA_RESTART`, and sends `SIGUSR1` to the
+/// calling thread after `delay` so that any syscall blocked in `f` is interrupted.
+fn run_with_interrupt<T>(delay: Duration, f: impl FnOnce() -> T) -> T {
+    let action = SigAction::new(
+        SigHandler::Handler(nop_signal_handler),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+    let old_action = unsafe { signal::sigaction(Signal::SIGUSR1, &action) }.unwrap();
+
+    let pid = nix::unistd::getpid();
+    let tid = nix::unistd::gettid();
+
+    let thread_handle = std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let rv =
+            unsafe { libc::syscall(libc::SYS_tgkill, pid.as_raw(), tid.as_raw(), libc::SIGUSR1) };
+        assert_eq!(rv, 0);
+    });
+
+    let rv = f();
+
+    thread_handle.join().unwrap();
+    unsafe { signal::sigaction(Signal::SIGUSR1, &old_action) }.unwrap();
+
+    rv
+}
+
+/// Reads from a non-blocking fd until it would block, and returns the number of bytes read.
+fn drain(fd: libc::c_int) -> usize {
+    let mut buf = vec![0u8; 4096];
+    let mut total = 0;
+    loop {
+        match nix::unistd::read(fd, &mut buf) {
+            Ok(0) | Err(nix::errno::Errno::EAGAIN) => break total,
+            Ok(n) => total += n,
+            Err(e) => panic!("Unexpected error {}", e),
+        }
+    }
+}
+
+fn test_interrupted_write_after_progress() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(|| { unsafe { libc::pipe2(fds.as_mut_ptr(), 0) } }, &[])?;
+
+    assert!(fds[0] > 0, "fds[0] not set");
+    assert!(fds[1] > 0, "fds[1] not set");
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        let size = test_utils::check_system_call!(
+            || unsafe { libc::fcntl(read_fd, libc::F_GETPIPE_SZ) },
+            &[]
+        )?;
+
+        // more than the pipe can hold, so the write will block once the pipe is full
+        let write_buf = vec![1u8; 2 * usize::try_from(size).unwrap()];
+
+        // the write should block rather than return a short count, and after it's interrupted it
+        // should return the number of bytes written rather than EINTR
+        let rv = run_with_interrupt(Duration::from_secs(1), || {
+            nix::unistd::write(write_fd, &write_buf)
+        });
+        let written = rv.unwrap();
+        assert!(
+            written > 0 && written < write_buf.len(),
+            "Wrote {written} bytes"
+        );
+
+        // the pipe should contain exactly the bytes that were reported as written
+        test_utils::check_system_call!(
+            || unsafe { libc::fcntl(read_fd, libc::F_SETFL, libc::O_NONBLOCK) },
+            &[]
+        )?;
+        assert_eq!(drain(read_fd), written);
+
+        Ok(())
+    })
+}
+
+fn test_interrupted_write_before_progress() -> Result<(), String> {
+    let mut fds = [0 as libc::c_int; 2];
+    test_utils::check_system_call!(
+        || { unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK) } },
+        &[]
+    )?;
+
+    assert!(fds[0] > 0, "fds[0] not set");
+    assert!(fds[1] > 0, "fds[1] not set");
+
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    test_utils::run_and_close_fds(&[write_fd, read_fd], || {
+        // write until buffer is full
+        let mut filled = 0;
+        loop {
+            match nix::unistd::write(write_fd, &[1; 500]) {
+                Ok(n) => filled += n,
+                Err(e) => {
+                    assert_eq!(e, nix::errno::Errno::EAGAIN);
+                    break;
+                }
+            }
+        }
+
+        // make the write end blocking
+        test_utils::check_system_call!(|| unsafe { libc::fcntl(write_fd, libc::F_SETFL, 0) }, &[])?;
+
+        // the write can't make any progress, so after it's interrupted it should return EINTR
+        let rv = run_with_interrupt(Duration::from_secs(1), || {
+            nix::unistd::write(write_fd, &[2; 100])
+        });
+        assert_eq!(rv, Err(nix::errno::Errno::EINTR));
+
+        // nothing else should have been written to the pipe
+        assert_eq!(drain(read_fd), filled);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,49 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+// Tests the host's preloaded files. The files are created by shadow before the process starts, so
+// this only runs in shadow.
+
+#include <fcntl.h>
+#include <glib.h>
+#include <unistd.h>
+
+#include "test/test_glib_helpers.h"
+
+// these must match the preloaded files
+#define INLINE_FILE "input.txt"
+#define INLINE_FILE_CONTENT "Inline data\n"
+#define COPIED_FILE "nested/copied.txt"
+#define COPIED_FILE_CONTENT "Copied data\n"
+#define TMPFS_FILE "/opt/shadow-test/tmpfs/dir/input.txt"
+#define TMPFS_FILE_CONTENT "Tmpfs data\n"
+
+static void _check_file(const char* path, const char* expected) {
+    int fd = open(path, O_RDONLY);
+    assert_nonneg_errno(fd);
+
+    char buf[100] = {0};
+    ssize_t rv = read(fd, buf, sizeof(buf) - 1);
+    assert_nonneg_errno(rv);
+    g_assert_cmpstr(buf, ==, expected);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_inline(void) { _check_file(INLINE_FILE, INLINE_FILE_CONTENT); }
+
+static void _test_copied(void) { _check_file(COPIED_FILE, COPIED_FILE_CONTENT); }
+
+static void _test_tmpfs(void) { _check_file(TMPFS_FILE, TMPFS_FILE_CONTENT); }
+
+int main(int argc, char** argv) {
+    g_test_init(&argc, &argv, NULL);
+
+    g_test_add_func("/preloaded_files/inline", _test_inline);
+    g_test_add_func("/preloaded_files/copied", _test_copied);
+    g_test_add_func("/preloaded_files/tmpfs", _test_tmpfs);
+
+    return g_test_run();
+}
@@ -17,6 +17,20 @@ fn main() -> Result<(), String> {
     let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
     // should we summarize the results rather than exit on a failed test
     let summarize = std::env::args().any(|x| x == "--summarize");
+    // is shadow configured so that the entropy pool isn't yet initialized?
+    let entropy_pool_uninit = std::env::args().any(|x| x == "--entropy-pool-uninit");
+
+    if entropy_pool_uninit {
+        let tests = vec![test_utils::ShadowTest::new(
+            "test_getrandom_entropy_pool_uninit",
+            test_getrandom_entropy_pool_uninit,
+            set![TestEnv::Shadow],
+        )];
+        test_utils::run_tests(&tests, summarize)?;
+
+        println!("Success.");
+        return Ok(());
+    }
 
     let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
         test_utils::ShadowTest::new(
@@ -32,6 +46,11 @@ fn main() -> Result<(), String> {
             test_getrandom,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_getrandom_flags",
+            test_getrandom_flags,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
     if filter_shadow_passing {
         tests.retain(|x| x.passing(TestEnv::Shadow))
@@ -143,3 +162,75 @@ fn test_getrandom() -> Result<(), String> {
 
     check_randomness(&values)
 }
+
+fn getrandom(buf: &mut [u8], flags: libc::c_uint) -> Result<usize, nix::errno::Errno> {
+    let rv = unsafe {
+        libc::syscall(
+            libc::SYS_getrandom,
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+            flags,
+        )
+    };
+    nix::errno::Errno::result(rv).map(|x| x as usize)
+}
+
+fn test_getrandom_flags() -> Result<(), String> {
+    let mut buf = [0_u8; 16];
+
+    for flags in [
+        libc::GRND_NONBLOCK,
+        libc::GRND_RANDOM,
+        libc::GRND_RANDOM | libc::GRND_NONBLOCK,
+        libc::GRND_INSECURE,
+    ] {
+        test_utils::result_assert_eq(
+            getrandom(&mut buf, flags),
+            Ok(buf.len()),
+            &format!("Unexpected getrandom() result for flags {flags:#x}"),
+        )?;
+    }
+
+    test_utils::result_assert_eq(
+        getrandom(&mut buf, libc::GRND_INSECURE | libc::GRND_RANDOM),
+        Err(nix::errno::Errno::EINVAL),
+        "Unexpected getrandom() result for GRND_INSECURE with GRND_RANDOM",
+    )?;
+
+    test_utils::result_assert_eq(
+        getrandom(&mut buf, 0x80),
+        Err(nix::errno::Errno::EINVAL),
+        "Unexpected getrandom() result for an unknown flag",
+    )
+}
+
+/// Expects to be run before the host's entropy pool is initialized (see
+/// `experimental.entropy_pool_init_time`).
+fn test_getrandom_entropy_pool_uninit() -> Result<(), String> {
+    let mut buf = [0_u8; 16];
+
+    for flags in [libc::GRND_NONBLOCK, libc::GRND_RANDOM | libc::GRND_NONBLOCK] {
+        test_utils::result_assert_eq(
+            getrandom(&mut buf, flags),
+            Err(nix::errno::Errno::EAGAIN),
+            &format!("Unexpected getrandom() result for flags {flags:#x}"),
+        )?;
+    }
+
+    // GRND_INSECURE doesn't require the pool to be initialized, and we don't model blocking until
+    // the pool is initialized.
+    for flags in [
+        0,
+        libc::GRND_RANDOM,
+        libc::GRND_INSECURE,
+        libc::GRND_INSECURE | libc::GRND_NONBLOCK,
+    ] {
+        test_utils::result_assert_eq(
+            getrandom(&mut buf, flags),
+            Ok(buf.len()),
+            &format!("Unexpected getrandom() result for flags {flags:#x}"),
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+// Tests the host's read-only mounts. The mounted paths don't exist on the real filesystem, so this
+// only runs in shadow. Legacy path syscalls such as `unlink` are executed natively and don't see
+// the mounts, so we use their `*at` variants.
+
+#include <errno.h>
+#include <fcntl.h>
+#include <glib.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#include "test/test_glib_helpers.h"
+
+// these must match the mounts and the mounted files' contents
+#define MOUNTED_FILE "/opt/shadow-test/data.txt"
+#define MOUNTED_DIR "/opt/shadow-test/dir"
+#define MOUNTED_FILE_CONTENT "Read-only data\n"
+#define NESTED_FILE_CONTENT "Nested read-only data\n"
+
+static void _check_content(int fd, const char* expected) {
+    char buf[100] = {0};
+    ssize_t rv = read(fd, buf, sizeof(buf) - 1);
+    assert_nonneg_errno(rv);
+    g_assert_cmpstr(buf, ==, expected);
+}
+
+static void _test_read_file(void) {
+    int fd = open(MOUNTED_FILE, O_RDONLY);
+    assert_nonneg_errno(fd);
+    _check_content(fd, MOUNTED_FILE_CONTENT);
+    assert_nonneg_errno(close(fd));
+
+    struct stat statbuf = {0};
+    assert_nonneg_errno(fstatat(AT_FDCWD, MOUNTED_FILE, &statbuf, 0));
+    g_assert_cmpint(statbuf.st_size, ==, strlen(MOUNTED_FILE_CONTENT));
+    g_assert_true(S_ISREG(statbuf.st_mode));
+
+    assert_nonneg_errno(faccessat(AT_FDCWD, MOUNTED_FILE, R_OK, 0));
+}
+
+static void _test_mmap_file(void) {
+    int fd = open(MOUNTED_FILE, O_RDONLY);
+    assert_nonneg_errno(fd);
+
+    size_t len = strlen(MOUNTED_FILE_CONTENT);
+    char* ptr = mmap(NULL, len, PROT_READ, MAP_PRIVATE, fd, 0);
+    assert_true_errno(ptr != MAP_FAILED);
+    g_assert_cmpmem(ptr, len, MOUNTED_FILE_CONTENT, len);
+
+    assert_nonneg_errno(munmap(ptr, len));
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_read_dir(void) {
+    int dirfd = open(MOUNTED_DIR, O_RDONLY | O_DIRECTORY);
+    assert_nonneg_errno(dirfd);
+
+    int fd = openat(dirfd, "nested.txt", O_RDONLY);
+    assert_nonneg_errno(fd);
+    _check_content(fd, NESTED_FILE_CONTENT);
+    assert_nonneg_errno(close(fd));
+
+    fd = open(MOUNTED_DIR "/nested.txt", O_RDONLY);
+    assert_nonneg_errno(fd);
+    _check_content(fd, NESTED_FILE_CONTENT);
+    assert_nonneg_errno(close(fd));
+
+    assert_nonneg_errno(close(dirfd));
+}
+
+static void _test_write_file(void) {
+    g_assert_cmpint(open(MOUNTED_FILE, O_WRONLY), ==, -1);
+    assert_errno_is(EROFS);
+
+    g_assert_cmpint(open(MOUNTED_FILE, O_RDWR), ==, -1);
+    assert_errno_is(EROFS);
+
+    g_assert_cmpint(open(MOUNTED_FILE, O_RDONLY | O_TRUNC), ==, -1);
+    assert_errno_is(EROFS);
+
+    g_assert_cmpint(faccessat(AT_FDCWD, MOUNTED_FILE, W_OK, 0), ==, -1);
+    assert_errno_is(EROFS);
+
+    int fd = open(MOUNTED_FILE, O_RDONLY);
+    assert_nonneg_errno(fd);
+
+    // the file was opened read-only
+    g_assert_cmpint(write(fd, "x", 1), ==, -1);
+    assert_errno_is(EBADF);
+
+    g_assert_cmpint(fchmod(fd, 0777), ==, -1);
+    assert_errno_is(EROFS);
+
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_write_dir(void) {
+    g_assert_cmpint(open(MOUNTED_DIR "/nested.txt", O_WRONLY), ==, -1);
+    assert_errno_is(EROFS);
+
+    g_assert_cmpint(open(MOUNTED_DIR "/new.txt", O_WRONLY | O_CREAT, 0644), ==, -1);
+    assert_errno_is(EROFS);
+
+    g_assert_cmpint(unlinkat(AT_FDCWD, MOUNTED_DIR "/nested.txt", 0), ==, -1);
+    assert_errno_is(EROFS);
+
+    g_assert_cmpint(mkdirat(AT_FDCWD, MOUNTED_DIR "/new", 0755), ==, -1);
+    assert_errno_is(EROFS);
+
+    int rv = renameat(AT_FDCWD, MOUNTED_DIR "/nested.txt", AT_FDCWD, MOUNTED_DIR "/renamed.txt");
+    g_assert_cmpint(rv, ==, -1);
+    assert_errno_is(EROFS);
+
+    // paths relative to a directory in the mount are also read-only
+    int dirfd = open(MOUNTED_DIR, O_RDONLY | O_DIRECTORY);
+    assert_nonneg_errno(dirfd);
+
+    g_assert_cmpint(openat(dirfd, "nested.txt", O_RDWR), ==, -1);
+    assert_errno_is(EROFS);
+
+    g_assert_cmpint(unlinkat(dirfd, "nested.txt", 0), ==, -1);
+    assert_errno_is(EROFS);
+
+    assert_nonneg_errno(close(dirfd));
+
+    // the real file is unchanged
+    int fd = open(MOUNTED_DIR "/nested.txt", O_RDONLY);
+    assert_nonneg_errno(fd);
+    _check_content(fd, NESTED_FILE_CONTENT);
+    assert_nonneg_errno(close(fd));
+}
+
+int main(int argc, char** argv) {
+    g_test_init(&argc, &argv, NULL);
+
+    g_test_add_func("/read_only_mounts/read_file", _test_read_file);
+    g_test_add_func("/read_only_mounts/mmap_file", _test_mmap_file);
+    g_test_add_func("/read_only_mounts/read_dir", _test_read_dir);
+    g_test_add_func("/read_only_mounts/write_file", _test_write_file);
+    g_test_add_func("/read_only_mounts/write_dir", _test_write_dir);
+
+    return g_test_run();
+}
@@ -0,0 +1,248 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for the `getrusage` syscall and the resource usage returned by `wait4`.
+
+use std::time::{Duration, Instant};
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+/// How long each busy loop runs for.
+const BUSY_DURATION: Duration = Duration::from_millis(200);
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let all_envs = set![TestEnv::Libc, TestEnv::Shadow];
+    vec![
+        test_utils::ShadowTest::new("test_invalid_who", test_invalid_who, all_envs.clone()),
+        test_utils::ShadowTest::new("test_max_rss", test_max_rss, all_envs.clone()),
+        // the native scheduler may preempt the busy loop, so the cpu time is only predictable in
+        // shadow
+        test_utils::ShadowTest::new("test_self", test_self, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new("test_monotonic", test_monotonic, all_envs.clone()),
+        test_utils::ShadowTest::new("test_thread", test_thread, all_envs.clone()),
+        test_utils::ShadowTest::new("test_children", test_children, all_envs),
+    ]
+}
+
+fn getrusage(who: libc::c_int) -> Result<libc::rusage, String> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let rv = unsafe { libc::getrusage(who, &mut usage) };
+    test_utils::result_assert_eq(rv, 0, "getrusage() failed")?;
+    Ok(usage)
+}
+
+fn to_duration(tv: libc::timeval) -> Duration {
+    Duration::new(tv.tv_sec as u64, 0) + Duration::from_micros(tv.tv_usec as u64)
+}
+
+fn user_time(usage: &libc::rusage) -> Duration {
+    to_duration(usage.ru_utime)
+}
+
+fn cpu_time(usage: &libc::rusage) -> Duration {
+    to_duration(usage.ru_utime) + to_duration(usage.ru_stime)
+}
+
+/// Check the time for `duration` without blocking. Under shadow this only uses simulated cpu time
+/// if `model_unblocked_syscall_latency` is enabled.
+fn busy_loop(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {}
+}
+
+fn check_approx_eq(actual: Duration, expected: Duration, msg: &str) -> Result<(), String> {
+    println!("{msg}: {actual:?} (expected {expected:?})");
+    test_utils::result_assert(
+        actual >= expected.mul_f64(0.9) && actual <= expected.mul_f64(1.1),
+        msg,
+    )
+}
+
+fn test_invalid_who() -> Result<(), String> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let rv = unsafe { libc::getrusage(42, &mut usage) };
+    test_utils::result_assert_eq(rv, -1, "getrusage() succeeded")?;
+    test_utils::result_assert_eq(test_utils::get_errno(), libc::EINVAL, "Unexpected errno")
+}
+
+fn test_max_rss() -> Result<(), String> {
+    let usage = getrusage(libc::RUSAGE_SELF)?;
+    test_utils::result_assert(usage.ru_maxrss > 0, "The peak rss is 0")?;
+
+    let usage = getrusage(libc::RUSAGE_THREAD)?;
+    test_utils::result_assert(usage.ru_maxrss > 0, "The thread's peak rss is 0")?;
+
+    // a large allocation that's in use raises the peak, which doesn't go down after it's freed
+    const ALLOC_LEN: usize = 64 * 1024 * 1024;
+    let before = getrusage(libc::RUSAGE_SELF)?;
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            ALLOC_LEN,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    test_utils::result_assert(ptr != libc::MAP_FAILED, "mmap() failed")?;
+    unsafe { std::ptr::write_bytes(ptr as *mut u8, 1, ALLOC_LEN) };
+    let during = getrusage(libc::RUSAGE_SELF)?;
+    test_utils::result_assert_eq(
+        unsafe { libc::munmap(ptr, ALLOC_LEN) },
+        0,
+        "munmap() failed",
+    )?;
+    let after = getrusage(libc::RUSAGE_SELF)?;
+
+    test_utils::result_assert(
+        during.ru_maxrss - before.ru_maxrss >= (ALLOC_LEN / 1024 / 2) as libc::c_long,
+        &format!(
+            "The peak rss didn't grow enough: {} -> {} KiB",
+            before.ru_maxrss, during.ru_maxrss
+        ),
+    )?;
+    test_utils::result_assert_eq(after.ru_maxrss, during.ru_maxrss, "The peak rss went down")
+}
+
+fn test_self() -> Result<(), String> {
+    let before = getrusage(libc::RUSAGE_SELF)?;
+    busy_loop(BUSY_DURATION);
+    let after = getrusage(libc::RUSAGE_SELF)?;
+
+    check_approx_eq(
+        user_time(&after) - user_time(&before),
+        BUSY_DURATION,
+        "Unexpected user time",
+    )?;
+
+    // shadow doesn't model fields such as context switches
+    test_utils::result_assert_eq(after.ru_nvcsw, 0, "Unexpected context switches")?;
+    test_utils::result_assert_eq(after.ru_nivcsw, 0, "Unexpected context switches")
+}
+
+/// Each call during a compute loop should report at least as much user time as the previous call,
+/// and the loop as a whole should advance it.
+fn test_monotonic() -> Result<(), String> {
+    let first = getrusage(libc::RUSAGE_SELF)?;
+    let mut prev = first;
+
+    for _ in 0..10 {
+        busy_loop(BUSY_DURATION / 10);
+        let usage = getrusage(libc::RUSAGE_SELF)?;
+        test_utils::result_assert(
+            user_time(&usage) >= user_time(&prev),
+            "The user time went backwards",
+        )?;
+        prev = usage;
+    }
+
+    test_utils::result_assert(
+        user_time(&prev) > user_time(&first),
+        "The user time didn't advance",
+    )
+}
+
+fn test_thread() -> Result<(), String> {
+    let thread_usage = std::thread::spawn(|| {
+        let before = getrusage(libc::RUSAGE_THREAD)?;
+        busy_loop(BUSY_DURATION);
+        let after = getrusage(libc::RUSAGE_THREAD)?;
+        Ok::<_, String>((before, after))
+    });
+    let (before, after) = thread_usage.join().unwrap()?;
+
+    // the process's usage includes the usage of its exited threads
+    let process_usage = getrusage(libc::RUSAGE_SELF)?;
+    test_utils::result_assert(
+        cpu_time(&process_usage) >= cpu_time(&after),
+        "The process used less cpu time than the thread",
+    )?;
+
+    if test_utils::running_in_shadow() {
+        // a new thread starts with no usage
+        test_utils::result_assert(
+            cpu_time(&before) < BUSY_DURATION / 10,
+            "The new thread has already used cpu time",
+        )?;
+        check_approx_eq(
+            user_time(&after),
+            BUSY_DURATION,
+            "Unexpected thread user time",
+        )?;
+    }
+
+    Ok(())
+}
+
+fn test_children() -> Result<(), String> {
+    let before = getrusage(libc::RUSAGE_CHILDREN)?;
+
+    let pid = unsafe { libc::fork() };
+    test_utils::result_assert(pid >= 0, "fork() failed")?;
+    if pid == 0 {
+        busy_loop(BUSY_DURATION);
+        unsafe { libc::_exit(0) };
+    }
+
+    // the child hasn't been reaped yet
+    let not_reaped = getrusage(libc::RUSAGE_CHILDREN)?;
+    test_utils::result_assert_eq(
+        cpu_time(&not_reaped),
+        cpu_time(&before),
+        "Unexpected usage before reaping the child",
+    )?;
+
+    let mut status = 0;
+    let mut child_usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let rv = unsafe { libc::wait4(pid, &mut status, 0, &mut child_usage) };
+    test_utils::result_assert_eq(rv, pid, "wait4() failed")?;
+    test_utils::result_assert(libc::WIFEXITED(status), "The child didn't exit")?;
+
+    let after = getrusage(libc::RUSAGE_CHILDREN)?;
+    test_utils::result_assert(
+        cpu_time(&after) >= cpu_time(&child_usage),
+        "The reaped child's usage is missing",
+    )?;
+    test_utils::result_assert(child_usage.ru_maxrss > 0, "The child's peak rss is 0")?;
+
+    // linux sums the nanosecond cpu times before rounding, so these may differ slightly
+    if test_utils::running_in_shadow() {
+        test_utils::result_assert_eq(
+            cpu_time(&after) - cpu_time(&before),
+            cpu_time(&child_usage),
+            "The reaped child's usage doesn't match the usage from wait4()",
+        )?;
+        check_approx_eq(
+            user_time(&child_usage),
+            BUSY_DURATION,
+            "Unexpected child user time",
+        )?;
+    }
+
+    Ok(())
+}
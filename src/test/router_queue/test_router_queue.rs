@@ -0,0 +1,145 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client uploads data to a server over a congested link. The server's inbound router queue has
+//! a small capacity and no AQM, so the queue should overflow and drop packets, which the client
+//! must retransmit. The upload should still be limited by the bandwidth of the congested link.
+//!
+//! Usage:
+//! - `test_router_queue sink <port>`: receive the upload
+//! - `test_router_queue upload <server-ip> <port>`: upload data as fast as possible
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::{Duration, Instant};
+
+use nix::sys::socket::{self, AddressFamily, MsgFlags, Shutdown, SockFlag, SockType, SockaddrIn};
+
+/// How long the client uploads for.
+const UPLOAD_DURATION: Duration = Duration::from_secs(10);
+
+/// The bandwidth of the congested link, which should match the server's `bandwidth_down` in the
+/// shadow config.
+const LINK_BITS_PER_SEC: f64 = 10_000_000.0;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} sink <port> | {0} upload <server-ip> <port>",
+        args[0]
+    );
+
+    let parse_port = |s: &str| s.parse::<u16>().map_err(|e| format!("Bad port: {e}"));
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("sink"), 3) => run_sink(parse_port(&args[2])?)?,
+        (Some("upload"), 4) => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            run_upload(SocketAddrV4::new(ip, parse_port(&args[3])?))?
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+fn new_tcp_socket() -> Result<libc::c_int, String> {
+    socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Get the `tcpi_total_retrans` field of the socket's `struct tcp_info`.
+fn get_info_total_retrans(fd: libc::c_int) -> Result<u32, String> {
+    // the libc package doesn't expose 'struct tcp_info', so we only read the start of the struct;
+    // 'tcpi_total_retrans' is the 24th u32 following 8 bytes of u8 fields
+    let mut info = [0u32; 26];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(
+        info_len as usize,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+    Ok(info[25])
+}
+
+/// Accept a connection and read everything that the client sends. Checks that the upload's
+/// throughput was limited by, but still made good use of, the link's bandwidth.
+fn run_sink(port: u16) -> Result<(), String> {
+    let fd_listen = new_tcp_socket()?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        let start = Instant::now();
+
+        let mut buf = vec![0u8; 65536];
+        let mut received = 0;
+        loop {
+            let rv = socket::recv(fd, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            if rv == 0 {
+                break;
+            }
+            received += rv;
+        }
+
+        let bits_per_sec = (received * 8) as f64 / start.elapsed().as_secs_f64();
+        println!("Received {received} bytes at {bits_per_sec:.0} bits/s");
+
+        test_utils::result_assert(
+            bits_per_sec > LINK_BITS_PER_SEC / 2.0,
+            "The upload didn't recover from the dropped packets",
+        )?;
+        // the payload is smaller than the link's bandwidth due to packet headers
+        test_utils::result_assert(
+            bits_per_sec < LINK_BITS_PER_SEC,
+            "The upload was faster than the link",
+        )
+    })
+}
+
+/// Upload data to the server as fast as possible for a fixed amount of time, and check that some
+/// packets needed to be retransmitted.
+fn run_upload(server_addr: SocketAddrV4) -> Result<(), String> {
+    let fd = new_tcp_socket()?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let start = Instant::now();
+        let buf = vec![0u8; 65536];
+        while start.elapsed() < UPLOAD_DURATION {
+            socket::send(fd, &buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        }
+
+        let retransmits = get_info_total_retrans(fd)?;
+        println!("Retransmitted {retransmits} packets");
+
+        test_utils::result_assert(
+            retransmits > 0,
+            "No packets were dropped by the router queue",
+        )?;
+
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())
+    })
+}
@@ -0,0 +1,134 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+fn main() {
+    let shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+
+    priority_range();
+    default_policy();
+    invalid_params();
+    set_batch();
+    if shadow_passing {
+        // Natively, real-time policies require CAP_SYS_NICE.
+        set_round_robin();
+    }
+    println!("Success.");
+}
+
+fn get_scheduler(pid: libc::pid_t) -> (libc::c_int, libc::c_int) {
+    let policy = unsafe { libc::sched_getscheduler(pid) };
+    assert!(policy >= 0, "errno: {}", test_utils::get_errno());
+
+    let mut param = libc::sched_param { sched_priority: -1 };
+    assert_eq!(unsafe { libc::sched_getparam(pid, &mut param) }, 0);
+
+    (policy, param.sched_priority)
+}
+
+fn set_scheduler(pid: libc::pid_t, policy: libc::c_int, priority: libc::c_int) -> libc::c_int {
+    let param = libc::sched_param {
+        sched_priority: priority,
+    };
+    unsafe { libc::sched_setscheduler(pid, policy, &param) }
+}
+
+fn priority_range() {
+    for policy in [libc::SCHED_FIFO, libc::SCHED_RR] {
+        assert_eq!(unsafe { libc::sched_get_priority_min(policy) }, 1);
+        assert_eq!(unsafe { libc::sched_get_priority_max(policy) }, 99);
+    }
+    for policy in [libc::SCHED_OTHER, libc::SCHED_BATCH, libc::SCHED_IDLE] {
+        assert_eq!(unsafe { libc::sched_get_priority_min(policy) }, 0);
+        assert_eq!(unsafe { libc::sched_get_priority_max(policy) }, 0);
+    }
+
+    assert_eq!(unsafe { libc::sched_get_priority_max(1234) }, -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+    assert_eq!(unsafe { libc::sched_get_priority_min(1234) }, -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+}
+
+fn default_policy() {
+    for pid in [0, unsafe { libc::getpid() }] {
+        assert_eq!(get_scheduler(pid), (libc::SCHED_OTHER, 0));
+    }
+}
+
+fn invalid_params() {
+    // priority out of range for the policy
+    for (policy, priority) in [
+        (libc::SCHED_RR, 0),
+        (libc::SCHED_RR, 100),
+        (libc::SCHED_FIFO, 0),
+        (libc::SCHED_FIFO, 100),
+        (libc::SCHED_OTHER, 1),
+        (libc::SCHED_BATCH, -1),
+    ] {
+        assert_eq!(set_scheduler(0, policy, priority), -1);
+        assert_eq!(test_utils::get_errno(), libc::EINVAL);
+    }
+
+    // unknown policy
+    assert_eq!(set_scheduler(0, 1234, 0), -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+
+    // negative pid
+    assert_eq!(set_scheduler(-1, libc::SCHED_OTHER, 0), -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+    assert_eq!(unsafe { libc::sched_getscheduler(-1) }, -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+
+    // non-existent thread
+    assert_eq!(unsafe { libc::sched_getscheduler(i32::MAX) }, -1);
+    assert_eq!(test_utils::get_errno(), libc::ESRCH);
+
+    // the failed calls shouldn't have changed anything
+    assert_eq!(get_scheduler(0), (libc::SCHED_OTHER, 0));
+}
+
+fn set_batch() {
+    assert_eq!(set_scheduler(0, libc::SCHED_BATCH, 0), 0);
+    assert_eq!(get_scheduler(0), (libc::SCHED_BATCH, 0));
+
+    assert_eq!(set_scheduler(0, libc::SCHED_OTHER, 0), 0);
+    assert_eq!(get_scheduler(0), (libc::SCHED_OTHER, 0));
+}
+
+fn set_round_robin() {
+    let pid = unsafe { libc::getpid() };
+
+    assert_eq!(set_scheduler(pid, libc::SCHED_RR, 50), 0);
+    assert_eq!(get_scheduler(pid), (libc::SCHED_RR, 50));
+    assert_eq!(get_scheduler(0), (libc::SCHED_RR, 50));
+
+    // change only the priority
+    let param = libc::sched_param { sched_priority: 10 };
+    assert_eq!(unsafe { libc::sched_setparam(0, &param) }, 0);
+    assert_eq!(get_scheduler(0), (libc::SCHED_RR, 10));
+
+    // the priority must still be valid for the current policy
+    let param = libc::sched_param { sched_priority: 0 };
+    assert_eq!(unsafe { libc::sched_setparam(0, &param) }, -1);
+    assert_eq!(test_utils::get_errno(), libc::EINVAL);
+
+    // a new thread inherits the policy
+    let child = std::thread::spawn(|| get_scheduler(0)).join().unwrap();
+    assert_eq!(child, (libc::SCHED_RR, 10));
+
+    // unless the reset-on-fork flag is set
+    assert_eq!(
+        set_scheduler(0, libc::SCHED_RR | libc::SCHED_RESET_ON_FORK, 50),
+        0
+    );
+    assert_eq!(
+        get_scheduler(0),
+        (libc::SCHED_RR | libc::SCHED_RESET_ON_FORK, 50)
+    );
+    let child = std::thread::spawn(|| get_scheduler(0)).join().unwrap();
+    assert_eq!(child, (libc::SCHED_OTHER, 0));
+
+    assert_eq!(set_scheduler(0, libc::SCHED_OTHER, 0), 0);
+    assert_eq!(get_scheduler(0), (libc::SCHED_OTHER, 0));
+}
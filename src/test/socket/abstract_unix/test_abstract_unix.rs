@@ -0,0 +1,185 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client connects to a server on the same host using a unix socket bound to an abstract name,
+//! and the name is freed when the server closes its listening socket. A process on a different
+//! host has its own abstract namespace and can't see the server's name.
+//!
+//! Usage: `test_abstract_unix <server|client|other-host>`
+
+/// An abstract name (not including the leading nul byte) with an embedded nul byte.
+const NAME: &[u8] = b"shadow\0abstract";
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 2 {
+        return Err(format!("Usage: {} <server|client|other-host>", args[0]));
+    }
+
+    match args[1].as_str() {
+        "server" => run_server()?,
+        "client" => run_client()?,
+        "other-host" => run_other_host()?,
+        x => return Err(format!("Unknown mode '{x}'")),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+/// Get the socket address for the abstract name `name`.
+fn abstract_addr(name: &[u8]) -> (libc::sockaddr_un, libc::socklen_t) {
+    let mut addr = libc::sockaddr_un {
+        sun_family: libc::AF_UNIX as u16,
+        sun_path: [0; 108],
+    };
+
+    // sun_path[0] is the nul byte that makes this an abstract address
+    for (dst, src) in addr.sun_path[1..].iter_mut().zip(name) {
+        *dst = *src as libc::c_char;
+    }
+
+    let len = std::mem::size_of::<libc::sa_family_t>() + 1 + name.len();
+    (addr, len as libc::socklen_t)
+}
+
+fn bind_abstract(fd: libc::c_int, name: &[u8]) -> libc::c_int {
+    let (addr, addr_len) = abstract_addr(name);
+    unsafe {
+        libc::bind(
+            fd,
+            std::ptr::from_ref(&addr) as *const libc::sockaddr,
+            addr_len,
+        )
+    }
+}
+
+fn new_unix_socket() -> libc::c_int {
+    let fd = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0) };
+    assert!(fd >= 0);
+    fd
+}
+
+fn run_server() -> Result<(), String> {
+    let fd_listen = new_unix_socket();
+    let fd_other = new_unix_socket();
+
+    test_utils::run_and_close_fds(&[fd_other], || {
+        test_utils::result_assert_eq(bind_abstract(fd_listen, NAME), 0, "bind() failed")?;
+
+        // the name is already in use
+        let rv = bind_abstract(fd_other, NAME);
+        test_utils::result_assert_eq(rv, -1, "Second bind() succeeded")?;
+        test_utils::result_assert_eq(
+            test_utils::get_errno(),
+            libc::EADDRINUSE,
+            "Unexpected errno",
+        )?;
+
+        // a name that differs only after the embedded nul is a different name
+        let rv = bind_abstract(fd_other, &NAME[..NAME.len() - 1]);
+        test_utils::result_assert_eq(rv, 0, "bind() of a different name failed")?;
+
+        nix::sys::socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+        let fd_peer = nix::sys::socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+        test_utils::run_and_close_fds(&[fd_peer], || {
+            let mut buf = [0u8; 5];
+            let rv = unsafe { libc::recv(fd_peer, buf.as_mut_ptr() as *mut libc::c_void, 5, 0) };
+            test_utils::result_assert_eq(rv, 5, "recv() failed")?;
+            test_utils::result_assert_eq(&buf, b"hello", "Unexpected message")?;
+
+            let rv = unsafe { libc::send(fd_peer, b"world".as_ptr() as *const libc::c_void, 5, 0) };
+            test_utils::result_assert_eq(rv, 5, "send() failed")?;
+
+            Ok(())
+        })
+    })?;
+
+    // closing the listening socket frees the name immediately
+    nix::unistd::close(fd_listen).map_err(|e| e.to_string())?;
+
+    let fd_rebind = new_unix_socket();
+    test_utils::run_and_close_fds(&[fd_rebind], || {
+        let rv = bind_abstract(fd_rebind, NAME);
+        test_utils::result_assert_eq(rv, 0, "bind() after close failed")
+    })
+}
+
+fn run_client() -> Result<(), String> {
+    let fd = new_unix_socket();
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let (addr, addr_len) = abstract_addr(NAME);
+        let rv = unsafe {
+            libc::connect(
+                fd,
+                std::ptr::from_ref(&addr) as *const libc::sockaddr,
+                addr_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 0, "connect() failed")?;
+
+        // the peer address includes the full name, including the embedded nul
+        let mut peer_addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+        let mut peer_addr_len = std::mem::size_of_val(&peer_addr) as libc::socklen_t;
+        let rv = unsafe {
+            libc::getpeername(
+                fd,
+                std::ptr::from_mut(&mut peer_addr) as *mut libc::sockaddr,
+                &mut peer_addr_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 0, "getpeername() failed")?;
+        test_utils::result_assert_eq(peer_addr_len, addr_len, "Unexpected peer address length")?;
+        test_utils::result_assert_eq(
+            &peer_addr.sun_path[..NAME.len() + 1],
+            &addr.sun_path[..NAME.len() + 1],
+            "Unexpected peer address",
+        )?;
+
+        let rv = unsafe { libc::send(fd, b"hello".as_ptr() as *const libc::c_void, 5, 0) };
+        test_utils::result_assert_eq(rv, 5, "send() failed")?;
+
+        let mut buf = [0u8; 5];
+        let rv = unsafe { libc::recv(fd, buf.as_mut_ptr() as *mut libc::c_void, 5, 0) };
+        test_utils::result_assert_eq(rv, 5, "recv() failed")?;
+        test_utils::result_assert_eq(&buf, b"world", "Unexpected message")?;
+
+        Ok(())
+    })
+}
+
+/// Runs on a different host while the server is listening.
+fn run_other_host() -> Result<(), String> {
+    let fd_client = new_unix_socket();
+    let fd_bind = new_unix_socket();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_bind], || {
+        // the server's name doesn't exist in this host's namespace
+        let (addr, addr_len) = abstract_addr(NAME);
+        let rv = unsafe {
+            libc::connect(
+                fd_client,
+                std::ptr::from_ref(&addr) as *const libc::sockaddr,
+                addr_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, -1, "connect() to another host's name succeeded")?;
+        test_utils::result_assert_eq(
+            test_utils::get_errno(),
+            libc::ECONNREFUSED,
+            "Unexpected errno",
+        )?;
+
+        // so the same name can be bound here
+        test_utils::result_assert_eq(
+            bind_abstract(fd_bind, NAME),
+            0,
+            "bind() of another host's name failed",
+        )
+    })
+}
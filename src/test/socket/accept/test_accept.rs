@@ -763,6 +763,10 @@ fn test_correctness(
     let rv = unsafe { libc::usleep(10000) };
     assert_eq!(rv, 0);
 
+    // the client's address, which should be returned as the peer address
+    let client_name =
+        nix::sys::socket::getsockname::<nix::sys::socket::SockaddrStorage>(fd_client).unwrap();
+
     let accept_addr = match domain {
         libc::AF_INET => SockAddr::dummy_init_inet(),
         libc::AF_UNIX => SockAddr::dummy_init_unix(),
@@ -838,9 +842,10 @@ fn test_correctness(
                     libc::AF_INET as u16,
                     "Unexpected family",
                 )?;
-                test_utils::result_assert(
-                    args.addr.unwrap().as_inet().unwrap().sin_port != 0u16.to_be(),
-                    "Unexpected port",
+                test_utils::result_assert_eq(
+                    u16::from_be(args.addr.unwrap().as_inet().unwrap().sin_port),
+                    client_name.as_sockaddr_in().unwrap().port(),
+                    "Port doesn't match the client's port",
                 )?;
                 test_utils::result_assert_eq(
                     args.addr.unwrap().as_inet().unwrap().sin_addr.s_addr,
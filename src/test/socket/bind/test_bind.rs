@@ -330,7 +330,7 @@ fn test_all_ports_used() -> Result<(), String> {
     let mut fds_used = vec![];
 
     fn inner(fds_used: &mut Vec<i32>) -> Result<(), String> {
-        // shadow will only assign ports >= 10_000 (MIN_RANDOM_PORT)
+        // shadow will only assign ports from its ephemeral port range (32768-60999 by default)
         for port in 10_000..=u16::MAX {
             let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
             assert!(fd >= 0);
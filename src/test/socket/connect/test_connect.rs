@@ -61,6 +61,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_null_addr,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_invalid_addr_ptr",
+            test_invalid_addr_ptr,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
         test_utils::ShadowTest::new(
             "test_short_len",
             test_short_len,
@@ -76,6 +81,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
             test_recv_original_bind_port,
             set![TestEnv::Libc, TestEnv::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "test_nonblocking_connect_stages",
+            test_nonblocking_connect_stages,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
     ];
 
     // inet-only tests
@@ -90,6 +100,12 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
                     move || test_non_existent_server(sock_type, flag),
                     set![TestEnv::Libc],
                 ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_non_simulated_address"),
+                    move || test_non_simulated_address(sock_type, flag),
+                    // a real host would try to reach the address
+                    set![TestEnv::Shadow],
+                ),
                 test_utils::ShadowTest::new(
                     &append_args("test_port_zero"),
                     move || test_port_zero(sock_type, flag),
@@ -367,6 +383,23 @@ fn test_null_addr() -> Result<(), String> {
     test_utils::run_and_close_fds(&[fd], || check_connect_call(&args, Some(libc::EFAULT)))
 }
 
+/// Test connect() using a valid fd, but with an address pointer that can't be read.
+fn test_invalid_addr_ptr() -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        for ptr in test_utils::invalid_pointers() {
+            let addr_len = std::mem::size_of::<libc::sockaddr_in>() as u32;
+            test_utils::check_system_call!(
+                || unsafe { libc::connect(fd, ptr as *const libc::sockaddr, addr_len) },
+                &[libc::EFAULT]
+            )?;
+        }
+        Ok(())
+    })
+}
+
 /// Test connect() using a valid fd and address, but an address length that is too low.
 fn test_short_len() -> Result<(), String> {
     let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
@@ -445,6 +478,52 @@ fn test_non_existent_server(sock_type: libc::c_int, flag: libc::c_int) -> Result
     test_utils::run_and_close_fds(&[fd], || check_connect_call(&args, expected_errno))
 }
 
+/// Test connect() and sendto() to a public address that doesn't belong to any simulated host.
+fn test_non_simulated_address(sock_type: libc::c_int, flag: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, sock_type | flag, 0) };
+    assert!(fd >= 0);
+
+    let addr = libc::sockaddr_in {
+        sin_family: libc::AF_INET as u16,
+        sin_port: 80u16.to_be(),
+        sin_addr: libc::in_addr {
+            s_addr: u32::from(std::net::Ipv4Addr::new(8, 8, 8, 8)).to_be(),
+        },
+        sin_zero: [0; 8],
+    };
+
+    let args = ConnectArguments {
+        fd,
+        addr: Some(SockAddr::Inet(addr)),
+        addr_len: std::mem::size_of_val(&addr) as u32,
+    };
+
+    test_utils::run_and_close_fds(&[fd], || {
+        check_connect_call(&args, Some(libc::ENETUNREACH))?;
+
+        if sock_type == libc::SOCK_DGRAM {
+            let rv = unsafe {
+                libc::sendto(
+                    fd,
+                    [1u8, 2, 3].as_ptr() as *const libc::c_void,
+                    3,
+                    0,
+                    std::ptr::from_ref(&addr) as *const libc::sockaddr,
+                    std::mem::size_of_val(&addr) as libc::socklen_t,
+                )
+            };
+            test_utils::result_assert_eq(rv, -1, "Expected sendto() to fail")?;
+            test_utils::result_assert_eq(
+                test_utils::get_errno(),
+                libc::ENETUNREACH,
+                "Unexpected errno for sendto()",
+            )?;
+        }
+
+        Ok(())
+    })
+}
+
 /// Test connect() to an address with port 0.
 fn test_port_zero(sock_type: libc::c_int, flag: libc::c_int) -> Result<(), String> {
     let fd = unsafe { libc::socket(libc::AF_INET, sock_type | flag, 0) };
@@ -1144,6 +1223,83 @@ fn test_server_close_during_blocking_connect(
     Ok(())
 }
 
+/// Test the return values of connect() and getsockopt(SO_ERROR) at each stage of a non-blocking
+/// TCP connection attempt.
+fn test_nonblocking_connect_stages() -> Result<(), String> {
+    let fd_server = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    let fd_client_1 = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    let fd_client_2 =
+        unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0) };
+    assert!(fd_server >= 0);
+    assert!(fd_client_1 >= 0);
+    assert!(fd_client_2 >= 0);
+
+    let (server_addr, server_addr_len) = socket_utils::autobind_helper(fd_server, libc::AF_INET);
+
+    nix::sys::socket::listen(fd_server, 0).map_err(|e| e.to_string())?;
+
+    let args_1 = ConnectArguments {
+        fd: fd_client_1,
+        addr: Some(server_addr),
+        addr_len: server_addr_len,
+    };
+
+    let args_2 = ConnectArguments {
+        fd: fd_client_2,
+        addr: Some(server_addr),
+        addr_len: server_addr_len,
+    };
+
+    test_utils::run_and_close_fds(&[fd_server, fd_client_1, fd_client_2], || {
+        // fill the server's accept queue so that the server drops the SYN packets of the
+        // non-blocking socket, which keeps its handshake in progress until we accept()
+        check_connect_call(&args_1, None)?;
+
+        // the first connect() starts the handshake
+        check_connect_call(&args_2, Some(libc::EINPROGRESS))?;
+
+        // the handshake can't complete while the accept queue is full
+        check_connect_call(&args_2, Some(libc::EALREADY))?;
+        test_utils::result_assert(
+            !test_utils::is_writable(fd_client_2, 0).unwrap(),
+            "Socket was writable before the handshake completed",
+        )?;
+
+        // make room in the accept queue
+        let accepted_fd = nix::sys::socket::accept(fd_server).unwrap();
+        nix::unistd::close(accepted_fd).unwrap();
+
+        // the socket becomes writable when the handshake completes after the client's SYN is
+        // retransmitted
+        test_utils::result_assert(
+            test_utils::is_writable(fd_client_2, 5000).unwrap(),
+            "Socket did not become writable after the handshake",
+        )?;
+
+        // there was no error
+        let mut error: libc::c_int = -1;
+        let mut error_len = std::mem::size_of_val(&error) as libc::socklen_t;
+        let rv = unsafe {
+            libc::getsockopt(
+                fd_client_2,
+                libc::SOL_SOCKET,
+                libc::SO_ERROR,
+                std::ptr::from_mut(&mut error) as *mut libc::c_void,
+                &mut error_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+        test_utils::result_assert_eq(error, 0, "Unexpected SO_ERROR value")?;
+
+        // like Linux, the first connect() after the handshake reports the success, and later
+        // connect() calls return EISCONN
+        check_connect_call(&args_2, None)?;
+        check_connect_call(&args_2, Some(libc::EISCONN))?;
+
+        Ok(())
+    })
+}
+
 fn check_connect_call(
     args: &ConnectArguments,
     expected_errno: Option<libc::c_int>,
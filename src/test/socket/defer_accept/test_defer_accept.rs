@@ -0,0 +1,235 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests that a listening socket with `TCP_DEFER_ACCEPT` doesn't accept connections until the peer
+//! has sent data or the timeout has passed.
+
+use std::time::Duration;
+
+use nix::sys::socket::MsgFlags;
+use test_utils::set;
+use test_utils::socket_utils;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_defer_accept_option",
+            test_defer_accept_option,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_accept_waits_for_data",
+            test_accept_waits_for_data,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_accept_after_timeout",
+            test_accept_after_timeout,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_reset_before_accept",
+            test_reset_before_accept,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ]
+}
+
+fn set_defer_accept(fd: libc::c_int, secs: libc::c_int) {
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_DEFER_ACCEPT,
+            std::ptr::from_ref(&secs) as *const libc::c_void,
+            std::mem::size_of_val(&secs) as libc::socklen_t,
+        )
+    };
+    assert_eq!(rv, 0);
+}
+
+fn get_defer_accept(fd: libc::c_int) -> libc::c_int {
+    let mut secs: libc::c_int = -1;
+    let mut secs_len = std::mem::size_of_val(&secs) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_DEFER_ACCEPT,
+            std::ptr::from_mut(&mut secs) as *mut libc::c_void,
+            &mut secs_len,
+        )
+    };
+    assert_eq!(rv, 0);
+    assert_eq!(secs_len as usize, std::mem::size_of_val(&secs));
+    secs
+}
+
+/// Returns a non-blocking listening socket with the given `TCP_DEFER_ACCEPT` timeout, and a
+/// connected client socket.
+fn connect_helper(defer_secs: libc::c_int) -> Result<(libc::c_int, libc::c_int), String> {
+    let fd_listen =
+        unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0) };
+    let fd_client = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd_listen >= 0);
+    assert!(fd_client >= 0);
+
+    set_defer_accept(fd_listen, defer_secs);
+
+    let (server_addr, server_addr_len) = socket_utils::autobind_helper(fd_listen, libc::AF_INET);
+    nix::sys::socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    // the handshake completes even though the server won't accept the connection yet
+    let rv = unsafe { libc::connect(fd_client, server_addr.as_ptr(), server_addr_len) };
+    test_utils::result_assert_eq(rv, 0, "connect() failed")?;
+
+    Ok((fd_listen, fd_client))
+}
+
+/// Check that the listening socket doesn't have a connection to accept.
+fn check_nothing_to_accept(fd_listen: libc::c_int) -> Result<(), String> {
+    let mut poll_fds = [nix::poll::PollFd::new(
+        fd_listen,
+        nix::poll::PollFlags::POLLIN,
+    )];
+    let rv = nix::poll::poll(&mut poll_fds, 0).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(rv, 0, "Listening socket is readable")?;
+
+    let rv = nix::sys::socket::accept(fd_listen);
+    test_utils::result_assert_eq(
+        rv,
+        Err(nix::errno::Errno::EAGAIN),
+        "accept() returned a connection",
+    )
+}
+
+/// The timeout is rounded up like linux, and non-positive values disable the option.
+fn test_defer_accept_option() -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        test_utils::result_assert_eq(get_defer_accept(fd), 0, "Unexpected default")?;
+
+        set_defer_accept(fd, 1);
+        test_utils::result_assert_eq(get_defer_accept(fd), 1, "Unexpected timeout")?;
+
+        // linux rounds this up to the total time of 3 syn-ack retransmissions (1+2+4 seconds)
+        set_defer_accept(fd, 5);
+        test_utils::result_assert_eq(get_defer_accept(fd), 7, "Unexpected timeout")?;
+
+        set_defer_accept(fd, -1);
+        test_utils::result_assert_eq(get_defer_accept(fd), 0, "Unexpected timeout")?;
+
+        Ok(())
+    })
+}
+
+/// A client connects but doesn't send anything, so the server can't accept the connection until
+/// the client sends data.
+fn test_accept_waits_for_data() -> Result<(), String> {
+    let (fd_listen, fd_client) = connect_helper(5)?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd_client], || {
+        std::thread::sleep(Duration::from_millis(100));
+        check_nothing_to_accept(fd_listen)?;
+
+        let rv = nix::sys::socket::send(fd_client, b"hello", MsgFlags::empty())
+            .map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 5, "send() failed")?;
+
+        // shadow needs to run events
+        std::thread::sleep(Duration::from_millis(100));
+
+        let fd_peer =
+            nix::sys::socket::accept4(fd_listen, nix::sys::socket::SockFlag::SOCK_NONBLOCK)
+                .map_err(|e| e.to_string())?;
+
+        test_utils::run_and_close_fds(&[fd_peer], || {
+            // the data is ready as soon as the connection is accepted
+            let mut buf = [0u8; 10];
+            let rv = nix::sys::socket::recv(fd_peer, &mut buf, MsgFlags::empty())
+                .map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(&buf[..rv], &b"hello"[..], "Unexpected data")
+        })
+    })
+}
+
+/// A client connects but doesn't send anything, and the server accepts the connection anyway once
+/// the timeout has passed.
+fn test_accept_after_timeout() -> Result<(), String> {
+    let (fd_listen, fd_client) = connect_helper(1)?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd_client], || {
+        std::thread::sleep(Duration::from_millis(100));
+        check_nothing_to_accept(fd_listen)?;
+
+        std::thread::sleep(Duration::from_secs(2));
+
+        let fd_peer =
+            nix::sys::socket::accept4(fd_listen, nix::sys::socket::SockFlag::SOCK_NONBLOCK)
+                .map_err(|e| e.to_string())?;
+
+        test_utils::run_and_close_fds(&[fd_peer], || {
+            // there's no data to read
+            let mut buf = [0u8; 10];
+            let rv = nix::sys::socket::recv(fd_peer, &mut buf, MsgFlags::empty());
+            test_utils::result_assert_eq(rv, Err(nix::errno::Errno::EAGAIN), "Unexpected recv()")
+        })
+    })
+}
+
+/// A client connects but resets the connection before sending anything, so there's nothing to
+/// accept even after the timeout has passed.
+fn test_reset_before_accept() -> Result<(), String> {
+    let (fd_listen, fd_client) = connect_helper(1)?;
+
+    test_utils::run_and_close_fds(&[fd_listen], || {
+        std::thread::sleep(Duration::from_millis(100));
+        check_nothing_to_accept(fd_listen)?;
+
+        // closing with a zero linger timeout sends a RST
+        let linger = libc::linger {
+            l_onoff: 1,
+            l_linger: 0,
+        };
+        let rv = unsafe {
+            libc::setsockopt(
+                fd_client,
+                libc::SOL_SOCKET,
+                libc::SO_LINGER,
+                std::ptr::from_ref(&linger) as *const libc::c_void,
+                std::mem::size_of_val(&linger) as libc::socklen_t,
+            )
+        };
+        assert_eq!(rv, 0);
+        assert_eq!(unsafe { libc::close(fd_client) }, 0);
+
+        std::thread::sleep(Duration::from_secs(2));
+        check_nothing_to_accept(fd_listen)
+    })
+}
@@ -0,0 +1,168 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client uploads data to a server over a congested link, with explicit congestion notification
+//! (ECN) enabled in the shadow config. Both sides should negotiate ECN, and the congested router
+//! queue marks packets rather than dropping them, so the client should reduce its congestion
+//! window in response to the echoed marks without ever retransmitting.
+//!
+//! Usage: `test_ecn server <port>` or `test_ecn client <server-ip> <port>`
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use nix::sys::socket::{self, AddressFamily, MsgFlags, Shutdown, SockFlag, SockType, SockaddrIn};
+
+const UPLOAD_LEN: usize = 2_000_000;
+
+// from 'netinet/tcp.h'
+const TCPI_OPT_ECN: u8 = 8;
+const TCPI_OPT_ECN_SEEN: u8 = 16;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} server <port> | {0} client <server-ip> <port>",
+        args[0]
+    );
+
+    match args.get(1).map(String::as_str) {
+        Some("server") if args.len() == 3 => {
+            let port = args[2].parse().map_err(|e| format!("Bad port: {e}"))?;
+            run_server(port)?;
+        }
+        Some("client") if args.len() == 4 => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            let port = args[3].parse().map_err(|e| format!("Bad port: {e}"))?;
+            run_client(SocketAddrV4::new(ip, port))?;
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+/// The fields of `struct tcp_info` that we use.
+struct TcpInfo {
+    options: u8,
+    snd_ssthresh: u32,
+    total_retrans: u32,
+}
+
+fn get_tcp_info(fd: libc::c_int) -> Result<TcpInfo, String> {
+    // the libc package doesn't expose 'struct tcp_info', so we read it as an array; 'tcpi_options'
+    // is the sixth u8 field, and the u32 fields follow the 8 bytes of u8 fields
+    let mut info = [0u32; 26];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(
+        info_len as usize,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+
+    Ok(TcpInfo {
+        options: info[1].to_ne_bytes()[1],
+        snd_ssthresh: info[19],
+        total_retrans: info[25],
+    })
+}
+
+/// Accept a connection, read everything that the client sends, and then send a single byte back.
+fn run_server(port: u16) -> Result<(), String> {
+    let fd_listen = socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        let mut buf = vec![0u8; 65536];
+        let mut received = 0;
+        loop {
+            let rv = socket::recv(fd, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            if rv == 0 {
+                break;
+            }
+            received += rv;
+        }
+        test_utils::result_assert_eq(received, UPLOAD_LEN, "Unexpected number of bytes")?;
+
+        let info = get_tcp_info(fd)?;
+        test_utils::result_assert(
+            info.options & TCPI_OPT_ECN != 0,
+            "ECN wasn't negotiated by the server",
+        )?;
+        test_utils::result_assert(
+            info.options & TCPI_OPT_ECN_SEEN != 0,
+            "The server didn't receive any ECN-capable packets",
+        )?;
+
+        let rv = socket::send(fd, &[1], MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 1, "send() failed")
+    })
+}
+
+/// Upload data to the server over the congested link, and check that we reacted to the
+/// congestion.
+fn run_client(server_addr: SocketAddrV4) -> Result<(), String> {
+    let fd = socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let buf = vec![0u8; UPLOAD_LEN];
+        let mut sent = 0;
+        while sent < buf.len() {
+            sent += socket::send(fd, &buf[sent..], MsgFlags::empty()).map_err(|e| e.to_string())?;
+        }
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())?;
+
+        // the server responds once it has received everything, so all of our data has been acked
+        let mut byte = [0u8];
+        let rv = socket::recv(fd, &mut byte, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 1, "recv() failed")?;
+
+        let info = get_tcp_info(fd)?;
+        test_utils::result_assert(
+            info.options & TCPI_OPT_ECN != 0,
+            "ECN wasn't negotiated by the client",
+        )?;
+
+        // the congestion was signalled with ECN marks rather than packet loss
+        test_utils::result_assert_eq(info.total_retrans, 0, "Packets were retransmitted")?;
+
+        // the slow start threshold starts out unbounded, and is only reduced by congestion
+        test_utils::result_assert(
+            info.snd_ssthresh < i32::MAX as u32,
+            "The client didn't react to the congestion",
+        )?;
+
+        Ok(())
+    })
+}
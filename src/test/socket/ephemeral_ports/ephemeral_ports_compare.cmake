@@ -0,0 +1,10 @@
+set(FILE1 ${CMAKE_BINARY_DIR}/ephemeral_ports-a-shadow.data/hosts/testnode/test_ephemeral_ports.1000.stdout)
+set(FILE2 ${CMAKE_BINARY_DIR}/ephemeral_ports-b-shadow.data/hosts/testnode/test_ephemeral_ports.1000.stdout)
+
+execute_process(
+    COMMAND ${CMAKE_COMMAND} -E compare_files ${FILE1} ${FILE2}
+    RESULT_VARIABLE RESULT)
+message(STATUS "Diff returned ${RESULT} for 'diff ${FILE1} ${FILE2}'")
+if(RESULT)
+    message(FATAL_ERROR "Differences found; test failed")
+endif()
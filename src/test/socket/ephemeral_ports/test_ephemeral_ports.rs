@@ -0,0 +1,163 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests that ephemeral ports are assigned from the configured ephemeral port range. The assigned
+//! ports are printed so that the output of different runs can be compared.
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use nix::sys::socket::SockaddrIn;
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+// must match the range in the shadow config
+const EPHEMERAL_PORTS: std::ops::RangeInclusive<u16> = 40000..=40009;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let mut tests = vec![];
+
+    for sock_type in [libc::SOCK_STREAM, libc::SOCK_DGRAM] {
+        let append_args = |s| format!("{s} <type={sock_type}>");
+
+        tests.extend(vec![
+            test_utils::ShadowTest::new(
+                &append_args("test_bind_in_range"),
+                move || test_bind_in_range(sock_type),
+                set![TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_range_exhausted"),
+                move || test_range_exhausted(sock_type),
+                set![TestEnv::Shadow],
+            ),
+            // run after the other tests since closed tcp connections may keep their ports in use
+            test_utils::ShadowTest::new(
+                &append_args("test_connect_in_range"),
+                move || test_connect_in_range(sock_type),
+                set![TestEnv::Shadow],
+            ),
+        ]);
+    }
+
+    tests
+}
+
+fn new_socket(sock_type: libc::c_int) -> libc::c_int {
+    let fd = unsafe { libc::socket(libc::AF_INET, sock_type, 0) };
+    assert!(fd >= 0);
+    fd
+}
+
+fn bind_port_zero(fd: libc::c_int) -> Result<(), nix::errno::Errno> {
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0));
+    nix::sys::socket::bind(fd, &addr)
+}
+
+/// Returns the port that the socket is bound to, and checks that it's in the ephemeral range.
+fn get_ephemeral_port(fd: libc::c_int) -> Result<u16, String> {
+    let addr: SockaddrIn = nix::sys::socket::getsockname(fd).map_err(|e| e.to_string())?;
+    let port = addr.port();
+    test_utils::result_assert(
+        EPHEMERAL_PORTS.contains(&port),
+        &format!("Port {port} is not in the ephemeral range"),
+    )?;
+
+    // print the port so that we can compare the output of different runs
+    println!("Assigned port {port}");
+
+    Ok(port)
+}
+
+/// Sockets that bind to port 0 are assigned unique ports from the ephemeral range.
+fn test_bind_in_range(sock_type: libc::c_int) -> Result<(), String> {
+    let fds: Vec<_> = (0..5).map(|_| new_socket(sock_type)).collect();
+
+    test_utils::run_and_close_fds(&fds, || {
+        let mut ports = Vec::new();
+        for fd in &fds {
+            bind_port_zero(*fd).map_err(|e| e.to_string())?;
+            ports.push(get_ephemeral_port(*fd)?);
+        }
+
+        ports.sort();
+        ports.dedup();
+        test_utils::result_assert_eq(ports.len(), fds.len(), "Ports were assigned twice")
+    })
+}
+
+/// A socket that connects without binding is implicitly bound to a port from the ephemeral range.
+fn test_connect_in_range(sock_type: libc::c_int) -> Result<(), String> {
+    let fd_server = new_socket(sock_type);
+    let fd_client = new_socket(sock_type);
+
+    test_utils::run_and_close_fds(&[fd_server, fd_client], || {
+        bind_port_zero(fd_server).map_err(|e| e.to_string())?;
+        let server_addr: SockaddrIn =
+            nix::sys::socket::getsockname(fd_server).map_err(|e| e.to_string())?;
+
+        if sock_type == libc::SOCK_STREAM {
+            nix::sys::socket::listen(fd_server, 10).map_err(|e| e.to_string())?;
+        }
+
+        nix::sys::socket::connect(fd_client, &server_addr).map_err(|e| e.to_string())?;
+        get_ephemeral_port(fd_client)?;
+
+        Ok(())
+    })
+}
+
+/// Binding to port 0 fails when every port in the ephemeral range is in use, and succeeds again
+/// once a port is released.
+fn test_range_exhausted(sock_type: libc::c_int) -> Result<(), String> {
+    let mut fds: Vec<_> = EPHEMERAL_PORTS.map(|_| new_socket(sock_type)).collect();
+
+    for fd in &fds {
+        bind_port_zero(*fd).map_err(|e| e.to_string())?;
+        get_ephemeral_port(*fd)?;
+    }
+
+    let fd = new_socket(sock_type);
+    let rv = bind_port_zero(fd);
+    nix::unistd::close(fd).unwrap();
+    test_utils::result_assert_eq(
+        rv,
+        Err(nix::errno::Errno::EADDRINUSE),
+        "Bind succeeded with an exhausted range",
+    )?;
+
+    // release one of the ports
+    let released_fd = fds.remove(3);
+    let released_port = get_ephemeral_port(released_fd)?;
+    nix::unistd::close(released_fd).unwrap();
+
+    let fd = new_socket(sock_type);
+    fds.push(fd);
+
+    test_utils::run_and_close_fds(&fds, || {
+        bind_port_zero(fd).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(get_ephemeral_port(fd)?, released_port, "Unexpected port")
+    })
+}
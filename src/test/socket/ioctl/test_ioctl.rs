@@ -5,6 +5,7 @@
 
 use std::time::Duration;
 
+use linux_api::ioctls::IoctlRequest;
 use test_utils::set;
 use test_utils::socket_utils::{socket_init_helper, SocketInitMethod};
 use test_utils::TestEnvironment as TestEnv;
@@ -65,30 +66,31 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
         for &sock_type in sock_types.iter() {
             // add details to the test names to avoid duplicates
             let append_args =
-                |s| format!("{s} <init_method={init_method:?}, sock_type={sock_type}>");
+                |s: &str| format!("{s} <init_method={init_method:?}, sock_type={sock_type}>");
 
-            tests.extend(vec![
-                test_utils::ShadowTest::new(
-                    &append_args("test_fionread"),
-                    move || test_fionread(init_method, sock_type),
-                    // TODO: this isn't supported yet in shadow for unix sockets
-                    if init_method.domain() == libc::AF_UNIX {
-                        set![TestEnv::Libc]
-                    } else {
-                        set![TestEnv::Libc, TestEnv::Shadow]
-                    },
-                ),
-                test_utils::ShadowTest::new(
-                    &append_args("test_siocgstamp"),
-                    move || test_siocgstamp(init_method, sock_type),
+            tests.extend(vec![test_utils::ShadowTest::new(
+                &append_args("test_fionread"),
+                move || test_fionread(init_method, sock_type),
+                // TODO: this isn't supported yet in shadow for unix sockets
+                if init_method.domain() == libc::AF_UNIX {
+                    set![TestEnv::Libc]
+                } else {
+                    set![TestEnv::Libc, TestEnv::Shadow]
+                },
+            )]);
+
+            for request in [IoctlRequest::SIOCGSTAMP, IoctlRequest::SIOCGSTAMPNS] {
+                tests.push(test_utils::ShadowTest::new(
+                    &append_args(&format!("test_siocgstamp <request={request:?}>")),
+                    move || test_siocgstamp(init_method, sock_type, request),
                     // TODO: this isn't supported yet in shadow for unix sockets
                     if init_method.domain() == libc::AF_UNIX {
                         set![TestEnv::Libc]
                     } else {
                         set![TestEnv::Libc, TestEnv::Shadow]
                     },
-                ),
-            ]);
+                ));
+            }
         }
     }
 
@@ -199,8 +201,12 @@ fn test_fionread(init_method: SocketInitMethod, sock_type: libc::c_int) -> Resul
     })
 }
 
-/// Test ioctl() using the `SIOCGSTAMP` ioctl request.
-fn test_siocgstamp(init_method: SocketInitMethod, sock_type: libc::c_int) -> Result<(), String> {
+/// Test ioctl() using the `SIOCGSTAMP` or `SIOCGSTAMPNS` ioctl request.
+fn test_siocgstamp(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+    request: IoctlRequest,
+) -> Result<(), String> {
     let (fd_client, fd_peer) = socket_init_helper(
         init_method,
         sock_type,
@@ -209,16 +215,29 @@ fn test_siocgstamp(init_method: SocketInitMethod, sock_type: libc::c_int) -> Res
     );
 
     /// Returns the value if successful, otherwise returns the errno.
-    fn ioctl_siocgstamp(fd: libc::c_int) -> Result<libc::timeval, libc::c_int> {
-        // not currently available in the libc crate
-        use linux_api::ioctls::IoctlRequest::SIOCGSTAMP;
-
-        let mut out: libc::timeval = unsafe { std::mem::zeroed() };
-        let rv = unsafe { libc::ioctl(fd, SIOCGSTAMP as u64, &mut out) };
-        if rv != 0 {
-            return Err(test_utils::get_errno());
+    fn ioctl_siocgstamp(fd: libc::c_int, request: IoctlRequest) -> Result<Duration, libc::c_int> {
+        // these requests are not currently available in the libc crate
+        match request {
+            IoctlRequest::SIOCGSTAMP => {
+                let mut out: libc::timeval = unsafe { std::mem::zeroed() };
+                let rv = unsafe { libc::ioctl(fd, request as u64, &mut out) };
+                if rv != 0 {
+                    return Err(test_utils::get_errno());
+                }
+                Ok(Duration::from_secs(out.tv_sec.try_into().unwrap())
+                    + Duration::from_micros(out.tv_usec.try_into().unwrap()))
+            }
+            IoctlRequest::SIOCGSTAMPNS => {
+                let mut out: libc::timespec = unsafe { std::mem::zeroed() };
+                let rv = unsafe { libc::ioctl(fd, request as u64, &mut out) };
+                if rv != 0 {
+                    return Err(test_utils::get_errno());
+                }
+                Ok(Duration::from_secs(out.tv_sec.try_into().unwrap())
+                    + Duration::from_nanos(out.tv_nsec.try_into().unwrap()))
+            }
+            _ => unimplemented!(),
         }
-        Ok(out)
     }
 
     test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
@@ -229,14 +248,14 @@ fn test_siocgstamp(init_method: SocketInitMethod, sock_type: libc::c_int) -> Res
             _ => unimplemented!(),
         };
         test_utils::result_assert_eq(
-            ioctl_siocgstamp(fd_client),
+            ioctl_siocgstamp(fd_client, request),
             expected_result,
-            "Unexpected SIOCGSTAMP result",
+            &format!("Unexpected {request:?} result"),
         )?;
         test_utils::result_assert_eq(
-            ioctl_siocgstamp(fd_peer),
+            ioctl_siocgstamp(fd_peer, request),
             expected_result,
-            "Unexpected SIOCGSTAMP result",
+            &format!("Unexpected {request:?} result"),
         )?;
 
         // send data from the client to the peer
@@ -263,16 +282,16 @@ fn test_siocgstamp(init_method: SocketInitMethod, sock_type: libc::c_int) -> Res
             _ => unimplemented!(),
         };
         test_utils::result_assert_eq(
-            ioctl_siocgstamp(fd_peer),
+            ioctl_siocgstamp(fd_peer, request),
             expected_result,
-            "Unexpected SIOCGSTAMP result",
+            &format!("Unexpected {request:?} result"),
         )?;
 
         // receive data at the peer
         let flags = nix::sys::socket::MsgFlags::empty();
         nix::sys::socket::recv(fd_peer, &mut [0u8; 3], flags).unwrap();
 
-        // check the result of SIOCGSTAMP on the peer; only supported by udp sockets
+        // check the result of the request on the peer; only supported by udp sockets
         let expected_err = match (init_method.domain(), sock_type) {
             (libc::AF_INET, libc::SOCK_DGRAM) => None,
             (libc::AF_INET, _) => Some(libc::ENOENT),
@@ -282,9 +301,7 @@ fn test_siocgstamp(init_method: SocketInitMethod, sock_type: libc::c_int) -> Res
         match expected_err {
             None => {
                 // the receive time reported by the kernel
-                let recv_time = ioctl_siocgstamp(fd_peer).unwrap();
-                let recv_time = Duration::from_secs(recv_time.tv_sec.try_into().unwrap())
-                    + Duration::from_micros(recv_time.tv_usec.try_into().unwrap());
+                let recv_time = ioctl_siocgstamp(fd_peer, request).unwrap();
 
                 // Get the time difference between the send and receive. We can't know if the
                 // receive or send time will be smaller since the send time was measured after the
@@ -306,9 +323,9 @@ fn test_siocgstamp(init_method: SocketInitMethod, sock_type: libc::c_int) -> Res
                 test_utils::result_assert(difference < threshold, "Time difference was too large")?;
             }
             Some(e) => test_utils::result_assert_eq(
-                ioctl_siocgstamp(fd_peer),
+                ioctl_siocgstamp(fd_peer, request),
                 Err(e),
-                "Unexpected SIOCGSTAMP result",
+                &format!("Unexpected {request:?} result"),
             )?,
         }
 
@@ -0,0 +1,342 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for the `SO_LINGER` socket option, which changes how `close()` closes a TCP connection.
+
+use std::time::{Duration, Instant};
+
+use nix::errno::Errno;
+use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
+use nix::sys::socket::MsgFlags;
+use test_utils::set;
+use test_utils::socket_utils::{socket_init_helper, SocketInitMethod};
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let all_envs = set![TestEnv::Libc, TestEnv::Shadow];
+    vec![
+        test_utils::ShadowTest::new("test_sockopt", test_sockopt, all_envs.clone()),
+        test_utils::ShadowTest::new("test_abortive_close", test_abortive_close, all_envs.clone()),
+        test_utils::ShadowTest::new("test_graceful_close", test_graceful_close, all_envs.clone()),
+        test_utils::ShadowTest::new("test_linger_timeout", test_linger_timeout, all_envs.clone()),
+        test_utils::ShadowTest::new("test_linger_interrupted", test_linger_interrupted, all_envs),
+    ]
+}
+
+fn set_linger(fd: libc::c_int, onoff: libc::c_int, secs: libc::c_int) -> Result<(), Errno> {
+    let linger = libc::linger {
+        l_onoff: onoff,
+        l_linger: secs,
+    };
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_LINGER,
+            std::ptr::from_ref(&linger) as *const libc::c_void,
+            std::mem::size_of_val(&linger) as libc::socklen_t,
+        )
+    };
+    Errno::result(rv).map(|_| ())
+}
+
+fn get_linger(fd: libc::c_int) -> Result<libc::linger, Errno> {
+    let mut linger = libc::linger {
+        l_onoff: -1,
+        l_linger: -1,
+    };
+    let mut optlen = std::mem::size_of_val(&linger) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_LINGER,
+            std::ptr::from_mut(&mut linger) as *mut libc::c_void,
+            &mut optlen,
+        )
+    };
+    Errno::result(rv)?;
+    assert_eq!(optlen as usize, std::mem::size_of_val(&linger));
+    Ok(linger)
+}
+
+/// Set a small receive buffer on `fd_client` and a small send buffer on `fd_peer`, so that the
+/// buffers can be filled quickly.
+fn shrink_buffers(fd_client: libc::c_int, fd_peer: libc::c_int) {
+    for (fd, optname) in [(fd_client, libc::SO_RCVBUF), (fd_peer, libc::SO_SNDBUF)] {
+        let size: libc::c_int = 16384;
+        let rv = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                optname,
+                std::ptr::from_ref(&size) as *const libc::c_void,
+                std::mem::size_of_val(&size) as libc::socklen_t,
+            )
+        };
+        assert_eq!(rv, 0);
+    }
+}
+
+/// Send from `fd` until its send buffer and the peer's receive buffer are full. Returns the number
+/// of bytes sent.
+fn fill_buffers(fd: libc::c_int) -> Result<usize, String> {
+    let buf = vec![0u8; 10_000];
+    let mut sent = 0;
+
+    // data moves from the send buffer to the peer while we sleep, so keep going until no more data
+    // can be sent
+    loop {
+        while let Ok(n) = nix::sys::socket::send(fd, &buf, MsgFlags::MSG_DONTWAIT) {
+            sent += n;
+        }
+
+        std::thread::sleep(Duration::from_millis(100));
+
+        match nix::sys::socket::send(fd, &buf, MsgFlags::MSG_DONTWAIT) {
+            Ok(n) => sent += n,
+            Err(Errno::EAGAIN) => return Ok(sent),
+            Err(e) => return Err(format!("Unexpected send() error: {e}")),
+        }
+    }
+}
+
+/// Receive from `fd` until EOF or an error. Returns the number of bytes received and the error, if
+/// any.
+fn recv_all(fd: libc::c_int) -> (usize, Option<Errno>) {
+    let mut buf = vec![0u8; 10_000];
+    let mut received = 0;
+
+    loop {
+        match nix::sys::socket::recv(fd, &mut buf, MsgFlags::empty()) {
+            Ok(0) => return (received, None),
+            Ok(n) => received += n,
+            Err(e) => return (received, Some(e)),
+        }
+    }
+}
+
+/// Check the option's value after setting it.
+fn test_sockopt() -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let linger = get_linger(fd).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(linger.l_onoff, 0, "Linger should be disabled by default")?;
+
+        set_linger(fd, 1, 5).map_err(|e| e.to_string())?;
+        let linger = get_linger(fd).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(linger.l_onoff, 1, "Linger should be enabled")?;
+        test_utils::result_assert_eq(linger.l_linger, 5, "Unexpected linger timeout")?;
+
+        set_linger(fd, 0, 0).map_err(|e| e.to_string())?;
+        let linger = get_linger(fd).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(linger.l_onoff, 0, "Linger should be disabled")?;
+
+        // too short for a 'struct linger'
+        let val: libc::c_int = 1;
+        let rv = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_LINGER,
+                std::ptr::from_ref(&val) as *const libc::c_void,
+                std::mem::size_of_val(&val) as libc::socklen_t,
+            )
+        };
+        test_utils::result_assert_eq(
+            Errno::result(rv),
+            Err(Errno::EINVAL),
+            "Unexpected setsockopt() result",
+        )?;
+
+        Ok(())
+    })
+}
+
+/// Closing with a zero linger timeout resets the connection and discards unsent data.
+fn test_abortive_close() -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+    shrink_buffers(fd_client, fd_peer);
+
+    test_utils::run_and_close_fds(&[fd_client], || {
+        let sent = fill_buffers(fd_peer)?;
+
+        set_linger(fd_peer, 1, 0).map_err(|e| e.to_string())?;
+        nix::unistd::close(fd_peer).map_err(|e| e.to_string())?;
+
+        // give the reset time to arrive
+        std::thread::sleep(Duration::from_millis(100));
+
+        let (received, err) = recv_all(fd_client);
+        test_utils::result_assert_eq(
+            err,
+            Some(Errno::ECONNRESET),
+            "The connection should have been reset",
+        )?;
+        test_utils::result_assert(
+            received < sent,
+            &format!("Expected unsent data to be discarded, but received {received} of {sent}"),
+        )
+    })
+}
+
+/// Closing with a non-zero linger timeout blocks until the sent data has been acknowledged.
+fn test_graceful_close() -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+    shrink_buffers(fd_client, fd_peer);
+
+    test_utils::run_and_close_fds(&[fd_client], || {
+        let sent = fill_buffers(fd_peer)?;
+
+        // the data can't be acknowledged until the client reads from its full receive buffer
+        let reader = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(500));
+            recv_all(fd_client)
+        });
+
+        set_linger(fd_peer, 1, 10).map_err(|e| e.to_string())?;
+
+        let start = Instant::now();
+        nix::unistd::close(fd_peer).map_err(|e| e.to_string())?;
+        let elapsed = start.elapsed();
+
+        let (received, err) = reader.join().unwrap();
+
+        test_utils::result_assert(
+            elapsed >= Duration::from_millis(400) && elapsed < Duration::from_secs(10),
+            &format!("Expected close() to wait for the reader, but took {elapsed:?}"),
+        )?;
+        test_utils::result_assert_eq(err, None, "Unexpected recv() error")?;
+        test_utils::result_assert_eq(received, sent, "Not all data was received")
+    })
+}
+
+/// Closing with a non-zero linger timeout returns once the timeout has passed, and the data is
+/// still sent afterwards.
+fn test_linger_timeout() -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+    shrink_buffers(fd_client, fd_peer);
+
+    test_utils::run_and_close_fds(&[fd_client], || {
+        let sent = fill_buffers(fd_peer)?;
+
+        let reader = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_secs(3));
+            recv_all(fd_client)
+        });
+
+        set_linger(fd_peer, 1, 1).map_err(|e| e.to_string())?;
+
+        let start = Instant::now();
+        nix::unistd::close(fd_peer).map_err(|e| e.to_string())?;
+        let elapsed = start.elapsed();
+
+        let (received, err) = reader.join().unwrap();
+
+        // linux rounds timeouts to the kernel's clock tick, so it may be slightly late
+        test_utils::result_assert(
+            elapsed >= Duration::from_secs(1) && elapsed < Duration::from_millis(1100),
+            &format!("Expected close() to return after the timeout, but took {elapsed:?}"),
+        )?;
+        test_utils::result_assert_eq(err, None, "Unexpected recv() error")?;
+        test_utils::result_assert_eq(received, sent, "Not all data was received")
+    })
+}
+
+extern "C" fn noop_signal_handler(_signal: libc::c_int) {}
+
+/// A signal that interrupts a lingering close doesn't leave the descriptor open.
+fn test_linger_interrupted() -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+    shrink_buffers(fd_client, fd_peer);
+
+    // without SA_RESTART, so that the signal interrupts the close
+    let action = SigAction::new(
+        SigHandler::Handler(noop_signal_handler),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+    unsafe { signal::sigaction(Signal::SIGUSR1, &action) }.map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_client], || {
+        fill_buffers(fd_peer)?;
+
+        let tid = unsafe { libc::gettid() };
+        let pid = unsafe { libc::getpid() };
+        let signaller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(500));
+            assert_eq!(
+                unsafe { libc::syscall(libc::SYS_tgkill, pid, tid, libc::SIGUSR1) },
+                0
+            );
+        });
+
+        set_linger(fd_peer, 1, 10).map_err(|e| e.to_string())?;
+
+        let start = Instant::now();
+        let rv = nix::unistd::close(fd_peer);
+        let elapsed = start.elapsed();
+
+        signaller.join().unwrap();
+
+        // linux may or may not report the interruption, but always releases the descriptor
+        test_utils::result_assert(
+            matches!(rv, Ok(()) | Err(Errno::EINTR)),
+            &format!("Unexpected close() result: {rv:?}"),
+        )?;
+        test_utils::result_assert(
+            elapsed < Duration::from_secs(10),
+            &format!("Expected close() to be interrupted, but took {elapsed:?}"),
+        )?;
+        test_utils::result_assert_eq(
+            nix::fcntl::fcntl(fd_peer, nix::fcntl::FcntlArg::F_GETFD),
+            Err(Errno::EBADF),
+            "The descriptor should have been released",
+        )
+    })
+}
@@ -0,0 +1,245 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests that a tcp socket with a `TCP_MAXSEG` doesn't send segments larger than the mss.
+
+use test_utils::set;
+use test_utils::socket_utils;
+use test_utils::TestEnvironment as TestEnv;
+
+/// The mss used by linux before a connection's mss is known (`TCP_MSS_DEFAULT`).
+const DEFAULT_MSS: libc::c_int = 536;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_invalid_mss",
+            test_invalid_mss,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_unconnected_mss",
+            test_unconnected_mss,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_small_mss",
+            test_small_mss,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ]
+}
+
+fn set_mss(fd: libc::c_int, mss: libc::c_int) -> libc::c_int {
+    unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_MAXSEG,
+            std::ptr::from_ref(&mss) as *const libc::c_void,
+            std::mem::size_of_val(&mss) as libc::socklen_t,
+        )
+    }
+}
+
+fn get_mss(fd: libc::c_int) -> Result<libc::c_int, String> {
+    let mut mss: libc::c_int = 0;
+    let mut mss_len = std::mem::size_of_val(&mss) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_MAXSEG,
+            std::ptr::from_mut(&mut mss) as *mut libc::c_void,
+            &mut mss_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(mss_len, 4, "Unexpected option length")?;
+    Ok(mss)
+}
+
+/// Get the `tcpi_snd_mss` and `tcpi_rcv_mss` fields of the socket's `struct tcp_info`.
+fn get_info_mss(fd: libc::c_int) -> Result<(u32, u32), String> {
+    // the libc package doesn't expose 'struct tcp_info', so we only read the start of the struct;
+    // 'tcpi_snd_mss' and 'tcpi_rcv_mss' are the third and fourth u32 following 8 bytes of u8 fields
+    let mut info = [0u32; 6];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(
+        info_len as usize,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+    Ok((info[4], info[5]))
+}
+
+/// Setting an mss that's out of range is an error.
+fn test_invalid_mss() -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        for mss in [-1, 10, 87, 32768] {
+            test_utils::result_assert_eq(set_mss(fd, mss), -1, "setsockopt() succeeded")?;
+            test_utils::result_assert_eq(
+                test_utils::get_errno(),
+                libc::EINVAL,
+                "Unexpected errno",
+            )?;
+        }
+
+        // the option value is too short
+        let mss: u16 = 1000;
+        let rv = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_TCP,
+                libc::TCP_MAXSEG,
+                std::ptr::from_ref(&mss) as *const libc::c_void,
+                std::mem::size_of_val(&mss) as libc::socklen_t,
+            )
+        };
+        test_utils::result_assert_eq(rv, -1, "setsockopt() succeeded")?;
+        test_utils::result_assert_eq(test_utils::get_errno(), libc::EINVAL, "Unexpected errno")?;
+
+        // nothing was changed
+        test_utils::result_assert_eq(get_mss(fd)?, DEFAULT_MSS, "Unexpected mss")
+    })
+}
+
+/// An unconnected socket returns the user's mss, or the default mss if none was set.
+fn test_unconnected_mss() -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        test_utils::result_assert_eq(get_mss(fd)?, DEFAULT_MSS, "Unexpected default mss")?;
+
+        test_utils::result_assert_eq(set_mss(fd, 1000), 0, "setsockopt() failed")?;
+        test_utils::result_assert_eq(get_mss(fd)?, 1000, "Unexpected mss")?;
+
+        // larger than the path mtu allows, but there's no connection yet
+        test_utils::result_assert_eq(set_mss(fd, 9000), 0, "setsockopt() failed")?;
+        test_utils::result_assert_eq(get_mss(fd)?, 9000, "Unexpected mss")?;
+
+        // 0 resets the mss
+        test_utils::result_assert_eq(set_mss(fd, 0), 0, "setsockopt() failed")?;
+        test_utils::result_assert_eq(get_mss(fd)?, DEFAULT_MSS, "Unexpected mss")
+    })
+}
+
+/// A connection with a small mss only sends small segments.
+fn test_small_mss() -> Result<(), String> {
+    const MSS: libc::c_int = 1000;
+    const NUM_BYTES: usize = 100_000;
+
+    let fd_listen = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    let fd_send = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd_listen >= 0);
+    assert!(fd_send >= 0);
+
+    let (server_addr, server_addr_len) = socket_utils::autobind_helper(fd_listen, libc::AF_INET);
+
+    test_utils::run_and_close_fds(&[fd_listen, fd_send], || {
+        nix::sys::socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+        test_utils::result_assert_eq(set_mss(fd_send, MSS), 0, "setsockopt() failed")?;
+
+        let rv = unsafe { libc::connect(fd_send, server_addr.as_ptr(), server_addr_len) };
+        test_utils::result_assert_eq(rv, 0, "connect() failed")?;
+
+        let fd_recv = nix::sys::socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+        test_utils::run_and_close_fds(&[fd_recv], || {
+            // linux may subtract the size of tcp options from the mss
+            let mss = get_mss(fd_send)?;
+            test_utils::result_assert(mss > 0 && mss <= MSS, &format!("Unexpected mss {mss}"))?;
+
+            let (snd_mss, _) = get_info_mss(fd_send)?;
+            test_utils::result_assert(
+                snd_mss > 0 && snd_mss <= MSS as u32,
+                &format!("Unexpected tcpi_snd_mss {snd_mss}"),
+            )?;
+
+            // read from another thread so that a full send buffer can't block us forever
+            let reader = std::thread::spawn(move || {
+                let mut buf = vec![0u8; NUM_BYTES];
+                let mut bytes_read = 0;
+                while bytes_read < NUM_BYTES {
+                    let rv = unsafe {
+                        libc::recv(
+                            fd_recv,
+                            buf[bytes_read..].as_mut_ptr() as *mut libc::c_void,
+                            NUM_BYTES - bytes_read,
+                            0,
+                        )
+                    };
+                    assert!(rv > 0);
+                    bytes_read += rv as usize;
+                }
+                buf
+            });
+
+            let buf: Vec<u8> = (0..NUM_BYTES).map(|x| x as u8).collect();
+            let mut bytes_sent = 0;
+            while bytes_sent < NUM_BYTES {
+                let rv = unsafe {
+                    libc::send(
+                        fd_send,
+                        buf[bytes_sent..].as_ptr() as *const libc::c_void,
+                        NUM_BYTES - bytes_sent,
+                        0,
+                    )
+                };
+                test_utils::result_assert(rv > 0, "send() failed")?;
+                bytes_sent += rv as usize;
+            }
+
+            let received = reader.join().unwrap();
+            test_utils::result_assert(received == buf, "Received data differs from sent data")?;
+
+            // the receiver's estimate of the sender's mss is based on the largest segment it has
+            // received, so it grows past the default but never past our mss
+            let (_, rcv_mss) = get_info_mss(fd_recv)?;
+            test_utils::result_assert(
+                rcv_mss > DEFAULT_MSS as u32 && rcv_mss <= MSS as u32,
+                &format!("Unexpected tcpi_rcv_mss {rcv_mss}"),
+            )?;
+
+            Ok(())
+        })
+    })
+}
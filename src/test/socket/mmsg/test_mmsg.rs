@@ -0,0 +1,266 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client sends a batch of UDP datagrams with a single `sendmmsg()` call, and a server on
+//! another host receives all of them with a single `recvmmsg()` call. The client then sends two
+//! more datagrams one second apart, which the server uses to test the `recvmmsg()` timeout.
+//!
+//! Usage: `test_mmsg server <bind-ip> <port>` or `test_mmsg client <server-ip> <port>`
+
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+const NUM_MSGS: usize = 4;
+const MSG_LEN: usize = 100;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 4 {
+        return Err(format!("Usage: {} <server|client> <ip> <port>", args[0]));
+    }
+
+    let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+    let port: u16 = args[3].parse().map_err(|e| format!("Bad port: {e}"))?;
+
+    let addr = libc::sockaddr_in {
+        sin_family: libc::AF_INET as u16,
+        sin_port: port.to_be(),
+        sin_addr: libc::in_addr {
+            s_addr: u32::from(ip).to_be(),
+        },
+        sin_zero: [0; 8],
+    };
+
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || match args[1].as_str() {
+        "server" => run_server(fd, addr),
+        "client" => run_client(fd, addr),
+        x => Err(format!("Unknown mode '{x}'")),
+    })?;
+
+    println!("Success.");
+    Ok(())
+}
+
+/// The payload of the `i`th message.
+fn msg_payload(i: usize) -> [u8; MSG_LEN] {
+    [u8::try_from(i).unwrap() + 1; MSG_LEN]
+}
+
+fn run_server(fd: libc::c_int, bind_addr: libc::sockaddr_in) -> Result<(), String> {
+    let rv = unsafe {
+        libc::bind(
+            fd,
+            std::ptr::from_ref(&bind_addr) as *const libc::sockaddr,
+            std::mem::size_of_val(&bind_addr) as libc::socklen_t,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "bind() failed")?;
+
+    // an empty batch returns immediately
+    let rv = unsafe { libc::recvmmsg(fd, std::ptr::null_mut(), 0, 0, std::ptr::null_mut()) };
+    test_utils::result_assert_eq(rv, 0, "Unexpected result for an empty batch")?;
+
+    // wait for the first datagram, and then give the rest of the batch time to arrive
+    test_utils::result_assert(
+        test_utils::is_readable(fd, 5000).unwrap(),
+        "No datagrams arrived",
+    )?;
+    std::thread::sleep(Duration::from_millis(100));
+
+    // room for more messages than were sent
+    let mut bufs = [[0u8; MSG_LEN + 1]; 2 * NUM_MSGS];
+    let mut iovs: Vec<libc::iovec> = bufs
+        .iter_mut()
+        .map(|buf| libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        })
+        .collect();
+    let mut from_addrs: Vec<libc::sockaddr_in> = (0..iovs.len())
+        .map(|_| unsafe { std::mem::zeroed() })
+        .collect();
+    let mut msgs: Vec<libc::mmsghdr> = iovs
+        .iter_mut()
+        .zip(from_addrs.iter_mut())
+        .map(|(iov, from_addr)| libc::mmsghdr {
+            msg_hdr: libc::msghdr {
+                msg_name: std::ptr::from_mut(from_addr) as *mut libc::c_void,
+                msg_namelen: std::mem::size_of_val(from_addr) as libc::socklen_t,
+                msg_iov: iov,
+                msg_iovlen: 1,
+                msg_control: std::ptr::null_mut(),
+                msg_controllen: 0,
+                msg_flags: 0,
+            },
+            msg_len: 0,
+        })
+        .collect();
+
+    let mut timeout = libc::timespec {
+        tv_sec: 1,
+        tv_nsec: 0,
+    };
+    let rv = unsafe {
+        libc::recvmmsg(
+            fd,
+            msgs.as_mut_ptr(),
+            msgs.len().try_into().unwrap(),
+            libc::MSG_WAITFORONE,
+            &mut timeout,
+        )
+    };
+    test_utils::result_assert_eq(rv, NUM_MSGS as i32, "Unexpected number of messages")?;
+
+    // the timeout is updated with the remaining time
+    test_utils::result_assert(
+        (timeout.tv_sec, timeout.tv_nsec) <= (1, 0) && timeout.tv_sec >= 0,
+        "Unexpected remaining timeout",
+    )?;
+
+    for (i, msg) in msgs.iter().take(NUM_MSGS).enumerate() {
+        test_utils::result_assert_eq(msg.msg_len as usize, MSG_LEN, "Unexpected message length")?;
+        test_utils::result_assert_eq(
+            msg.msg_hdr.msg_namelen as usize,
+            std::mem::size_of::<libc::sockaddr_in>(),
+            "Unexpected address length",
+        )?;
+        test_utils::result_assert_eq(
+            from_addrs[i].sin_family,
+            libc::AF_INET as u16,
+            "Unexpected address family",
+        )?;
+        test_utils::result_assert_eq(
+            &bufs[i][..MSG_LEN],
+            &msg_payload(i)[..],
+            "Unexpected message payload",
+        )?;
+    }
+
+    // all of the datagrams from the batch were received
+    let rv = unsafe {
+        libc::recvmmsg(
+            fd,
+            msgs.as_mut_ptr(),
+            msgs.len().try_into().unwrap(),
+            libc::MSG_DONTWAIT,
+            std::ptr::null_mut(),
+        )
+    };
+    test_utils::result_assert_eq(rv, -1, "Unexpected datagrams")?;
+    test_utils::result_assert_eq(test_utils::get_errno(), libc::EAGAIN, "Unexpected errno")?;
+
+    // no datagram arrives before the timeout, so we stop waiting at the deadline
+    let start = std::time::Instant::now();
+    let (rv, _) = recv_one_with_timeout(fd, NUM_MSGS, Duration::from_millis(10))?;
+    test_utils::result_assert_eq(rv, -1, "Unexpected result when the timeout expired")?;
+    test_utils::result_assert_eq(test_utils::get_errno(), libc::EAGAIN, "Unexpected errno")?;
+    let elapsed = start.elapsed();
+    test_utils::result_assert(
+        elapsed >= Duration::from_millis(10) && elapsed < Duration::from_millis(500),
+        &format!("recvmmsg() didn't return at the deadline, but after {elapsed:?}"),
+    )?;
+
+    // the last datagrams arrive before the timeout, and the timeout is updated with the remaining
+    // time
+    for i in NUM_MSGS..(NUM_MSGS + 2) {
+        let (rv, timeout) = recv_one_with_timeout(fd, i, Duration::from_secs(5))?;
+        test_utils::result_assert_eq(rv, 1, "Unexpected number of messages")?;
+        test_utils::result_assert(
+            timeout > Duration::from_secs(3) && timeout < Duration::from_millis(4500),
+            &format!("Unexpected remaining timeout {timeout:?}"),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Receive a single datagram, which must be the `i`th message if one is received, with
+/// `recvmmsg()` and the given timeout. Returns the result of `recvmmsg()` and the remaining
+/// timeout.
+fn recv_one_with_timeout(
+    fd: libc::c_int,
+    i: usize,
+    timeout: Duration,
+) -> Result<(libc::c_int, Duration), String> {
+    let mut buf = [0u8; MSG_LEN];
+    let mut iov = libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let mut msg: libc::mmsghdr = unsafe { std::mem::zeroed() };
+    msg.msg_hdr.msg_iov = &mut iov;
+    msg.msg_hdr.msg_iovlen = 1;
+    let mut timeout = libc::timespec {
+        tv_sec: timeout.as_secs().try_into().unwrap(),
+        tv_nsec: timeout.subsec_nanos().into(),
+    };
+
+    let rv = unsafe { libc::recvmmsg(fd, &mut msg, 1, 0, &mut timeout) };
+    if rv == 1 {
+        test_utils::result_assert_eq(&buf[..], &msg_payload(i)[..], "Unexpected message payload")?;
+    }
+
+    let timeout = Duration::new(
+        timeout.tv_sec.try_into().unwrap(),
+        timeout.tv_nsec.try_into().unwrap(),
+    );
+    Ok((rv, timeout))
+}
+
+fn run_client(fd: libc::c_int, mut server_addr: libc::sockaddr_in) -> Result<(), String> {
+    let mut payloads: Vec<[u8; MSG_LEN]> = (0..NUM_MSGS).map(msg_payload).collect();
+    let mut iovs: Vec<libc::iovec> = payloads
+        .iter_mut()
+        .map(|payload| libc::iovec {
+            iov_base: payload.as_mut_ptr() as *mut libc::c_void,
+            iov_len: payload.len(),
+        })
+        .collect();
+    let mut msgs: Vec<libc::mmsghdr> = iovs
+        .iter_mut()
+        .map(|iov| libc::mmsghdr {
+            msg_hdr: libc::msghdr {
+                msg_name: std::ptr::from_mut(&mut server_addr) as *mut libc::c_void,
+                msg_namelen: std::mem::size_of_val(&server_addr) as libc::socklen_t,
+                msg_iov: iov,
+                msg_iovlen: 1,
+                msg_control: std::ptr::null_mut(),
+                msg_controllen: 0,
+                msg_flags: 0,
+            },
+            msg_len: 0,
+        })
+        .collect();
+
+    let rv = unsafe { libc::sendmmsg(fd, msgs.as_mut_ptr(), msgs.len().try_into().unwrap(), 0) };
+    test_utils::result_assert_eq(rv, NUM_MSGS as i32, "Unexpected number of messages")?;
+
+    for msg in &msgs {
+        test_utils::result_assert_eq(msg.msg_len as usize, MSG_LEN, "Unexpected message length")?;
+    }
+
+    // send two more datagrams one second apart
+    for i in NUM_MSGS..(NUM_MSGS + 2) {
+        std::thread::sleep(Duration::from_secs(1));
+
+        let payload = msg_payload(i);
+        let rv = unsafe {
+            libc::sendto(
+                fd,
+                payload.as_ptr() as *const libc::c_void,
+                payload.len(),
+                0,
+                std::ptr::from_ref(&server_addr) as *const libc::sockaddr,
+                std::mem::size_of_val(&server_addr) as libc::socklen_t,
+            )
+        };
+        test_utils::result_assert_eq(rv, MSG_LEN as isize, "sendto() failed")?;
+    }
+
+    Ok(())
+}
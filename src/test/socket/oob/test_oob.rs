@@ -0,0 +1,380 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for TCP urgent (out-of-band) data sent with `MSG_OOB`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use nix::sys::signal;
+use test_utils::set;
+use test_utils::socket_utils;
+use test_utils::TestEnvironment as TestEnv;
+
+static SIGURG_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn sigurg_handler(_sig: i32) {
+    SIGURG_RECEIVED.store(true, Ordering::SeqCst);
+}
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let all_envs = set![TestEnv::Libc, TestEnv::Shadow];
+    vec![
+        test_utils::ShadowTest::new("test_no_urgent_data", test_no_urgent_data, all_envs.clone()),
+        test_utils::ShadowTest::new("test_urgent_data", test_urgent_data, all_envs.clone()),
+        test_utils::ShadowTest::new(
+            "test_urgent_data_iovs",
+            test_urgent_data_iovs,
+            all_envs.clone(),
+        ),
+        test_utils::ShadowTest::new(
+            "test_urgent_data_inline",
+            test_urgent_data_inline,
+            all_envs.clone(),
+        ),
+        test_utils::ShadowTest::new(
+            "test_urgent_data_multiplexing",
+            test_urgent_data_multiplexing,
+            all_envs,
+        ),
+    ]
+}
+
+/// Run `f` with a connected pair of tcp sockets `(client, server)`.
+fn with_tcp_pair(
+    f: impl FnOnce(libc::c_int, libc::c_int) -> Result<(), String>,
+) -> Result<(), String> {
+    let fd_listen = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    let fd_client = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd_listen >= 0);
+    assert!(fd_client >= 0);
+
+    let (server_addr, server_addr_len) = socket_utils::autobind_helper(fd_listen, libc::AF_INET);
+
+    test_utils::run_and_close_fds(&[fd_listen, fd_client], || {
+        nix::sys::socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+        let rv = unsafe { libc::connect(fd_client, server_addr.as_ptr(), server_addr_len) };
+        test_utils::result_assert_eq(rv, 0, "connect() failed")?;
+
+        let fd_server = nix::sys::socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+        test_utils::run_and_close_fds(&[fd_server], || f(fd_client, fd_server))
+    })
+}
+
+/// Poll `fd` for `events`, retrying if interrupted by a signal.
+fn poll_retry(fd: libc::c_int, events: libc::c_short, timeout_ms: libc::c_int) -> libc::c_short {
+    loop {
+        let mut pfd = libc::pollfd {
+            fd,
+            events,
+            revents: 0,
+        };
+        let rv = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+        if rv < 0 && test_utils::get_errno() == libc::EINTR {
+            continue;
+        }
+        assert!(rv >= 0);
+        return pfd.revents;
+    }
+}
+
+/// Receive a single out-of-band byte.
+fn recv_oob(fd: libc::c_int) -> Result<u8, i32> {
+    let mut byte = 0u8;
+    let rv = unsafe {
+        libc::recv(
+            fd,
+            std::ptr::from_mut(&mut byte) as *mut libc::c_void,
+            1,
+            libc::MSG_OOB,
+        )
+    };
+    match rv {
+        1 => Ok(byte),
+        -1 => Err(test_utils::get_errno()),
+        rv => panic!("Unexpected return value {rv}"),
+    }
+}
+
+/// Receive exactly `len` bytes of normal data.
+fn recv_exact(fd: libc::c_int, len: usize) -> Result<Vec<u8>, String> {
+    let mut buf = vec![0u8; len];
+    let mut bytes_read = 0;
+
+    // linux stops reading at the urgent mark, so this may take multiple reads
+    while bytes_read < len {
+        let rv = unsafe {
+            libc::recv(
+                fd,
+                buf[bytes_read..].as_mut_ptr() as *mut libc::c_void,
+                len - bytes_read,
+                0,
+            )
+        };
+        test_utils::result_assert(rv > 0, "recv() failed")?;
+        bytes_read += rv as usize;
+    }
+
+    Ok(buf)
+}
+
+/// Receiving out-of-band data when none was sent is an error.
+fn test_no_urgent_data() -> Result<(), String> {
+    with_tcp_pair(|fd_client, fd_server| {
+        let buf = b"ab";
+        let rv = unsafe { libc::send(fd_client, buf.as_ptr() as *const libc::c_void, 2, 0) };
+        test_utils::result_assert_eq(rv, 2, "send() failed")?;
+
+        let revents = poll_retry(fd_server, libc::POLLIN, 1000);
+        test_utils::result_assert_eq(revents, libc::POLLIN, "Unexpected poll events")?;
+
+        test_utils::result_assert_eq(recv_oob(fd_server), Err(libc::EINVAL), "Unexpected OOB")?;
+        test_utils::result_assert_eq(recv_exact(fd_server, 2)?, buf.to_vec(), "Wrong data")?;
+
+        Ok(())
+    })
+}
+
+/// The last byte sent with `MSG_OOB` is removed from the data stream, signals the owner, and is
+/// reported by poll until it's read.
+fn test_urgent_data() -> Result<(), String> {
+    with_tcp_pair(|fd_client, fd_server| {
+        unsafe {
+            signal::sigaction(
+                signal::Signal::SIGURG,
+                &signal::SigAction::new(
+                    signal::SigHandler::Handler(sigurg_handler),
+                    signal::SaFlags::empty(),
+                    signal::SigSet::empty(),
+                ),
+            )
+        }
+        .unwrap();
+        SIGURG_RECEIVED.store(false, Ordering::SeqCst);
+
+        let pid = unsafe { libc::getpid() };
+        let rv = unsafe { libc::fcntl(fd_server, libc::F_SETOWN, pid) };
+        test_utils::result_assert_eq(rv, 0, "fcntl(F_SETOWN) failed")?;
+        let rv = unsafe { libc::fcntl(fd_server, libc::F_GETOWN) };
+        test_utils::result_assert_eq(rv, pid, "Unexpected owner")?;
+
+        let buf = b"abc";
+        let rv = unsafe {
+            libc::send(
+                fd_client,
+                buf.as_ptr() as *const libc::c_void,
+                3,
+                libc::MSG_OOB,
+            )
+        };
+        test_utils::result_assert_eq(rv, 3, "send() failed")?;
+
+        let revents = poll_retry(fd_server, libc::POLLPRI, 1000);
+        test_utils::result_assert_eq(revents, libc::POLLPRI, "Expected POLLPRI")?;
+        test_utils::result_assert(SIGURG_RECEIVED.load(Ordering::SeqCst), "No SIGURG")?;
+
+        test_utils::result_assert_eq(recv_oob(fd_server), Ok(b'c'), "Wrong OOB byte")?;
+        test_utils::result_assert_eq(recv_exact(fd_server, 2)?, b"ab".to_vec(), "Wrong data")?;
+
+        // the urgent byte can only be read once
+        test_utils::result_assert_eq(recv_oob(fd_server), Err(libc::EINVAL), "Unexpected OOB")?;
+        let revents = poll_retry(fd_server, libc::POLLPRI, 0);
+        test_utils::result_assert_eq(revents, 0, "Unexpected POLLPRI")?;
+
+        Ok(())
+    })
+}
+
+/// The urgent byte is the last byte of the whole message, even if the message's last iov is empty.
+fn test_urgent_data_iovs() -> Result<(), String> {
+    with_tcp_pair(|fd_client, fd_server| {
+        let bufs: [&[u8]; 3] = [b"ab", b"c", b""];
+        let mut iovs: Vec<libc::iovec> = bufs
+            .iter()
+            .map(|buf| libc::iovec {
+                iov_base: buf.as_ptr() as *mut libc::c_void,
+                iov_len: buf.len(),
+            })
+            .collect();
+
+        let msg = libc::msghdr {
+            msg_name: std::ptr::null_mut(),
+            msg_namelen: 0,
+            msg_iov: iovs.as_mut_ptr(),
+            msg_iovlen: iovs.len(),
+            msg_control: std::ptr::null_mut(),
+            msg_controllen: 0,
+            msg_flags: 0,
+        };
+
+        let rv = unsafe { libc::sendmsg(fd_client, &msg, libc::MSG_OOB) };
+        test_utils::result_assert_eq(rv, 3, "sendmsg() failed")?;
+
+        let revents = poll_retry(fd_server, libc::POLLPRI, 1000);
+        test_utils::result_assert_eq(revents, libc::POLLPRI, "Expected POLLPRI")?;
+
+        test_utils::result_assert_eq(recv_oob(fd_server), Ok(b'c'), "Wrong OOB byte")?;
+        test_utils::result_assert_eq(recv_exact(fd_server, 2)?, b"ab".to_vec(), "Wrong data")?;
+
+        Ok(())
+    })
+}
+
+/// With `SO_OOBINLINE`, the urgent byte stays in the data stream.
+fn test_urgent_data_inline() -> Result<(), String> {
+    with_tcp_pair(|fd_client, fd_server| {
+        let enable: libc::c_int = 1;
+        let rv = unsafe {
+            libc::setsockopt(
+                fd_server,
+                libc::SOL_SOCKET,
+                libc::SO_OOBINLINE,
+                std::ptr::from_ref(&enable) as *const libc::c_void,
+                std::mem::size_of_val(&enable) as libc::socklen_t,
+            )
+        };
+        test_utils::result_assert_eq(rv, 0, "setsockopt() failed")?;
+
+        let buf = b"abc";
+        let rv = unsafe {
+            libc::send(
+                fd_client,
+                buf.as_ptr() as *const libc::c_void,
+                3,
+                libc::MSG_OOB,
+            )
+        };
+        test_utils::result_assert_eq(rv, 3, "send() failed")?;
+
+        let revents = poll_retry(fd_server, libc::POLLPRI, 1000);
+        test_utils::result_assert_eq(revents, libc::POLLPRI, "Expected POLLPRI")?;
+
+        test_utils::result_assert_eq(recv_oob(fd_server), Err(libc::EINVAL), "Unexpected OOB")?;
+        test_utils::result_assert_eq(recv_exact(fd_server, 3)?, buf.to_vec(), "Wrong data")?;
+
+        // reading past the urgent byte clears the urgent state
+        let revents = poll_retry(fd_server, libc::POLLPRI, 0);
+        test_utils::result_assert_eq(revents, 0, "Unexpected POLLPRI")?;
+
+        Ok(())
+    })
+}
+
+/// Is `fd` in select's exceptfds? Waits up to `timeout_ms` for the exceptional condition.
+fn select_except(fd: libc::c_int, timeout_ms: libc::c_long) -> Result<bool, String> {
+    let mut exceptfds: libc::fd_set = unsafe { std::mem::zeroed() };
+    unsafe { libc::FD_SET(fd, &mut exceptfds) };
+
+    let mut timeout = libc::timeval {
+        tv_sec: timeout_ms / 1000,
+        tv_usec: (timeout_ms % 1000) * 1000,
+    };
+
+    let rv = unsafe {
+        libc::select(
+            fd + 1,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut exceptfds,
+            &mut timeout,
+        )
+    };
+    test_utils::result_assert(rv >= 0, "select() failed")?;
+
+    Ok(unsafe { libc::FD_ISSET(fd, &exceptfds) })
+}
+
+/// The events that epoll reports for `fd` when only interested in `EPOLLPRI`.
+fn epoll_pri(fd: libc::c_int) -> Result<u32, String> {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    test_utils::result_assert(epfd >= 0, "epoll_create1() failed")?;
+
+    test_utils::run_and_close_fds(&[epfd], || {
+        let mut event = libc::epoll_event {
+            events: libc::EPOLLPRI as u32,
+            u64: 0,
+        };
+        let rv = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) };
+        test_utils::result_assert_eq(rv, 0, "epoll_ctl() failed")?;
+
+        let mut events = [libc::epoll_event { events: 0, u64: 0 }; 1];
+        let rv = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 1, 0) };
+        test_utils::result_assert(rv >= 0, "epoll_wait() failed")?;
+
+        Ok(if rv == 1 { events[0].events } else { 0 })
+    })
+}
+
+/// Pending urgent data is reported as an exceptional condition by select, poll, and epoll.
+fn test_urgent_data_multiplexing() -> Result<(), String> {
+    with_tcp_pair(|fd_client, fd_server| {
+        test_utils::result_assert(!select_except(fd_server, 0)?, "Unexpected exceptfds")?;
+        test_utils::result_assert_eq(
+            poll_retry(fd_server, libc::POLLPRI, 0),
+            0,
+            "Unexpected POLLPRI",
+        )?;
+        test_utils::result_assert_eq(epoll_pri(fd_server)?, 0, "Unexpected EPOLLPRI")?;
+
+        let buf = b"abc";
+        let rv = unsafe {
+            libc::send(
+                fd_client,
+                buf.as_ptr() as *const libc::c_void,
+                3,
+                libc::MSG_OOB,
+            )
+        };
+        test_utils::result_assert_eq(rv, 3, "send() failed")?;
+
+        // wait for the urgent data to arrive
+        test_utils::result_assert(select_except(fd_server, 1000)?, "Expected exceptfds")?;
+        test_utils::result_assert_eq(
+            poll_retry(fd_server, libc::POLLPRI, 0),
+            libc::POLLPRI,
+            "Expected POLLPRI",
+        )?;
+        test_utils::result_assert_eq(
+            epoll_pri(fd_server)?,
+            libc::EPOLLPRI as u32,
+            "Expected EPOLLPRI",
+        )?;
+
+        // reading the urgent byte clears the exceptional condition
+        test_utils::result_assert_eq(recv_oob(fd_server), Ok(b'c'), "Wrong OOB byte")?;
+
+        test_utils::result_assert(!select_except(fd_server, 0)?, "Unexpected exceptfds")?;
+        test_utils::result_assert_eq(
+            poll_retry(fd_server, libc::POLLPRI, 0),
+            0,
+            "Unexpected POLLPRI",
+        )?;
+        test_utils::result_assert_eq(epoll_pri(fd_server)?, 0, "Unexpected EPOLLPRI")?;
+
+        Ok(())
+    })
+}
@@ -0,0 +1,179 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests that a socket with a `SO_MAX_PACING_RATE` doesn't send faster than the rate.
+
+use std::time::{Duration, Instant};
+
+use test_utils::set;
+use test_utils::socket_utils;
+use test_utils::TestEnvironment as TestEnv;
+
+/// The pacing rate in bytes per second.
+const PACING_RATE: u64 = 100_000;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    // linux only paces udp sockets when using the fq qdisc, and its tcp timing on a loaded machine
+    // isn't precise enough for these tests
+    vec![
+        test_utils::ShadowTest::new("test_udp_pacing", test_udp_pacing, set![TestEnv::Shadow]),
+        test_utils::ShadowTest::new("test_tcp_pacing", test_tcp_pacing, set![TestEnv::Shadow]),
+    ]
+}
+
+fn set_max_pacing_rate(fd: libc::c_int, rate: u64) {
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_MAX_PACING_RATE,
+            std::ptr::from_ref(&rate) as *const libc::c_void,
+            std::mem::size_of_val(&rate) as libc::socklen_t,
+        )
+    };
+    assert_eq!(rv, 0);
+}
+
+/// Check that sending `num_bytes` over `elapsed` time is close to the pacing rate.
+fn check_rate(num_bytes: usize, elapsed: Duration) -> Result<(), String> {
+    let rate = num_bytes as f64 / elapsed.as_secs_f64();
+    println!("Sent {num_bytes} bytes in {elapsed:?} ({rate:.0} bytes/s)");
+
+    test_utils::result_assert(
+        rate <= 1.1 * PACING_RATE as f64,
+        &format!("Sent faster than the pacing rate: {rate:.0} bytes/s"),
+    )?;
+    test_utils::result_assert(
+        rate >= 0.8 * PACING_RATE as f64,
+        &format!("Sent much slower than the pacing rate: {rate:.0} bytes/s"),
+    )?;
+
+    Ok(())
+}
+
+/// A udp socket spreads its datagrams out over time.
+fn test_udp_pacing() -> Result<(), String> {
+    const NUM_MSGS: usize = 50;
+    const MSG_LEN: usize = 1000;
+
+    let fd_send = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    let fd_recv = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    assert!(fd_send >= 0);
+    assert!(fd_recv >= 0);
+
+    let (recv_addr, recv_addr_len) = socket_utils::autobind_helper(fd_recv, libc::AF_INET);
+
+    test_utils::run_and_close_fds(&[fd_send, fd_recv], || {
+        set_max_pacing_rate(fd_send, PACING_RATE);
+
+        let rv = unsafe { libc::connect(fd_send, recv_addr.as_ptr(), recv_addr_len) };
+        test_utils::result_assert_eq(rv, 0, "connect() failed")?;
+
+        let start = Instant::now();
+
+        // the datagrams fit in the send buffer, so none of these block
+        for _ in 0..NUM_MSGS {
+            let buf = [1u8; MSG_LEN];
+            let rv =
+                unsafe { libc::send(fd_send, buf.as_ptr() as *const libc::c_void, buf.len(), 0) };
+            test_utils::result_assert_eq(rv, MSG_LEN as isize, "send() failed")?;
+        }
+
+        for _ in 0..NUM_MSGS {
+            let mut buf = [0u8; MSG_LEN];
+            let rv =
+                unsafe { libc::recv(fd_recv, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+            test_utils::result_assert_eq(rv, MSG_LEN as isize, "recv() failed")?;
+        }
+
+        check_rate(NUM_MSGS * MSG_LEN, start.elapsed())
+    })
+}
+
+/// A tcp socket spreads its segments out over time.
+fn test_tcp_pacing() -> Result<(), String> {
+    const NUM_BYTES: usize = 100_000;
+
+    let fd_listen = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    let fd_send = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd_listen >= 0);
+    assert!(fd_send >= 0);
+
+    let (server_addr, server_addr_len) = socket_utils::autobind_helper(fd_listen, libc::AF_INET);
+
+    test_utils::run_and_close_fds(&[fd_listen, fd_send], || {
+        nix::sys::socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+        let rv = unsafe { libc::connect(fd_send, server_addr.as_ptr(), server_addr_len) };
+        test_utils::result_assert_eq(rv, 0, "connect() failed")?;
+
+        let fd_recv = nix::sys::socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+        test_utils::run_and_close_fds(&[fd_recv], || {
+            set_max_pacing_rate(fd_send, PACING_RATE);
+
+            let start = Instant::now();
+
+            // read from another thread so that a full send buffer can't block us forever
+            let reader = std::thread::spawn(move || {
+                let mut buf = vec![0u8; NUM_BYTES];
+                let mut bytes_read = 0;
+                while bytes_read < NUM_BYTES {
+                    let rv = unsafe {
+                        libc::recv(
+                            fd_recv,
+                            buf[bytes_read..].as_mut_ptr() as *mut libc::c_void,
+                            NUM_BYTES - bytes_read,
+                            0,
+                        )
+                    };
+                    assert!(rv > 0);
+                    bytes_read += rv as usize;
+                }
+                start.elapsed()
+            });
+
+            let buf = vec![1u8; NUM_BYTES];
+            let mut bytes_sent = 0;
+            while bytes_sent < NUM_BYTES {
+                let rv = unsafe {
+                    libc::send(
+                        fd_send,
+                        buf[bytes_sent..].as_ptr() as *const libc::c_void,
+                        NUM_BYTES - bytes_sent,
+                        0,
+                    )
+                };
+                test_utils::result_assert(rv > 0, "send() failed")?;
+                bytes_sent += rv as usize;
+            }
+
+            let elapsed = reader.join().unwrap();
+
+            check_rate(NUM_BYTES, elapsed)
+        })
+    })
+}
@@ -0,0 +1,452 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for `AF_PACKET` sockets, which capture copies of the packets sent and received on the
+//! host's interfaces. The tests send a known UDP packet over the loopback interface and check what
+//! was captured.
+//!
+//! These tests only run in shadow since packet sockets require `CAP_NET_RAW` on linux, and a real
+//! host's interfaces will have unrelated traffic.
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use nix::errno::Errno;
+use nix::sys::socket::{self, AddressFamily, MsgFlags, SockFlag, SockType, SockaddrIn};
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+/// The interface index of the loopback interface.
+const LO_INDEX: libc::c_int = 1;
+
+/// The interface index of the host's other interface.
+const ETH0_INDEX: libc::c_int = 2;
+
+/// The length of an ethernet header.
+const ETH_HEADER_LEN: usize = 14;
+
+const PAYLOAD: &[u8] = b"a packet to capture";
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let shadow_only = set![TestEnv::Shadow];
+    vec![
+        test_utils::ShadowTest::new(
+            "test_capture_dgram",
+            test_capture_dgram,
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new("test_capture_raw", test_capture_raw, shadow_only.clone()),
+        test_utils::ShadowTest::new(
+            "test_interface_filter",
+            test_interface_filter,
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new(
+            "test_protocol_filter",
+            test_protocol_filter,
+            shadow_only.clone(),
+        ),
+        test_utils::ShadowTest::new("test_bind_errors", test_bind_errors, shadow_only.clone()),
+        test_utils::ShadowTest::new("test_bpf_filter", test_bpf_filter, shadow_only.clone()),
+        test_utils::ShadowTest::new(
+            "test_bpf_filter_errors",
+            test_bpf_filter_errors,
+            shadow_only,
+        ),
+    ]
+}
+
+/// Create a packet socket capturing the ethernet `protocol` (in host byte order).
+fn packet_socket(sock_type: libc::c_int, protocol: libc::c_int) -> Result<libc::c_int, String> {
+    let protocol = (protocol as u16).to_be();
+    let fd = unsafe { libc::socket(libc::AF_PACKET, sock_type, protocol.into()) };
+    Errno::result(fd).map_err(|e| format!("socket() failed: {e}"))
+}
+
+fn new_link_addr(protocol: libc::c_int, ifindex: libc::c_int) -> libc::sockaddr_ll {
+    let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+    addr.sll_family = libc::AF_PACKET as u16;
+    addr.sll_protocol = (protocol as u16).to_be();
+    addr.sll_ifindex = ifindex;
+    addr
+}
+
+/// A classic BPF instruction.
+fn bpf_insn(code: u16, jt: u8, jf: u8, k: u32) -> libc::sock_filter {
+    libc::sock_filter { code, jt, jf, k }
+}
+
+/// A classic BPF program for `SOCK_DGRAM` packet sockets that accepts IPv4 UDP packets with a
+/// destination port of `port`, and drops everything else. Equivalent to tcpdump's "udp dst port
+/// <port>".
+fn udp_dst_port_filter(port: u16) -> Vec<libc::sock_filter> {
+    vec![
+        // ldb [9] (the ip protocol)
+        bpf_insn(0x30, 0, 0, 9),
+        // jeq #17, next, drop
+        bpf_insn(0x15, 0, 6, libc::IPPROTO_UDP as u32),
+        // ldh [6] (the fragment offset)
+        bpf_insn(0x28, 0, 0, 6),
+        // jset #0x1fff, drop, next
+        bpf_insn(0x45, 4, 0, 0x1fff),
+        // ldxb 4*([0]&0xf) (the ip header length)
+        bpf_insn(0xb1, 0, 0, 0),
+        // ldh [x + 2] (the udp destination port)
+        bpf_insn(0x48, 0, 0, 2),
+        // jeq #port, accept, drop
+        bpf_insn(0x15, 0, 1, port.into()),
+        // ret #262144
+        bpf_insn(0x06, 0, 0, 262144),
+        // ret #0
+        bpf_insn(0x06, 0, 0, 0),
+    ]
+}
+
+fn attach_filter(fd: libc::c_int, insns: &mut [libc::sock_filter]) -> Result<(), Errno> {
+    let fprog = libc::sock_fprog {
+        len: insns.len().try_into().unwrap(),
+        filter: insns.as_mut_ptr(),
+    };
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_ATTACH_FILTER,
+            std::ptr::from_ref(&fprog).cast(),
+            std::mem::size_of_val(&fprog).try_into().unwrap(),
+        )
+    };
+    Errno::result(rv).map(drop)
+}
+
+fn detach_filter(fd: libc::c_int) -> Result<(), Errno> {
+    let val: libc::c_int = 0;
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_DETACH_FILTER,
+            std::ptr::from_ref(&val).cast(),
+            std::mem::size_of_val(&val).try_into().unwrap(),
+        )
+    };
+    Errno::result(rv).map(drop)
+}
+
+fn bind_packet(fd: libc::c_int, addr: &libc::sockaddr_ll, len: usize) -> Result<(), Errno> {
+    let rv = unsafe { libc::bind(fd, std::ptr::from_ref(addr).cast(), len.try_into().unwrap()) };
+    Errno::result(rv).map(drop)
+}
+
+/// Bind the packet socket to capture the ethernet `protocol` on the interface `ifindex`.
+fn bind_interface(
+    fd: libc::c_int,
+    protocol: libc::c_int,
+    ifindex: libc::c_int,
+) -> Result<(), String> {
+    let addr = new_link_addr(protocol, ifindex);
+    bind_packet(fd, &addr, std::mem::size_of_val(&addr)).map_err(|e| e.to_string())
+}
+
+/// Receive a captured packet without blocking.
+fn recv_packet(fd: libc::c_int) -> Result<(Vec<u8>, libc::sockaddr_ll), Errno> {
+    let mut buf = vec![0u8; 65536];
+    let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+    let mut addr_len = std::mem::size_of_val(&addr) as libc::socklen_t;
+
+    let rv = unsafe {
+        libc::recvfrom(
+            fd,
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+            libc::MSG_DONTWAIT,
+            std::ptr::from_mut(&mut addr).cast(),
+            &mut addr_len,
+        )
+    };
+    let len = Errno::result(rv)? as usize;
+
+    assert_eq!(addr_len as usize, std::mem::size_of_val(&addr));
+
+    buf.truncate(len);
+    Ok((buf, addr))
+}
+
+/// Send `PAYLOAD` between two udp sockets on the loopback interface, and return the source and
+/// destination addresses. Returns after the packet was received.
+fn send_udp_packet() -> Result<(SocketAddrV4, SocketAddrV4), String> {
+    send_udp_packet_to_port(0)
+}
+
+/// Like [`send_udp_packet`], but the destination socket is bound to `dst_port` (or an ephemeral
+/// port if 0).
+fn send_udp_packet_to_port(dst_port: u16) -> Result<(SocketAddrV4, SocketAddrV4), String> {
+    let new_socket = |port| {
+        let fd = socket::socket(
+            AddressFamily::Inet,
+            SockType::Datagram,
+            SockFlag::empty(),
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+
+        let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port));
+        socket::bind(fd, &addr).map_err(|e| e.to_string())?;
+
+        let addr: SockaddrIn = socket::getsockname(fd).map_err(|e| e.to_string())?;
+        Ok::<_, String>((fd, SocketAddrV4::from(addr)))
+    };
+
+    let (fd_src, src) = new_socket(0)?;
+    let (fd_dst, dst) = new_socket(dst_port)?;
+
+    test_utils::run_and_close_fds(&[fd_src, fd_dst], || {
+        socket::sendto(fd_src, PAYLOAD, &SockaddrIn::from(dst), MsgFlags::empty())
+            .map_err(|e| e.to_string())?;
+
+        let mut buf = [0u8; 100];
+        let len = socket::recv(fd_dst, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(&buf[..len], PAYLOAD, "Unexpected udp payload")?;
+
+        Ok((src, dst))
+    })
+}
+
+/// Check that `packet` is an IPv4 UDP packet from `src` to `dst` containing `PAYLOAD`.
+fn check_udp_packet(packet: &[u8], src: SocketAddrV4, dst: SocketAddrV4) -> Result<(), String> {
+    test_utils::result_assert(packet.len() >= 20, "Packet is too short")?;
+    test_utils::result_assert_eq(packet[0] >> 4, 4, "Not an IPv4 packet")?;
+    test_utils::result_assert_eq(packet[9], libc::IPPROTO_UDP as u8, "Not a UDP packet")?;
+    test_utils::result_assert_eq(&packet[12..16], &src.ip().octets(), "Unexpected source ip")?;
+    test_utils::result_assert_eq(&packet[16..20], &dst.ip().octets(), "Unexpected dest ip")?;
+
+    let udp = &packet[usize::from(packet[0] & 0xf) * 4..];
+    test_utils::result_assert_eq(
+        &udp[0..2],
+        &src.port().to_be_bytes(),
+        "Unexpected source port",
+    )?;
+    test_utils::result_assert_eq(
+        &udp[2..4],
+        &dst.port().to_be_bytes(),
+        "Unexpected dest port",
+    )?;
+    test_utils::result_assert_eq(&udp[8..], PAYLOAD, "Unexpected payload")
+}
+
+/// Check the address describing where a packet was captured.
+fn check_link_addr(
+    addr: &libc::sockaddr_ll,
+    ifindex: libc::c_int,
+    pkttype: u8,
+) -> Result<(), String> {
+    test_utils::result_assert_eq(addr.sll_family, libc::AF_PACKET as u16, "Unexpected family")?;
+    test_utils::result_assert_eq(
+        u16::from_be(addr.sll_protocol),
+        libc::ETH_P_IP as u16,
+        "Unexpected protocol",
+    )?;
+    test_utils::result_assert_eq(addr.sll_ifindex, ifindex, "Unexpected interface")?;
+    test_utils::result_assert_eq(addr.sll_pkttype, pkttype, "Unexpected packet type")
+}
+
+/// Test that a `SOCK_DGRAM` socket capturing all protocols receives both the sent and received
+/// copies of a packet, without a link-layer header.
+fn test_capture_dgram() -> Result<(), String> {
+    let fd = packet_socket(libc::SOCK_DGRAM, libc::ETH_P_ALL)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        bind_interface(fd, libc::ETH_P_ALL, LO_INDEX)?;
+
+        let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        let mut addr_len = std::mem::size_of_val(&addr) as libc::socklen_t;
+        let rv =
+            unsafe { libc::getsockname(fd, std::ptr::from_mut(&mut addr).cast(), &mut addr_len) };
+        Errno::result(rv).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(addr.sll_ifindex, LO_INDEX, "Unexpected bound interface")?;
+        test_utils::result_assert_eq(
+            addr.sll_hatype,
+            libc::ARPHRD_LOOPBACK,
+            "Unexpected hardware type",
+        )?;
+
+        let (src, dst) = send_udp_packet()?;
+
+        let (packet, addr) = recv_packet(fd).map_err(|e| e.to_string())?;
+        check_link_addr(&addr, LO_INDEX, libc::PACKET_OUTGOING)?;
+        check_udp_packet(&packet, src, dst)?;
+
+        let (packet, addr) = recv_packet(fd).map_err(|e| e.to_string())?;
+        check_link_addr(&addr, LO_INDEX, libc::PACKET_HOST)?;
+        check_udp_packet(&packet, src, dst)?;
+
+        test_utils::result_assert_eq(recv_packet(fd).unwrap_err(), Errno::EAGAIN, "Extra packet")
+    })
+}
+
+/// Test that a `SOCK_RAW` socket receives packets with an ethernet header, and that a socket
+/// capturing only IPv4 doesn't receive sent packets.
+fn test_capture_raw() -> Result<(), String> {
+    let fd = packet_socket(libc::SOCK_RAW, libc::ETH_P_IP)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        bind_interface(fd, libc::ETH_P_IP, LO_INDEX)?;
+
+        let (src, dst) = send_udp_packet()?;
+
+        let (packet, addr) = recv_packet(fd).map_err(|e| e.to_string())?;
+        check_link_addr(&addr, LO_INDEX, libc::PACKET_HOST)?;
+
+        test_utils::result_assert(packet.len() > ETH_HEADER_LEN, "Packet is too short")?;
+        test_utils::result_assert_eq(
+            &packet[12..14],
+            &(libc::ETH_P_IP as u16).to_be_bytes(),
+            "Unexpected ethertype",
+        )?;
+        check_udp_packet(&packet[ETH_HEADER_LEN..], src, dst)?;
+
+        test_utils::result_assert_eq(recv_packet(fd).unwrap_err(), Errno::EAGAIN, "Extra packet")
+    })
+}
+
+/// Test that a socket bound to one interface doesn't capture packets on other interfaces.
+fn test_interface_filter() -> Result<(), String> {
+    let fd = packet_socket(libc::SOCK_DGRAM, libc::ETH_P_ALL)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        bind_interface(fd, libc::ETH_P_ALL, ETH0_INDEX)?;
+
+        send_udp_packet()?;
+
+        test_utils::result_assert_eq(recv_packet(fd).unwrap_err(), Errno::EAGAIN, "Extra packet")
+    })
+}
+
+/// Test that a socket doesn't capture packets of other protocols, and that binding can change the
+/// protocol.
+fn test_protocol_filter() -> Result<(), String> {
+    let fd_none = packet_socket(libc::SOCK_DGRAM, 0)?;
+    let fd_arp = packet_socket(libc::SOCK_DGRAM, libc::ETH_P_ARP)?;
+
+    test_utils::run_and_close_fds(&[fd_none, fd_arp], || {
+        send_udp_packet()?;
+
+        for fd in [fd_none, fd_arp] {
+            let err = recv_packet(fd).unwrap_err();
+            test_utils::result_assert_eq(err, Errno::EAGAIN, "Captured another protocol")?;
+        }
+
+        // capture ipv4 packets from all interfaces
+        bind_interface(fd_none, libc::ETH_P_IP, 0)?;
+
+        let (src, dst) = send_udp_packet()?;
+
+        let (packet, addr) = recv_packet(fd_none).map_err(|e| e.to_string())?;
+        check_link_addr(&addr, LO_INDEX, libc::PACKET_HOST)?;
+        check_udp_packet(&packet, src, dst)
+    })
+}
+
+/// Test that binding fails for bad addresses.
+fn test_bind_errors() -> Result<(), String> {
+    let fd = packet_socket(libc::SOCK_DGRAM, libc::ETH_P_ALL)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let len = std::mem::size_of::<libc::sockaddr_ll>();
+
+        let addr = new_link_addr(libc::ETH_P_ALL, 1000);
+        let rv = bind_packet(fd, &addr, len);
+        test_utils::result_assert_eq(rv, Err(Errno::ENODEV), "Bound to a missing interface")?;
+
+        let addr = new_link_addr(libc::ETH_P_ALL, LO_INDEX);
+        let rv = bind_packet(fd, &addr, len - 1);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Bound with a short address")?;
+
+        let mut addr = new_link_addr(libc::ETH_P_ALL, LO_INDEX);
+        addr.sll_family = libc::AF_INET as u16;
+        let rv = bind_packet(fd, &addr, len);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Bound with the wrong family")
+    })
+}
+
+/// Test that an attached BPF filter drops packets that don't match, and that detaching it captures
+/// all packets again.
+fn test_bpf_filter() -> Result<(), String> {
+    const PORT: u16 = 5353;
+
+    let fd = packet_socket(libc::SOCK_DGRAM, libc::ETH_P_ALL)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        bind_interface(fd, libc::ETH_P_ALL, LO_INDEX)?;
+        attach_filter(fd, &mut udp_dst_port_filter(PORT)).map_err(|e| e.to_string())?;
+
+        // to an ephemeral port, which shouldn't match
+        send_udp_packet()?;
+        test_utils::result_assert_eq(recv_packet(fd).unwrap_err(), Errno::EAGAIN, "Not filtered")?;
+
+        // both the sent and received copies should match
+        let (src, dst) = send_udp_packet_to_port(PORT)?;
+        for pkttype in [libc::PACKET_OUTGOING, libc::PACKET_HOST] {
+            let (packet, addr) = recv_packet(fd).map_err(|e| e.to_string())?;
+            check_link_addr(&addr, LO_INDEX, pkttype)?;
+            check_udp_packet(&packet, src, dst)?;
+        }
+        test_utils::result_assert_eq(recv_packet(fd).unwrap_err(), Errno::EAGAIN, "Extra packet")?;
+
+        detach_filter(fd).map_err(|e| e.to_string())?;
+
+        let (src, dst) = send_udp_packet()?;
+        let (packet, _addr) = recv_packet(fd).map_err(|e| e.to_string())?;
+        check_udp_packet(&packet, src, dst)
+    })
+}
+
+/// Test that invalid BPF filters are rejected, and that detaching fails without a filter.
+fn test_bpf_filter_errors() -> Result<(), String> {
+    let fd = packet_socket(libc::SOCK_DGRAM, libc::ETH_P_ALL)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let rv = detach_filter(fd);
+        test_utils::result_assert_eq(rv, Err(Errno::ENOENT), "Detached a missing filter")?;
+
+        let rv = attach_filter(fd, &mut []);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Attached an empty filter")?;
+
+        // an unknown opcode
+        let rv = attach_filter(fd, &mut [bpf_insn(0xff, 0, 0, 0), bpf_insn(0x06, 0, 0, 0)]);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Attached a bad opcode")?;
+
+        // a jump past the end of the program
+        let rv = attach_filter(fd, &mut [bpf_insn(0x15, 5, 0, 0), bpf_insn(0x06, 0, 0, 0)]);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Attached a bad jump")?;
+
+        // doesn't end with a return
+        let rv = attach_filter(fd, &mut [bpf_insn(0x00, 0, 0, 0)]);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Attached without a return")?;
+
+        attach_filter(fd, &mut [bpf_insn(0x06, 0, 0, 0)]).map_err(|e| e.to_string())?;
+        detach_filter(fd).map_err(|e| e.to_string())
+    })
+}
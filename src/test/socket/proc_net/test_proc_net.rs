@@ -0,0 +1,334 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for the `/proc/net/tcp` and `/proc/net/udp` files, which list the host's sockets, and for
+//! the `/proc/net/dev` file, which lists the packet counters of the host's interfaces.
+
+use std::io::Read;
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use nix::sys::socket::{self, AddressFamily, MsgFlags, SockFlag, SockType, SockaddrIn};
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+// values of linux's 'enum tcp_states'
+const TCP_ESTABLISHED: u8 = 1;
+const TCP_CLOSE: u8 = 7;
+const TCP_LISTEN: u8 = 10;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let all_envs = set![TestEnv::Libc, TestEnv::Shadow];
+    vec![
+        // run first so that packets from the other tests' closed sockets aren't counted
+        test_utils::ShadowTest::new("test_dev_counters", test_dev_counters, all_envs.clone()),
+        test_utils::ShadowTest::new("test_tcp_connection", test_tcp_connection, all_envs.clone()),
+        test_utils::ShadowTest::new("test_udp_sockets", test_udp_sockets, all_envs),
+        // the real host's sockets may change between reads
+        test_utils::ShadowTest::new(
+            "test_partial_reads",
+            test_partial_reads,
+            set![TestEnv::Shadow],
+        ),
+    ]
+}
+
+/// A row of `/proc/net/tcp` or `/proc/net/udp`.
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    local_addr: SocketAddrV4,
+    peer_addr: SocketAddrV4,
+    state: u8,
+}
+
+/// Read the entire file using reads of at most `chunk_size` bytes.
+fn read_file(path: &str, chunk_size: usize) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut contents = Vec::new();
+    let mut buf = vec![0u8; chunk_size];
+
+    loop {
+        let len = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if len == 0 {
+            break;
+        }
+        contents.extend_from_slice(&buf[..len]);
+    }
+
+    String::from_utf8(contents).map_err(|e| e.to_string())
+}
+
+/// Parse an address like "0100007F:1F90", where the IP address is the hex value of its
+/// network-order bytes read as a native-endian integer.
+fn parse_addr(s: &str) -> Result<SocketAddrV4, String> {
+    let (ip, port) = s.split_once(':').ok_or(format!("Bad address: {s}"))?;
+    let ip = u32::from_str_radix(ip, 16).map_err(|e| e.to_string())?;
+    let port = u16::from_str_radix(port, 16).map_err(|e| e.to_string())?;
+    Ok(SocketAddrV4::new(Ipv4Addr::from(ip.to_ne_bytes()), port))
+}
+
+/// Read and parse the rows of `/proc/net/tcp` or `/proc/net/udp`. Small reads are used to check
+/// that partial reads don't lose or repeat any bytes.
+fn read_entries(path: &str) -> Result<Vec<Entry>, String> {
+    let contents = read_file(path, 7)?;
+    let mut lines = contents.lines();
+
+    let header = lines.next().ok_or("Missing header")?;
+    test_utils::result_assert(header.trim_start().starts_with("sl"), "Unexpected header")?;
+
+    lines
+        .map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                return Err(format!("Bad line: {line}"));
+            }
+            Ok(Entry {
+                local_addr: parse_addr(fields[1])?,
+                peer_addr: parse_addr(fields[2])?,
+                state: u8::from_str_radix(fields[3], 16).map_err(|e| e.to_string())?,
+            })
+        })
+        .collect()
+}
+
+fn new_socket(sock_type: SockType) -> Result<libc::c_int, String> {
+    socket::socket(AddressFamily::Inet, sock_type, SockFlag::empty(), None)
+        .map_err(|e| e.to_string())
+}
+
+/// Bind the socket to a free port on the loopback interface and return the bound address.
+fn bind_loopback(fd: libc::c_int) -> Result<SocketAddrV4, String> {
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0));
+    socket::bind(fd, &addr).map_err(|e| e.to_string())?;
+    get_local_addr(fd)
+}
+
+fn get_local_addr(fd: libc::c_int) -> Result<SocketAddrV4, String> {
+    let addr: SockaddrIn = socket::getsockname(fd).map_err(|e| e.to_string())?;
+    Ok(addr.into())
+}
+
+fn check_has_entry(entries: &[Entry], expected: Entry) -> Result<(), String> {
+    if !entries.contains(&expected) {
+        return Err(format!("Missing entry {expected:?} in {entries:?}"));
+    }
+    Ok(())
+}
+
+/// Test that a listening socket and both ends of a connection are listed in `/proc/net/tcp`.
+fn test_tcp_connection() -> Result<(), String> {
+    let fd_listen = new_socket(SockType::Stream)?;
+    let fd_client = new_socket(SockType::Stream)?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd_client], || {
+        let server_addr = bind_loopback(fd_listen)?;
+        socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+        socket::connect(fd_client, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+        let client_addr = get_local_addr(fd_client)?;
+
+        let fd_server = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+        test_utils::run_and_close_fds(&[fd_server], || {
+            let entries = read_entries("/proc/net/tcp")?;
+            let unspecified = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+
+            check_has_entry(
+                &entries,
+                Entry {
+                    local_addr: server_addr,
+                    peer_addr: unspecified,
+                    state: TCP_LISTEN,
+                },
+            )?;
+            check_has_entry(
+                &entries,
+                Entry {
+                    local_addr: client_addr,
+                    peer_addr: server_addr,
+                    state: TCP_ESTABLISHED,
+                },
+            )?;
+            check_has_entry(
+                &entries,
+                Entry {
+                    local_addr: server_addr,
+                    peer_addr: client_addr,
+                    state: TCP_ESTABLISHED,
+                },
+            )
+        })
+    })
+}
+
+/// Test that unconnected and connected udp sockets are listed in `/proc/net/udp`.
+fn test_udp_sockets() -> Result<(), String> {
+    let fd_server = new_socket(SockType::Datagram)?;
+    let fd_client = new_socket(SockType::Datagram)?;
+
+    test_utils::run_and_close_fds(&[fd_server, fd_client], || {
+        let server_addr = bind_loopback(fd_server)?;
+
+        socket::connect(fd_client, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+        let client_addr = get_local_addr(fd_client)?;
+
+        let entries = read_entries("/proc/net/udp")?;
+
+        check_has_entry(
+            &entries,
+            Entry {
+                local_addr: server_addr,
+                peer_addr: SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0),
+                state: TCP_CLOSE,
+            },
+        )?;
+        check_has_entry(
+            &entries,
+            Entry {
+                local_addr: client_addr,
+                peer_addr: server_addr,
+                state: TCP_ESTABLISHED,
+            },
+        )
+    })
+}
+
+/// Test that reading the file in small chunks gives the same contents as a single large read.
+fn test_partial_reads() -> Result<(), String> {
+    let fd_listen = new_socket(SockType::Stream)?;
+    let fd_udp = new_socket(SockType::Datagram)?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd_udp], || {
+        bind_loopback(fd_listen)?;
+        socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+        bind_loopback(fd_udp)?;
+
+        for path in ["/proc/net/tcp", "/proc/net/udp", "/proc/net/dev"] {
+            let expected = read_file(path, 65536)?;
+            for chunk_size in [1, 13, 149, 150] {
+                let contents = read_file(path, chunk_size)?;
+                test_utils::result_assert_eq(&contents, &expected, "Partial reads differ")?;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// The counters of an interface in `/proc/net/dev`.
+#[derive(Debug)]
+struct DevCounters {
+    rx_bytes: u64,
+    rx_packets: u64,
+    tx_bytes: u64,
+    tx_packets: u64,
+}
+
+/// Read and parse the counters of the interface `name` from `/proc/net/dev`.
+fn read_dev_counters(name: &str) -> Result<DevCounters, String> {
+    let contents = read_file("/proc/net/dev", 7)?;
+
+    // skip the two header lines
+    for line in contents.lines().skip(2) {
+        let (iface, counters) = line.split_once(':').ok_or(format!("Bad line: {line}"))?;
+        if iface.trim() != name {
+            continue;
+        }
+
+        let counters: Vec<u64> = counters
+            .split_whitespace()
+            .map(|x| {
+                x.parse()
+                    .map_err(|e| format!("Bad counter in line '{line}': {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        if counters.len() != 16 {
+            return Err(format!("Bad line: {line}"));
+        }
+
+        return Ok(DevCounters {
+            rx_bytes: counters[0],
+            rx_packets: counters[1],
+            tx_bytes: counters[8],
+            tx_packets: counters[9],
+        });
+    }
+
+    Err(format!("Missing interface {name} in {contents}"))
+}
+
+/// Test that the loopback interface's counters in `/proc/net/dev` increase as traffic flows.
+fn test_dev_counters() -> Result<(), String> {
+    const NUM_PACKETS: u64 = 10;
+    const PAYLOAD_LEN: usize = 1000;
+    // the size of the ip and udp headers
+    const HEADERS_LEN: u64 = 28;
+
+    let fd_server = new_socket(SockType::Datagram)?;
+    let fd_client = new_socket(SockType::Datagram)?;
+
+    test_utils::run_and_close_fds(&[fd_server, fd_client], || {
+        let server_addr = bind_loopback(fd_server)?;
+        socket::connect(fd_client, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let before = read_dev_counters("lo")?;
+
+        let mut buf = [0u8; PAYLOAD_LEN];
+        for _ in 0..NUM_PACKETS {
+            socket::send(fd_client, &buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            // wait for the packet to pass through the interface
+            socket::recv(fd_server, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        }
+
+        let after = read_dev_counters("lo")?;
+        println!("Counters before: {before:?}, after: {after:?}");
+
+        let min_bytes = NUM_PACKETS * PAYLOAD_LEN as u64;
+        let tx_bytes = after.tx_bytes - before.tx_bytes;
+        let rx_bytes = after.rx_bytes - before.rx_bytes;
+
+        if test_utils::running_in_shadow() {
+            // there's no other traffic on the simulated host
+            let expected_bytes = NUM_PACKETS * (PAYLOAD_LEN as u64 + HEADERS_LEN);
+            test_utils::result_assert_eq(tx_bytes, expected_bytes, "Unexpected tx bytes")?;
+            test_utils::result_assert_eq(rx_bytes, expected_bytes, "Unexpected rx bytes")?;
+            test_utils::result_assert_eq(
+                after.tx_packets - before.tx_packets,
+                NUM_PACKETS,
+                "Unexpected tx packets",
+            )?;
+            test_utils::result_assert_eq(
+                after.rx_packets - before.rx_packets,
+                NUM_PACKETS,
+                "Unexpected rx packets",
+            )?;
+        } else {
+            test_utils::result_assert(tx_bytes >= min_bytes, "Too few tx bytes")?;
+            test_utils::result_assert(rx_bytes >= min_bytes, "Too few rx bytes")?;
+        }
+
+        Ok(())
+    })
+}
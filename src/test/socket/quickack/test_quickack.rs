@@ -0,0 +1,175 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests that a tcp socket delays its acks unless `TCP_QUICKACK` is enabled.
+
+use std::time::Duration;
+
+use nix::sys::socket::MsgFlags;
+use test_utils::set;
+use test_utils::socket_utils::{socket_init_helper, SocketInitMethod};
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_quickack_option",
+            test_quickack_option,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        // linux acks immediately at the start of a connection regardless of the option, and its
+        // loopback timing isn't precise enough for this test
+        test_utils::ShadowTest::new(
+            "test_quickack_ack_timing",
+            test_quickack_ack_timing,
+            set![TestEnv::Shadow],
+        ),
+    ]
+}
+
+fn set_quickack(fd: libc::c_int, enable: bool) {
+    let val = libc::c_int::from(enable);
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_QUICKACK,
+            std::ptr::from_ref(&val) as *const libc::c_void,
+            std::mem::size_of_val(&val) as libc::socklen_t,
+        )
+    };
+    assert_eq!(rv, 0);
+}
+
+fn get_quickack(fd: libc::c_int) -> libc::c_int {
+    let mut val: libc::c_int = -1;
+    let mut val_len = std::mem::size_of_val(&val) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_QUICKACK,
+            std::ptr::from_mut(&mut val) as *mut libc::c_void,
+            &mut val_len,
+        )
+    };
+    assert_eq!(rv, 0);
+    assert_eq!(val_len as usize, std::mem::size_of_val(&val));
+    val
+}
+
+/// Get the `tcpi_unacked` field of the socket's `struct tcp_info`.
+fn get_info_unacked(fd: libc::c_int) -> Result<u32, String> {
+    // the libc package doesn't expose 'struct tcp_info', so we only read the start of the struct;
+    // 'tcpi_unacked' is the fifth u32 following 8 bytes of u8 fields
+    let mut info = [0u32; 7];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(
+        info_len as usize,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+    Ok(info[6])
+}
+
+/// Quick acks are enabled by default, and the option can be toggled.
+fn test_quickack_option() -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        test_utils::result_assert_eq(get_quickack(fd), 1, "Unexpected default")?;
+
+        set_quickack(fd, false);
+        test_utils::result_assert_eq(get_quickack(fd), 0, "Unexpected value")?;
+
+        set_quickack(fd, true);
+        test_utils::result_assert_eq(get_quickack(fd), 1, "Unexpected value")?;
+
+        Ok(())
+    })
+}
+
+/// Send a byte to the peer and return the number of unacked segments a short time later, which is
+/// less than the receiver's ack delay.
+fn send_and_get_unacked(fd_client: libc::c_int, fd_server: libc::c_int) -> Result<u32, String> {
+    let rv =
+        nix::sys::socket::send(fd_client, &[1], MsgFlags::empty()).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(rv, 1, "send() failed")?;
+
+    std::thread::sleep(Duration::from_micros(500));
+    let unacked = get_info_unacked(fd_client)?;
+
+    // the ack has been sent by now
+    std::thread::sleep(Duration::from_millis(10));
+    test_utils::result_assert_eq(get_info_unacked(fd_client)?, 0, "Data wasn't acked")?;
+
+    let mut buf = [0u8; 1];
+    let rv = nix::sys::socket::recv(fd_server, &mut buf, MsgFlags::empty())
+        .map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(rv, 1, "recv() failed")?;
+
+    Ok(unacked)
+}
+
+/// The receiver delays its ack by default, but acks the next segment immediately after it enables
+/// `TCP_QUICKACK`.
+fn test_quickack_ack_timing() -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        // the ack is delayed
+        set_quickack(fd_server, false);
+        let unacked = send_and_get_unacked(fd_client, fd_server)?;
+        test_utils::result_assert_eq(unacked, 1, "Ack wasn't delayed")?;
+
+        // the ack is sent immediately
+        set_quickack(fd_server, true);
+        let unacked = send_and_get_unacked(fd_client, fd_server)?;
+        test_utils::result_assert_eq(unacked, 0, "Ack was delayed")?;
+
+        // quick acks only apply to the next segment, so the ack is delayed again
+        let unacked = send_and_get_unacked(fd_client, fd_server)?;
+        test_utils::result_assert_eq(unacked, 1, "Ack wasn't delayed")?;
+
+        Ok(())
+    })
+}
@@ -0,0 +1,315 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests that sockets with `SO_REUSEPORT` can share an address, and that connections and datagrams
+//! are distributed across them.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::sys::socket::{AddressFamily, MsgFlags, SockFlag, SockType, SockaddrIn};
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+const NUM_LISTENERS: usize = 3;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_tcp_distribution",
+            test_tcp_distribution,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_udp_distribution",
+            test_udp_distribution,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
+
+    for &sock_type in &[SockType::Stream, SockType::Datagram] {
+        // add details to the test names to avoid duplicates
+        let append_args = |s| format!("{} <sock_type={:?}>", s, sock_type);
+
+        let more_tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+            test_utils::ShadowTest::new(
+                &append_args("test_option"),
+                move || test_option(sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_bind_conflict"),
+                move || test_bind_conflict(sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+        ];
+
+        tests.extend(more_tests);
+    }
+
+    tests
+}
+
+fn new_socket(sock_type: SockType, reuse_port: bool) -> Result<libc::c_int, String> {
+    let fd = nix::sys::socket::socket(AddressFamily::Inet, sock_type, SockFlag::empty(), None)
+        .map_err(|e| e.to_string())?;
+
+    if reuse_port {
+        nix::sys::socket::setsockopt(fd, nix::sys::socket::sockopt::ReusePort, &true)
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(fd)
+}
+
+fn local_addr(fd: libc::c_int) -> Result<SockaddrIn, String> {
+    nix::sys::socket::getsockname(fd).map_err(|e| e.to_string())
+}
+
+/// Bind `count` sockets with `SO_REUSEPORT` to the same loopback address.
+fn bind_group(sock_type: SockType, count: usize) -> Result<Vec<libc::c_int>, String> {
+    let mut fds = vec![];
+    let mut addr = SockaddrIn::new(127, 0, 0, 1, 0);
+
+    for _ in 0..count {
+        let fd = new_socket(sock_type, true)?;
+        fds.push(fd);
+
+        nix::sys::socket::bind(fd, &addr).map_err(|e| e.to_string())?;
+
+        // the remaining sockets bind to the port chosen for the first socket
+        addr = local_addr(fd)?;
+    }
+
+    Ok(fds)
+}
+
+fn set_nonblocking(fd: libc::c_int) -> Result<(), String> {
+    nix::fcntl::fcntl(
+        fd,
+        nix::fcntl::FcntlArg::F_SETFL(nix::fcntl::OFlag::O_NONBLOCK),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn close_all(fds: &[libc::c_int]) -> Result<(), String> {
+    for fd in fds {
+        nix::unistd::close(*fd).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Check that every listener received at least a quarter of its share of the `total` flows.
+fn check_balanced(counts: &[usize], total: usize) -> Result<(), String> {
+    test_utils::result_assert_eq(counts.iter().sum::<usize>(), total, "Flows were lost")?;
+
+    let min = total / counts.len() / 4;
+    test_utils::result_assert(
+        counts.iter().all(|x| *x >= min),
+        &format!("Flows weren't balanced across sockets: {counts:?}"),
+    )
+}
+
+/// The option is disabled by default, and can be toggled.
+fn test_option(sock_type: SockType) -> Result<(), String> {
+    let fd = new_socket(sock_type, false)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let get = || {
+            nix::sys::socket::getsockopt(fd, nix::sys::socket::sockopt::ReusePort)
+                .map_err(|e| e.to_string())
+        };
+
+        test_utils::result_assert_eq(get()?, false, "Unexpected default")?;
+
+        nix::sys::socket::setsockopt(fd, nix::sys::socket::sockopt::ReusePort, &true)
+            .map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(get()?, true, "Unexpected value")?;
+
+        nix::sys::socket::setsockopt(fd, nix::sys::socket::sockopt::ReusePort, &false)
+            .map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(get()?, false, "Unexpected value")?;
+
+        Ok(())
+    })
+}
+
+/// Sockets can only share an address if they all set `SO_REUSEPORT`.
+fn test_bind_conflict(sock_type: SockType) -> Result<(), String> {
+    let fds = bind_group(sock_type, 2)?;
+    let addr = local_addr(fds[0])?;
+
+    let fd_without = new_socket(sock_type, false)?;
+
+    test_utils::run_and_close_fds(&[fds[0], fds[1], fd_without], || {
+        let rv = nix::sys::socket::bind(fd_without, &addr);
+        test_utils::result_assert_eq(rv, Err(Errno::EADDRINUSE), "Unexpected bind() result")?;
+
+        // an address used by a socket without the option can't be shared
+        let fd_first = new_socket(sock_type, false)?;
+        let fd_second = new_socket(sock_type, true)?;
+
+        test_utils::run_and_close_fds(&[fd_first, fd_second], || {
+            let any_port = SockaddrIn::new(127, 0, 0, 1, 0);
+            nix::sys::socket::bind(fd_first, &any_port).map_err(|e| e.to_string())?;
+
+            let rv = nix::sys::socket::bind(fd_second, &local_addr(fd_first)?);
+            test_utils::result_assert_eq(rv, Err(Errno::EADDRINUSE), "Unexpected bind() result")
+        })
+    })
+}
+
+/// Connect `count` clients to `addr`, and return the number of connections accepted by each
+/// listener. All connections are closed before returning.
+fn connect_and_count(
+    listeners: &[libc::c_int],
+    addr: &SockaddrIn,
+    count: usize,
+) -> Result<Vec<usize>, String> {
+    let mut clients = vec![];
+    for _ in 0..count {
+        let fd = new_socket(SockType::Stream, false)?;
+        clients.push(fd);
+        nix::sys::socket::connect(fd, addr).map_err(|e| e.to_string())?;
+    }
+
+    // give the listeners time to finish the handshakes
+    std::thread::sleep(Duration::from_millis(10));
+
+    let mut counts = vec![0; listeners.len()];
+    let mut accepted = vec![];
+
+    for (i, listener) in listeners.iter().enumerate() {
+        loop {
+            match nix::sys::socket::accept4(*listener, SockFlag::SOCK_NONBLOCK) {
+                Ok(fd) => {
+                    accepted.push(fd);
+                    counts[i] += 1;
+                }
+                Err(Errno::EAGAIN) => break,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+
+    // the clients close first so that the server ends of the connections don't linger
+    close_all(&clients)?;
+    std::thread::sleep(Duration::from_millis(10));
+    close_all(&accepted)?;
+    std::thread::sleep(Duration::from_millis(10));
+
+    Ok(counts)
+}
+
+/// Connections are balanced across the listeners, and after a listener closes, new connections
+/// are balanced across the remaining listeners.
+fn test_tcp_distribution() -> Result<(), String> {
+    let listeners = bind_group(SockType::Stream, NUM_LISTENERS)?;
+    let addr = local_addr(listeners[0])?;
+
+    for fd in &listeners {
+        nix::sys::socket::listen(*fd, 100).map_err(|e| e.to_string())?;
+        set_nonblocking(*fd)?;
+    }
+
+    let counts = connect_and_count(&listeners, &addr, 60)?;
+    check_balanced(&counts, 60)?;
+
+    // remove the first listener from the group
+    nix::unistd::close(listeners[0]).map_err(|e| e.to_string())?;
+    std::thread::sleep(Duration::from_millis(100));
+
+    test_utils::run_and_close_fds(&listeners[1..], || {
+        let counts = connect_and_count(&listeners[1..], &addr, 30)?;
+        check_balanced(&counts, 30)
+    })
+}
+
+/// Datagrams are balanced across the sockets, and all datagrams from a single client are received
+/// by the same socket.
+fn test_udp_distribution() -> Result<(), String> {
+    const NUM_CLIENTS: usize = 30;
+    const DATAGRAMS_PER_CLIENT: usize = 3;
+
+    let servers = bind_group(SockType::Datagram, NUM_LISTENERS)?;
+    let addr = local_addr(servers[0])?;
+
+    for fd in &servers {
+        set_nonblocking(*fd)?;
+    }
+
+    let mut clients = vec![];
+    for _ in 0..NUM_CLIENTS {
+        clients.push(new_socket(SockType::Datagram, false)?);
+    }
+
+    let mut all_fds = servers.clone();
+    all_fds.extend(&clients);
+
+    test_utils::run_and_close_fds(&all_fds, || {
+        for fd in &clients {
+            for _ in 0..DATAGRAMS_PER_CLIENT {
+                nix::sys::socket::sendto(*fd, &[1, 2, 3], &addr, MsgFlags::empty())
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+
+        // map each client's port to the server that received its datagrams
+        let mut receivers: HashMap<u16, usize> = HashMap::new();
+        let mut counts = vec![0; servers.len()];
+
+        for (i, server) in servers.iter().enumerate() {
+            let mut buf = [0u8; 10];
+            loop {
+                let client_addr = match nix::sys::socket::recvfrom::<SockaddrIn>(*server, &mut buf)
+                {
+                    Ok((_, addr)) => addr.unwrap(),
+                    Err(Errno::EAGAIN) => break,
+                    Err(e) => return Err(e.to_string()),
+                };
+
+                let receiver = *receivers.entry(client_addr.port()).or_insert(i);
+                test_utils::result_assert_eq(
+                    receiver,
+                    i,
+                    "Datagrams from a client were received by different sockets",
+                )?;
+
+                counts[i] += 1;
+            }
+        }
+
+        test_utils::result_assert_eq(receivers.len(), NUM_CLIENTS, "Clients are missing")?;
+
+        // count the number of clients per server
+        let counts: Vec<_> = counts.iter().map(|x| x / DATAGRAMS_PER_CLIENT).collect();
+        check_balanced(&counts, NUM_CLIENTS)
+    })
+}
@@ -69,10 +69,8 @@ enum SendRecvMethod {
     /// For `sendto()`/`recvfrom()`.
     ToFrom,
     /// For `sendmsg()`/`recvmsg()`.
-    #[allow(dead_code)]
     Msg,
-    /// For `sendmmsg()`/`recvmmsg()`.
-    #[allow(dead_code)]
+    /// For `sendmmsg()`/`recvmmsg()` with a single message.
     Mmsg,
 }
 
@@ -103,8 +101,7 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
     let sys_methods = [
         SendRecvMethod::ToFrom,
         SendRecvMethod::Msg,
-        // TODO: support sendmmsg/recvmmsg
-        //SendRecvMethod::Mmsg,
+        SendRecvMethod::Mmsg,
     ];
 
     for &sys_method in sys_methods.iter() {
@@ -204,13 +201,12 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
                     test_utils::ShadowTest::new(
                         &append_args("test_flag_peek"),
                         move || test_flag_peek(sys_method, init_method, sock_type),
-                        match (init_method.domain(), sock_type) {
-                            // TODO: enable if shadow supports MSG_PEEK for tcp or unix sockets
-                            (libc::AF_INET, libc::SOCK_DGRAM) => {
-                                set![TestEnv::Libc, TestEnv::Shadow]
-                            }
-                            _ => set![TestEnv::Libc],
-                        },
+                        set![TestEnv::Libc, TestEnv::Shadow],
+                    ),
+                    test_utils::ShadowTest::new(
+                        &append_args("test_flag_peek_contents"),
+                        move || test_flag_peek_contents(init_method, sock_type),
+                        set![TestEnv::Libc, TestEnv::Shadow],
                     ),
                     test_utils::ShadowTest::new(
                         &append_args("test_blocking"),
@@ -475,6 +471,99 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
         set![TestEnv::Libc, TestEnv::Shadow],
     )]);
 
+    for &sock_type in &[libc::SOCK_STREAM, libc::SOCK_DGRAM, libc::SOCK_SEQPACKET] {
+        // add details to the test names to avoid duplicates
+        let append_args = |s| format!("{s} <sock_type={sock_type}>");
+
+        tests.extend(vec![
+            test_utils::ShadowTest::new(
+                &append_args("test_recvmsg_unused_control_buf"),
+                move || test_recvmsg_unused_control_buf(sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_recvmsg_ctrunc"),
+                move || test_recvmsg_ctrunc(sock_type),
+                // TODO: enable if shadow supports SCM_RIGHTS
+                set![TestEnv::Libc],
+            ),
+        ]);
+    }
+
+    tests.extend(vec![test_utils::ShadowTest::new(
+        "test_peek_stream_prefix",
+        test_peek_stream_prefix,
+        set![TestEnv::Libc, TestEnv::Shadow],
+    )]);
+
+    tests.extend(vec![test_utils::ShadowTest::new(
+        "test_unconnected_udp_request_response",
+        test_unconnected_udp_request_response,
+        set![TestEnv::Libc, TestEnv::Shadow],
+    )]);
+
+    for &domain in &[libc::AF_INET, libc::AF_UNIX] {
+        // add details to the test names to avoid duplicates
+        let append_args = |s| format!("{s} <domain={domain:?}>");
+
+        tests.extend(vec![test_utils::ShadowTest::new(
+            &append_args("test_zero_len_dgram_sendto"),
+            move || test_zero_len_dgram_sendto(domain),
+            set![TestEnv::Libc, TestEnv::Shadow],
+        )]);
+    }
+
+    for &sys_method in &[SendRecvMethod::ToFrom, SendRecvMethod::Msg] {
+        // add details to the test names to avoid duplicates
+        let append_args = |s| format!("{s} <sys_method={sys_method:?}>");
+
+        tests.extend(vec![
+            test_utils::ShadowTest::new(
+                &append_args("test_flag_waitall"),
+                move || test_flag_waitall(sys_method),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_flag_waitall_peer_close"),
+                move || test_flag_waitall_peer_close(sys_method),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+        ]);
+
+        for &init_method in &[
+            SocketInitMethod::Inet,
+            SocketInitMethod::Unix,
+            SocketInitMethod::UnixSocketpair,
+        ] {
+            // add details to the test names to avoid duplicates
+            let append_args =
+                |s| format!("{s} <init_method={init_method:?}, sys_method={sys_method:?}>");
+
+            tests.extend(vec![
+                test_utils::ShadowTest::new(
+                    &append_args("test_flag_waitall_chunks"),
+                    move || test_flag_waitall_chunks(init_method, sys_method),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_flag_waitall_dgram"),
+                    move || test_flag_waitall_dgram(init_method, sys_method),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_interrupted_send_after_progress"),
+                    move || test_interrupted_send_after_progress(init_method, sys_method),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_interrupted_send_before_progress"),
+                    move || test_interrupted_send_before_progress(init_method, sys_method),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+            ]);
+        }
+    }
+
     tests
 }
 
@@ -1007,7 +1096,6 @@ fn test_flag_peek(
         fd: fd_client,
         len: outbuf_10_bytes.len(),
         buf: Some(&outbuf_10_bytes),
-        flags: libc::MSG_PEEK,
         ..Default::default()
     };
 
@@ -1934,7 +2022,19 @@ fn test_short_recv_buf_dgram(
         assert_eq!(unsafe { libc::usleep(10000) }, 0);
 
         // read 1999 bytes; last byte will be discarded and should not cause an error
-        simple_recvfrom_helper(sys_method, fd_server, &mut vec![0u8; 1999], &[], true)?;
+        let mut buf = vec![0u8; 1999];
+        let mut args = RecvfromArguments {
+            fd: fd_server,
+            len: buf.len(),
+            buf: Some(&mut buf),
+            ..Default::default()
+        };
+        let (_, msg_flags) = check_recv_call(&mut args, sys_method, &[], true)?;
+
+        // MSG_TRUNC should be set in msg_flags, even though it wasn't passed to recvmsg()
+        if let Some(msg_flags) = msg_flags {
+            test_utils::result_assert(msg_flags & libc::MSG_TRUNC != 0, "MSG_TRUNC was not set")?;
+        }
 
         Ok(())
     })
@@ -2439,6 +2539,760 @@ fn test_unix_dgram_multiple_senders() -> Result<(), String> {
     Ok(())
 }
 
+/// Test recvmsg() on a unix socket with a control buffer when there's no control data to receive.
+fn test_recvmsg_unused_control_buf(sock_type: libc::c_int) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        SocketInitMethod::UnixSocketpair,
+        sock_type,
+        libc::SOCK_NONBLOCK,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        simple_sendto_helper(SendRecvMethod::Msg, fd_client, &[1, 2, 3], &[], true)?;
+
+        // shadow needs to run events
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        let mut buf = [0u8; 10];
+        let mut control = [0u8; 64];
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        };
+        let mut msg = libc::msghdr {
+            msg_name: std::ptr::null_mut(),
+            msg_namelen: 0,
+            msg_iov: &mut iov,
+            msg_iovlen: 1,
+            msg_control: control.as_mut_ptr() as *mut libc::c_void,
+            msg_controllen: control.len(),
+            msg_flags: 0,
+        };
+
+        let rv = unsafe { libc::recvmsg(fd_server, &mut msg, 0) };
+        test_utils::result_assert_eq(rv, 3, "Unexpected recvmsg() return value")?;
+        test_utils::result_assert_eq(msg.msg_controllen, 0, "Unexpected control data")?;
+        test_utils::result_assert_eq(msg.msg_flags, 0, "Unexpected msg_flags")?;
+
+        Ok(())
+    })
+}
+
+/// Test that recvmsg() sets MSG_CTRUNC and closes the received fds when the control buffer is too
+/// small for an SCM_RIGHTS message.
+fn test_recvmsg_ctrunc(sock_type: libc::c_int) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        SocketInitMethod::UnixSocketpair,
+        sock_type,
+        libc::SOCK_NONBLOCK,
+        /* bind_client = */ false,
+    );
+
+    // the fd that we'll send
+    let fd_sent = nix::unistd::dup(libc::STDOUT_FILENO).unwrap();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server, fd_sent], || {
+        let control_space = libc::CMSG_SPACE(std::mem::size_of::<libc::c_int>() as u32) as usize;
+
+        // send one byte with the fd as control data
+        {
+            let mut buf = [1u8];
+            let mut control = vec![0u8; control_space];
+            let mut iov = libc::iovec {
+                iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+                iov_len: buf.len(),
+            };
+            let msg = libc::msghdr {
+                msg_name: std::ptr::null_mut(),
+                msg_namelen: 0,
+                msg_iov: &mut iov,
+                msg_iovlen: 1,
+                msg_control: control.as_mut_ptr() as *mut libc::c_void,
+                msg_controllen: control.len(),
+                msg_flags: 0,
+            };
+            unsafe {
+                let cmsg = libc::CMSG_FIRSTHDR(&msg);
+                (*cmsg).cmsg_level = libc::SOL_SOCKET;
+                (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+                (*cmsg).cmsg_len =
+                    libc::CMSG_LEN(std::mem::size_of::<libc::c_int>() as u32) as usize;
+                std::ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut libc::c_int, fd_sent);
+            }
+
+            let rv = unsafe { libc::sendmsg(fd_client, &msg, 0) };
+            test_utils::result_assert_eq(rv, 1, "Unexpected sendmsg() return value")?;
+        }
+
+        // shadow needs to run events
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        // the next fd that will be allocated, so that we can check that the received fd was closed
+        let next_fd = nix::unistd::dup(libc::STDOUT_FILENO).unwrap();
+        nix::unistd::close(next_fd).unwrap();
+
+        // receive with a control buffer that's too small for the cmsg header
+        let mut buf = [0u8; 10];
+        let mut control = [0u8; 4];
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        };
+        let mut msg = libc::msghdr {
+            msg_name: std::ptr::null_mut(),
+            msg_namelen: 0,
+            msg_iov: &mut iov,
+            msg_iovlen: 1,
+            msg_control: control.as_mut_ptr() as *mut libc::c_void,
+            msg_controllen: control.len(),
+            msg_flags: 0,
+        };
+
+        let rv = unsafe { libc::recvmsg(fd_server, &mut msg, 0) };
+        test_utils::result_assert_eq(rv, 1, "Unexpected recvmsg() return value")?;
+        test_utils::result_assert_eq(msg.msg_controllen, 0, "Unexpected control data")?;
+        test_utils::result_assert(
+            msg.msg_flags & libc::MSG_CTRUNC != 0,
+            "MSG_CTRUNC was not set",
+        )?;
+
+        // the discarded fd should not have been installed in our descriptor table
+        test_utils::result_assert_eq(
+            nix::fcntl::fcntl(next_fd, nix::fcntl::FcntlArg::F_GETFD),
+            Err(nix::errno::Errno::EBADF),
+            "The discarded fd was not closed",
+        )?;
+
+        Ok(())
+    })
+}
+
+/// Test that peeking at a tcp stream returns a prefix of the data, even when the data was sent in
+/// multiple packets and is peeked into multiple iovs.
+fn test_peek_stream_prefix() -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        libc::SOCK_NONBLOCK,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        // send the message in two parts
+        for part in [&b"hello "[..], &b"world"[..]] {
+            let rv = nix::unistd::write(fd_client, part).map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(rv, part.len(), "write() failed")?;
+        }
+
+        // shadow needs to run events
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        // peek into two iovs
+        let mut iov_1 = [0u8; 3];
+        let mut iov_2 = [0u8; 5];
+        let mut iovs = [
+            std::io::IoSliceMut::new(&mut iov_1),
+            std::io::IoSliceMut::new(&mut iov_2),
+        ];
+        let bytes = nix::sys::socket::recvmsg::<nix::sys::socket::SockaddrStorage>(
+            fd_server,
+            &mut iovs,
+            None,
+            MsgFlags::MSG_PEEK,
+        )
+        .map_err(|e| e.to_string())?
+        .bytes;
+        test_utils::result_assert_eq(bytes, 8, "Unexpected number of peeked bytes")?;
+        test_utils::result_assert_eq(&iov_1, b"hel", "Unexpected peeked data")?;
+        test_utils::result_assert_eq(&iov_2, b"lo wo", "Unexpected peeked data")?;
+
+        // peeking again returns the same data
+        let mut peeked = [0u8; 4];
+        let rv = nix::sys::socket::recv(fd_server, &mut peeked, MsgFlags::MSG_PEEK)
+            .map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 4, "Unexpected number of peeked bytes")?;
+        test_utils::result_assert_eq(&peeked, b"hell", "Unexpected peeked data")?;
+
+        // the full message starts with the peeked data
+        let mut buf = [0u8; 20];
+        let rv = nix::sys::socket::recv(fd_server, &mut buf, MsgFlags::empty())
+            .map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(&buf[..rv], &b"hello world"[..], "Unexpected message")?;
+        test_utils::result_assert_eq(&buf[..4], &peeked[..], "Peeked data isn't a prefix")?;
+
+        // the data was consumed
+        let rv = nix::sys::socket::recv(fd_server, &mut buf, MsgFlags::MSG_PEEK);
+        test_utils::result_assert_eq(rv, Err(nix::errno::Errno::EAGAIN), "Unexpected result")
+    })
+}
+
+/// Test a request and response between two unconnected udp sockets, where the client isn't bound
+/// before its first `sendto()` and each side learns its peer's address from `recvfrom()`.
+fn test_unconnected_udp_request_response() -> Result<(), String> {
+    use nix::sys::socket::{self, AddressFamily, SockFlag, SockType, SockaddrIn};
+
+    let new_udp_socket = || {
+        socket::socket(
+            AddressFamily::Inet,
+            SockType::Datagram,
+            SockFlag::SOCK_NONBLOCK,
+            None,
+        )
+        .unwrap()
+    };
+
+    let fd_client = new_udp_socket();
+    let fd_server = new_udp_socket();
+
+    socket::bind(fd_server, &SockaddrIn::new(127, 0, 0, 1, 0)).unwrap();
+    let server_addr: SockaddrIn = socket::getsockname(fd_server).unwrap();
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        // the client is implicitly bound to an ephemeral port
+        let rv = socket::sendto(fd_client, b"request", &server_addr, MsgFlags::empty())
+            .map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 7, "Unexpected number of bytes sent")?;
+
+        let client_port = socket::getsockname::<SockaddrIn>(fd_client)
+            .map_err(|e| e.to_string())?
+            .port();
+        test_utils::result_assert(client_port != 0, "The client wasn't bound")?;
+
+        // shadow needs to run events
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        // the server learns the client's address from the request
+        let mut buf = [0u8; 20];
+        let (rv, client_addr) =
+            socket::recvfrom::<SockaddrIn>(fd_server, &mut buf).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(&buf[..rv], &b"request"[..], "Unexpected request")?;
+        test_utils::result_assert_eq(
+            client_addr,
+            Some(SockaddrIn::new(127, 0, 0, 1, client_port)),
+            "Unexpected client address",
+        )?;
+
+        // reply to the address that the request came from
+        let rv = socket::sendto(
+            fd_server,
+            b"response",
+            &client_addr.unwrap(),
+            MsgFlags::empty(),
+        )
+        .map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 8, "Unexpected number of bytes sent")?;
+
+        // shadow needs to run events
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        // receive the response with an address buffer that's too small for the server's address
+        let mut addr: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+        let mut addr_len: libc::socklen_t = 4;
+        let rv = unsafe {
+            libc::recvfrom(
+                fd_client,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+                0,
+                std::ptr::from_mut(&mut addr) as *mut libc::sockaddr,
+                &mut addr_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 8, "Unexpected number of bytes received")?;
+        test_utils::result_assert_eq(&buf[..8], &b"response"[..], "Unexpected response")?;
+
+        // the full length is returned, but only the first 4 bytes (the family and port) are
+        // written
+        test_utils::result_assert_eq(
+            addr_len as usize,
+            std::mem::size_of::<libc::sockaddr_in>(),
+            "Unexpected address length",
+        )?;
+        test_utils::result_assert_eq(
+            addr.sin_family,
+            libc::AF_INET as libc::sa_family_t,
+            "Unexpected address family",
+        )?;
+        test_utils::result_assert_eq(
+            u16::from_be(addr.sin_port),
+            server_addr.port(),
+            "Unexpected server port",
+        )?;
+        test_utils::result_assert_eq(addr.sin_addr.s_addr, 0, "The address was not truncated")
+    })
+}
+
+/// Test that `MSG_PEEK` returns the same bytes that a following recv returns, and that peeking a
+/// datagram doesn't dequeue it or merge it with the next datagram.
+fn test_flag_peek_contents(
+    init_method: SocketInitMethod,
+    sock_type: libc::c_int,
+) -> Result<(), String> {
+    use nix::sys::socket;
+
+    let (fd_client, fd_server) = socket_init_helper(
+        init_method,
+        sock_type,
+        libc::SOCK_NONBLOCK,
+        /* bind_client = */ false,
+    );
+
+    let is_dgram = sock_type != libc::SOCK_STREAM;
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        socket::send(fd_client, &[1, 2, 3, 4], MsgFlags::empty()).map_err(|e| e.to_string())?;
+        socket::send(fd_client, &[5, 6, 7, 8, 9, 10], MsgFlags::empty())
+            .map_err(|e| e.to_string())?;
+
+        // shadow needs to run events
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        // a datagram peek only returns the first datagram; a stream peek returns all of the bytes
+        let expected_first: &[u8] = if is_dgram {
+            &[1, 2, 3, 4]
+        } else {
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+        };
+
+        let mut peeked = [0u8; 20];
+        let rv =
+            socket::recv(fd_server, &mut peeked, MsgFlags::MSG_PEEK).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(&peeked[..rv], expected_first, "Unexpected peeked bytes")?;
+
+        if is_dgram {
+            // a truncated peek with MSG_TRUNC returns the full length of the datagram
+            let mut small = [0u8; 2];
+            let rv = socket::recv(
+                fd_server,
+                &mut small,
+                MsgFlags::MSG_PEEK | MsgFlags::MSG_TRUNC,
+            )
+            .map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(rv, 4, "Unexpected truncated peek length")?;
+            test_utils::result_assert_eq(small, [1, 2], "Unexpected truncated peek bytes")?;
+        }
+
+        // the real read returns the same bytes that we peeked
+        let mut read = [0u8; 20];
+        let rv =
+            socket::recv(fd_server, &mut read, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(&read[..rv], expected_first, "Unexpected read bytes")?;
+
+        if is_dgram {
+            // the second datagram is still queued
+            let rv = socket::recv(fd_server, &mut peeked, MsgFlags::MSG_PEEK)
+                .map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(
+                &peeked[..rv],
+                &[5, 6, 7, 8, 9, 10][..],
+                "Unexpected peek",
+            )?;
+            let rv =
+                socket::recv(fd_server, &mut read, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(&read[..rv], &[5, 6, 7, 8, 9, 10][..], "Unexpected read")?;
+        }
+
+        // nothing is left
+        test_utils::result_assert_eq(
+            socket::recv(fd_server, &mut read, MsgFlags::MSG_PEEK),
+            Err(nix::errno::Errno::EAGAIN),
+            "Unexpected peek result on an empty socket",
+        )
+    })
+}
+
+/// Test that a zero-length `sendto()` on an unconnected datagram socket enqueues a distinct empty
+/// datagram, which `recvfrom()` returns as a 0-byte message rather than as "no message".
+fn test_zero_len_dgram_sendto(domain: libc::c_int) -> Result<(), String> {
+    use nix::sys::socket::{self, AddressFamily, SockFlag, SockType, SockaddrStorage};
+
+    let (family, bind_addr_client, bind_addr_server): (_, SockaddrStorage, SockaddrStorage) =
+        match domain {
+            libc::AF_INET => (
+                AddressFamily::Inet,
+                socket::SockaddrIn::new(127, 0, 0, 1, 0).into(),
+                socket::SockaddrIn::new(127, 0, 0, 1, 0).into(),
+            ),
+            libc::AF_UNIX => {
+                let pid = std::process::id();
+                let name = |s| format!("test_zero_len_dgram_{s}_{pid}");
+                (
+                    AddressFamily::Unix,
+                    socket::UnixAddr::new_abstract(name("client").as_bytes())
+                        .unwrap()
+                        .into(),
+                    socket::UnixAddr::new_abstract(name("server").as_bytes())
+                        .unwrap()
+                        .into(),
+                )
+            }
+            _ => unimplemented!(),
+        };
+
+    let new_dgram_socket =
+        || socket::socket(family, SockType::Datagram, SockFlag::SOCK_NONBLOCK, None).unwrap();
+
+    let fd_client = new_dgram_socket();
+    let fd_server = new_dgram_socket();
+
+    socket::bind(fd_client, &bind_addr_client).unwrap();
+    socket::bind(fd_server, &bind_addr_server).unwrap();
+    let client_addr: SockaddrStorage = socket::getsockname(fd_client).unwrap();
+    let server_addr: SockaddrStorage = socket::getsockname(fd_server).unwrap();
+
+    let is_readable = |fd| {
+        let mut fds = [nix::poll::PollFd::new(fd, nix::poll::PollFlags::POLLIN)];
+        nix::poll::poll(&mut fds, 0).unwrap();
+        fds[0]
+            .revents()
+            .unwrap()
+            .contains(nix::poll::PollFlags::POLLIN)
+    };
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        let mut buf = [0u8; 10];
+
+        // no message
+        test_utils::result_assert(!is_readable(fd_server), "Server was readable")?;
+        test_utils::result_assert_eq(
+            socket::recvfrom::<SockaddrStorage>(fd_server, &mut buf).map(|(rv, _)| rv),
+            Err(nix::errno::Errno::EAGAIN),
+            "Unexpected recvfrom result with no message",
+        )?;
+
+        // two empty datagrams, which shouldn't be merged or dropped
+        for _ in 0..2 {
+            let rv = socket::sendto(fd_client, &[], &server_addr, MsgFlags::empty())
+                .map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(rv, 0, "Unexpected number of bytes sent")?;
+        }
+
+        // shadow needs to run events
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        for _ in 0..2 {
+            test_utils::result_assert(is_readable(fd_server), "Server wasn't readable")?;
+            let (rv, from) = socket::recvfrom::<SockaddrStorage>(fd_server, &mut buf)
+                .map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(rv, 0, "Unexpected number of bytes received")?;
+            test_utils::result_assert_eq(from, Some(client_addr), "Unexpected source address")?;
+        }
+
+        // both datagrams were consumed
+        test_utils::result_assert(!is_readable(fd_server), "Server was readable")?;
+        test_utils::result_assert_eq(
+            socket::recvfrom::<SockaddrStorage>(fd_server, &mut buf).map(|(rv, _)| rv),
+            Err(nix::errno::Errno::EAGAIN),
+            "Unexpected recvfrom result after consuming the datagrams",
+        )
+    })
+}
+
+/// Test that a blocking tcp recv with `MSG_WAITALL` doesn't return until all of the requested data
+/// has arrived, even when the data is sent in multiple parts.
+fn test_flag_waitall(sys_method: SendRecvMethod) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    let outbuf: Vec<u8> = (0..20).collect();
+    let mut inbuf: Vec<u8> = vec![0u8; 20];
+
+    let sendto_args_1 = SendtoArguments {
+        fd: fd_client,
+        len: 10,
+        buf: Some(&outbuf[..10]),
+        ..Default::default()
+    };
+
+    let sendto_args_2 = SendtoArguments {
+        fd: fd_client,
+        len: 10,
+        buf: Some(&outbuf[10..]),
+        ..Default::default()
+    };
+
+    let mut recvfrom_args = RecvfromArguments {
+        fd: fd_server,
+        len: inbuf.len(),
+        buf: Some(&mut inbuf),
+        flags: libc::MSG_WAITALL,
+        ..Default::default()
+    };
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        std::thread::scope(|scope| {
+            // send the first half immediately, and the second half after 100 ms
+            let handle = scope.spawn(move || {
+                check_send_call(&sendto_args_1, sys_method, &[], true)?;
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                check_send_call(&sendto_args_2, sys_method, &[], true)
+            });
+
+            // the recv shouldn't return until the second half has arrived
+            let time_start = std::time::Instant::now();
+            check_recv_call(&mut recvfrom_args, sys_method, &[], true)?;
+            assert!(time_start.elapsed() > std::time::Duration::from_millis(70));
+
+            handle.join().unwrap()
+        })
+    })?;
+
+    test_utils::result_assert_eq(inbuf, outbuf, "Unexpected data")
+}
+
+/// Test that a blocking tcp recv with `MSG_WAITALL` returns the data it has received when the peer
+/// closes the connection.
+fn test_flag_waitall_peer_close(sys_method: SendRecvMethod) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    let outbuf: Vec<u8> = vec![1u8; 10];
+    let mut inbuf: Vec<u8> = vec![0u8; 20];
+
+    let sendto_args = SendtoArguments {
+        fd: fd_client,
+        len: outbuf.len(),
+        buf: Some(&outbuf),
+        ..Default::default()
+    };
+
+    let mut recvfrom_args = RecvfromArguments {
+        fd: fd_server,
+        len: inbuf.len(),
+        buf: Some(&mut inbuf),
+        flags: libc::MSG_WAITALL,
+        ..Default::default()
+    };
+
+    test_utils::run_and_close_fds(&[fd_server], || {
+        std::thread::scope(|scope| {
+            // send half of the requested data, and then close the connection after 100 ms
+            let handle = scope.spawn(move || {
+                check_send_call(&sendto_args, sys_method, &[], true)?;
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                nix::unistd::close(fd_client).map_err(|e| e.to_string())
+            });
+
+            // the recv returns the data it received before the peer closed
+            let (rv, _) = check_recv_call(&mut recvfrom_args, sys_method, &[], false)?;
+            test_utils::result_assert_eq(rv, 10, "Unexpected number of bytes")?;
+
+            handle.join().unwrap()
+        })
+    })
+}
+
+/// Test that a single blocking stream recv with `MSG_WAITALL` returns all of the requested data
+/// when it's sent in two chunks.
+fn test_flag_waitall_chunks(
+    init_method: SocketInitMethod,
+    sys_method: SendRecvMethod,
+) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        init_method,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    let outbuf: Vec<u8> = (0..1000).map(|x| x as u8).collect();
+    let mut inbuf: Vec<u8> = vec![0u8; 1000];
+
+    let sendto_args_1 = SendtoArguments {
+        fd: fd_client,
+        len: 500,
+        buf: Some(&outbuf[..500]),
+        ..Default::default()
+    };
+
+    let sendto_args_2 = SendtoArguments {
+        fd: fd_client,
+        len: 500,
+        buf: Some(&outbuf[500..]),
+        ..Default::default()
+    };
+
+    let mut recvfrom_args = RecvfromArguments {
+        fd: fd_server,
+        len: inbuf.len(),
+        buf: Some(&mut inbuf),
+        flags: libc::MSG_WAITALL,
+        ..Default::default()
+    };
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        std::thread::scope(|scope| {
+            // send the first chunk immediately, and the second chunk after 100 ms
+            let handle = scope.spawn(move || {
+                check_send_call(&sendto_args_1, sys_method, &[], true)?;
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                check_send_call(&sendto_args_2, sys_method, &[], true)
+            });
+
+            let (rv, _) = check_recv_call(&mut recvfrom_args, sys_method, &[], false)?;
+            test_utils::result_assert_eq(rv, 1000, "Unexpected number of bytes")?;
+
+            handle.join().unwrap()
+        })
+    })?;
+
+    test_utils::result_assert_eq(inbuf, outbuf, "Unexpected data")
+}
+
+/// Test that `MSG_WAITALL` is ignored for datagram sockets, so a recv returns a single message
+/// even if it's shorter than the requested length.
+fn test_flag_waitall_dgram(
+    init_method: SocketInitMethod,
+    sys_method: SendRecvMethod,
+) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        init_method,
+        libc::SOCK_DGRAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    let outbuf: Vec<u8> = vec![1u8; 500];
+    let mut inbuf: Vec<u8> = vec![0u8; 1000];
+
+    let sendto_args = SendtoArguments {
+        fd: fd_client,
+        len: outbuf.len(),
+        buf: Some(&outbuf),
+        ..Default::default()
+    };
+
+    let mut recvfrom_args = RecvfromArguments {
+        fd: fd_server,
+        len: inbuf.len(),
+        buf: Some(&mut inbuf),
+        flags: libc::MSG_WAITALL,
+        ..Default::default()
+    };
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        check_send_call(&sendto_args, sys_method, &[], true)?;
+
+        // the recv returns the single message without waiting for more data
+        let (rv, _) = check_recv_call(&mut recvfrom_args, sys_method, &[], false)?;
+        test_utils::result_assert_eq(rv, 500, "Unexpected number of bytes")
+    })
+}
+
+/// Test that a blocking stream send doesn't return a short count when the send buffer becomes
+/// full, and that it returns the number of bytes sent rather than `EINTR` when it's interrupted by
+/// a signal after sending some of the data.
+fn test_interrupted_send_after_progress(
+    init_method: SocketInitMethod,
+    sys_method: SendRecvMethod,
+) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        init_method,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    // much more than the socket buffers can hold, so the send will block once they're full
+    let outbuf: Vec<u8> = vec![1u8; 16 * 1024 * 1024];
+    let mut inbuf: Vec<u8> = vec![0u8; 65536];
+
+    let sendto_args = SendtoArguments {
+        fd: fd_client,
+        len: outbuf.len(),
+        buf: Some(&outbuf),
+        ..Default::default()
+    };
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        let signal = nix::sys::signal::Signal::SIGUSR1;
+        test_utils::install_nop_signal_handler(signal).map_err(|e| e.to_string())?;
+
+        let interruptor =
+            test_utils::Interruptor::new(std::time::Duration::from_millis(100), signal);
+        let rv = check_send_call(&sendto_args, sys_method, &[], false)?;
+        drop(interruptor);
+
+        let sent = usize::try_from(rv).unwrap();
+        test_utils::result_assert(
+            sent > 0 && sent < outbuf.len(),
+            &format!("Unexpected number of bytes sent: {sent}"),
+        )?;
+
+        // the peer should receive exactly the bytes that were reported as sent
+        let mut received = 0;
+        while received < sent {
+            received += nix::unistd::read(fd_server, &mut inbuf).map_err(|e| e.to_string())?;
+        }
+        test_utils::result_assert_eq(received, sent, "Unexpected number of bytes received")?;
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let rv = nix::sys::socket::recv(fd_server, &mut inbuf, MsgFlags::MSG_DONTWAIT);
+        test_utils::result_assert_eq(rv, Err(nix::errno::Errno::EAGAIN), "Unexpected data")
+    })
+}
+
+/// Test that a blocking stream send returns `EINTR` when it's interrupted by a signal before
+/// sending any data.
+fn test_interrupted_send_before_progress(
+    init_method: SocketInitMethod,
+    sys_method: SendRecvMethod,
+) -> Result<(), String> {
+    let (fd_client, fd_server) = socket_init_helper(
+        init_method,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    let outbuf: Vec<u8> = vec![1u8; 100];
+
+    let sendto_args = SendtoArguments {
+        fd: fd_client,
+        len: outbuf.len(),
+        buf: Some(&outbuf),
+        ..Default::default()
+    };
+
+    test_utils::run_and_close_fds(&[fd_client, fd_server], || {
+        // fill the socket buffers, waiting for any data in flight to be acknowledged
+        loop {
+            let mut filled = 0;
+            loop {
+                match nix::sys::socket::send(fd_client, &[1u8; 4096], MsgFlags::MSG_DONTWAIT) {
+                    Ok(n) => filled += n,
+                    Err(nix::errno::Errno::EAGAIN) => break,
+                    Err(e) => return Err(e.to_string()),
+                }
+            }
+            if filled == 0 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        let signal = nix::sys::signal::Signal::SIGUSR1;
+        test_utils::install_nop_signal_handler(signal).map_err(|e| e.to_string())?;
+
+        // the send can't make any progress, so after it's interrupted it should return EINTR
+        let interruptor =
+            test_utils::Interruptor::new(std::time::Duration::from_millis(100), signal);
+        check_send_call(&sendto_args, sys_method, &[libc::EINTR], false)?;
+        drop(interruptor);
+
+        Ok(())
+    })
+}
+
 /// A helper function to call sendto() and recvfrom() with valid values
 /// and a user-provided fd.
 fn fd_test_helper(
@@ -2614,7 +3468,42 @@ fn check_send_call(
             )?
         }
         SendRecvMethod::Mmsg => {
-            todo!();
+            let mut iov = libc::iovec {
+                // casting a const pointer to a mut pointer, but syscall should not mutate data
+                iov_base: buf_ptr as *mut core::ffi::c_void,
+                iov_len: args.len,
+            };
+            let mut msgs = [libc::mmsghdr {
+                msg_hdr: libc::msghdr {
+                    // casting a const pointer to a mut pointer, but syscall should not mutate data
+                    msg_name: addr_ptr as *mut _,
+                    msg_namelen: args.addr_len,
+                    msg_iov: &mut iov,
+                    msg_iovlen: 1,
+                    msg_control: std::ptr::null_mut(),
+                    msg_controllen: 0,
+                    msg_flags: 0,
+                },
+                msg_len: 0,
+            }];
+            let rv = test_utils::check_system_call!(
+                || unsafe {
+                    libc::sendmmsg(
+                        args.fd,
+                        msgs.as_mut_ptr(),
+                        msgs.len().try_into().unwrap(),
+                        args.flags,
+                    )
+                },
+                expected_errnos,
+            )?;
+            // return the number of bytes rather than the number of messages, like the other
+            // methods
+            if rv == 1 {
+                msgs[0].msg_len as libc::ssize_t
+            } else {
+                rv as libc::ssize_t
+            }
         }
     };
 
@@ -2699,7 +3588,47 @@ fn check_recv_call(
             (rv, Some(msg.msg_flags))
         }
         SendRecvMethod::Mmsg => {
-            todo!();
+            let mut iov = libc::iovec {
+                iov_base: buf_ptr as *mut core::ffi::c_void,
+                iov_len: args.len,
+            };
+            let mut msgs = [libc::mmsghdr {
+                msg_hdr: libc::msghdr {
+                    msg_name: addr_ptr as *mut libc::c_void,
+                    msg_namelen: args.addr_len.unwrap_or(0),
+                    msg_iov: &mut iov,
+                    msg_iovlen: 1,
+                    msg_control: std::ptr::null_mut(),
+                    msg_controllen: 0,
+                    msg_flags: 0,
+                },
+                msg_len: 0,
+            }];
+            let rv = test_utils::check_system_call!(
+                || unsafe {
+                    libc::recvmmsg(
+                        args.fd,
+                        msgs.as_mut_ptr(),
+                        msgs.len().try_into().unwrap(),
+                        args.flags,
+                        std::ptr::null_mut(),
+                    )
+                },
+                expected_errnos,
+            )?;
+            if let Some(ref mut addr_len) = args.addr_len {
+                *addr_len = msgs[0].msg_hdr.msg_namelen;
+            }
+            // return the number of bytes rather than the number of messages, like the other
+            // methods
+            if rv == 1 {
+                (
+                    msgs[0].msg_len as libc::ssize_t,
+                    Some(msgs[0].msg_hdr.msg_flags),
+                )
+            } else {
+                (rv as libc::ssize_t, None)
+            }
         }
     };
 
@@ -154,6 +154,35 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
         }
     }
 
+    for &domain in domains.iter() {
+        for &how in [libc::SHUT_RD, libc::SHUT_RDWR].iter() {
+            // add details to the test names to avoid duplicates
+            let append_args = |s| format!("{} <domain={},how={}>", s, domain, how);
+
+            tests.extend(vec![
+                test_utils::ShadowTest::new(
+                    &append_args("test_listener_wakes_accept"),
+                    move || test_listener_wakes_accept(domain, how),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_listener_resets_pending"),
+                    move || test_listener_resets_pending(domain, how),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+            ])
+        }
+
+        // add details to the test names to avoid duplicates
+        let append_args = |s| format!("{} <domain={}>", s, domain);
+
+        tests.extend(vec![test_utils::ShadowTest::new(
+            &append_args("test_listener_shut_wr"),
+            move || test_listener_shut_wr(domain),
+            set![TestEnv::Libc, TestEnv::Shadow],
+        )])
+    }
+
     tests
 }
 
@@ -898,6 +927,154 @@ fn test_tcp_fin_correctness(domain: libc::c_int, flag: libc::c_int) -> Result<()
     })
 }
 
+/// Test that shutdown() with `SHUT_RD` or `SHUT_RDWR` on a listening socket wakes up an accept()
+/// that's blocked in another thread.
+fn test_listener_wakes_accept(domain: libc::c_int, how: libc::c_int) -> Result<(), String> {
+    let fd_listener = unsafe { libc::socket(domain, libc::SOCK_STREAM, 0) };
+    assert!(fd_listener >= 0);
+    assert_eq!(unsafe { libc::listen(fd_listener, 10) }, 0);
+
+    test_utils::run_and_close_fds(&[fd_listener], || {
+        let accept_thread = std::thread::spawn(move || {
+            let rv =
+                unsafe { libc::accept(fd_listener, std::ptr::null_mut(), std::ptr::null_mut()) };
+            (rv, test_utils::get_errno())
+        });
+
+        // give the thread time to block in accept()
+        assert_eq!(unsafe { libc::usleep(100_000) }, 0);
+
+        let args = ShutdownArguments {
+            fd: fd_listener,
+            how,
+        };
+        check_shutdown_call(&args, &[])?;
+
+        let (rv, errno) = accept_thread.join().unwrap();
+        test_utils::result_assert_eq(rv, -1, "Expected accept() to fail")?;
+        test_utils::result_assert_eq(errno, libc::EINVAL, "Unexpected accept() errno")?;
+
+        // the socket is no longer listening, so accept() fails without blocking
+        test_utils::check_system_call!(
+            || unsafe { libc::accept(fd_listener, std::ptr::null_mut(), std::ptr::null_mut()) },
+            &[libc::EINVAL],
+        )?;
+
+        Ok(())
+    })
+}
+
+/// Test that shutdown() with `SHUT_RD` or `SHUT_RDWR` on a listening socket resets connections
+/// that haven't been accepted yet.
+fn test_listener_resets_pending(domain: libc::c_int, how: libc::c_int) -> Result<(), String> {
+    let fd_client = unsafe { libc::socket(domain, libc::SOCK_STREAM, 0) };
+    let fd_listener = unsafe { libc::socket(domain, libc::SOCK_STREAM, 0) };
+    assert!(fd_client >= 0);
+    assert!(fd_listener >= 0);
+    assert_eq!(unsafe { libc::listen(fd_listener, 10) }, 0);
+
+    test_utils::run_and_close_fds(&[fd_client, fd_listener], || {
+        // get the listener address
+        let mut addr: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+        let mut addr_len = std::mem::size_of_val(&addr) as u32;
+        let rv = unsafe {
+            libc::getsockname(
+                fd_listener,
+                std::ptr::from_mut(&mut addr) as *mut libc::sockaddr,
+                &mut addr_len,
+            )
+        };
+        assert_eq!(rv, 0);
+
+        let rv = unsafe {
+            libc::connect(
+                fd_client,
+                std::ptr::from_ref(&addr) as *const libc::sockaddr,
+                addr_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 0, "Expected connect() to succeed")?;
+
+        // give the connection time to be queued for accept()
+        assert_eq!(unsafe { libc::usleep(100_000) }, 0);
+
+        let args = ShutdownArguments {
+            fd: fd_listener,
+            how,
+        };
+        check_shutdown_call(&args, &[])?;
+
+        // the connection was never accepted, so it's reset
+        let mut buf = [0u8; 10];
+        test_utils::check_system_call!(
+            || unsafe {
+                libc::recv(
+                    fd_client,
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    0,
+                )
+            },
+            &[libc::ECONNRESET],
+        )?;
+
+        // and it can't be accepted
+        test_utils::check_system_call!(
+            || unsafe { libc::accept(fd_listener, std::ptr::null_mut(), std::ptr::null_mut()) },
+            &[libc::EINVAL],
+        )?;
+
+        Ok(())
+    })
+}
+
+/// Test that shutdown() with `SHUT_WR` on a listening socket has no effect.
+fn test_listener_shut_wr(domain: libc::c_int) -> Result<(), String> {
+    let fd_client = unsafe { libc::socket(domain, libc::SOCK_STREAM, 0) };
+    let fd_listener = unsafe { libc::socket(domain, libc::SOCK_STREAM, 0) };
+    assert!(fd_client >= 0);
+    assert!(fd_listener >= 0);
+    assert_eq!(unsafe { libc::listen(fd_listener, 10) }, 0);
+
+    test_utils::run_and_close_fds(&[fd_client, fd_listener], || {
+        let args = ShutdownArguments {
+            fd: fd_listener,
+            how: libc::SHUT_WR,
+        };
+        check_shutdown_call(&args, &[])?;
+
+        // get the listener address
+        let mut addr: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+        let mut addr_len = std::mem::size_of_val(&addr) as u32;
+        let rv = unsafe {
+            libc::getsockname(
+                fd_listener,
+                std::ptr::from_mut(&mut addr) as *mut libc::sockaddr,
+                &mut addr_len,
+            )
+        };
+        assert_eq!(rv, 0);
+
+        // the socket is still listening
+        let rv = unsafe {
+            libc::connect(
+                fd_client,
+                std::ptr::from_ref(&addr) as *const libc::sockaddr,
+                addr_len,
+            )
+        };
+        test_utils::result_assert_eq(rv, 0, "Expected connect() to succeed")?;
+
+        let fd_server = test_utils::check_system_call!(
+            || unsafe { libc::accept(fd_listener, std::ptr::null_mut(), std::ptr::null_mut()) },
+            &[],
+        )?;
+        assert_eq!(unsafe { libc::close(fd_server) }, 0);
+
+        Ok(())
+    })
+}
+
 fn check_shutdown_call(
     args: &ShutdownArguments,
     expected_errnos: &[libc::c_int],
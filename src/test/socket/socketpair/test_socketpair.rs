@@ -47,15 +47,27 @@ fn main() -> Result<(), String> {
 }
 
 fn get_tests() -> Vec<test_utils::ShadowTest<Option<[libc::c_int; 2]>, String>> {
-    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![test_utils::ShadowTest::new(
-        "test_null_fds",
-        test_null_fds,
-        set![TestEnv::Libc, TestEnv::Shadow],
-    )];
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_null_fds",
+            test_null_fds,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_invalid_arguments",
+            test_invalid_arguments,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_raw_type",
+            test_raw_type,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
 
     // tests to repeat for different socket options
     for &domain in [libc::AF_UNIX, libc::AF_LOCAL, libc::AF_INET].iter() {
-        for &sock_type in [libc::SOCK_STREAM, libc::SOCK_DGRAM].iter() {
+        for &sock_type in [libc::SOCK_STREAM, libc::SOCK_DGRAM, libc::SOCK_SEQPACKET].iter() {
             for &flag in [0, libc::SOCK_NONBLOCK, libc::SOCK_CLOEXEC].iter() {
                 for &protocol in [0, libc::IPPROTO_TCP, libc::IPPROTO_UDP].iter() {
                     // add details to the test names to avoid duplicates
@@ -82,6 +94,14 @@ fn get_tests() -> Vec<test_utils::ShadowTest<Option<[libc::c_int; 2]>, String>>
                     tests.extend(more_tests);
                 }
             }
+
+            let append_args = |s| format!("{} <domain={},type={}>", s, domain, sock_type);
+
+            tests.push(test_utils::ShadowTest::new(
+                &append_args("test_flags"),
+                move || test_flags(domain, sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ));
         }
     }
 
@@ -104,6 +124,140 @@ fn test_null_fds() -> Result<Option<[libc::c_int; 2]>, String> {
     Ok(None)
 }
 
+/// Test socketpair with invalid types, protocols, and domains.
+fn test_invalid_arguments() -> Result<Option<[libc::c_int; 2]>, String> {
+    let cases = [
+        // unknown flag in the type
+        (libc::AF_UNIX, libc::SOCK_STREAM | 0x100, 0, libc::EINVAL),
+        // type out of range
+        (libc::AF_UNIX, 12, 0, libc::EINVAL),
+        // type not supported by unix sockets
+        (libc::AF_UNIX, libc::SOCK_RDM, 0, libc::ESOCKTNOSUPPORT),
+        // domain out of range
+        (-1, libc::SOCK_STREAM, 0, libc::EAFNOSUPPORT),
+        (1000, libc::SOCK_STREAM, 0, libc::EAFNOSUPPORT),
+    ];
+
+    for (domain, sock_type, protocol, errno) in cases {
+        let mut args = SocketpairArguments {
+            domain,
+            sock_type,
+            flag: 0,
+            protocol,
+            fds: Some([-1, -1]),
+        };
+        check_socketpair_call(&mut args, Some(&[errno]))?;
+    }
+
+    // unix sockets accept their own protocol family as the protocol
+    let mut args = SocketpairArguments {
+        domain: libc::AF_UNIX,
+        sock_type: libc::SOCK_STREAM,
+        flag: 0,
+        protocol: libc::PF_UNIX,
+        fds: Some([-1, -1]),
+    };
+    check_socketpair_call(&mut args, None)?;
+
+    Ok(args.fds)
+}
+
+/// Test that a raw unix socket pair behaves like a datagram socket pair.
+fn test_raw_type() -> Result<Option<[libc::c_int; 2]>, String> {
+    let mut args = SocketpairArguments {
+        domain: libc::AF_UNIX,
+        sock_type: libc::SOCK_RAW,
+        flag: 0,
+        protocol: 0,
+        fds: Some([-1, -1]),
+    };
+    check_socketpair_call(&mut args, None)?;
+
+    let [fd_a, fd_b] = args.fds.unwrap();
+
+    for msg in [b"ab", b"cd"] {
+        let rv = unsafe { libc::write(fd_a, msg.as_ptr() as *const libc::c_void, msg.len()) };
+        test_utils::result_assert_eq(rv, 2, "write() failed")?;
+    }
+
+    // message boundaries are preserved
+    let mut buf = [0u8; 4];
+    let rv = unsafe { libc::read(fd_b, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+    test_utils::result_assert_eq(rv, 2, "Expected to read only the first message")?;
+    test_utils::result_assert_eq(&buf[..2], &b"ab"[..], "Unexpected message")?;
+
+    Ok(args.fds)
+}
+
+/// Test that the `SOCK_NONBLOCK` and `SOCK_CLOEXEC` flags are applied to both sockets.
+fn test_flags(
+    domain: libc::c_int,
+    sock_type: libc::c_int,
+) -> Result<Option<[libc::c_int; 2]>, String> {
+    if ![libc::AF_UNIX, libc::AF_LOCAL].contains(&domain) {
+        return Ok(None);
+    }
+
+    for flag in [
+        0,
+        libc::SOCK_NONBLOCK,
+        libc::SOCK_CLOEXEC,
+        libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+    ] {
+        let mut args = SocketpairArguments {
+            domain,
+            sock_type,
+            flag,
+            protocol: 0,
+            fds: Some([-1, -1]),
+        };
+        check_socketpair_call(&mut args, None)?;
+
+        for fd in args.fds.unwrap() {
+            let status = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+            let fd_flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+            test_utils::result_assert(status >= 0 && fd_flags >= 0, "fcntl() failed")?;
+
+            test_utils::result_assert_eq(
+                status & libc::O_NONBLOCK != 0,
+                flag & libc::SOCK_NONBLOCK != 0,
+                &format!("Unexpected O_NONBLOCK for fd {fd} with flag {flag}"),
+            )?;
+            test_utils::result_assert_eq(
+                fd_flags & libc::FD_CLOEXEC != 0,
+                flag & libc::SOCK_CLOEXEC != 0,
+                &format!("Unexpected FD_CLOEXEC for fd {fd} with flag {flag}"),
+            )?;
+
+            // the access mode isn't affected by the flags
+            test_utils::result_assert_eq(
+                status & libc::O_ACCMODE,
+                libc::O_RDWR,
+                "Unexpected access mode",
+            )?;
+        }
+
+        // a nonblocking socket with no data shouldn't block
+        if flag & libc::SOCK_NONBLOCK != 0 {
+            let mut buf = [0u8; 1];
+            let fd = args.fds.unwrap()[0];
+            let rv = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, 1) };
+            test_utils::result_assert_eq(rv, -1, "read() should have failed")?;
+            test_utils::result_assert_eq(
+                test_utils::get_errno(),
+                libc::EAGAIN,
+                "Unexpected errno",
+            )?;
+        }
+
+        for fd in args.fds.unwrap() {
+            nix::unistd::close(fd).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(None)
+}
+
 /// Test socketpair with various arguments.
 fn test_arguments(
     domain: libc::c_int,
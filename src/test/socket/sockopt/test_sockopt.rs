@@ -185,6 +185,16 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
                     move || test_so_acceptconn(domain, sock_type),
                     set![TestEnv::Libc, TestEnv::Shadow],
                 ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_so_max_pacing_rate"),
+                    move || test_so_max_pacing_rate(domain, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_so_timeo"),
+                    move || test_so_timeo(domain, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
                 test_utils::ShadowTest::new(
                     &append_args("test_tcp_info"),
                     move || test_tcp_info(domain, sock_type),
@@ -200,6 +210,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
                     move || test_tcp_congestion(domain, sock_type),
                     set![TestEnv::Libc, TestEnv::Shadow],
                 ),
+                test_utils::ShadowTest::new(
+                    &append_args("test_invalid_buffers"),
+                    move || test_invalid_buffers(domain, sock_type),
+                    set![TestEnv::Libc, TestEnv::Shadow],
+                ),
             ];
 
             tests.extend(more_tests);
@@ -472,8 +487,7 @@ fn test_null_val_nonzero_len() -> Result<(), String> {
 
     test_utils::run_and_close_fds(&[fd], || {
         check_getsockopt_call(&mut get_args, &[libc::EFAULT])?;
-        // glibc returns EINVAL but shadow returns EFAULT
-        check_setsockopt_call(&mut set_args, &[libc::EINVAL, libc::EFAULT])?;
+        check_setsockopt_call(&mut set_args, &[libc::EINVAL])?;
         Ok(())
     })
 }
@@ -776,6 +790,114 @@ fn test_so_acceptconn(domain: libc::c_int, sock_type: libc::c_int) -> Result<(),
     })
 }
 
+/// Test getsockopt() and setsockopt() using the SO_MAX_PACING_RATE option with both 32-bit and
+/// 64-bit values.
+fn test_so_max_pacing_rate(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(domain, sock_type | libc::SOCK_NONBLOCK, 0) };
+    assert!(fd >= 0);
+
+    let level = libc::SOL_SOCKET;
+    let optname = libc::SO_MAX_PACING_RATE;
+
+    let get_rate_u32 = || -> Result<u32, String> {
+        let mut args = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 4]));
+        check_getsockopt_call(&mut args, &[])?;
+        test_utils::result_assert_eq(args.optlen.unwrap(), 4, "Unexpected optlen")?;
+        Ok(u32::from_ne_bytes(args.optval.unwrap().try_into().unwrap()))
+    };
+    let get_rate_u64 = || -> Result<u64, String> {
+        let mut args = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 8]));
+        check_getsockopt_call(&mut args, &[])?;
+        test_utils::result_assert_eq(args.optlen.unwrap(), 8, "Unexpected optlen")?;
+        Ok(u64::from_ne_bytes(args.optval.unwrap().try_into().unwrap()))
+    };
+
+    test_utils::run_and_close_fds(&[fd], || {
+        // unlimited by default
+        test_utils::result_assert_eq(get_rate_u32()?, u32::MAX, "Unexpected default rate")?;
+        test_utils::result_assert_eq(get_rate_u64()?, u64::MAX, "Unexpected default rate")?;
+
+        // a 32-bit value
+        let optval = 1_000_000u32.to_ne_bytes();
+        let mut set_args = SetsockoptArguments::new(fd, level, optname, Some(optval.into()));
+        check_setsockopt_call(&mut set_args, &[])?;
+        test_utils::result_assert_eq(get_rate_u32()?, 1_000_000, "Unexpected rate")?;
+        test_utils::result_assert_eq(get_rate_u64()?, 1_000_000, "Unexpected rate")?;
+
+        // a 64-bit value is capped when read as a 32-bit value
+        let optval = (1u64 << 40).to_ne_bytes();
+        let mut set_args = SetsockoptArguments::new(fd, level, optname, Some(optval.into()));
+        check_setsockopt_call(&mut set_args, &[])?;
+        test_utils::result_assert_eq(get_rate_u32()?, u32::MAX, "Unexpected rate")?;
+        test_utils::result_assert_eq(get_rate_u64()?, 1u64 << 40, "Unexpected rate")?;
+
+        // the largest 32-bit value means unlimited
+        let optval = u32::MAX.to_ne_bytes();
+        let mut set_args = SetsockoptArguments::new(fd, level, optname, Some(optval.into()));
+        check_setsockopt_call(&mut set_args, &[])?;
+        test_utils::result_assert_eq(get_rate_u64()?, u64::MAX, "Unexpected rate")?;
+
+        // too short
+        let optval = 1000u16.to_ne_bytes();
+        let mut set_args = SetsockoptArguments::new(fd, level, optname, Some(optval.into()));
+        check_setsockopt_call(&mut set_args, &[libc::EINVAL])?;
+
+        Ok(())
+    })
+}
+
+/// Test getsockopt() and setsockopt() using the SO_RCVTIMEO and SO_SNDTIMEO options.
+fn test_so_timeo(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(domain, sock_type, 0) };
+    assert!(fd >= 0);
+
+    let level = libc::SOL_SOCKET;
+
+    let timeval_bytes =
+        |sec: i64, usec: i64| -> Vec<u8> { [sec.to_ne_bytes(), usec.to_ne_bytes()].concat() };
+
+    test_utils::run_and_close_fds(&[fd], || {
+        for optname in [libc::SO_RCVTIMEO, libc::SO_SNDTIMEO] {
+            let get_timeout = || -> Result<Vec<u8>, String> {
+                let mut args = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 16]));
+                check_getsockopt_call(&mut args, &[])?;
+                test_utils::result_assert_eq(args.optlen.unwrap(), 16, "Unexpected optlen")?;
+                Ok(args.optval.unwrap())
+            };
+
+            // no timeout by default
+            test_utils::result_assert_eq(
+                get_timeout()?,
+                timeval_bytes(0, 0),
+                "Unexpected default",
+            )?;
+
+            let optval = timeval_bytes(1, 500_000);
+            let mut set_args = SetsockoptArguments::new(fd, level, optname, Some(optval.clone()));
+            check_setsockopt_call(&mut set_args, &[])?;
+            test_utils::result_assert_eq(get_timeout()?, optval, "Unexpected timeout")?;
+
+            // a zero timeout disables the timeout
+            let optval = timeval_bytes(0, 0);
+            let mut set_args = SetsockoptArguments::new(fd, level, optname, Some(optval.clone()));
+            check_setsockopt_call(&mut set_args, &[])?;
+            test_utils::result_assert_eq(get_timeout()?, optval, "Unexpected timeout")?;
+
+            // the microseconds are out of range
+            let optval = timeval_bytes(1, 1_000_000);
+            let mut set_args = SetsockoptArguments::new(fd, level, optname, Some(optval));
+            check_setsockopt_call(&mut set_args, &[libc::EDOM])?;
+
+            // too short
+            let optval = 5i64.to_ne_bytes();
+            let mut set_args = SetsockoptArguments::new(fd, level, optname, Some(optval.into()));
+            check_setsockopt_call(&mut set_args, &[libc::EINVAL])?;
+        }
+
+        Ok(())
+    })
+}
+
 /// Test getsockopt() and setsockopt() using the TCP_INFO option.
 fn test_tcp_info(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
     let fd = unsafe { libc::socket(domain, sock_type, 0) };
@@ -859,6 +981,7 @@ fn test_tcp_congestion(domain: libc::c_int, sock_type: libc::c_int) -> Result<()
     let get_args_2 = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 3]));
     let mut set_args_1 = SetsockoptArguments::new(fd, level, optname, Some("reno".into()));
     let mut set_args_2 = SetsockoptArguments::new(fd, level, optname, Some("ren".into()));
+    let mut set_args_3 = SetsockoptArguments::new(fd, level, optname, Some("cubic\0".into()));
 
     test_utils::run_and_close_fds(&[fd], || {
         for mut get_args in [get_args_1, get_args_2] {
@@ -905,13 +1028,25 @@ fn test_tcp_congestion(domain: libc::c_int, sock_type: libc::c_int) -> Result<()
             )?;
         }
 
-        // try setting a valid name
+        // try setting valid names
         let expected_errnos = if sock_type == libc::SOCK_STREAM {
             vec![]
         } else {
             vec![libc::ENOPROTOOPT, libc::EOPNOTSUPP]
         };
         check_setsockopt_call(&mut set_args_1, &expected_errnos)?;
+        check_setsockopt_call(&mut set_args_3, &expected_errnos)?;
+
+        if sock_type == libc::SOCK_STREAM {
+            // the last algorithm that we set should now be in use
+            let mut get_args = GetsockoptArguments::new(fd, level, optname, Some(vec![0u8; 16]));
+            check_getsockopt_call(&mut get_args, &[])?;
+            let returned_str = get_args.optval.as_ref().unwrap();
+            test_utils::result_assert(
+                returned_str.starts_with(b"cubic\0"),
+                "Unexpected value for TCP_CONGESTION",
+            )?;
+        }
 
         // try setting an invalid name
         let expected_errnos = if sock_type == libc::SOCK_STREAM {
@@ -925,6 +1060,178 @@ fn test_tcp_congestion(domain: libc::c_int, sock_type: libc::c_int) -> Result<()
     })
 }
 
+/// Test getsockopt() and setsockopt() with undersized, null, and negative-length buffers for
+/// several options.
+fn test_invalid_buffers(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(domain, sock_type, 0) };
+    assert!(fd >= 0);
+
+    let int_size = std::mem::size_of::<libc::c_int>();
+
+    // (level, optname, size of the option value, can the option be set)
+    let mut options = vec![
+        (libc::SOL_SOCKET, libc::SO_RCVBUF, int_size, true),
+        (libc::SOL_SOCKET, libc::SO_REUSEPORT, int_size, true),
+        (libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE, int_size, true),
+        (
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            std::mem::size_of::<libc::timeval>(),
+            true,
+        ),
+    ];
+
+    if sock_type == libc::SOCK_STREAM {
+        options.extend([
+            (libc::SOL_TCP, libc::TCP_NODELAY, int_size, true),
+            // the libc package doesn't expose 'struct tcp_info', but any prefix of it is valid
+            (libc::SOL_TCP, libc::TCP_INFO, 20, false),
+        ]);
+    }
+
+    test_utils::run_and_close_fds(&[fd], || {
+        for (level, optname, size, settable) in options {
+            let size = size as libc::socklen_t;
+
+            // a short buffer is filled with as much of the value as fits, and the optlen is
+            // unchanged
+            let dummy_optval = vec![0xAAu8; 2 * size as usize];
+            let mut get_args = GetsockoptArguments {
+                fd,
+                level,
+                optname,
+                optval: Some(dummy_optval.clone()),
+                optlen: Some(2),
+            };
+            check_getsockopt_call(&mut get_args, &[])?;
+            test_utils::result_assert_eq(
+                get_args.optlen.unwrap(),
+                2,
+                "The optlen should not have changed",
+            )?;
+            test_utils::result_assert_eq(
+                &get_args.optval.as_ref().unwrap()[2..],
+                &dummy_optval[2..],
+                "Bytes past the optlen should not have changed",
+            )?;
+
+            // a null optval or optlen is a bad address
+            let mut get_args = GetsockoptArguments {
+                fd,
+                level,
+                optname,
+                optval: None,
+                optlen: Some(size),
+            };
+            check_getsockopt_call(&mut get_args, &[libc::EFAULT])?;
+
+            let mut get_args = GetsockoptArguments {
+                fd,
+                level,
+                optname,
+                optval: Some(vec![0u8; size as usize]),
+                optlen: None,
+            };
+            check_getsockopt_call(&mut get_args, &[libc::EFAULT])?;
+
+            if level == libc::SOL_SOCKET {
+                // a negative optlen is invalid for socket-level options
+                let mut get_args = GetsockoptArguments {
+                    fd,
+                    level,
+                    optname,
+                    optval: None,
+                    optlen: Some(-1i32 as libc::socklen_t),
+                };
+                check_getsockopt_call(&mut get_args, &[libc::EINVAL])?;
+            } else {
+                // linux clamps the optlen of `SOL_TCP` options as an unsigned int, so a negative
+                // optlen is treated as a large buffer (`check_getsockopt_call()` doesn't allow an
+                // optlen larger than the buffer, so make the call directly)
+                let mut optval = [0u8; 1024];
+                let mut optlen = -1i32 as libc::socklen_t;
+                test_utils::check_system_call!(
+                    || unsafe {
+                        libc::getsockopt(
+                            fd,
+                            level,
+                            optname,
+                            optval.as_mut_ptr() as *mut core::ffi::c_void,
+                            &mut optlen,
+                        )
+                    },
+                    &[],
+                )?;
+                test_utils::result_assert(
+                    optlen >= size && (optlen as usize) <= optval.len(),
+                    &format!("Unexpected optlen {optlen} for a negative optlen"),
+                )?;
+            }
+
+            if !settable {
+                continue;
+            }
+
+            // a buffer that's too short for the value is invalid
+            let mut set_args = SetsockoptArguments {
+                fd,
+                level,
+                optname,
+                optval: Some(vec![0u8; size as usize]),
+                optlen: size - 1,
+            };
+            check_setsockopt_call(&mut set_args, &[libc::EINVAL])?;
+
+            // a null optval is a bad address
+            let mut set_args = SetsockoptArguments {
+                fd,
+                level,
+                optname,
+                optval: None,
+                optlen: size,
+            };
+            check_setsockopt_call(&mut set_args, &[libc::EFAULT])?;
+
+            // a negative optlen is invalid
+            let mut set_args = SetsockoptArguments {
+                fd,
+                level,
+                optname,
+                optval: None,
+                optlen: -1i32 as libc::socklen_t,
+            };
+            check_setsockopt_call(&mut set_args, &[libc::EINVAL])?;
+        }
+
+        if sock_type == libc::SOCK_STREAM {
+            // the congestion control name can be truncated, but not empty
+            let mut get_args = GetsockoptArguments {
+                fd,
+                level: libc::SOL_TCP,
+                optname: libc::TCP_CONGESTION,
+                optval: None,
+                optlen: Some(16),
+            };
+            check_getsockopt_call(&mut get_args, &[libc::EFAULT])?;
+
+            let mut set_args =
+                SetsockoptArguments::new(fd, libc::SOL_TCP, libc::TCP_CONGESTION, Some(vec![]));
+            check_setsockopt_call(&mut set_args, &[libc::EINVAL])?;
+
+            let mut set_args = SetsockoptArguments {
+                fd,
+                level: libc::SOL_TCP,
+                optname: libc::TCP_CONGESTION,
+                optval: None,
+                optlen: 4,
+            };
+            check_setsockopt_call(&mut set_args, &[libc::EFAULT])?;
+        }
+
+        Ok(())
+    })
+}
+
 fn check_getsockopt_call(
     args: &mut GetsockoptArguments,
     expected_errnos: &[libc::c_int],
@@ -0,0 +1,259 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client uploads data to a server over a link with a very large router queue and no AQM
+//! ("bufferbloat"), using a given congestion control algorithm. Loss-based algorithms like cubic
+//! only slow down once the queue overflows, so they keep the queue full. BBR paces its packets at
+//! the estimated bottleneck bandwidth and limits the data in flight to a small multiple of the
+//! bandwidth-delay product, so it should keep the queue small while still using the link well.
+//! The uploader also checks the algorithm's stats from `TCP_CC_INFO`.
+//!
+//! Usage:
+//! - `test_tcp_bufferbloat sink <port>`: receive the upload
+//! - `test_tcp_bufferbloat upload <server-ip> <port> <algorithm>`: upload data as fast as possible
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::{Duration, Instant};
+
+use nix::sys::socket::{self, AddressFamily, MsgFlags, Shutdown, SockFlag, SockType, SockaddrIn};
+
+/// How long the client uploads for.
+const UPLOAD_DURATION: Duration = Duration::from_secs(10);
+
+/// How long the client waits before measuring the data in flight, so that it isn't affected by
+/// the start of the connection.
+const WARMUP_DURATION: Duration = Duration::from_secs(5);
+
+/// The bandwidth of the congested link, which should match the server's `bandwidth_down` in the
+/// shadow config.
+const LINK_BITS_PER_SEC: f64 = 10_000_000.0;
+
+/// The round-trip time without any queueing, which should match the latency in the shadow
+/// config.
+const BASE_RTT: Duration = Duration::from_millis(20);
+
+/// The size of the packet payloads.
+const MSS: f64 = 1448.0;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} sink <port> | {0} upload <server-ip> <port> <algorithm>",
+        args[0]
+    );
+
+    let parse_port = |s: &str| s.parse::<u16>().map_err(|e| format!("Bad port: {e}"));
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("sink"), 3) => run_sink(parse_port(&args[2])?)?,
+        (Some("upload"), 5) => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            run_upload(SocketAddrV4::new(ip, parse_port(&args[3])?), &args[4])?
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+fn new_tcp_socket() -> Result<libc::c_int, String> {
+    socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Get the `tcpi_unacked` field of the socket's `struct tcp_info`, which is the number of packets
+/// in flight.
+fn get_info_unacked(fd: libc::c_int) -> Result<u32, String> {
+    // the libc package doesn't expose 'struct tcp_info', so we read it as an array; 'tcpi_unacked'
+    // is the 5th u32 following 8 bytes of u8 fields
+    let mut info = [0u32; 26];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(
+        info_len as usize,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+    Ok(info[6])
+}
+
+/// Get the name of the socket's congestion control algorithm from `TCP_CONGESTION`.
+fn get_congestion(fd: libc::c_int) -> Result<String, String> {
+    let mut name = [0u8; 16];
+    let mut name_len = name.len() as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_CONGESTION,
+            name.as_mut_ptr() as *mut libc::c_void,
+            &mut name_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+
+    let name = std::ffi::CStr::from_bytes_until_nul(&name).map_err(|e| e.to_string())?;
+    Ok(name.to_str().map_err(|e| e.to_string())?.to_string())
+}
+
+/// Get the socket's `union tcp_cc_info` from `TCP_CC_INFO`, and the number of bytes returned.
+fn get_cc_info(fd: libc::c_int) -> Result<([u32; 5], usize), String> {
+    // the libc package doesn't expose 'union tcp_cc_info', so we read it as an array; its largest
+    // member is 'struct tcp_bbr_info' which has 5 u32 fields
+    let mut info = [0u32; 5];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_CC_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    Ok((info, info_len as usize))
+}
+
+/// Check the algorithm-specific stats of a running connection.
+fn check_cc_info(fd: libc::c_int, algorithm: &str) -> Result<(), String> {
+    test_utils::result_assert_eq(
+        get_congestion(fd)?.as_str(),
+        algorithm,
+        "Unexpected congestion control algorithm",
+    )?;
+
+    let (info, info_len) = get_cc_info(fd)?;
+
+    if algorithm != "bbr" {
+        // like linux, only bbr has stats
+        return test_utils::result_assert_eq(info_len, 0, "Unexpected option length");
+    }
+
+    test_utils::result_assert_eq(
+        info_len,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+
+    // 'struct tcp_bbr_info' fields
+    let bw_bytes_per_sec = u64::from(info[0]) | (u64::from(info[1]) << 32);
+    let min_rtt = Duration::from_micros(info[2].into());
+
+    let bw_bits_per_sec = (bw_bytes_per_sec * 8) as f64;
+    println!("BBR estimated a bandwidth of {bw_bits_per_sec:.0} bits/s and min rtt of {min_rtt:?}");
+
+    // the estimate only counts payload bytes, so it should be slightly below the link bandwidth
+    test_utils::result_assert(
+        bw_bits_per_sec > LINK_BITS_PER_SEC * 0.8 && bw_bits_per_sec <= LINK_BITS_PER_SEC * 1.1,
+        "BBR's bandwidth estimate doesn't match the link",
+    )?;
+    test_utils::result_assert(
+        min_rtt >= BASE_RTT && min_rtt < BASE_RTT * 2,
+        "BBR's min rtt estimate doesn't match the link",
+    )
+}
+
+/// Accept a connection and read everything that the client sends.
+fn run_sink(port: u16) -> Result<(), String> {
+    let fd_listen = new_tcp_socket()?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        let start = Instant::now();
+
+        let mut buf = vec![0u8; 65536];
+        let mut received = 0;
+        loop {
+            let rv = socket::recv(fd, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            if rv == 0 {
+                break;
+            }
+            received += rv;
+        }
+
+        let bits_per_sec = (received * 8) as f64 / start.elapsed().as_secs_f64();
+        println!("Received {received} bytes at {bits_per_sec:.0} bits/s");
+
+        // every algorithm should make good use of the link
+        test_utils::result_assert(
+            bits_per_sec > LINK_BITS_PER_SEC * 0.8,
+            "The upload didn't use the link's bandwidth",
+        )
+    })
+}
+
+/// Upload data to the server as fast as possible for a fixed amount of time, and check how much
+/// data was queued in the network.
+fn run_upload(server_addr: SocketAddrV4, algorithm: &str) -> Result<(), String> {
+    let fd = new_tcp_socket()?;
+
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_CONGESTION,
+            algorithm.as_ptr() as *const libc::c_void,
+            algorithm.len() as libc::socklen_t,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "setsockopt() failed")?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let start = Instant::now();
+        let buf = vec![0u8; 16384];
+        let mut inflight_samples = vec![];
+        while start.elapsed() < UPLOAD_DURATION {
+            socket::send(fd, &buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            if start.elapsed() > WARMUP_DURATION {
+                inflight_samples.push(f64::from(get_info_unacked(fd)?));
+            }
+        }
+
+        check_cc_info(fd, algorithm)?;
+
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())?;
+
+        let inflight = inflight_samples.iter().sum::<f64>() / inflight_samples.len() as f64;
+
+        // the bandwidth-delay product in packets; any data in flight beyond this is queued
+        let bdp = LINK_BITS_PER_SEC / 8.0 * BASE_RTT.as_secs_f64() / MSS;
+
+        println!("Average of {inflight:.1} packets in flight, with a BDP of {bdp:.1} packets");
+
+        // bbr's congestion window is twice the bdp, plus some room while probing for bandwidth
+        let small_queue = inflight < 3.0 * bdp;
+
+        match algorithm {
+            "bbr" => test_utils::result_assert(small_queue, "BBR filled the router queue"),
+            "cubic" => {
+                test_utils::result_assert(!small_queue, "Cubic didn't fill the router queue")
+            }
+            _ => Err(format!("Unknown algorithm '{algorithm}'")),
+        }
+    })
+}
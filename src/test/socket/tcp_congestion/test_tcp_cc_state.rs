@@ -0,0 +1,190 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client uploads data to a server over a link with a known latency, and reads its socket's
+//! congestion control state with shadow's custom `shadow_tcp_cc_state` syscall while the
+//! connection is in slow start. It checks that the congestion window roughly doubles each round
+//! trip.
+//!
+//! Usage:
+//! - `test_tcp_cc_state server <port>`: receive everything that the client sends
+//! - `test_tcp_cc_state client <server-ip> <port>`: send data and check the congestion state
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::sys::socket::{self, AddressFamily, MsgFlags, Shutdown, SockFlag, SockType, SockaddrIn};
+
+/// The round-trip time, which should be twice the latency in the shadow config.
+const RTT: Duration = Duration::from_millis(20);
+
+/// The number of round trips that the client samples the congestion state for.
+const NUM_ROUND_TRIPS: u32 = 4;
+
+/// The interval between samples of the congestion state.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Shadow's custom syscall number for `SYS_shadow_tcp_cc_state`.
+const SYS_SHADOW_TCP_CC_STATE: libc::c_long = 1006;
+
+/// The value of `SHADOW_TCP_CC_PHASE_SLOW_START`.
+const PHASE_SLOW_START: u32 = 0;
+
+/// The layout of shadow's `ShadowTcpCcState`.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct CcState {
+    time_ns: u64,
+    cwnd: u32,
+    ssthresh: u32,
+    bytes_in_flight: u64,
+    phase: u32,
+    _padding: u32,
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} server <port> | {0} client <server-ip> <port>",
+        args[0]
+    );
+
+    let parse_port = |s: &str| s.parse::<u16>().map_err(|e| format!("Bad port: {e}"));
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("server"), 3) => run_server(parse_port(&args[2])?)?,
+        (Some("client"), 4) => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            run_client(SocketAddrV4::new(ip, parse_port(&args[3])?))?
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+fn new_socket(sock_type: SockType) -> Result<libc::c_int, String> {
+    socket::socket(AddressFamily::Inet, sock_type, SockFlag::empty(), None)
+        .map_err(|e| e.to_string())
+}
+
+fn get_cc_state(fd: libc::c_int) -> Result<CcState, Errno> {
+    let mut state = CcState::default();
+    let rv = unsafe {
+        libc::syscall(
+            SYS_SHADOW_TCP_CC_STATE,
+            fd,
+            &mut state as *mut CcState,
+            std::mem::size_of::<CcState>(),
+        )
+    };
+    Errno::result(rv).map(|_| state)
+}
+
+/// Accept a connection and read everything that the client sends.
+fn run_server(port: u16) -> Result<(), String> {
+    let fd_listen = new_socket(SockType::Stream)?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        let mut buf = vec![0u8; 65536];
+        while socket::recv(fd, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())? > 0 {}
+        Ok(())
+    })
+}
+
+/// Check the errors for sockets that don't have a congestion state.
+fn check_errors() -> Result<(), String> {
+    let fd_tcp = new_socket(SockType::Stream)?;
+    let fd_udp = new_socket(SockType::Datagram)?;
+
+    test_utils::run_and_close_fds(&[fd_tcp, fd_udp], || {
+        test_utils::result_assert_eq(get_cc_state(fd_tcp).unwrap_err(), Errno::ENOTCONN, "tcp")?;
+        test_utils::result_assert_eq(get_cc_state(fd_udp).unwrap_err(), Errno::EOPNOTSUPP, "udp")?;
+        test_utils::result_assert_eq(get_cc_state(-1).unwrap_err(), Errno::EBADF, "bad fd")
+    })
+}
+
+/// Keep the send buffer full and sample the congestion state until the connection leaves slow
+/// start or the round trips have passed.
+fn sample_slow_start(fd: libc::c_int) -> Result<Vec<CcState>, String> {
+    let buf = vec![0u8; 65536];
+    let mut samples = vec![get_cc_state(fd).map_err(|e| e.to_string())?];
+
+    let end_ns = samples[0].time_ns + (RTT * NUM_ROUND_TRIPS).as_nanos() as u64;
+
+    while samples.last().unwrap().time_ns < end_ns {
+        match socket::send(fd, &buf, MsgFlags::MSG_DONTWAIT) {
+            Ok(_) | Err(Errno::EAGAIN) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let state = get_cc_state(fd).map_err(|e| e.to_string())?;
+        if state.phase != PHASE_SLOW_START {
+            break;
+        }
+        samples.push(state);
+
+        std::thread::sleep(SAMPLE_INTERVAL);
+    }
+
+    Ok(samples)
+}
+
+/// Connect to the server, send data, and check the congestion state during slow start.
+fn run_client(server_addr: SocketAddrV4) -> Result<(), String> {
+    check_errors()?;
+
+    let fd = new_socket(SockType::Stream)?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let samples = sample_slow_start(fd)?;
+        let first = samples[0];
+        let last = *samples.last().unwrap();
+        println!("First state: {first:?}, last state: {last:?}");
+
+        test_utils::result_assert_eq(first.phase, PHASE_SLOW_START, "Not in slow start")?;
+        test_utils::result_assert_eq(first.bytes_in_flight, 0, "Unexpected bytes in flight")?;
+        test_utils::result_assert(
+            first.ssthresh > first.cwnd,
+            "The slow start threshold is below the window",
+        )?;
+
+        for pair in samples.windows(2) {
+            let (prev, next) = (pair[0], pair[1]);
+            test_utils::result_assert(next.time_ns > prev.time_ns, "Time didn't advance")?;
+            test_utils::result_assert(next.cwnd >= prev.cwnd, "The window shrank in slow start")?;
+        }
+
+        // each round trip acks a full window, so the window should roughly double, but we allow
+        // for the acks of a round trip that are still in flight
+        for round in 1..NUM_ROUND_TRIPS {
+            let time_ns = first.time_ns + (RTT * round + SAMPLE_INTERVAL * 2).as_nanos() as u64;
+            let Some(state) = samples.iter().find(|x| x.time_ns >= time_ns) else {
+                return Err(format!(
+                    "The connection left slow start before round {round}"
+                ));
+            };
+            println!("Round {round}: {state:?}");
+
+            test_utils::result_assert(
+                state.cwnd >= first.cwnd << (round - 1),
+                "The window didn't grow as expected",
+            )?;
+            test_utils::result_assert(state.bytes_in_flight > 0, "No bytes in flight")?;
+        }
+
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())
+    })
+}
@@ -0,0 +1,236 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client uploads data to a server over a congested link using a given congestion control
+//! algorithm, which is either the host's default or chosen with the `TCP_CONGESTION` socket
+//! option. The congested router queue marks packets with ECN rather than dropping them, so the
+//! congestion events are the same for every algorithm. The client follows its congestion window
+//! and checks that each reduction matches the algorithm: reno halves the window, and cubic reduces
+//! it to 70%.
+//!
+//! Usage: `test_tcp_congestion server <port>` or
+//! `test_tcp_congestion client <server-ip> <port> <algorithm> [--set]`
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use nix::sys::socket::{self, AddressFamily, MsgFlags, Shutdown, SockFlag, SockType, SockaddrIn};
+
+const UPLOAD_LEN: usize = 5_000_000;
+const SEND_LEN: usize = 1000;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} server <port> | {0} client <server-ip> <port> <algorithm> [--set]",
+        args[0]
+    );
+
+    match args.get(1).map(String::as_str) {
+        Some("server") if args.len() == 3 => {
+            let port = args[2].parse().map_err(|e| format!("Bad port: {e}"))?;
+            run_server(port)?;
+        }
+        Some("client") if args.len() == 5 || (args.len() == 6 && args[5] == "--set") => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            let port = args[3].parse().map_err(|e| format!("Bad port: {e}"))?;
+            let set = args.len() == 6;
+            run_client(SocketAddrV4::new(ip, port), &args[4], set)?;
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+/// The fields of `struct tcp_info` that we use.
+struct TcpInfo {
+    snd_ssthresh: u32,
+    snd_cwnd: u32,
+    total_retrans: u32,
+}
+
+fn get_tcp_info(fd: libc::c_int) -> Result<TcpInfo, String> {
+    // the libc package doesn't expose 'struct tcp_info', so we read it as an array; the u32 fields
+    // follow the 8 bytes of u8 fields
+    let mut info = [0u32; 26];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(
+        info_len as usize,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+
+    Ok(TcpInfo {
+        snd_ssthresh: info[19],
+        snd_cwnd: info[20],
+        total_retrans: info[25],
+    })
+}
+
+fn get_congestion(fd: libc::c_int) -> Result<String, String> {
+    let mut name = [0u8; 16];
+    let mut name_len = name.len() as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_CONGESTION,
+            name.as_mut_ptr() as *mut libc::c_void,
+            &mut name_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+
+    let name = &name[..name_len as usize];
+    let name = &name[..name.iter().position(|&c| c == b'\0').unwrap_or(name.len())];
+    Ok(String::from_utf8_lossy(name).into_owned())
+}
+
+fn set_congestion(fd: libc::c_int, name: &str) -> Result<(), String> {
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_CONGESTION,
+            name.as_ptr() as *const libc::c_void,
+            name.len() as libc::socklen_t,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "setsockopt() failed")
+}
+
+/// Accept a connection, read everything that the client sends, and then send a single byte back.
+fn run_server(port: u16) -> Result<(), String> {
+    let fd_listen = socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+
+    // the accepted socket should use the same algorithm as the listening socket
+    set_congestion(fd_listen, "cubic")?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        test_utils::result_assert_eq(
+            get_congestion(fd)?.as_str(),
+            "cubic",
+            "Unexpected algorithm for the accepted socket",
+        )?;
+
+        let mut buf = vec![0u8; 65536];
+        let mut received = 0;
+        loop {
+            let rv = socket::recv(fd, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            if rv == 0 {
+                break;
+            }
+            received += rv;
+        }
+        test_utils::result_assert_eq(received, UPLOAD_LEN, "Unexpected number of bytes")?;
+
+        let rv = socket::send(fd, &[1], MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 1, "send() failed")
+    })
+}
+
+/// Upload data to the server over the congested link, and check how the congestion window was
+/// reduced in response to the congestion.
+fn run_client(server_addr: SocketAddrV4, algorithm: &str, set: bool) -> Result<(), String> {
+    // the fraction of the congestion window that remains after a reduction
+    let expected_reduction = match algorithm {
+        "reno" => 0.4..0.6,
+        "cubic" => 0.6..0.8,
+        _ => return Err(format!("Unknown algorithm '{algorithm}'")),
+    };
+
+    let fd = socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        if set {
+            set_congestion(fd, algorithm)?;
+        }
+        test_utils::result_assert_eq(
+            get_congestion(fd)?.as_str(),
+            algorithm,
+            "Unexpected algorithm",
+        )?;
+
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let mut info = get_tcp_info(fd)?;
+        let mut max_cwnd = info.snd_cwnd;
+        let mut reductions = vec![];
+
+        // send in small pieces so that we see most changes to the congestion window
+        let buf = vec![0u8; UPLOAD_LEN];
+        let mut sent = 0;
+        while sent < buf.len() {
+            let end = std::cmp::min(sent + SEND_LEN, buf.len());
+            sent +=
+                socket::send(fd, &buf[sent..end], MsgFlags::empty()).map_err(|e| e.to_string())?;
+
+            let new_info = get_tcp_info(fd)?;
+            if new_info.snd_ssthresh != info.snd_ssthresh {
+                reductions.push(f64::from(new_info.snd_ssthresh) / f64::from(max_cwnd));
+                max_cwnd = new_info.snd_cwnd;
+            } else {
+                max_cwnd = std::cmp::max(max_cwnd, new_info.snd_cwnd);
+            }
+            info = new_info;
+        }
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())?;
+
+        // the server responds once it has received everything, so all of our data has been acked
+        let mut byte = [0u8];
+        let rv = socket::recv(fd, &mut byte, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, 1, "recv() failed")?;
+
+        // the congestion was signalled with ECN marks rather than packet loss, so timeouts or
+        // fast retransmits didn't affect the congestion window
+        let info = get_tcp_info(fd)?;
+        test_utils::result_assert_eq(info.total_retrans, 0, "Packets were retransmitted")?;
+
+        println!("Congestion window reductions: {reductions:?}");
+
+        test_utils::result_assert(
+            reductions.len() >= 2,
+            "The client didn't react to the congestion",
+        )?;
+
+        for reduction in reductions {
+            test_utils::result_assert(
+                expected_reduction.contains(&reduction),
+                &format!("Unexpected reduction {reduction} for {algorithm}"),
+            )?;
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,134 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client connects to a server and sends more data than fits in the initial congestion window,
+//! and checks that the first flight of data is limited to the configured initial window
+//! (`experimental.tcp_initial_cwnd`) until the server's first ACK arrives.
+//!
+//! Usage:
+//! - `test_tcp_initial_cwnd server <port>`: receive everything that the client sends
+//! - `test_tcp_initial_cwnd client <server-ip> <port> <initial-cwnd>`: send data and check the
+//!   first flight
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use nix::sys::socket::{self, AddressFamily, MsgFlags, Shutdown, SockFlag, SockType, SockaddrIn};
+
+/// The number of segments that the client sends, which is more than any initial window we test.
+const NUM_SEGMENTS: usize = 30;
+
+/// The size of the segment payloads.
+const MSS: usize = 1448;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} server <port> | {0} client <server-ip> <port> <initial-cwnd>",
+        args[0]
+    );
+
+    let parse_port = |s: &str| s.parse::<u16>().map_err(|e| format!("Bad port: {e}"));
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("server"), 3) => run_server(parse_port(&args[2])?)?,
+        (Some("client"), 5) => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            let cwnd = args[4].parse().map_err(|e| format!("Bad cwnd: {e}"))?;
+            run_client(SocketAddrV4::new(ip, parse_port(&args[3])?), cwnd)?
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+fn new_tcp_socket() -> Result<libc::c_int, String> {
+    socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Get the `tcpi_unacked` and `tcpi_snd_cwnd` fields of the socket's `struct tcp_info`.
+fn get_info_unacked_and_cwnd(fd: libc::c_int) -> Result<(u32, u32), String> {
+    // the libc package doesn't expose 'struct tcp_info', so we read it as an array; the u32 fields
+    // follow the 8 bytes of u8 fields
+    let mut info = [0u32; 26];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(
+        info_len as usize,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+    Ok((info[6], info[20]))
+}
+
+/// Accept a connection and read everything that the client sends.
+fn run_server(port: u16) -> Result<(), String> {
+    let fd_listen = new_tcp_socket()?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        let mut buf = vec![0u8; 65536];
+        let mut received = 0;
+        loop {
+            let rv = socket::recv(fd, &mut buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            if rv == 0 {
+                break;
+            }
+            received += rv;
+        }
+
+        test_utils::result_assert_eq(received, NUM_SEGMENTS * MSS, "Unexpected number of bytes")
+    })
+}
+
+/// Connect to the server and check how much of the data is sent before the first ACK.
+fn run_client(server_addr: SocketAddrV4, initial_cwnd: u32) -> Result<(), String> {
+    let fd = new_tcp_socket()?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        let (_, cwnd) = get_info_unacked_and_cwnd(fd)?;
+        test_utils::result_assert_eq(cwnd, initial_cwnd, "Unexpected cwnd of a new socket")?;
+
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        // the handshake doesn't grow the window
+        let (_, cwnd) = get_info_unacked_and_cwnd(fd)?;
+        test_utils::result_assert_eq(cwnd, initial_cwnd, "Unexpected cwnd after connecting")?;
+
+        let buf = vec![0u8; NUM_SEGMENTS * MSS];
+        let rv = socket::send(fd, &buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, buf.len(), "Not all bytes were sent")?;
+
+        // no time has passed, so the server hasn't acked anything yet and only the first flight
+        // has been sent
+        let (unacked, cwnd) = get_info_unacked_and_cwnd(fd)?;
+        println!("First flight of {unacked} segments with a cwnd of {cwnd}");
+        test_utils::result_assert_eq(unacked, initial_cwnd, "Unexpected first flight size")?;
+        test_utils::result_assert_eq(cwnd, initial_cwnd, "Unexpected cwnd after sending")?;
+
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())
+    })
+}
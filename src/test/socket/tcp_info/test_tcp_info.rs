@@ -0,0 +1,156 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client exchanges small messages with a server over a link with a known latency, and checks
+//! that the connection's `struct tcp_info` reports the round-trip time and congestion state.
+//!
+//! Usage:
+//! - `test_tcp_info server <port>`: echo everything that the client sends
+//! - `test_tcp_info client <server-ip> <port>`: send messages and check the connection's info
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::Duration;
+
+use nix::sys::socket::{self, AddressFamily, MsgFlags, SockFlag, SockType, SockaddrIn};
+
+/// The round-trip time, which should be twice the latency in the shadow config.
+const RTT: Duration = Duration::from_millis(50);
+
+/// The number of messages that the client sends, so that the rtt estimate has converged.
+const NUM_MESSAGES: usize = 20;
+
+const MESSAGE_LEN: usize = 100;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} server <port> | {0} client <server-ip> <port>",
+        args[0]
+    );
+
+    let parse_port = |s: &str| s.parse::<u16>().map_err(|e| format!("Bad port: {e}"));
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("server"), 3) => run_server(parse_port(&args[2])?)?,
+        (Some("client"), 4) => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            run_client(SocketAddrV4::new(ip, parse_port(&args[3])?))?
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+fn new_tcp_socket() -> Result<libc::c_int, String> {
+    socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Get the socket's `struct tcp_info`.
+fn get_info(fd: libc::c_int) -> Result<[u32; 26], String> {
+    // the libc package doesn't expose 'struct tcp_info', so we read it as an array of u32 where the
+    // first two elements hold the 8 bytes of u8 fields
+    let mut info = [0u32; 26];
+    let mut info_len = std::mem::size_of_val(&info) as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_TCP,
+            libc::TCP_INFO,
+            info.as_mut_ptr() as *mut libc::c_void,
+            &mut info_len,
+        )
+    };
+    test_utils::result_assert_eq(rv, 0, "getsockopt() failed")?;
+    test_utils::result_assert_eq(
+        info_len as usize,
+        std::mem::size_of_val(&info),
+        "Unexpected option length",
+    )?;
+    Ok(info)
+}
+
+/// Read exactly `buf.len()` bytes, or return an error if the peer closed the connection first.
+fn recv_exact(fd: libc::c_int, buf: &mut [u8]) -> Result<(), String> {
+    let mut received = 0;
+    while received < buf.len() {
+        let rv =
+            socket::recv(fd, &mut buf[received..], MsgFlags::empty()).map_err(|e| e.to_string())?;
+        if rv == 0 {
+            return Err("The connection was closed early".to_string());
+        }
+        received += rv;
+    }
+    Ok(())
+}
+
+/// Accept a connection and echo each message that the client sends.
+fn run_server(port: u16) -> Result<(), String> {
+    let fd_listen = new_tcp_socket()?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd], || {
+        let mut buf = [0u8; MESSAGE_LEN];
+        for _ in 0..NUM_MESSAGES {
+            recv_exact(fd, &mut buf)?;
+            let rv = socket::send(fd, &buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(rv, buf.len(), "Not all bytes were sent")?;
+        }
+        Ok(())
+    })
+}
+
+/// Send messages to the server and wait for each response, and then check the connection's info.
+fn run_client(server_addr: SocketAddrV4) -> Result<(), String> {
+    let fd = new_tcp_socket()?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let mut buf = [0u8; MESSAGE_LEN];
+        for _ in 0..NUM_MESSAGES {
+            let rv = socket::send(fd, &buf, MsgFlags::empty()).map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(rv, buf.len(), "Not all bytes were sent")?;
+            recv_exact(fd, &mut buf)?;
+        }
+
+        let info = get_info(fd)?;
+
+        // the value of TCP_ESTABLISHED in linux's 'enum tcp_states'
+        const TCP_ESTABLISHED: u32 = 1;
+
+        // 'struct tcp_info' fields
+        let state = info[0] & 0xff;
+        let rto = Duration::from_micros(info[2].into());
+        let unacked = info[6];
+        let rtt = Duration::from_micros(info[17].into());
+        let snd_cwnd = info[20];
+        let total_retrans = info[25];
+
+        println!("rtt={rtt:?}, rto={rto:?}, cwnd={snd_cwnd}, retransmits={total_retrans}");
+
+        test_utils::result_assert_eq(state, TCP_ESTABLISHED, "Unexpected state")?;
+        test_utils::result_assert(
+            rtt >= RTT.mul_f64(0.9) && rtt <= RTT.mul_f64(1.1),
+            "The rtt doesn't match the link latency",
+        )?;
+        test_utils::result_assert(rto > rtt, "The rto is smaller than the rtt")?;
+        test_utils::result_assert(snd_cwnd > 0, "The congestion window is empty")?;
+        test_utils::result_assert_eq(unacked, 0, "Unexpected data in flight")?;
+        test_utils::result_assert_eq(total_retrans, 0, "Unexpected retransmissions")
+    })
+}
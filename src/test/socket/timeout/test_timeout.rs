@@ -0,0 +1,267 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests that blocking sends and recvs time out after the `SO_SNDTIMEO` or `SO_RCVTIMEO` timeout.
+
+use std::time::{Duration, Instant};
+
+use nix::errno::Errno;
+use nix::sys::socket::MsgFlags;
+use test_utils::set;
+use test_utils::socket_utils::{socket_init_helper, SocketInitMethod};
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+        test_utils::ShadowTest::new(
+            "test_recv_waitall_timeout",
+            test_recv_waitall_timeout,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_send_timeout",
+            test_send_timeout,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_write_timeout",
+            test_write_timeout,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ];
+
+    for &sock_type in &[libc::SOCK_STREAM, libc::SOCK_DGRAM] {
+        // add details to the test names to avoid duplicates
+        let append_args = |s| format!("{} <sock_type={}>", s, sock_type);
+
+        let more_tests: Vec<test_utils::ShadowTest<_, _>> = vec![
+            test_utils::ShadowTest::new(
+                &append_args("test_recv_timeout"),
+                move || test_recv_timeout(sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_read_timeout"),
+                move || test_read_timeout(sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+            test_utils::ShadowTest::new(
+                &append_args("test_recv_before_timeout"),
+                move || test_recv_before_timeout(sock_type),
+                set![TestEnv::Libc, TestEnv::Shadow],
+            ),
+        ];
+
+        tests.extend(more_tests);
+    }
+
+    tests
+}
+
+fn set_timeout(fd: libc::c_int, optname: libc::c_int, timeout: Duration) {
+    let timeout = libc::timeval {
+        tv_sec: timeout.as_secs().try_into().unwrap(),
+        tv_usec: timeout.subsec_micros().into(),
+    };
+    let rv = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            optname,
+            std::ptr::from_ref(&timeout) as *const libc::c_void,
+            std::mem::size_of_val(&timeout) as libc::socklen_t,
+        )
+    };
+    assert_eq!(rv, 0);
+}
+
+/// Check that the time elapsed since `start` is close to `expected`. Linux rounds timeouts to the
+/// kernel's clock tick, so it may be slightly late.
+fn check_elapsed(start: Instant, expected: Duration) -> Result<(), String> {
+    let elapsed = start.elapsed();
+    test_utils::result_assert(
+        elapsed >= expected && elapsed < expected + Duration::from_millis(100),
+        &format!("Expected the call to return after {expected:?}, but took {elapsed:?}"),
+    )
+}
+
+/// A recv on an idle socket returns `EAGAIN` once the timeout has passed.
+fn test_recv_timeout(sock_type: libc::c_int) -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        sock_type,
+        0,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        set_timeout(fd_peer, libc::SO_RCVTIMEO, Duration::from_secs(5));
+
+        let start = Instant::now();
+        let mut buf = [0u8; 10];
+        let rv = nix::sys::socket::recv(fd_peer, &mut buf, MsgFlags::empty());
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected recv() result")?;
+
+        check_elapsed(start, Duration::from_secs(5))
+    })
+}
+
+/// A read on an idle socket also returns `EAGAIN` once the timeout has passed.
+fn test_read_timeout(sock_type: libc::c_int) -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        sock_type,
+        0,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        set_timeout(fd_peer, libc::SO_RCVTIMEO, Duration::from_secs(1));
+
+        let start = Instant::now();
+        let mut buf = [0u8; 10];
+        let rv = nix::unistd::read(fd_peer, &mut buf);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected read() result")?;
+
+        check_elapsed(start, Duration::from_secs(1))
+    })
+}
+
+/// A recv returns data that arrives before the timeout.
+fn test_recv_before_timeout(sock_type: libc::c_int) -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        sock_type,
+        0,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        set_timeout(fd_peer, libc::SO_RCVTIMEO, Duration::from_secs(5));
+
+        let rv = nix::sys::socket::send(fd_client, &[1, 2, 3], MsgFlags::empty());
+        test_utils::result_assert_eq(rv, Ok(3), "Unexpected send() result")?;
+
+        let mut buf = [0u8; 10];
+        let rv = nix::sys::socket::recv(fd_peer, &mut buf, MsgFlags::empty());
+        test_utils::result_assert_eq(rv, Ok(3), "Unexpected recv() result")?;
+
+        // the timeout applies to each call separately
+        let start = Instant::now();
+        let rv = nix::sys::socket::recv(fd_peer, &mut buf, MsgFlags::empty());
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected recv() result")?;
+
+        check_elapsed(start, Duration::from_secs(5))
+    })
+}
+
+/// A `MSG_WAITALL` recv returns the data it received so far once the timeout has passed.
+fn test_recv_waitall_timeout() -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        set_timeout(fd_peer, libc::SO_RCVTIMEO, Duration::from_secs(1));
+
+        let rv = nix::sys::socket::send(fd_client, &[1, 2, 3], MsgFlags::empty());
+        test_utils::result_assert_eq(rv, Ok(3), "Unexpected send() result")?;
+
+        let start = Instant::now();
+        let mut buf = [0u8; 10];
+        let rv = nix::sys::socket::recv(fd_peer, &mut buf, MsgFlags::MSG_WAITALL);
+        test_utils::result_assert_eq(rv, Ok(3), "Unexpected recv() result")?;
+        test_utils::result_assert_eq(&buf[..3], &[1, 2, 3][..], "Unexpected data")?;
+
+        check_elapsed(start, Duration::from_secs(1))
+    })
+}
+
+/// Fill the send buffer of `fd` and the receive buffer of its peer.
+fn fill_send_buffer(fd: libc::c_int) -> Result<(), String> {
+    let buf = vec![0u8; 10_000];
+
+    // data moves from the send buffer to the peer while we sleep, so keep going until no more data
+    // can be sent
+    loop {
+        while nix::sys::socket::send(fd, &buf, MsgFlags::MSG_DONTWAIT).is_ok() {}
+
+        std::thread::sleep(Duration::from_millis(100));
+
+        let rv = nix::sys::socket::send(fd, &buf, MsgFlags::MSG_DONTWAIT);
+        if rv == Err(Errno::EAGAIN) {
+            return Ok(());
+        }
+        test_utils::result_assert(rv.is_ok(), "Unexpected send() result")?;
+    }
+}
+
+/// A send on a socket with a full send buffer returns `EAGAIN` once the timeout has passed.
+fn test_send_timeout() -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        fill_send_buffer(fd_client)?;
+        set_timeout(fd_client, libc::SO_SNDTIMEO, Duration::from_secs(1));
+
+        let start = Instant::now();
+        let buf = vec![0u8; 10_000];
+        let rv = nix::sys::socket::send(fd_client, &buf, MsgFlags::empty());
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected send() result")?;
+
+        check_elapsed(start, Duration::from_secs(1))
+    })
+}
+
+/// A write on a socket with a full send buffer also returns `EAGAIN` once the timeout has passed.
+fn test_write_timeout() -> Result<(), String> {
+    let (fd_client, fd_peer) = socket_init_helper(
+        SocketInitMethod::Inet,
+        libc::SOCK_STREAM,
+        0,
+        /* bind_client = */ false,
+    );
+
+    test_utils::run_and_close_fds(&[fd_client, fd_peer], || {
+        fill_send_buffer(fd_client)?;
+        set_timeout(fd_client, libc::SO_SNDTIMEO, Duration::from_secs(1));
+
+        let start = Instant::now();
+        let buf = vec![0u8; 10_000];
+        let rv = nix::unistd::write(fd_client, &buf);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected write() result")?;
+
+        check_elapsed(start, Duration::from_secs(1))
+    })
+}
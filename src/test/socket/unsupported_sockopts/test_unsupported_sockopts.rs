@@ -0,0 +1,236 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests setting socket options that shadow doesn't support. By default they should return
+//! `ENOPROTOOPT`, but with `experimental.ignore_unsupported_sockopts` they should succeed and their
+//! values should be returned by `getsockopt()`.
+//!
+//! Usage: `test_unsupported_sockopts (strict|ignored)`, where the mode must match whether the
+//! option is enabled in the shadow config.
+
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let ignored = match args.get(1).map(String::as_str) {
+        Some("strict") => false,
+        Some("ignored") => true,
+        _ => return Err(format!("Usage: {} (strict|ignored)", args[0])),
+    };
+
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let tests = get_tests(ignored);
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests(ignored: bool) -> Vec<test_utils::ShadowTest<(), String>> {
+    let mut tests: Vec<test_utils::ShadowTest<_, _>> = vec![];
+
+    for &domain in &[libc::AF_INET, libc::AF_UNIX] {
+        for &sock_type in &[libc::SOCK_STREAM, libc::SOCK_DGRAM] {
+            // add details to the test names to avoid duplicates
+            let append_args = |s| format!("{} <domain={},sock_type={}>", s, domain, sock_type);
+
+            let mut more_tests: Vec<test_utils::ShadowTest<_, _>> = if ignored {
+                vec![
+                    test_utils::ShadowTest::new(
+                        &append_args("test_ignored_round_trip"),
+                        move || test_ignored_round_trip(domain, sock_type),
+                        set![TestEnv::Shadow],
+                    ),
+                    test_utils::ShadowTest::new(
+                        &append_args("test_ignored_invalid_buffers"),
+                        move || test_ignored_invalid_buffers(domain, sock_type),
+                        set![TestEnv::Shadow],
+                    ),
+                ]
+            } else {
+                vec![test_utils::ShadowTest::new(
+                    &append_args("test_strict"),
+                    move || test_strict(domain, sock_type),
+                    set![TestEnv::Shadow],
+                )]
+            };
+
+            // shadow doesn't support any options for unix sockets
+            if ignored && domain == libc::AF_INET {
+                more_tests.push(test_utils::ShadowTest::new(
+                    &append_args("test_ignored_supported_option"),
+                    move || test_ignored_supported_option(sock_type),
+                    set![TestEnv::Shadow],
+                ));
+            }
+
+            tests.extend(more_tests);
+        }
+    }
+
+    tests
+}
+
+/// Socket options that shadow doesn't support for the socket domain and type.
+fn unsupported_options(
+    domain: libc::c_int,
+    sock_type: libc::c_int,
+) -> Vec<(libc::c_int, libc::c_int)> {
+    let mut options = vec![(libc::SOL_SOCKET, libc::SO_PRIORITY)];
+
+    if domain == libc::AF_INET {
+        options.push((libc::IPPROTO_IP, libc::IP_TTL));
+
+        if sock_type == libc::SOCK_STREAM {
+            options.push((libc::IPPROTO_TCP, libc::TCP_KEEPIDLE));
+        }
+    }
+
+    options
+}
+
+fn setsockopt(
+    fd: libc::c_int,
+    level: libc::c_int,
+    optname: libc::c_int,
+    optval: Option<&[u8]>,
+    optlen: libc::socklen_t,
+) -> Result<(), nix::errno::Errno> {
+    let optval_ptr = optval.map_or(std::ptr::null(), |x| x.as_ptr());
+    let rv = unsafe { libc::setsockopt(fd, level, optname, optval_ptr as *const _, optlen) };
+    nix::errno::Errno::result(rv).map(|_| ())
+}
+
+fn getsockopt(
+    fd: libc::c_int,
+    level: libc::c_int,
+    optname: libc::c_int,
+    optval: &mut [u8],
+) -> Result<libc::socklen_t, nix::errno::Errno> {
+    let mut optlen = optval.len() as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(
+            fd,
+            level,
+            optname,
+            optval.as_mut_ptr() as *mut _,
+            &mut optlen,
+        )
+    };
+    nix::errno::Errno::result(rv).map(|_| optlen)
+}
+
+/// Without the option enabled, unsupported options return `ENOPROTOOPT`.
+fn test_strict(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(domain, sock_type, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        for (level, optname) in unsupported_options(domain, sock_type) {
+            let optval = 5i32.to_ne_bytes();
+            test_utils::result_assert_eq(
+                setsockopt(fd, level, optname, Some(&optval), optval.len() as _),
+                Err(nix::errno::Errno::ENOPROTOOPT),
+                &format!("Unexpected result for level {level} and opt {optname}"),
+            )?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Unsupported options can be set, and `getsockopt()` returns the most recent value.
+fn test_ignored_round_trip(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(domain, sock_type, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        for (level, optname) in unsupported_options(domain, sock_type) {
+            for value in [5i32, 7] {
+                let optval = value.to_ne_bytes();
+                setsockopt(fd, level, optname, Some(&optval), optval.len() as _)
+                    .map_err(|e| e.to_string())?;
+
+                let mut buf = [0u8; 8];
+                let optlen = getsockopt(fd, level, optname, &mut buf).map_err(|e| e.to_string())?;
+                test_utils::result_assert_eq(
+                    &buf[..optlen as usize],
+                    &optval[..],
+                    "The stored value wasn't returned",
+                )?;
+            }
+
+            // a short buffer receives the start of the value
+            let mut buf = [0u8; 2];
+            let optlen = getsockopt(fd, level, optname, &mut buf).map_err(|e| e.to_string())?;
+            test_utils::result_assert_eq(optlen, 2, "Unexpected optlen")?;
+            test_utils::result_assert_eq(
+                &buf[..],
+                &7i32.to_ne_bytes()[..2],
+                "Unexpected truncated value",
+            )?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Unsupported options with invalid buffers still return errors.
+fn test_ignored_invalid_buffers(domain: libc::c_int, sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(domain, sock_type, 0) };
+    assert!(fd >= 0);
+
+    let (level, optname) = (libc::SOL_SOCKET, libc::SO_PRIORITY);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        test_utils::result_assert_eq(
+            setsockopt(fd, level, optname, None, 4),
+            Err(nix::errno::Errno::EFAULT),
+            "A null optval should be a bad address",
+        )?;
+
+        // a value too large to store is rejected like before
+        let optval = vec![0u8; 4096];
+        test_utils::result_assert_eq(
+            setsockopt(fd, level, optname, Some(&optval), optval.len() as _),
+            Err(nix::errno::Errno::ENOPROTOOPT),
+            "A large optval shouldn't be stored",
+        )?;
+
+        Ok(())
+    })
+}
+
+/// Supported options aren't affected.
+fn test_ignored_supported_option(sock_type: libc::c_int) -> Result<(), String> {
+    let fd = unsafe { libc::socket(libc::AF_INET, sock_type, 0) };
+    assert!(fd >= 0);
+
+    test_utils::run_and_close_fds(&[fd], || {
+        // too short for an int
+        let optval = [0u8; 2];
+        test_utils::result_assert_eq(
+            setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_SNDBUF,
+                Some(&optval),
+                optval.len() as _,
+            ),
+            Err(nix::errno::Errno::EINVAL),
+            "A supported option should still validate its value",
+        )?;
+
+        let mut buf = [0u8; 4];
+        getsockopt(fd, libc::SOL_SOCKET, libc::SO_SNDBUF, &mut buf).map_err(|e| e.to_string())?;
+        test_utils::result_assert(
+            i32::from_ne_bytes(buf) > 0,
+            "The send buffer size should be positive",
+        )
+    })
+}
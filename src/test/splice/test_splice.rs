@@ -0,0 +1,534 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for `splice()` between pipes, sockets, and regular files, and for `tee()` between pipes.
+
+use nix::errno::Errno;
+use nix::sys::socket::{self, AddressFamily, SockFlag, SockType};
+use test_utils::set;
+use test_utils::TestEnvironment as TestEnv;
+
+const PATTERN_LEN: usize = 1000;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_file_to_pipe",
+            test_file_to_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_file_to_pipe_offset",
+            test_file_to_pipe_offset,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_pipe_to_file",
+            test_pipe_to_file,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_pipe_to_pipe",
+            test_pipe_to_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_no_pipe",
+            test_no_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_pipe_offset",
+            test_pipe_offset,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_nonblock_empty_pipe",
+            test_nonblock_empty_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_nonblock_full_pipe",
+            test_nonblock_full_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_socket_to_pipe",
+            test_socket_to_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_pipe_to_socket",
+            test_pipe_to_socket,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_socket_no_pipe",
+            test_socket_no_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_socket_offset",
+            test_socket_offset,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_nonblock_socket",
+            test_nonblock_socket,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new("test_tee", test_tee, set![TestEnv::Libc, TestEnv::Shadow]),
+        test_utils::ShadowTest::new(
+            "test_tee_not_pipe",
+            test_tee_not_pipe,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_tee_nonblock",
+            test_tee_nonblock,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ]
+}
+
+fn pattern() -> Vec<u8> {
+    (0..PATTERN_LEN).map(|x| (x % 251) as u8).collect()
+}
+
+fn splice(
+    fd_in: libc::c_int,
+    off_in: Option<&mut libc::loff_t>,
+    fd_out: libc::c_int,
+    off_out: Option<&mut libc::loff_t>,
+    len: usize,
+    flags: libc::c_uint,
+) -> Result<usize, Errno> {
+    let off_in = off_in.map_or(std::ptr::null_mut(), std::ptr::from_mut);
+    let off_out = off_out.map_or(std::ptr::null_mut(), std::ptr::from_mut);
+    let rv = unsafe { libc::splice(fd_in, off_in, fd_out, off_out, len, flags) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+fn tee(
+    fd_in: libc::c_int,
+    fd_out: libc::c_int,
+    len: usize,
+    flags: libc::c_uint,
+) -> Result<usize, Errno> {
+    let rv = unsafe { libc::tee(fd_in, fd_out, len, flags) };
+    Errno::result(rv).map(|x| x as usize)
+}
+
+/// Create a temporary file containing `contents`, with the file offset at the start of the file.
+fn temp_file(contents: &[u8]) -> Result<libc::c_int, String> {
+    let (fd, path) = nix::unistd::mkstemp(&b"testsplice_XXXXXX"[..]).map_err(|e| e.to_string())?;
+    nix::unistd::unlink(&path).map_err(|e| e.to_string())?;
+
+    let rv = nix::unistd::write(fd, contents).map_err(|e| e.to_string())?;
+    test_utils::result_assert_eq(rv, contents.len(), "Unexpected write() result")?;
+
+    nix::unistd::lseek(fd, 0, nix::unistd::Whence::SeekSet).map_err(|e| e.to_string())?;
+
+    Ok(fd)
+}
+
+/// Create a connected pair of unix stream sockets.
+fn socket_pair(flags: SockFlag) -> Result<(libc::c_int, libc::c_int), String> {
+    socket::socketpair(AddressFamily::Unix, SockType::Stream, None, flags)
+        .map_err(|e| e.to_string())
+}
+
+/// Read exactly `len` bytes from `fd`.
+fn read_exact(fd: libc::c_int, len: usize) -> Result<Vec<u8>, String> {
+    let mut buf = vec![0u8; len];
+    let mut num_read = 0;
+    while num_read < len {
+        let rv = nix::unistd::read(fd, &mut buf[num_read..]).map_err(|e| e.to_string())?;
+        test_utils::result_assert(rv > 0, "Unexpected end of file")?;
+        num_read += rv;
+    }
+    Ok(buf)
+}
+
+/// Bytes spliced from a file into a pipe can be read out of the pipe unchanged.
+fn test_file_to_pipe() -> Result<(), String> {
+    let fd_file = temp_file(&pattern())?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_file, fd_read, fd_write], || {
+        let mut num_spliced = 0;
+        while num_spliced < PATTERN_LEN {
+            let rv = splice(fd_file, None, fd_write, None, PATTERN_LEN - num_spliced, 0);
+            let rv = rv.map_err(|e| e.to_string())?;
+            test_utils::result_assert(rv > 0, "Unexpected end of file")?;
+            num_spliced += rv;
+        }
+
+        test_utils::result_assert_eq(read_exact(fd_read, PATTERN_LEN)?, pattern(), "Bad data")?;
+
+        // the file offset was updated, so there's no more data to splice
+        let rv = splice(fd_file, None, fd_write, None, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Ok(0), "Unexpected splice() result")?;
+
+        Ok(())
+    })
+}
+
+/// When an offset is given for the file, that offset is used and updated instead of the file
+/// offset.
+fn test_file_to_pipe_offset() -> Result<(), String> {
+    let fd_file = temp_file(&pattern())?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_file, fd_read, fd_write], || {
+        let mut offset: libc::loff_t = 100;
+        let rv = splice(fd_file, Some(&mut offset), fd_write, None, 50, 0);
+        test_utils::result_assert_eq(rv, Ok(50), "Unexpected splice() result")?;
+        test_utils::result_assert_eq(offset, 150, "Offset wasn't updated")?;
+
+        test_utils::result_assert_eq(
+            read_exact(fd_read, 50)?,
+            pattern()[100..150].to_vec(),
+            "Bad data",
+        )?;
+
+        // the file offset wasn't changed
+        let file_offset = nix::unistd::lseek(fd_file, 0, nix::unistd::Whence::SeekCur)
+            .map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(file_offset, 0, "File offset was changed")?;
+
+        Ok(())
+    })
+}
+
+/// Bytes spliced from a pipe into a file are written to the file and removed from the pipe.
+fn test_pipe_to_file() -> Result<(), String> {
+    let fd_file = temp_file(&[])?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_file, fd_read, fd_write], || {
+        let rv = nix::unistd::write(fd_write, &pattern()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, PATTERN_LEN, "Unexpected write() result")?;
+
+        let mut offset: libc::loff_t = 0;
+        let rv = splice(fd_read, None, fd_file, Some(&mut offset), PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Ok(PATTERN_LEN), "Unexpected splice() result")?;
+        test_utils::result_assert_eq(offset, PATTERN_LEN as libc::loff_t, "Bad offset")?;
+
+        test_utils::result_assert_eq(read_exact(fd_file, PATTERN_LEN)?, pattern(), "Bad data")?;
+
+        // the pipe is now empty
+        let rv = splice(fd_read, None, fd_file, None, 1, libc::SPLICE_F_NONBLOCK);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected splice() result")?;
+
+        Ok(())
+    })
+}
+
+/// Bytes can be spliced between two pipes.
+fn test_pipe_to_pipe() -> Result<(), String> {
+    let (fd_read_1, fd_write_1) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+    let (fd_read_2, fd_write_2) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_read_1, fd_write_1, fd_read_2, fd_write_2], || {
+        let rv = nix::unistd::write(fd_write_1, &pattern()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, PATTERN_LEN, "Unexpected write() result")?;
+
+        // splice more than is available
+        let rv = splice(fd_read_1, None, fd_write_2, None, 2 * PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Ok(PATTERN_LEN), "Unexpected splice() result")?;
+
+        test_utils::result_assert_eq(read_exact(fd_read_2, PATTERN_LEN)?, pattern(), "Bad data")?;
+
+        // a pipe can't be spliced to itself
+        let rv = splice(fd_read_1, None, fd_write_1, None, 1, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Unexpected splice() result")?;
+
+        Ok(())
+    })
+}
+
+/// At least one of the descriptors must be a pipe.
+fn test_no_pipe() -> Result<(), String> {
+    let fd_file_1 = temp_file(&pattern())?;
+    let fd_file_2 = temp_file(&[])?;
+
+    test_utils::run_and_close_fds(&[fd_file_1, fd_file_2], || {
+        let rv = splice(fd_file_1, None, fd_file_2, None, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Unexpected splice() result")?;
+        Ok(())
+    })
+}
+
+/// Offsets can't be given for pipes.
+fn test_pipe_offset() -> Result<(), String> {
+    let fd_file = temp_file(&pattern())?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_file, fd_read, fd_write], || {
+        let mut offset: libc::loff_t = 0;
+        let rv = splice(fd_file, None, fd_write, Some(&mut offset), PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::ESPIPE), "Unexpected splice() result")?;
+        Ok(())
+    })
+}
+
+/// A non-blocking splice from an empty pipe returns `EAGAIN`.
+fn test_nonblock_empty_pipe() -> Result<(), String> {
+    let fd_file = temp_file(&[])?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    // the write end is closed during the test
+    test_utils::run_and_close_fds(&[fd_file, fd_read], || {
+        let rv = splice(fd_read, None, fd_file, None, 10, libc::SPLICE_F_NONBLOCK);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected splice() result")?;
+
+        // with no writers, the splice returns 0 instead
+        nix::unistd::close(fd_write).map_err(|e| e.to_string())?;
+        let rv = splice(fd_read, None, fd_file, None, 10, libc::SPLICE_F_NONBLOCK);
+        test_utils::result_assert_eq(rv, Ok(0), "Unexpected splice() result")?;
+
+        Ok(())
+    })
+}
+
+/// A non-blocking splice into a full pipe returns `EAGAIN`.
+fn test_nonblock_full_pipe() -> Result<(), String> {
+    let fd_file = temp_file(&pattern())?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_file, fd_read, fd_write], || {
+        // fill the pipe
+        let buf = vec![0u8; 4096];
+        nix::fcntl::fcntl(
+            fd_write,
+            nix::fcntl::FcntlArg::F_SETFL(nix::fcntl::OFlag::O_NONBLOCK),
+        )
+        .map_err(|e| e.to_string())?;
+        while nix::unistd::write(fd_write, &buf).is_ok() {}
+        nix::fcntl::fcntl(
+            fd_write,
+            nix::fcntl::FcntlArg::F_SETFL(nix::fcntl::OFlag::empty()),
+        )
+        .map_err(|e| e.to_string())?;
+
+        let rv = splice(
+            fd_file,
+            None,
+            fd_write,
+            None,
+            PATTERN_LEN,
+            libc::SPLICE_F_NONBLOCK,
+        );
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected splice() result")?;
+
+        Ok(())
+    })
+}
+
+/// Bytes spliced from a socket into a pipe are removed from the socket and can be read out of the
+/// pipe unchanged.
+fn test_socket_to_pipe() -> Result<(), String> {
+    let (fd_sock_1, fd_sock_2) = socket_pair(SockFlag::empty())?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    // the write end of the socket pair is closed during the test
+    test_utils::run_and_close_fds(&[fd_sock_2, fd_read, fd_write], || {
+        let rv = nix::unistd::write(fd_sock_1, &pattern()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, PATTERN_LEN, "Unexpected write() result")?;
+
+        let mut num_spliced = 0;
+        while num_spliced < PATTERN_LEN {
+            let rv = splice(
+                fd_sock_2,
+                None,
+                fd_write,
+                None,
+                PATTERN_LEN - num_spliced,
+                0,
+            );
+            let rv = rv.map_err(|e| e.to_string())?;
+            test_utils::result_assert(rv > 0, "Unexpected end of file")?;
+            num_spliced += rv;
+        }
+
+        test_utils::result_assert_eq(read_exact(fd_read, PATTERN_LEN)?, pattern(), "Bad data")?;
+
+        // after the peer closes the socket, the splice returns 0
+        nix::unistd::close(fd_sock_1).map_err(|e| e.to_string())?;
+        let rv = splice(fd_sock_2, None, fd_write, None, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Ok(0), "Unexpected splice() result")?;
+
+        Ok(())
+    })
+}
+
+/// Bytes spliced from a pipe into a socket are removed from the pipe and can be received by the
+/// peer.
+fn test_pipe_to_socket() -> Result<(), String> {
+    let (fd_sock_1, fd_sock_2) = socket_pair(SockFlag::empty())?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_sock_1, fd_sock_2, fd_read, fd_write], || {
+        let rv = nix::unistd::write(fd_write, &pattern()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, PATTERN_LEN, "Unexpected write() result")?;
+
+        let mut num_spliced = 0;
+        while num_spliced < PATTERN_LEN {
+            let rv = splice(fd_read, None, fd_sock_1, None, PATTERN_LEN - num_spliced, 0);
+            let rv = rv.map_err(|e| e.to_string())?;
+            test_utils::result_assert(rv > 0, "Nothing was spliced")?;
+            num_spliced += rv;
+        }
+
+        test_utils::result_assert_eq(read_exact(fd_sock_2, PATTERN_LEN)?, pattern(), "Bad data")?;
+
+        // the pipe is now empty
+        let rv = splice(fd_read, None, fd_sock_1, None, 1, libc::SPLICE_F_NONBLOCK);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected splice() result")?;
+
+        Ok(())
+    })
+}
+
+/// A socket can't be spliced to a regular file without a pipe in between.
+fn test_socket_no_pipe() -> Result<(), String> {
+    let (fd_sock_1, fd_sock_2) = socket_pair(SockFlag::empty())?;
+    let fd_file = temp_file(&pattern())?;
+
+    test_utils::run_and_close_fds(&[fd_sock_1, fd_sock_2, fd_file], || {
+        let rv = splice(fd_file, None, fd_sock_1, None, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Unexpected splice() result")?;
+        Ok(())
+    })
+}
+
+/// Offsets can't be given for sockets.
+fn test_socket_offset() -> Result<(), String> {
+    let (fd_sock_1, fd_sock_2) = socket_pair(SockFlag::empty())?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_sock_1, fd_sock_2, fd_read, fd_write], || {
+        let rv = nix::unistd::write(fd_sock_1, &pattern()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, PATTERN_LEN, "Unexpected write() result")?;
+
+        let mut offset: libc::loff_t = 0;
+        let rv = splice(fd_sock_2, Some(&mut offset), fd_write, None, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::ESPIPE), "Unexpected splice() result")?;
+        Ok(())
+    })
+}
+
+/// A splice from a non-blocking socket with no data returns `EAGAIN`.
+fn test_nonblock_socket() -> Result<(), String> {
+    let (fd_sock_1, fd_sock_2) = socket_pair(SockFlag::SOCK_NONBLOCK)?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_sock_1, fd_sock_2, fd_read, fd_write], || {
+        let rv = splice(fd_sock_2, None, fd_write, None, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected splice() result")?;
+        Ok(())
+    })
+}
+
+/// Bytes teed from one pipe into another can be read from both pipes.
+fn test_tee() -> Result<(), String> {
+    let (fd_read_1, fd_write_1) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+    let (fd_read_2, fd_write_2) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_read_1, fd_write_1, fd_read_2, fd_write_2], || {
+        let rv = nix::unistd::write(fd_write_1, &pattern()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, PATTERN_LEN, "Unexpected write() result")?;
+
+        // tee more than is available
+        let rv = tee(fd_read_1, fd_write_2, 2 * PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Ok(PATTERN_LEN), "Unexpected tee() result")?;
+
+        test_utils::result_assert_eq(read_exact(fd_read_2, PATTERN_LEN)?, pattern(), "Bad data")?;
+        test_utils::result_assert_eq(read_exact(fd_read_1, PATTERN_LEN)?, pattern(), "Bad data")?;
+
+        // both pipes are now empty
+        let rv = tee(fd_read_1, fd_write_2, 1, libc::SPLICE_F_NONBLOCK);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected tee() result")?;
+        let rv = tee(fd_read_2, fd_write_1, 1, libc::SPLICE_F_NONBLOCK);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected tee() result")?;
+
+        Ok(())
+    })
+}
+
+/// Both descriptors must be different pipes.
+fn test_tee_not_pipe() -> Result<(), String> {
+    let fd_file = temp_file(&pattern())?;
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_file, fd_read, fd_write], || {
+        let rv = nix::unistd::write(fd_write, &pattern()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, PATTERN_LEN, "Unexpected write() result")?;
+
+        let rv = tee(fd_read, fd_file, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Unexpected tee() result")?;
+        let rv = tee(fd_file, fd_write, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Unexpected tee() result")?;
+        let rv = tee(fd_read, fd_write, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::EINVAL), "Unexpected tee() result")?;
+
+        Ok(())
+    })
+}
+
+/// A non-blocking tee from an empty pipe or into a full pipe returns `EAGAIN`.
+fn test_tee_nonblock() -> Result<(), String> {
+    let (fd_read_1, fd_write_1) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+    let (fd_read_2, fd_write_2) =
+        nix::unistd::pipe2(nix::fcntl::OFlag::O_NONBLOCK).map_err(|e| e.to_string())?;
+
+    // the write end of the first pipe is closed during the test
+    test_utils::run_and_close_fds(&[fd_read_1, fd_read_2, fd_write_2], || {
+        let rv = tee(fd_read_1, fd_write_2, PATTERN_LEN, libc::SPLICE_F_NONBLOCK);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected tee() result")?;
+
+        // fill the second pipe, which is non-blocking
+        let buf = vec![0u8; 4096];
+        while nix::unistd::write(fd_write_2, &buf).is_ok() {}
+
+        let rv = nix::unistd::write(fd_write_1, &pattern()).map_err(|e| e.to_string())?;
+        test_utils::result_assert_eq(rv, PATTERN_LEN, "Unexpected write() result")?;
+
+        // the destination pipe's O_NONBLOCK also makes the tee non-blocking
+        let rv = tee(fd_read_1, fd_write_2, PATTERN_LEN, 0);
+        test_utils::result_assert_eq(rv, Err(Errno::EAGAIN), "Unexpected tee() result")?;
+
+        // with no writers and no data, the tee returns 0 if the destination has space
+        test_utils::result_assert_eq(read_exact(fd_read_1, PATTERN_LEN)?, pattern(), "Bad data")?;
+        read_exact(fd_read_2, buf.len())?;
+        nix::unistd::close(fd_write_1).map_err(|e| e.to_string())?;
+        let rv = tee(fd_read_1, fd_write_2, PATTERN_LEN, libc::SPLICE_F_NONBLOCK);
+        test_utils::result_assert_eq(rv, Ok(0), "Unexpected tee() result")?;
+
+        Ok(())
+    })
+}
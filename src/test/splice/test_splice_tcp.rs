@@ -0,0 +1,150 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! A client sends a file to a server by splicing it from the file into a pipe and from the pipe
+//! into a TCP socket. The server splices the received data from its socket into a pipe and from the
+//! pipe into a file, and checks that the file matches what the client sent.
+//!
+//! Usage:
+//! - `test_splice_tcp server <port>`: receive the file and check its contents
+//! - `test_splice_tcp client <server-ip> <port>`: send the file
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use nix::sys::socket::{self, AddressFamily, Shutdown, SockFlag, SockType, SockaddrIn};
+
+/// Larger than a pipe's default capacity, so that the data is moved in multiple splices.
+const FILE_LEN: usize = 1_000_000;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = format!(
+        "Usage: {0} server <port> | {0} client <server-ip> <port>",
+        args[0]
+    );
+
+    let parse_port = |s: &str| s.parse::<u16>().map_err(|e| format!("Bad port: {e}"));
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("server"), 3) => run_server(parse_port(&args[2])?)?,
+        (Some("client"), 4) => {
+            let ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad ip: {e}"))?;
+            run_client(SocketAddrV4::new(ip, parse_port(&args[3])?))?
+        }
+        _ => return Err(usage),
+    }
+
+    println!("Success.");
+    Ok(())
+}
+
+fn file_contents() -> Vec<u8> {
+    (0..FILE_LEN).map(|x| (x % 251) as u8).collect()
+}
+
+fn splice(fd_in: libc::c_int, fd_out: libc::c_int, len: usize) -> Result<usize, String> {
+    let null = std::ptr::null_mut();
+    let rv = unsafe { libc::splice(fd_in, null, fd_out, null, len, 0) };
+    nix::errno::Errno::result(rv)
+        .map(|x| x as usize)
+        .map_err(|e| format!("splice() failed: {e}"))
+}
+
+/// Create a temporary file containing `contents`, with the file offset at the start of the file.
+fn temp_file(contents: &[u8]) -> Result<libc::c_int, String> {
+    let (fd, path) =
+        nix::unistd::mkstemp(&b"testsplicetcp_XXXXXX"[..]).map_err(|e| e.to_string())?;
+    nix::unistd::unlink(&path).map_err(|e| e.to_string())?;
+
+    let mut num_written = 0;
+    while num_written < contents.len() {
+        num_written +=
+            nix::unistd::write(fd, &contents[num_written..]).map_err(|e| e.to_string())?;
+    }
+
+    nix::unistd::lseek(fd, 0, nix::unistd::Whence::SeekSet).map_err(|e| e.to_string())?;
+
+    Ok(fd)
+}
+
+/// Move everything from `fd_in` to `fd_out` through a pipe until `fd_in` reaches the end of the
+/// file, and return the number of bytes moved.
+fn splice_through_pipe(fd_in: libc::c_int, fd_out: libc::c_int) -> Result<usize, String> {
+    let (fd_read, fd_write) = nix::unistd::pipe().map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd_read, fd_write], || {
+        let mut total = 0;
+        loop {
+            let mut in_pipe = splice(fd_in, fd_write, 65536)?;
+            if in_pipe == 0 {
+                return Ok(total);
+            }
+            total += in_pipe;
+
+            while in_pipe > 0 {
+                let rv = splice(fd_read, fd_out, in_pipe)?;
+                test_utils::result_assert(rv > 0, "Nothing was spliced from the pipe")?;
+                in_pipe -= rv;
+            }
+        }
+    })
+}
+
+/// Receive the file from the client, and check that it's unchanged.
+fn run_server(port: u16) -> Result<(), String> {
+    let fd_listen = socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let addr = SockaddrIn::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    socket::bind(fd_listen, &addr).map_err(|e| e.to_string())?;
+    socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+    let fd = socket::accept(fd_listen).map_err(|e| e.to_string())?;
+    let fd_file = temp_file(&[])?;
+
+    test_utils::run_and_close_fds(&[fd_listen, fd, fd_file], || {
+        let num_received = splice_through_pipe(fd, fd_file)?;
+        test_utils::result_assert_eq(num_received, FILE_LEN, "Unexpected number of bytes")?;
+
+        nix::unistd::lseek(fd_file, 0, nix::unistd::Whence::SeekSet).map_err(|e| e.to_string())?;
+
+        let mut received = vec![0u8; FILE_LEN];
+        let mut num_read = 0;
+        while num_read < FILE_LEN {
+            let rv =
+                nix::unistd::read(fd_file, &mut received[num_read..]).map_err(|e| e.to_string())?;
+            test_utils::result_assert(rv > 0, "Unexpected end of file")?;
+            num_read += rv;
+        }
+
+        test_utils::result_assert(received == file_contents(), "The file was changed")
+    })
+}
+
+/// Send the file to the server.
+fn run_client(server_addr: SocketAddrV4) -> Result<(), String> {
+    let fd = socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::empty(),
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+    let fd_file = temp_file(&file_contents())?;
+
+    test_utils::run_and_close_fds(&[fd, fd_file], || {
+        socket::connect(fd, &SockaddrIn::from(server_addr)).map_err(|e| e.to_string())?;
+
+        let num_sent = splice_through_pipe(fd_file, fd)?;
+        test_utils::result_assert_eq(num_sent, FILE_LEN, "Unexpected number of bytes")?;
+
+        socket::shutdown(fd, Shutdown::Write).map_err(|e| e.to_string())
+    })
+}
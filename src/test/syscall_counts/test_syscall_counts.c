@@ -0,0 +1,24 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+
+// Makes the given number of `getppid` syscalls, so that the syscall counts can be checked.
+int main(int argc, char* argv[]) {
+    if (argc != 2) {
+        fprintf(stderr, "Usage: %s <num-getppid-calls>\n", argv[0]);
+        return EXIT_FAILURE;
+    }
+
+    int num_calls = atoi(argv[1]);
+    for (int i = 0; i < num_calls; i++) {
+        syscall(SYS_getppid);
+    }
+
+    return EXIT_SUCCESS;
+}
@@ -0,0 +1,28 @@
+#!/usr/bin/env python3
+
+# Checks the file written by shadow's '--dump-syscall-counts' option for the
+# 'syscall-counts.yaml' simulation.
+
+import json
+import sys
+
+with open(sys.argv[1]) as f:
+    counts = json.load(f)
+
+assert sorted(counts.keys()) == ['alice', 'bob'], counts.keys()
+
+# the number of getppid calls made by each host's processes, in the order that they were started
+expected = {'alice': [3], 'bob': [5, 7]}
+
+for (host, expected_calls) in expected.items():
+    processes = counts[host]
+    assert all(name.startswith('test_syscall_counts.') for name in processes), processes
+
+    # processes are named '<exe>.<pid>', and pids are assigned in the order that processes start
+    names = sorted(processes.keys(), key=lambda name: int(name.split('.')[-1]))
+    assert [processes[name]['getppid'] for name in names] == expected_calls, processes
+
+    for syscalls in processes.values():
+        assert all(isinstance(count, int) and count > 0 for count in syscalls.values()), syscalls
+
+print('Success.')
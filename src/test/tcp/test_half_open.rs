@@ -0,0 +1,154 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Tests for half-open TCP connections, where one side of the connection goes away and the other
+//! side only finds out when the peer answers its next send with a RST.
+
+use nix::errno::Errno;
+use nix::sys::socket::MsgFlags;
+
+use test_utils::set;
+use test_utils::socket_utils;
+use test_utils::TestEnvironment as TestEnv;
+
+fn main() -> Result<(), String> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnv::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnv::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    println!("Success.");
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), String>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_close_with_unread_data",
+            test_close_with_unread_data,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_send_after_peer_closed",
+            test_send_after_peer_closed,
+            set![TestEnv::Libc, TestEnv::Shadow],
+        ),
+    ]
+}
+
+/// Returns a connected (client, server-side) pair of sockets. The listening socket is closed.
+fn connected_pair() -> Result<(libc::c_int, libc::c_int), String> {
+    let fd_listen = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    let fd_client = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
+    assert!(fd_listen >= 0);
+    assert!(fd_client >= 0);
+
+    let (server_addr, server_addr_len) = socket_utils::autobind_helper(fd_listen, libc::AF_INET);
+
+    test_utils::run_and_close_fds(&[fd_listen], || {
+        nix::sys::socket::listen(fd_listen, 10).map_err(|e| e.to_string())?;
+
+        let rv = unsafe { libc::connect(fd_client, server_addr.as_ptr(), server_addr_len) };
+        test_utils::result_assert_eq(rv, 0, "connect() failed")?;
+
+        let fd_peer = nix::sys::socket::accept(fd_listen).map_err(|e| e.to_string())?;
+
+        Ok((fd_client, fd_peer))
+    })
+}
+
+/// A peer that closes its socket without reading all of the data resets the connection. The
+/// survivor's next send returns ECONNRESET, and later sends return EPIPE.
+fn test_close_with_unread_data() -> Result<(), String> {
+    let (fd_client, fd_peer) = connected_pair()?;
+
+    test_utils::run_and_close_fds(&[fd_client], || {
+        // send data that the peer will never read
+        test_utils::result_assert_eq(
+            nix::sys::socket::send(fd_client, &[1, 2, 3], MsgFlags::MSG_NOSIGNAL),
+            Ok(3),
+            "Could not send data",
+        )?;
+
+        test_utils::result_assert(
+            test_utils::is_readable(fd_peer, 5000).unwrap(),
+            "The data did not arrive at the peer",
+        )?;
+
+        // the peer goes away, which sends a RST rather than a FIN
+        nix::unistd::close(fd_peer).unwrap();
+
+        test_utils::result_assert(
+            test_utils::is_readable(fd_client, 5000).unwrap(),
+            "The reset did not arrive at the client",
+        )?;
+
+        test_utils::result_assert_eq(
+            nix::sys::socket::send(fd_client, &[1, 2, 3], MsgFlags::MSG_NOSIGNAL),
+            Err(Errno::ECONNRESET),
+            "Unexpected result of the first send after the reset",
+        )?;
+
+        test_utils::result_assert_eq(
+            nix::sys::socket::send(fd_client, &[1, 2, 3], MsgFlags::MSG_NOSIGNAL),
+            Err(Errno::EPIPE),
+            "Unexpected result of the second send after the reset",
+        )?;
+
+        Ok(())
+    })
+}
+
+/// A peer that closed its socket answers new data with a RST, so the survivor's first send
+/// succeeds but later sends return EPIPE.
+fn test_send_after_peer_closed() -> Result<(), String> {
+    let (fd_client, fd_peer) = connected_pair()?;
+
+    test_utils::run_and_close_fds(&[fd_client], || {
+        // the peer closes cleanly, so the client only sees an EOF
+        nix::unistd::close(fd_peer).unwrap();
+
+        test_utils::result_assert(
+            test_utils::is_readable(fd_client, 5000).unwrap(),
+            "The FIN did not arrive at the client",
+        )?;
+        let mut buf = [0u8; 3];
+        test_utils::result_assert_eq(
+            nix::sys::socket::recv(fd_client, &mut buf, MsgFlags::empty()),
+            Ok(0),
+            "Expected an EOF",
+        )?;
+
+        // the client doesn't know that the peer is gone, so the send succeeds
+        test_utils::result_assert_eq(
+            nix::sys::socket::send(fd_client, &[1, 2, 3], MsgFlags::MSG_NOSIGNAL),
+            Ok(3),
+            "Could not send data",
+        )?;
+
+        // wait for the peer's RST
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        // the peer had already closed its end, so linux reports EPIPE rather than ECONNRESET
+        test_utils::result_assert_eq(
+            nix::sys::socket::send(fd_client, &[1, 2, 3], MsgFlags::MSG_NOSIGNAL),
+            Err(Errno::EPIPE),
+            "Unexpected result of the send after the reset",
+        )?;
+
+        Ok(())
+    })
+}
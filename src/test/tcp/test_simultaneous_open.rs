@@ -0,0 +1,105 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+//! Two hosts run this test at the same time, and each connects to the other without listening, so
+//! that their SYN packets cross in the network (a TCP "simultaneous open"). Both sides should end
+//! up with a single established connection to each other.
+//!
+//! Usage: `test_tcp_simultaneous_open <local-ip> <peer-ip>`
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use nix::errno::Errno;
+use nix::sys::socket::{self, AddressFamily, MsgFlags, SockFlag, SockType, SockaddrIn};
+
+const PORT: u16 = 9000;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        return Err(format!("Usage: {} <local-ip> <peer-ip>", args[0]));
+    }
+
+    let local_ip: Ipv4Addr = args[1].parse().map_err(|e| format!("Bad local ip: {e}"))?;
+    let peer_ip: Ipv4Addr = args[2].parse().map_err(|e| format!("Bad peer ip: {e}"))?;
+
+    let local_addr = SockaddrIn::from(SocketAddrV4::new(local_ip, PORT));
+    let peer_addr = SockaddrIn::from(SocketAddrV4::new(peer_ip, PORT));
+
+    let fd = socket::socket(
+        AddressFamily::Inet,
+        SockType::Stream,
+        SockFlag::SOCK_NONBLOCK,
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+
+    test_utils::run_and_close_fds(&[fd], || {
+        // both sides use a fixed port so that each side's SYN matches the other side's socket
+        socket::bind(fd, &local_addr).map_err(|e| e.to_string())?;
+
+        test_utils::result_assert_eq(
+            socket::connect(fd, &peer_addr),
+            Err(Errno::EINPROGRESS),
+            "Unexpected connect() result",
+        )?;
+
+        // the handshake completes after each side acknowledges the other's SYN
+        test_utils::result_assert(
+            test_utils::is_writable(fd, 5000).unwrap(),
+            "Socket did not become writable after the simultaneous open",
+        )?;
+
+        let error = socket::getsockopt(fd, socket::sockopt::SocketError).unwrap();
+        test_utils::result_assert_eq(error, 0, "Unexpected SO_ERROR value")?;
+
+        // the first connect() after the handshake reports the success
+        test_utils::result_assert_eq(
+            socket::connect(fd, &peer_addr),
+            Ok(()),
+            "Unexpected connect() result after the handshake",
+        )?;
+        test_utils::result_assert_eq(
+            socket::connect(fd, &peer_addr),
+            Err(Errno::EISCONN),
+            "Unexpected connect() result after the connection was reported",
+        )?;
+
+        let peer_name: SockaddrIn = socket::getpeername(fd).unwrap();
+        test_utils::result_assert_eq(peer_name, peer_addr, "Unexpected peer name")?;
+
+        // exchange addresses over the connection to make sure that both sides use the same one
+        let msg = local_ip.to_string();
+        test_utils::result_assert_eq(
+            socket::send(fd, msg.as_bytes(), MsgFlags::empty()),
+            Ok(msg.len()),
+            "Could not send the message",
+        )?;
+
+        let expected_msg = peer_ip.to_string();
+        let mut buf = vec![0u8; expected_msg.len()];
+        let mut bytes_read = 0;
+        while bytes_read < buf.len() {
+            test_utils::result_assert(
+                test_utils::is_readable(fd, 5000).unwrap(),
+                "Socket did not become readable",
+            )?;
+            let rv = socket::recv(fd, &mut buf[bytes_read..], MsgFlags::empty()).unwrap();
+            test_utils::result_assert_ne(rv, 0, "Unexpected EOF")?;
+            bytes_read += rv;
+        }
+
+        test_utils::result_assert_eq(
+            buf.as_slice(),
+            expected_msg.as_bytes(),
+            "Unexpected message from the peer",
+        )?;
+
+        Ok(())
+    })?;
+
+    println!("Success.");
+    Ok(())
+}
@@ -442,6 +442,31 @@ pub fn i8_to_u8_slice(s: &[i8]) -> &[u8] {
     unsafe { std::slice::from_raw_parts(s.as_ptr() as *const u8, s.len()) }
 }
 
+/// Pointers that syscalls can't access: an address that isn't mapped, a kernel address, and an
+/// address where even a small object would wrap around the end of the address space.
+pub fn invalid_pointers() -> [*mut libc::c_void; 3] {
+    // map and then unmap a page, so that we know the address isn't mapped
+    let page_size = usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap();
+    let unmapped = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(unmapped, libc::MAP_FAILED);
+    assert_eq!(unsafe { libc::munmap(unmapped, page_size) }, 0);
+
+    [
+        unmapped,
+        0xffff_8000_0000_0000_usize as *mut libc::c_void,
+        (usize::MAX - 3) as *mut libc::c_void,
+    ]
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct ITimer {
     pub interval: TimeVal,
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use test_utils::time::*;
+use test_utils::{ensure_ord, running_in_shadow, set, TestEnvironment};
+
+fn main() -> anyhow::Result<()> {
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let mut tests = get_tests();
+
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnvironment::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnvironment::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    Ok(())
+}
+
+fn get_tests() -> Vec<test_utils::ShadowTest<(), anyhow::Error>> {
+    vec![
+        test_utils::ShadowTest::new(
+            "test_read_clock_state",
+            test_read_clock_state,
+            set![TestEnvironment::Libc, TestEnvironment::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_adjtime_monotonic",
+            test_adjtime_monotonic,
+            set![TestEnvironment::Libc, TestEnvironment::Shadow],
+        ),
+        // Outside of Shadow these would adjust the real clock if run with `CAP_SYS_TIME`.
+        test_utils::ShadowTest::new(
+            "test_adjust_frequency",
+            test_adjust_frequency,
+            set![TestEnvironment::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_step_clock",
+            test_step_clock,
+            set![TestEnvironment::Shadow],
+        ),
+        test_utils::ShadowTest::new(
+            "test_monotonic_raw_slewed",
+            test_monotonic_raw_slewed,
+            set![TestEnvironment::Shadow],
+        ),
+        // Outside of Shadow the clocks may be slewed by NTP.
+        test_utils::ShadowTest::new(
+            "test_monotonic_raw_matches_monotonic",
+            test_monotonic_raw_matches_monotonic,
+            set![TestEnvironment::Shadow],
+        ),
+    ]
+}
+
+fn clock_adjtime(clockid: libc::clockid_t, timex: &mut libc::timex) -> Result<i32, nix::Error> {
+    let rv = unsafe { libc::syscall(libc::SYS_clock_adjtime, clockid, std::ptr::from_mut(timex)) };
+    nix::errno::Errno::result(rv).map(|x| x.try_into().unwrap())
+}
+
+fn zeroed_timex() -> libc::timex {
+    unsafe { std::mem::zeroed() }
+}
+
+/// Reading the clock state shouldn't require any privileges.
+fn test_read_clock_state() -> anyhow::Result<()> {
+    let mut timex = zeroed_timex();
+    let rv = unsafe { libc::adjtimex(&mut timex) };
+    ensure_ord!(rv, >=, 0);
+
+    let mut timex_realtime = zeroed_timex();
+    ensure_ord!(clock_adjtime(libc::CLOCK_REALTIME, &mut timex_realtime), ==, Ok(rv));
+
+    if running_in_shadow() {
+        // Shadow's clock hasn't been adjusted yet.
+        ensure_ord!(rv, ==, libc::TIME_OK);
+        ensure_ord!(timex.offset, ==, 0);
+        ensure_ord!(timex.freq, ==, 0);
+        ensure_ord!(timex.status, ==, 0);
+    }
+
+    Ok(())
+}
+
+/// Only the realtime clocks can be adjusted.
+fn test_adjtime_monotonic() -> anyhow::Result<()> {
+    for clockid in [libc::CLOCK_MONOTONIC, libc::CLOCK_MONOTONIC_RAW] {
+        let mut timex = zeroed_timex();
+        ensure_ord!(
+            clock_adjtime(clockid, &mut timex),
+            ==,
+            Err(nix::errno::Errno::EOPNOTSUPP)
+        );
+    }
+
+    Ok(())
+}
+
+/// Set the clock's frequency offset in scaled ppm, returning the offset that was applied.
+fn set_freq(freq: i64) -> anyhow::Result<i64> {
+    let mut timex = zeroed_timex();
+    timex.modes = libc::ADJ_FREQUENCY;
+    timex.freq = freq;
+    ensure_ord!(unsafe { libc::adjtimex(&mut timex) }, ==, libc::TIME_OK);
+    Ok(timex.freq)
+}
+
+/// The frequency offset should be reported back, and clamped to 500 ppm like Linux.
+fn test_adjust_frequency() -> anyhow::Result<()> {
+    // 100 ppm
+    ensure_ord!(set_freq(100 << 16)?, ==, 100 << 16);
+
+    let mut timex = zeroed_timex();
+    ensure_ord!(unsafe { libc::adjtimex(&mut timex) }, ==, libc::TIME_OK);
+    ensure_ord!(timex.freq, ==, 100 << 16);
+
+    ensure_ord!(set_freq(1000 << 16)?, ==, 500 << 16);
+    ensure_ord!(set_freq(-1000 << 16)?, ==, -500 << 16);
+    ensure_ord!(set_freq(0)?, ==, 0);
+
+    Ok(())
+}
+
+/// Shadow can't step the clock.
+fn test_step_clock() -> anyhow::Result<()> {
+    let mut timex = zeroed_timex();
+    timex.modes = libc::ADJ_SETOFFSET;
+    timex.time.tv_sec = 1;
+    let rv = unsafe { libc::adjtimex(&mut timex) };
+    ensure_ord!(rv, ==, -1);
+    ensure_ord!(nix::errno::Errno::last(), ==, nix::errno::Errno::EINVAL);
+
+    Ok(())
+}
+
+/// Returns the time elapsed on CLOCK_MONOTONIC and CLOCK_MONOTONIC_RAW while sleeping for 10
+/// seconds.
+fn monotonic_and_raw_elapsed() -> anyhow::Result<(Duration, Duration)> {
+    let mono_start = clock_now_duration(libc::CLOCK_MONOTONIC)?;
+    let raw_start = clock_now_duration(libc::CLOCK_MONOTONIC_RAW)?;
+
+    std::thread::sleep(Duration::from_secs(10));
+
+    let mono_end = clock_now_duration(libc::CLOCK_MONOTONIC)?;
+    let raw_end = clock_now_duration(libc::CLOCK_MONOTONIC_RAW)?;
+
+    let mono_elapsed = mono_end - mono_start;
+    ensure_ord!(mono_elapsed, >=, Duration::from_secs(10));
+
+    Ok((mono_elapsed, raw_end - raw_start))
+}
+
+/// When the clock isn't slewed, CLOCK_MONOTONIC and CLOCK_MONOTONIC_RAW should advance at the same
+/// rate.
+fn test_monotonic_raw_matches_monotonic() -> anyhow::Result<()> {
+    let (mono_elapsed, raw_elapsed) = monotonic_and_raw_elapsed()?;
+
+    // Even a small slew of 1 ppm would be a difference of 10 us here, so the difference should
+    // only be from the time it takes to read the clocks.
+    ensure_ord!(
+        duration_abs_diff(mono_elapsed, raw_elapsed),
+        <=,
+        Duration::from_micros(2)
+    );
+
+    Ok(())
+}
+
+/// When the clock is slewed, CLOCK_MONOTONIC_RAW should diverge from CLOCK_MONOTONIC.
+fn test_monotonic_raw_slewed() -> anyhow::Result<()> {
+    for ppm in [100, -100] {
+        ensure_ord!(set_freq(ppm << 16)?, ==, ppm << 16);
+        let (mono_elapsed, raw_elapsed) = monotonic_and_raw_elapsed()?;
+        ensure_ord!(set_freq(0)?, ==, 0);
+
+        // CLOCK_MONOTONIC runs `1 + ppm / 1e6` times as fast as CLOCK_MONOTONIC_RAW, so it
+        // should gain (or lose) about 1 ms over 10 seconds.
+        let expected_diff = mono_elapsed.as_secs_f64() * (ppm as f64 / (1e6 + ppm as f64));
+        let diff = mono_elapsed.as_secs_f64() - raw_elapsed.as_secs_f64();
+        ensure_ord!((diff - expected_diff).abs(), <=, 2e-6);
+        ensure_ord!(diff.abs(), >=, 999e-6);
+    }
+
+    Ok(())
+}
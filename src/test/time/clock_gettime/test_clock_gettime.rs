@@ -102,9 +102,138 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), anyhow::Error>> {
         }
     }
 
+    tests.push(test_utils::ShadowTest::new(
+        "monotonic_clocks_agree",
+        test_monotonic_clocks_agree,
+        set![TestEnvironment::Libc, TestEnvironment::Shadow],
+    ));
+
+    tests.push(test_utils::ShadowTest::new(
+        "cpu_time_clocks_dont_advance_while_sleeping",
+        test_cpu_time_clocks_dont_advance_while_sleeping,
+        set![TestEnvironment::Libc, TestEnvironment::Shadow],
+    ));
+
     tests
 }
 
+fn clock_gettime_nanos(clockid: libc::clockid_t) -> anyhow::Result<u128> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let rv = unsafe { libc::clock_gettime(clockid, &mut ts) };
+    anyhow::ensure!(rv == 0, "clock_gettime({clockid}) failed");
+    let secs = u128::try_from(ts.tv_sec)?;
+    let nanos = u128::try_from(ts.tv_nsec)?;
+    Ok(secs * 1_000_000_000 + nanos)
+}
+
+/// `CLOCK_MONOTONIC`, `CLOCK_BOOTTIME`, and `CLOCK_MONOTONIC_RAW` should never go backwards. In
+/// shadow there's no suspend or clock adjustment, so they should also all agree.
+fn test_monotonic_clocks_agree() -> anyhow::Result<()> {
+    let clockids = [
+        libc::CLOCK_MONOTONIC,
+        libc::CLOCK_BOOTTIME,
+        libc::CLOCK_MONOTONIC_RAW,
+    ];
+
+    let mut prev: Option<[u128; 3]> = None;
+    for _ in 0..100 {
+        let mut now = [0; 3];
+        for (now, clockid) in now.iter_mut().zip(clockids) {
+            *now = clock_gettime_nanos(clockid)?;
+        }
+
+        if let Some(prev) = prev {
+            for ((now, prev), clockid) in now.iter().zip(prev).zip(clockids) {
+                anyhow::ensure!(*now >= prev, "Clock {clockid} went backwards");
+            }
+        }
+
+        if test_utils::running_in_shadow() {
+            // The clocks were read at (almost) the same simulated instant; each read may advance
+            // time by a small simulated syscall latency.
+            let min = *now.iter().min().unwrap();
+            let max = *now.iter().max().unwrap();
+            anyhow::ensure!(
+                max - min < 1_000_000,
+                "Clocks don't agree: {now:?} for clocks {clockids:?}"
+            );
+        }
+
+        prev = Some(now);
+
+        // let simulated time advance between iterations
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    Ok(())
+}
+
+/// The CPU-time clocks should only advance while a thread is running, so they should barely move
+/// while the thread sleeps, even though the monotonic clock does.
+fn test_cpu_time_clocks_dont_advance_while_sleeping() -> anyhow::Result<()> {
+    let sleep = std::time::Duration::from_millis(100);
+    // sleeping needs a little CPU time for the syscalls
+    let max_cpu_time = 10_000_000;
+
+    let read_clocks = || -> anyhow::Result<[u128; 3]> {
+        Ok([
+            clock_gettime_nanos(libc::CLOCK_MONOTONIC)?,
+            clock_gettime_nanos(libc::CLOCK_PROCESS_CPUTIME_ID)?,
+            clock_gettime_nanos(libc::CLOCK_THREAD_CPUTIME_ID)?,
+        ])
+    };
+
+    let [mono_1, process_1, thread_1] = read_clocks()?;
+    // the process's CPU time includes this thread's CPU time
+    anyhow::ensure!(process_1 >= thread_1, "{process_1} < {thread_1}");
+
+    std::thread::sleep(sleep);
+
+    let [mono_2, process_2, thread_2] = read_clocks()?;
+    anyhow::ensure!(process_2 >= thread_2, "{process_2} < {thread_2}");
+
+    anyhow::ensure!(
+        mono_2 - mono_1 >= sleep.as_nanos(),
+        "Monotonic clock didn't advance"
+    );
+    anyhow::ensure!(process_2 >= process_1, "Process CPU time went backwards");
+    anyhow::ensure!(thread_2 >= thread_1, "Thread CPU time went backwards");
+    anyhow::ensure!(
+        process_2 - process_1 < max_cpu_time,
+        "Process CPU time advanced by {}ns while sleeping",
+        process_2 - process_1,
+    );
+    anyhow::ensure!(
+        thread_2 - thread_1 < max_cpu_time,
+        "Thread CPU time advanced by {}ns while sleeping",
+        thread_2 - thread_1,
+    );
+
+    // a thread that only sleeps uses little CPU time, even though the process has been running
+    let [child_mono, child_thread] = std::thread::spawn(move || -> anyhow::Result<[u128; 2]> {
+        std::thread::sleep(sleep);
+        Ok([
+            clock_gettime_nanos(libc::CLOCK_MONOTONIC)?,
+            clock_gettime_nanos(libc::CLOCK_THREAD_CPUTIME_ID)?,
+        ])
+    })
+    .join()
+    .unwrap()?;
+    anyhow::ensure!(
+        child_mono - mono_2 >= sleep.as_nanos(),
+        "Monotonic clock didn't advance"
+    );
+    anyhow::ensure!(
+        child_thread < max_cpu_time,
+        "New thread's CPU time is {child_thread}ns"
+    );
+
+    Ok(())
+}
+
 fn test_clock_gettime(
     clockid: FuzzArg<libc::clockid_t>,
     mut ts: FuzzArg<Option<libc::timespec>>,
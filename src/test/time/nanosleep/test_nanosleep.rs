@@ -120,6 +120,11 @@ fn get_tests() -> Vec<test_utils::ShadowTest<(), anyhow::Error>> {
             test_interrupted_sleep,
             set![TestEnvironment::Libc, TestEnvironment::Shadow],
         ),
+        test_utils::ShadowTest::new(
+            "invalid_request_pointer",
+            test_invalid_request_pointer,
+            set![TestEnvironment::Libc, TestEnvironment::Shadow],
+        ),
     ]);
 
     tests
@@ -167,6 +172,22 @@ fn test_sleep_duration() -> anyhow::Result<()> {
     })
 }
 
+/// A request that can't be read should return EFAULT.
+fn test_invalid_request_pointer() -> anyhow::Result<()> {
+    for ptr in test_utils::invalid_pointers() {
+        let (rv, errno) = unsafe {
+            (
+                libc::nanosleep(ptr as *const libc::timespec, std::ptr::null_mut()),
+                *libc::__errno_location(),
+            )
+        };
+        ensure_ord!(-1, ==, rv);
+        ensure_ord!(libc::EFAULT, ==, errno);
+    }
+
+    Ok(())
+}
+
 /// A clock_nanosleep interrupted by a signal handler should return EINTR.
 fn test_interrupted_sleep() -> anyhow::Result<()> {
     // The signaler sleeps and then interrupts a sleeping sleeper.
@@ -0,0 +1,409 @@
+use std::sync::atomic::{AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigSet, Signal};
+use test_utils::{ensure_ord, set, ShadowTest, TestEnvironment};
+
+// Counts how many times the SIGALRM handler ran.
+static SIGNAL_CTR: AtomicU64 = AtomicU64::new(0);
+
+// SIGALRM handler.
+extern "C" fn sigalrm_handler(sig: i32) {
+    assert_eq!(sig, libc::SIGALRM);
+    SIGNAL_CTR.fetch_add(1, Ordering::Relaxed);
+}
+
+// CLOCK_MONOTONIC times (in nanoseconds) at which the SIGUSR1 handler ran.
+static ARRIVALS: [AtomicU64; 8] = [const { AtomicU64::new(0) }; 8];
+static ARRIVALS_LEN: AtomicUsize = AtomicUsize::new(0);
+// Thread id of the thread that last ran the SIGUSR1 handler.
+static ARRIVAL_TID: AtomicI32 = AtomicI32::new(0);
+
+// SIGUSR1 handler. Only uses async-signal-safe functions.
+extern "C" fn sigusr1_handler(_sig: i32) {
+    let now = timespec_to_duration(clock_gettime(libc::CLOCK_MONOTONIC));
+    let idx = ARRIVALS_LEN.fetch_add(1, Ordering::Relaxed);
+    if let Some(arrival) = ARRIVALS.get(idx) {
+        arrival.store(now.as_nanos().try_into().unwrap(), Ordering::Relaxed);
+    }
+    ARRIVAL_TID.store(unsafe { libc::gettid() }, Ordering::Relaxed);
+}
+
+/// Returns the times recorded by `sigusr1_handler`, and resets them.
+fn take_arrivals() -> Vec<Duration> {
+    let len = ARRIVALS_LEN.swap(0, Ordering::Relaxed).min(ARRIVALS.len());
+    ARRIVALS[..len]
+        .iter()
+        .map(|x| Duration::from_nanos(x.swap(0, Ordering::Relaxed)))
+        .collect()
+}
+
+fn clock_gettime(clockid: libc::clockid_t) -> libc::timespec {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    Errno::result(unsafe { libc::clock_gettime(clockid, &mut now) }).unwrap();
+    now
+}
+
+fn sigevent(notify: libc::c_int) -> libc::sigevent {
+    // `sigevent` has private padding fields, so we can't construct it directly.
+    let mut sevp: libc::sigevent = unsafe { std::mem::zeroed() };
+    sevp.sigev_notify = notify;
+    sevp.sigev_signo = libc::SIGALRM;
+    sevp
+}
+
+fn timer_create(
+    clockid: libc::clockid_t,
+    sevp: Option<&mut libc::sigevent>,
+) -> Result<libc::timer_t, Errno> {
+    let mut timer_id: libc::timer_t = std::ptr::null_mut();
+    let sevp = sevp.map_or(std::ptr::null_mut(), std::ptr::from_mut);
+    Errno::result(unsafe { libc::timer_create(clockid, sevp, &mut timer_id) })?;
+    Ok(timer_id)
+}
+
+fn timer_settime(
+    timer_id: libc::timer_t,
+    flags: libc::c_int,
+    value: Duration,
+    interval: Duration,
+) -> Result<libc::itimerspec, Errno> {
+    let new_value = libc::itimerspec {
+        it_value: duration_to_timespec(value),
+        it_interval: duration_to_timespec(interval),
+    };
+    let mut old_value: libc::itimerspec = unsafe { std::mem::zeroed() };
+    Errno::result(unsafe { libc::timer_settime(timer_id, flags, &new_value, &mut old_value) })?;
+    Ok(old_value)
+}
+
+fn timer_gettime(timer_id: libc::timer_t) -> Result<(Duration, Duration), Errno> {
+    let mut value: libc::itimerspec = unsafe { std::mem::zeroed() };
+    Errno::result(unsafe { libc::timer_gettime(timer_id, &mut value) })?;
+    Ok((
+        timespec_to_duration(value.it_value),
+        timespec_to_duration(value.it_interval),
+    ))
+}
+
+fn timer_delete(timer_id: libc::timer_t) -> Result<(), Errno> {
+    Errno::result(unsafe { libc::timer_delete(timer_id) })?;
+    Ok(())
+}
+
+fn timer_getoverrun(timer_id: libc::timer_t) -> Result<libc::c_int, Errno> {
+    Errno::result(unsafe { libc::timer_getoverrun(timer_id) })
+}
+
+fn duration_to_timespec(dur: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: dur.as_secs().try_into().unwrap(),
+        tv_nsec: dur.subsec_nanos().into(),
+    }
+}
+
+fn timespec_to_duration(ts: libc::timespec) -> Duration {
+    Duration::new(
+        ts.tv_sec.try_into().unwrap(),
+        ts.tv_nsec.try_into().unwrap(),
+    )
+}
+
+/// Run `f` with a new timer, deleting the timer afterwards.
+fn with_timer(
+    clockid: libc::clockid_t,
+    sevp: Option<&mut libc::sigevent>,
+    f: impl FnOnce(libc::timer_t) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    SIGNAL_CTR.store(0, Ordering::Relaxed);
+    let timer_id = timer_create(clockid, sevp)?;
+    let rv = f(timer_id);
+    timer_delete(timer_id)?;
+    rv
+}
+
+fn test_initially_unset() -> anyhow::Result<()> {
+    with_timer(libc::CLOCK_MONOTONIC, None, |timer_id| {
+        ensure_ord!(timer_gettime(timer_id)?, ==, (Duration::ZERO, Duration::ZERO));
+        Ok(())
+    })
+}
+
+fn test_set_then_get() -> anyhow::Result<()> {
+    with_timer(libc::CLOCK_MONOTONIC, None, |timer_id| {
+        let value = Duration::new(1, 2);
+        let interval = Duration::new(3, 4);
+        let old = timer_settime(timer_id, 0, value, interval)?;
+        ensure_ord!(timespec_to_duration(old.it_value), ==, Duration::ZERO);
+        ensure_ord!(timespec_to_duration(old.it_interval), ==, Duration::ZERO);
+
+        let (remaining, curr_interval) = timer_gettime(timer_id)?;
+        // Interval should be exactly as was set.
+        ensure_ord!(curr_interval, ==, interval);
+        // Time remaining should be equal to or slightly less than what was set.
+        ensure_ord!(remaining, <=, value);
+        ensure_ord!(remaining, >, value - Duration::from_millis(1));
+
+        // Setting a zero value disarms the timer and returns the old value.
+        let old = timer_settime(timer_id, 0, Duration::ZERO, interval)?;
+        ensure_ord!(timespec_to_duration(old.it_interval), ==, interval);
+        ensure_ord!(timer_gettime(timer_id)?, ==, (Duration::ZERO, Duration::ZERO));
+        Ok(())
+    })
+}
+
+fn test_oneshot() -> anyhow::Result<()> {
+    with_timer(libc::CLOCK_MONOTONIC, None, |timer_id| {
+        timer_settime(timer_id, 0, Duration::from_millis(100), Duration::ZERO)?;
+
+        std::thread::sleep(Duration::from_millis(50));
+        ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 0);
+
+        std::thread::sleep(Duration::from_millis(100));
+        ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 1);
+        ensure_ord!(timer_gettime(timer_id)?, ==, (Duration::ZERO, Duration::ZERO));
+
+        // Shouldn't fire again.
+        std::thread::sleep(Duration::from_millis(150));
+        ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 1);
+        Ok(())
+    })
+}
+
+fn test_periodic() -> anyhow::Result<()> {
+    with_timer(libc::CLOCK_MONOTONIC, None, |timer_id| {
+        let period = Duration::from_millis(100);
+        timer_settime(timer_id, 0, period, period)?;
+
+        // Sleep for 1s, plus half a period to avoid racing with the last expiration.
+        std::thread::sleep(Duration::from_millis(1050));
+
+        // Should have fired about 10 times; allow some slack for the real kernel.
+        let count = SIGNAL_CTR.load(Ordering::Relaxed);
+        ensure_ord!(count, >=, 9);
+        ensure_ord!(count, <=, 11);
+
+        ensure_ord!(timer_getoverrun(timer_id)?, ==, 0);
+        Ok(())
+    })
+}
+
+fn test_absolute() -> anyhow::Result<()> {
+    with_timer(libc::CLOCK_REALTIME, None, |timer_id| {
+        let now = clock_gettime(libc::CLOCK_REALTIME);
+        let expire = timespec_to_duration(now) + Duration::from_millis(100);
+
+        timer_settime(timer_id, libc::TIMER_ABSTIME, expire, Duration::ZERO)?;
+
+        // The remaining time is relative, even for an absolute timer.
+        let (remaining, _) = timer_gettime(timer_id)?;
+        ensure_ord!(remaining, <=, Duration::from_millis(100));
+        ensure_ord!(remaining, >, Duration::from_millis(90));
+
+        std::thread::sleep(Duration::from_millis(150));
+        ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 1);
+        Ok(())
+    })
+}
+
+fn test_arrival_times() -> anyhow::Result<()> {
+    let mut sevp = sigevent(libc::SIGEV_SIGNAL);
+    sevp.sigev_signo = libc::SIGUSR1;
+    with_timer(libc::CLOCK_MONOTONIC, Some(&mut sevp), |timer_id| {
+        take_arrivals();
+
+        // First expiration 1s from now, then every 500ms.
+        let start = timespec_to_duration(clock_gettime(libc::CLOCK_MONOTONIC));
+        let value = Duration::from_secs(1);
+        let interval = Duration::from_millis(500);
+        timer_settime(timer_id, libc::TIMER_ABSTIME, start + value, interval)?;
+
+        // Stop before a fourth expiration at 2.5s.
+        std::thread::sleep(Duration::from_millis(2250));
+        let (remaining, _) = timer_gettime(timer_id)?;
+        let arrivals = take_arrivals();
+
+        // Shadow delivers the signal at exactly the expiration time, but the real kernel may be
+        // delayed by scheduling.
+        let slack = if test_utils::running_in_shadow() {
+            Duration::from_millis(1)
+        } else {
+            Duration::from_millis(50)
+        };
+        let expected: Vec<Duration> = (0..3).map(|i| start + value + interval * i).collect();
+        ensure_ord!(arrivals.len(), ==, expected.len());
+        for (arrival, expected) in arrivals.iter().zip(&expected) {
+            ensure_ord!(*arrival, >=, *expected);
+            ensure_ord!(*arrival, <=, *expected + slack);
+        }
+
+        // The next expiration is at 2.5s.
+        ensure_ord!(remaining, <=, Duration::from_millis(250));
+        ensure_ord!(remaining + slack, >=, Duration::from_millis(250));
+        Ok(())
+    })
+}
+
+fn test_sigev_thread_id() -> anyhow::Result<()> {
+    let (tid_sender, tid_receiver) = std::sync::mpsc::channel();
+    let thread = std::thread::spawn(move || {
+        tid_sender.send(unsafe { libc::gettid() }).unwrap();
+        // Wait in a syscall for the signal to interrupt.
+        std::thread::sleep(Duration::from_millis(300));
+    });
+    let tid = tid_receiver.recv()?;
+
+    let mut sevp = sigevent(libc::SIGEV_THREAD_ID);
+    sevp.sigev_signo = libc::SIGUSR1;
+    sevp.sigev_notify_thread_id = tid;
+    let rv = with_timer(libc::CLOCK_MONOTONIC, Some(&mut sevp), |timer_id| {
+        take_arrivals();
+        ARRIVAL_TID.store(0, Ordering::Relaxed);
+        timer_settime(timer_id, 0, Duration::from_millis(100), Duration::ZERO)?;
+        std::thread::sleep(Duration::from_millis(150));
+
+        // The signal was handled by the target thread rather than this one.
+        ensure_ord!(take_arrivals().len(), ==, 1);
+        ensure_ord!(ARRIVAL_TID.load(Ordering::Relaxed), ==, tid);
+        Ok(())
+    });
+    thread.join().unwrap();
+    rv?;
+
+    // The thread no longer exists.
+    ensure_ord!(timer_create(libc::CLOCK_MONOTONIC, Some(&mut sevp)), ==, Err(Errno::EINVAL));
+    Ok(())
+}
+
+fn test_sigev_none() -> anyhow::Result<()> {
+    let mut sevp = sigevent(libc::SIGEV_NONE);
+    with_timer(libc::CLOCK_MONOTONIC, Some(&mut sevp), |timer_id| {
+        let period = Duration::from_millis(100);
+        timer_settime(timer_id, 0, period, period)?;
+
+        std::thread::sleep(Duration::from_millis(250));
+
+        // No signals, but the timer still runs.
+        ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 0);
+        let (remaining, interval) = timer_gettime(timer_id)?;
+        ensure_ord!(remaining, >, Duration::ZERO);
+        ensure_ord!(remaining, <, period);
+        ensure_ord!(interval, ==, period);
+        Ok(())
+    })
+}
+
+fn test_sigev_signal() -> anyhow::Result<()> {
+    let mut sevp = sigevent(libc::SIGEV_SIGNAL);
+    with_timer(libc::CLOCK_MONOTONIC, Some(&mut sevp), |timer_id| {
+        timer_settime(timer_id, 0, Duration::from_millis(100), Duration::ZERO)?;
+        std::thread::sleep(Duration::from_millis(150));
+        ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 1);
+        Ok(())
+    })
+}
+
+fn test_invalid_args() -> anyhow::Result<()> {
+    // Bad notification method.
+    let mut sevp = sigevent(-1);
+    ensure_ord!(timer_create(libc::CLOCK_MONOTONIC, Some(&mut sevp)), ==, Err(Errno::EINVAL));
+
+    // Bad signal.
+    let mut sevp = sigevent(libc::SIGEV_SIGNAL);
+    sevp.sigev_signo = 0;
+    ensure_ord!(timer_create(libc::CLOCK_MONOTONIC, Some(&mut sevp)), ==, Err(Errno::EINVAL));
+
+    with_timer(libc::CLOCK_MONOTONIC, None, |timer_id| {
+        // Bad nanoseconds.
+        let new_value = libc::itimerspec {
+            it_value: libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 1_000_000_000,
+            },
+            it_interval: libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+        };
+        let rv = unsafe { libc::timer_settime(timer_id, 0, &new_value, std::ptr::null_mut()) };
+        ensure_ord!(Errno::result(rv), ==, Err(Errno::EINVAL));
+        Ok(())
+    })
+}
+
+fn test_deleted() -> anyhow::Result<()> {
+    let timer_id = timer_create(libc::CLOCK_MONOTONIC, None)?;
+    timer_settime(timer_id, 0, Duration::from_millis(100), Duration::ZERO)?;
+    timer_delete(timer_id)?;
+
+    // The timer no longer exists.
+    ensure_ord!(timer_gettime(timer_id), ==, Err(Errno::EINVAL));
+    ensure_ord!(timer_delete(timer_id), ==, Err(Errno::EINVAL));
+
+    // Deleting the timer disarmed it.
+    SIGNAL_CTR.store(0, Ordering::Relaxed);
+    std::thread::sleep(Duration::from_millis(150));
+    ensure_ord!(SIGNAL_CTR.load(Ordering::Relaxed), ==, 0);
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    // Install a SIGALRM handler that counts how many times it's been received, and a SIGUSR1
+    // handler that records when it's received.
+    unsafe {
+        nix::sys::signal::sigaction(
+            Signal::SIGALRM,
+            &SigAction::new(
+                SigHandler::Handler(sigalrm_handler),
+                SaFlags::empty(),
+                SigSet::empty(),
+            ),
+        )
+        .unwrap();
+        nix::sys::signal::sigaction(
+            Signal::SIGUSR1,
+            &SigAction::new(
+                SigHandler::Handler(sigusr1_handler),
+                SaFlags::empty(),
+                SigSet::empty(),
+            ),
+        )
+        .unwrap();
+    };
+
+    // should we restrict the tests we run?
+    let filter_shadow_passing = std::env::args().any(|x| x == "--shadow-passing");
+    let filter_libc_passing = std::env::args().any(|x| x == "--libc-passing");
+    // should we summarize the results rather than exit on a failed test
+    let summarize = std::env::args().any(|x| x == "--summarize");
+
+    let all_envs = set![TestEnvironment::Libc, TestEnvironment::Shadow];
+    let mut tests: Vec<test_utils::ShadowTest<(), anyhow::Error>> = vec![
+        ShadowTest::new("initially_unset", test_initially_unset, all_envs.clone()),
+        ShadowTest::new("set_then_get", test_set_then_get, all_envs.clone()),
+        ShadowTest::new("oneshot", test_oneshot, all_envs.clone()),
+        ShadowTest::new("periodic", test_periodic, all_envs.clone()),
+        ShadowTest::new("absolute", test_absolute, all_envs.clone()),
+        ShadowTest::new("arrival_times", test_arrival_times, all_envs.clone()),
+        ShadowTest::new("sigev_thread_id", test_sigev_thread_id, all_envs.clone()),
+        ShadowTest::new("sigev_none", test_sigev_none, all_envs.clone()),
+        ShadowTest::new("sigev_signal", test_sigev_signal, all_envs.clone()),
+        ShadowTest::new("invalid_args", test_invalid_args, all_envs.clone()),
+        ShadowTest::new("deleted", test_deleted, all_envs),
+    ];
+
+    if filter_shadow_passing {
+        tests.retain(|x| x.passing(TestEnvironment::Shadow));
+    }
+    if filter_libc_passing {
+        tests.retain(|x| x.passing(TestEnvironment::Libc));
+    }
+
+    test_utils::run_tests(&tests, summarize)?;
+
+    Ok(())
+}
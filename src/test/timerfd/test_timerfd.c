@@ -1,3 +1,4 @@
+#include <errno.h>
 #include <stdbool.h>
 #include <stdio.h>
 #include <stdlib.h>
@@ -13,17 +14,17 @@
 #define S_TO_NS 1000000000L
 #define TOLERANCE_MILLISECONDS 100000000L
 
-static void _test_timer_helper(bool use_abs_timer) {
+static void _test_timer_helper(clockid_t clockid, bool use_abs_timer) {
     int efd, tfd;
 
     /* create new epoll/timerfd */
-    assert_nonneg_errno(tfd = timerfd_create(CLOCK_MONOTONIC, TFD_NONBLOCK));
+    assert_nonneg_errno(tfd = timerfd_create(clockid, TFD_NONBLOCK));
 
     struct timespec start = {0};
     struct timespec end = {0};
 
     /* get the start time */
-    assert_nonneg_errno(clock_gettime(CLOCK_MONOTONIC, &start));
+    assert_nonneg_errno(clock_gettime(clockid, &start));
 
     /* Timer will expire in 1 seconds, then every 1 second */
     /* if we get TIMER_ABSTIME, we want an absolute time based on current*/
@@ -55,7 +56,7 @@ static void _test_timer_helper(bool use_abs_timer) {
     }
 
     /* get the end time */
-    assert_nonneg_errno(clock_gettime(CLOCK_MONOTONIC, &end));
+    assert_nonneg_errno(clock_gettime(clockid, &end));
 
     /* let's make sure its within a small range (100ms) of 3 seconds */
     long diff = end.tv_nsec - start.tv_nsec;
@@ -69,11 +70,19 @@ static void _test_timer_helper(bool use_abs_timer) {
 }
 
 static void _test_absolute_timer() {
-    _test_timer_helper(true);
+    _test_timer_helper(CLOCK_MONOTONIC, true);
 }
 
 static void _test_relative_timer() {
-    _test_timer_helper(false);
+    _test_timer_helper(CLOCK_MONOTONIC, false);
+}
+
+static void _test_absolute_boottime_timer() {
+    _test_timer_helper(CLOCK_BOOTTIME, true);
+}
+
+static void _test_relative_boottime_timer() {
+    _test_timer_helper(CLOCK_BOOTTIME, false);
 }
 
 static void _test_expired_timer_helper(int timeout_before_read) {
@@ -242,17 +251,92 @@ static void _test_double_arm_timer() {
     close(tfd);
 }
 
+static long _timespec_diff_ns(const struct timespec* end, const struct timespec* start) {
+    return (end->tv_sec - start->tv_sec) * S_TO_NS + (end->tv_nsec - start->tv_nsec);
+}
+
+/* An absolute deadline should expire at that time, and the old value reported by a later
+ * timerfd_settime should be the remaining (relative) time. */
+static void _test_absolute_deadline_helper(clockid_t clockid, int flags) {
+    int tfd;
+    assert_nonneg_errno(tfd = timerfd_create(clockid, 0));
+
+    struct timespec start = {0};
+    assert_nonneg_errno(clock_gettime(clockid, &start));
+
+    /* expire 500ms from now */
+    struct itimerspec t = {0};
+    t.it_value = start;
+    t.it_value.tv_nsec += 500000000L;
+    if (t.it_value.tv_nsec >= S_TO_NS) {
+        t.it_value.tv_sec += 1;
+        t.it_value.tv_nsec -= S_TO_NS;
+    }
+    assert_nonneg_errno(timerfd_settime(tfd, TFD_TIMER_ABSTIME | flags, &t, NULL));
+
+    /* re-arm with the same deadline, and check the old value */
+    struct itimerspec old = {0};
+    assert_nonneg_errno(timerfd_settime(tfd, TFD_TIMER_ABSTIME | flags, &t, &old));
+    long old_ns = old.it_value.tv_sec * S_TO_NS + old.it_value.tv_nsec;
+    g_assert_cmpint(old_ns, <=, 500000000L);
+    g_assert_cmpint(old_ns, >, 500000000L - TOLERANCE_MILLISECONDS);
+    g_assert_cmpint(old.it_interval.tv_sec, ==, 0);
+    g_assert_cmpint(old.it_interval.tv_nsec, ==, 0);
+
+    /* block until the timer expires */
+    uint64_t expired = 0;
+    g_assert_cmpint(read(tfd, &expired, sizeof(expired)), ==, sizeof(expired));
+    g_assert_cmpint(expired, ==, 1);
+
+    struct timespec end = {0};
+    assert_nonneg_errno(clock_gettime(clockid, &end));
+
+    long diff = _timespec_diff_ns(&end, &start) - 500000000L;
+    g_assert_cmpint(diff, >=, 0);
+    g_assert_cmpint(diff, <=, TOLERANCE_MILLISECONDS);
+
+    close(tfd);
+}
+
+static void _test_absolute_deadline() {
+    _test_absolute_deadline_helper(CLOCK_MONOTONIC, 0);
+}
+
+static void _test_absolute_deadline_realtime_cancel_on_set() {
+    _test_absolute_deadline_helper(CLOCK_REALTIME, TFD_TIMER_CANCEL_ON_SET);
+}
+
+static void _test_invalid_settime_flags() {
+    int tfd;
+    assert_nonneg_errno(tfd = timerfd_create(CLOCK_MONOTONIC, 0));
+
+    struct itimerspec t = {0};
+    t.it_value.tv_sec = 1;
+    g_assert_cmpint(timerfd_settime(tfd, 0x80, &t, NULL), ==, -1);
+    g_assert_cmpint(errno, ==, EINVAL);
+
+    /* CANCEL_ON_SET is accepted (but has no effect) for other clocks and relative timers */
+    assert_nonneg_errno(timerfd_settime(tfd, TFD_TIMER_CANCEL_ON_SET, &t, NULL));
+
+    close(tfd);
+}
 
 int main(int argc, char* argv[]) {
     g_test_init(&argc, &argv, NULL);
 
     g_test_add_func("/timerfd/absolute", _test_absolute_timer);
     g_test_add_func("/timerfd/relative", _test_relative_timer);
+    g_test_add_func("/timerfd/absolute_boottime", _test_absolute_boottime_timer);
+    g_test_add_func("/timerfd/relative_boottime", _test_relative_boottime_timer);
     g_test_add_func("/timerfd/expired_block", _test_expired_timer_block);
     g_test_add_func("/timerfd/expired_pause", _test_expired_timer_pause);
     g_test_add_func("/timerfd/disarm", _test_disarm_timer);
     g_test_add_func("/timerfd/rearm", _test_rearm_timer);
     g_test_add_func("/timerfd/double-arm", _test_double_arm_timer);
+    g_test_add_func("/timerfd/absolute_deadline", _test_absolute_deadline);
+    g_test_add_func("/timerfd/absolute_deadline_realtime_cancel_on_set",
+                    _test_absolute_deadline_realtime_cancel_on_set);
+    g_test_add_func("/timerfd/invalid_settime_flags", _test_invalid_settime_flags);
 
     return g_test_run();
 }
\ No newline at end of file
@@ -0,0 +1,223 @@
+/*
+ * The Shadow Simulator
+ * See LICENSE for licensing information
+ */
+
+// Tests the host's tmpfs mounts. The mounted path doesn't exist on the real filesystem, so this
+// only runs in shadow.
+
+#include <dirent.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <glib.h>
+#include <linux/magic.h>
+#include <string.h>
+#include <stdio.h>
+#include <sys/stat.h>
+#include <sys/statfs.h>
+#include <unistd.h>
+
+#include "test/test_glib_helpers.h"
+
+// these must match the mount
+#define MOUNT_DIR "/opt/shadow-test/tmpfs"
+#define MOUNT_SIZE (64 * 1024)
+
+#define TEST_FILE MOUNT_DIR "/file"
+#define TEST_FILE_2 MOUNT_DIR "/file2"
+#define TEST_FIFO MOUNT_DIR "/fifo"
+#define TEST_DIR MOUNT_DIR "/dir"
+#define TEST_DIR_2 MOUNT_DIR "/dir2"
+#define TEST_DIR_FILE TEST_DIR_2 "/file"
+
+static void _check_statfs(uint64_t expected_free) {
+    struct statfs buf = {0};
+    assert_nonneg_errno(statfs(MOUNT_DIR, &buf));
+    g_assert_cmpint(buf.f_type, ==, TMPFS_MAGIC);
+    g_assert_cmpint(buf.f_blocks * buf.f_bsize, ==, MOUNT_SIZE);
+    g_assert_cmpint(buf.f_bfree * buf.f_bsize, ==, expected_free);
+    g_assert_cmpint(buf.f_bavail * buf.f_bsize, ==, expected_free);
+
+    // a file within the mount reports the same filesystem
+    int fd = open(MOUNT_DIR, O_RDONLY | O_DIRECTORY);
+    assert_nonneg_errno(fd);
+    struct statfs fbuf = {0};
+    assert_nonneg_errno(fstatfs(fd, &fbuf));
+    g_assert_cmpint(fbuf.f_type, ==, TMPFS_MAGIC);
+    g_assert_cmpint(fbuf.f_blocks, ==, buf.f_blocks);
+    g_assert_cmpint(fbuf.f_bfree, ==, buf.f_bfree);
+    assert_nonneg_errno(close(fd));
+}
+
+static void _test_starts_empty(void) {
+    DIR* dir = opendir(MOUNT_DIR);
+    assert_true_errno(dir != NULL);
+
+    struct dirent* entry = NULL;
+    while ((entry = readdir(dir)) != NULL) {
+        g_assert_true(!strcmp(entry->d_name, ".") || !strcmp(entry->d_name, ".."));
+    }
+
+    assert_nonneg_errno(closedir(dir));
+    _check_statfs(MOUNT_SIZE);
+}
+
+static void _test_write_to_limit(void) {
+    static char buf[MOUNT_SIZE / 4];
+    memset(buf, 'a', sizeof(buf));
+
+    int fd = open(TEST_FILE, O_WRONLY | O_CREAT | O_EXCL, 0600);
+    assert_nonneg_errno(fd);
+
+    // fill the mount
+    for (int i = 0; i < 4; i++) {
+        g_assert_cmpint(write(fd, buf, sizeof(buf)), ==, sizeof(buf));
+    }
+    _check_statfs(0);
+
+    // the mount is full
+    g_assert_cmpint(write(fd, buf, 1), ==, -1);
+    assert_errno_is(ENOSPC);
+    g_assert_cmpint(pwrite(fd, buf, 1, MOUNT_SIZE), ==, -1);
+    assert_errno_is(ENOSPC);
+
+    // overwriting existing data doesn't need more space
+    g_assert_cmpint(pwrite(fd, buf, sizeof(buf), 0), ==, sizeof(buf));
+
+    // removing the file frees its space
+    assert_nonneg_errno(close(fd));
+    assert_nonneg_errno(unlinkat(AT_FDCWD, TEST_FILE, 0));
+    _check_statfs(MOUNT_SIZE);
+}
+
+static void _test_partial_write(void) {
+    static char buf[MOUNT_SIZE + 100];
+    memset(buf, 'b', sizeof(buf));
+
+    int fd = open(TEST_FILE, O_WRONLY | O_CREAT | O_EXCL, 0600);
+    assert_nonneg_errno(fd);
+
+    // only the bytes that fit are written
+    g_assert_cmpint(write(fd, buf, sizeof(buf)), ==, MOUNT_SIZE);
+    g_assert_cmpint(write(fd, buf, sizeof(buf)), ==, -1);
+    assert_errno_is(ENOSPC);
+
+    struct stat statbuf = {0};
+    assert_nonneg_errno(fstat(fd, &statbuf));
+    g_assert_cmpint(statbuf.st_size, ==, MOUNT_SIZE);
+
+    assert_nonneg_errno(close(fd));
+    assert_nonneg_errno(unlinkat(AT_FDCWD, TEST_FILE, 0));
+}
+
+static void _test_usage_tracking(void) {
+    static char buf[MOUNT_SIZE / 4];
+    memset(buf, 'c', sizeof(buf));
+
+    int fd = open(TEST_FILE, O_WRONLY | O_CREAT | O_EXCL, 0600);
+    assert_nonneg_errno(fd);
+    g_assert_cmpint(write(fd, buf, sizeof(buf)), ==, sizeof(buf));
+    _check_statfs(MOUNT_SIZE - sizeof(buf));
+
+    // truncating the file changes its usage
+    assert_nonneg_errno(ftruncate(fd, 2 * sizeof(buf)));
+    _check_statfs(MOUNT_SIZE - 2 * sizeof(buf));
+    assert_nonneg_errno(ftruncate(fd, 0));
+    _check_statfs(MOUNT_SIZE);
+    g_assert_cmpint(write(fd, buf, sizeof(buf)), ==, sizeof(buf));
+    assert_nonneg_errno(close(fd));
+
+    // a hard link doesn't use more space, and the file's space isn't freed until its last link is
+    // removed
+    assert_nonneg_errno(link(TEST_FILE, TEST_FILE_2));
+    _check_statfs(MOUNT_SIZE - sizeof(buf));
+    assert_nonneg_errno(unlink(TEST_FILE_2));
+    _check_statfs(MOUNT_SIZE - sizeof(buf));
+
+    // renaming a file over another frees the replaced file's space
+    fd = open(TEST_FILE_2, O_WRONLY | O_CREAT | O_EXCL, 0600);
+    assert_nonneg_errno(fd);
+    g_assert_cmpint(write(fd, buf, sizeof(buf)), ==, sizeof(buf));
+    assert_nonneg_errno(close(fd));
+    _check_statfs(MOUNT_SIZE - 2 * sizeof(buf));
+    assert_nonneg_errno(rename(TEST_FILE_2, TEST_FILE));
+    _check_statfs(MOUNT_SIZE - sizeof(buf));
+
+    // truncating the file when opening it frees its space
+    fd = open(TEST_FILE, O_WRONLY | O_TRUNC);
+    assert_nonneg_errno(fd);
+    _check_statfs(MOUNT_SIZE);
+    assert_nonneg_errno(close(fd));
+
+    assert_nonneg_errno(unlink(TEST_FILE));
+    _check_statfs(MOUNT_SIZE);
+}
+
+static void _test_path_syscalls(void) {
+    // the legacy path syscalls resolve their paths within the mount
+    assert_nonneg_errno(mkdir(TEST_DIR, 0700));
+    assert_nonneg_errno(access(TEST_DIR, R_OK | W_OK | X_OK));
+    assert_nonneg_errno(rename(TEST_DIR, TEST_DIR_2));
+    g_assert_cmpint(access(TEST_DIR, F_OK), ==, -1);
+    assert_errno_is(ENOENT);
+
+    int fd = open(TEST_DIR_FILE, O_WRONLY | O_CREAT | O_EXCL, 0600);
+    assert_nonneg_errno(fd);
+    assert_nonneg_errno(close(fd));
+
+    assert_nonneg_errno(truncate(TEST_DIR_FILE, MOUNT_SIZE / 2));
+    _check_statfs(MOUNT_SIZE / 2);
+    g_assert_cmpint(truncate(TEST_DIR_FILE, MOUNT_SIZE + 1), ==, -1);
+    assert_errno_is(EFBIG);
+
+    struct stat statbuf = {0};
+    assert_nonneg_errno(stat(TEST_DIR_FILE, &statbuf));
+    g_assert_true(S_ISREG(statbuf.st_mode));
+    g_assert_cmpint(statbuf.st_size, ==, MOUNT_SIZE / 2);
+    assert_nonneg_errno(chmod(TEST_DIR_FILE, 0400));
+    assert_nonneg_errno(lstat(TEST_DIR_FILE, &statbuf));
+    g_assert_cmpint(statbuf.st_mode & 0777, ==, 0400);
+
+    g_assert_cmpint(rmdir(TEST_DIR_2), ==, -1);
+    assert_errno_is(ENOTEMPTY);
+    assert_nonneg_errno(unlink(TEST_DIR_FILE));
+    assert_nonneg_errno(rmdir(TEST_DIR_2));
+    _check_statfs(MOUNT_SIZE);
+}
+
+static void _check_fifo(const char* path) {
+    assert_nonneg_errno(mkfifo(path, 0600));
+
+    // opening a fifo for reading and writing doesn't block
+    int fd = open(path, O_RDWR);
+    assert_nonneg_errno(fd);
+
+    // fifos can't seek, but they can be written to
+    char buf[] = "hello";
+    g_assert_cmpint(write(fd, buf, sizeof(buf)), ==, sizeof(buf));
+    char readbuf[sizeof(buf)] = {0};
+    g_assert_cmpint(read(fd, readbuf, sizeof(readbuf)), ==, sizeof(buf));
+    g_assert_cmpstr(readbuf, ==, buf);
+
+    assert_nonneg_errno(close(fd));
+    assert_nonneg_errno(unlink(path));
+}
+
+static void _test_fifo(void) {
+    _check_fifo(TEST_FIFO);
+    _check_fifo("fifo");
+    _check_statfs(MOUNT_SIZE);
+}
+
+int main(int argc, char** argv) {
+    g_test_init(&argc, &argv, NULL);
+
+    g_test_add_func("/tmpfs_mounts/starts_empty", _test_starts_empty);
+    g_test_add_func("/tmpfs_mounts/write_to_limit", _test_write_to_limit);
+    g_test_add_func("/tmpfs_mounts/partial_write", _test_partial_write);
+    g_test_add_func("/tmpfs_mounts/usage_tracking", _test_usage_tracking);
+    g_test_add_func("/tmpfs_mounts/path_syscalls", _test_path_syscalls);
+    g_test_add_func("/tmpfs_mounts/fifo", _test_fifo);
+
+    return g_test_run();
+}